@@ -32,6 +32,7 @@ object PrettyPrinter extends ByteStringPrettyPrinter {
         case Some(v) => s"Write(${buildString(v)})"
       }
     case Transform.TransformInstance.AddU64(TransformAddUInt64(x)) => s"AddU64($x)"
+    case Transform.TransformInstance.Prune(_)                      => "Prune"
   }
 
   def buildString(v: Option[ProtocolVersion]): String = v match {
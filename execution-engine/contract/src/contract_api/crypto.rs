@@ -0,0 +1,72 @@
+//! Functions for hashing and verifying signatures on the host, which is considerably cheaper than
+//! doing so in Wasm.
+
+use casperlabs_types::{api_error, BLAKE2B_DIGEST_LENGTH};
+
+use crate::{ext_ffi, unwrap_or_revert::UnwrapOrRevert};
+
+/// The length in bytes of an Ed25519 public key.
+pub const ED25519_PUBLIC_KEY_LENGTH: usize = 32;
+/// The length in bytes of an Ed25519 signature.
+pub const ED25519_SIGNATURE_LENGTH: usize = 64;
+/// The length in bytes of a compact secp256k1 signature.
+pub const SECP256K1_SIGNATURE_LENGTH: usize = 64;
+
+/// Returns the 32-byte BLAKE2b hash of `input`.
+pub fn blake2b<T: AsRef<[u8]>>(input: T) -> [u8; BLAKE2B_DIGEST_LENGTH] {
+    let input = input.as_ref();
+    let mut digest = [0u8; BLAKE2B_DIGEST_LENGTH];
+    let ret = unsafe {
+        ext_ffi::blake2b(
+            input.as_ptr(),
+            input.len(),
+            digest.as_mut_ptr(),
+            digest.len(),
+        )
+    };
+    api_error::result_from(ret).unwrap_or_revert();
+    digest
+}
+
+/// Returns `true` if `signature` is a valid Ed25519 signature of `message` made with the key
+/// `public_key`.
+pub fn ed25519_verify(
+    public_key: &[u8; ED25519_PUBLIC_KEY_LENGTH],
+    message: &[u8],
+    signature: &[u8; ED25519_SIGNATURE_LENGTH],
+) -> bool {
+    let ret = unsafe {
+        ext_ffi::ed25519_verify(
+            public_key.as_ptr(),
+            public_key.len(),
+            message.as_ptr(),
+            message.len(),
+            signature.as_ptr(),
+            signature.len(),
+        )
+    };
+    ret != 0
+}
+
+/// Returns `true` if `signature` is a valid compact secp256k1 signature of `message_digest` made
+/// with the key `public_key`.
+///
+/// `public_key` may be either a 33-byte compressed or a 65-byte uncompressed key. Returns `false`
+/// if it is neither.
+pub fn secp256k1_verify(
+    public_key: &[u8],
+    message_digest: &[u8; BLAKE2B_DIGEST_LENGTH],
+    signature: &[u8; SECP256K1_SIGNATURE_LENGTH],
+) -> bool {
+    let ret = unsafe {
+        ext_ffi::secp256k1_verify(
+            public_key.as_ptr(),
+            public_key.len(),
+            message_digest.as_ptr(),
+            message_digest.len(),
+            signature.as_ptr(),
+            signature.len(),
+        )
+    };
+    ret != 0
+}
@@ -7,12 +7,13 @@ use alloc::vec::Vec;
 use core::mem::MaybeUninit;
 
 use casperlabs_types::{
-    account::AccountHash,
+    account::{AccountHash, ACCOUNT_HASH_SERIALIZED_LENGTH},
     api_error,
-    bytesrepr::{self, FromBytes},
+    bytesrepr::{self, FromBytes, ToBytes, U64_SERIALIZED_LENGTH, U8_SERIALIZED_LENGTH},
     contracts::{ContractVersion, NamedKeys},
     ApiError, BlockTime, CLTyped, CLValue, ContractHash, ContractPackageHash, Key, Phase,
-    RuntimeArgs, URef, BLOCKTIME_SERIALIZED_LENGTH, PHASE_SERIALIZED_LENGTH,
+    RuntimeArgs, URef, BLAKE2B_DIGEST_LENGTH, BLOCKTIME_SERIALIZED_LENGTH, KEY_HASH_LENGTH,
+    PHASE_SERIALIZED_LENGTH,
 };
 
 use crate::{contract_api, ext_ffi, unwrap_or_revert::UnwrapOrRevert};
@@ -305,6 +306,81 @@ pub fn is_valid_uref(uref: URef) -> bool {
     result != 0
 }
 
+/// Emits an event named `name` carrying `value`.
+///
+/// Events are tagged with the hash of the emitting contract (or the caller's account for session
+/// code) and are discarded if the current call frame reverts.
+pub fn emit_event<T: CLTyped + ToBytes>(name: &str, value: T) {
+    let cl_value = CLValue::from_t(value).unwrap_or_revert();
+    let (name_ptr, name_size, _bytes) = contract_api::to_ptr(name);
+    let (value_ptr, value_size, _bytes2) = contract_api::to_ptr(cl_value);
+    unsafe { ext_ffi::emit_event(name_ptr, name_size, value_ptr, value_size) }
+}
+
+/// Returns the hash of the deploy being executed.
+pub fn get_deploy_hash() -> [u8; KEY_HASH_LENGTH] {
+    let dest_non_null_ptr = contract_api::alloc_bytes(KEY_HASH_LENGTH);
+    let bytes = unsafe {
+        ext_ffi::get_deploy_hash(dest_non_null_ptr.as_ptr());
+        Vec::from_raw_parts(dest_non_null_ptr.as_ptr(), KEY_HASH_LENGTH, KEY_HASH_LENGTH)
+    };
+    bytesrepr::deserialize(bytes).unwrap_or_revert()
+}
+
+/// Returns the height of the block the deploy is executed in.
+pub fn get_block_height() -> u64 {
+    let dest_non_null_ptr = contract_api::alloc_bytes(U64_SERIALIZED_LENGTH);
+    let bytes = unsafe {
+        ext_ffi::get_block_height(dest_non_null_ptr.as_ptr());
+        Vec::from_raw_parts(
+            dest_non_null_ptr.as_ptr(),
+            U64_SERIALIZED_LENGTH,
+            U64_SERIALIZED_LENGTH,
+        )
+    };
+    bytesrepr::deserialize(bytes).unwrap_or_revert()
+}
+
+/// Returns the [`AccountHash`] of the validator which proposed the block the deploy is executed
+/// in, or `None` if the proposer is not known.
+pub fn get_proposer() -> Option<AccountHash> {
+    // Zeroed, as the host only writes the tag of a `None`.
+    let mut bytes = [0u8; U8_SERIALIZED_LENGTH + ACCOUNT_HASH_SERIALIZED_LENGTH];
+    unsafe { ext_ffi::get_proposer(bytes.as_mut_ptr()) };
+    let (proposer, _) = Option::<AccountHash>::from_bytes(&bytes).unwrap_or_revert();
+    proposer
+}
+
+/// Returns the hash of the global state the block the deploy is executed in is executed against.
+pub fn get_parent_state_hash() -> [u8; BLAKE2B_DIGEST_LENGTH] {
+    let dest_non_null_ptr = contract_api::alloc_bytes(BLAKE2B_DIGEST_LENGTH);
+    let bytes = unsafe {
+        ext_ffi::get_parent_state_hash(dest_non_null_ptr.as_ptr());
+        Vec::from_raw_parts(
+            dest_non_null_ptr.as_ptr(),
+            BLAKE2B_DIGEST_LENGTH,
+            BLAKE2B_DIGEST_LENGTH,
+        )
+    };
+    bytesrepr::deserialize(bytes).unwrap_or_revert()
+}
+
+/// Returns `len` random bytes.
+///
+/// The bytes are deterministic for a given deploy, phase and block time, so all validators
+/// executing the deploy see the same values. Successive calls, including those made by called
+/// contracts, return successive bytes of the same stream.
+pub fn random_bytes(len: usize) -> Vec<u8> {
+    if len == 0 {
+        return Vec::new();
+    }
+    let bytes_non_null_ptr = contract_api::alloc_bytes(len);
+    unsafe {
+        ext_ffi::random_bytes(bytes_non_null_ptr.as_ptr(), len);
+        Vec::from_raw_parts(bytes_non_null_ptr.as_ptr(), len, len)
+    }
+}
+
 fn read_host_buffer_into(dest: &mut [u8]) -> Result<usize, ApiError> {
     let mut bytes_written = MaybeUninit::uninit();
     let ret = unsafe {
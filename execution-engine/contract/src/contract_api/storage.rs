@@ -90,6 +90,17 @@ pub fn write_local<K: ToBytes, V: CLTyped + ToBytes>(key: K, value: V) {
     }
 }
 
+/// Removes the value under `uref` from the global state.
+///
+/// `uref` must grant write access.  Reading it afterwards returns `None` until it is written to
+/// again.
+pub fn prune(uref: URef) {
+    let (uref_ptr, uref_size, _bytes) = contract_api::to_ptr(uref);
+    unsafe {
+        ext_ffi::prune(uref_ptr, uref_size);
+    }
+}
+
 /// Adds `value` to the one currently under `uref` in the global state.
 pub fn add<T: CLTyped + ToBytes>(uref: URef, value: T) {
     let key = Key::from(uref);
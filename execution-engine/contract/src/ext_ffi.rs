@@ -75,6 +75,17 @@ extern "C" {
     /// * `value_ptr` - pointer to bytes representing the value to write at the key
     /// * `value_size` - size of the value (in bytes)
     pub fn add(key_ptr: *const u8, key_size: usize, value_ptr: *const u8, value_size: usize);
+    /// This function removes the value under the provided
+    /// [`casperlabs_types::uref::URef`] (read via de-serializing the bytes in wasm memory from
+    /// offset `uref_ptr` to `uref_ptr + uref_size`) from the global state. This function will
+    /// cause a `Trap` if the [`casperlabs_types::uref::URef`] fails to de-serialize, is not
+    /// valid in the current context or does not grant write access.
+    ///
+    /// # Arguments
+    ///
+    /// * `uref_ptr` - pointer to bytes representing the [`casperlabs_types::uref::URef`] to prune
+    /// * `uref_size` - size of the [`casperlabs_types::uref::URef`] (in bytes)
+    pub fn prune(uref_ptr: *const u8, uref_size: usize);
     ///
     pub fn add_local(key_ptr: *const u8, key_size: usize, value_ptr: *const u8, value_size: usize);
     /// This function causes the runtime to generate a new [`casperlabs_types::uref::URef`], with
@@ -626,6 +637,110 @@ extern "C" {
         urefs_ptr: *const u8,
         urefs_size: usize,
     ) -> i32;
+    /// Emits an event with the given name and [`casperlabs_types::CLValue`] payload. Events are
+    /// returned alongside the execution result of the deploy, in the order in which they were
+    /// emitted, and are discarded if the emitting call fails.
+    ///
+    /// # Arguments
+    ///
+    /// * `name_ptr` - pointer to serialized event name
+    /// * `name_size` - size of serialized event name
+    /// * `value_ptr` - pointer to serialized [`casperlabs_types::CLValue`] payload
+    /// * `value_size` - size of serialized [`casperlabs_types::CLValue`] payload
+    pub fn emit_event(
+        name_ptr: *const u8,
+        name_size: usize,
+        value_ptr: *const u8,
+        value_size: usize,
+    );
+    /// Computes the 32-byte BLAKE2b hash of the input bytes and writes it to the output buffer.
+    /// Returns standard error code.
+    ///
+    /// # Arguments
+    ///
+    /// * `in_ptr` - pointer to the bytes to hash
+    /// * `in_size` - number of bytes to hash
+    /// * `out_ptr` - pointer to the buffer the digest will be written to
+    /// * `out_size` - size of the output buffer, which must be at least 32 bytes
+    pub fn blake2b(in_ptr: *const u8, in_size: usize, out_ptr: *mut u8, out_size: usize) -> i32;
+    /// Returns non-zero value if the Ed25519 signature of the message is valid for the given
+    /// public key. Malformed public keys and signatures are reported as invalid.
+    ///
+    /// # Arguments
+    ///
+    /// * `public_key_ptr` - pointer to the 32-byte public key
+    /// * `public_key_size` - size of the public key
+    /// * `message_ptr` - pointer to the signed message
+    /// * `message_size` - size of the signed message
+    /// * `signature_ptr` - pointer to the 64-byte signature
+    /// * `signature_size` - size of the signature
+    pub fn ed25519_verify(
+        public_key_ptr: *const u8,
+        public_key_size: usize,
+        message_ptr: *const u8,
+        message_size: usize,
+        signature_ptr: *const u8,
+        signature_size: usize,
+    ) -> i32;
+    /// Returns non-zero value if the compact secp256k1 signature of the message digest is valid for
+    /// the given public key. Malformed public keys, digests and signatures are reported as invalid.
+    ///
+    /// # Arguments
+    ///
+    /// * `public_key_ptr` - pointer to the 33-byte compressed or 65-byte uncompressed public key
+    /// * `public_key_size` - size of the public key
+    /// * `message_digest_ptr` - pointer to the 32-byte digest of the signed message
+    /// * `message_digest_size` - size of the message digest
+    /// * `signature_ptr` - pointer to the 64-byte compact signature
+    /// * `signature_size` - size of the signature
+    pub fn secp256k1_verify(
+        public_key_ptr: *const u8,
+        public_key_size: usize,
+        message_digest_ptr: *const u8,
+        message_digest_size: usize,
+        signature_ptr: *const u8,
+        signature_size: usize,
+    ) -> i32;
+    /// Fills the output buffer with bytes from a random stream which is deterministic for a
+    /// given deploy, phase and block time. Drawing from it doesn't affect the addresses of
+    /// [`casperlabs_types::URef`]s or contracts created by the deploy.
+    ///
+    /// # Arguments
+    ///
+    /// * `out_ptr` - pointer to the buffer the random bytes will be written to
+    /// * `out_size` - number of random bytes to write
+    pub fn random_bytes(out_ptr: *mut u8, out_size: usize);
+    /// This function writes the 32-byte hash of the deploy being executed to `dest_ptr`. It is up
+    /// to the caller to ensure there are 32 bytes allocated at `dest_ptr`.
+    ///
+    /// # Arguments
+    ///
+    /// * `dest_ptr` - pointer in wasm memory where to write the result
+    pub fn get_deploy_hash(dest_ptr: *mut u8);
+    /// This function writes the height of the block the deploy is executed in, serialized as a
+    /// 64-bit unsigned integer, to `dest_ptr`. It is up to the caller to ensure there are 8 bytes
+    /// allocated at `dest_ptr`.
+    ///
+    /// # Arguments
+    ///
+    /// * `dest_ptr` - pointer in wasm memory where to write the result
+    pub fn get_block_height(dest_ptr: *mut u8);
+    /// This function writes the [`casperlabs_types::account::AccountHash`] of the validator which
+    /// proposed the block the deploy is executed in, serialized as an `Option` which is `None` if
+    /// the proposer is not known, to `dest_ptr`. It is up to the caller to ensure there are 33
+    /// bytes allocated at `dest_ptr`.
+    ///
+    /// # Arguments
+    ///
+    /// * `dest_ptr` - pointer in wasm memory where to write the result
+    pub fn get_proposer(dest_ptr: *mut u8);
+    /// This function writes the 32-byte hash of the global state the block is executed against to
+    /// `dest_ptr`. It is up to the caller to ensure there are 32 bytes allocated at `dest_ptr`.
+    ///
+    /// # Arguments
+    ///
+    /// * `dest_ptr` - pointer in wasm memory where to write the result
+    pub fn get_parent_state_hash(dest_ptr: *mut u8);
 
     /// Prints data directly to stanadard output on the host.
     ///
@@ -0,0 +1,67 @@
+#![no_std]
+#![no_main]
+
+extern crate alloc;
+
+use alloc::{string::ToString, vec};
+use core::convert::TryInto;
+
+use contract::{
+    contract_api::{runtime, storage},
+    unwrap_or_revert::UnwrapOrRevert,
+};
+use types::{
+    contracts::{EntryPoint, EntryPoints, NamedKeys},
+    CLType, EntryPointAccess, EntryPointType, Parameter, URef, U512,
+};
+
+const ENTRY_FUNCTION_NAME: &str = "add";
+const HASH_KEY_NAME: &str = "add_u512_hash";
+const PACKAGE_HASH_KEY_NAME: &str = "add_u512_package_hash";
+const ACCESS_KEY_NAME: &str = "add_u512_access";
+const TOTAL_KEY_NAME: &str = "total";
+const ARG_AMOUNT: &str = "amount";
+
+#[no_mangle]
+pub extern "C" fn add() {
+    let total: URef = runtime::get_key(TOTAL_KEY_NAME)
+        .unwrap_or_revert()
+        .try_into()
+        .unwrap_or_revert();
+    let amount: U512 = runtime::get_named_arg(ARG_AMOUNT);
+    storage::add(total, amount);
+}
+
+#[no_mangle]
+pub extern "C" fn call() {
+    let entry_points = {
+        let mut entry_points = EntryPoints::new();
+        let entry_point = EntryPoint::new(
+            ENTRY_FUNCTION_NAME.to_string(),
+            vec![Parameter::new(ARG_AMOUNT, CLType::U512)],
+            CLType::Unit,
+            EntryPointAccess::Public,
+            EntryPointType::Contract,
+        );
+        entry_points.add_entry_point(entry_point);
+        entry_points
+    };
+
+    let named_keys = {
+        let mut named_keys = NamedKeys::new();
+        named_keys.insert(
+            TOTAL_KEY_NAME.to_string(),
+            storage::new_uref(U512::zero()).into(),
+        );
+        named_keys
+    };
+
+    let (contract_hash, _contract_version) = storage::new_contract(
+        entry_points,
+        Some(named_keys),
+        Some(PACKAGE_HASH_KEY_NAME.to_string()),
+        Some(ACCESS_KEY_NAME.to_string()),
+    );
+
+    runtime::put_key(HASH_KEY_NAME, contract_hash.into());
+}
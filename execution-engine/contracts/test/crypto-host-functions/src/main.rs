@@ -0,0 +1,73 @@
+#![no_std]
+#![no_main]
+
+extern crate alloc;
+
+use alloc::{string::String, vec::Vec};
+
+use contract::contract_api::{
+    crypto::{self, ED25519_PUBLIC_KEY_LENGTH},
+    runtime, storage,
+};
+use types::ApiError;
+
+const ARG_SCHEME: &str = "scheme";
+const ARG_PUBLIC_KEY: &str = "public_key";
+const ARG_MESSAGE: &str = "message";
+const ARG_SIGNATURE: &str = "signature";
+const DIGEST_KEY_NAME: &str = "digest";
+const VERIFIED_KEY_NAME: &str = "verified";
+const SCHEME_ED25519: &str = "ed25519";
+const SCHEME_SECP256K1: &str = "secp256k1";
+// Both Ed25519 and compact secp256k1 signatures are 64 bytes long
+const SIGNATURE_LENGTH: usize = 64;
+
+#[repr(u16)]
+enum Error {
+    InvalidScheme = 0,
+    InvalidPublicKeyLength,
+    InvalidSignatureLength,
+}
+
+impl From<Error> for ApiError {
+    fn from(error: Error) -> Self {
+        ApiError::User(error as u16)
+    }
+}
+
+fn to_signature(bytes: &[u8]) -> [u8; SIGNATURE_LENGTH] {
+    if bytes.len() != SIGNATURE_LENGTH {
+        runtime::revert(Error::InvalidSignatureLength)
+    }
+    let mut signature = [0u8; SIGNATURE_LENGTH];
+    signature.copy_from_slice(bytes);
+    signature
+}
+
+#[no_mangle]
+pub extern "C" fn call() {
+    let scheme: String = runtime::get_named_arg(ARG_SCHEME);
+    let public_key: Vec<u8> = runtime::get_named_arg(ARG_PUBLIC_KEY);
+    let message: Vec<u8> = runtime::get_named_arg(ARG_MESSAGE);
+    let signature: Vec<u8> = runtime::get_named_arg(ARG_SIGNATURE);
+
+    let digest = crypto::blake2b(&message);
+    let signature = to_signature(&signature);
+
+    let verified = match scheme.as_str() {
+        SCHEME_ED25519 => {
+            if public_key.len() != ED25519_PUBLIC_KEY_LENGTH {
+                runtime::revert(Error::InvalidPublicKeyLength)
+            }
+            let mut ed25519_public_key = [0u8; ED25519_PUBLIC_KEY_LENGTH];
+            ed25519_public_key.copy_from_slice(&public_key);
+            crypto::ed25519_verify(&ed25519_public_key, &message, &signature)
+        }
+        // secp256k1 signatures are made over the BLAKE2b digest of the message
+        SCHEME_SECP256K1 => crypto::secp256k1_verify(&public_key, &digest, &signature),
+        _ => runtime::revert(Error::InvalidScheme),
+    };
+
+    runtime::put_key(DIGEST_KEY_NAME, storage::new_uref(digest).into());
+    runtime::put_key(VERIFIED_KEY_NAME, storage::new_uref(verified).into());
+}
@@ -0,0 +1,58 @@
+#![no_std]
+#![no_main]
+
+extern crate alloc;
+
+use alloc::{string::ToString, vec::Vec};
+
+use contract::contract_api::{runtime, storage};
+use types::{
+    ApiError, CLType, EntryPoint, EntryPointAccess, EntryPointType, EntryPoints, RuntimeArgs,
+};
+
+const ENTRY_POINT_NAME: &str = "emit_event_ext";
+const HASH_KEY_NAME: &str = "emit_event_hash";
+const ACCESS_KEY_NAME: &str = "emit_event_access";
+const ARG_REVERT: &str = "revert";
+const SESSION_START_EVENT: &str = "session_start";
+const SESSION_END_EVENT: &str = "session_end";
+const CONTRACT_EVENT: &str = "contract_event";
+const CONTRACT_EVENT_VALUE: &str = "hello";
+
+#[no_mangle]
+pub extern "C" fn emit_event_ext() {
+    runtime::emit_event(CONTRACT_EVENT, CONTRACT_EVENT_VALUE.to_string());
+}
+
+#[no_mangle]
+pub extern "C" fn call() {
+    let revert: bool = runtime::get_named_arg(ARG_REVERT);
+
+    let entry_points = {
+        let mut entry_points = EntryPoints::new();
+        let entry_point = EntryPoint::new(
+            ENTRY_POINT_NAME.to_string(),
+            Vec::new(),
+            CLType::Unit,
+            EntryPointAccess::Public,
+            EntryPointType::Contract,
+        );
+        entry_points.add_entry_point(entry_point);
+        entry_points
+    };
+
+    let (contract_hash, _contract_version) = storage::new_contract(
+        entry_points,
+        None,
+        Some(HASH_KEY_NAME.to_string()),
+        Some(ACCESS_KEY_NAME.to_string()),
+    );
+
+    runtime::emit_event(SESSION_START_EVENT, 1u64);
+    runtime::call_contract::<()>(contract_hash, ENTRY_POINT_NAME, RuntimeArgs::default());
+    runtime::emit_event(SESSION_END_EVENT, 2u64);
+
+    if revert {
+        runtime::revert(ApiError::User(0));
+    }
+}
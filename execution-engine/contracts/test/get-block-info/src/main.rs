@@ -0,0 +1,40 @@
+#![no_std]
+#![no_main]
+
+use contract::contract_api::runtime;
+use types::{account::AccountHash, BLAKE2B_DIGEST_LENGTH, KEY_HASH_LENGTH};
+
+const ARG_KNOWN_DEPLOY_HASH: &str = "known_deploy_hash";
+const ARG_KNOWN_BLOCK_HEIGHT: &str = "known_block_height";
+const ARG_KNOWN_PROPOSER: &str = "known_proposer";
+const ARG_KNOWN_PARENT_STATE_HASH: &str = "known_parent_state_hash";
+
+#[no_mangle]
+pub extern "C" fn call() {
+    let known_deploy_hash: [u8; KEY_HASH_LENGTH] = runtime::get_named_arg(ARG_KNOWN_DEPLOY_HASH);
+    let known_block_height: u64 = runtime::get_named_arg(ARG_KNOWN_BLOCK_HEIGHT);
+    let known_proposer: Option<AccountHash> = runtime::get_named_arg(ARG_KNOWN_PROPOSER);
+    let known_parent_state_hash: [u8; BLAKE2B_DIGEST_LENGTH] =
+        runtime::get_named_arg(ARG_KNOWN_PARENT_STATE_HASH);
+
+    assert_eq!(
+        runtime::get_deploy_hash(),
+        known_deploy_hash,
+        "actual deploy hash not known deploy hash"
+    );
+    assert_eq!(
+        runtime::get_block_height(),
+        known_block_height,
+        "actual block height not known block height"
+    );
+    assert_eq!(
+        runtime::get_proposer(),
+        known_proposer,
+        "actual proposer not known proposer"
+    );
+    assert_eq!(
+        runtime::get_parent_state_hash(),
+        known_parent_state_hash,
+        "actual parent state hash not known parent state hash"
+    );
+}
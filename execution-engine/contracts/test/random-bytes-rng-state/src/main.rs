@@ -0,0 +1,68 @@
+#![no_std]
+#![no_main]
+
+extern crate alloc;
+
+use alloc::{string::String, vec::Vec};
+
+use contract::{
+    contract_api::{runtime, storage},
+    unwrap_or_revert::UnwrapOrRevert,
+};
+use types::{
+    contracts::Parameters, CLType, CLTyped, CLValue, EntryPoint, EntryPointAccess, EntryPointType,
+    EntryPoints, Key, RuntimeArgs, URef, U512,
+};
+
+const ARG_FLAG: &str = "flag";
+const RANDOM_BYTES_LENGTH: usize = 32;
+
+#[no_mangle]
+pub extern "C" fn random_bytes_ext() {
+    // Advances the random bytes stream, but not the RNG used for addresses
+    let random_bytes = runtime::random_bytes(RANDOM_BYTES_LENGTH);
+    runtime::ret(CLValue::from_t(random_bytes).unwrap_or_revert())
+}
+
+#[no_mangle]
+pub extern "C" fn call() {
+    let flag: String = runtime::get_named_arg(ARG_FLAG);
+
+    let entry_points = {
+        let mut entry_points = EntryPoints::new();
+
+        let random_bytes_entry_point = EntryPoint::new(
+            "random_bytes_ext",
+            Parameters::default(),
+            Vec::<u8>::cl_type(),
+            EntryPointAccess::Public,
+            EntryPointType::Contract,
+        );
+
+        entry_points.add_entry_point(random_bytes_entry_point);
+
+        entry_points
+    };
+    let (contract_hash, _contract_version) = storage::new_contract(entry_points, None, None, None);
+
+    if flag == "pass1" {
+        // Two calls should forward the internal RNG. This pass is a baseline.
+        let uref1: URef = storage::new_uref(U512::from(0));
+        let uref2: URef = storage::new_uref(U512::from(1));
+        runtime::put_key("uref1", Key::URef(uref1));
+        runtime::put_key("uref2", Key::URef(uref2));
+    } else if flag == "pass2" {
+        let uref1: URef = storage::new_uref(U512::from(0));
+        runtime::put_key("uref1", Key::URef(uref1));
+        // Drawing random bytes, both directly and in a subcall, SHOULD not forward the RNG used
+        // for addresses.
+        let random1 = runtime::random_bytes(RANDOM_BYTES_LENGTH);
+        let random2: Vec<u8> =
+            runtime::call_contract(contract_hash, "random_bytes_ext", RuntimeArgs::default());
+        let uref2: URef = storage::new_uref(U512::from(1));
+        runtime::put_key("uref2", Key::URef(uref2));
+
+        runtime::put_key("random1", storage::new_uref(random1).into());
+        runtime::put_key("random2", storage::new_uref(random2).into());
+    }
+}
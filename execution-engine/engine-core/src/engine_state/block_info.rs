@@ -0,0 +1,38 @@
+use engine_shared::newtypes::Blake2bHash;
+use types::account::AccountHash;
+
+/// The properties of the block a deploy is executed in which are exposed to contracts, besides
+/// its [`BlockTime`](types::BlockTime).
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct BlockInfo {
+    parent_state_hash: Blake2bHash,
+    block_height: u64,
+    proposer: Option<AccountHash>,
+}
+
+impl BlockInfo {
+    pub fn new(
+        parent_state_hash: Blake2bHash,
+        block_height: u64,
+        proposer: Option<AccountHash>,
+    ) -> Self {
+        BlockInfo {
+            parent_state_hash,
+            block_height,
+            proposer,
+        }
+    }
+
+    pub fn parent_state_hash(&self) -> Blake2bHash {
+        self.parent_state_hash
+    }
+
+    pub fn block_height(&self) -> u64 {
+        self.block_height
+    }
+
+    /// Returns the account which proposed the block, if the block was proposed by an account.
+    pub fn proposer(&self) -> Option<AccountHash> {
+        self.proposer
+    }
+}
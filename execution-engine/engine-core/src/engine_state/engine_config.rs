@@ -1,9 +1,28 @@
+/// The default number of threads used to execute deploys in parallel.
+pub const DEFAULT_EXECUTION_THREADS: usize = 4;
+
+/// The default maximum gas the session code of an estimated deploy may spend.
+pub const DEFAULT_ESTIMATE_GAS_LIMIT: u64 = 100_000_000_000;
+
 /// The runtime configuration of the execution engine
-#[derive(Debug, Copy, Clone, Default)]
+#[derive(Debug, Copy, Clone)]
 pub struct EngineConfig {
     // feature flags go here
     use_system_contracts: bool,
     enable_bonding: bool,
+    execution_threads: usize,
+    estimate_gas_limit: u64,
+}
+
+impl Default for EngineConfig {
+    fn default() -> Self {
+        EngineConfig {
+            use_system_contracts: false,
+            enable_bonding: false,
+            execution_threads: DEFAULT_EXECUTION_THREADS,
+            estimate_gas_limit: DEFAULT_ESTIMATE_GAS_LIMIT,
+        }
+    }
 }
 
 impl EngineConfig {
@@ -29,4 +48,26 @@ impl EngineConfig {
         self.enable_bonding = enable_bonding;
         self
     }
+
+    /// The number of threads used to execute the deploys of a request in
+    /// [`ExecutionMode::Parallel`](super::execute_request::ExecutionMode::Parallel).
+    pub fn execution_threads(self) -> usize {
+        self.execution_threads
+    }
+
+    pub fn with_execution_threads(mut self, execution_threads: usize) -> EngineConfig {
+        self.execution_threads = execution_threads;
+        self
+    }
+
+    /// The maximum gas the session code of a deploy may spend when it is
+    /// [estimated](super::EngineState::estimate).
+    pub fn estimate_gas_limit(self) -> u64 {
+        self.estimate_gas_limit
+    }
+
+    pub fn with_estimate_gas_limit(mut self, estimate_gas_limit: u64) -> EngineConfig {
+        self.estimate_gas_limit = estimate_gas_limit;
+        self
+    }
 }
@@ -0,0 +1,86 @@
+use engine_shared::{gas::Gas, motes::Motes, newtypes::Blake2bHash};
+use types::{ApiError, ProtocolVersion};
+
+use super::{
+    deploy_item::DeployItem, execution_effect::ExecutionEffect, execution_result::ExecutionResult,
+    Error, CONV_RATE,
+};
+use crate::execution;
+
+/// A request to measure the gas spent by the session code of a deploy.
+#[derive(Debug)]
+pub struct EstimateRequest {
+    pub parent_state_hash: Blake2bHash,
+    pub block_time: u64,
+    pub deploy_item: DeployItem,
+    pub protocol_version: ProtocolVersion,
+}
+
+impl EstimateRequest {
+    pub fn new(
+        parent_state_hash: Blake2bHash,
+        block_time: u64,
+        deploy_item: DeployItem,
+        protocol_version: ProtocolVersion,
+    ) -> Self {
+        EstimateRequest {
+            parent_state_hash,
+            block_time,
+            deploy_item,
+            protocol_version,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum EstimateResult {
+    RootNotFound,
+    /// The session code couldn't be executed, or failed other than by reverting or running out of
+    /// gas.
+    Failure(Error),
+    /// The session code would spend more than the given gas limit, which is
+    /// [`EngineConfig::estimate_gas_limit`](super::EngineConfig::estimate_gas_limit).
+    GasLimitExceeded(Gas),
+    Success {
+        /// The gas spent by the session code.
+        session_cost: Gas,
+        /// The payment in motes which buys `session_cost` at [`CONV_RATE`].  It doesn't cover the
+        /// gas spent by the payment code itself.
+        payment: Motes,
+        /// The effect the session code would produce.
+        effect: ExecutionEffect,
+        /// The error the session code reverted with, if it reverted.
+        revert: Option<ApiError>,
+    },
+}
+
+impl EstimateResult {
+    /// Returns the estimate given by executing session code with `gas_limit`.
+    pub fn from_execution_result(execution_result: ExecutionResult, gas_limit: Gas) -> Self {
+        let session_cost = execution_result.cost();
+        let payment = Motes::from_gas(session_cost, CONV_RATE).expect("motes overflow");
+        match execution_result {
+            ExecutionResult::Success { effect, .. } => EstimateResult::Success {
+                session_cost,
+                payment,
+                effect,
+                revert: None,
+            },
+            ExecutionResult::Failure {
+                error: Error::Exec(execution::Error::Revert(api_error)),
+                effect,
+                ..
+            } => EstimateResult::Success {
+                session_cost,
+                payment,
+                effect,
+                revert: Some(api_error),
+            },
+            ExecutionResult::Failure {
+                error: Error::Exec(execution::Error::GasLimit),
+                ..
+            } => EstimateResult::GasLimitExceeded(gas_limit),
+            ExecutionResult::Failure { error, .. } => EstimateResult::Failure(error),
+        }
+    }
+}
@@ -0,0 +1,21 @@
+use types::{CLValue, Key};
+
+/// An event emitted by a contract via the `emit_event` host function.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Event {
+    /// The base key of the emitter, i.e. the hash of the contract or, for session code, the key
+    /// of the account.
+    pub emitter: Key,
+    pub name: String,
+    pub value: CLValue,
+}
+
+impl Event {
+    pub fn new(emitter: Key, name: String, value: CLValue) -> Self {
+        Event {
+            emitter,
+            name,
+            value,
+        }
+    }
+}
@@ -1,33 +1,71 @@
-use std::mem;
+use std::{fmt, mem};
 
-use engine_shared::newtypes::Blake2bHash;
-use types::ProtocolVersion;
+use engine_shared::{newtypes::Blake2bHash, TypeMismatch};
+use engine_storage::global_state::CommitResult;
+use types::{account::AccountHash, bytesrepr, Key, ProtocolVersion};
 
-use super::{deploy_item::DeployItem, execution_result::ExecutionResult};
+use super::{
+    block_info::BlockInfo, deploy_item::DeployItem, execution_effect::ExecutionEffect,
+    execution_result::ExecutionResult,
+};
+
+/// How the deploys of an [`ExecuteRequest`] see each other's effects.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ExecutionMode {
+    /// Every deploy is executed against the parent state.
+    Independent,
+    /// Every deploy is executed against the parent state with the effects of the deploys before
+    /// it applied.
+    Sequential,
+    /// As [`ExecutionMode::Sequential`], but the deploys are first executed in parallel against
+    /// the parent state, and only the ones which read a value changed by an earlier deploy are
+    /// executed again.
+    Parallel,
+}
+
+impl Default for ExecutionMode {
+    fn default() -> Self {
+        ExecutionMode::Independent
+    }
+}
 
 #[derive(Debug)]
 pub struct ExecuteRequest {
     pub parent_state_hash: Blake2bHash,
     pub block_time: u64,
+    pub block_height: u64,
+    /// The account which proposed the block, if it is known.
+    pub proposer: Option<AccountHash>,
     pub deploys: Vec<Result<DeployItem, ExecutionResult>>,
     pub protocol_version: ProtocolVersion,
+    pub execution_mode: ExecutionMode,
 }
 
 impl ExecuteRequest {
     pub fn new(
         parent_state_hash: Blake2bHash,
         block_time: u64,
+        block_height: u64,
+        proposer: Option<AccountHash>,
         deploys: Vec<Result<DeployItem, ExecutionResult>>,
         protocol_version: ProtocolVersion,
+        execution_mode: ExecutionMode,
     ) -> Self {
         Self {
             parent_state_hash,
             block_time,
+            block_height,
+            proposer,
             deploys,
             protocol_version,
+            execution_mode,
         }
     }
 
+    pub fn block_info(&self) -> BlockInfo {
+        BlockInfo::new(self.parent_state_hash, self.block_height, self.proposer)
+    }
+
     pub fn take_deploys(&mut self) -> Vec<Result<DeployItem, ExecutionResult>> {
         mem::replace(&mut self.deploys, vec![])
     }
@@ -38,8 +76,80 @@ impl Default for ExecuteRequest {
         Self {
             parent_state_hash: [0u8; 32].into(),
             block_time: 0,
+            block_height: 0,
+            proposer: None,
             deploys: vec![],
             protocol_version: Default::default(),
+            execution_mode: Default::default(),
+        }
+    }
+}
+
+/// The result of executing the deploys of an [`ExecuteRequest`] in [`ExecutionMode::Sequential`]
+/// or [`ExecutionMode::Parallel`].
+#[derive(Debug)]
+pub enum SequentialExecutionResult {
+    RootNotFound,
+    KeyNotFound(Key),
+    TypeMismatch(TypeMismatch),
+    Serialization(bytesrepr::Error),
+    Success {
+        /// The result of each deploy, holding the effect of that deploy alone.
+        execution_results: Vec<ExecutionResult>,
+        /// The combined effect of all the deploys.
+        effect: ExecutionEffect,
+        post_state_hash: Blake2bHash,
+        /// The number of deploys whose result is the one of their speculative execution, which is
+        /// always zero in [`ExecutionMode::Sequential`].
+        speculative_results_used: usize,
+    },
+}
+
+impl fmt::Display for SequentialExecutionResult {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match self {
+            SequentialExecutionResult::RootNotFound => write!(f, "Root not found"),
+            SequentialExecutionResult::KeyNotFound(key) => write!(f, "Key not found: {}", key),
+            SequentialExecutionResult::TypeMismatch(type_mismatch) => {
+                write!(f, "Type mismatch: {:?}", type_mismatch)
+            }
+            SequentialExecutionResult::Serialization(error) => {
+                write!(f, "Serialization error: {:?}", error)
+            }
+            SequentialExecutionResult::Success {
+                execution_results,
+                post_state_hash,
+                ..
+            } => write!(
+                f,
+                "Success: {} deploys, post state hash {}",
+                execution_results.len(),
+                post_state_hash
+            ),
+        }
+    }
+}
+
+impl SequentialExecutionResult {
+    pub fn from_commit_result(
+        commit_result: CommitResult,
+        execution_results: Vec<ExecutionResult>,
+        effect: ExecutionEffect,
+        speculative_results_used: usize,
+    ) -> Self {
+        match commit_result {
+            CommitResult::RootNotFound => SequentialExecutionResult::RootNotFound,
+            CommitResult::KeyNotFound(key) => SequentialExecutionResult::KeyNotFound(key),
+            CommitResult::TypeMismatch(type_mismatch) => {
+                SequentialExecutionResult::TypeMismatch(type_mismatch)
+            }
+            CommitResult::Serialization(error) => SequentialExecutionResult::Serialization(error),
+            CommitResult::Success { state_root, .. } => SequentialExecutionResult::Success {
+                execution_results,
+                effect,
+                post_state_hash: state_root,
+                speculative_results_used,
+            },
         }
     }
 }
@@ -1,4 +1,4 @@
-use super::{error, execution_effect::ExecutionEffect, op::Op, CONV_RATE};
+use super::{error, event::Event, execution_effect::ExecutionEffect, op::Op, CONV_RATE};
 use engine_shared::{
     additive_map::AdditiveMap, gas::Gas, motes::Motes, newtypes::CorrelationId,
     stored_value::StoredValue, transform::Transform,
@@ -45,9 +45,20 @@ pub enum ExecutionResult {
         error: error::Error,
         effect: ExecutionEffect,
         cost: Gas,
+        /// The part of `cost` charged for values written to global state
+        storage_cost: Gas,
+        /// Events emitted by the code which ran successfully, in the order they were emitted
+        events: Vec<Event>,
     },
     /// Execution was finished successfully
-    Success { effect: ExecutionEffect, cost: Gas },
+    Success {
+        effect: ExecutionEffect,
+        cost: Gas,
+        /// The part of `cost` charged for values written to global state
+        storage_cost: Gas,
+        /// Events emitted during execution, in the order they were emitted
+        events: Vec<Event>,
+    },
 }
 
 pub enum ForcedTransferResult {
@@ -66,6 +77,8 @@ impl ExecutionResult {
             error,
             effect: Default::default(),
             cost: Gas::default(),
+            storage_cost: Gas::default(),
+            events: Vec::new(),
         }
     }
 
@@ -99,6 +112,19 @@ impl ExecutionResult {
         }
     }
 
+    /// Returns the part of the total cost charged for storage.
+    pub fn storage_cost(&self) -> Gas {
+        match self {
+            ExecutionResult::Failure { storage_cost, .. } => *storage_cost,
+            ExecutionResult::Success { storage_cost, .. } => *storage_cost,
+        }
+    }
+
+    /// Returns the part of the total cost charged for computation, i.e. everything but storage.
+    pub fn compute_cost(&self) -> Gas {
+        self.cost() - self.storage_cost()
+    }
+
     pub fn effect(&self) -> &ExecutionEffect {
         match self {
             ExecutionResult::Failure { effect, .. } => effect,
@@ -106,25 +132,91 @@ impl ExecutionResult {
         }
     }
 
-    pub fn with_cost(self, cost: Gas) -> Self {
+    pub fn events(&self) -> &[Event] {
+        match self {
+            ExecutionResult::Failure { events, .. } => events,
+            ExecutionResult::Success { events, .. } => events,
+        }
+    }
+
+    pub fn with_cost(self, cost: Gas, storage_cost: Gas) -> Self {
         match self {
-            ExecutionResult::Failure { error, effect, .. } => ExecutionResult::Failure {
+            ExecutionResult::Failure {
+                error,
+                effect,
+                events,
+                ..
+            } => ExecutionResult::Failure {
                 error,
                 effect,
                 cost,
+                storage_cost,
+                events,
+            },
+            ExecutionResult::Success { effect, events, .. } => ExecutionResult::Success {
+                effect,
+                cost,
+                storage_cost,
+                events,
             },
-            ExecutionResult::Success { effect, .. } => ExecutionResult::Success { effect, cost },
         }
     }
 
     pub fn with_effect(self, effect: ExecutionEffect) -> Self {
         match self {
-            ExecutionResult::Failure { error, cost, .. } => ExecutionResult::Failure {
+            ExecutionResult::Failure {
+                error,
+                cost,
+                storage_cost,
+                events,
+                ..
+            } => ExecutionResult::Failure {
+                error,
+                effect,
+                cost,
+                storage_cost,
+                events,
+            },
+            ExecutionResult::Success {
+                cost,
+                storage_cost,
+                events,
+                ..
+            } => ExecutionResult::Success {
+                effect,
+                cost,
+                storage_cost,
+                events,
+            },
+        }
+    }
+
+    pub fn with_events(self, events: Vec<Event>) -> Self {
+        match self {
+            ExecutionResult::Failure {
+                error,
+                effect,
+                cost,
+                storage_cost,
+                ..
+            } => ExecutionResult::Failure {
                 error,
                 effect,
                 cost,
+                storage_cost,
+                events,
+            },
+            ExecutionResult::Success {
+                effect,
+                cost,
+                storage_cost,
+                ..
+            } => ExecutionResult::Success {
+                effect,
+                cost,
+                storage_cost,
+                events,
             },
-            ExecutionResult::Success { cost, .. } => ExecutionResult::Success { effect, cost },
         }
     }
 
@@ -191,6 +283,8 @@ impl ExecutionResult {
             error,
             effect,
             cost,
+            storage_cost: Gas::default(),
+            events: Vec::new(),
         }
     }
 
@@ -266,18 +360,36 @@ impl ExecutionResultBuilder {
         payment_cost + session_cost
     }
 
+    pub fn total_storage_cost(&self) -> Gas {
+        let payment_storage_cost = self
+            .payment_execution_result
+            .as_ref()
+            .map(ExecutionResult::storage_cost)
+            .unwrap_or_default();
+        let session_storage_cost = self
+            .session_execution_result
+            .as_ref()
+            .map(ExecutionResult::storage_cost)
+            .unwrap_or_default();
+        payment_storage_cost + session_storage_cost
+    }
+
     pub fn build<R: StateReader<Key, StoredValue>>(
         self,
         reader: &R,
         correlation_id: CorrelationId,
     ) -> Result<ExecutionResult, ExecutionResultBuilderError> {
         let cost = self.total_cost();
+        let storage_cost = self.total_storage_cost();
         let mut ops = AdditiveMap::new();
         let mut transforms = AdditiveMap::new();
+        let mut events = Vec::new();
 
         let mut ret: ExecutionResult = ExecutionResult::Success {
             effect: Default::default(),
             cost,
+            storage_cost,
+            events: Vec::new(),
         };
 
         match self.payment_execution_result {
@@ -286,6 +398,7 @@ impl ExecutionResultBuilder {
                     return Ok(result);
                 } else {
                     Self::add_effects(&mut ops, &mut transforms, result.effect());
+                    events.extend_from_slice(result.events());
                 }
             }
             None => return Err(ExecutionResultBuilderError::MissingPaymentExecutionResult),
@@ -296,9 +409,10 @@ impl ExecutionResultBuilder {
         match self.session_execution_result {
             Some(result) => {
                 if result.is_failure() {
-                    ret = result.with_cost(cost);
+                    ret = result.with_cost(cost, storage_cost);
                 } else {
                     Self::add_effects(&mut ops, &mut transforms, result.effect());
+                    events.extend_from_slice(result.events());
                 }
             }
             None => return Err(ExecutionResultBuilderError::MissingSessionExecutionResult),
@@ -321,7 +435,7 @@ impl ExecutionResultBuilder {
         // Remove redundant writes to allow more opportunity to commute
         let reduced_effect = Self::reduce_identity_writes(ops, transforms, reader, correlation_id);
 
-        Ok(ret.with_effect(reduced_effect))
+        Ok(ret.with_effect(reduced_effect).with_events(events))
     }
 
     fn add_effects(
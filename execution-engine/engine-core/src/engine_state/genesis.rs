@@ -6,9 +6,14 @@ use rand::{
     Rng,
 };
 
-use engine_shared::{motes::Motes, newtypes::Blake2bHash, TypeMismatch};
+use engine_shared::{
+    motes::Motes, newtypes::Blake2bHash, storage_costs::StorageCosts, TypeMismatch,
+};
 use engine_storage::global_state::CommitResult;
-use engine_wasm_prep::wasm_costs::WasmCosts;
+use engine_wasm_prep::{
+    host_function_costs::{HostFunctionCost, HostFunctionCosts},
+    wasm_costs::WasmCosts,
+};
 use types::{account::AccountHash, bytesrepr, Key, ProtocolVersion, U512};
 
 use crate::engine_state::execution_effect::ExecutionEffect;
@@ -188,6 +193,8 @@ pub struct ExecConfig {
     standard_payment_installer_bytes: Vec<u8>,
     accounts: Vec<GenesisAccount>,
     wasm_costs: WasmCosts,
+    host_function_costs: HostFunctionCosts,
+    storage_costs: StorageCosts,
 }
 
 impl ExecConfig {
@@ -197,6 +204,8 @@ impl ExecConfig {
         standard_payment_installer_bytes: Vec<u8>,
         accounts: Vec<GenesisAccount>,
         wasm_costs: WasmCosts,
+        host_function_costs: HostFunctionCosts,
+        storage_costs: StorageCosts,
     ) -> ExecConfig {
         ExecConfig {
             mint_installer_bytes,
@@ -204,6 +213,8 @@ impl ExecConfig {
             standard_payment_installer_bytes,
             accounts,
             wasm_costs,
+            host_function_costs,
+            storage_costs,
         }
     }
     pub fn mint_installer_bytes(&self) -> &[u8] {
@@ -222,6 +233,14 @@ impl ExecConfig {
         self.wasm_costs
     }
 
+    pub fn host_function_costs(&self) -> HostFunctionCosts {
+        self.host_function_costs
+    }
+
+    pub fn storage_costs(&self) -> StorageCosts {
+        self.storage_costs
+    }
+
     pub fn get_bonded_validators(&self) -> impl Iterator<Item = (AccountHash, Motes)> + '_ {
         let zero = Motes::zero();
         self.accounts.iter().filter_map(move |genesis_account| {
@@ -274,12 +293,70 @@ impl Distribution<ExecConfig> for Standard {
             opcodes_div: rng.gen(),
         };
 
+        let mut host_function_cost = || HostFunctionCost::new(rng.gen(), rng.gen());
+        let host_function_costs = HostFunctionCosts {
+            read_value: host_function_cost(),
+            read_value_local: host_function_cost(),
+            load_named_keys: host_function_cost(),
+            write: host_function_cost(),
+            write_local: host_function_cost(),
+            add: host_function_cost(),
+            new_uref: host_function_cost(),
+            ret: host_function_cost(),
+            get_key: host_function_cost(),
+            has_key: host_function_cost(),
+            put_key: host_function_cost(),
+            remove_key: host_function_cost(),
+            is_valid_uref: host_function_cost(),
+            revert: host_function_cost(),
+            add_associated_key: host_function_cost(),
+            remove_associated_key: host_function_cost(),
+            update_associated_key: host_function_cost(),
+            set_action_threshold: host_function_cost(),
+            get_caller: host_function_cost(),
+            get_blocktime: host_function_cost(),
+            create_purse: host_function_cost(),
+            transfer_to_account: host_function_cost(),
+            transfer_from_purse_to_account: host_function_cost(),
+            transfer_from_purse_to_purse: host_function_cost(),
+            get_balance: host_function_cost(),
+            get_phase: host_function_cost(),
+            get_system_contract: host_function_cost(),
+            get_main_purse: host_function_cost(),
+            read_host_buffer: host_function_cost(),
+            create_contract_package_at_hash: host_function_cost(),
+            create_contract_user_group: host_function_cost(),
+            add_contract_version: host_function_cost(),
+            disable_contract_version: host_function_cost(),
+            call_contract: host_function_cost(),
+            call_versioned_contract: host_function_cost(),
+            get_named_arg_size: host_function_cost(),
+            get_named_arg: host_function_cost(),
+            remove_contract_user_group: host_function_cost(),
+            provision_contract_user_group_uref: host_function_cost(),
+            remove_contract_user_group_urefs: host_function_cost(),
+            prune: host_function_cost(),
+            emit_event: host_function_cost(),
+            blake2b: host_function_cost(),
+            ed25519_verify: host_function_cost(),
+            secp256k1_verify: host_function_cost(),
+            random_bytes: host_function_cost(),
+            get_deploy_hash: host_function_cost(),
+            get_block_height: host_function_cost(),
+            get_proposer: host_function_cost(),
+            get_parent_state_hash: host_function_cost(),
+        };
+
+        let storage_costs = StorageCosts::new(rng.gen());
+
         ExecConfig {
             mint_installer_bytes,
             proof_of_stake_installer_bytes,
             standard_payment_installer_bytes,
             accounts,
             wasm_costs,
+            host_function_costs,
+            storage_costs,
         }
     }
 }
@@ -1,6 +1,9 @@
+pub mod block_info;
 pub mod deploy_item;
 pub mod engine_config;
 mod error;
+pub mod estimate;
+pub mod event;
 pub mod executable_deploy_item;
 pub mod execute_request;
 pub mod execution_effect;
@@ -9,6 +12,7 @@ pub mod genesis;
 pub mod op;
 pub mod query;
 pub mod run_genesis_request;
+mod speculative;
 pub mod system_contract_cache;
 mod transfer;
 pub mod upgrade;
@@ -16,10 +20,14 @@ pub mod utils;
 
 use std::{
     cell::RefCell,
-    collections::{BTreeMap, BTreeSet, HashMap},
+    cmp,
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
     rc::Rc,
+    sync::atomic::{AtomicUsize, Ordering},
 };
 
+use crossbeam_utils::thread;
+
 use log::{debug, warn};
 use num_traits::Zero;
 use parity_wasm::elements::Module;
@@ -34,7 +42,7 @@ use engine_shared::{
     transform::Transform,
 };
 use engine_storage::{
-    global_state::{CommitResult, StateProvider, StateReader},
+    global_state::{CommitManyResult, CommitResult, StateProvider, StateReader},
     protocol_data::ProtocolData,
 };
 use engine_wasm_prep::{wasm_costs::WasmCosts, Preprocessor};
@@ -57,15 +65,18 @@ pub use self::{
 };
 use crate::{
     engine_state::{
+        block_info::BlockInfo,
         deploy_item::DeployItem,
         error::Error::MissingSystemContract,
+        estimate::{EstimateRequest, EstimateResult},
         executable_deploy_item::ExecutableDeployItem,
-        execute_request::ExecuteRequest,
+        execute_request::{ExecuteRequest, SequentialExecutionResult},
         execution_result::{ExecutionResult, ForcedTransferResult},
         genesis::{
             ExecConfig, GenesisAccount, GenesisResult, POS_PAYMENT_PURSE, POS_REWARDS_PURSE,
         },
         query::{QueryRequest, QueryResult},
+        speculative::{ReadRecorder, SpeculativeExecution},
         system_contract_cache::SystemContractCache,
         transfer::TransferTargetMode,
         upgrade::{UpgradeConfig, UpgradeResult},
@@ -73,7 +84,8 @@ use crate::{
     execution::{
         self, AddressGenerator, AddressGeneratorBuilder, DirectSystemContractCall, Executor,
     },
-    tracking_copy::{TrackingCopy, TrackingCopyExt},
+    runtime::trace::ExecutionTrace,
+    tracking_copy::{AddResult, TrackingCopy, TrackingCopyExt, TrackingCopyQueryResult},
 };
 
 // TODO?: MAX_PAYMENT && CONV_RATE values are currently arbitrary w/ real values
@@ -172,7 +184,10 @@ where
         let phase = Phase::System;
 
         let initial_root_hash = self.state.empty_root();
+        let block_info = BlockInfo::new(initial_root_hash, 0, None);
         let wasm_costs = ee_config.wasm_costs();
+        let host_function_costs = ee_config.host_function_costs();
+        let storage_costs = ee_config.storage_costs();
         let preprocessor = Preprocessor::new(wasm_costs);
 
         // Spec #3: Create "virtual system account" object.
@@ -232,6 +247,7 @@ where
                 &mut virtual_system_account,
                 authorization_keys,
                 blocktime,
+                block_info,
                 install_deploy_hash,
                 gas_limit,
                 hash_address_generator,
@@ -284,6 +300,7 @@ where
                 &mut virtual_system_account,
                 authorization_keys,
                 blocktime,
+                block_info,
                 install_deploy_hash,
                 gas_limit,
                 hash_address_generator,
@@ -303,6 +320,8 @@ where
         // specification.
         let protocol_data = ProtocolData::partial_without_standard_payment(
             wasm_costs,
+            host_function_costs,
+            storage_costs,
             mint_hash,
             proof_of_stake_hash,
         );
@@ -336,6 +355,7 @@ where
                 &mut virtual_system_account,
                 authorization_keys,
                 blocktime,
+                block_info,
                 install_deploy_hash,
                 gas_limit,
                 hash_address_generator,
@@ -352,6 +372,8 @@ where
         // Spec #2: Associate given CostTable with given ProtocolVersion.
         let protocol_data = ProtocolData::new(
             wasm_costs,
+            host_function_costs,
+            storage_costs,
             mint_hash,
             proof_of_stake_hash,
             standard_payment_hash,
@@ -439,6 +461,7 @@ where
                         &virtual_system_account,
                         authorization_keys,
                         blocktime,
+                        block_info,
                         purse_creation_deploy_hash,
                         gas_limit,
                         hash_address_generator,
@@ -535,9 +558,21 @@ where
             None => *current_protocol_data.wasm_costs(),
         };
 
+        let new_host_function_costs = match upgrade_config.host_function_costs() {
+            Some(new_host_function_costs) => new_host_function_costs,
+            None => *current_protocol_data.host_function_costs(),
+        };
+
+        let new_storage_costs = match upgrade_config.storage_costs() {
+            Some(new_storage_costs) => new_storage_costs,
+            None => *current_protocol_data.storage_costs(),
+        };
+
         // 3.1.2.2 persist wasm CostTable
         let mut new_protocol_data = ProtocolData::new(
             new_wasm_costs,
+            new_host_function_costs,
+            new_storage_costs,
             current_protocol_data.mint(),
             current_protocol_data.proof_of_stake(),
             current_protocol_data.standard_payment(),
@@ -591,6 +626,7 @@ where
                 };
 
                 let blocktime = BlockTime::default();
+                let block_info = BlockInfo::new(pre_state_hash, 0, None);
 
                 let deploy_hash = {
                     // seeds address generator w/ protocol version
@@ -625,6 +661,7 @@ where
                     &mut system_account,
                     authorization_keys,
                     blocktime,
+                    block_info,
                     deploy_hash,
                     gas_limit,
                     hash_address_generator,
@@ -685,24 +722,57 @@ where
 
         let tracking_copy = tracking_copy.borrow();
 
-        Ok(tracking_copy
-            .query(correlation_id, query_request.key(), query_request.path())
-            .map_err(|err| Error::Exec(err.into()))?
-            .into())
+        if !query_request.include_proofs() {
+            return Ok(tracking_copy
+                .query(correlation_id, query_request.key(), query_request.path())
+                .map_err(|err| Error::Exec(err.into()))?
+                .into());
+        }
+
+        let (query_result, proofs) = tracking_copy
+            .query_with_proofs(correlation_id, query_request.key(), query_request.path())
+            .map_err(|err| Error::Exec(err.into()))?;
+
+        match query_result {
+            TrackingCopyQueryResult::Success(value) => Ok(QueryResult::Success { value, proofs }),
+            query_result => Ok(query_result.into()),
+        }
     }
 
     pub fn run_execute(
         &self,
         correlation_id: CorrelationId,
-        mut exec_request: ExecuteRequest,
+        exec_request: ExecuteRequest,
     ) -> Result<Vec<ExecutionResult>, RootNotFound> {
+        let executor = Executor::new(self.config);
+        let results = self.execute_independently(correlation_id, exec_request, &executor)?;
+        Ok(results.into_iter().map(|(result, _)| result).collect())
+    }
+
+    /// As [`run_execute`](Self::run_execute), but also returns the host functions called while
+    /// executing each deploy.
+    pub fn run_execute_traced(
+        &self,
+        correlation_id: CorrelationId,
+        exec_request: ExecuteRequest,
+    ) -> Result<Vec<(ExecutionResult, ExecutionTrace)>, RootNotFound> {
+        let executor = Executor::with_tracing(self.config);
+        self.execute_independently(correlation_id, exec_request, &executor)
+    }
+
+    fn execute_independently(
+        &self,
+        correlation_id: CorrelationId,
+        mut exec_request: ExecuteRequest,
+        executor: &Executor,
+    ) -> Result<Vec<(ExecutionResult, ExecutionTrace)>, RootNotFound> {
         // TODO: do not unwrap
         let wasm_costs = self
             .wasm_costs(exec_request.protocol_version)
             .unwrap()
             .unwrap();
-        let executor = Executor::new(self.config);
         let preprocessor = Preprocessor::new(wasm_costs);
+        let block_info = exec_request.block_info();
 
         let mut results = Vec::new();
 
@@ -712,26 +782,28 @@ where
                 Ok(deploy_item) => match deploy_item.session {
                     ExecutableDeployItem::Transfer { .. } => self.transfer(
                         correlation_id,
-                        &executor,
+                        executor,
                         &preprocessor,
                         exec_request.protocol_version,
                         exec_request.parent_state_hash,
                         BlockTime::new(exec_request.block_time),
+                        block_info,
                         deploy_item,
                     ),
                     _ => self.deploy(
                         correlation_id,
-                        &executor,
+                        executor,
                         &preprocessor,
                         exec_request.protocol_version,
                         exec_request.parent_state_hash,
                         BlockTime::new(exec_request.block_time),
+                        block_info,
                         deploy_item,
                     ),
                 },
             };
             match result {
-                Ok(result) => results.push(result),
+                Ok(result) => results.push((result, executor.take_trace())),
                 Err(error) => {
                     return Err(error);
                 }
@@ -741,15 +813,462 @@ where
         Ok(results)
     }
 
-    pub fn get_module(
+    /// Executes the deploys of `exec_request` one after the other against a shared
+    /// `TrackingCopy`, so that each deploy sees the effects of the deploys before it, then commits
+    /// their combined effect on top of the parent state.
+    pub fn run_execute_sequential(
+        &self,
+        correlation_id: CorrelationId,
+        mut exec_request: ExecuteRequest,
+    ) -> Result<SequentialExecutionResult, Error> {
+        let protocol_data = self.get_execution_protocol_data(exec_request.protocol_version)?;
+
+        let tracking_copy = match self.tracking_copy(exec_request.parent_state_hash)? {
+            Some(tracking_copy) => tracking_copy,
+            None => return Ok(SequentialExecutionResult::RootNotFound),
+        };
+
+        let deploys = exec_request.take_deploys();
+        let speculative_executions = deploys.iter().map(|_| None).collect();
+
+        self.execute_in_order(
+            correlation_id,
+            &exec_request,
+            protocol_data,
+            tracking_copy,
+            deploys,
+            speculative_executions,
+        )
+    }
+
+    /// Executes the deploys of `exec_request` with the same results as
+    /// [`run_execute_sequential`](Self::run_execute_sequential).
+    ///
+    /// The deploys are first executed in parallel against the parent state, on up to
+    /// [`EngineConfig::execution_threads`] threads.  Then, in request order, a deploy is executed
+    /// again on top of the deploys before it only if one of them changed a value it read.
+    pub fn run_execute_parallel(
+        &self,
+        correlation_id: CorrelationId,
+        mut exec_request: ExecuteRequest,
+    ) -> Result<SequentialExecutionResult, Error>
+    where
+        S: Sync,
+    {
+        let protocol_data = self.get_execution_protocol_data(exec_request.protocol_version)?;
+
+        let tracking_copy = match self.tracking_copy(exec_request.parent_state_hash)? {
+            Some(tracking_copy) => tracking_copy,
+            None => return Ok(SequentialExecutionResult::RootNotFound),
+        };
+
+        let deploys = exec_request.take_deploys();
+        let speculative_executions =
+            self.execute_speculatively(correlation_id, &exec_request, protocol_data, &deploys)?;
+
+        self.execute_in_order(
+            correlation_id,
+            &exec_request,
+            protocol_data,
+            tracking_copy,
+            deploys,
+            speculative_executions,
+        )
+    }
+
+    fn get_execution_protocol_data(
+        &self,
+        protocol_version: ProtocolVersion,
+    ) -> Result<ProtocolData, Error> {
+        match self.get_protocol_data(protocol_version)? {
+            Some(protocol_data) => Ok(protocol_data),
+            None => Err(Error::InvalidProtocolVersion(protocol_version)),
+        }
+    }
+
+    /// Executes each of `deploys` against the parent state of `exec_request`, recording the
+    /// values it reads.
+    ///
+    /// Deploys which were not executed, as their deploy item is an error, are left as `None`.
+    fn execute_speculatively(
         &self,
-        tracking_copy: Rc<RefCell<TrackingCopy<<S as StateProvider>::Reader>>>,
+        correlation_id: CorrelationId,
+        exec_request: &ExecuteRequest,
+        protocol_data: ProtocolData,
+        deploys: &[Result<DeployItem, ExecutionResult>],
+    ) -> Result<Vec<Option<SpeculativeExecution>>, Error>
+    where
+        S: Sync,
+    {
+        let protocol_version = exec_request.protocol_version;
+        let prestate_hash = exec_request.parent_state_hash;
+        let blocktime = BlockTime::new(exec_request.block_time);
+        let block_info = exec_request.block_info();
+        let thread_count = cmp::min(self.config.execution_threads(), deploys.len());
+        let next_index = AtomicUsize::new(0);
+
+        let finished = thread::scope(|scope| {
+            let workers: Vec<_> = (0..thread_count)
+                .map(|_| {
+                    scope.spawn(|_| {
+                        let executor = Executor::new(self.config);
+                        let preprocessor = Preprocessor::new(*protocol_data.wasm_costs());
+                        let mut finished = Vec::new();
+                        loop {
+                            let index = next_index.fetch_add(1, Ordering::SeqCst);
+                            let deploy_item = match deploys.get(index) {
+                                Some(Ok(deploy_item)) => deploy_item.to_owned(),
+                                Some(Err(_)) => continue,
+                                None => break,
+                            };
+                            // The parent state was found before, so it is missing only if it was
+                            // removed meanwhile, in which case the deploy is executed in order.
+                            let reader = match self.state.checkout(prestate_hash) {
+                                Ok(Some(reader)) => reader,
+                                Ok(None) => continue,
+                                Err(error) => return Err(Error::Exec(error.into())),
+                            };
+                            let tracking_copy =
+                                Rc::new(RefCell::new(TrackingCopy::new(ReadRecorder::new(reader))));
+                            let execution_result = self.execute_deploy_item(
+                                correlation_id,
+                                &executor,
+                                &preprocessor,
+                                protocol_version,
+                                protocol_data,
+                                blocktime,
+                                block_info,
+                                deploy_item,
+                                Rc::clone(&tracking_copy),
+                            );
+                            let reads = tracking_copy.borrow().reader().take_reads();
+                            finished.push((
+                                index,
+                                SpeculativeExecution {
+                                    execution_result,
+                                    reads,
+                                },
+                            ));
+                        }
+                        Ok(finished)
+                    })
+                })
+                .collect();
+            workers
+                .into_iter()
+                .map(|worker| worker.join().expect("execution thread should not panic"))
+                .collect::<Result<Vec<_>, Error>>()
+        })
+        .expect("execution threads should not panic")?;
+
+        let mut speculative_executions: Vec<Option<SpeculativeExecution>> =
+            deploys.iter().map(|_| None).collect();
+        for (index, speculative_execution) in finished.into_iter().flatten() {
+            speculative_executions[index] = Some(speculative_execution);
+        }
+        Ok(speculative_executions)
+    }
+
+    /// Executes `deploys` one after the other on `tracking_copy`, using the matching speculative
+    /// execution of a deploy instead where it is still valid, then commits their combined effect
+    /// on top of the parent state.
+    fn execute_in_order(
+        &self,
+        correlation_id: CorrelationId,
+        exec_request: &ExecuteRequest,
+        protocol_data: ProtocolData,
+        mut tracking_copy: TrackingCopy<S::Reader>,
+        deploys: Vec<Result<DeployItem, ExecutionResult>>,
+        speculative_executions: Vec<Option<SpeculativeExecution>>,
+    ) -> Result<SequentialExecutionResult, Error> {
+        let protocol_version = exec_request.protocol_version;
+        let blocktime = BlockTime::new(exec_request.block_time);
+        let block_info = exec_request.block_info();
+
+        let executor = Executor::new(self.config);
+        let preprocessor = Preprocessor::new(*protocol_data.wasm_costs());
+
+        let mut execution_results = Vec::new();
+        // The keys changed by the deploys executed so far
+        let mut modified_keys = HashSet::new();
+        let mut speculative_results_used = 0;
+
+        for (deploy_item, speculative_execution) in deploys.into_iter().zip(speculative_executions)
+        {
+            let execution_result = match deploy_item {
+                Err(exec_result) => exec_result,
+                Ok(deploy_item) => {
+                    let is_stale = match &speculative_execution {
+                        Some(speculative_execution) => speculative_execution
+                            .is_stale(correlation_id, &mut tracking_copy, &modified_keys)
+                            .map_err(|error| Error::Exec(error.into()))?,
+                        None => true,
+                    };
+                    match speculative_execution {
+                        Some(speculative_execution) if !is_stale => {
+                            speculative_results_used += 1;
+                            speculative_execution.execution_result
+                        }
+                        // Each deploy runs against a fork, so that only its own effect ends up in
+                        // its result.
+                        _ => self.execute_deploy_item(
+                            correlation_id,
+                            &executor,
+                            &preprocessor,
+                            protocol_version,
+                            protocol_data,
+                            blocktime,
+                            block_info,
+                            deploy_item,
+                            Rc::new(RefCell::new(tracking_copy.fork())),
+                        ),
+                    }
+                }
+            };
+
+            modified_keys.extend(
+                execution_result
+                    .effect()
+                    .transforms
+                    .iter()
+                    .filter(|(_, transform)| **transform != Transform::Identity)
+                    .map(|(key, _)| *key),
+            );
+
+            match tracking_copy
+                .apply_effect(correlation_id, execution_result.effect().to_owned())
+                .map_err(|error| Error::Exec(error.into()))?
+            {
+                AddResult::Success => (),
+                AddResult::KeyNotFound(key) => {
+                    return Ok(SequentialExecutionResult::KeyNotFound(key))
+                }
+                AddResult::TypeMismatch(type_mismatch) => {
+                    return Ok(SequentialExecutionResult::TypeMismatch(type_mismatch))
+                }
+                AddResult::Serialization(error) => {
+                    return Ok(SequentialExecutionResult::Serialization(error))
+                }
+            }
+
+            execution_results.push(execution_result);
+        }
+
+        let effect = tracking_copy.effect();
+
+        let commit_result = self
+            .state
+            .commit(
+                correlation_id,
+                exec_request.parent_state_hash,
+                effect.transforms.to_owned(),
+            )
+            .map_err(Into::into)?;
+
+        Ok(SequentialExecutionResult::from_commit_result(
+            commit_result,
+            execution_results,
+            effect,
+            speculative_results_used,
+        ))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn execute_deploy_item<R>(
+        &self,
+        correlation_id: CorrelationId,
+        executor: &Executor,
+        preprocessor: &Preprocessor,
+        protocol_version: ProtocolVersion,
+        protocol_data: ProtocolData,
+        blocktime: BlockTime,
+        block_info: BlockInfo,
+        deploy_item: DeployItem,
+        tracking_copy: Rc<RefCell<TrackingCopy<R>>>,
+    ) -> ExecutionResult
+    where
+        R: StateReader<Key, StoredValue>,
+        R::Error: Into<execution::Error>,
+    {
+        match deploy_item.session {
+            ExecutableDeployItem::Transfer { .. } => self.transfer_with_tracking_copy(
+                correlation_id,
+                executor,
+                preprocessor,
+                protocol_version,
+                protocol_data,
+                blocktime,
+                block_info,
+                deploy_item,
+                tracking_copy,
+            ),
+            _ => self.deploy_with_tracking_copy(
+                correlation_id,
+                executor,
+                preprocessor,
+                protocol_version,
+                protocol_data,
+                blocktime,
+                block_info,
+                deploy_item,
+                tracking_copy,
+            ),
+        }
+    }
+
+    /// Executes the session code of the deploy in `estimate_request` against its parent state
+    /// with a gas limit of [`EngineConfig::estimate_gas_limit`], without executing or charging for
+    /// its payment code, and reports the gas spent.  Nothing is committed.
+    ///
+    /// The session of a transfer is the transfer itself.
+    pub fn estimate(
+        &self,
+        correlation_id: CorrelationId,
+        estimate_request: EstimateRequest,
+    ) -> Result<EstimateResult, Error> {
+        let protocol_version = estimate_request.protocol_version;
+        let blocktime = BlockTime::new(estimate_request.block_time);
+        // An estimate isn't made for a particular block, so neither its height nor its proposer
+        // are known.
+        let block_info = BlockInfo::new(estimate_request.parent_state_hash, 0, None);
+        let deploy_item = estimate_request.deploy_item;
+
+        let protocol_data = self.get_execution_protocol_data(protocol_version)?;
+
+        let tracking_copy = match self.tracking_copy(estimate_request.parent_state_hash)? {
+            Some(tracking_copy) => Rc::new(RefCell::new(tracking_copy)),
+            None => return Ok(EstimateResult::RootNotFound),
+        };
+
+        let executor = Executor::new(self.config);
+        let preprocessor = Preprocessor::new(*protocol_data.wasm_costs());
+        let gas_limit = Gas::new(U512::from(self.config.estimate_gas_limit()));
+
+        if let ExecutableDeployItem::Transfer { .. } = deploy_item.session {
+            let transfer_result = self.transfer_with_tracking_copy(
+                correlation_id,
+                &executor,
+                &preprocessor,
+                protocol_version,
+                protocol_data,
+                blocktime,
+                block_info,
+                deploy_item,
+                tracking_copy,
+            );
+            debug!("Estimated transfer result: {:?}", transfer_result);
+            return Ok(EstimateResult::from_execution_result(
+                transfer_result,
+                gas_limit,
+            ));
+        }
+
+        let account = match self.get_authorized_account(
+            correlation_id,
+            deploy_item.address,
+            &deploy_item.authorization_keys,
+            Rc::clone(&tracking_copy),
+        ) {
+            Ok(account) => account,
+            Err(error) => return Ok(EstimateResult::Failure(error)),
+        };
+
+        let session_module = match self.get_module(
+            Rc::clone(&tracking_copy),
+            &deploy_item.session,
+            &account,
+            correlation_id,
+            &preprocessor,
+            &protocol_version,
+        ) {
+            Ok(module) => module,
+            Err(error) => return Ok(EstimateResult::Failure(error)),
+        };
+
+        let (
+            session_module,
+            session_base_key,
+            mut session_named_keys,
+            session_package,
+            session_entry_point,
+        ) = match session_module {
+            GetModuleResult::Session {
+                module,
+                contract_package,
+                entry_point,
+            } => (
+                module,
+                Key::Account(deploy_item.address),
+                account.named_keys().clone(),
+                contract_package,
+                entry_point,
+            ),
+            GetModuleResult::Contract {
+                module,
+                base_key,
+                contract,
+                contract_package,
+                entry_point,
+            } => (
+                module,
+                base_key,
+                contract.named_keys().clone(),
+                contract_package,
+                entry_point,
+            ),
+        };
+
+        let session_args = match deploy_item.session.into_runtime_args() {
+            Ok(args) => args,
+            Err(error) => {
+                let exec_err: execution::Error = error.into();
+                return Ok(EstimateResult::Failure(exec_err.into()));
+            }
+        };
+
+        let system_contract_cache = SystemContractCache::clone(&self.system_contract_cache);
+
+        let session_result = executor.exec(
+            session_module,
+            session_entry_point,
+            session_args,
+            session_base_key,
+            &account,
+            &mut session_named_keys,
+            deploy_item.authorization_keys,
+            blocktime,
+            block_info,
+            deploy_item.deploy_hash,
+            gas_limit,
+            protocol_version,
+            correlation_id,
+            tracking_copy,
+            Phase::Session,
+            protocol_data,
+            system_contract_cache,
+            &session_package,
+        );
+        debug!("Estimated session result: {:?}", session_result);
+
+        Ok(EstimateResult::from_execution_result(
+            session_result,
+            gas_limit,
+        ))
+    }
+
+    pub fn get_module<R>(
+        &self,
+        tracking_copy: Rc<RefCell<TrackingCopy<R>>>,
         deploy_item: &ExecutableDeployItem,
         account: &Account,
         correlation_id: CorrelationId,
         preprocessor: &Preprocessor,
         protocol_version: &ProtocolVersion,
-    ) -> Result<GetModuleResult, error::Error> {
+    ) -> Result<GetModuleResult, error::Error>
+    where
+        R: StateReader<Key, StoredValue>,
+        R::Error: Into<execution::Error>,
+    {
         let (contract_package, contract, base_key) = match deploy_item {
             ExecutableDeployItem::ModuleBytes { module_bytes, .. } => {
                 let module = preprocessor.preprocess(&module_bytes)?;
@@ -859,13 +1378,17 @@ where
         }
     }
 
-    fn get_module_from_contract_hash(
+    fn get_module_from_contract_hash<R>(
         &self,
-        tracking_copy: Rc<RefCell<TrackingCopy<<S as StateProvider>::Reader>>>,
+        tracking_copy: Rc<RefCell<TrackingCopy<R>>>,
         contract_hash: ContractHash,
         correlation_id: CorrelationId,
         protocol_version: &ProtocolVersion,
-    ) -> Result<Module, error::Error> {
+    ) -> Result<Module, error::Error>
+    where
+        R: StateReader<Key, StoredValue>,
+        R::Error: Into<execution::Error>,
+    {
         let contract = tracking_copy
             .borrow_mut()
             .get_contract(correlation_id, contract_hash)?;
@@ -889,13 +1412,17 @@ where
         Ok(module)
     }
 
-    fn get_authorized_account(
+    fn get_authorized_account<R>(
         &self,
         correlation_id: CorrelationId,
         account_hash: AccountHash,
         authorization_keys: &BTreeSet<AccountHash>,
-        tracking_copy: Rc<RefCell<TrackingCopy<<S as StateProvider>::Reader>>>,
-    ) -> Result<Account, Error> {
+        tracking_copy: Rc<RefCell<TrackingCopy<R>>>,
+    ) -> Result<Account, Error>
+    where
+        R: StateReader<Key, StoredValue>,
+        R::Error: Into<execution::Error>,
+    {
         let account: Account = match tracking_copy
             .borrow_mut()
             .get_account(correlation_id, account_hash)
@@ -928,6 +1455,7 @@ where
         protocol_version: ProtocolVersion,
         prestate_hash: Blake2bHash,
         blocktime: BlockTime,
+        block_info: BlockInfo,
         deploy_item: DeployItem,
     ) -> Result<ExecutionResult, RootNotFound> {
         let protocol_data = match self.state.get_protocol_data(protocol_version) {
@@ -949,14 +1477,43 @@ where
             Ok(Some(tracking_copy)) => Rc::new(RefCell::new(tracking_copy)),
         };
 
+        Ok(self.transfer_with_tracking_copy(
+            correlation_id,
+            executor,
+            preprocessor,
+            protocol_version,
+            protocol_data,
+            blocktime,
+            block_info,
+            deploy_item,
+            tracking_copy,
+        ))
+    }
+
+    /// Executes the transfer `deploy_item` against `tracking_copy`.
+    #[allow(clippy::too_many_arguments)]
+    fn transfer_with_tracking_copy<R>(
+        &self,
+        correlation_id: CorrelationId,
+        executor: &Executor,
+        preprocessor: &Preprocessor,
+        protocol_version: ProtocolVersion,
+        protocol_data: ProtocolData,
+        blocktime: BlockTime,
+        block_info: BlockInfo,
+        deploy_item: DeployItem,
+        tracking_copy: Rc<RefCell<TrackingCopy<R>>>,
+    ) -> ExecutionResult
+    where
+        R: StateReader<Key, StoredValue>,
+        R::Error: Into<execution::Error>,
+    {
         let base_key = Key::Account(deploy_item.address);
 
         let account_public_key = match base_key.into_account() {
             Some(account_addr) => account_addr,
             None => {
-                return Ok(ExecutionResult::precondition_failure(
-                    error::Error::Authorization,
-                ));
+                return ExecutionResult::precondition_failure(error::Error::Authorization);
             }
         };
 
@@ -969,7 +1526,7 @@ where
             Rc::clone(&tracking_copy),
         ) {
             Ok(account) => account,
-            Err(e) => return Ok(ExecutionResult::precondition_failure(e)),
+            Err(e) => return ExecutionResult::precondition_failure(e),
         };
 
         let mint_contract = match tracking_copy
@@ -978,7 +1535,7 @@ where
         {
             Ok(contract) => contract,
             Err(error) => {
-                return Ok(ExecutionResult::precondition_failure(error.into()));
+                return ExecutionResult::precondition_failure(error.into());
             }
         };
 
@@ -993,7 +1550,7 @@ where
             ) {
                 Ok(module) => module,
                 Err(error) => {
-                    return Ok(ExecutionResult::precondition_failure(error.into()));
+                    return ExecutionResult::precondition_failure(error.into());
                 }
             }
         };
@@ -1005,7 +1562,7 @@ where
 
         let input_runtime_args = match deploy_item.session.into_runtime_args() {
             Ok(runtime_args) => runtime_args,
-            Err(error) => return Ok(ExecutionResult::precondition_failure(error.into())),
+            Err(error) => return ExecutionResult::precondition_failure(error.into()),
         };
 
         let mut runtime_args_builder = TransferRuntimeArgsBuilder::new(input_runtime_args);
@@ -1024,6 +1581,7 @@ where
                             &account,
                             authorization_keys.clone(),
                             blocktime,
+                            block_info,
                             deploy_item.deploy_hash,
                             gas_limit,
                             protocol_version,
@@ -1044,17 +1602,19 @@ where
                                 .write(Key::Account(public_key), StoredValue::Account(new_account))
                         }
                         None => {
-                            return Ok(execution_result);
+                            return execution_result;
                         }
                     }
                 }
             },
             Err(error) => {
-                return Ok(ExecutionResult::Failure {
+                return ExecutionResult::Failure {
                     error,
                     effect: Default::default(),
                     cost: Gas::default(),
-                });
+                    storage_cost: Gas::default(),
+                    events: Vec::new(),
+                };
             }
         }
 
@@ -1062,11 +1622,13 @@ where
             match runtime_args_builder.build(&account, correlation_id, Rc::clone(&tracking_copy)) {
                 Ok(runtime_args) => runtime_args,
                 Err(error) => {
-                    return Ok(ExecutionResult::Failure {
+                    return ExecutionResult::Failure {
                         error,
                         effect: Default::default(),
                         cost: Gas::default(),
-                    });
+                        storage_cost: Gas::default(),
+                        events: Vec::new(),
+                    };
                 }
             };
 
@@ -1081,6 +1643,7 @@ where
                 &account,
                 authorization_keys,
                 blocktime,
+                block_info,
                 deploy_item.deploy_hash,
                 gas_limit,
                 protocol_version,
@@ -1091,7 +1654,7 @@ where
                 SystemContractCache::clone(&self.system_contract_cache),
             );
 
-        Ok(execution_result)
+        execution_result
     }
 
     #[allow(clippy::too_many_arguments)]
@@ -1103,6 +1666,7 @@ where
         protocol_version: ProtocolVersion,
         prestate_hash: Blake2bHash,
         blocktime: BlockTime,
+        block_info: BlockInfo,
         deploy_item: DeployItem,
     ) -> Result<ExecutionResult, RootNotFound> {
         // spec: https://casperlabs.atlassian.net/wiki/spaces/EN/pages/123404576/Payment+code+execution+specification
@@ -1131,6 +1695,40 @@ where
             Ok(Some(tracking_copy)) => Rc::new(RefCell::new(tracking_copy)),
         };
 
+        Ok(self.deploy_with_tracking_copy(
+            correlation_id,
+            executor,
+            preprocessor,
+            protocol_version,
+            protocol_data,
+            blocktime,
+            block_info,
+            deploy_item,
+            tracking_copy,
+        ))
+    }
+
+    /// Executes `deploy_item` against `tracking_copy`.
+    ///
+    /// Payment is executed directly against `tracking_copy`, while session code and finalization
+    /// are executed against forks of it.  The returned result holds the combined effect.
+    #[allow(clippy::too_many_arguments)]
+    fn deploy_with_tracking_copy<R>(
+        &self,
+        correlation_id: CorrelationId,
+        executor: &Executor,
+        preprocessor: &Preprocessor,
+        protocol_version: ProtocolVersion,
+        protocol_data: ProtocolData,
+        blocktime: BlockTime,
+        block_info: BlockInfo,
+        deploy_item: DeployItem,
+        tracking_copy: Rc<RefCell<TrackingCopy<R>>>,
+    ) -> ExecutionResult
+    where
+        R: StateReader<Key, StoredValue>,
+        R::Error: Into<execution::Error>,
+    {
         let base_key = Key::Account(deploy_item.address);
 
         // Get addr bytes from `address` (which is actually a Key)
@@ -1138,9 +1736,7 @@ where
         let account_public_key = match base_key.into_account() {
             Some(account_addr) => account_addr,
             None => {
-                return Ok(ExecutionResult::precondition_failure(
-                    error::Error::Authorization,
-                ));
+                return ExecutionResult::precondition_failure(error::Error::Authorization);
             }
         };
 
@@ -1155,7 +1751,7 @@ where
             Rc::clone(&tracking_copy),
         ) {
             Ok(account) => account,
-            Err(e) => return Ok(ExecutionResult::precondition_failure(e)),
+            Err(e) => return ExecutionResult::precondition_failure(e),
         };
 
         let session = deploy_item.session;
@@ -1175,7 +1771,7 @@ where
         ) {
             Ok(module) => module,
             Err(error) => {
-                return Ok(ExecutionResult::precondition_failure(error));
+                return ExecutionResult::precondition_failure(error);
             }
         };
 
@@ -1189,7 +1785,7 @@ where
         {
             Ok(contract) => contract,
             Err(error) => {
-                return Ok(ExecutionResult::precondition_failure(error.into()));
+                return ExecutionResult::precondition_failure(error.into());
             }
         };
 
@@ -1203,7 +1799,7 @@ where
             ) {
                 Ok(contract) => contract,
                 Err(error) => {
-                    return Ok(ExecutionResult::precondition_failure(error.into()));
+                    return ExecutionResult::precondition_failure(error.into());
                 }
             };
 
@@ -1223,7 +1819,7 @@ where
         {
             Ok(contract) => contract,
             Err(error) => {
-                return Ok(ExecutionResult::precondition_failure(error.into()));
+                return ExecutionResult::precondition_failure(error.into());
             }
         };
 
@@ -1235,7 +1831,7 @@ where
         ) {
             Ok(module) => module,
             Err(error) => {
-                return Ok(ExecutionResult::precondition_failure(error.into()));
+                return ExecutionResult::precondition_failure(error.into());
             }
         };
 
@@ -1255,7 +1851,7 @@ where
             {
                 Ok(key) => key,
                 Err(error) => {
-                    return Ok(ExecutionResult::precondition_failure(error.into()));
+                    return ExecutionResult::precondition_failure(error.into());
                 }
             }
         };
@@ -1267,7 +1863,7 @@ where
             .get_purse_balance(correlation_id, account_main_purse_balance_key)
         {
             Ok(balance) => balance,
-            Err(error) => return Ok(ExecutionResult::precondition_failure(error.into())),
+            Err(error) => return ExecutionResult::precondition_failure(error.into()),
         };
 
         let max_payment_cost: Motes = Motes::new(U512::from(MAX_PAYMENT));
@@ -1275,9 +1871,7 @@ where
         // Enforce minimum main purse balance validation
         // validation_spec_5: account main purse minimum balance
         if account_main_purse_balance < max_payment_cost {
-            return Ok(ExecutionResult::precondition_failure(
-                Error::InsufficientPayment,
-            ));
+            return ExecutionResult::precondition_failure(Error::InsufficientPayment);
         }
 
         // Finalization is executed by system account (currently genesis account)
@@ -1313,11 +1907,11 @@ where
                     match self.state.get_protocol_data(protocol_version) {
                         Ok(Some(protocol_data)) => protocol_data.standard_payment(),
                         Ok(None) => {
-                            return Ok(ExecutionResult::precondition_failure(
+                            return ExecutionResult::precondition_failure(
                                 Error::InvalidProtocolVersion(protocol_version),
-                            ));
+                            );
                         }
-                        Err(_) => return Ok(ExecutionResult::precondition_failure(Error::Deploy)),
+                        Err(_) => return ExecutionResult::precondition_failure(Error::Deploy),
                     };
 
                 // if "use-system-contracts" is false, "do_nothing" wasm is returned
@@ -1346,7 +1940,7 @@ where
             let payment_module = match maybe_payment_module {
                 Ok(module) => module,
                 Err(error) => {
-                    return Ok(ExecutionResult::precondition_failure(error));
+                    return ExecutionResult::precondition_failure(error);
                 }
             };
 
@@ -1390,7 +1984,7 @@ where
                 Err(e) => {
                     let exec_err: crate::execution::Error = e.into();
                     warn!("Unable to deserialize arguments: {:?}", exec_err);
-                    return Ok(ExecutionResult::precondition_failure(exec_err.into()));
+                    return ExecutionResult::precondition_failure(exec_err.into());
                 }
             };
 
@@ -1406,6 +2000,7 @@ where
                     &mut payment_named_keys,
                     authorization_keys.clone(),
                     blocktime,
+                    block_info,
                     deploy_hash,
                     pay_gas_limit,
                     protocol_version,
@@ -1437,6 +2032,7 @@ where
                     &account,
                     authorization_keys.clone(),
                     blocktime,
+                    block_info,
                     deploy_hash,
                     pay_gas_limit,
                     hash_address_generator,
@@ -1450,7 +2046,7 @@ where
                 ) {
                     Ok((_instance, runtime)) => runtime,
                     Err(error) => {
-                        return Ok(ExecutionResult::precondition_failure(Error::Exec(error)));
+                        return ExecutionResult::precondition_failure(Error::Exec(error));
                     }
                 };
 
@@ -1460,11 +2056,15 @@ where
                     Ok(()) => ExecutionResult::Success {
                         effect: runtime.context().effect(),
                         cost: runtime.context().gas_counter(),
+                        storage_cost: runtime.context().storage_gas_counter(),
+                        events: runtime.context().events().to_vec(),
                     },
                     Err(error) => ExecutionResult::Failure {
                         error: error.into(),
                         effect: effects_snapshot,
                         cost: runtime.context().gas_counter(),
+                        storage_cost: runtime.context().storage_gas_counter(),
+                        events: Vec::new(),
                     },
                 }
             }
@@ -1481,7 +2081,7 @@ where
             let payment_purse_key: Key =
                 match proof_of_stake_contract.named_keys().get(POS_PAYMENT_PURSE) {
                     Some(key) => *key,
-                    None => return Ok(ExecutionResult::precondition_failure(Error::Deploy)),
+                    None => return ExecutionResult::precondition_failure(Error::Deploy),
                 };
 
             let purse_balance_key = match tracking_copy
@@ -1490,7 +2090,7 @@ where
             {
                 Ok(key) => key,
                 Err(error) => {
-                    return Ok(ExecutionResult::precondition_failure(error.into()));
+                    return ExecutionResult::precondition_failure(error.into());
                 }
             };
 
@@ -1500,7 +2100,7 @@ where
             {
                 Ok(balance) => balance,
                 Err(error) => {
-                    return Ok(ExecutionResult::precondition_failure(error.into()));
+                    return ExecutionResult::precondition_failure(error.into());
                 }
             }
         };
@@ -1515,7 +2115,7 @@ where
                     match proof_of_stake_contract.named_keys().get(POS_REWARDS_PURSE) {
                         Some(key) => *key,
                         None => {
-                            return Ok(ExecutionResult::precondition_failure(Error::Deploy));
+                            return ExecutionResult::precondition_failure(Error::Deploy);
                         }
                     };
 
@@ -1525,7 +2125,7 @@ where
                 {
                     Ok(key) => key,
                     Err(error) => {
-                        return Ok(ExecutionResult::precondition_failure(error.into()));
+                        return ExecutionResult::precondition_failure(error.into());
                     }
                 }
             };
@@ -1534,13 +2134,13 @@ where
                 ForcedTransferResult::InsufficientPayment => Error::InsufficientPayment,
                 ForcedTransferResult::PaymentFailure => payment_result.take_error().unwrap(),
             };
-            return Ok(ExecutionResult::new_payment_code_error(
+            return ExecutionResult::new_payment_code_error(
                 error,
                 max_payment_cost,
                 account_main_purse_balance,
                 account_main_purse_balance_key,
                 rewards_purse_balance_key,
-            ));
+            );
         }
 
         execution_result_builder.set_payment_execution_result(payment_result);
@@ -1587,7 +2187,7 @@ where
             Err(e) => {
                 let exec_err: crate::execution::Error = e.into();
                 warn!("Unable to deserialize session arguments: {:?}", exec_err);
-                return Ok(ExecutionResult::precondition_failure(exec_err.into()));
+                return ExecutionResult::precondition_failure(exec_err.into());
             }
         };
         let session_result = {
@@ -1609,6 +2209,7 @@ where
                 &mut session_named_keys,
                 authorization_keys.clone(),
                 blocktime,
+                block_info,
                 deploy_hash,
                 session_gas_limit,
                 protocol_version,
@@ -1657,7 +2258,7 @@ where
                 .get_contract(correlation_id, proof_of_stake_hash)
             {
                 Ok(info) => info,
-                Err(error) => return Ok(ExecutionResult::precondition_failure(error.into())),
+                Err(error) => return ExecutionResult::precondition_failure(error.into()),
             };
 
             let mut proof_of_stake_keys = proof_of_stake_contract.named_keys().to_owned();
@@ -1675,6 +2276,7 @@ where
                 &system_account,
                 authorization_keys,
                 blocktime,
+                block_info,
                 deploy_hash,
                 gas_limit,
                 protocol_version,
@@ -1696,7 +2298,7 @@ where
         // NOTE: payment_code_spec_5_a is enforced in execution_result_builder.build()
         // payment_code_spec_6: return properly combined set of transforms and
         // appropriate error
-        Ok(ret)
+        ret
     }
 
     pub fn apply_effect(
@@ -1722,6 +2324,35 @@ where
         }
     }
 
+    /// Applies each set of effects on top of the post state of the previous one, starting from
+    /// `pre_state_hash`.  Either every set is committed, or none are.
+    pub fn apply_many_effects(
+        &self,
+        correlation_id: CorrelationId,
+        protocol_version: ProtocolVersion,
+        pre_state_hash: Blake2bHash,
+        effects: Vec<AdditiveMap<Key, Transform>>,
+    ) -> Result<CommitManyResult, Error>
+    where
+        Error: From<S::Error>,
+    {
+        match self
+            .state
+            .commit_many(correlation_id, pre_state_hash, effects)?
+        {
+            CommitManyResult::Success { state_roots, .. } => {
+                let post_state_hash = state_roots.last().copied().unwrap_or(pre_state_hash);
+                let bonded_validators =
+                    self.get_bonded_validators(correlation_id, protocol_version, post_state_hash)?;
+                Ok(CommitManyResult::Success {
+                    state_roots,
+                    bonded_validators,
+                })
+            }
+            commit_many_result => Ok(commit_many_result),
+        }
+    }
+
     /// Calculates bonded validators at `root_hash` state.
     ///
     /// Should only be called with a valid root hash after a successful call to
@@ -1,4 +1,5 @@
 use engine_shared::{newtypes::Blake2bHash, stored_value::StoredValue};
+use engine_storage::trie::TrieMerkleProof;
 use types::Key;
 
 use crate::tracking_copy::TrackingCopyQueryResult;
@@ -7,7 +8,11 @@ pub enum QueryResult {
     RootNotFound,
     ValueNotFound(String),
     CircularReference(String),
-    Success(StoredValue),
+    Success {
+        value: StoredValue,
+        /// Proofs of the values read while following the query path, if requested.
+        proofs: Vec<TrieMerkleProof<Key, StoredValue>>,
+    },
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -15,14 +20,16 @@ pub struct QueryRequest {
     state_hash: Blake2bHash,
     key: Key,
     path: Vec<String>,
+    include_proofs: bool,
 }
 
 impl QueryRequest {
-    pub fn new(state_hash: Blake2bHash, key: Key, path: Vec<String>) -> Self {
+    pub fn new(state_hash: Blake2bHash, key: Key, path: Vec<String>, include_proofs: bool) -> Self {
         QueryRequest {
             state_hash,
             key,
             path,
+            include_proofs,
         }
     }
 
@@ -37,6 +44,10 @@ impl QueryRequest {
     pub fn path(&self) -> &[String] {
         &self.path
     }
+
+    pub fn include_proofs(&self) -> bool {
+        self.include_proofs
+    }
 }
 
 impl From<TrackingCopyQueryResult> for QueryResult {
@@ -46,7 +57,10 @@ impl From<TrackingCopyQueryResult> for QueryResult {
             TrackingCopyQueryResult::CircularReference(message) => {
                 QueryResult::CircularReference(message)
             }
-            TrackingCopyQueryResult::Success(value) => QueryResult::Success(value),
+            TrackingCopyQueryResult::Success(value) => QueryResult::Success {
+                value,
+                proofs: Vec::new(),
+            },
         }
     }
 }
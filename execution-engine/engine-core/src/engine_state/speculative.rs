@@ -0,0 +1,224 @@
+//! Speculative execution of deploys against the parent state of an
+//! [`ExecuteRequest`](super::execute_request::ExecuteRequest).
+//!
+//! A deploy is deterministic given the values it reads, so executing it against the parent state
+//! gives the same result as executing it after the deploys before it, as long as none of those
+//! deploys changed a value it read.  Adds commute, so a value which a deploy only added to may be
+//! changed by the deploys before it, as long as they don't change its type.
+
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+};
+
+use engine_shared::{newtypes::CorrelationId, stored_value::StoredValue};
+use engine_storage::{global_state::StateReader, trie::TrieMerkleProof};
+use types::Key;
+
+use crate::{
+    engine_state::{execution_result::ExecutionResult, op::Op},
+    tracking_copy::TrackingCopy,
+};
+
+/// The values read by a deploy from the state it was executed against.
+pub(crate) type ReadSet = HashMap<Key, Option<StoredValue>>;
+
+/// A [`StateReader`] which records the values read through it.
+pub(crate) struct ReadRecorder<R> {
+    reader: R,
+    reads: RefCell<ReadSet>,
+}
+
+impl<R> ReadRecorder<R> {
+    pub fn new(reader: R) -> Self {
+        ReadRecorder {
+            reader,
+            reads: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// Returns the values read so far, and stops tracking them.
+    pub fn take_reads(&self) -> ReadSet {
+        self.reads.replace(HashMap::new())
+    }
+}
+
+impl<R: StateReader<Key, StoredValue>> StateReader<Key, StoredValue> for ReadRecorder<R> {
+    type Error = R::Error;
+
+    fn read(
+        &self,
+        correlation_id: CorrelationId,
+        key: &Key,
+    ) -> Result<Option<StoredValue>, Self::Error> {
+        let value = self.reader.read(correlation_id, key)?;
+        self.reads
+            .borrow_mut()
+            .entry(*key)
+            .or_insert_with(|| value.clone());
+        Ok(value)
+    }
+
+    fn read_with_proof(
+        &self,
+        correlation_id: CorrelationId,
+        key: &Key,
+    ) -> Result<Option<TrieMerkleProof<Key, StoredValue>>, Self::Error> {
+        self.read(correlation_id, key)?;
+        self.reader.read_with_proof(correlation_id, key)
+    }
+}
+
+/// The result of executing a deploy against the parent state, along with the values it read.
+pub(crate) struct SpeculativeExecution {
+    pub execution_result: ExecutionResult,
+    pub reads: ReadSet,
+}
+
+impl SpeculativeExecution {
+    /// Returns `true` if any of the values read by the deploy under `modified_keys` differ in
+    /// `tracking_copy`, in which case the deploy has to be executed again.
+    ///
+    /// A value which the deploy only added to is read to be added to, but its result doesn't
+    /// depend on it unless it was created, removed or given another type. In particular, the
+    /// storage gas of an add is charged for the added value, not for the sum.
+    pub fn is_stale<R: StateReader<Key, StoredValue>>(
+        &self,
+        correlation_id: CorrelationId,
+        tracking_copy: &mut TrackingCopy<R>,
+        modified_keys: &HashSet<Key>,
+    ) -> Result<bool, R::Error> {
+        let ops = &self.execution_result.effect().ops;
+        for (key, value) in &self.reads {
+            if !modified_keys.contains(key) {
+                continue;
+            }
+            let current_value = tracking_copy.get(correlation_id, key)?;
+            let is_changed = match ops.get(key) {
+                Some(Op::Add) => {
+                    value.as_ref().map(StoredValue::type_name)
+                        != current_value.as_ref().map(StoredValue::type_name)
+                }
+                _ => current_value != *value,
+            };
+            if is_changed {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, fmt::Debug, iter};
+
+    use assert_matches::assert_matches;
+
+    use engine_shared::{gas::Gas, newtypes::CorrelationId, stored_value::StoredValue};
+    use engine_storage::global_state::{
+        in_memory::{InMemoryGlobalState, InMemoryGlobalStateView},
+        StateProvider, StateReader,
+    };
+    use types::{CLValue, Key, U512};
+
+    use super::{ReadRecorder, SpeculativeExecution};
+    use crate::{
+        engine_state::execution_result::ExecutionResult,
+        tracking_copy::{AddResult, TrackingCopy},
+    };
+
+    const KEY: Key = Key::Hash([1; 32]);
+
+    fn u512_value(value: u64) -> StoredValue {
+        StoredValue::CLValue(CLValue::from_t(U512::from(value)).unwrap())
+    }
+
+    /// Returns the state holding `1` under `KEY`.
+    fn parent_state() -> InMemoryGlobalStateView {
+        let correlation_id = CorrelationId::new();
+        let (state, root_hash) =
+            InMemoryGlobalState::from_pairs(correlation_id, &[(KEY, u512_value(1))]).unwrap();
+        state.checkout(root_hash).unwrap().unwrap()
+    }
+
+    /// Executes `deploy` against the parent state, as a deploy executed speculatively.
+    fn execute_speculatively<F>(deploy: F) -> SpeculativeExecution
+    where
+        F: FnOnce(&mut TrackingCopy<ReadRecorder<InMemoryGlobalStateView>>),
+    {
+        let mut tracking_copy = TrackingCopy::new(ReadRecorder::new(parent_state()));
+        deploy(&mut tracking_copy);
+        SpeculativeExecution {
+            execution_result: ExecutionResult::Success {
+                effect: tracking_copy.effect(),
+                cost: Gas::default(),
+                storage_cost: Gas::default(),
+                events: Vec::new(),
+            },
+            reads: tracking_copy.reader().take_reads(),
+        }
+    }
+
+    /// Returns whether `speculative_execution` is stale once `KEY` has been changed by `deploy`.
+    fn is_stale_after<F>(speculative_execution: &SpeculativeExecution, deploy: F) -> bool
+    where
+        F: FnOnce(&mut TrackingCopy<InMemoryGlobalStateView>),
+    {
+        let mut tracking_copy = TrackingCopy::new(parent_state());
+        deploy(&mut tracking_copy);
+        let modified_keys: HashSet<Key> = iter::once(KEY).collect();
+        speculative_execution
+            .is_stale(CorrelationId::new(), &mut tracking_copy, &modified_keys)
+            .unwrap()
+    }
+
+    fn add<R>(tracking_copy: &mut TrackingCopy<R>, value: u64)
+    where
+        R: StateReader<Key, StoredValue>,
+        R::Error: Debug,
+    {
+        let result = tracking_copy
+            .add(CorrelationId::new(), KEY, u512_value(value))
+            .unwrap();
+        assert_matches!(result, AddResult::Success);
+    }
+
+    #[test]
+    fn should_not_be_stale_after_add_to_value_only_added_to() {
+        let speculative_execution = execute_speculatively(|tracking_copy| add(tracking_copy, 2));
+        assert!(!is_stale_after(&speculative_execution, |tracking_copy| {
+            add(tracking_copy, 3)
+        }));
+    }
+
+    #[test]
+    fn should_be_stale_after_add_to_value_read() {
+        let speculative_execution = execute_speculatively(|tracking_copy| {
+            tracking_copy.read(CorrelationId::new(), &KEY).unwrap();
+            add(tracking_copy, 2)
+        });
+        assert!(is_stale_after(&speculative_execution, |tracking_copy| {
+            add(tracking_copy, 3)
+        }));
+    }
+
+    #[test]
+    fn should_be_stale_after_type_change_of_value_only_added_to() {
+        let speculative_execution = execute_speculatively(|tracking_copy| add(tracking_copy, 2));
+        assert!(is_stale_after(&speculative_execution, |tracking_copy| {
+            let value = StoredValue::CLValue(CLValue::from_t("one".to_string()).unwrap());
+            tracking_copy.write(KEY, value)
+        }));
+    }
+
+    #[test]
+    fn should_not_be_stale_after_write_of_value_read_again() {
+        let speculative_execution = execute_speculatively(|tracking_copy| {
+            tracking_copy.read(CorrelationId::new(), &KEY).unwrap();
+        });
+        assert!(!is_stale_after(&speculative_execution, |tracking_copy| {
+            tracking_copy.write(KEY, u512_value(1))
+        }));
+    }
+}
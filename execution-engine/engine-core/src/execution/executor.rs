@@ -16,12 +16,14 @@ use types::{
 
 use crate::{
     engine_state::{
-        execution_effect::ExecutionEffect, execution_result::ExecutionResult,
-        system_contract_cache::SystemContractCache, EngineConfig,
+        block_info::BlockInfo, execution_effect::ExecutionEffect,
+        execution_result::ExecutionResult, system_contract_cache::SystemContractCache,
+        EngineConfig,
     },
-    execution::{address_generator::AddressGenerator, Error},
+    execution::{address_generator::AddressGenerator, Error, RandomBytesGenerator},
     runtime::{
         extract_access_rights_from_keys, extract_access_rights_from_urefs, instance_and_memory,
+        trace::{ExecutionTrace, HostCallTracer},
         Runtime,
     },
     runtime_context::{self, RuntimeContext},
@@ -41,7 +43,7 @@ macro_rules! on_fail_charge {
             }
         }
     };
-    ($fn:expr, $cost:expr) => {
+    ($fn:expr, $cost:expr, $storage_cost:expr) => {
         match $fn {
             Ok(res) => res,
             Err(e) => {
@@ -51,11 +53,13 @@ macro_rules! on_fail_charge {
                     error: exec_err.into(),
                     effect: Default::default(),
                     cost: $cost,
+                    storage_cost: $storage_cost,
+                    events: Vec::new(),
                 };
             }
         }
     };
-    ($fn:expr, $cost:expr, $effect:expr) => {
+    ($fn:expr, $cost:expr, $storage_cost:expr, $effect:expr) => {
         match $fn {
             Ok(res) => res,
             Err(e) => {
@@ -65,6 +69,8 @@ macro_rules! on_fail_charge {
                     error: exec_err.into(),
                     effect: $effect,
                     cost: $cost,
+                    storage_cost: $storage_cost,
+                    events: Vec::new(),
                 };
             }
         }
@@ -73,18 +79,40 @@ macro_rules! on_fail_charge {
 
 pub struct Executor {
     config: EngineConfig,
+    tracer: Option<Rc<RefCell<HostCallTracer>>>,
 }
 
 #[allow(clippy::too_many_arguments)]
 impl Executor {
     pub fn new(config: EngineConfig) -> Self {
-        Executor { config }
+        Executor {
+            config,
+            tracer: None,
+        }
+    }
+
+    /// Constructs an `Executor` which records the host functions called by the contracts it
+    /// executes.
+    pub fn with_tracing(config: EngineConfig) -> Self {
+        Executor {
+            config,
+            tracer: Some(Rc::new(RefCell::new(HostCallTracer::new()))),
+        }
     }
 
     pub fn config(&self) -> EngineConfig {
         self.config
     }
 
+    /// Returns the host calls recorded since the last call to `take_trace`, or an empty trace if
+    /// tracing is disabled.
+    pub fn take_trace(&self) -> ExecutionTrace {
+        match &self.tracer {
+            Some(tracer) => tracer.borrow_mut().take_host_calls(),
+            None => ExecutionTrace::new(),
+        }
+    }
+
     pub fn exec<R>(
         &self,
         module: Module,
@@ -95,6 +123,7 @@ impl Executor {
         named_keys: &mut NamedKeys,
         authorization_keys: BTreeSet<AccountHash>,
         blocktime: BlockTime,
+        block_info: BlockInfo,
         deploy_hash: [u8; 32],
         gas_limit: Gas,
         protocol_version: ProtocolVersion,
@@ -129,6 +158,10 @@ impl Executor {
             let generator = AddressGenerator::new(&deploy_hash, phase);
             Rc::new(RefCell::new(generator))
         };
+        let random_bytes_generator = {
+            let generator = RandomBytesGenerator::new(&deploy_hash, phase, blocktime);
+            Rc::new(RefCell::new(generator))
+        };
         let gas_counter: Gas = Gas::default();
 
         // Snapshot of effects before execution, so in case of error
@@ -145,18 +178,27 @@ impl Executor {
             &account,
             base_key,
             blocktime,
+            block_info,
             deploy_hash,
             gas_limit,
             gas_counter,
             hash_address_generator,
             uref_address_generator,
+            random_bytes_generator,
             protocol_version,
             correlation_id,
             phase,
             protocol_data,
         );
 
-        let mut runtime = Runtime::new(self.config, system_contract_cache, memory, module, context);
+        let mut runtime = Runtime::new(
+            self.config,
+            system_contract_cache,
+            memory,
+            module,
+            context,
+            self.tracer.clone(),
+        );
 
         let accounts_access_rights = {
             let keys: Vec<Key> = account.named_keys().values().cloned().collect();
@@ -182,6 +224,8 @@ impl Executor {
                         return ExecutionResult::Success {
                             effect: runtime.context().effect(),
                             cost: runtime.context().gas_counter(),
+                            storage_cost: runtime.context().storage_gas_counter(),
+                            events: runtime.context().events().to_vec(),
                         };
                     }
                     Err(error) => {
@@ -189,6 +233,8 @@ impl Executor {
                             error: error.into(),
                             effect: effects_snapshot,
                             cost: runtime.context().gas_counter(),
+                            storage_cost: runtime.context().storage_gas_counter(),
+                            events: Vec::new(),
                         };
                     }
                 }
@@ -204,6 +250,8 @@ impl Executor {
                         return ExecutionResult::Success {
                             effect: runtime.context().effect(),
                             cost: runtime.context().gas_counter(),
+                            storage_cost: runtime.context().storage_gas_counter(),
+                            events: runtime.context().events().to_vec(),
                         };
                     }
                     Err(error) => {
@@ -211,6 +259,8 @@ impl Executor {
                             error: error.into(),
                             effect: effects_snapshot,
                             cost: runtime.context().gas_counter(),
+                            storage_cost: runtime.context().storage_gas_counter(),
+                            events: Vec::new(),
                         };
                     }
                 }
@@ -220,12 +270,15 @@ impl Executor {
         on_fail_charge!(
             instance.invoke_export(entry_point_name, &[], &mut runtime),
             runtime.context().gas_counter(),
+            runtime.context().storage_gas_counter(),
             effects_snapshot
         );
 
         ExecutionResult::Success {
             effect: runtime.context().effect(),
             cost: runtime.context().gas_counter(),
+            storage_cost: runtime.context().storage_gas_counter(),
+            events: runtime.context().events().to_vec(),
         }
     }
 
@@ -240,6 +293,7 @@ impl Executor {
         account: &Account,
         authorization_keys: BTreeSet<AccountHash>,
         blocktime: BlockTime,
+        block_info: BlockInfo,
         deploy_hash: [u8; 32],
         gas_limit: Gas,
         protocol_version: ProtocolVersion,
@@ -298,6 +352,7 @@ impl Executor {
                 account,
                 authorization_keys,
                 blocktime,
+                block_info,
                 deploy_hash,
                 gas_limit,
                 hash_address_generator,
@@ -313,6 +368,8 @@ impl Executor {
                 ExecutionResult::Failure {
                     effect: effect_snapshot.clone(),
                     cost: gas_counter,
+                    storage_cost: Gas::default(),
+                    events: Vec::new(),
                     error: e.into(),
                 }
                 .take_without_ret::<T>();
@@ -374,6 +431,7 @@ impl Executor {
         let runtime_context = runtime.context();
 
         let cost = runtime_context.gas_counter();
+        let storage_cost = runtime_context.storage_gas_counter();
 
         let effect = if revert_effect {
             effect_snapshot
@@ -386,8 +444,15 @@ impl Executor {
                 error: error.into(),
                 effect,
                 cost,
+                storage_cost,
+                events: Vec::new(),
+            },
+            None => ExecutionResult::Success {
+                effect,
+                cost,
+                storage_cost,
+                events: runtime.context().events().to_vec(),
             },
-            None => ExecutionResult::Success { effect, cost },
         };
 
         match maybe_ret {
@@ -406,6 +471,7 @@ impl Executor {
         account: &mut Account,
         authorization_keys: BTreeSet<AccountHash>,
         blocktime: BlockTime,
+        block_info: BlockInfo,
         deploy_hash: [u8; 32],
         gas_limit: Gas,
         hash_address_generator: Rc<RefCell<AddressGenerator>>,
@@ -435,6 +501,7 @@ impl Executor {
             account,
             authorization_keys,
             blocktime,
+            block_info,
             deploy_hash,
             gas_limit,
             hash_address_generator,
@@ -491,6 +558,7 @@ impl Executor {
         account: &'a Account,
         authorization_keys: BTreeSet<AccountHash>,
         blocktime: BlockTime,
+        block_info: BlockInfo,
         deploy_hash: [u8; 32],
         gas_limit: Gas,
         hash_address_generator: Rc<RefCell<AddressGenerator>>,
@@ -513,6 +581,10 @@ impl Executor {
         };
 
         let gas_counter = Gas::default();
+        let random_bytes_generator = {
+            let generator = RandomBytesGenerator::new(&deploy_hash, phase, blocktime);
+            Rc::new(RefCell::new(generator))
+        };
 
         let runtime_context = RuntimeContext::new(
             tracking_copy,
@@ -524,11 +596,13 @@ impl Executor {
             account,
             base_key,
             blocktime,
+            block_info,
             deploy_hash,
             gas_limit,
             gas_counter,
             hash_address_generator,
             uref_address_generator,
+            random_bytes_generator,
             protocol_version,
             correlation_id,
             phase,
@@ -543,6 +617,7 @@ impl Executor {
             memory,
             module,
             runtime_context,
+            self.tracer.clone(),
         );
 
         Ok((instance, runtime))
@@ -602,12 +677,16 @@ impl DirectSystemContractCall {
                 Ok(ret) => ExecutionResult::Success {
                     effect: runtime.context().effect(),
                     cost: runtime.context().gas_counter(),
+                    storage_cost: runtime.context().storage_gas_counter(),
+                    events: runtime.context().events().to_vec(),
                 }
                 .take_with_ret(ret),
                 Err(error) => ExecutionResult::Failure {
                     error: Error::CLValue(error).into(),
                     effect: execution_effect,
                     cost: runtime.context().gas_counter(),
+                    storage_cost: runtime.context().storage_gas_counter(),
+                    events: Vec::new(),
                 }
                 .take_without_ret(),
             },
@@ -615,6 +694,8 @@ impl DirectSystemContractCall {
                 error: error.into(),
                 effect: execution_effect,
                 cost: runtime.context().gas_counter(),
+                storage_cost: runtime.context().storage_gas_counter(),
+                events: Vec::new(),
             }
             .take_without_ret(),
         }
@@ -2,6 +2,7 @@ mod address_generator;
 mod error;
 #[macro_use]
 mod executor;
+mod random_bytes_generator;
 #[cfg(test)]
 mod tests;
 
@@ -9,4 +10,5 @@ pub use self::{
     address_generator::{AddressGenerator, AddressGeneratorBuilder},
     error::Error,
     executor::{DirectSystemContractCall, Executor},
+    random_bytes_generator::RandomBytesGenerator,
 };
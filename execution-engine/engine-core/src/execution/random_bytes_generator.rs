@@ -0,0 +1,100 @@
+use blake2::{
+    digest::{Input, VariableOutput},
+    VarBlake2b,
+};
+use rand::{RngCore, SeedableRng};
+use rand_chacha::ChaChaRng;
+
+use types::{BlockTime, Phase};
+
+const SEED_LENGTH: usize = 32;
+/// Mixed into the seed so that the stream never coincides with the one used by an
+/// [`AddressGenerator`](super::AddressGenerator) seeded with the same deploy hash and phase.
+const DOMAIN_SEPARATOR: &[u8] = b"random_bytes";
+
+/// A [`RandomBytesGenerator`] generates the random bytes returned to contracts by the
+/// `random_bytes` host function.
+///
+/// It is kept separate from the [`AddressGenerator`](super::AddressGenerator)s so that drawing
+/// random bytes doesn't change the addresses of [`URef`](types::URef)s or contracts created
+/// afterwards.
+pub struct RandomBytesGenerator(ChaChaRng);
+
+impl RandomBytesGenerator {
+    /// Creates a [`RandomBytesGenerator`] from a 32-byte deploy hash, [`Phase`] and [`BlockTime`].
+    pub fn new(deploy_hash: &[u8], phase: Phase, blocktime: BlockTime) -> RandomBytesGenerator {
+        let blocktime: u64 = blocktime.into();
+        let mut seed: [u8; SEED_LENGTH] = [0u8; SEED_LENGTH];
+        let mut hasher = VarBlake2b::new(SEED_LENGTH).unwrap();
+        hasher.input(DOMAIN_SEPARATOR);
+        hasher.input(deploy_hash);
+        hasher.input([phase as u8]);
+        hasher.input(blocktime.to_le_bytes());
+        hasher.variable_result(|hash| seed.clone_from_slice(hash));
+        RandomBytesGenerator(ChaChaRng::from_seed(seed))
+    }
+
+    pub fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.0.fill_bytes(dest)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use types::{BlockTime, Phase};
+
+    use super::RandomBytesGenerator;
+    use crate::execution::AddressGenerator;
+
+    const DEPLOY_HASH_1: [u8; 32] = [1u8; 32];
+    const DEPLOY_HASH_2: [u8; 32] = [2u8; 32];
+    const BLOCKTIME: u64 = 42;
+    const LEN: usize = 32;
+
+    fn random_bytes(rbg: &mut RandomBytesGenerator) -> [u8; LEN] {
+        let mut buff = [0u8; LEN];
+        rbg.fill_bytes(&mut buff);
+        buff
+    }
+
+    #[test]
+    fn should_generate_same_bytes_for_same_seed() {
+        let mut rbg_a =
+            RandomBytesGenerator::new(&DEPLOY_HASH_1, Phase::Session, BlockTime::new(BLOCKTIME));
+        let mut rbg_b =
+            RandomBytesGenerator::new(&DEPLOY_HASH_1, Phase::Session, BlockTime::new(BLOCKTIME));
+
+        assert_eq!(random_bytes(&mut rbg_a), random_bytes(&mut rbg_b));
+        assert_eq!(random_bytes(&mut rbg_a), random_bytes(&mut rbg_b));
+    }
+
+    #[test]
+    fn should_generate_different_bytes_for_different_seeds() {
+        let mut rbg =
+            RandomBytesGenerator::new(&DEPLOY_HASH_1, Phase::Session, BlockTime::new(BLOCKTIME));
+        let random = random_bytes(&mut rbg);
+
+        let mut rbg_hash =
+            RandomBytesGenerator::new(&DEPLOY_HASH_2, Phase::Session, BlockTime::new(BLOCKTIME));
+        let mut rbg_phase =
+            RandomBytesGenerator::new(&DEPLOY_HASH_1, Phase::Payment, BlockTime::new(BLOCKTIME));
+        let mut rbg_blocktime = RandomBytesGenerator::new(
+            &DEPLOY_HASH_1,
+            Phase::Session,
+            BlockTime::new(BLOCKTIME + 1),
+        );
+
+        assert_ne!(random, random_bytes(&mut rbg_hash));
+        assert_ne!(random, random_bytes(&mut rbg_phase));
+        assert_ne!(random, random_bytes(&mut rbg_blocktime));
+    }
+
+    #[test]
+    fn should_not_generate_addresses() {
+        let mut rbg =
+            RandomBytesGenerator::new(&DEPLOY_HASH_1, Phase::Session, BlockTime::new(BLOCKTIME));
+        let mut address_generator = AddressGenerator::new(&DEPLOY_HASH_1, Phase::Session);
+
+        assert_ne!(random_bytes(&mut rbg), address_generator.create_address());
+    }
+}
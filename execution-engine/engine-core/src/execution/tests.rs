@@ -12,10 +12,12 @@ fn on_fail_charge_test_helper<T>(
     success_cost: Gas,
     error_cost: Gas,
 ) -> ExecutionResult {
-    let _result = on_fail_charge!(f(), error_cost);
+    let _result = on_fail_charge!(f(), error_cost, Gas::default());
     ExecutionResult::Success {
         effect: Default::default(),
         cost: success_cost,
+        storage_cost: Gas::default(),
+        events: Vec::new(),
     }
 }
 
@@ -45,7 +47,7 @@ fn on_fail_charge_err_laziness_test() {
 fn on_fail_charge_with_action() {
     let f = || {
         let input: Result<(), Error> = Err(Error::GasLimit);
-        on_fail_charge!(input, Gas::new(U512::from(456)), {
+        on_fail_charge!(input, Gas::new(U512::from(456)), Gas::default(), {
             let mut effect = ExecutionEffect::default();
 
             effect.ops.insert(Key::Hash([42u8; 32]), Op::Read);
@@ -58,6 +60,8 @@ fn on_fail_charge_with_action() {
         ExecutionResult::Success {
             effect: Default::default(),
             cost: Gas::default(),
+            storage_cost: Gas::default(),
+            events: Vec::new(),
         }
     };
     match f() {
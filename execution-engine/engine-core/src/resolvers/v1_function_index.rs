@@ -49,6 +49,16 @@ pub enum FunctionIndex {
     RemoveContractUserGroupIndex,
     ExtendContractUserGroupURefsIndex,
     RemoveContractUserGroupURefsIndex,
+    PruneFuncIndex,
+    EmitEventFuncIndex,
+    Blake2bFuncIndex,
+    Ed25519VerifyFuncIndex,
+    Secp256k1VerifyFuncIndex,
+    RandomBytesFuncIndex,
+    GetDeployHashIndex,
+    GetBlockHeightIndex,
+    GetProposerIndex,
+    GetParentStateHashIndex,
 }
 
 impl Into<usize> for FunctionIndex {
@@ -0,0 +1,122 @@
+//! Signature verification backing the cryptographic host functions.
+//!
+//! Malformed keys and signatures are treated the same as signatures which fail to verify, so a
+//! contract can't trap its caller by passing garbage to either function.
+use std::convert::TryFrom;
+
+use ed25519_dalek::Verifier;
+
+/// Verifies an Ed25519 `signature` of `message` made with the key `public_key`.
+pub(crate) fn ed25519_verify(public_key: &[u8], message: &[u8], signature: &[u8]) -> bool {
+    let public_key = match ed25519_dalek::PublicKey::from_bytes(public_key) {
+        Ok(public_key) => public_key,
+        Err(_) => return false,
+    };
+    let signature = match ed25519_dalek::Signature::try_from(signature) {
+        Ok(signature) => signature,
+        Err(_) => return false,
+    };
+    public_key.verify(message, &signature).is_ok()
+}
+
+/// Verifies a compact (64-byte) secp256k1 `signature` of the 32-byte `message_digest` made with the
+/// key `public_key`, which may be either compressed or uncompressed.
+pub(crate) fn secp256k1_verify(public_key: &[u8], message_digest: &[u8], signature: &[u8]) -> bool {
+    let public_key = match secp256k1::PublicKey::parse_slice(public_key, None) {
+        Ok(public_key) => public_key,
+        Err(_) => return false,
+    };
+    let message = match secp256k1::Message::parse_slice(message_digest) {
+        Ok(message) => message,
+        Err(_) => return false,
+    };
+    let signature = match secp256k1::Signature::parse_slice(signature) {
+        Ok(signature) => signature,
+        Err(_) => return false,
+    };
+    secp256k1::verify(&message, &signature, &public_key)
+}
+
+#[cfg(test)]
+mod tests {
+    use ed25519_dalek::Signer;
+
+    use engine_shared::newtypes::Blake2bHash;
+
+    use super::*;
+
+    const MESSAGE: &[u8] = b"message to sign";
+
+    fn ed25519_keypair() -> ed25519_dalek::Keypair {
+        let secret = ed25519_dalek::SecretKey::from_bytes(&[1u8; 32]).unwrap();
+        let public = ed25519_dalek::PublicKey::from(&secret);
+        ed25519_dalek::Keypair { secret, public }
+    }
+
+    #[test]
+    fn should_verify_ed25519_signature() {
+        let keypair = ed25519_keypair();
+        let signature = keypair.sign(MESSAGE).to_bytes();
+        let public_key = keypair.public.to_bytes();
+
+        assert!(ed25519_verify(&public_key, MESSAGE, &signature));
+        assert!(!ed25519_verify(&public_key, b"other message", &signature));
+
+        let mut bad_signature = signature;
+        bad_signature[0] ^= 1;
+        assert!(!ed25519_verify(&public_key, MESSAGE, &bad_signature));
+    }
+
+    #[test]
+    fn should_reject_malformed_ed25519_inputs() {
+        let keypair = ed25519_keypair();
+        let signature = keypair.sign(MESSAGE).to_bytes();
+        let public_key = keypair.public.to_bytes();
+
+        assert!(!ed25519_verify(&public_key[..31], MESSAGE, &signature));
+        assert!(!ed25519_verify(&public_key, MESSAGE, &signature[..63]));
+        assert!(!ed25519_verify(&[], MESSAGE, &[]));
+    }
+
+    #[test]
+    fn should_verify_secp256k1_signature() {
+        let secret_key = secp256k1::SecretKey::parse(&[2u8; 32]).unwrap();
+        let public_key = secp256k1::PublicKey::from_secret_key(&secret_key);
+        let digest = Blake2bHash::new(MESSAGE).value();
+        let (signature, _recovery_id) =
+            secp256k1::sign(&secp256k1::Message::parse(&digest), &secret_key);
+        let signature = signature.serialize();
+
+        assert!(secp256k1_verify(
+            &public_key.serialize_compressed(),
+            &digest,
+            &signature
+        ));
+        assert!(secp256k1_verify(
+            &public_key.serialize(),
+            &digest,
+            &signature
+        ));
+
+        let other_digest = Blake2bHash::new(b"other message").value();
+        assert!(!secp256k1_verify(
+            &public_key.serialize_compressed(),
+            &other_digest,
+            &signature
+        ));
+    }
+
+    #[test]
+    fn should_reject_malformed_secp256k1_inputs() {
+        let secret_key = secp256k1::SecretKey::parse(&[2u8; 32]).unwrap();
+        let public_key = secp256k1::PublicKey::from_secret_key(&secret_key).serialize_compressed();
+        let digest = Blake2bHash::new(MESSAGE).value();
+        let (signature, _recovery_id) =
+            secp256k1::sign(&secp256k1::Message::parse(&digest), &secret_key);
+        let signature = signature.serialize();
+
+        assert!(!secp256k1_verify(&public_key[..32], &digest, &signature));
+        assert!(!secp256k1_verify(&public_key, MESSAGE, &signature));
+        assert!(!secp256k1_verify(&public_key, &digest, &signature[..63]));
+    }
+}
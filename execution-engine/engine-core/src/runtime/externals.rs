@@ -27,13 +27,44 @@ where
         args: RuntimeArgs,
     ) -> Result<Option<RuntimeValue>, Trap> {
         let func = FunctionIndex::try_from(index).expect("unknown function index");
+        let trace_index = self.tracer.as_ref().map(|tracer| {
+            tracer.borrow_mut().start(
+                func,
+                args.as_ref(),
+                &self.memory,
+                self.context.gas_counter(),
+                self.context.base_key(),
+            )
+        });
+        let result = self.invoke_host_function(func, args);
+        if let (Some(tracer), Some(trace_index)) = (self.tracer.as_ref(), trace_index) {
+            tracer
+                .borrow_mut()
+                .finish(trace_index, self.context.gas_counter());
+        }
+        result
+    }
+}
+
+impl<'a, R> Runtime<'a, R>
+where
+    R: StateReader<Key, StoredValue>,
+    R::Error: Into<Error>,
+{
+    fn invoke_host_function(
+        &mut self,
+        func: FunctionIndex,
+        args: RuntimeArgs,
+    ) -> Result<Option<RuntimeValue>, Trap> {
         let mut scoped_instrumenter = ScopedInstrumenter::new(func);
+        let host_function_costs = *self.context.protocol_data().host_function_costs();
         match func {
             FunctionIndex::ReadFuncIndex => {
                 // args(0) = pointer to key in Wasm memory
                 // args(1) = size of key in Wasm memory
                 // args(2) = pointer to output size (output param)
                 let (key_ptr, key_size, output_size_ptr) = Args::parse(args)?;
+                self.charge_host_function_call(&host_function_costs.read_value, &[key_size])?;
                 let ret = self.read(key_ptr, key_size, output_size_ptr)?;
                 Ok(Some(RuntimeValue::I32(api_error::i32_from(ret))))
             }
@@ -43,6 +74,7 @@ where
                 // args(1) = size of key in Wasm memory
                 // args(2) = pointer to output size (output param)
                 let (key_ptr, key_size, output_size_ptr): (_, u32, _) = Args::parse(args)?;
+                self.charge_host_function_call(&host_function_costs.read_value_local, &[key_size])?;
                 scoped_instrumenter.add_property("key_size", key_size);
                 let ret = self.read_local(key_ptr, key_size, output_size_ptr)?;
                 Ok(Some(RuntimeValue::I32(api_error::i32_from(ret))))
@@ -52,6 +84,7 @@ where
                 // args(0) = pointer to amount of keys (output)
                 // args(1) = pointer to amount of serialized bytes (output)
                 let (total_keys_ptr, result_size_ptr) = Args::parse(args)?;
+                self.charge_host_function_call(&host_function_costs.load_named_keys, &[])?;
                 let ret = self.load_named_keys(
                     total_keys_ptr,
                     result_size_ptr,
@@ -66,6 +99,10 @@ where
                 // args(2) = pointer to value
                 // args(3) = size of value
                 let (key_ptr, key_size, value_ptr, value_size): (_, _, _, u32) = Args::parse(args)?;
+                self.charge_host_function_call(
+                    &host_function_costs.write,
+                    &[key_size, value_size],
+                )?;
                 scoped_instrumenter.add_property("value_size", value_size);
                 self.write(key_ptr, key_size, value_ptr, value_size)?;
                 Ok(None)
@@ -78,6 +115,10 @@ where
                 // args(3) = size of value
                 let (key_bytes_ptr, key_bytes_size, value_ptr, value_size): (_, u32, _, u32) =
                     Args::parse(args)?;
+                self.charge_host_function_call(
+                    &host_function_costs.write_local,
+                    &[key_bytes_size, value_size],
+                )?;
                 scoped_instrumenter.add_property("key_bytes_size", key_bytes_size);
                 scoped_instrumenter.add_property("value_size", value_size);
                 self.write_local(key_bytes_ptr, key_bytes_size, value_ptr, value_size)?;
@@ -90,6 +131,7 @@ where
                 // args(2) = pointer to value
                 // args(3) = size of value
                 let (key_ptr, key_size, value_ptr, value_size) = Args::parse(args)?;
+                self.charge_host_function_call(&host_function_costs.add, &[key_size, value_size])?;
                 self.add(key_ptr, key_size, value_ptr, value_size)?;
                 Ok(None)
             }
@@ -99,6 +141,7 @@ where
                 // args(1) = pointer to initial value
                 // args(2) = size of initial value
                 let (uref_ptr, value_ptr, value_size): (_, _, u32) = Args::parse(args)?;
+                self.charge_host_function_call(&host_function_costs.new_uref, &[value_size])?;
                 scoped_instrumenter.add_property("value_size", value_size);
                 self.new_uref(uref_ptr, value_ptr, value_size)?;
                 Ok(None)
@@ -108,6 +151,7 @@ where
                 // args(0) = pointer to value
                 // args(1) = size of value
                 let (value_ptr, value_size): (_, u32) = Args::parse(args)?;
+                self.charge_host_function_call(&host_function_costs.ret, &[value_size])?;
                 scoped_instrumenter.add_property("value_size", value_size);
                 Err(self.ret(value_ptr, value_size as usize, &mut scoped_instrumenter))
             }
@@ -125,6 +169,10 @@ where
                     u32,
                     u32,
                 ) = Args::parse(args)?;
+                self.charge_host_function_call(
+                    &host_function_costs.get_key,
+                    &[name_size, output_size],
+                )?;
                 scoped_instrumenter.add_property("name_size", name_size);
                 let ret = self.load_key(
                     name_ptr,
@@ -140,6 +188,7 @@ where
                 // args(0) = pointer to key name in Wasm memory
                 // args(1) = size of key name
                 let (name_ptr, name_size): (_, u32) = Args::parse(args)?;
+                self.charge_host_function_call(&host_function_costs.has_key, &[name_size])?;
                 scoped_instrumenter.add_property("name_size", name_size);
                 let result = self.has_key(name_ptr, name_size)?;
                 Ok(Some(RuntimeValue::I32(result)))
@@ -151,6 +200,10 @@ where
                 // args(2) = pointer to key in Wasm memory
                 // args(3) = size of key
                 let (name_ptr, name_size, key_ptr, key_size): (_, u32, _, _) = Args::parse(args)?;
+                self.charge_host_function_call(
+                    &host_function_costs.put_key,
+                    &[name_size, key_size],
+                )?;
                 scoped_instrumenter.add_property("name_size", name_size);
                 self.put_key(name_ptr, name_size, key_ptr, key_size)?;
                 Ok(None)
@@ -160,6 +213,7 @@ where
                 // args(0) = pointer to key name in Wasm memory
                 // args(1) = size of key name
                 let (name_ptr, name_size): (_, u32) = Args::parse(args)?;
+                self.charge_host_function_call(&host_function_costs.remove_key, &[name_size])?;
                 scoped_instrumenter.add_property("name_size", name_size);
                 self.remove_key(name_ptr, name_size)?;
                 Ok(None)
@@ -168,6 +222,7 @@ where
             FunctionIndex::GetCallerIndex => {
                 // args(0) = pointer where a size of serialized bytes will be stored
                 let output_size = Args::parse(args)?;
+                self.charge_host_function_call(&host_function_costs.get_caller, &[])?;
                 let ret = self.get_caller(output_size)?;
                 Ok(Some(RuntimeValue::I32(api_error::i32_from(ret))))
             }
@@ -175,6 +230,7 @@ where
             FunctionIndex::GetBlocktimeIndex => {
                 // args(0) = pointer to Wasm memory where to write.
                 let dest_ptr = Args::parse(args)?;
+                self.charge_host_function_call(&host_function_costs.get_blocktime, &[])?;
                 self.get_blocktime(dest_ptr)?;
                 Ok(None)
             }
@@ -189,6 +245,7 @@ where
                 // args(0) = pointer to value to validate
                 // args(1) = size of value
                 let (uref_ptr, uref_size) = Args::parse(args)?;
+                self.charge_host_function_call(&host_function_costs.is_valid_uref, &[uref_size])?;
 
                 Ok(Some(RuntimeValue::I32(i32::from(
                     self.is_valid_uref(uref_ptr, uref_size)?,
@@ -198,6 +255,7 @@ where
             FunctionIndex::RevertFuncIndex => {
                 // args(0) = status u32
                 let status = Args::parse(args)?;
+                self.charge_host_function_call(&host_function_costs.revert, &[])?;
 
                 Err(self.revert(status))
             }
@@ -208,6 +266,10 @@ where
                 // args(2) = weight of the key
                 let (account_hash_ptr, account_hash_size, weight_value): (u32, u32, u8) =
                     Args::parse(args)?;
+                self.charge_host_function_call(
+                    &host_function_costs.add_associated_key,
+                    &[account_hash_size],
+                )?;
                 let value = self.add_associated_key(
                     account_hash_ptr,
                     account_hash_size as usize,
@@ -220,6 +282,10 @@ where
                 // args(0) = pointer to array of bytes of an account hash
                 // args(1) = size of an account hash
                 let (account_hash_ptr, account_hash_size): (_, u32) = Args::parse(args)?;
+                self.charge_host_function_call(
+                    &host_function_costs.remove_associated_key,
+                    &[account_hash_size],
+                )?;
                 let value =
                     self.remove_associated_key(account_hash_ptr, account_hash_size as usize)?;
                 Ok(Some(RuntimeValue::I32(value)))
@@ -231,6 +297,10 @@ where
                 // args(2) = weight of the key
                 let (account_hash_ptr, account_hash_size, weight_value): (u32, u32, u8) =
                     Args::parse(args)?;
+                self.charge_host_function_call(
+                    &host_function_costs.update_associated_key,
+                    &[account_hash_size],
+                )?;
                 let value = self.update_associated_key(
                     account_hash_ptr,
                     account_hash_size as usize,
@@ -243,6 +313,7 @@ where
                 // args(0) = action type
                 // args(1) = new threshold
                 let (action_type_value, threshold_value): (u32, u8) = Args::parse(args)?;
+                self.charge_host_function_call(&host_function_costs.set_action_threshold, &[])?;
                 let value = self.set_action_threshold(action_type_value, threshold_value)?;
                 Ok(Some(RuntimeValue::I32(value)))
             }
@@ -251,6 +322,7 @@ where
                 // args(0) = pointer to array for return value
                 // args(1) = length of array for return value
                 let (dest_ptr, dest_size): (u32, u32) = Args::parse(args)?;
+                self.charge_host_function_call(&host_function_costs.create_purse, &[dest_size])?;
                 let purse = self.create_purse()?;
                 let purse_bytes = purse.into_bytes().map_err(Error::BytesRepr)?;
                 assert_eq!(dest_size, purse_bytes.len() as u32);
@@ -267,6 +339,10 @@ where
                 // args(3) = length of array of bytes of an amount
                 let (key_ptr, key_size, amount_ptr, amount_size): (u32, u32, u32, u32) =
                     Args::parse(args)?;
+                self.charge_host_function_call(
+                    &host_function_costs.transfer_to_account,
+                    &[key_size, amount_size],
+                )?;
                 let account_hash: AccountHash = {
                     let bytes = self.bytes_from_mem(key_ptr, key_size as usize)?;
                     bytesrepr::deserialize(bytes).map_err(Error::BytesRepr)?
@@ -294,6 +370,10 @@ where
                     u32,
                     u32,
                 ) = Args::parse(args)?;
+                self.charge_host_function_call(
+                    &host_function_costs.transfer_from_purse_to_account,
+                    &[source_size, key_size, amount_size],
+                )?;
 
                 let source_purse = {
                     let bytes = self.bytes_from_mem(source_ptr, source_size as usize)?;
@@ -321,6 +401,10 @@ where
                 // args(5) = length of array of bytes in Wasm memory of an amount
                 let (source_ptr, source_size, target_ptr, target_size, amount_ptr, amount_size) =
                     Args::parse(args)?;
+                self.charge_host_function_call(
+                    &host_function_costs.transfer_from_purse_to_purse,
+                    &[source_size, target_size, amount_size],
+                )?;
                 let ret = self.transfer_from_purse_to_purse(
                     source_ptr,
                     source_size,
@@ -337,6 +421,7 @@ where
                 // args(1) = length of purse
                 // args(2) = pointer to output size (output)
                 let (ptr, ptr_size, output_size_ptr): (_, u32, _) = Args::parse(args)?;
+                self.charge_host_function_call(&host_function_costs.get_balance, &[ptr_size])?;
                 let ret = self.get_balance_host_buffer(ptr, ptr_size as usize, output_size_ptr)?;
                 Ok(Some(RuntimeValue::I32(api_error::i32_from(ret))))
             }
@@ -344,6 +429,7 @@ where
             FunctionIndex::GetPhaseIndex => {
                 // args(0) = pointer to Wasm memory where to write.
                 let dest_ptr = Args::parse(args)?;
+                self.charge_host_function_call(&host_function_costs.get_phase, &[])?;
                 self.get_phase(dest_ptr)?;
                 Ok(None)
             }
@@ -353,6 +439,10 @@ where
                 // args(1) = dest pointer for storing serialized result
                 // args(2) = dest pointer size
                 let (system_contract_index, dest_ptr, dest_size) = Args::parse(args)?;
+                self.charge_host_function_call(
+                    &host_function_costs.get_system_contract,
+                    &[dest_size],
+                )?;
                 let ret = self.get_system_contract(system_contract_index, dest_ptr, dest_size)?;
                 Ok(Some(RuntimeValue::I32(api_error::i32_from(ret))))
             }
@@ -360,6 +450,7 @@ where
             FunctionIndex::GetMainPurseIndex => {
                 // args(0) = pointer to Wasm memory where to write.
                 let dest_ptr = Args::parse(args)?;
+                self.charge_host_function_call(&host_function_costs.get_main_purse, &[])?;
                 self.get_main_purse(dest_ptr)?;
                 Ok(None)
             }
@@ -367,6 +458,10 @@ where
             FunctionIndex::ReadHostBufferIndex => {
                 // args(0) = pointer to Wasm memory where to write size.
                 let (dest_ptr, dest_size, bytes_written_ptr): (_, u32, _) = Args::parse(args)?;
+                self.charge_host_function_call(
+                    &host_function_costs.read_host_buffer,
+                    &[dest_size],
+                )?;
                 scoped_instrumenter.add_property("dest_size", dest_size);
                 let ret = self.read_host_buffer(dest_ptr, dest_size as usize, bytes_written_ptr)?;
                 Ok(Some(RuntimeValue::I32(api_error::i32_from(ret))))
@@ -376,6 +471,10 @@ where
                 // args(0) = pointer to wasm memory where to write 32-byte Hash address
                 // args(1) = pointer to wasm memory where to write 32-byte access key address
                 let (hash_dest_ptr, access_dest_ptr) = Args::parse(args)?;
+                self.charge_host_function_call(
+                    &host_function_costs.create_contract_package_at_hash,
+                    &[],
+                )?;
                 let (hash_addr, access_addr) = self.create_contract_package_at_hash()?;
                 self.function_address(hash_addr, hash_dest_ptr)?;
                 self.function_address(access_addr, access_dest_ptr)?;
@@ -401,6 +500,10 @@ where
                     existing_urefs_size,
                     output_size_ptr,
                 ): (_, _, _, u32, _, _, u32, _) = Args::parse(args)?;
+                self.charge_host_function_call(
+                    &host_function_costs.create_contract_user_group,
+                    &[package_key_size, label_size, existing_urefs_size],
+                )?;
                 scoped_instrumenter
                     .add_property("existing_urefs_size", existing_urefs_size.to_string());
                 scoped_instrumenter.add_property("label_size", label_size.to_string());
@@ -443,6 +546,15 @@ where
                     output_size,
                     bytes_written_ptr,
                 ): (u32, u32, u32, u32, u32, u32, u32, u32, u32, u32) = Args::parse(args)?;
+                self.charge_host_function_call(
+                    &host_function_costs.add_contract_version,
+                    &[
+                        contract_package_hash_size,
+                        entry_points_size,
+                        named_keys_size,
+                        output_size,
+                    ],
+                )?;
 
                 scoped_instrumenter
                     .add_property("entry_points_size", entry_points_size.to_string());
@@ -472,6 +584,10 @@ where
                 // args(3) = size of contract hash in wasm memory
                 let (package_key_ptr, package_key_size, contract_hash_ptr, contract_hash_size) =
                     Args::parse(args)?;
+                self.charge_host_function_call(
+                    &host_function_costs.disable_contract_version,
+                    &[package_key_size, contract_hash_size],
+                )?;
 
                 let contract_package_hash = self.t_from_mem(package_key_ptr, package_key_size)?;
                 let contract_hash = self.t_from_mem(contract_hash_ptr, contract_hash_size)?;
@@ -498,6 +614,10 @@ where
                     args_size,
                     result_size_ptr,
                 ): (_, _, _, u32, _, u32, _) = Args::parse(args)?;
+                self.charge_host_function_call(
+                    &host_function_costs.call_contract,
+                    &[contract_hash_size, entry_point_name_size, args_size],
+                )?;
                 scoped_instrumenter
                     .add_property("entry_point_name_size", entry_point_name_size.to_string());
                 scoped_instrumenter.add_property("args_size", args_size.to_string());
@@ -542,6 +662,15 @@ where
                     args_size,
                     result_size_ptr,
                 ): (_, _, _, _, _, u32, _, u32, _) = Args::parse(args)?;
+                self.charge_host_function_call(
+                    &host_function_costs.call_versioned_contract,
+                    &[
+                        contract_package_hash_size,
+                        contract_package_size,
+                        entry_point_name_size,
+                        args_size,
+                    ],
+                )?;
 
                 scoped_instrumenter
                     .add_property("entry_point_name_size", entry_point_name_size.to_string());
@@ -582,6 +711,10 @@ where
                 // args(1) = size of name of the host runtime arg
                 // args(2) = pointer to a argument size (output)
                 let (name_ptr, name_size, size_ptr): (u32, u32, u32) = Args::parse(args)?;
+                self.charge_host_function_call(
+                    &host_function_costs.get_named_arg_size,
+                    &[name_size],
+                )?;
                 scoped_instrumenter.add_property("name_size", name_size.to_string());
                 let ret = self.get_named_arg_size(name_ptr, name_size as usize, size_ptr)?;
                 Ok(Some(RuntimeValue::I32(api_error::i32_from(ret))))
@@ -594,6 +727,10 @@ where
                 // args(3) = size of available data under output pointer
                 let (name_ptr, name_size, dest_ptr, dest_size): (u32, u32, u32, u32) =
                     Args::parse(args)?;
+                self.charge_host_function_call(
+                    &host_function_costs.get_named_arg,
+                    &[name_size, dest_size],
+                )?;
                 scoped_instrumenter.add_property("name_size", name_size.to_string());
                 scoped_instrumenter.add_property("dest_size", dest_size.to_string());
                 let ret =
@@ -608,6 +745,10 @@ where
                 // args(3) = size of serialized group label
                 let (package_key_ptr, package_key_size, label_ptr, label_size): (_, _, _, u32) =
                     Args::parse(args)?;
+                self.charge_host_function_call(
+                    &host_function_costs.remove_contract_user_group,
+                    &[package_key_size, label_size],
+                )?;
                 scoped_instrumenter.add_property("label_size", label_size.to_string());
                 let package_key = self.t_from_mem(package_key_ptr, package_key_size)?;
                 let label: Group = self.t_from_mem(label_ptr, label_size)?;
@@ -629,6 +770,10 @@ where
                     u32,
                     _,
                 ) = Args::parse(args)?;
+                self.charge_host_function_call(
+                    &host_function_costs.provision_contract_user_group_uref,
+                    &[package_size, label_size],
+                )?;
                 scoped_instrumenter.add_property("label_size", label_size.to_string());
                 let ret = self.provision_contract_user_group_uref(
                     package_ptr,
@@ -655,6 +800,10 @@ where
                     _,
                     u32,
                 ) = Args::parse(args)?;
+                self.charge_host_function_call(
+                    &host_function_costs.remove_contract_user_group_urefs,
+                    &[package_size, label_size, urefs_size],
+                )?;
                 scoped_instrumenter.add_property("label_size", label_size.to_string());
                 scoped_instrumenter.add_property("urefs_size", urefs_size.to_string());
                 let ret = self.remove_contract_user_group_urefs(
@@ -667,6 +816,146 @@ where
                 )?;
                 Ok(Some(RuntimeValue::I32(api_error::i32_from(ret))))
             }
+
+            FunctionIndex::PruneFuncIndex => {
+                // args(0) = pointer to uref in Wasm memory
+                // args(1) = size of uref
+                let (uref_ptr, uref_size) = Args::parse(args)?;
+                self.charge_host_function_call(&host_function_costs.prune, &[uref_size])?;
+                self.prune(uref_ptr, uref_size)?;
+                Ok(None)
+            }
+
+            FunctionIndex::EmitEventFuncIndex => {
+                // args(0) = pointer to event name in Wasm memory
+                // args(1) = size of event name
+                // args(2) = pointer to event value in Wasm memory
+                // args(3) = size of event value
+                let (name_ptr, name_size, value_ptr, value_size): (_, u32, _, u32) =
+                    Args::parse(args)?;
+                self.charge_host_function_call(
+                    &host_function_costs.emit_event,
+                    &[name_size, value_size],
+                )?;
+                scoped_instrumenter.add_property("value_size", value_size);
+                self.emit_event(name_ptr, name_size, value_ptr, value_size)?;
+                Ok(None)
+            }
+
+            FunctionIndex::Blake2bFuncIndex => {
+                // args(0) = pointer to input bytes in Wasm memory
+                // args(1) = size of input bytes
+                // args(2) = pointer to Wasm memory where to write the 32-byte digest
+                // args(3) = size of the output buffer
+                let (in_ptr, in_size, out_ptr, out_size): (_, u32, _, _) = Args::parse(args)?;
+                self.charge_host_function_call(&host_function_costs.blake2b, &[in_size])?;
+                scoped_instrumenter.add_property("in_size", in_size);
+                let ret = self.blake2b(in_ptr, in_size, out_ptr, out_size)?;
+                Ok(Some(RuntimeValue::I32(api_error::i32_from(ret))))
+            }
+
+            FunctionIndex::Ed25519VerifyFuncIndex => {
+                // args(0) = pointer to public key bytes in Wasm memory
+                // args(1) = size of public key bytes
+                // args(2) = pointer to message bytes in Wasm memory
+                // args(3) = size of message bytes
+                // args(4) = pointer to signature bytes in Wasm memory
+                // args(5) = size of signature bytes
+                let (
+                    public_key_ptr,
+                    public_key_size,
+                    message_ptr,
+                    message_size,
+                    signature_ptr,
+                    signature_size,
+                ): (_, u32, _, u32, _, u32) = Args::parse(args)?;
+                self.charge_host_function_call(
+                    &host_function_costs.ed25519_verify,
+                    &[public_key_size, message_size, signature_size],
+                )?;
+                scoped_instrumenter.add_property("message_size", message_size);
+                let verified = self.ed25519_verify(
+                    public_key_ptr,
+                    public_key_size,
+                    message_ptr,
+                    message_size,
+                    signature_ptr,
+                    signature_size,
+                )?;
+                Ok(Some(RuntimeValue::I32(i32::from(verified))))
+            }
+
+            FunctionIndex::Secp256k1VerifyFuncIndex => {
+                // args(0) = pointer to public key bytes in Wasm memory
+                // args(1) = size of public key bytes
+                // args(2) = pointer to 32-byte message digest in Wasm memory
+                // args(3) = size of message digest
+                // args(4) = pointer to signature bytes in Wasm memory
+                // args(5) = size of signature bytes
+                let (
+                    public_key_ptr,
+                    public_key_size,
+                    message_digest_ptr,
+                    message_digest_size,
+                    signature_ptr,
+                    signature_size,
+                ): (_, u32, _, u32, _, u32) = Args::parse(args)?;
+                self.charge_host_function_call(
+                    &host_function_costs.secp256k1_verify,
+                    &[public_key_size, message_digest_size, signature_size],
+                )?;
+                let verified = self.secp256k1_verify(
+                    public_key_ptr,
+                    public_key_size,
+                    message_digest_ptr,
+                    message_digest_size,
+                    signature_ptr,
+                    signature_size,
+                )?;
+                Ok(Some(RuntimeValue::I32(i32::from(verified))))
+            }
+
+            FunctionIndex::RandomBytesFuncIndex => {
+                // args(0) = pointer to Wasm memory where to write the random bytes
+                // args(1) = number of random bytes to write
+                let (out_ptr, out_size): (_, u32) = Args::parse(args)?;
+                self.charge_host_function_call(&host_function_costs.random_bytes, &[out_size])?;
+                scoped_instrumenter.add_property("out_size", out_size);
+                self.random_bytes(out_ptr, out_size)?;
+                Ok(None)
+            }
+
+            FunctionIndex::GetDeployHashIndex => {
+                // args(0) = pointer to Wasm memory where to write.
+                let dest_ptr = Args::parse(args)?;
+                self.charge_host_function_call(&host_function_costs.get_deploy_hash, &[])?;
+                self.get_deploy_hash(dest_ptr)?;
+                Ok(None)
+            }
+
+            FunctionIndex::GetBlockHeightIndex => {
+                // args(0) = pointer to Wasm memory where to write.
+                let dest_ptr = Args::parse(args)?;
+                self.charge_host_function_call(&host_function_costs.get_block_height, &[])?;
+                self.get_block_height(dest_ptr)?;
+                Ok(None)
+            }
+
+            FunctionIndex::GetProposerIndex => {
+                // args(0) = pointer to Wasm memory where to write.
+                let dest_ptr = Args::parse(args)?;
+                self.charge_host_function_call(&host_function_costs.get_proposer, &[])?;
+                self.get_proposer(dest_ptr)?;
+                Ok(None)
+            }
+
+            FunctionIndex::GetParentStateHashIndex => {
+                // args(0) = pointer to Wasm memory where to write.
+                let dest_ptr = Args::parse(args)?;
+                self.charge_host_function_call(&host_function_costs.get_parent_state_hash, &[])?;
+                self.get_parent_state_hash(dest_ptr)?;
+                Ok(None)
+            }
         }
     }
 }
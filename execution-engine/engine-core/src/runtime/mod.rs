@@ -1,15 +1,19 @@
 mod args;
+mod crypto;
 mod externals;
 mod mint_internal;
 mod proof_of_stake_internal;
 mod scoped_instrumenter;
 mod standard_payment_internal;
+pub mod trace;
 
 use std::{
+    cell::RefCell,
     cmp,
     collections::{BTreeMap, BTreeSet, HashMap, HashSet},
     convert::TryFrom,
     iter::IntoIterator,
+    rc::Rc,
 };
 
 use itertools::Itertools;
@@ -17,8 +21,9 @@ use parity_wasm::elements::Module;
 use wasmi::{ImportsBuilder, MemoryRef, ModuleInstance, ModuleRef, Trap, TrapKind};
 
 use ::mint::Mint;
-use engine_shared::{account::Account, gas::Gas, stored_value::StoredValue};
+use engine_shared::{account::Account, gas::Gas, newtypes::Blake2bHash, stored_value::StoredValue};
 use engine_storage::{global_state::StateReader, protocol_data::ProtocolData};
+use engine_wasm_prep::host_function_costs::HostFunctionCost;
 use proof_of_stake::ProofOfStake;
 use standard_payment::StandardPayment;
 use types::{
@@ -31,7 +36,8 @@ use types::{
     system_contract_errors::mint,
     AccessRights, ApiError, CLType, CLTyped, CLValue, ContractHash, ContractPackageHash,
     ContractVersionKey, ContractWasm, EntryPointType, Key, ProtocolVersion, RuntimeArgs,
-    SystemContractType, TransferResult, TransferredTo, URef, U128, U256, U512,
+    SystemContractType, TransferResult, TransferredTo, URef, BLAKE2B_DIGEST_LENGTH, U128, U256,
+    U512,
 };
 
 use crate::{
@@ -43,6 +49,7 @@ use crate::{
 };
 use contracts::{ContractVersion, ContractVersions, DisabledVersions, Groups, NamedKeys};
 use scoped_instrumenter::ScopedInstrumenter;
+use trace::HostCallTracer;
 
 pub struct Runtime<'a, R> {
     system_contract_cache: SystemContractCache,
@@ -51,6 +58,7 @@ pub struct Runtime<'a, R> {
     module: Module,
     host_buffer: Option<CLValue>,
     context: RuntimeContext<'a, R>,
+    tracer: Option<Rc<RefCell<HostCallTracer>>>,
 }
 
 /// Rename function called `name` in the `module` to `call`.
@@ -1355,6 +1363,7 @@ where
         memory: MemoryRef,
         module: Module,
         context: RuntimeContext<'a, R>,
+        tracer: Option<Rc<RefCell<HostCallTracer>>>,
     ) -> Self {
         Runtime {
             config,
@@ -1363,6 +1372,7 @@ where
             module,
             host_buffer: None,
             context,
+            tracer,
         }
     }
 
@@ -1382,30 +1392,17 @@ where
         self.context.protocol_data()
     }
 
-    /// Charge specified amount of gas
-    ///
-    /// Returns false if gas limit exceeded and true if not.
-    /// Intuition about the return value sense is to answer the question 'are we
-    /// allowed to continue?'
-    fn charge_gas(&mut self, amount: Gas) -> bool {
-        let prev = self.context.gas_counter();
-        match prev.checked_add(amount) {
-            // gas charge overflow protection
-            None => false,
-            Some(val) if val > self.context.gas_limit() => false,
-            Some(val) => {
-                self.context.set_gas_counter(val);
-                true
-            }
-        }
+    fn gas(&mut self, amount: Gas) -> Result<(), Trap> {
+        self.context.charge_gas(amount).map_err(Into::into)
     }
 
-    fn gas(&mut self, amount: Gas) -> Result<(), Trap> {
-        if self.charge_gas(amount) {
-            Ok(())
-        } else {
-            Err(Error::GasLimit.into())
-        }
+    /// Charges for a call to a host function which was passed arguments of the given sizes.
+    fn charge_host_function_call(
+        &mut self,
+        cost: &HostFunctionCost,
+        sizes: &[u32],
+    ) -> Result<(), Trap> {
+        self.gas(Gas::new(cost.calculate(sizes).into()))
     }
 
     fn bytes_from_mem(&self, ptr: u32, size: usize) -> Result<Vec<u8>, Error> {
@@ -1597,6 +1594,50 @@ where
             .map_err(|e| Error::Interpreter(e.into()).into())
     }
 
+    /// Writes the hash of the current deploy to [dest_ptr] in Wasm memory.
+    fn get_deploy_hash(&self, dest_ptr: u32) -> Result<(), Trap> {
+        let deploy_hash = self.context.get_deploy_hash();
+        self.memory
+            .set(dest_ptr, &deploy_hash)
+            .map_err(|e| Error::Interpreter(e.into()).into())
+    }
+
+    /// Writes the height of the current block to [dest_ptr] in Wasm memory.
+    fn get_block_height(&self, dest_ptr: u32) -> Result<(), Trap> {
+        let block_height = self
+            .context
+            .get_block_info()
+            .block_height()
+            .into_bytes()
+            .map_err(Error::BytesRepr)?;
+        self.memory
+            .set(dest_ptr, &block_height)
+            .map_err(|e| Error::Interpreter(e.into()).into())
+    }
+
+    /// Writes the account hash of the proposer of the current block, if it is known, to [dest_ptr]
+    /// in Wasm memory.
+    fn get_proposer(&self, dest_ptr: u32) -> Result<(), Trap> {
+        let proposer = self
+            .context
+            .get_block_info()
+            .proposer()
+            .into_bytes()
+            .map_err(Error::BytesRepr)?;
+        self.memory
+            .set(dest_ptr, &proposer)
+            .map_err(|e| Error::Interpreter(e.into()).into())
+    }
+
+    /// Writes the hash of the global state the current block is executed against to [dest_ptr] in
+    /// Wasm memory.
+    fn get_parent_state_hash(&self, dest_ptr: u32) -> Result<(), Trap> {
+        let parent_state_hash = self.context.get_block_info().parent_state_hash();
+        self.memory
+            .set(dest_ptr, &parent_state_hash.value())
+            .map_err(|e| Error::Interpreter(e.into()).into())
+    }
+
     /// Return some bytes from the memory and terminate the current `sub_call`. Note that the return
     /// type is `Trap`, indicating that this function will always kill the current Wasm instance.
     fn ret(
@@ -1689,11 +1730,13 @@ where
         let account = self.context.account();
         let base_key = self.protocol_data().mint().into();
         let blocktime = self.context.get_blocktime();
+        let block_info = self.context.get_block_info();
         let deploy_hash = self.context.get_deploy_hash();
         let gas_limit = self.context.gas_limit();
         let gas_counter = self.context.gas_counter();
         let hash_address_generator = self.context.hash_address_generator();
         let uref_address_generator = self.context.uref_address_generator();
+        let random_bytes_generator = self.context.random_bytes_generator();
         let correlation_id = self.context.correlation_id();
         let phase = self.context.phase();
         let protocol_data = self.context.protocol_data();
@@ -1708,11 +1751,13 @@ where
             account,
             base_key,
             blocktime,
+            block_info,
             deploy_hash,
             gas_limit,
             gas_counter,
             hash_address_generator,
             uref_address_generator,
+            random_bytes_generator,
             protocol_version,
             correlation_id,
             phase,
@@ -1783,11 +1828,13 @@ where
         let account = self.context.account();
         let base_key = self.protocol_data().proof_of_stake().into();
         let blocktime = self.context.get_blocktime();
+        let block_info = self.context.get_block_info();
         let deploy_hash = self.context.get_deploy_hash();
         let gas_limit = self.context.gas_limit();
         let gas_counter = self.context.gas_counter();
         let fn_store_id = self.context.hash_address_generator();
         let address_generator = self.context.uref_address_generator();
+        let random_bytes_generator = self.context.random_bytes_generator();
         let correlation_id = self.context.correlation_id();
         let phase = self.context.phase();
         let protocol_data = self.context.protocol_data();
@@ -1802,11 +1849,13 @@ where
             account,
             base_key,
             blocktime,
+            block_info,
             deploy_hash,
             gas_limit,
             gas_counter,
             fn_store_id,
             address_generator,
+            random_bytes_generator,
             protocol_version,
             correlation_id,
             phase,
@@ -1819,6 +1868,7 @@ where
             self.memory.clone(),
             self.module.clone(),
             runtime_context,
+            self.tracer.clone(),
         );
 
         let ret: CLValue = match entry_point_name {
@@ -2138,11 +2188,13 @@ where
             &self.context.account(),
             base_key,
             self.context.get_blocktime(),
+            self.context.get_block_info(),
             self.context.get_deploy_hash(),
             self.context.gas_limit(),
             self.context.gas_counter(),
             self.context.hash_address_generator(),
             self.context.uref_address_generator(),
+            self.context.random_bytes_generator(),
             protocol_version,
             self.context.correlation_id(),
             self.context.phase(),
@@ -2156,6 +2208,7 @@ where
             module,
             host_buffer,
             context,
+            tracer: self.tracer.clone(),
         };
 
         let result = instance.invoke_export(entry_point_name, &[], &mut runtime);
@@ -2164,6 +2217,10 @@ where
         // charged by the sub-call was added to its counter - so let's copy the correct value of the
         // counter from there to our counter
         self.context.set_gas_counter(runtime.context.gas_counter());
+        // The sub-call's storage counter on the other hand started from zero
+        self.context.set_storage_gas_counter(
+            self.context.storage_gas_counter() + runtime.context.storage_gas_counter(),
+        );
 
         let error = match result {
             Err(error) => error,
@@ -2179,6 +2236,7 @@ where
                     // running session code
                     *self.context.named_keys_mut() = runtime.context.named_keys().clone();
                 }
+                self.context.extend_events(runtime.context.take_events());
                 return Ok(runtime.take_host_buffer().unwrap_or(CLValue::from_t(())?));
             }
         };
@@ -2202,6 +2260,7 @@ where
                         // running session code
                         *self.context.named_keys_mut() = runtime.context.named_keys().clone();
                     }
+                    self.context.extend_events(runtime.context.take_events());
                     return runtime.take_host_buffer().ok_or(Error::ExpectedReturnValue);
                 }
                 error => return Err(error.clone()),
@@ -2351,7 +2410,7 @@ where
         let key = Key::Hash(addr);
         let (stored_value, access_key) = self.create_contract_value()?;
 
-        self.context.state().borrow_mut().write(key, stored_value);
+        self.context.metered_write_gs_unsafe(key, stored_value)?;
         Ok((addr, access_key.addr()))
     }
 
@@ -2421,10 +2480,10 @@ where
         }
 
         // Write updated package to the global state
-        self.context.state().borrow_mut().write(
+        self.context.metered_write_gs_unsafe(
             contract_package_key,
             StoredValue::ContractPackage(contract_package),
-        );
+        )?;
 
         Ok(Ok(()))
     }
@@ -2480,19 +2539,15 @@ where
         let insert_contract_result = contract_package.insert_contract_version(major, contract_hash);
 
         self.context
-            .state()
-            .borrow_mut()
-            .write(contract_wasm_key, StoredValue::ContractWasm(contract_wasm));
+            .metered_write_gs_unsafe(contract_wasm_key, StoredValue::ContractWasm(contract_wasm))?;
 
         self.context
-            .state()
-            .borrow_mut()
-            .write(contract_key, StoredValue::Contract(contract));
+            .metered_write_gs_unsafe(contract_key, StoredValue::Contract(contract))?;
 
-        self.context.state().borrow_mut().write(
+        self.context.metered_write_gs_unsafe(
             contract_package_key,
             StoredValue::ContractPackage(contract_package),
-        );
+        )?;
 
         // return contract key to caller
         {
@@ -2544,10 +2599,10 @@ where
             return Ok(Err(err.into()));
         }
 
-        self.context.state().borrow_mut().write(
+        self.context.metered_write_gs_unsafe(
             contract_package_key,
             StoredValue::ContractPackage(contract_package),
-        );
+        )?;
 
         Ok(Ok(()))
     }
@@ -2585,6 +2640,102 @@ where
             .map_err(Into::into)
     }
 
+    /// Records an event named `name` carrying `value`, emitted by the current contract.
+    fn emit_event(
+        &mut self,
+        name_ptr: u32,
+        name_size: u32,
+        value_ptr: u32,
+        value_size: u32,
+    ) -> Result<(), Trap> {
+        let name = self.string_from_mem(name_ptr, name_size)?;
+        let cl_value = self.cl_value_from_mem(value_ptr, value_size)?;
+        self.context.emit_event(name, cl_value);
+        Ok(())
+    }
+
+    /// Writes the BLAKE2b hash of the `in_size` bytes at `in_ptr` to `out_ptr`.
+    fn blake2b(
+        &mut self,
+        in_ptr: u32,
+        in_size: u32,
+        out_ptr: u32,
+        out_size: u32,
+    ) -> Result<Result<(), ApiError>, Trap> {
+        if (out_size as usize) < BLAKE2B_DIGEST_LENGTH {
+            return Ok(Err(ApiError::BufferTooSmall));
+        }
+        let input = self.bytes_from_mem(in_ptr, in_size as usize)?;
+        let digest = Blake2bHash::new(&input);
+        self.memory
+            .set(out_ptr, &digest.value())
+            .map_err(|e| Error::Interpreter(e.into()))?;
+        Ok(Ok(()))
+    }
+
+    /// Checks an Ed25519 signature of a message, both given as raw bytes in Wasm memory.
+    fn ed25519_verify(
+        &mut self,
+        public_key_ptr: u32,
+        public_key_size: u32,
+        message_ptr: u32,
+        message_size: u32,
+        signature_ptr: u32,
+        signature_size: u32,
+    ) -> Result<bool, Trap> {
+        let public_key = self.bytes_from_mem(public_key_ptr, public_key_size as usize)?;
+        let message = self.bytes_from_mem(message_ptr, message_size as usize)?;
+        let signature = self.bytes_from_mem(signature_ptr, signature_size as usize)?;
+        Ok(crypto::ed25519_verify(&public_key, &message, &signature))
+    }
+
+    /// Checks a secp256k1 signature of a 32-byte message digest, both given as raw bytes in Wasm
+    /// memory.
+    fn secp256k1_verify(
+        &mut self,
+        public_key_ptr: u32,
+        public_key_size: u32,
+        message_digest_ptr: u32,
+        message_digest_size: u32,
+        signature_ptr: u32,
+        signature_size: u32,
+    ) -> Result<bool, Trap> {
+        let public_key = self.bytes_from_mem(public_key_ptr, public_key_size as usize)?;
+        let message_digest =
+            self.bytes_from_mem(message_digest_ptr, message_digest_size as usize)?;
+        let signature = self.bytes_from_mem(signature_ptr, signature_size as usize)?;
+        Ok(crypto::secp256k1_verify(
+            &public_key,
+            &message_digest,
+            &signature,
+        ))
+    }
+
+    /// Writes the next `out_size` bytes of the deploy's random byte stream to `out_ptr`.
+    fn random_bytes(&mut self, out_ptr: u32, out_size: u32) -> Result<(), Trap> {
+        let random_bytes_generator = self.context.random_bytes_generator();
+        // Fill Wasm memory in place so that a contract can't make the host allocate more than the
+        // contract's own memory.
+        self.memory
+            .with_direct_access_mut(|buffer| {
+                let dest = (out_ptr as usize)
+                    .checked_add(out_size as usize)
+                    .and_then(|end| buffer.get_mut(out_ptr as usize..end))
+                    .ok_or_else(|| {
+                        Error::Interpreter("random bytes out of memory bounds".to_string())
+                    })?;
+                random_bytes_generator.borrow_mut().fill_bytes(dest);
+                Ok(())
+            })
+            .map_err(|error: Error| error.into())
+    }
+
+    /// Removes the value under `uref` from GlobalState.
+    fn prune(&mut self, uref_ptr: u32, uref_size: u32) -> Result<(), Trap> {
+        let uref: URef = self.t_from_mem(uref_ptr, uref_size)?;
+        self.context.prune_gs(uref).map_err(Into::into)
+    }
+
     /// Writes `value` under a key derived from `key` in the "local cluster" of
     /// GlobalState
     fn write_local(
@@ -3238,10 +3389,10 @@ where
         }
 
         // Write updated package to the global state
-        self.context.state().borrow_mut().write(
+        self.context.metered_write_gs_unsafe(
             Key::from(package_key),
             StoredValue::ContractPackage(package),
-        );
+        )?;
         Ok(Ok(()))
     }
 
@@ -3304,10 +3455,10 @@ where
         }
 
         // Write updated package to the global state
-        self.context.state().borrow_mut().write(
+        self.context.metered_write_gs_unsafe(
             Key::from(contract_package_hash),
             StoredValue::ContractPackage(contract_package),
-        );
+        )?;
 
         Ok(Ok(()))
     }
@@ -3349,10 +3500,10 @@ where
             }
         }
         // Write updated package to the global state
-        self.context.state().borrow_mut().write(
+        self.context.metered_write_gs_unsafe(
             Key::from(contract_package_hash),
             StoredValue::ContractPackage(contract_package),
-        );
+        )?;
 
         Ok(Ok(()))
     }
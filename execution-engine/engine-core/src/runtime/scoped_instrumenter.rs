@@ -139,6 +139,16 @@ impl Drop for ScopedInstrumenter {
             FunctionIndex::RemoveContractUserGroupURefsIndex => {
                 "host_remove_contract_user_group_urefs"
             }
+            FunctionIndex::PruneFuncIndex => "host_function_prune",
+            FunctionIndex::EmitEventFuncIndex => "host_function_emit_event",
+            FunctionIndex::Blake2bFuncIndex => "host_function_blake2b",
+            FunctionIndex::Ed25519VerifyFuncIndex => "host_function_ed25519_verify",
+            FunctionIndex::Secp256k1VerifyFuncIndex => "host_function_secp256k1_verify",
+            FunctionIndex::RandomBytesFuncIndex => "host_function_random_bytes",
+            FunctionIndex::GetDeployHashIndex => "host_function_get_deploy_hash",
+            FunctionIndex::GetBlockHeightIndex => "host_function_get_block_height",
+            FunctionIndex::GetProposerIndex => "host_function_get_proposer",
+            FunctionIndex::GetParentStateHashIndex => "host_function_get_parent_state_hash",
         };
 
         let mut properties = mem::take(&mut self.properties);
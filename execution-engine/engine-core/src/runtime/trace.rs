@@ -0,0 +1,200 @@
+use std::mem;
+
+use wasmi::{MemoryRef, RuntimeValue};
+
+use engine_shared::gas::Gas;
+use types::Key;
+
+use crate::resolvers::v1_function_index::FunctionIndex;
+
+/// The host functions called while executing a deploy, in the order they were called.
+pub type ExecutionTrace = Vec<HostCall>;
+
+/// An argument passed to a host function.
+#[derive(Clone, Debug, PartialEq)]
+pub enum HostCallArg {
+    I32(i32),
+    I64(i64),
+    F32(f32),
+    F64(f64),
+    /// A pointer and a size passed as two arguments to a buffer read by the host function, along
+    /// with the bytes of the buffer in the memory of the contract.
+    Buffer {
+        ptr: u32,
+        bytes: Vec<u8>,
+    },
+}
+
+impl From<RuntimeValue> for HostCallArg {
+    fn from(value: RuntimeValue) -> Self {
+        match value {
+            RuntimeValue::I32(value) => HostCallArg::I32(value),
+            RuntimeValue::I64(value) => HostCallArg::I64(value),
+            RuntimeValue::F32(value) => HostCallArg::F32(value.to_float()),
+            RuntimeValue::F64(value) => HostCallArg::F64(value.to_float()),
+        }
+    }
+}
+
+/// A call to a host function made by a contract.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HostCall {
+    pub function_index: FunctionIndex,
+    /// The arguments passed by the contract.  The pointer and size of each buffer read by the host
+    /// function are decoded together as a [`HostCallArg::Buffer`].
+    pub args: Vec<HostCallArg>,
+    pub gas_before: Gas,
+    /// The gas counter once the call returned, including the gas spent by any contract it called.
+    pub gas_after: Gas,
+    /// The base key of the contract which made the call.
+    pub base_key: Key,
+    /// The number of host calls still in progress when the call was made, i.e. `0` for calls made
+    /// by the deploy itself and `1` for calls made by a contract it called.
+    pub depth: usize,
+}
+
+/// Records the host functions called by a contract and by the contracts it calls.
+#[derive(Debug, Default)]
+pub struct HostCallTracer {
+    host_calls: ExecutionTrace,
+    depth: usize,
+}
+
+impl HostCallTracer {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Records the start of a host call, and returns its index to pass to
+    /// [`finish`](Self::finish).
+    pub(crate) fn start(
+        &mut self,
+        function_index: FunctionIndex,
+        args: &[RuntimeValue],
+        memory: &MemoryRef,
+        gas_before: Gas,
+        base_key: Key,
+    ) -> usize {
+        let index = self.host_calls.len();
+        self.host_calls.push(HostCall {
+            function_index,
+            args: decode_args(function_index, args, memory),
+            gas_before,
+            gas_after: gas_before,
+            base_key,
+            depth: self.depth,
+        });
+        self.depth += 1;
+        index
+    }
+
+    /// Records the end of the host call started at `index`.
+    pub(crate) fn finish(&mut self, index: usize, gas_after: Gas) {
+        self.host_calls[index].gas_after = gas_after;
+        self.depth -= 1;
+    }
+
+    /// Returns the host calls recorded so far, and stops tracking them.
+    pub fn take_host_calls(&mut self) -> ExecutionTrace {
+        mem::replace(&mut self.host_calls, Vec::new())
+    }
+}
+
+/// Decodes `args`, reading the buffers passed to the host function at `function_index` from
+/// `memory`.  The pointer and size of a buffer which can't be read are kept as they are.
+fn decode_args(
+    function_index: FunctionIndex,
+    args: &[RuntimeValue],
+    memory: &MemoryRef,
+) -> Vec<HostCallArg> {
+    let buffers = input_buffers(function_index);
+    let mut decoded_args = Vec::with_capacity(args.len());
+    let mut index = 0;
+    while index < args.len() {
+        let buffer = if buffers.contains(&index) {
+            match (args[index], args.get(index + 1)) {
+                (RuntimeValue::I32(ptr), Some(RuntimeValue::I32(size))) => memory
+                    .get(ptr as u32, *size as u32 as usize)
+                    .ok()
+                    .map(|bytes| HostCallArg::Buffer {
+                        ptr: ptr as u32,
+                        bytes,
+                    }),
+                _ => None,
+            }
+        } else {
+            None
+        };
+        match buffer {
+            Some(buffer) => {
+                decoded_args.push(buffer);
+                index += 2;
+            }
+            None => {
+                decoded_args.push(args[index].into());
+                index += 1;
+            }
+        }
+    }
+    decoded_args
+}
+
+/// Returns the index of the pointer argument of each buffer read by the host function at
+/// `function_index`, which is followed by the size of the buffer.
+fn input_buffers(function_index: FunctionIndex) -> &'static [usize] {
+    match function_index {
+        FunctionIndex::WriteFuncIndex
+        | FunctionIndex::WriteLocalFuncIndex
+        | FunctionIndex::AddFuncIndex
+        | FunctionIndex::PutKeyFuncIndex
+        | FunctionIndex::TransferToAccountIndex
+        | FunctionIndex::DisableContractVersion
+        | FunctionIndex::RemoveContractUserGroupIndex
+        | FunctionIndex::ExtendContractUserGroupURefsIndex
+        | FunctionIndex::EmitEventFuncIndex => &[0, 2],
+        FunctionIndex::ReadFuncIndex
+        | FunctionIndex::ReadLocalFuncIndex
+        | FunctionIndex::RetFuncIndex
+        | FunctionIndex::GetKeyFuncIndex
+        | FunctionIndex::HasKeyFuncIndex
+        | FunctionIndex::RemoveKeyFuncIndex
+        | FunctionIndex::IsValidURefFnIndex
+        | FunctionIndex::AddAssociatedKeyFuncIndex
+        | FunctionIndex::RemoveAssociatedKeyFuncIndex
+        | FunctionIndex::UpdateAssociatedKeyFuncIndex
+        | FunctionIndex::GetBalanceIndex
+        | FunctionIndex::GetRuntimeArgsizeIndex
+        | FunctionIndex::GetRuntimeArgIndex
+        | FunctionIndex::PruneFuncIndex
+        | FunctionIndex::Blake2bFuncIndex => &[0],
+        #[cfg(feature = "test-support")]
+        FunctionIndex::PrintIndex => &[0],
+        FunctionIndex::NewFuncIndex => &[1],
+        FunctionIndex::CallContractFuncIndex
+        | FunctionIndex::TransferFromPurseToAccountIndex
+        | FunctionIndex::TransferFromPurseToPurseIndex
+        | FunctionIndex::RemoveContractUserGroupURefsIndex
+        | FunctionIndex::Ed25519VerifyFuncIndex
+        | FunctionIndex::Secp256k1VerifyFuncIndex => &[0, 2, 4],
+        FunctionIndex::CallVersionedContract => &[0, 2, 4, 6],
+        FunctionIndex::CreateContractUserGroup => &[0, 2, 5],
+        FunctionIndex::AddContractVersion => &[0, 3, 5],
+        FunctionIndex::LoadNamedKeysFuncIndex
+        | FunctionIndex::GasFuncIndex
+        | FunctionIndex::RevertFuncIndex
+        | FunctionIndex::SetActionThresholdFuncIndex
+        | FunctionIndex::GetCallerIndex
+        | FunctionIndex::GetBlocktimeIndex
+        | FunctionIndex::CreatePurseIndex
+        | FunctionIndex::GetPhaseIndex
+        | FunctionIndex::GetSystemContractIndex
+        | FunctionIndex::GetMainPurseIndex
+        | FunctionIndex::ReadHostBufferIndex
+        | FunctionIndex::CreateContractPackageAtHash
+        | FunctionIndex::RandomBytesFuncIndex
+        | FunctionIndex::GetDeployHashIndex
+        | FunctionIndex::GetBlockHeightIndex
+        | FunctionIndex::GetProposerIndex
+        | FunctionIndex::GetParentStateHashIndex => &[],
+    }
+}
@@ -3,6 +3,7 @@ use std::{
     collections::{BTreeSet, HashMap, HashSet},
     convert::{TryFrom, TryInto},
     fmt::Debug,
+    mem,
     rc::Rc,
 };
 
@@ -28,8 +29,8 @@ use types::{
 };
 
 use crate::{
-    engine_state::execution_effect::ExecutionEffect,
-    execution::{AddressGenerator, Error},
+    engine_state::{block_info::BlockInfo, event::Event, execution_effect::ExecutionEffect},
+    execution::{AddressGenerator, Error, RandomBytesGenerator},
     tracking_copy::{AddResult, TrackingCopy},
     Address,
 };
@@ -96,16 +97,22 @@ pub struct RuntimeContext<'a, R> {
     //(could point at an account or contract in the global state)
     base_key: Key,
     blocktime: BlockTime,
+    block_info: BlockInfo,
     deploy_hash: [u8; KEY_HASH_LENGTH],
     gas_limit: Gas,
     gas_counter: Gas,
+    // The part of `gas_counter` spent on values written to global state
+    storage_gas_counter: Gas,
     hash_address_generator: Rc<RefCell<AddressGenerator>>,
     uref_address_generator: Rc<RefCell<AddressGenerator>>,
+    random_bytes_generator: Rc<RefCell<RandomBytesGenerator>>,
     protocol_version: ProtocolVersion,
     correlation_id: CorrelationId,
     phase: Phase,
     protocol_data: ProtocolData,
     entry_point_type: EntryPointType,
+    // Events emitted by this contract and, once they have returned, the contracts it called
+    events: Vec<Event>,
 }
 
 impl<'a, R> RuntimeContext<'a, R>
@@ -124,11 +131,13 @@ where
         account: &'a Account,
         base_key: Key,
         blocktime: BlockTime,
+        block_info: BlockInfo,
         deploy_hash: [u8; KEY_HASH_LENGTH],
         gas_limit: Gas,
         gas_counter: Gas,
         hash_address_generator: Rc<RefCell<AddressGenerator>>,
         uref_address_generator: Rc<RefCell<AddressGenerator>>,
+        random_bytes_generator: Rc<RefCell<RandomBytesGenerator>>,
         protocol_version: ProtocolVersion,
         correlation_id: CorrelationId,
         phase: Phase,
@@ -143,16 +152,20 @@ where
             account,
             authorization_keys,
             blocktime,
+            block_info,
             deploy_hash,
             base_key,
             gas_limit,
             gas_counter,
+            storage_gas_counter: Gas::default(),
             hash_address_generator,
             uref_address_generator,
+            random_bytes_generator,
             protocol_version,
             correlation_id,
             phase,
             protocol_data,
+            events: Vec::new(),
         }
     }
 
@@ -241,6 +254,10 @@ where
         self.blocktime
     }
 
+    pub fn get_block_info(&self) -> BlockInfo {
+        self.block_info
+    }
+
     pub fn get_deploy_hash(&self) -> [u8; KEY_HASH_LENGTH] {
         self.deploy_hash
     }
@@ -265,6 +282,10 @@ where
         Rc::clone(&self.hash_address_generator)
     }
 
+    pub fn random_bytes_generator(&self) -> Rc<RefCell<RandomBytesGenerator>> {
+        Rc::clone(&self.random_bytes_generator)
+    }
+
     pub fn state(&self) -> Rc<RefCell<TrackingCopy<R>>> {
         Rc::clone(&self.tracking_copy)
     }
@@ -281,6 +302,43 @@ where
         self.gas_counter = new_gas_counter;
     }
 
+    pub fn storage_gas_counter(&self) -> Gas {
+        self.storage_gas_counter
+    }
+
+    pub fn set_storage_gas_counter(&mut self, new_storage_gas_counter: Gas) {
+        self.storage_gas_counter = new_storage_gas_counter;
+    }
+
+    /// Charges `amount` of gas, failing with `Error::GasLimit` without charging anything if it
+    /// would take the gas counter over the gas limit.
+    pub(crate) fn charge_gas(&mut self, amount: Gas) -> Result<(), Error> {
+        match self.gas_counter.checked_add(amount) {
+            Some(gas_counter) if gas_counter <= self.gas_limit => {
+                self.gas_counter = gas_counter;
+                Ok(())
+            }
+            _ => Err(Error::GasLimit),
+        }
+    }
+
+    /// Charges gas for each byte of `value` once serialized, according to the storage costs in the
+    /// protocol data.  Writes made by system contracts are not charged.
+    ///
+    /// The charge is added to both `gas_counter` and `storage_gas_counter`.
+    pub(crate) fn charge_storage_gas(&mut self, value: &StoredValue) -> Result<(), Error> {
+        if let Key::Hash(hash) = self.base_key {
+            if self.protocol_data.system_contracts().contains(&hash) {
+                return Ok(());
+            }
+        }
+        let cost = self.protocol_data.storage_costs().calculate_gas_cost(value);
+        self.charge_gas(cost)?;
+        // Can't overflow as it never exceeds `gas_counter`
+        self.storage_gas_counter = self.storage_gas_counter + cost;
+        Ok(())
+    }
+
     pub fn base_key(&self) -> Key {
         self.base_key
     }
@@ -368,9 +426,9 @@ where
             });
         }
         let hash: [u8; KEY_HASH_LENGTH] = key_bytes.try_into().unwrap();
-        self.tracking_copy
-            .borrow_mut()
-            .write(hash.into(), StoredValue::CLValue(cl_value));
+        let value = StoredValue::CLValue(cl_value);
+        self.charge_storage_gas(&value)?;
+        self.tracking_copy.borrow_mut().write(hash.into(), value);
         Ok(())
     }
 
@@ -418,10 +476,33 @@ where
         self.validate_writeable(&key)?;
         self.validate_key(&key)?;
         self.validate_value(&value)?;
+        self.charge_storage_gas(&value)?;
         self.tracking_copy.borrow_mut().write(key, value);
         Ok(())
     }
 
+    /// Writes `value` under `key` without validating either of them, charging for the storage it
+    /// takes up.  Used by the host when installing contracts and updating contract packages.
+    pub(crate) fn metered_write_gs_unsafe(
+        &mut self,
+        key: Key,
+        value: StoredValue,
+    ) -> Result<(), Error> {
+        self.charge_storage_gas(&value)?;
+        self.tracking_copy.borrow_mut().write(key, value);
+        Ok(())
+    }
+
+    /// Removes the value under `uref` from global state.  The `uref` must be valid in this context
+    /// and carry write access.
+    pub fn prune_gs(&mut self, uref: URef) -> Result<(), Error> {
+        let key = Key::URef(uref);
+        self.validate_writeable(&key)?;
+        self.validate_key(&key)?;
+        self.tracking_copy.borrow_mut().prune(key);
+        Ok(())
+    }
+
     pub fn read_account(&mut self, key: &Key) -> Result<Option<StoredValue>, Error> {
         if let Key::Account(_) = key {
             self.validate_key(key)?;
@@ -460,7 +541,7 @@ where
         let new_hash = self.new_hash_address()?;
         self.validate_value(&contract)?;
         let hash_key = Key::Hash(new_hash);
-        self.tracking_copy.borrow_mut().write(hash_key, contract);
+        self.metered_write_gs_unsafe(hash_key, contract)?;
         Ok(new_hash)
     }
 
@@ -484,6 +565,27 @@ where
         self.tracking_copy.borrow_mut().effect()
     }
 
+    /// Records an event emitted by the current contract.
+    pub fn emit_event(&mut self, name: String, value: CLValue) {
+        let event = Event::new(self.base_key, name, value);
+        self.events.push(event);
+    }
+
+    /// Returns the events emitted so far, in the order they were emitted.
+    pub fn events(&self) -> &[Event] {
+        &self.events
+    }
+
+    /// Takes the events emitted so far, leaving none behind.
+    pub fn take_events(&mut self) -> Vec<Event> {
+        mem::take(&mut self.events)
+    }
+
+    /// Appends events emitted by a contract called from the current one.
+    pub(crate) fn extend_events(&mut self, events: Vec<Event>) {
+        self.events.extend(events);
+    }
+
     /// Validates whether keys used in the `value` are not forged.
     fn validate_value(&self, value: &StoredValue) -> Result<(), Error> {
         match value {
@@ -643,10 +745,15 @@ where
     /// values can't be added, either because they're not a Monoid or if the
     /// value stored under `key` has different type, then `TypeMismatch`
     /// errors is returned.
+    ///
+    /// The storage gas is charged for `value` itself rather than for the result of the addition,
+    /// so that the cost of an add does not depend on what other deploys have added to `key`
+    /// before it.
     pub fn add_gs(&mut self, key: Key, value: StoredValue) -> Result<(), Error> {
         self.validate_addable(&key)?;
         self.validate_key(&key)?;
         self.validate_value(&value)?;
+        self.charge_storage_gas(&value)?;
         self.add_unsafe(key, value)
     }
 
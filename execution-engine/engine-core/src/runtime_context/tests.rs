@@ -12,17 +12,22 @@ use engine_shared::{
     additive_map::AdditiveMap,
     gas::Gas,
     newtypes::CorrelationId,
+    storage_costs::StorageCosts,
     stored_value::StoredValue,
     transform::Transform,
 };
-use engine_storage::global_state::{
-    in_memory::{InMemoryGlobalState, InMemoryGlobalStateView},
-    CommitResult, StateProvider,
+use engine_storage::{
+    global_state::{
+        in_memory::{InMemoryGlobalState, InMemoryGlobalStateView},
+        CommitResult, StateProvider,
+    },
+    protocol_data::ProtocolData,
 };
 use types::{
     account::{
         AccountHash, ActionType, AddKeyFailure, RemoveKeyFailure, SetThresholdFailure, Weight,
     },
+    bytesrepr::ToBytes,
     contracts::NamedKeys,
     AccessRights, BlockTime, CLValue, Contract, EntryPointType, EntryPoints, Key, Phase,
     ProtocolVersion, RuntimeArgs, URef, KEY_HASH_LENGTH,
@@ -30,12 +35,16 @@ use types::{
 
 use super::{Address, Error, RuntimeContext};
 use crate::{
-    execution::AddressGenerator, runtime::extract_access_rights_from_keys,
+    engine_state::block_info::BlockInfo,
+    execution::{AddressGenerator, RandomBytesGenerator},
+    runtime::extract_access_rights_from_keys,
     tracking_copy::TrackingCopy,
 };
 
 const DEPLOY_HASH: [u8; 32] = [1u8; 32];
 const PHASE: Phase = Phase::Session;
+const STORAGE_GAS_PER_BYTE: u32 = 2;
+const MINT_HASH: [u8; 32] = [10u8; 32];
 
 fn mock_tracking_copy(
     init_key: Key,
@@ -128,11 +137,17 @@ fn mock_runtime_context<'a>(
         &account,
         base_key,
         BlockTime::new(0),
+        BlockInfo::new([0u8; 32].into(), 0, None),
         [1u8; 32],
         Gas::default(),
         Gas::default(),
         Rc::new(RefCell::new(hash_address_generator)),
         Rc::new(RefCell::new(uref_address_generator)),
+        Rc::new(RefCell::new(RandomBytesGenerator::new(
+            &DEPLOY_HASH,
+            PHASE,
+            BlockTime::new(0),
+        ))),
         ProtocolVersion::V1_0_0,
         CorrelationId::new(),
         Phase::Session,
@@ -140,6 +155,49 @@ fn mock_runtime_context<'a>(
     )
 }
 
+fn mock_metered_runtime_context<'a>(
+    account: &'a Account,
+    base_key: Key,
+    named_keys: &'a mut NamedKeys,
+    gas_limit: Gas,
+) -> RuntimeContext<'a, InMemoryGlobalStateView> {
+    let tracking_copy = mock_tracking_copy(base_key, account.clone());
+    let protocol_data = ProtocolData::new(
+        Default::default(),
+        Default::default(),
+        StorageCosts::new(STORAGE_GAS_PER_BYTE),
+        MINT_HASH,
+        [11u8; 32],
+        [12u8; 32],
+    );
+    RuntimeContext::new(
+        Rc::new(RefCell::new(tracking_copy)),
+        EntryPointType::Session,
+        named_keys,
+        HashMap::new(),
+        RuntimeArgs::new(),
+        BTreeSet::from_iter(vec![AccountHash::new([0; 32])]),
+        account,
+        base_key,
+        BlockTime::new(0),
+        BlockInfo::new([0u8; 32].into(), 0, None),
+        DEPLOY_HASH,
+        gas_limit,
+        Gas::default(),
+        Rc::new(RefCell::new(AddressGenerator::new(&DEPLOY_HASH, PHASE))),
+        Rc::new(RefCell::new(AddressGenerator::new(&DEPLOY_HASH, PHASE))),
+        Rc::new(RefCell::new(RandomBytesGenerator::new(
+            &DEPLOY_HASH,
+            PHASE,
+            BlockTime::new(0),
+        ))),
+        ProtocolVersion::V1_0_0,
+        CorrelationId::new(),
+        PHASE,
+        protocol_data,
+    )
+}
+
 #[allow(clippy::assertions_on_constants)]
 fn assert_forged_reference<T>(result: Result<T, Error>) {
     match result {
@@ -356,11 +414,17 @@ fn contract_key_addable_valid() {
         &account,
         contract_key,
         BlockTime::new(0),
+        BlockInfo::new([0u8; 32].into(), 0, None),
         DEPLOY_HASH,
         Gas::default(),
         Gas::default(),
         Rc::new(RefCell::new(hash_address_generator)),
         Rc::new(RefCell::new(uref_address_generator)),
+        Rc::new(RefCell::new(RandomBytesGenerator::new(
+            &DEPLOY_HASH,
+            PHASE,
+            BlockTime::new(0),
+        ))),
         ProtocolVersion::V1_0_0,
         CorrelationId::new(),
         PHASE,
@@ -426,11 +490,17 @@ fn contract_key_addable_invalid() {
         &account,
         other_contract_key,
         BlockTime::new(0),
+        BlockInfo::new([0u8; 32].into(), 0, None),
         DEPLOY_HASH,
         Gas::default(),
         Gas::default(),
         Rc::new(RefCell::new(hash_address_generator)),
         Rc::new(RefCell::new(uref_address_generator)),
+        Rc::new(RefCell::new(RandomBytesGenerator::new(
+            &DEPLOY_HASH,
+            PHASE,
+            BlockTime::new(0),
+        ))),
         ProtocolVersion::V1_0_0,
         CorrelationId::new(),
         PHASE,
@@ -831,3 +901,87 @@ fn validate_valid_purse_of_an_account() {
     let purse = URef::new([53; 32], AccessRights::READ_ADD_WRITE);
     assert!(runtime_context.validate_uref(&purse).is_err());
 }
+
+#[test]
+fn should_charge_storage_gas_per_byte_written() {
+    let (base_key, account) = mock_account(AccountHash::new([0u8; 32]));
+    let mut named_keys = NamedKeys::new();
+    let mut runtime_context =
+        mock_metered_runtime_context(&account, base_key, &mut named_keys, Gas::new(1_000.into()));
+
+    let cl_value = CLValue::from_t("test_value".to_string()).unwrap();
+    let expected_cost = Gas::new(
+        (u64::from(STORAGE_GAS_PER_BYTE)
+            * StoredValue::CLValue(cl_value.clone()).serialized_length() as u64)
+            .into(),
+    );
+
+    runtime_context
+        .write_ls(&[1u8; 32], cl_value.clone())
+        .expect("should write_ls");
+    assert_eq!(runtime_context.storage_gas_counter(), expected_cost);
+    assert_eq!(runtime_context.gas_counter(), expected_cost);
+
+    runtime_context
+        .new_uref(StoredValue::CLValue(cl_value))
+        .expect("should create new uref");
+    assert_eq!(
+        runtime_context.storage_gas_counter(),
+        expected_cost + expected_cost
+    );
+    assert_eq!(runtime_context.gas_counter(), expected_cost + expected_cost);
+}
+
+#[test]
+fn should_charge_storage_gas_for_value_added_to() {
+    let (base_key, account) = mock_account(AccountHash::new([0u8; 32]));
+    let mut named_keys = NamedKeys::new();
+    let mut runtime_context =
+        mock_metered_runtime_context(&account, base_key, &mut named_keys, Gas::new(10_000.into()));
+
+    let named_key = CLValue::from_t(("name".to_string(), Key::Hash([1u8; 32]))).unwrap();
+    let named_key_value = StoredValue::CLValue(named_key);
+    let expected_cost = Gas::new(
+        (u64::from(STORAGE_GAS_PER_BYTE) * named_key_value.serialized_length() as u64).into(),
+    );
+    runtime_context
+        .add_gs(base_key, named_key_value)
+        .expect("should add named key");
+    assert_eq!(runtime_context.storage_gas_counter(), expected_cost);
+    assert_eq!(runtime_context.gas_counter(), expected_cost);
+}
+
+#[test]
+fn should_not_write_when_storage_gas_exceeds_limit() {
+    let (base_key, account) = mock_account(AccountHash::new([0u8; 32]));
+    let mut named_keys = NamedKeys::new();
+    let mut runtime_context =
+        mock_metered_runtime_context(&account, base_key, &mut named_keys, Gas::new(10.into()));
+
+    let test_key = [1u8; 32];
+    let cl_value = CLValue::from_t("test_value".to_string()).unwrap();
+    let result = runtime_context.write_ls(&test_key, cl_value);
+    assert!(matches!(result, Err(Error::GasLimit)));
+    assert_eq!(runtime_context.gas_counter(), Gas::default());
+    assert_eq!(runtime_context.storage_gas_counter(), Gas::default());
+    assert_eq!(runtime_context.read_ls(&test_key).unwrap(), None);
+}
+
+#[test]
+fn should_not_charge_storage_gas_to_system_contracts() {
+    let (_, account) = mock_account(AccountHash::new([0u8; 32]));
+    let mut named_keys = NamedKeys::new();
+    let mut runtime_context = mock_metered_runtime_context(
+        &account,
+        Key::Hash(MINT_HASH),
+        &mut named_keys,
+        Gas::default(),
+    );
+
+    let cl_value = CLValue::from_t("test_value".to_string()).unwrap();
+    runtime_context
+        .write_ls(&[1u8; 32], cl_value)
+        .expect("should write_ls");
+    assert_eq!(runtime_context.gas_counter(), Gas::default());
+    assert_eq!(runtime_context.storage_gas_counter(), Gas::default());
+}
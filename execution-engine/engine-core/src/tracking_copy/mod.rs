@@ -19,7 +19,7 @@ use engine_shared::{
     transform::{self, Transform},
     TypeMismatch,
 };
-use engine_storage::global_state::StateReader;
+use engine_storage::{global_state::StateReader, trie::TrieMerkleProof};
 use types::{bytesrepr, CLType, CLValueError, Key};
 
 use crate::engine_state::{execution_effect::ExecutionEffect, op::Op};
@@ -100,6 +100,7 @@ pub struct TrackingCopyCache<M> {
     current_cache_size: usize,
     reads_cached: LinkedHashMap<Key, StoredValue>,
     muts_cached: HashMap<Key, StoredValue>,
+    prunes_cached: HashSet<Key>,
     meter: M,
 }
 
@@ -114,6 +115,7 @@ impl<M: Meter<Key, StoredValue>> TrackingCopyCache<M> {
             current_cache_size: 0,
             reads_cached: LinkedHashMap::new(),
             muts_cached: HashMap::new(),
+            prunes_cached: HashSet::new(),
             meter,
         }
     }
@@ -136,9 +138,25 @@ impl<M: Meter<Key, StoredValue>> TrackingCopyCache<M> {
 
     /// Inserts `key` and `value` pair to Write/Add cache.
     pub fn insert_write(&mut self, key: Key, value: StoredValue) {
+        self.prunes_cached.remove(&key);
         self.muts_cached.insert(key, value);
     }
 
+    /// Marks `key` as pruned, dropping any value cached under it.
+    pub fn insert_prune(&mut self, key: Key) {
+        self.muts_cached.remove(&key);
+        if let Some(value) = self.reads_cached.remove(&key) {
+            let element_size = Meter::measure(&self.meter, &key, &value);
+            self.current_cache_size -= element_size;
+        }
+        self.prunes_cached.insert(key);
+    }
+
+    /// Returns `true` if `key` has been pruned and not written to since.
+    pub fn is_pruned(&self, key: &Key) -> bool {
+        self.prunes_cached.contains(key)
+    }
+
     /// Gets value from `key` in the cache.
     pub fn get(&mut self, key: &Key) -> Option<&StoredValue> {
         if let Some(value) = self.muts_cached.get(&key) {
@@ -215,6 +233,9 @@ impl<R: StateReader<Key, StoredValue>> TrackingCopy<R> {
         correlation_id: CorrelationId,
         key: &Key,
     ) -> Result<Option<StoredValue>, R::Error> {
+        if self.cache.is_pruned(key) {
+            return Ok(None);
+        }
         if let Some(value) = self.cache.get(key) {
             return Ok(Some(value.to_owned()));
         }
@@ -248,6 +269,15 @@ impl<R: StateReader<Key, StoredValue>> TrackingCopy<R> {
         self.fns.insert_add(normalized_key, Transform::Write(value));
     }
 
+    /// Removes the value under `key` from global state.  Subsequent reads of `key` through this
+    /// `TrackingCopy` return `None` until it is written to again.
+    pub fn prune(&mut self, key: Key) {
+        let normalized_key = key.normalize();
+        self.cache.insert_prune(normalized_key);
+        self.ops.insert_add(normalized_key, Op::Write);
+        self.fns.insert_add(normalized_key, Transform::Prune);
+    }
+
     /// Ok(None) represents missing key to which we want to "add" some value.
     /// Ok(Some(unit)) represents successful operation.
     /// Err(error) is reserved for unexpected errors when accessing global
@@ -325,6 +355,45 @@ impl<R: StateReader<Key, StoredValue>> TrackingCopy<R> {
         }
     }
 
+    /// Applies `effect` as if its operations had been made through this `TrackingCopy`.
+    ///
+    /// The intended use case is to bring the changes made in a fork of this `TrackingCopy` back
+    /// into it, e.g. the effect of a deploy executed against the fork.  Stops at the first
+    /// transform which can't be applied to the current value under its key.
+    pub fn apply_effect(
+        &mut self,
+        correlation_id: CorrelationId,
+        effect: ExecutionEffect,
+    ) -> Result<AddResult, R::Error> {
+        for (key, transform) in effect.transforms.into_iter() {
+            match &transform {
+                Transform::Identity => (),
+                Transform::Write(value) => self.cache.insert_write(key, value.to_owned()),
+                Transform::Prune => self.cache.insert_prune(key),
+                _ => {
+                    let current_value = match self.get(correlation_id, &key)? {
+                        None => return Ok(AddResult::KeyNotFound(key)),
+                        Some(current_value) => current_value,
+                    };
+                    match transform.clone().apply(current_value) {
+                        Ok(new_value) => self.cache.insert_write(key, new_value),
+                        Err(transform::Error::TypeMismatch(type_mismatch)) => {
+                            return Ok(AddResult::TypeMismatch(type_mismatch))
+                        }
+                        Err(transform::Error::Serialization(error)) => {
+                            return Ok(AddResult::Serialization(error))
+                        }
+                    }
+                }
+            }
+            self.fns.insert_add(key, transform);
+        }
+        for (key, op) in effect.ops.into_iter() {
+            self.ops.insert_add(key, op);
+        }
+        Ok(AddResult::Success)
+    }
+
     pub fn effect(&self) -> ExecutionEffect {
         ExecutionEffect::new(self.ops.clone(), self.fns.clone())
     }
@@ -341,6 +410,38 @@ impl<R: StateReader<Key, StoredValue>> TrackingCopy<R> {
         correlation_id: CorrelationId,
         base_key: Key,
         path: &[String],
+    ) -> Result<TrackingCopyQueryResult, R::Error> {
+        self.query_impl(correlation_id, base_key, path, None)
+    }
+
+    /// Runs a query as per [`TrackingCopy::query`], also returning a proof of each value read
+    /// while following `path`, in the order in which they were read.
+    ///
+    /// The proofs are of the state of the underlying reader, so any values written through this
+    /// `TrackingCopy` are ignored.  No proofs are returned if the reader can't provide them.
+    pub fn query_with_proofs(
+        &self,
+        correlation_id: CorrelationId,
+        base_key: Key,
+        path: &[String],
+    ) -> Result<
+        (
+            TrackingCopyQueryResult,
+            Vec<TrieMerkleProof<Key, StoredValue>>,
+        ),
+        R::Error,
+    > {
+        let mut proofs = Vec::new();
+        let result = self.query_impl(correlation_id, base_key, path, Some(&mut proofs))?;
+        Ok((result, proofs))
+    }
+
+    fn query_impl(
+        &self,
+        correlation_id: CorrelationId,
+        base_key: Key,
+        path: &[String],
+        mut maybe_proofs: Option<&mut Vec<TrieMerkleProof<Key, StoredValue>>>,
     ) -> Result<TrackingCopyQueryResult, R::Error> {
         let mut query = Query::new(base_key, path);
 
@@ -348,7 +449,23 @@ impl<R: StateReader<Key, StoredValue>> TrackingCopy<R> {
             if !query.visited_keys.insert(query.current_key) {
                 return Ok(query.into_circular_ref_result());
             }
-            let stored_value = match self.reader.read(correlation_id, &query.current_key)? {
+            let maybe_stored_value = match maybe_proofs.as_mut() {
+                Some(proofs) => {
+                    match self
+                        .reader
+                        .read_with_proof(correlation_id, &query.current_key)?
+                    {
+                        Some(proof) => {
+                            let maybe_value = proof.value().cloned();
+                            proofs.push(proof);
+                            maybe_value
+                        }
+                        None => self.reader.read(correlation_id, &query.current_key)?,
+                    }
+                }
+                None => self.reader.read(correlation_id, &query.current_key)?,
+            };
+            let stored_value = match maybe_stored_value {
                 None => {
                     return Ok(query.into_not_found_result("Failed to find base key"));
                 }
@@ -417,6 +534,9 @@ impl<R: StateReader<Key, StoredValue>> StateReader<Key, StoredValue> for &Tracki
         correlation_id: CorrelationId,
         key: &Key,
     ) -> Result<Option<StoredValue>, Self::Error> {
+        if self.cache.is_pruned(key) {
+            return Ok(None);
+        }
         if let Some(value) = self.cache.muts_cached.get(key) {
             return Ok(Some(value.to_owned()));
         }
@@ -426,4 +546,16 @@ impl<R: StateReader<Key, StoredValue>> StateReader<Key, StoredValue> for &Tracki
             Ok(None)
         }
     }
+
+    fn read_with_proof(
+        &self,
+        correlation_id: CorrelationId,
+        key: &Key,
+    ) -> Result<Option<TrieMerkleProof<Key, StoredValue>>, Self::Error> {
+        // Cached writes and prunes are not part of the underlying state, so can't be proven
+        if self.cache.muts_cached.contains_key(key) || self.cache.is_pruned(key) {
+            return Ok(None);
+        }
+        self.reader.read_with_proof(correlation_id, key)
+    }
 }
@@ -9,7 +9,10 @@ use engine_shared::{
     stored_value::{gens::stored_value_arb, StoredValue},
     transform::Transform,
 };
-use engine_storage::global_state::{in_memory::InMemoryGlobalState, StateProvider, StateReader};
+use engine_storage::{
+    global_state::{in_memory::InMemoryGlobalState, StateProvider, StateReader},
+    trie::TrieMerkleProof,
+};
 use types::{
     account::{AccountHash, Weight, ACCOUNT_HASH_LENGTH},
     contracts::NamedKeys,
@@ -58,6 +61,14 @@ impl StateReader<Key, StoredValue> for CountingDb {
         self.count.set(count + 1);
         Ok(Some(value))
     }
+
+    fn read_with_proof(
+        &self,
+        _correlation_id: CorrelationId,
+        _key: &Key,
+    ) -> Result<Option<TrieMerkleProof<Key, StoredValue>>, Self::Error> {
+        Ok(None)
+    }
 }
 
 #[test]
@@ -285,6 +296,73 @@ fn tracking_copy_aw() {
     assert_eq!(tc.ops.get(&k), Some(&Op::Write));
 }
 
+#[test]
+fn tracking_copy_prune() {
+    let correlation_id = CorrelationId::new();
+    let counter = Rc::new(Cell::new(0));
+    let db = CountingDb::new(Rc::clone(&counter));
+    let mut tc = TrackingCopy::new(db);
+    let k = Key::Hash([0u8; 32]);
+
+    // reading then pruning should hide the value
+    let _ = tc.read(correlation_id, &k);
+    tc.prune(k);
+    assert_eq!(tc.fns.len(), 1);
+    assert_eq!(tc.fns.get(&k), Some(&Transform::Prune));
+    assert_eq!(tc.ops.len(), 1);
+    assert_eq!(tc.ops.get(&k), Some(&Op::Write));
+    let db_value = counter.get();
+    assert_eq!(tc.get(correlation_id, &k).unwrap(), None);
+    assert_eq!((&tc).read(correlation_id, &k).unwrap(), None);
+    assert_eq!(counter.get(), db_value);
+
+    // adding to a pruned key should fail
+    let value = StoredValue::CLValue(CLValue::from_t(3_i32).unwrap());
+    let add = tc.add(correlation_id, k, value.clone());
+    assert_matches!(add, Ok(AddResult::KeyNotFound(_)));
+
+    // writing after pruning should restore the value
+    tc.write(k, value.clone());
+    assert_eq!(tc.fns.get(&k), Some(&Transform::Write(value.clone())));
+    assert_eq!(tc.get(correlation_id, &k).unwrap(), Some(value));
+}
+
+#[test]
+fn tracking_copy_apply_effect_of_fork() {
+    let correlation_id = CorrelationId::new();
+    let db = CountingDb::new_init(StoredValue::CLValue(CLValue::from_t(1_i32).unwrap()));
+    let mut tc = TrackingCopy::new(db);
+    let k1 = Key::Hash([0u8; 32]);
+    let k2 = Key::Hash([1u8; 32]);
+    let k3 = Key::Hash([2u8; 32]);
+
+    let written = StoredValue::CLValue(CLValue::from_t(7_i32).unwrap());
+    tc.write(k1, written.clone());
+
+    let effect = {
+        let mut fork = tc.fork();
+        let _ = fork.read(correlation_id, &k1);
+        let added = StoredValue::CLValue(CLValue::from_t(3_i32).unwrap());
+        assert_matches!(fork.add(correlation_id, k2, added), Ok(AddResult::Success));
+        fork.prune(k3);
+        fork.effect()
+    };
+
+    assert_matches!(
+        tc.apply_effect(correlation_id, effect),
+        Ok(AddResult::Success)
+    );
+    assert_eq!(tc.fns.get(&k1), Some(&Transform::Write(written.clone())));
+    assert_eq!(tc.ops.get(&k1), Some(&Op::Write));
+    assert_eq!(tc.fns.get(&k2), Some(&Transform::AddInt32(3)));
+    assert_eq!(tc.ops.get(&k2), Some(&Op::Add));
+    assert_eq!(tc.fns.get(&k3), Some(&Transform::Prune));
+    assert_eq!(tc.get(correlation_id, &k1).unwrap(), Some(written));
+    let expected = StoredValue::CLValue(CLValue::from_t(4_i32).unwrap());
+    assert_eq!(tc.get(correlation_id, &k2).unwrap(), Some(expected));
+    assert_eq!(tc.get(correlation_id, &k3).unwrap(), None);
+}
+
 proptest! {
     #[test]
     fn query_empty_path(k in key_arb(), missing_key in key_arb(), v in stored_value_arb()) {
@@ -440,6 +518,15 @@ proptest! {
         } else {
             panic!("Query failed when it should not have!");
         }
+
+        let (result, proofs) = tc.query_with_proofs(correlation_id, account_key, &path).unwrap();
+        assert_matches!(result, TrackingCopyQueryResult::Success(_));
+        let proven_keys: Vec<Key> = proofs.iter().map(|proof| *proof.key()).collect();
+        assert_eq!(proven_keys, vec![account_key, contract_key, k.normalize()]);
+        for proof in &proofs {
+            assert!(proof.verify(&root_hash).unwrap().is_some());
+        }
+        assert_eq!(proofs.last().unwrap().value(), Some(&v));
     }
 }
 
@@ -0,0 +1,155 @@
+use std::{convert::TryFrom, path::PathBuf, process};
+
+use clap::{App, Arg, ArgMatches};
+use dirs::home_dir;
+use serde_json::{json, Value};
+
+use engine_shared::{
+    newtypes::{Blake2bHash, CorrelationId},
+    stored_value::StoredValue,
+};
+use engine_storage::{
+    error,
+    transaction_source::{lmdb::LmdbEnvironment, Transaction, TransactionSource},
+    trie_store::{
+        lmdb::LmdbTrieStore,
+        operations::verify::{self, Defect, VerifyReport},
+    },
+};
+use types::Key;
+
+const APP_NAME: &str = "casperlabs-engine-fsck";
+const APP_ABOUT: &str = "Checks the integrity of the global state reachable from the given \
+                         state roots, and prints a JSON report.  Exits with status 1 if any \
+                         defects are found.";
+
+const ARG_DATA_DIR: &str = "data-dir";
+const ARG_DATA_DIR_SHORT: &str = "d";
+const ARG_DATA_DIR_VALUE: &str = "DIR";
+const ARG_DATA_DIR_HELP: &str = "Sets the data directory of the execution engine";
+const DEFAULT_DATA_DIR_RELATIVE: &str = ".casperlabs";
+const GLOBAL_STATE_DIR: &str = "global_state";
+const GET_HOME_DIR_EXPECT: &str = "Could not get home directory";
+
+const ARG_ROOT: &str = "root";
+const ARG_ROOT_VALUE: &str = "ROOT";
+const ARG_ROOT_HELP: &str = "A state root to check, in hex";
+const ARG_ROOT_EXPECT: &str = "Could not parse state root";
+
+const LMDB_ENVIRONMENT_EXPECT: &str = "Could not open LmdbEnvironment";
+const LMDB_TRIE_STORE_EXPECT: &str = "Could not open LmdbTrieStore";
+const READ_TXN_EXPECT: &str = "Could not create read transaction";
+const VERIFY_EXPECT: &str = "Could not verify state root";
+
+const DEFECTS_FOUND_EXIT_CODE: i32 = 1;
+
+fn get_args() -> ArgMatches<'static> {
+    App::new(APP_NAME)
+        .about(APP_ABOUT)
+        .arg(
+            Arg::with_name(ARG_DATA_DIR)
+                .short(ARG_DATA_DIR_SHORT)
+                .long(ARG_DATA_DIR)
+                .value_name(ARG_DATA_DIR_VALUE)
+                .help(ARG_DATA_DIR_HELP)
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name(ARG_ROOT)
+                .value_name(ARG_ROOT_VALUE)
+                .help(ARG_ROOT_HELP)
+                .required(true)
+                .multiple(true),
+        )
+        .get_matches()
+}
+
+/// Gets the global state directory inside the value of the data-dir argument
+fn get_global_state_dir(arg_matches: &ArgMatches) -> PathBuf {
+    let mut buf = arg_matches.value_of(ARG_DATA_DIR).map_or(
+        {
+            let mut dir = home_dir().expect(GET_HOME_DIR_EXPECT);
+            dir.push(DEFAULT_DATA_DIR_RELATIVE);
+            dir
+        },
+        PathBuf::from,
+    );
+    buf.push(GLOBAL_STATE_DIR);
+    buf
+}
+
+fn get_state_roots(arg_matches: &ArgMatches) -> Vec<Blake2bHash> {
+    arg_matches
+        .values_of(ARG_ROOT)
+        .expect(ARG_ROOT_EXPECT)
+        .map(|root| {
+            let bytes = base16::decode(root).expect(ARG_ROOT_EXPECT);
+            Blake2bHash::try_from(bytes.as_slice()).expect(ARG_ROOT_EXPECT)
+        })
+        .collect()
+}
+
+fn defect_to_json(defect: &Defect) -> Value {
+    let kind = match defect {
+        Defect::Missing { .. } => "missing",
+        Defect::HashMismatch { .. } => "hash_mismatch",
+        Defect::Undecodable { .. } => "undecodable",
+        Defect::PointerMismatch { .. } => "pointer_mismatch",
+        Defect::MisplacedLeaf { .. } => "misplaced_leaf",
+    };
+    let mut ret = json!({
+        "kind": kind,
+        "hash": format!("{:x}", defect.hash()),
+        "path": base16::encode_lower(defect.path()),
+    });
+    match defect {
+        Defect::HashMismatch { actual, .. } => ret["actual"] = json!(format!("{:x}", actual)),
+        Defect::Undecodable { error, .. } => ret["error"] = json!(error.to_string()),
+        _ => (),
+    }
+    ret
+}
+
+fn report_to_json(root: &Blake2bHash, report: &VerifyReport) -> Value {
+    json!({
+        "root": format!("{:x}", root),
+        "ok": report.is_ok(),
+        "tries": report.tries,
+        "leaves": report.leaves,
+        "defects": report.defects.iter().map(defect_to_json).collect::<Vec<Value>>(),
+    })
+}
+
+fn main() {
+    let arg_matches = get_args();
+    let global_state_dir = get_global_state_dir(&arg_matches);
+    let roots = get_state_roots(&arg_matches);
+
+    let environment =
+        LmdbEnvironment::open_read_only(&global_state_dir).expect(LMDB_ENVIRONMENT_EXPECT);
+    let trie_store = LmdbTrieStore::open(&environment, None).expect(LMDB_TRIE_STORE_EXPECT);
+
+    let correlation_id = CorrelationId::new();
+    let mut all_ok = true;
+    let mut reports = Vec::new();
+    for root in roots {
+        let txn = environment.create_read_txn().expect(READ_TXN_EXPECT);
+        let report = verify::verify::<Key, StoredValue, _, _, error::Error>(
+            correlation_id,
+            &txn,
+            &trie_store,
+            &root,
+        )
+        .expect(VERIFY_EXPECT);
+        txn.commit().expect(READ_TXN_EXPECT);
+
+        all_ok &= report.is_ok();
+        reports.push(report_to_json(&root, &report));
+    }
+
+    println!("{}", json!({ "roots": reports }));
+
+    if !all_ok {
+        process::exit(DEFECTS_FOUND_EXIT_CODE);
+    }
+}
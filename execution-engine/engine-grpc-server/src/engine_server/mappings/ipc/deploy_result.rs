@@ -4,6 +4,7 @@ use engine_core::{
         Error as EngineStateError,
     },
     execution::Error as ExecutionError,
+    runtime::trace::ExecutionTrace,
 };
 use engine_shared::gas::Gas;
 
@@ -11,14 +12,40 @@ use crate::engine_server::ipc::{DeployError_OutOfGasError, DeployResult};
 
 impl From<ExecutionResult> for DeployResult {
     fn from(execution_result: ExecutionResult) -> DeployResult {
-        match execution_result {
-            ExecutionResult::Success { effect, cost } => detail::execution_success(effect, cost),
+        let storage_cost = execution_result.storage_cost();
+        let events = execution_result.events().to_vec();
+        let mut pb_deploy_result = match execution_result {
+            ExecutionResult::Success { effect, cost, .. } => {
+                detail::execution_success(effect, cost)
+            }
             ExecutionResult::Failure {
                 error,
                 effect,
                 cost,
+                ..
             } => (error, effect, cost).into(),
+        };
+        if pb_deploy_result.has_execution_result() {
+            pb_deploy_result
+                .mut_execution_result()
+                .set_storage_cost(storage_cost.value().into());
+            pb_deploy_result
+                .mut_execution_result()
+                .set_events(events.into_iter().map(Into::into).collect());
+        }
+        pb_deploy_result
+    }
+}
+
+impl From<(ExecutionResult, ExecutionTrace)> for DeployResult {
+    fn from((execution_result, trace): (ExecutionResult, ExecutionTrace)) -> DeployResult {
+        let mut pb_deploy_result: DeployResult = execution_result.into();
+        if pb_deploy_result.has_execution_result() {
+            pb_deploy_result
+                .mut_execution_result()
+                .set_host_calls(trace.into_iter().map(Into::into).collect());
         }
+        pb_deploy_result
     }
 }
 
@@ -145,11 +172,50 @@ mod detail {
 mod tests {
     use std::convert::TryInto;
 
+    use engine_core::engine_state::event::Event;
     use engine_shared::{additive_map::AdditiveMap, transform::Transform};
-    use types::{bytesrepr::Error as BytesReprError, AccessRights, ApiError, Key, URef, U512};
+    use types::{
+        bytesrepr::Error as BytesReprError, AccessRights, ApiError, CLValue, Key, URef, U512,
+    };
 
     use super::*;
 
+    #[test]
+    fn deploy_result_to_ipc_events() {
+        let emitter = Key::Hash([3u8; 32]);
+        let events = vec![
+            Event::new(emitter, "first".to_string(), CLValue::from_t(1u64).unwrap()),
+            Event::new(
+                emitter,
+                "second".to_string(),
+                CLValue::from_t(2u64).unwrap(),
+            ),
+        ];
+        let execution_result = ExecutionResult::Success {
+            effect: Default::default(),
+            cost: Gas::default(),
+            storage_cost: Gas::default(),
+            events: events.clone(),
+        };
+        let mut ipc_deploy_result: DeployResult = execution_result.into();
+        let mut success = ipc_deploy_result.take_execution_result();
+        let ipc_events = success.take_events().into_vec();
+        assert_eq!(ipc_events.len(), events.len());
+        for (mut ipc_event, event) in ipc_events.into_iter().zip(events) {
+            assert_eq!(ipc_event.get_name(), event.name);
+            let value: CLValue = ipc_event
+                .take_value()
+                .try_into()
+                .expect("should map to CLValue");
+            assert_eq!(value, event.value);
+            let emitter: Key = ipc_event
+                .take_emitter()
+                .try_into()
+                .expect("should map to Key");
+            assert_eq!(emitter, event.emitter);
+        }
+    }
+
     #[test]
     fn deploy_result_to_ipc_success() {
         let input_transforms: AdditiveMap<Key, Transform> = {
@@ -162,15 +228,23 @@ mod tests {
         };
         let execution_effect = ExecutionEffect::new(AdditiveMap::new(), input_transforms.clone());
         let cost = Gas::new(U512::from(123));
+        let storage_cost = Gas::new(U512::from(23));
         let execution_result = ExecutionResult::Success {
             effect: execution_effect,
             cost,
+            storage_cost,
+            events: Vec::new(),
         };
         let mut ipc_deploy_result: DeployResult = execution_result.into();
         assert!(ipc_deploy_result.has_execution_result());
         let mut success = ipc_deploy_result.take_execution_result();
         let execution_cost: U512 = success.take_cost().try_into().expect("should map to U512");
         assert_eq!(execution_cost, cost.value());
+        let execution_storage_cost: U512 = success
+            .take_storage_cost()
+            .try_into()
+            .expect("should map to U512");
+        assert_eq!(execution_storage_cost, storage_cost.value());
 
         // Extract transform map from the IPC message and parse it back to the domain
         let ipc_transforms: AdditiveMap<Key, Transform> = {
@@ -190,6 +264,8 @@ mod tests {
             error: error.into(),
             effect: Default::default(),
             cost: expected_cost,
+            storage_cost: Gas::default(),
+            events: Vec::new(),
         };
         let mut ipc_deploy_result: DeployResult = execution_failure.into();
         assert!(ipc_deploy_result.has_execution_result());
@@ -233,6 +309,8 @@ mod tests {
             error: EngineStateError::Exec(revert_error),
             effect: Default::default(),
             cost: Gas::new(amount),
+            storage_cost: Gas::default(),
+            events: Vec::new(),
         };
         let mut ipc_result: DeployResult = exec_result.into();
         assert!(
@@ -0,0 +1,39 @@
+use std::convert::{TryFrom, TryInto};
+
+use engine_core::engine_state::estimate::EstimateRequest;
+use engine_shared::newtypes::BLAKE2B_DIGEST_LENGTH;
+
+use crate::engine_server::{ipc, mappings::MappingError};
+
+impl TryFrom<ipc::EstimateRequest> for EstimateRequest {
+    type Error = MappingError;
+
+    fn try_from(mut request: ipc::EstimateRequest) -> Result<Self, Self::Error> {
+        let parent_state_hash = {
+            let parent_state_hash = request.get_parent_state_hash();
+            let length = parent_state_hash.len();
+            if length != BLAKE2B_DIGEST_LENGTH {
+                return Err(MappingError::InvalidStateHashLength {
+                    expected: BLAKE2B_DIGEST_LENGTH,
+                    actual: length,
+                });
+            }
+            parent_state_hash
+                .try_into()
+                .map_err(|_| MappingError::TryFromSlice)?
+        };
+
+        let block_time = request.get_block_time();
+
+        let deploy_item = request.take_deploy().try_into()?;
+
+        let protocol_version = request.take_protocol_version().into();
+
+        Ok(EstimateRequest::new(
+            parent_state_hash,
+            block_time,
+            deploy_item,
+            protocol_version,
+        ))
+    }
+}
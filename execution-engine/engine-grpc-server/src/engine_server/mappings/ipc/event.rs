@@ -0,0 +1,13 @@
+use engine_core::engine_state::event::Event;
+
+use crate::engine_server::ipc;
+
+impl From<Event> for ipc::Event {
+    fn from(event: Event) -> Self {
+        let mut pb_event = ipc::Event::new();
+        pb_event.set_name(event.name);
+        pb_event.set_value(event.value.into());
+        pb_event.set_emitter(event.emitter.into());
+        pb_event
+    }
+}
@@ -15,7 +15,10 @@ impl TryFrom<ipc::ChainSpec_GenesisConfig_ExecConfig> for ExecConfig {
             .into_iter()
             .map(TryInto::try_into)
             .collect::<Result<Vec<GenesisAccount>, Self::Error>>()?;
-        let wasm_costs = pb_exec_config.take_costs().take_wasm().into();
+        let mut pb_costs = pb_exec_config.take_costs();
+        let wasm_costs = pb_costs.take_wasm().into();
+        let host_function_costs = pb_costs.take_host_functions().into();
+        let storage_costs = pb_costs.take_storage().into();
         let mint_initializer_bytes = pb_exec_config.take_mint_installer();
         let proof_of_stake_initializer_bytes = pb_exec_config.take_pos_installer();
         let standard_payment_installer_bytes = pb_exec_config.take_standard_payment_installer();
@@ -25,6 +28,8 @@ impl TryFrom<ipc::ChainSpec_GenesisConfig_ExecConfig> for ExecConfig {
             standard_payment_installer_bytes,
             accounts,
             wasm_costs,
+            host_function_costs,
+            storage_costs,
         ))
     }
 }
@@ -46,9 +51,12 @@ impl From<ExecConfig> for ipc::ChainSpec_GenesisConfig_ExecConfig {
                 .collect::<Vec<ipc::ChainSpec_GenesisConfig_ExecConfig_GenesisAccount>>();
             pb_exec_config.set_accounts(accounts.into());
         }
-        pb_exec_config
-            .mut_costs()
-            .set_wasm(exec_config.wasm_costs().into());
+        {
+            let pb_costs = pb_exec_config.mut_costs();
+            pb_costs.set_wasm(exec_config.wasm_costs().into());
+            pb_costs.set_host_functions(exec_config.host_function_costs().into());
+            pb_costs.set_storage(exec_config.storage_costs().into());
+        }
         pb_exec_config
     }
 }
@@ -1,9 +1,11 @@
 use std::convert::{TryFrom, TryInto};
 
 use engine_core::engine_state::{
-    execute_request::ExecuteRequest, execution_result::ExecutionResult,
+    execute_request::{ExecuteRequest, ExecutionMode},
+    execution_result::ExecutionResult,
 };
 use engine_shared::newtypes::BLAKE2B_DIGEST_LENGTH;
+use types::account::AccountHash;
 
 use crate::engine_server::{ipc, mappings::MappingError};
 
@@ -30,6 +32,22 @@ impl TryFrom<ipc::ExecuteRequest> for ExecuteRequest {
 
         let block_time = request.get_block_time();
 
+        let block_height = request.get_block_height();
+
+        let proposer = {
+            let proposer = request.take_proposer();
+            if proposer.is_empty() {
+                None
+            } else {
+                let account_hash = AccountHash::try_from(proposer.as_slice()).map_err(|_| {
+                    let mut result = ipc::ExecuteResponse::new();
+                    result.mut_invalid_proposer().set_proposer(proposer.clone());
+                    result
+                })?;
+                Some(account_hash)
+            }
+        };
+
         let deploys = Into::<Vec<_>>::into(request.take_deploys())
             .into_iter()
             .map(|deploy_item| {
@@ -41,11 +59,16 @@ impl TryFrom<ipc::ExecuteRequest> for ExecuteRequest {
 
         let protocol_version = request.take_protocol_version().into();
 
+        let execution_mode = request.get_execution_mode().into();
+
         Ok(ExecuteRequest::new(
             parent_state_hash,
             block_time,
+            block_height,
+            proposer,
             deploys,
             protocol_version,
+            execution_mode,
         ))
     }
 }
@@ -55,6 +78,10 @@ impl From<ExecuteRequest> for ipc::ExecuteRequest {
         let mut result = ipc::ExecuteRequest::new();
         result.set_parent_state_hash(req.parent_state_hash.to_vec());
         result.set_block_time(req.block_time);
+        result.set_block_height(req.block_height);
+        if let Some(proposer) = req.proposer {
+            result.set_proposer(proposer.as_bytes().to_vec());
+        }
         result.set_deploys(
             req.deploys
                 .into_iter()
@@ -65,6 +92,67 @@ impl From<ExecuteRequest> for ipc::ExecuteRequest {
                 .collect(),
         );
         result.set_protocol_version(req.protocol_version.into());
+        result.set_execution_mode(req.execution_mode.into());
         result
     }
 }
+
+impl From<ipc::ExecuteRequest_ExecutionMode> for ExecutionMode {
+    fn from(pb_execution_mode: ipc::ExecuteRequest_ExecutionMode) -> Self {
+        match pb_execution_mode {
+            ipc::ExecuteRequest_ExecutionMode::INDEPENDENT => ExecutionMode::Independent,
+            ipc::ExecuteRequest_ExecutionMode::SEQUENTIAL => ExecutionMode::Sequential,
+            ipc::ExecuteRequest_ExecutionMode::PARALLEL => ExecutionMode::Parallel,
+        }
+    }
+}
+
+impl From<ExecutionMode> for ipc::ExecuteRequest_ExecutionMode {
+    fn from(execution_mode: ExecutionMode) -> Self {
+        match execution_mode {
+            ExecutionMode::Independent => ipc::ExecuteRequest_ExecutionMode::INDEPENDENT,
+            ExecutionMode::Sequential => ipc::ExecuteRequest_ExecutionMode::SEQUENTIAL,
+            ExecutionMode::Parallel => ipc::ExecuteRequest_ExecutionMode::PARALLEL,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request_with_proposer(proposer: Vec<u8>) -> ipc::ExecuteRequest {
+        let mut request = ipc::ExecuteRequest::new();
+        request.set_parent_state_hash(vec![1u8; BLAKE2B_DIGEST_LENGTH]);
+        request.set_proposer(proposer);
+        request
+    }
+
+    #[test]
+    fn should_map_proposer() {
+        let proposer = AccountHash::new([2u8; 32]);
+        let request = ExecuteRequest::try_from(request_with_proposer(proposer.as_bytes().to_vec()))
+            .expect("should map request");
+        assert_eq!(request.proposer, Some(proposer));
+    }
+
+    #[test]
+    fn should_map_empty_proposer_to_none() {
+        let request =
+            ExecuteRequest::try_from(request_with_proposer(vec![])).expect("should map request");
+        assert_eq!(request.proposer, None);
+    }
+
+    #[test]
+    fn should_reject_invalid_proposer() {
+        for proposer in vec![vec![2u8; 1], vec![2u8; 31], vec![2u8; 33]] {
+            let response = ExecuteRequest::try_from(request_with_proposer(proposer.clone()))
+                .err()
+                .expect("should reject request");
+            assert_eq!(
+                response.get_invalid_proposer().get_proposer(),
+                &proposer[..]
+            );
+        }
+    }
+}
@@ -0,0 +1,35 @@
+use engine_core::runtime::trace::{HostCall, HostCallArg};
+
+use crate::engine_server::ipc;
+
+impl From<HostCallArg> for ipc::HostCallArg {
+    fn from(arg: HostCallArg) -> Self {
+        let mut pb_arg = ipc::HostCallArg::new();
+        match arg {
+            HostCallArg::I32(value) => pb_arg.set_i32(value),
+            HostCallArg::I64(value) => pb_arg.set_i64(value),
+            HostCallArg::F32(value) => pb_arg.set_f32(value),
+            HostCallArg::F64(value) => pb_arg.set_f64(value),
+            HostCallArg::Buffer { ptr, bytes } => {
+                let pb_buffer = pb_arg.mut_buffer();
+                pb_buffer.set_ptr(ptr);
+                pb_buffer.set_bytes(bytes);
+            }
+        }
+        pb_arg
+    }
+}
+
+impl From<HostCall> for ipc::HostCall {
+    fn from(host_call: HostCall) -> Self {
+        let mut pb_host_call = ipc::HostCall::new();
+        pb_host_call.set_function_index(host_call.function_index as u32);
+        pb_host_call.set_function_name(format!("{:?}", host_call.function_index));
+        pb_host_call.set_args(host_call.args.into_iter().map(Into::into).collect());
+        pb_host_call.set_gas_before(host_call.gas_before.value().into());
+        pb_host_call.set_gas_after(host_call.gas_after.value().into());
+        pb_host_call.set_base_key(host_call.base_key.into());
+        pb_host_call.set_depth(host_call.depth as u32);
+        pb_host_call
+    }
+}
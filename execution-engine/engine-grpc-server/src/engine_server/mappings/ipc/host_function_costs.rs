@@ -0,0 +1,199 @@
+use engine_wasm_prep::host_function_costs::{HostFunctionCost, HostFunctionCosts};
+
+use crate::engine_server::ipc::{
+    ChainSpec_CostTable_HostFunctionCost, ChainSpec_CostTable_HostFunctionCosts,
+};
+
+impl From<HostFunctionCost> for ChainSpec_CostTable_HostFunctionCost {
+    fn from(host_function_cost: HostFunctionCost) -> Self {
+        ChainSpec_CostTable_HostFunctionCost {
+            base: host_function_cost.base,
+            per_byte: host_function_cost.per_byte,
+            ..Default::default()
+        }
+    }
+}
+
+impl From<ChainSpec_CostTable_HostFunctionCost> for HostFunctionCost {
+    fn from(pb_host_function_cost: ChainSpec_CostTable_HostFunctionCost) -> Self {
+        HostFunctionCost {
+            base: pb_host_function_cost.base,
+            per_byte: pb_host_function_cost.per_byte,
+        }
+    }
+}
+
+impl From<HostFunctionCosts> for ChainSpec_CostTable_HostFunctionCosts {
+    fn from(host_function_costs: HostFunctionCosts) -> Self {
+        let mut pb_host_function_costs = ChainSpec_CostTable_HostFunctionCosts::new();
+        pb_host_function_costs.set_read_value(host_function_costs.read_value.into());
+        pb_host_function_costs.set_read_value_local(host_function_costs.read_value_local.into());
+        pb_host_function_costs.set_load_named_keys(host_function_costs.load_named_keys.into());
+        pb_host_function_costs.set_write(host_function_costs.write.into());
+        pb_host_function_costs.set_write_local(host_function_costs.write_local.into());
+        pb_host_function_costs.set_add(host_function_costs.add.into());
+        pb_host_function_costs.set_new_uref(host_function_costs.new_uref.into());
+        pb_host_function_costs.set_ret(host_function_costs.ret.into());
+        pb_host_function_costs.set_get_key(host_function_costs.get_key.into());
+        pb_host_function_costs.set_has_key(host_function_costs.has_key.into());
+        pb_host_function_costs.set_put_key(host_function_costs.put_key.into());
+        pb_host_function_costs.set_remove_key(host_function_costs.remove_key.into());
+        pb_host_function_costs.set_is_valid_uref(host_function_costs.is_valid_uref.into());
+        pb_host_function_costs.set_revert(host_function_costs.revert.into());
+        pb_host_function_costs
+            .set_add_associated_key(host_function_costs.add_associated_key.into());
+        pb_host_function_costs
+            .set_remove_associated_key(host_function_costs.remove_associated_key.into());
+        pb_host_function_costs
+            .set_update_associated_key(host_function_costs.update_associated_key.into());
+        pb_host_function_costs
+            .set_set_action_threshold(host_function_costs.set_action_threshold.into());
+        pb_host_function_costs.set_get_caller(host_function_costs.get_caller.into());
+        pb_host_function_costs.set_get_blocktime(host_function_costs.get_blocktime.into());
+        pb_host_function_costs.set_create_purse(host_function_costs.create_purse.into());
+        pb_host_function_costs
+            .set_transfer_to_account(host_function_costs.transfer_to_account.into());
+        pb_host_function_costs.set_transfer_from_purse_to_account(
+            host_function_costs.transfer_from_purse_to_account.into(),
+        );
+        pb_host_function_costs.set_transfer_from_purse_to_purse(
+            host_function_costs.transfer_from_purse_to_purse.into(),
+        );
+        pb_host_function_costs.set_get_balance(host_function_costs.get_balance.into());
+        pb_host_function_costs.set_get_phase(host_function_costs.get_phase.into());
+        pb_host_function_costs
+            .set_get_system_contract(host_function_costs.get_system_contract.into());
+        pb_host_function_costs.set_get_main_purse(host_function_costs.get_main_purse.into());
+        pb_host_function_costs.set_read_host_buffer(host_function_costs.read_host_buffer.into());
+        pb_host_function_costs.set_create_contract_package_at_hash(
+            host_function_costs.create_contract_package_at_hash.into(),
+        );
+        pb_host_function_costs
+            .set_create_contract_user_group(host_function_costs.create_contract_user_group.into());
+        pb_host_function_costs
+            .set_add_contract_version(host_function_costs.add_contract_version.into());
+        pb_host_function_costs
+            .set_disable_contract_version(host_function_costs.disable_contract_version.into());
+        pb_host_function_costs.set_call_contract(host_function_costs.call_contract.into());
+        pb_host_function_costs
+            .set_call_versioned_contract(host_function_costs.call_versioned_contract.into());
+        pb_host_function_costs
+            .set_get_named_arg_size(host_function_costs.get_named_arg_size.into());
+        pb_host_function_costs.set_get_named_arg(host_function_costs.get_named_arg.into());
+        pb_host_function_costs
+            .set_remove_contract_user_group(host_function_costs.remove_contract_user_group.into());
+        pb_host_function_costs.set_provision_contract_user_group_uref(
+            host_function_costs
+                .provision_contract_user_group_uref
+                .into(),
+        );
+        pb_host_function_costs.set_remove_contract_user_group_urefs(
+            host_function_costs.remove_contract_user_group_urefs.into(),
+        );
+        pb_host_function_costs.set_prune(host_function_costs.prune.into());
+        pb_host_function_costs.set_emit_event(host_function_costs.emit_event.into());
+        pb_host_function_costs.set_blake2b(host_function_costs.blake2b.into());
+        pb_host_function_costs.set_ed25519_verify(host_function_costs.ed25519_verify.into());
+        pb_host_function_costs.set_secp256k1_verify(host_function_costs.secp256k1_verify.into());
+        pb_host_function_costs.set_random_bytes(host_function_costs.random_bytes.into());
+        pb_host_function_costs.set_get_deploy_hash(host_function_costs.get_deploy_hash.into());
+        pb_host_function_costs.set_get_block_height(host_function_costs.get_block_height.into());
+        pb_host_function_costs.set_get_proposer(host_function_costs.get_proposer.into());
+        pb_host_function_costs
+            .set_get_parent_state_hash(host_function_costs.get_parent_state_hash.into());
+        pb_host_function_costs
+    }
+}
+
+impl From<ChainSpec_CostTable_HostFunctionCosts> for HostFunctionCosts {
+    fn from(mut pb_host_function_costs: ChainSpec_CostTable_HostFunctionCosts) -> Self {
+        HostFunctionCosts {
+            read_value: pb_host_function_costs.take_read_value().into(),
+            read_value_local: pb_host_function_costs.take_read_value_local().into(),
+            load_named_keys: pb_host_function_costs.take_load_named_keys().into(),
+            write: pb_host_function_costs.take_write().into(),
+            write_local: pb_host_function_costs.take_write_local().into(),
+            add: pb_host_function_costs.take_add().into(),
+            new_uref: pb_host_function_costs.take_new_uref().into(),
+            ret: pb_host_function_costs.take_ret().into(),
+            get_key: pb_host_function_costs.take_get_key().into(),
+            has_key: pb_host_function_costs.take_has_key().into(),
+            put_key: pb_host_function_costs.take_put_key().into(),
+            remove_key: pb_host_function_costs.take_remove_key().into(),
+            is_valid_uref: pb_host_function_costs.take_is_valid_uref().into(),
+            revert: pb_host_function_costs.take_revert().into(),
+            add_associated_key: pb_host_function_costs.take_add_associated_key().into(),
+            remove_associated_key: pb_host_function_costs.take_remove_associated_key().into(),
+            update_associated_key: pb_host_function_costs.take_update_associated_key().into(),
+            set_action_threshold: pb_host_function_costs.take_set_action_threshold().into(),
+            get_caller: pb_host_function_costs.take_get_caller().into(),
+            get_blocktime: pb_host_function_costs.take_get_blocktime().into(),
+            create_purse: pb_host_function_costs.take_create_purse().into(),
+            transfer_to_account: pb_host_function_costs.take_transfer_to_account().into(),
+            transfer_from_purse_to_account: pb_host_function_costs
+                .take_transfer_from_purse_to_account()
+                .into(),
+            transfer_from_purse_to_purse: pb_host_function_costs
+                .take_transfer_from_purse_to_purse()
+                .into(),
+            get_balance: pb_host_function_costs.take_get_balance().into(),
+            get_phase: pb_host_function_costs.take_get_phase().into(),
+            get_system_contract: pb_host_function_costs.take_get_system_contract().into(),
+            get_main_purse: pb_host_function_costs.take_get_main_purse().into(),
+            read_host_buffer: pb_host_function_costs.take_read_host_buffer().into(),
+            create_contract_package_at_hash: pb_host_function_costs
+                .take_create_contract_package_at_hash()
+                .into(),
+            create_contract_user_group: pb_host_function_costs
+                .take_create_contract_user_group()
+                .into(),
+            add_contract_version: pb_host_function_costs.take_add_contract_version().into(),
+            disable_contract_version: pb_host_function_costs
+                .take_disable_contract_version()
+                .into(),
+            call_contract: pb_host_function_costs.take_call_contract().into(),
+            call_versioned_contract: pb_host_function_costs.take_call_versioned_contract().into(),
+            get_named_arg_size: pb_host_function_costs.take_get_named_arg_size().into(),
+            get_named_arg: pb_host_function_costs.take_get_named_arg().into(),
+            remove_contract_user_group: pb_host_function_costs
+                .take_remove_contract_user_group()
+                .into(),
+            provision_contract_user_group_uref: pb_host_function_costs
+                .take_provision_contract_user_group_uref()
+                .into(),
+            remove_contract_user_group_urefs: pb_host_function_costs
+                .take_remove_contract_user_group_urefs()
+                .into(),
+            prune: pb_host_function_costs.take_prune().into(),
+            emit_event: pb_host_function_costs.take_emit_event().into(),
+            blake2b: pb_host_function_costs.take_blake2b().into(),
+            ed25519_verify: pb_host_function_costs.take_ed25519_verify().into(),
+            secp256k1_verify: pb_host_function_costs.take_secp256k1_verify().into(),
+            random_bytes: pb_host_function_costs.take_random_bytes().into(),
+            get_deploy_hash: pb_host_function_costs.take_get_deploy_hash().into(),
+            get_block_height: pb_host_function_costs.take_get_block_height().into(),
+            get_proposer: pb_host_function_costs.take_get_proposer().into(),
+            get_parent_state_hash: pb_host_function_costs.take_get_parent_state_hash().into(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::proptest;
+
+    use engine_wasm_prep::host_function_costs::gens;
+
+    use super::*;
+    use crate::engine_server::mappings::test_utils;
+
+    proptest! {
+        #[test]
+        fn round_trip(host_function_costs in gens::host_function_costs_arb()) {
+            test_utils::protobuf_round_trip::<
+                HostFunctionCosts,
+                ChainSpec_CostTable_HostFunctionCosts,
+            >(host_function_costs);
+        }
+    }
+}
@@ -30,6 +30,8 @@ impl TryFrom<ipc::QueryRequest> for QueryRequest {
 
         let path = query_request.take_path().into_vec();
 
-        Ok(QueryRequest::new(state_hash, key, path))
+        let include_proofs = query_request.get_include_proofs();
+
+        Ok(QueryRequest::new(state_hash, key, path, include_proofs))
     }
 }
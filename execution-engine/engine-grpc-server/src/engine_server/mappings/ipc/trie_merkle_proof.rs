@@ -0,0 +1,18 @@
+use std::convert::TryFrom;
+
+use engine_shared::stored_value::StoredValue;
+use engine_storage::trie::TrieMerkleProof;
+use types::{bytesrepr, Key};
+
+use crate::engine_server::ipc;
+
+impl TryFrom<TrieMerkleProof<Key, StoredValue>> for ipc::TrieMerkleProof {
+    type Error = bytesrepr::Error;
+
+    fn try_from(proof: TrieMerkleProof<Key, StoredValue>) -> Result<Self, Self::Error> {
+        let mut pb_proof = ipc::TrieMerkleProof::new();
+        pb_proof.set_key((*proof.key()).into());
+        pb_proof.set_nodes(proof.to_serialized_nodes()?.into());
+        Ok(pb_proof)
+    }
+}
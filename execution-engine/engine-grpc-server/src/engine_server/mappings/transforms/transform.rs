@@ -32,6 +32,9 @@ impl From<Transform> for transforms::Transform {
                 let pb_named_keys: Vec<NamedKey> = NamedKeyMap::new(keys_map).into();
                 pb_transform.mut_add_keys().set_value(pb_named_keys.into());
             }
+            Transform::Prune => {
+                pb_transform.set_prune(Default::default());
+            }
             Transform::Failure(transform_error) => pb_transform.set_failure(transform_error.into()),
             Transform::AddUInt128(uint128) => {
                 pb_transform.mut_add_big_int().set_value(uint128.into());
@@ -95,6 +98,7 @@ impl TryFrom<transforms::Transform> for Transform {
                 let value = StoredValue::try_from(pb_write.take_value())?;
                 Transform::Write(value)
             }
+            Transform_oneof_transform_instance::prune(_) => Transform::Prune,
             Transform_oneof_transform_instance::failure(pb_failure) => {
                 let error = TransformError::try_from(pb_failure)?;
                 Transform::Failure(error)
@@ -29,39 +29,48 @@ use std::{
 use grpc::{Error as GrpcError, RequestOptions, ServerBuilder, SingleResponse};
 use log::{info, warn, Level};
 
-use engine_core::engine_state::{
-    execute_request::ExecuteRequest,
-    genesis::GenesisResult,
-    query::{QueryRequest, QueryResult},
-    run_genesis_request::RunGenesisRequest,
-    upgrade::{UpgradeConfig, UpgradeResult},
-    EngineState, Error as EngineError,
+use engine_core::{
+    engine_state::{
+        estimate::{EstimateRequest, EstimateResult},
+        execute_request::{ExecuteRequest, ExecutionMode, SequentialExecutionResult},
+        genesis::GenesisResult,
+        query::{QueryRequest, QueryResult},
+        run_genesis_request::RunGenesisRequest,
+        upgrade::{UpgradeConfig, UpgradeResult},
+        EngineState, Error as EngineError,
+    },
+    runtime::trace::ExecutionTrace,
 };
 use engine_shared::{
     logging::{self, log_duration},
     newtypes::{Blake2bHash, CorrelationId},
 };
-use engine_storage::global_state::{CommitResult, StateProvider};
+use engine_storage::global_state::{CommitManyResult, CommitResult, StateProvider};
 use types::{bytesrepr::ToBytes, ProtocolVersion};
 
 use self::{
     ipc::{
-        BidStateRequest, BidStateResponse, CommitRequest, CommitResponse, DistributeRewardsRequest,
-        DistributeRewardsResponse, ExecuteResponse, GenesisResponse, QueryResponse, SlashRequest,
-        SlashResponse, UnbondPayoutRequest, UnbondPayoutResponse, UpgradeRequest, UpgradeResponse,
+        BidStateRequest, BidStateResponse, CommitManyRequest, CommitManyResponse, CommitRequest,
+        CommitResponse, DistributeRewardsRequest, DistributeRewardsResponse, EstimateResponse,
+        ExecuteResponse, GenesisResponse, QueryResponse, SlashRequest, SlashResponse,
+        UnbondPayoutRequest, UnbondPayoutResponse, UpgradeRequest, UpgradeResponse,
     },
     ipc_grpc::{ExecutionEngineService, ExecutionEngineServiceServer},
     mappings::{ParsingError, TransformMap},
 };
 
 const METRIC_DURATION_COMMIT: &str = "commit_duration";
+const METRIC_DURATION_COMMIT_MANY: &str = "commit_many_duration";
 const METRIC_DURATION_EXEC: &str = "exec_duration";
+const METRIC_DURATION_ESTIMATE: &str = "estimate_duration";
 const METRIC_DURATION_QUERY: &str = "query_duration";
 const METRIC_DURATION_GENESIS: &str = "genesis_duration";
 const METRIC_DURATION_UPGRADE: &str = "upgrade_duration";
 
 const TAG_RESPONSE_COMMIT: &str = "commit_response";
+const TAG_RESPONSE_COMMIT_MANY: &str = "commit_many_response";
 const TAG_RESPONSE_EXEC: &str = "exec_response";
+const TAG_RESPONSE_ESTIMATE: &str = "estimate_response";
 const TAG_RESPONSE_QUERY: &str = "query_response";
 const TAG_RESPONSE_GENESIS: &str = "genesis_response";
 const TAG_RESPONSE_UPGRADE: &str = "upgrade_response";
@@ -77,7 +86,7 @@ const DEFAULT_PROTOCOL_VERSION: ProtocolVersion = ProtocolVersion::V1_0_0;
 // (outer layer) leading to cleaner design.
 impl<S> ExecutionEngineService for EngineState<S>
 where
-    S: StateProvider,
+    S: StateProvider + Sync,
     EngineError: From<S::Error>,
     S::Error: Into<engine_core::execution::Error> + Debug,
 {
@@ -109,18 +118,26 @@ where
         let result = self.run_query(correlation_id, request);
 
         let response = match result {
-            Ok(QueryResult::Success(value)) => {
+            Ok(QueryResult::Success { value, proofs }) => {
                 let mut result = ipc::QueryResponse::new();
-                match value.to_bytes() {
-                    Ok(serialized_value) => {
+                let maybe_proofs: Result<Vec<ipc::TrieMerkleProof>, _> =
+                    proofs.into_iter().map(TryInto::try_into).collect();
+                match (value.to_bytes(), maybe_proofs) {
+                    (Ok(serialized_value), Ok(pb_proofs)) => {
                         info!("query successful; correlation_id: {}", correlation_id);
                         result.set_success(serialized_value);
+                        result.set_proofs(pb_proofs.into());
                     }
-                    Err(error_msg) => {
+                    (Err(error_msg), _) => {
                         let log_message = format!("Failed to serialize StoredValue: {}", error_msg);
                         warn!("{}", log_message);
                         result.set_failure(log_message);
                     }
+                    (_, Err(error_msg)) => {
+                        let log_message = format!("Failed to serialize proof: {}", error_msg);
+                        warn!("{}", log_message);
+                        result.set_failure(log_message);
+                    }
                 }
                 result
             }
@@ -170,6 +187,8 @@ where
         let start = Instant::now();
         let correlation_id = CorrelationId::new();
 
+        let trace_host_calls = exec_request.get_trace_host_calls();
+
         let exec_request: ExecuteRequest = match exec_request.try_into() {
             Ok(ret) => ret,
             Err(err) => {
@@ -179,7 +198,65 @@ where
 
         let mut exec_response = ExecuteResponse::new();
 
-        let results = match self.run_execute(correlation_id, exec_request) {
+        if exec_request.execution_mode != ExecutionMode::Independent {
+            let parent_state_hash = exec_request.parent_state_hash;
+            let sequential_execution_result = match exec_request.execution_mode {
+                ExecutionMode::Parallel => self.run_execute_parallel(correlation_id, exec_request),
+                _ => self.run_execute_sequential(correlation_id, exec_request),
+            };
+            match sequential_execution_result {
+                Ok(SequentialExecutionResult::Success {
+                    execution_results,
+                    effect,
+                    post_state_hash,
+                    ..
+                }) => {
+                    info!("sequential execution successful: {}", post_state_hash);
+                    let protobuf_results_iter = execution_results.into_iter().map(Into::into);
+                    let exec_result = exec_response.mut_success();
+                    exec_result.set_deploy_results(FromIterator::from_iter(protobuf_results_iter));
+                    exec_result.set_effect(effect.into());
+                    exec_result.set_poststate_hash(post_state_hash.to_vec());
+                }
+                Ok(SequentialExecutionResult::RootNotFound) => {
+                    info!("deploy results error: RootNotFound");
+                    exec_response
+                        .mut_missing_parent()
+                        .set_hash(parent_state_hash.to_vec());
+                }
+                Ok(sequential_execution_result) => {
+                    let err_msg = sequential_execution_result.to_string();
+                    warn!("{}", err_msg);
+                    exec_response.mut_failed_commit().set_message(err_msg);
+                }
+                Err(error) => {
+                    let err_msg = error.to_string();
+                    warn!("{}", err_msg);
+                    exec_response.mut_failed_commit().set_message(err_msg);
+                }
+            }
+            log_duration(
+                correlation_id,
+                METRIC_DURATION_EXEC,
+                TAG_RESPONSE_EXEC,
+                start.elapsed(),
+            );
+            return SingleResponse::completed(exec_response);
+        }
+
+        let results = if trace_host_calls {
+            self.run_execute_traced(correlation_id, exec_request)
+        } else {
+            self.run_execute(correlation_id, exec_request)
+                .map(|results| {
+                    results
+                        .into_iter()
+                        .map(|result| (result, ExecutionTrace::new()))
+                        .collect()
+                })
+        };
+
+        let results = match results {
             Ok(results) => results,
             Err(error) => {
                 info!("deploy results error: RootNotFound");
@@ -207,6 +284,81 @@ where
         SingleResponse::completed(exec_response)
     }
 
+    fn estimate(
+        &self,
+        _request_options: RequestOptions,
+        estimate_request: ipc::EstimateRequest,
+    ) -> SingleResponse<EstimateResponse> {
+        let start = Instant::now();
+        let correlation_id = CorrelationId::new();
+
+        let parent_state_hash = estimate_request.get_parent_state_hash().to_vec();
+
+        let request: EstimateRequest = match estimate_request.try_into() {
+            Ok(ret) => ret,
+            Err(err) => {
+                let log_message = format!("{:?}", err);
+                warn!("{}", log_message);
+                let mut result = EstimateResponse::new();
+                result.set_failure(log_message);
+                log_duration(
+                    correlation_id,
+                    METRIC_DURATION_ESTIMATE,
+                    TAG_RESPONSE_ESTIMATE,
+                    start.elapsed(),
+                );
+                return SingleResponse::completed(result);
+            }
+        };
+
+        let mut response = EstimateResponse::new();
+
+        match self.estimate(correlation_id, request) {
+            Ok(EstimateResult::Success {
+                session_cost,
+                payment,
+                effect,
+                revert,
+            }) => {
+                info!("estimate successful; correlation_id: {}", correlation_id);
+                let result = response.mut_success();
+                result.set_session_cost(session_cost.value().into());
+                result.set_payment(payment.value().into());
+                result.set_effect(effect.into());
+                if let Some(api_error) = revert {
+                    result.mut_revert().set_api_error(api_error.into());
+                }
+            }
+            Ok(EstimateResult::RootNotFound) => {
+                info!("estimate error: RootNotFound");
+                response.mut_missing_parent().set_hash(parent_state_hash);
+            }
+            Ok(EstimateResult::Failure(error)) => {
+                let log_message = error.to_string();
+                info!("{}", log_message);
+                response.set_failure(log_message);
+            }
+            Ok(EstimateResult::GasLimitExceeded(gas_limit)) => {
+                info!("estimate error: gas limit of {} exceeded", gas_limit);
+                response.set_gas_limit_exceeded(gas_limit.value().into());
+            }
+            Err(error) => {
+                let log_message = error.to_string();
+                warn!("{}", log_message);
+                response.set_failure(log_message);
+            }
+        }
+
+        log_duration(
+            correlation_id,
+            METRIC_DURATION_ESTIMATE,
+            TAG_RESPONSE_ESTIMATE,
+            start.elapsed(),
+        );
+
+        SingleResponse::completed(response)
+    }
+
     fn commit(
         &self,
         _request_options: RequestOptions,
@@ -315,6 +467,130 @@ where
         SingleResponse::completed(commit_response)
     }
 
+    fn commit_many(
+        &self,
+        _request_options: RequestOptions,
+        mut commit_many_request: CommitManyRequest,
+    ) -> SingleResponse<CommitManyResponse> {
+        let start = Instant::now();
+        let correlation_id = CorrelationId::new();
+
+        let protocol_version = {
+            let protocol_version = commit_many_request.take_protocol_version().into();
+            if protocol_version < DEFAULT_PROTOCOL_VERSION {
+                DEFAULT_PROTOCOL_VERSION
+            } else {
+                protocol_version
+            }
+        };
+
+        // Acquire pre-state hash
+        let pre_state_hash: Blake2bHash = match commit_many_request.get_prestate_hash().try_into() {
+            Err(_) => {
+                let error_message = "Could not parse pre-state hash".to_string();
+                warn!("{}", error_message);
+                let mut commit_many_response = CommitManyResponse::new();
+                commit_many_response
+                    .mut_failed_transform()
+                    .set_message(error_message);
+                return SingleResponse::completed(commit_many_response);
+            }
+            Ok(hash) => hash,
+        };
+
+        // Acquire each set of commit transforms
+        let mut effects = Vec::with_capacity(commit_many_request.get_effects().len());
+        for (index, mut pb_effects) in commit_many_request.take_effects().into_iter().enumerate() {
+            match TransformMap::try_from(pb_effects.take_effects().into_vec()) {
+                Err(ParsingError(error_message)) => {
+                    warn!("{}", error_message);
+                    let mut commit_many_response = CommitManyResponse::new();
+                    commit_many_response
+                        .mut_failed_transform()
+                        .set_message(error_message);
+                    commit_many_response.set_failed_index(index as u32);
+                    return SingleResponse::completed(commit_many_response);
+                }
+                Ok(transforms) => effects.push(transforms.into_inner()),
+            }
+        }
+
+        // "Apply" each set of effects to global state
+        let commit_many_response = {
+            let mut ret = CommitManyResponse::new();
+
+            match self.apply_many_effects(correlation_id, protocol_version, pre_state_hash, effects)
+            {
+                Ok(CommitManyResult::Success {
+                    state_roots,
+                    bonded_validators,
+                }) => {
+                    let properties = {
+                        let mut tmp = BTreeMap::new();
+                        tmp.insert("post-state-hashes", format!("{:?}", state_roots));
+                        tmp.insert("success", true.to_string());
+                        tmp
+                    };
+                    logging::log_details(
+                        Level::Info,
+                        "effects applied; new state hashes are: {post-state-hashes}".to_owned(),
+                        properties,
+                    );
+
+                    let post_state_hashes = state_roots
+                        .into_iter()
+                        .map(|state_root| state_root.to_vec())
+                        .collect();
+                    let bonds = bonded_validators.into_iter().map(Into::into).collect();
+                    let commit_many_result = ret.mut_success();
+                    commit_many_result.set_poststate_hashes(post_state_hashes);
+                    commit_many_result.set_bonded_validators(bonds);
+                }
+                Ok(CommitManyResult::Failure { index, result }) => {
+                    match result {
+                        CommitResult::RootNotFound => {
+                            warn!("RootNotFound");
+                            ret.mut_missing_prestate().set_hash(pre_state_hash.to_vec());
+                        }
+                        CommitResult::KeyNotFound(key) => {
+                            warn!("{:?} not found in effects {}", key, index);
+                            ret.set_key_not_found(key.into());
+                        }
+                        CommitResult::TypeMismatch(type_mismatch) => {
+                            warn!("{:?} in effects {}", type_mismatch, index);
+                            ret.set_type_mismatch(type_mismatch.into());
+                        }
+                        CommitResult::Serialization(error) => {
+                            warn!("{:?} in effects {}", error, index);
+                            ret.mut_failed_transform()
+                                .set_message(format!("{:?}", error));
+                        }
+                        CommitResult::Success { .. } => {
+                            unreachable!("a successful commit is not a failure")
+                        }
+                    }
+                    ret.set_failed_index(index as u32);
+                }
+                Err(error) => {
+                    warn!("State error {:?} when applying transforms", error);
+                    ret.mut_failed_transform()
+                        .set_message(format!("{:?}", error));
+                }
+            }
+
+            ret
+        };
+
+        log_duration(
+            correlation_id,
+            METRIC_DURATION_COMMIT_MANY,
+            TAG_RESPONSE_COMMIT_MANY,
+            start.elapsed(),
+        );
+
+        SingleResponse::completed(commit_many_response)
+    }
+
     fn run_genesis(
         &self,
         _request_options: RequestOptions,
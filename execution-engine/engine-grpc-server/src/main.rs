@@ -1,7 +1,9 @@
 use std::{
     collections::BTreeMap,
+    convert::TryFrom,
     fs,
     path::PathBuf,
+    process,
     str::FromStr,
     sync::{
         atomic::{AtomicBool, Ordering},
@@ -10,7 +12,7 @@ use std::{
     time::Duration,
 };
 
-use clap::{App, Arg, ArgMatches};
+use clap::{App, AppSettings, Arg, ArgMatches, SubCommand};
 use dirs::home_dir;
 use engine_core::engine_state::{EngineConfig, EngineState};
 use lmdb::DatabaseFlags;
@@ -18,16 +20,29 @@ use log::{error, info, Level, LevelFilter};
 
 use engine_shared::{
     logging::{self, Settings, Style},
+    newtypes::{Blake2bHash, CorrelationId},
     os::get_page_size,
     socket,
 };
 use engine_storage::{
-    global_state::lmdb::LmdbGlobalState, transaction_source::lmdb::LmdbEnvironment,
-    trie_store::lmdb::LmdbTrieStore,
+    global_state::{lmdb::LmdbGlobalState, sled::SledGlobalState, StartPruneResult},
+    prune_store::{lmdb::LmdbPruneStore, sled::SledPruneStore},
+    transaction_source::{lmdb::LmdbEnvironment, sled::SledEnvironment},
+    trie_store::{
+        compression::{Compression, CompressionAlgorithm},
+        lmdb::LmdbTrieStore,
+        operations::{
+            diff::DiffResult,
+            stats::{self, StatsResult},
+        },
+        sled::SledTrieStore,
+    },
 };
 
 use casperlabs_engine_grpc_server::engine_server;
-use engine_storage::protocol_data_store::lmdb::LmdbProtocolDataStore;
+use engine_storage::protocol_data_store::{
+    lmdb::LmdbProtocolDataStore, sled::SledProtocolDataStore,
+};
 
 // exe / proc
 const PROC_NAME: &str = "casperlabs-engine-grpc-server";
@@ -49,16 +64,90 @@ const LMDB_TRIE_STORE_EXPECT: &str = "Could not create LmdbTrieStore";
 const LMDB_PROTOCOL_DATA_STORE_EXPECT: &str = "Could not create LmdbProtocolDataStore";
 const LMDB_GLOBAL_STATE_EXPECT: &str = "Could not create LmdbGlobalState";
 
+// storage backend
+const ARG_STORAGE_BACKEND: &str = "storage-backend";
+const ARG_STORAGE_BACKEND_VALUE: &str = "BACKEND";
+const ARG_STORAGE_BACKEND_HELP: &str =
+    "Sets the database used to store global state.  Unlike lmdb, sled does not read from a \
+     snapshot, so a read may see commits made while it is in progress";
+const STORAGE_BACKEND_LMDB: &str = "lmdb";
+const STORAGE_BACKEND_SLED: &str = "sled";
+const SLED_DIR: &str = "sled";
+const SLED_ENVIRONMENT_EXPECT: &str = "Could not create SledEnvironment";
+const SLED_TRIE_STORE_EXPECT: &str = "Could not create SledTrieStore";
+const SLED_PROTOCOL_DATA_STORE_EXPECT: &str = "Could not create SledProtocolDataStore";
+const SLED_GLOBAL_STATE_EXPECT: &str = "Could not create SledGlobalState";
+
 // pages / lmdb
 const ARG_PAGES: &str = "pages";
 const ARG_PAGES_SHORT: &str = "p";
 const ARG_PAGES_VALUE: &str = "NUM";
-const ARG_PAGES_HELP: &str = "Sets the max number of pages to use for lmdb's mmap";
+const ARG_PAGES_HELP: &str =
+    "Sets the max number of pages to use for lmdb's mmap.  Ignored by the sled backend.";
 const GET_PAGES_EXPECT: &str = "Could not parse pages argument";
 // 750 GiB = 805306368000 bytes
 // page size on x86_64 linux = 4096 bytes
 // 805306368000 / 4096 = 196608000
 const DEFAULT_PAGES: usize = 196_608_000;
+const ARG_MAX_PAGES: &str = "max-pages";
+const ARG_MAX_PAGES_VALUE: &str = "NUM";
+const ARG_MAX_PAGES_HELP: &str =
+    "Lets lmdb's mmap grow when it is full, up to this max number of pages.  By default it can't \
+     grow beyond --pages.  Ignored by the sled backend.";
+const GET_MAX_PAGES_EXPECT: &str = "Could not parse max-pages argument";
+const ARG_MAP_GROWTH_FACTOR: &str = "map-growth-factor";
+const ARG_MAP_GROWTH_FACTOR_VALUE: &str = "FACTOR";
+const ARG_MAP_GROWTH_FACTOR_HELP: &str =
+    "Sets the factor by which lmdb's mmap grows each time it is full, up to --max-pages";
+const GET_MAP_GROWTH_FACTOR_EXPECT: &str =
+    "Could not parse map-growth-factor argument as a number greater than 1";
+const DEFAULT_MAP_GROWTH_FACTOR: &str = "2";
+
+// trie cache
+const ARG_TRIE_CACHE_SIZE: &str = "trie-cache-size";
+const ARG_TRIE_CACHE_SIZE_VALUE: &str = "MIB";
+const ARG_TRIE_CACHE_SIZE_HELP: &str =
+    "Sets the size in MiB of the cache of decoded trie nodes shared by all readers of global \
+     state.  0 disables it.  Ignored by the sled backend.";
+const ARG_TRIE_CACHE_SIZE_EXPECT: &str = "Could not parse trie-cache-size argument";
+const DEFAULT_TRIE_CACHE_SIZE: &str = "128";
+const MIB: usize = 1024 * 1024;
+
+// trie compression
+const ARG_TRIE_COMPRESSION: &str = "trie-compression";
+const ARG_TRIE_COMPRESSION_VALUE: &str = "ALGORITHM";
+const ARG_TRIE_COMPRESSION_HELP: &str =
+    "Compresses the large trie leaves written to global state.  Leaves written before are only \
+     compressed by the recompress subcommand.  Ignored by the sled backend.";
+const TRIE_COMPRESSION_NONE: &str = "none";
+const TRIE_COMPRESSION_LZ4: &str = "lz4";
+const TRIE_COMPRESSION_ZSTD: &str = "zstd";
+const ARG_TRIE_COMPRESSION_THRESHOLD: &str = "trie-compression-threshold";
+const ARG_TRIE_COMPRESSION_THRESHOLD_VALUE: &str = "BYTES";
+const ARG_TRIE_COMPRESSION_THRESHOLD_HELP: &str =
+    "Sets the serialized size in bytes from which trie leaves are compressed";
+const ARG_TRIE_COMPRESSION_THRESHOLD_EXPECT: &str =
+    "Could not parse trie-compression-threshold argument";
+const DEFAULT_TRIE_COMPRESSION_THRESHOLD: &str = "1024";
+
+// pruning
+const ARG_PRUNE_RETAINED_ROOTS: &str = "prune-retained-roots";
+const ARG_PRUNE_RETAINED_ROOTS_VALUE: &str = "ROOT";
+const ARG_PRUNE_RETAINED_ROOTS_HELP: &str =
+    "Starts a prune of the tries which are not reachable from the given hex-encoded state roots, \
+     from the empty root or from the roots committed while it is in progress.  May be given more \
+     than once.  The prune is carried out a step at a time by the commits made while the server \
+     is running, and a prune which was interrupted carries on after a restart.  Commits should \
+     only be made on top of retained roots until it is complete";
+const ARG_PRUNE_BATCH_SIZE: &str = "prune-batch-size";
+const ARG_PRUNE_BATCH_SIZE_VALUE: &str = "NUM";
+const ARG_PRUNE_BATCH_SIZE_HELP: &str =
+    "Sets the number of tries marked or swept by each commit made while a prune is in progress";
+const ARG_PRUNE_BATCH_SIZE_EXPECT: &str = "Could not parse prune-batch-size argument";
+const DEFAULT_PRUNE_BATCH_SIZE: &str = "1000";
+const LMDB_PRUNE_STORE_EXPECT: &str = "Could not create LmdbPruneStore";
+const SLED_PRUNE_STORE_EXPECT: &str = "Could not create SledPruneStore";
+const START_PRUNE_EXPECT: &str = "Could not start prune";
 
 // socket
 const ARG_SOCKET: &str = "socket";
@@ -96,6 +185,22 @@ const ARG_THREAD_COUNT_VALUE: &str = "NUM";
 const ARG_THREAD_COUNT_HELP: &str = "Worker thread count";
 const ARG_THREAD_COUNT_EXPECT: &str = "expected valid thread count";
 
+// execution thread count
+const ARG_EXECUTION_THREAD_COUNT: &str = "execution-threads";
+const ARG_EXECUTION_THREAD_COUNT_DEFAULT: &str = "4";
+const ARG_EXECUTION_THREAD_COUNT_VALUE: &str = "NUM";
+const ARG_EXECUTION_THREAD_COUNT_HELP: &str =
+    "Number of threads used to execute the deploys of a request in parallel execution mode";
+const ARG_EXECUTION_THREAD_COUNT_EXPECT: &str = "expected valid execution thread count";
+
+// estimate gas limit
+const ARG_ESTIMATE_GAS_LIMIT: &str = "estimate-gas-limit";
+const ARG_ESTIMATE_GAS_LIMIT_DEFAULT: &str = "100000000000";
+const ARG_ESTIMATE_GAS_LIMIT_VALUE: &str = "GAS";
+const ARG_ESTIMATE_GAS_LIMIT_HELP: &str =
+    "Maximum gas the session code of an estimated deploy may spend before the estimate fails";
+const ARG_ESTIMATE_GAS_LIMIT_EXPECT: &str = "expected valid estimate gas limit";
+
 // use system contracts
 const ARG_USE_SYSTEM_CONTRACTS: &str = "use-system-contracts";
 const ARG_USE_SYSTEM_CONTRACTS_SHORT: &str = "z";
@@ -107,10 +212,61 @@ const ARG_ENABLE_BONDING: &str = "enable-bonding";
 const ARG_ENABLE_BONDING_SHORT: &str = "b";
 const ARG_ENABLE_BONDING_HELP: &str = "Enable bonding";
 
+// diff
+const SUBCOMMAND_DIFF: &str = "diff";
+const SUBCOMMAND_DIFF_ABOUT: &str =
+    "Prints the keys whose values differ between two global state roots, along with both values";
+const ARG_LEFT_ROOT: &str = "left-root";
+const ARG_LEFT_ROOT_HELP: &str = "Hex-encoded hash of the state root to compare from";
+const ARG_RIGHT_ROOT: &str = "right-root";
+const ARG_RIGHT_ROOT_HELP: &str = "Hex-encoded hash of the state root to compare to";
+const ARG_ROOT_EXPECT: &str = "Could not parse state root argument";
+const DIFF_EXPECT: &str = "Could not compute diff";
+const ROOT_NOT_FOUND: &str = "State root not found";
+
+// stats
+const SUBCOMMAND_STATS: &str = "stats";
+const SUBCOMMAND_STATS_ABOUT: &str =
+    "Prints statistics about the global state at a root, or compares them between two roots";
+const ARG_ROOT: &str = "root";
+const ARG_ROOT_HELP: &str = "Hex-encoded hash of the state root to gather statistics for";
+const ARG_OTHER_ROOT: &str = "other-root";
+const ARG_OTHER_ROOT_HELP: &str =
+    "Hex-encoded hash of a later state root, to show growth since the first root";
+const STATS_EXPECT: &str = "Could not compute stats";
+
+// recompress
+const SUBCOMMAND_RECOMPRESS: &str = "recompress";
+const SUBCOMMAND_RECOMPRESS_ABOUT: &str =
+    "Rewrites every stored trie with the compression set by --trie-compression, and prints how \
+     the size of the stored tries changed.  The server must not be running.";
+const RECOMPRESS_EXPECT: &str = "Could not recompress global state";
+const RECOMPRESS_SLED_UNSUPPORTED: &str = "Only lmdb global state can be recompressed";
+const RECOMPRESS_BATCH_SIZE: usize = 10_000;
+
 // runnable
 const SIGINT_HANDLE_EXPECT: &str = "Error setting Ctrl-C handler";
 const RUNNABLE_CHECK_INTERVAL_SECONDS: u64 = 3;
 
+/// The size of lmdb's mmap, and how far it may grow.
+struct LmdbMapConfig {
+    map_size: usize,
+    max_map_size: usize,
+    growth_factor: f64,
+}
+
+/// The prune to start, if any, and how far each commit carries it out.
+struct PruneConfig {
+    retained_roots: Option<Vec<Blake2bHash>>,
+    batch_size: usize,
+}
+
+/// The database used to store global state.
+enum StorageBackend {
+    Lmdb,
+    Sled,
+}
+
 fn main() {
     set_panic_hook();
 
@@ -118,6 +274,22 @@ fn main() {
 
     let _ = logging::initialize(get_log_settings(&arg_matches));
 
+    match arg_matches.subcommand() {
+        (SUBCOMMAND_DIFF, Some(diff_arg_matches)) => {
+            run_diff(&arg_matches, diff_arg_matches);
+            return;
+        }
+        (SUBCOMMAND_STATS, Some(stats_arg_matches)) => {
+            run_stats(&arg_matches, stats_arg_matches);
+            return;
+        }
+        (SUBCOMMAND_RECOMPRESS, Some(_)) => {
+            run_recompress(&arg_matches);
+            return;
+        }
+        _ => (),
+    }
+
     info!("starting Execution Engine Server");
 
     let socket = get_socket(&arg_matches);
@@ -129,13 +301,31 @@ fn main() {
 
     let data_dir = get_data_dir(&arg_matches);
 
-    let map_size = get_map_size(&arg_matches);
+    let storage_backend = get_storage_backend(&arg_matches);
+
+    let map_config = get_lmdb_map_config(&arg_matches);
+
+    let trie_cache_size = get_trie_cache_size(&arg_matches);
+
+    let trie_compression = get_trie_compression(&arg_matches);
+
+    let prune_config = get_prune_config(&arg_matches);
 
     let thread_count = get_thread_count(&arg_matches);
 
     let engine_config: EngineConfig = get_engine_config(&arg_matches);
 
-    let _server = get_grpc_server(&socket, data_dir, map_size, thread_count, engine_config);
+    let _server = get_grpc_server(
+        &socket,
+        data_dir,
+        storage_backend,
+        map_config,
+        trie_cache_size,
+        trie_compression,
+        prune_config,
+        thread_count,
+        engine_config,
+    );
 
     log_listening_message(&socket);
 
@@ -169,6 +359,7 @@ fn set_panic_hook() {
 fn get_args() -> ArgMatches<'static> {
     App::new(APP_NAME)
         .version(env!("CARGO_PKG_VERSION"))
+        .setting(AppSettings::SubcommandsNegateReqs)
         .arg(
             Arg::with_name(ARG_LOG_LEVEL)
                 .required(false)
@@ -210,6 +401,17 @@ fn get_args() -> ArgMatches<'static> {
                 .help(ARG_DATA_DIR_HELP)
                 .takes_value(true),
         )
+        .arg(
+            Arg::with_name(ARG_STORAGE_BACKEND)
+                .required(false)
+                .long(ARG_STORAGE_BACKEND)
+                .takes_value(true)
+                .possible_value(STORAGE_BACKEND_LMDB)
+                .possible_value(STORAGE_BACKEND_SLED)
+                .default_value(STORAGE_BACKEND_LMDB)
+                .value_name(ARG_STORAGE_BACKEND_VALUE)
+                .help(ARG_STORAGE_BACKEND_HELP),
+        )
         .arg(
             Arg::with_name(ARG_PAGES)
                 .short(ARG_PAGES_SHORT)
@@ -218,6 +420,61 @@ fn get_args() -> ArgMatches<'static> {
                 .help(ARG_PAGES_HELP)
                 .takes_value(true),
         )
+        .arg(
+            Arg::with_name(ARG_MAX_PAGES)
+                .long(ARG_MAX_PAGES)
+                .value_name(ARG_MAX_PAGES_VALUE)
+                .help(ARG_MAX_PAGES_HELP)
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name(ARG_MAP_GROWTH_FACTOR)
+                .long(ARG_MAP_GROWTH_FACTOR)
+                .value_name(ARG_MAP_GROWTH_FACTOR_VALUE)
+                .help(ARG_MAP_GROWTH_FACTOR_HELP)
+                .default_value(DEFAULT_MAP_GROWTH_FACTOR),
+        )
+        .arg(
+            Arg::with_name(ARG_TRIE_CACHE_SIZE)
+                .long(ARG_TRIE_CACHE_SIZE)
+                .value_name(ARG_TRIE_CACHE_SIZE_VALUE)
+                .help(ARG_TRIE_CACHE_SIZE_HELP)
+                .default_value(DEFAULT_TRIE_CACHE_SIZE),
+        )
+        .arg(
+            Arg::with_name(ARG_TRIE_COMPRESSION)
+                .long(ARG_TRIE_COMPRESSION)
+                .takes_value(true)
+                .possible_value(TRIE_COMPRESSION_NONE)
+                .possible_value(TRIE_COMPRESSION_LZ4)
+                .possible_value(TRIE_COMPRESSION_ZSTD)
+                .default_value(TRIE_COMPRESSION_NONE)
+                .value_name(ARG_TRIE_COMPRESSION_VALUE)
+                .help(ARG_TRIE_COMPRESSION_HELP),
+        )
+        .arg(
+            Arg::with_name(ARG_TRIE_COMPRESSION_THRESHOLD)
+                .long(ARG_TRIE_COMPRESSION_THRESHOLD)
+                .value_name(ARG_TRIE_COMPRESSION_THRESHOLD_VALUE)
+                .help(ARG_TRIE_COMPRESSION_THRESHOLD_HELP)
+                .default_value(DEFAULT_TRIE_COMPRESSION_THRESHOLD),
+        )
+        .arg(
+            Arg::with_name(ARG_PRUNE_RETAINED_ROOTS)
+                .long(ARG_PRUNE_RETAINED_ROOTS)
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1)
+                .value_name(ARG_PRUNE_RETAINED_ROOTS_VALUE)
+                .help(ARG_PRUNE_RETAINED_ROOTS_HELP),
+        )
+        .arg(
+            Arg::with_name(ARG_PRUNE_BATCH_SIZE)
+                .long(ARG_PRUNE_BATCH_SIZE)
+                .value_name(ARG_PRUNE_BATCH_SIZE_VALUE)
+                .help(ARG_PRUNE_BATCH_SIZE_HELP)
+                .default_value(DEFAULT_PRUNE_BATCH_SIZE),
+        )
         .arg(
             Arg::with_name(ARG_THREAD_COUNT)
                 .short(ARG_THREAD_COUNT_SHORT)
@@ -227,6 +484,22 @@ fn get_args() -> ArgMatches<'static> {
                 .value_name(ARG_THREAD_COUNT_VALUE)
                 .help(ARG_THREAD_COUNT_HELP),
         )
+        .arg(
+            Arg::with_name(ARG_EXECUTION_THREAD_COUNT)
+                .long(ARG_EXECUTION_THREAD_COUNT)
+                .takes_value(true)
+                .default_value(ARG_EXECUTION_THREAD_COUNT_DEFAULT)
+                .value_name(ARG_EXECUTION_THREAD_COUNT_VALUE)
+                .help(ARG_EXECUTION_THREAD_COUNT_HELP),
+        )
+        .arg(
+            Arg::with_name(ARG_ESTIMATE_GAS_LIMIT)
+                .long(ARG_ESTIMATE_GAS_LIMIT)
+                .takes_value(true)
+                .default_value(ARG_ESTIMATE_GAS_LIMIT_DEFAULT)
+                .value_name(ARG_ESTIMATE_GAS_LIMIT_VALUE)
+                .help(ARG_ESTIMATE_GAS_LIMIT_HELP),
+        )
         .arg(
             Arg::with_name(ARG_USE_SYSTEM_CONTRACTS)
                 .short(ARG_USE_SYSTEM_CONTRACTS_SHORT)
@@ -245,6 +518,38 @@ fn get_args() -> ArgMatches<'static> {
                 .help(ARG_SOCKET_HELP)
                 .index(1),
         )
+        .subcommand(
+            SubCommand::with_name(SUBCOMMAND_DIFF)
+                .about(SUBCOMMAND_DIFF_ABOUT)
+                .arg(
+                    Arg::with_name(ARG_LEFT_ROOT)
+                        .required(true)
+                        .help(ARG_LEFT_ROOT_HELP)
+                        .index(1),
+                )
+                .arg(
+                    Arg::with_name(ARG_RIGHT_ROOT)
+                        .required(true)
+                        .help(ARG_RIGHT_ROOT_HELP)
+                        .index(2),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name(SUBCOMMAND_STATS)
+                .about(SUBCOMMAND_STATS_ABOUT)
+                .arg(
+                    Arg::with_name(ARG_ROOT)
+                        .required(true)
+                        .help(ARG_ROOT_HELP)
+                        .index(1),
+                )
+                .arg(
+                    Arg::with_name(ARG_OTHER_ROOT)
+                        .help(ARG_OTHER_ROOT_HELP)
+                        .index(2),
+                ),
+        )
+        .subcommand(SubCommand::with_name(SUBCOMMAND_RECOMPRESS).about(SUBCOMMAND_RECOMPRESS_ABOUT))
         .get_matches()
 }
 
@@ -281,14 +586,88 @@ fn get_data_dir(arg_matches: &ArgMatches) -> PathBuf {
     buf
 }
 
-///  Parses pages argument and returns map size
-fn get_map_size(arg_matches: &ArgMatches) -> usize {
+/// Gets value of storage-backend argument
+fn get_storage_backend(arg_matches: &ArgMatches) -> StorageBackend {
+    match arg_matches
+        .value_of(ARG_STORAGE_BACKEND)
+        .expect("should have default value if not explicitly set")
+    {
+        STORAGE_BACKEND_LMDB => StorageBackend::Lmdb,
+        STORAGE_BACKEND_SLED => StorageBackend::Sled,
+        _ => unreachable!("should validate storage-backend arg to match one of the options"),
+    }
+}
+
+/// Parses the pages, max-pages and map-growth-factor arguments and returns the lmdb map config
+fn get_lmdb_map_config(arg_matches: &ArgMatches) -> LmdbMapConfig {
     let page_size = get_page_size().unwrap();
     let pages = arg_matches
         .value_of(ARG_PAGES)
         .map_or(Ok(DEFAULT_PAGES), usize::from_str)
         .expect(GET_PAGES_EXPECT);
-    page_size * pages
+    let max_pages = arg_matches
+        .value_of(ARG_MAX_PAGES)
+        .map_or(Ok(pages), usize::from_str)
+        .expect(GET_MAX_PAGES_EXPECT);
+    let growth_factor = arg_matches
+        .value_of(ARG_MAP_GROWTH_FACTOR)
+        .map(f64::from_str)
+        .expect("should have default value if not explicitly set")
+        .ok()
+        .filter(|growth_factor| *growth_factor > 1.0)
+        .expect(GET_MAP_GROWTH_FACTOR_EXPECT);
+    LmdbMapConfig {
+        map_size: page_size * pages,
+        max_map_size: page_size * max_pages,
+        growth_factor,
+    }
+}
+
+/// Parses the trie-cache-size argument and returns the size of the trie cache in bytes
+fn get_trie_cache_size(arg_matches: &ArgMatches) -> usize {
+    arg_matches
+        .value_of(ARG_TRIE_CACHE_SIZE)
+        .map(usize::from_str)
+        .expect("should have default value if not explicitly set")
+        .ok()
+        .and_then(|size| size.checked_mul(MIB))
+        .expect(ARG_TRIE_CACHE_SIZE_EXPECT)
+}
+
+/// Parses the trie-compression and trie-compression-threshold arguments and returns the compression
+/// of trie leaves, if any
+fn get_trie_compression(arg_matches: &ArgMatches) -> Option<Compression> {
+    let algorithm = match arg_matches
+        .value_of(ARG_TRIE_COMPRESSION)
+        .expect("should have default value if not explicitly set")
+    {
+        TRIE_COMPRESSION_NONE => return None,
+        TRIE_COMPRESSION_LZ4 => CompressionAlgorithm::Lz4,
+        TRIE_COMPRESSION_ZSTD => CompressionAlgorithm::Zstd,
+        _ => unreachable!("should validate trie-compression arg to match one of the options"),
+    };
+    let threshold = arg_matches
+        .value_of(ARG_TRIE_COMPRESSION_THRESHOLD)
+        .map(usize::from_str)
+        .expect("should have default value if not explicitly set")
+        .expect(ARG_TRIE_COMPRESSION_THRESHOLD_EXPECT);
+    Some(Compression::new(algorithm, threshold))
+}
+
+/// Parses the prune-retained-roots and prune-batch-size arguments
+fn get_prune_config(arg_matches: &ArgMatches) -> PruneConfig {
+    let retained_roots = arg_matches
+        .values_of(ARG_PRUNE_RETAINED_ROOTS)
+        .map(|values| values.map(parse_state_root).collect());
+    let batch_size = arg_matches
+        .value_of(ARG_PRUNE_BATCH_SIZE)
+        .map(usize::from_str)
+        .expect("should have default value if not explicitly set")
+        .expect(ARG_PRUNE_BATCH_SIZE_EXPECT);
+    PruneConfig {
+        retained_roots,
+        batch_size,
+    }
 }
 
 fn get_thread_count(arg_matches: &ArgMatches) -> usize {
@@ -299,45 +678,125 @@ fn get_thread_count(arg_matches: &ArgMatches) -> usize {
         .expect(ARG_THREAD_COUNT_EXPECT)
 }
 
+/// Parses a hex-encoded state root argument
+fn get_state_root(arg_matches: &ArgMatches, name: &str) -> Blake2bHash {
+    arg_matches
+        .value_of(name)
+        .map(parse_state_root)
+        .expect(ARG_ROOT_EXPECT)
+}
+
+/// Parses a hex-encoded state root
+fn parse_state_root(value: &str) -> Blake2bHash {
+    let bytes = base16::decode(value).expect(ARG_ROOT_EXPECT);
+    Blake2bHash::try_from(bytes.as_slice()).expect(ARG_ROOT_EXPECT)
+}
+
 /// Returns an [`EngineConfig`].
 fn get_engine_config(arg_matches: &ArgMatches) -> EngineConfig {
     // feature flags go here
     let use_system_contracts = arg_matches.is_present(ARG_USE_SYSTEM_CONTRACTS);
     let enable_bonding = arg_matches.is_present(ARG_ENABLE_BONDING);
+    let execution_threads = arg_matches
+        .value_of(ARG_EXECUTION_THREAD_COUNT)
+        .map(usize::from_str)
+        .expect("should have default value if not explicitly set")
+        .expect(ARG_EXECUTION_THREAD_COUNT_EXPECT);
+    let estimate_gas_limit = arg_matches
+        .value_of(ARG_ESTIMATE_GAS_LIMIT)
+        .map(u64::from_str)
+        .expect("should have default value if not explicitly set")
+        .expect(ARG_ESTIMATE_GAS_LIMIT_EXPECT);
     EngineConfig::new()
         .with_use_system_contracts(use_system_contracts)
         .with_enable_bonding(enable_bonding)
+        .with_execution_threads(execution_threads)
+        .with_estimate_gas_limit(estimate_gas_limit)
 }
 
 /// Builds and returns a gRPC server.
+#[allow(clippy::too_many_arguments)]
 fn get_grpc_server(
     socket: &socket::Socket,
     data_dir: PathBuf,
-    map_size: usize,
+    storage_backend: StorageBackend,
+    map_config: LmdbMapConfig,
+    trie_cache_size: usize,
+    trie_compression: Option<Compression>,
+    prune_config: PruneConfig,
     thread_count: usize,
     engine_config: EngineConfig,
 ) -> grpc::Server {
-    let engine_state = get_engine_state(data_dir, map_size, engine_config);
+    let server_builder = match storage_backend {
+        StorageBackend::Lmdb => {
+            let global_state = get_lmdb_global_state(data_dir, map_config, trie_compression)
+                .with_trie_cache(trie_cache_size);
+            let prune_store =
+                LmdbPruneStore::new(&global_state.environment, None, DatabaseFlags::empty())
+                    .expect(LMDB_PRUNE_STORE_EXPECT);
+            let global_state =
+                global_state.with_pruning(Arc::new(prune_store), prune_config.batch_size);
+            if let Some(retained_roots) = prune_config.retained_roots {
+                let result = global_state
+                    .start_prune(&retained_roots)
+                    .expect(START_PRUNE_EXPECT);
+                check_prune_started(result);
+            }
+            let engine_state = EngineState::new(global_state, engine_config);
+            engine_server::new(socket.as_str(), thread_count, engine_state)
+        }
+        StorageBackend::Sled => {
+            let global_state = get_sled_global_state(data_dir);
+            let prune_store = SledPruneStore::new(&global_state.environment, None)
+                .expect(SLED_PRUNE_STORE_EXPECT);
+            let global_state =
+                global_state.with_pruning(Arc::new(prune_store), prune_config.batch_size);
+            if let Some(retained_roots) = prune_config.retained_roots {
+                let result = global_state
+                    .start_prune(&retained_roots)
+                    .expect(START_PRUNE_EXPECT);
+                check_prune_started(result);
+            }
+            let engine_state = EngineState::new(global_state, engine_config);
+            engine_server::new(socket.as_str(), thread_count, engine_state)
+        }
+    };
 
-    engine_server::new(socket.as_str(), thread_count, engine_state)
-        .build()
-        .expect(SERVER_START_EXPECT)
+    server_builder.build().expect(SERVER_START_EXPECT)
 }
 
-/// Builds and returns engine global state
-fn get_engine_state(
+/// Exits if a retained root of the prune to start was not found
+fn check_prune_started(result: StartPruneResult) {
+    match result {
+        StartPruneResult::Started => info!("started pruning global state"),
+        StartPruneResult::RootNotFound(root) => {
+            eprintln!("{}: {}", ROOT_NOT_FOUND, root);
+            process::exit(1);
+        }
+    }
+}
+
+/// Builds and returns LMDB-backed global state
+fn get_lmdb_global_state(
     data_dir: PathBuf,
-    map_size: usize,
-    engine_config: EngineConfig,
-) -> EngineState<LmdbGlobalState> {
+    map_config: LmdbMapConfig,
+    trie_compression: Option<Compression>,
+) -> LmdbGlobalState {
     let environment = {
-        let ret = LmdbEnvironment::new(&data_dir, map_size).expect(LMDB_ENVIRONMENT_EXPECT);
+        let mut ret =
+            LmdbEnvironment::new(&data_dir, map_config.map_size).expect(LMDB_ENVIRONMENT_EXPECT);
+        if map_config.max_map_size > map_config.map_size {
+            ret = ret.with_map_growth(map_config.max_map_size, map_config.growth_factor);
+        }
         Arc::new(ret)
     };
 
     let trie_store = {
-        let ret = LmdbTrieStore::new(&environment, None, DatabaseFlags::empty())
+        let mut ret = LmdbTrieStore::new(&environment, None, DatabaseFlags::empty())
             .expect(LMDB_TRIE_STORE_EXPECT);
+        if let Some(compression) = trie_compression {
+            ret = ret.with_compression(compression);
+        }
         Arc::new(ret)
     };
 
@@ -347,10 +806,156 @@ fn get_engine_state(
         Arc::new(ret)
     };
 
-    let global_state = LmdbGlobalState::empty(environment, trie_store, protocol_data_store)
-        .expect(LMDB_GLOBAL_STATE_EXPECT);
+    LmdbGlobalState::empty(environment, trie_store, protocol_data_store)
+        .expect(LMDB_GLOBAL_STATE_EXPECT)
+}
+
+/// Builds and returns sled-backed global state, kept in a subdirectory of the data directory
+fn get_sled_global_state(mut data_dir: PathBuf) -> SledGlobalState {
+    data_dir.push(SLED_DIR);
+
+    let environment = {
+        let ret = SledEnvironment::new(&data_dir).expect(SLED_ENVIRONMENT_EXPECT);
+        Arc::new(ret)
+    };
+
+    let trie_store = {
+        let ret = SledTrieStore::new(&environment, None).expect(SLED_TRIE_STORE_EXPECT);
+        Arc::new(ret)
+    };
+
+    let protocol_data_store = {
+        let ret =
+            SledProtocolDataStore::new(&environment, None).expect(SLED_PROTOCOL_DATA_STORE_EXPECT);
+        Arc::new(ret)
+    };
+
+    SledGlobalState::empty(environment, trie_store, protocol_data_store)
+        .expect(SLED_GLOBAL_STATE_EXPECT)
+}
+
+/// Prints the keys whose values differ between two state roots, one per line, followed by their
+/// values at each root
+fn run_diff(arg_matches: &ArgMatches, diff_arg_matches: &ArgMatches) {
+    let left_root = get_state_root(diff_arg_matches, ARG_LEFT_ROOT);
+    let right_root = get_state_root(diff_arg_matches, ARG_RIGHT_ROOT);
+
+    let correlation_id = CorrelationId::new();
+    let data_dir = get_data_dir(arg_matches);
+    let diff_result = match get_storage_backend(arg_matches) {
+        StorageBackend::Lmdb => {
+            let map_config = get_lmdb_map_config(arg_matches);
+            let trie_compression = get_trie_compression(arg_matches);
+            get_lmdb_global_state(data_dir, map_config, trie_compression).diff(
+                correlation_id,
+                &left_root,
+                &right_root,
+            )
+        }
+        StorageBackend::Sled => {
+            get_sled_global_state(data_dir).diff(correlation_id, &left_root, &right_root)
+        }
+    };
+
+    match diff_result.expect(DIFF_EXPECT) {
+        DiffResult::RootNotFound(root) => {
+            eprintln!("{}: {}", ROOT_NOT_FOUND, root);
+            process::exit(1);
+        }
+        DiffResult::Success(key_diffs) => {
+            for (key, left_value, right_value) in key_diffs {
+                println!("{}\t{:?}\t{:?}", key, left_value, right_value);
+            }
+        }
+    }
+}
+
+/// Prints statistics about the state at a root, one per line, followed by its value.  If a second
+/// root is given, each statistic is followed by its value at both roots and by the growth between
+/// them
+fn run_stats(arg_matches: &ArgMatches, stats_arg_matches: &ArgMatches) {
+    let mut roots = vec![get_state_root(stats_arg_matches, ARG_ROOT)];
+    if stats_arg_matches.is_present(ARG_OTHER_ROOT) {
+        roots.push(get_state_root(stats_arg_matches, ARG_OTHER_ROOT));
+    }
+
+    let correlation_id = CorrelationId::new();
+    let data_dir = get_data_dir(arg_matches);
+    let stats_results = match get_storage_backend(arg_matches) {
+        StorageBackend::Lmdb => {
+            let map_config = get_lmdb_map_config(arg_matches);
+            let trie_compression = get_trie_compression(arg_matches);
+            let global_state = get_lmdb_global_state(data_dir, map_config, trie_compression);
+            roots
+                .iter()
+                .map(|root| global_state.stats(correlation_id, root))
+                .collect::<Result<Vec<StatsResult>, _>>()
+        }
+        StorageBackend::Sled => {
+            let global_state = get_sled_global_state(data_dir);
+            roots
+                .iter()
+                .map(|root| global_state.stats(correlation_id, root))
+                .collect::<Result<Vec<StatsResult>, _>>()
+        }
+    };
+
+    let mut all_stats = Vec::new();
+    for (root, stats_result) in roots.iter().zip(stats_results.expect(STATS_EXPECT)) {
+        match stats_result {
+            StatsResult::RootNotFound => {
+                eprintln!("{}: {}", ROOT_NOT_FOUND, root);
+                process::exit(1);
+            }
+            StatsResult::Success(stats) => all_stats.push(stats),
+        }
+    }
+
+    match all_stats.as_slice() {
+        [stats] => {
+            for (name, value) in stats.to_named_values() {
+                println!("{}\t{}", name, value);
+            }
+        }
+        [left, right] => {
+            for comparison in stats::compare(left, right) {
+                println!(
+                    "{}\t{}\t{}\t{}",
+                    comparison.name,
+                    comparison.left,
+                    comparison.right,
+                    comparison.growth()
+                );
+            }
+        }
+        _ => unreachable!("should have one or two roots"),
+    }
+}
+
+/// Rewrites every stored trie with the compression given by the trie-compression argument, then
+/// prints the number of tries rewritten, the number now compressed, and their total size before
+/// and after, one per line
+fn run_recompress(arg_matches: &ArgMatches) {
+    let data_dir = get_data_dir(arg_matches);
+    let global_state = match get_storage_backend(arg_matches) {
+        StorageBackend::Lmdb => {
+            let map_config = get_lmdb_map_config(arg_matches);
+            let trie_compression = get_trie_compression(arg_matches);
+            get_lmdb_global_state(data_dir, map_config, trie_compression)
+        }
+        StorageBackend::Sled => {
+            eprintln!("{}", RECOMPRESS_SLED_UNSUPPORTED);
+            process::exit(1);
+        }
+    };
 
-    EngineState::new(global_state, engine_config)
+    let stats = global_state
+        .recompress(CorrelationId::new(), RECOMPRESS_BATCH_SIZE)
+        .expect(RECOMPRESS_EXPECT);
+    println!("tries\t{}", stats.tries);
+    println!("compressed\t{}", stats.compressed);
+    println!("bytes_before\t{}", stats.bytes_before);
+    println!("bytes_after\t{}", stats.bytes_after);
 }
 
 /// Builds and returns log settings
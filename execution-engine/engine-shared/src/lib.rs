@@ -7,6 +7,7 @@ pub mod motes;
 pub mod newtypes;
 pub mod os;
 pub mod socket;
+pub mod storage_costs;
 pub mod stored_value;
 pub mod test_utils;
 pub mod transform;
@@ -0,0 +1,92 @@
+use types::{
+    bytesrepr::{self, FromBytes, ToBytes, U32_SERIALIZED_LENGTH},
+    U512,
+};
+
+use crate::{gas::Gas, stored_value::StoredValue};
+
+pub const STORAGE_COSTS_SERIALIZED_LENGTH: usize = U32_SERIALIZED_LENGTH;
+
+/// The cost of the data written to global state.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct StorageCosts {
+    /// Cost per byte of a serialized value written to global state
+    pub gas_per_byte: u32,
+}
+
+impl StorageCosts {
+    pub fn new(gas_per_byte: u32) -> Self {
+        StorageCosts { gas_per_byte }
+    }
+
+    /// Returns the cost of writing `value` to global state.
+    pub fn calculate_gas_cost(&self, value: &StoredValue) -> Gas {
+        let size = U512::from(value.serialized_length() as u64);
+        Gas::new(U512::from(self.gas_per_byte) * size)
+    }
+}
+
+impl ToBytes for StorageCosts {
+    fn to_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
+        self.gas_per_byte.to_bytes()
+    }
+
+    fn serialized_length(&self) -> usize {
+        STORAGE_COSTS_SERIALIZED_LENGTH
+    }
+}
+
+impl FromBytes for StorageCosts {
+    fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), bytesrepr::Error> {
+        let (gas_per_byte, rem) = FromBytes::from_bytes(bytes)?;
+        Ok((StorageCosts { gas_per_byte }, rem))
+    }
+}
+
+pub mod gens {
+    use proptest::{num, prop_compose};
+
+    use super::StorageCosts;
+
+    prop_compose! {
+        pub fn storage_costs_arb()(gas_per_byte in num::u32::ANY) -> StorageCosts {
+            StorageCosts { gas_per_byte }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::proptest;
+
+    use types::{
+        bytesrepr::{self, ToBytes},
+        CLValue, U512,
+    };
+
+    use super::{gens, StorageCosts};
+    use crate::{gas::Gas, stored_value::StoredValue};
+
+    #[test]
+    fn should_charge_per_byte_of_serialized_value() {
+        let value = StoredValue::CLValue(CLValue::from_t(U512::from(u64::max_value())).unwrap());
+        let storage_costs = StorageCosts::new(3);
+        assert_eq!(
+            storage_costs.calculate_gas_cost(&value),
+            Gas::new(U512::from(3 * value.serialized_length() as u64))
+        );
+        assert_eq!(
+            StorageCosts::default().calculate_gas_cost(&value),
+            Gas::default()
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn should_serialize_and_deserialize_with_arbitrary_values(
+            storage_costs in gens::storage_costs_arb()
+        ) {
+            bytesrepr::test_serialization_roundtrip(&storage_costs);
+        }
+    }
+}
@@ -59,6 +59,8 @@ pub enum Transform {
     AddUInt256(U256),
     AddUInt512(U512),
     AddKeys(NamedKeys),
+    /// Removes the value stored under a key from global state.
+    Prune,
     Failure(Error),
 }
 
@@ -136,6 +138,10 @@ where
 }
 
 impl Transform {
+    /// Applies the transform to `stored_value`.
+    ///
+    /// A `Prune` doesn't yield a value, so it must be handled by the caller rather than applied;
+    /// applying one results in a `TypeMismatch`.
     pub fn apply(self, stored_value: StoredValue) -> Result<StoredValue, Error> {
         match self {
             Transform::Identity => Ok(stored_value),
@@ -170,6 +176,11 @@ impl Transform {
                     Err(TypeMismatch::new(expected, found).into())
                 }
             },
+            Transform::Prune => {
+                let expected = "Write or Add transform".to_string();
+                let found = "Prune".to_string();
+                Err(TypeMismatch::new(expected, found).into())
+            }
             Transform::Failure(error) => Err(error),
         }
     }
@@ -217,6 +228,7 @@ impl Add for Transform {
             (a @ Transform::Failure(_), _) => a,
             (_, b @ Transform::Failure(_)) => b,
             (_, b @ Transform::Write(_)) => b,
+            (_, b @ Transform::Prune) => b,
             (Transform::Write(v), b) => {
                 // second transform changes value being written
                 match b.apply(v) {
@@ -256,6 +268,9 @@ impl Add for Transform {
                     TypeMismatch::new("AddKeys".to_owned(), format!("{:?}", other)).into(),
                 ),
             },
+            (Transform::Prune, other) => Transform::Failure(
+                TypeMismatch::new("Write".to_owned(), format!("{:?}", other)).into(),
+            ),
         }
     }
 }
@@ -287,6 +302,7 @@ pub mod gens {
     pub fn transform_arb() -> impl Strategy<Value = Transform> {
         prop_oneof![
             Just(Transform::Identity),
+            Just(Transform::Prune),
             stored_value_arb().prop_map(Transform::Write),
             any::<i32>().prop_map(Transform::AddInt32),
             any::<u64>().prop_map(Transform::AddUInt64),
@@ -430,6 +446,30 @@ mod tests {
         uint_overflow_test::<U512>();
     }
 
+    #[test]
+    fn prune_should_combine_with_other_transforms() {
+        let value = StoredValue::CLValue(CLValue::from_t(ONE_I32).unwrap());
+
+        assert_eq!(Transform::AddInt32(1) + Transform::Prune, Transform::Prune);
+        assert_eq!(
+            Transform::Write(value.clone()) + Transform::Prune,
+            Transform::Prune
+        );
+        assert_eq!(
+            Transform::Prune + Transform::Write(value.clone()),
+            Transform::Write(value.clone())
+        );
+        assert_eq!(Transform::Prune + Transform::Identity, Transform::Prune);
+        match Transform::Prune + Transform::AddInt32(1) {
+            Transform::Failure(Error::TypeMismatch(_)) => (),
+            other => panic!("adding to a pruned value should fail, got {:?}", other),
+        }
+        match Transform::Prune.apply(value) {
+            Err(Error::TypeMismatch(_)) => (),
+            other => panic!("applying a prune should fail, got {:?}", other),
+        }
+    }
+
     #[test]
     fn addition_between_mismatched_types_should_fail() {
         fn assert_yields_type_mismatch_error(stored_value: StoredValue) {
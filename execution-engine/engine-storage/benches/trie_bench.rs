@@ -4,8 +4,17 @@ extern crate test;
 
 use test::{black_box, Bencher};
 
-use casperlabs_engine_storage::trie::{Pointer, PointerBlock, Trie};
-use engine_shared::{newtypes::Blake2bHash, stored_value::StoredValue};
+use casperlabs_engine_storage::{
+    error::in_memory,
+    global_state::in_memory::InMemoryGlobalState,
+    transaction_source::{Transaction, TransactionSource},
+    trie::{Pointer, PointerBlock, Trie},
+    trie_store::operations::{self, batch::write_batch, WriteResult},
+};
+use engine_shared::{
+    newtypes::{Blake2bHash, CorrelationId},
+    stored_value::StoredValue,
+};
 use types::{
     account::AccountHash,
     bytesrepr::{FromBytes, ToBytes},
@@ -69,3 +78,73 @@ fn deserialize_trie_node_pointer(b: &mut Bencher) {
 
     b.iter(|| Trie::<Key, StoredValue>::from_bytes(black_box(&node_bytes)));
 }
+
+const WRITE_BATCH_SIZE: u32 = 1000;
+
+fn write_batch_pairs() -> Vec<(Key, StoredValue)> {
+    (0..WRITE_BATCH_SIZE)
+        .map(|i| {
+            let mut account_hash = [0u8; 32];
+            account_hash[..4].copy_from_slice(&i.to_le_bytes());
+            account_hash[4..8].copy_from_slice(&i.wrapping_mul(2_654_435_761).to_le_bytes());
+            (
+                Key::Account(AccountHash::new(account_hash)),
+                StoredValue::CLValue(CLValue::from_t(i).unwrap()),
+            )
+        })
+        .collect()
+}
+
+#[bench]
+fn write_trie_leaves_sequentially(b: &mut Bencher) {
+    let correlation_id = CorrelationId::new();
+    let state = InMemoryGlobalState::empty().unwrap();
+    let pairs = write_batch_pairs();
+
+    b.iter(|| {
+        let mut txn = state.environment.create_read_write_txn().unwrap();
+        let mut root = state.empty_root_hash;
+        for (key, value) in &pairs {
+            match operations::write::<_, _, _, _, in_memory::Error>(
+                correlation_id,
+                &mut txn,
+                &*state.trie_store,
+                &root,
+                key,
+                value,
+            )
+            .unwrap()
+            {
+                WriteResult::Written(root_hash) => root = root_hash,
+                result => panic!("unexpected write result: {:?}", result),
+            }
+        }
+        txn.commit().unwrap();
+        black_box(root)
+    });
+}
+
+#[bench]
+fn write_trie_leaves_as_batch(b: &mut Bencher) {
+    let correlation_id = CorrelationId::new();
+    let state = InMemoryGlobalState::empty().unwrap();
+    let pairs = write_batch_pairs();
+
+    b.iter(|| {
+        let updates = pairs
+            .iter()
+            .map(|(key, value)| (*key, Some(value.to_owned())))
+            .collect();
+        let mut txn = state.environment.create_read_write_txn().unwrap();
+        let result = write_batch::<_, _, _, _, in_memory::Error>(
+            correlation_id,
+            &mut txn,
+            &*state.trie_store,
+            &state.empty_root_hash,
+            updates,
+        )
+        .unwrap();
+        txn.commit().unwrap();
+        black_box(result)
+    });
+}
@@ -0,0 +1,93 @@
+#![feature(test)]
+
+extern crate test;
+
+use lmdb::DatabaseFlags;
+use tempfile::{tempdir, TempDir};
+use test::{black_box, Bencher};
+
+use casperlabs_engine_storage::{
+    store::Store,
+    transaction_source::{lmdb::LmdbEnvironment, Transaction, TransactionSource},
+    trie::Trie,
+    trie_store::{
+        compression::{Compression, CompressionAlgorithm, DEFAULT_THRESHOLD},
+        lmdb::LmdbTrieStore,
+    },
+};
+use engine_shared::{newtypes::Blake2bHash, os::get_page_size, stored_value::StoredValue};
+use types::{account::AccountHash, bytesrepr::ToBytes, CLValue, ContractWasm, Key};
+
+const LEAF_COUNT: u8 = 16;
+const WASM_LENGTH: usize = 64 * 1024;
+const LIST_LENGTH: u64 = 8 * 1024;
+
+/// Returns leaves holding contract Wasm and long lists, which dominate the size of global state.
+fn large_leaves() -> Vec<Trie<Key, StoredValue>> {
+    (0..LEAF_COUNT)
+        .map(|i| {
+            let key = Key::Account(AccountHash::new([i; 32]));
+            let value = if i % 2 == 0 {
+                let wasm_bytes = (0..WASM_LENGTH)
+                    .map(|j| (j.wrapping_mul(7) ^ (j >> 5) ^ usize::from(i)) as u8)
+                    .collect();
+                StoredValue::ContractWasm(ContractWasm::new(wasm_bytes))
+            } else {
+                let list: Vec<u64> = (0..LIST_LENGTH).map(|j| j * u64::from(i)).collect();
+                StoredValue::CLValue(CLValue::from_t(list).unwrap())
+            };
+            Trie::Leaf { key, value }
+        })
+        .collect()
+}
+
+/// Stores the large leaves with the given compression, and returns their hashes.
+fn create_store(
+    compression: Option<CompressionAlgorithm>,
+) -> (TempDir, LmdbEnvironment, LmdbTrieStore, Vec<Blake2bHash>) {
+    let dir = tempdir().unwrap();
+    let map_size = get_page_size().unwrap() * 25_600;
+    let env = LmdbEnvironment::new(&dir.path().to_path_buf(), map_size).unwrap();
+    let mut store = LmdbTrieStore::new(&env, None, DatabaseFlags::empty()).unwrap();
+    if let Some(algorithm) = compression {
+        store = store.with_compression(Compression::new(algorithm, DEFAULT_THRESHOLD));
+    }
+
+    let mut hashes = Vec::new();
+    let mut txn = env.create_read_write_txn().unwrap();
+    for leaf in large_leaves() {
+        let hash = Blake2bHash::new(&leaf.to_bytes().unwrap());
+        store.put(&mut txn, &hash, &leaf).unwrap();
+        hashes.push(hash);
+    }
+    txn.commit().unwrap();
+    (dir, env, store, hashes)
+}
+
+fn read_large_leaves(b: &mut Bencher, compression: Option<CompressionAlgorithm>) {
+    let (_dir, env, store, hashes) = create_store(compression);
+
+    b.iter(|| {
+        let txn = env.create_read_txn().unwrap();
+        for hash in &hashes {
+            let leaf: Option<Trie<Key, StoredValue>> = store.get(&txn, hash).unwrap();
+            black_box(leaf);
+        }
+        txn.commit().unwrap();
+    });
+}
+
+#[bench]
+fn read_large_leaves_uncompressed(b: &mut Bencher) {
+    read_large_leaves(b, None);
+}
+
+#[bench]
+fn read_large_leaves_lz4(b: &mut Bencher) {
+    read_large_leaves(b, Some(CompressionAlgorithm::Lz4));
+}
+
+#[bench]
+fn read_large_leaves_zstd(b: &mut Bencher) {
+    read_large_leaves(b, Some(CompressionAlgorithm::Zstd));
+}
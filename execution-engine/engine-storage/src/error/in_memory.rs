@@ -2,8 +2,11 @@ use std::sync;
 
 use failure::Fail;
 
+use engine_shared::newtypes::Blake2bHash;
 use types::bytesrepr;
 
+use super::TrieNotFound;
+
 #[derive(Debug, Fail, PartialEq, Eq)]
 pub enum Error {
     #[fail(display = "{}", _0)]
@@ -11,6 +14,9 @@ pub enum Error {
 
     #[fail(display = "Another thread panicked while holding a lock")]
     Poison,
+
+    #[fail(display = "No trie at key: {}", _0)]
+    TrieNotFound(Blake2bHash),
 }
 
 impl From<bytesrepr::Error> for Error {
@@ -19,6 +25,12 @@ impl From<bytesrepr::Error> for Error {
     }
 }
 
+impl From<TrieNotFound> for Error {
+    fn from(error: TrieNotFound) -> Self {
+        Error::TrieNotFound(error.0)
+    }
+}
+
 impl<T> From<sync::PoisonError<T>> for Error {
     fn from(_error: sync::PoisonError<T>) -> Self {
         Error::Poison
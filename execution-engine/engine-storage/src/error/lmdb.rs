@@ -2,21 +2,29 @@ use std::sync;
 
 use failure::Fail;
 use lmdb as lmdb_external;
+use sled as sled_external;
 
+use engine_shared::newtypes::Blake2bHash;
 use types::bytesrepr;
 
-use super::in_memory;
+use super::{in_memory, TrieNotFound};
 
 #[derive(Debug, Clone, Fail, PartialEq, Eq)]
 pub enum Error {
     #[fail(display = "{}", _0)]
     Lmdb(#[fail(cause)] lmdb_external::Error),
 
+    #[fail(display = "{}", _0)]
+    Sled(#[fail(cause)] sled_external::Error),
+
     #[fail(display = "{}", _0)]
     BytesRepr(#[fail(cause)] bytesrepr::Error),
 
     #[fail(display = "Another thread panicked while holding a lock")]
     Poison,
+
+    #[fail(display = "No trie at key: {}", _0)]
+    TrieNotFound(Blake2bHash),
 }
 
 impl wasmi::HostError for Error {}
@@ -27,12 +35,24 @@ impl From<lmdb_external::Error> for Error {
     }
 }
 
+impl From<sled_external::Error> for Error {
+    fn from(error: sled_external::Error) -> Self {
+        Error::Sled(error)
+    }
+}
+
 impl From<bytesrepr::Error> for Error {
     fn from(error: bytesrepr::Error) -> Self {
         Error::BytesRepr(error)
     }
 }
 
+impl From<TrieNotFound> for Error {
+    fn from(error: TrieNotFound) -> Self {
+        Error::TrieNotFound(error.0)
+    }
+}
+
 impl<T> From<sync::PoisonError<T>> for Error {
     fn from(_error: sync::PoisonError<T>) -> Self {
         Error::Poison
@@ -44,6 +64,7 @@ impl From<in_memory::Error> for Error {
         match error {
             in_memory::Error::BytesRepr(error) => Error::BytesRepr(error),
             in_memory::Error::Poison => Error::Poison,
+            in_memory::Error::TrieNotFound(hash) => Error::TrieNotFound(hash),
         }
     }
 }
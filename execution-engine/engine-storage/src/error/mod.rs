@@ -1,4 +1,10 @@
 pub mod in_memory;
 pub mod lmdb;
 
+use engine_shared::newtypes::Blake2bHash;
+
 pub use self::lmdb::Error;
+
+/// A trie which is referred to by a root or by another trie is missing from its store.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct TrieNotFound(pub Blake2bHash);
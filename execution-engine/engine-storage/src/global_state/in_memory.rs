@@ -6,11 +6,14 @@ use engine_shared::{
     stored_value::StoredValue,
     transform::Transform,
 };
-use types::{Key, ProtocolVersion};
+use types::{bytesrepr::ToBytes, Key, ProtocolVersion};
 
 use crate::{
     error::{self, in_memory},
-    global_state::{commit, CommitResult, StateProvider, StateReader},
+    global_state::{
+        collect_page, commit, commit_many, CommitManyResult, CommitResult, StateIterator,
+        StatePage, StateProvider, StateReader,
+    },
     protocol_data::ProtocolData,
     protocol_data_store::in_memory::InMemoryProtocolDataStore,
     store::Store,
@@ -18,10 +21,18 @@ use crate::{
         in_memory::{InMemoryEnvironment, InMemoryReadTransaction},
         Transaction, TransactionSource,
     },
-    trie::{operations::create_hashed_empty_trie, Trie},
+    trie::{operations::create_hashed_empty_trie, Trie, TrieMerkleProof},
     trie_store::{
         in_memory::InMemoryTrieStore,
-        operations::{self, read, ReadResult, WriteResult},
+        operations::{
+            self,
+            diff::{self, DiffResult},
+            entries_with_prefix,
+            prune::{self, PruneResult},
+            read, read_with_proof,
+            stats::{self, StatsResult},
+            ReadResult, WriteResult,
+        },
     },
 };
 
@@ -106,6 +117,69 @@ impl InMemoryGlobalState {
         }
         Ok((state, current_root))
     }
+
+    /// Deletes every trie node which is not reachable from one of `retained_roots` or from the
+    /// empty root, within a single read-write transaction.
+    ///
+    /// Nothing is deleted if one of `retained_roots` is not found.  See `TriePruner` for pruning in
+    /// increments which can interleave with commits.
+    pub fn prune(
+        &self,
+        correlation_id: CorrelationId,
+        retained_roots: &[Blake2bHash],
+    ) -> Result<PruneResult, error::Error> {
+        let mut roots = Vec::with_capacity(retained_roots.len() + 1);
+        roots.push(self.empty_root_hash);
+        roots.extend_from_slice(retained_roots);
+
+        let mut txn = self.environment.create_read_write_txn()?;
+        let result = prune::prune::<Key, StoredValue, _, InMemoryTrieStore, error::Error>(
+            correlation_id,
+            &mut txn,
+            &self.trie_store,
+            &roots,
+        )?;
+        if let PruneResult::Success(_) = result {
+            txn.commit()?;
+        }
+        Ok(result)
+    }
+
+    /// Returns the keys whose values differ between the states at `left_root` and `right_root`.
+    pub fn diff(
+        &self,
+        correlation_id: CorrelationId,
+        left_root: &Blake2bHash,
+        right_root: &Blake2bHash,
+    ) -> Result<DiffResult<Key, StoredValue>, error::Error> {
+        let txn = self.environment.create_read_txn()?;
+        let result = diff::diff::<Key, StoredValue, _, InMemoryTrieStore, error::Error>(
+            correlation_id,
+            &txn,
+            &self.trie_store,
+            left_root,
+            right_root,
+        )?;
+        txn.commit()?;
+        Ok(result)
+    }
+
+    /// Returns statistics about the tries reachable from `root`.
+    pub fn stats(
+        &self,
+        correlation_id: CorrelationId,
+        root: &Blake2bHash,
+    ) -> Result<StatsResult, error::Error> {
+        let txn = self.environment.create_read_txn()?;
+        let result = stats::stats::<Key, StoredValue, _, InMemoryTrieStore, error::Error>(
+            correlation_id,
+            &txn,
+            &self.trie_store,
+            root,
+        )?;
+        txn.commit()?;
+        Ok(result)
+    }
 }
 
 impl StateReader<Key, StoredValue> for InMemoryGlobalStateView {
@@ -137,6 +211,56 @@ impl StateReader<Key, StoredValue> for InMemoryGlobalStateView {
         txn.commit()?;
         Ok(ret)
     }
+
+    fn read_with_proof(
+        &self,
+        correlation_id: CorrelationId,
+        key: &Key,
+    ) -> Result<Option<TrieMerkleProof<Key, StoredValue>>, Self::Error> {
+        let txn = self.environment.create_read_txn()?;
+        let ret = match read_with_proof::<
+            Key,
+            StoredValue,
+            InMemoryReadTransaction,
+            InMemoryTrieStore,
+            Self::Error,
+        >(
+            correlation_id,
+            &txn,
+            self.store.deref(),
+            &self.root_hash,
+            key,
+        )? {
+            Some(proof) => proof,
+            None => panic!("InMemoryGlobalState has invalid root"),
+        };
+        txn.commit()?;
+        Ok(Some(ret))
+    }
+}
+
+impl StateIterator<Key, StoredValue> for InMemoryGlobalStateView {
+    fn read_prefix(
+        &self,
+        correlation_id: CorrelationId,
+        prefix: &[u8],
+        start_after: Option<&Key>,
+        limit: usize,
+    ) -> Result<StatePage<Key, StoredValue>, Self::Error> {
+        let start_after = start_after.map(ToBytes::to_bytes).transpose()?;
+        let txn = self.environment.create_read_txn()?;
+        let entries = entries_with_prefix::<Key, StoredValue, InMemoryReadTransaction, _>(
+            correlation_id,
+            &txn,
+            self.store.deref(),
+            &self.root_hash,
+            prefix,
+            start_after.as_deref(),
+        );
+        let page = collect_page(entries, limit)?;
+        txn.commit()?;
+        Ok(page)
+    }
 }
 
 impl StateProvider for InMemoryGlobalState {
@@ -173,6 +297,23 @@ impl StateProvider for InMemoryGlobalState {
         Ok(commit_result)
     }
 
+    fn commit_many(
+        &self,
+        correlation_id: CorrelationId,
+        prestate_hash: Blake2bHash,
+        effects: Vec<AdditiveMap<Key, Transform>>,
+    ) -> Result<CommitManyResult, Self::Error> {
+        let commit_many_result =
+            commit_many::<InMemoryEnvironment, InMemoryTrieStore, _, Self::Error>(
+                &self.environment,
+                &self.trie_store,
+                correlation_id,
+                prestate_hash,
+                effects,
+            )?;
+        Ok(commit_many_result)
+    }
+
     fn put_protocol_data(
         &self,
         protocol_version: ProtocolVersion,
@@ -201,7 +342,7 @@ impl StateProvider for InMemoryGlobalState {
 
 #[cfg(test)]
 mod tests {
-    use types::{account::AccountHash, CLValue};
+    use types::{account::AccountHash, AccessRights, CLValue, KeyTag, URef};
 
     use super::*;
 
@@ -263,6 +404,61 @@ mod tests {
         }
     }
 
+    #[test]
+    fn read_prefix_pages_through_keys_of_a_kind() {
+        let correlation_id = CorrelationId::new();
+        let accounts: Vec<(Key, StoredValue)> = (1..=5_u8)
+            .map(|i| {
+                (
+                    Key::Account(AccountHash::new([i; 32])),
+                    StoredValue::CLValue(CLValue::from_t(i32::from(i)).unwrap()),
+                )
+            })
+            .collect();
+        let uref = (
+            Key::URef(URef::new([1u8; 32], AccessRights::NONE)),
+            StoredValue::CLValue(CLValue::from_t("uref".to_string()).unwrap()),
+        );
+        let hash = (
+            Key::Hash([1u8; 32]),
+            StoredValue::CLValue(CLValue::from_t("hash".to_string()).unwrap()),
+        );
+        let pairs = [&[uref.clone(), hash][..], &accounts[..]].concat();
+        let (state, root_hash) = InMemoryGlobalState::from_pairs(correlation_id, &pairs).unwrap();
+        let checkout = state.checkout(root_hash).unwrap().unwrap();
+
+        let prefix = [KeyTag::Account as u8];
+        let mut actual = Vec::new();
+        let mut start_after = None;
+        loop {
+            let page = checkout
+                .read_prefix(correlation_id, &prefix, start_after.as_ref(), 2)
+                .unwrap();
+            assert!(page.entries.len() <= 2);
+            start_after = page.entries.last().map(|(key, _)| *key);
+            actual.extend(page.entries);
+            if !page.has_more {
+                break;
+            }
+        }
+        assert_eq!(actual, accounts);
+
+        let page = checkout
+            .read_prefix(correlation_id, &[KeyTag::URef as u8], None, 10)
+            .unwrap();
+        assert_eq!(
+            page,
+            StatePage {
+                entries: vec![uref],
+                has_more: false
+            }
+        );
+
+        let page = checkout.read_prefix(correlation_id, &[], None, 0).unwrap();
+        assert!(page.entries.is_empty());
+        assert!(page.has_more);
+    }
+
     #[test]
     fn checkout_fails_if_unknown_hash_is_given() {
         let (state, _) = create_test_state();
@@ -343,6 +539,47 @@ mod tests {
         );
     }
 
+    #[test]
+    fn commit_of_prune_removes_key() {
+        let correlation_id = CorrelationId::new();
+        let test_pairs = create_test_pairs();
+        let (state, root_hash) = create_test_state();
+
+        let effects: AdditiveMap<Key, Transform> = vec![
+            (test_pairs[0].key, Transform::Prune),
+            (create_test_pairs_updated()[2].key, Transform::Prune),
+        ]
+        .into_iter()
+        .collect();
+
+        let updated_hash = match state.commit(correlation_id, root_hash, effects).unwrap() {
+            CommitResult::Success { state_root, .. } => state_root,
+            _ => panic!("commit failed"),
+        };
+
+        // Same root as a state which never contained the pruned key
+        let (_, expected_hash) = InMemoryGlobalState::from_pairs(
+            correlation_id,
+            &[(test_pairs[1].key, test_pairs[1].value.clone())],
+        )
+        .unwrap();
+        assert_eq!(updated_hash, expected_hash);
+
+        let updated_checkout = state.checkout(updated_hash).unwrap().unwrap();
+        assert_eq!(
+            None,
+            updated_checkout
+                .read(correlation_id, &test_pairs[0].key)
+                .unwrap()
+        );
+        assert_eq!(
+            Some(test_pairs[1].value.clone()),
+            updated_checkout
+                .read(correlation_id, &test_pairs[1].key)
+                .unwrap()
+        );
+    }
+
     #[test]
     fn initial_state_has_the_expected_hash() {
         let correlation_id = CorrelationId::new();
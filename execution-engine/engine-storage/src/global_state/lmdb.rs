@@ -6,27 +6,51 @@ use engine_shared::{
     stored_value::StoredValue,
     transform::Transform,
 };
-use types::{Key, ProtocolVersion};
+use types::{bytesrepr::ToBytes, Key, ProtocolVersion};
 
 use crate::{
     error,
-    global_state::{commit, CommitResult, StateProvider, StateReader},
+    global_state::{
+        collect_page, commit, commit_many, CommitManyResult, CommitPruning, CommitResult,
+        StartPruneResult, StateIterator, StatePage, StateProvider, StateReader,
+    },
     protocol_data::ProtocolData,
     protocol_data_store::lmdb::LmdbProtocolDataStore,
+    prune_store::lmdb::LmdbPruneStore,
     store::Store,
-    transaction_source::{lmdb::LmdbEnvironment, Transaction, TransactionSource},
-    trie::{operations::create_hashed_empty_trie, Trie},
+    transaction_source::{
+        lmdb::{LmdbEnvironment, LmdbReadTransaction},
+        Transaction, TransactionSource,
+    },
+    trie::{operations::create_hashed_empty_trie, Trie, TrieMerkleProof},
     trie_store::{
+        cache::TrieCache,
         lmdb::LmdbTrieStore,
-        operations::{read, ReadResult},
+        operations::{
+            diff::{self, DiffResult},
+            entries_with_prefix,
+            read, read_with_proof,
+            recompress::{RecompressResult, RecompressStats, TrieRecompressor},
+            stats::{self, StatsResult},
+            ReadResult,
+        },
     },
 };
 
+const READ: &str = "read";
+const READ_WITH_PROOF: &str = "read_with_proof";
+const READ_PREFIX: &str = "read_prefix";
+const PRUNING_EXPECT: &str = "pruning should be enabled with with_pruning";
+
 pub struct LmdbGlobalState {
     pub environment: Arc<LmdbEnvironment>,
     pub trie_store: Arc<LmdbTrieStore>,
     pub protocol_data_store: Arc<LmdbProtocolDataStore>,
     pub empty_root_hash: Blake2bHash,
+    /// Decoded tries shared by every view of this state.
+    pub trie_cache: Arc<TrieCache<Key, StoredValue>>,
+    /// The prune driven by commits, if pruning is enabled.
+    pruning: Option<CommitPruning<LmdbPruneStore>>,
 }
 
 /// Represents a "view" of global state at a particular root hash.
@@ -34,6 +58,7 @@ pub struct LmdbGlobalStateView {
     pub environment: Arc<LmdbEnvironment>,
     pub store: Arc<LmdbTrieStore>,
     pub root_hash: Blake2bHash,
+    pub trie_cache: Arc<TrieCache<Key, StoredValue>>,
 }
 
 impl LmdbGlobalState {
@@ -71,8 +96,117 @@ impl LmdbGlobalState {
             trie_store,
             protocol_data_store,
             empty_root_hash,
+            trie_cache: Arc::new(TrieCache::new(0)),
+            pruning: None,
+        }
+    }
+
+    /// Makes the views of this state share a cache of up to `max_bytes` of decoded tries.
+    ///
+    /// By default nothing is cached.
+    pub fn with_trie_cache(mut self, max_bytes: usize) -> Self {
+        self.trie_cache = Arc::new(TrieCache::new(max_bytes));
+        self
+    }
+
+    /// Makes the commits to this state drive the prune saved in `prune_store`, marking or
+    /// sweeping at most `batch_size` tries per commit.
+    ///
+    /// A prune which was in progress when the state was last used carries on with the next
+    /// commit.  See `CommitPruning`.
+    pub fn with_pruning(mut self, prune_store: Arc<LmdbPruneStore>, batch_size: usize) -> Self {
+        self.pruning = Some(CommitPruning::new(prune_store, batch_size));
+        self
+    }
+
+    /// Starts a prune which deletes every trie node which is not reachable from one of
+    /// `retained_roots`, from the empty root, or from the state root of a commit made while the
+    /// prune is in progress.  The prune is carried out by the following commits, and replaces the
+    /// prune in progress if there is one.
+    ///
+    /// Nothing is started if one of `retained_roots` is not found.  Commits should only be made
+    /// on top of retained roots until the prune is complete.
+    ///
+    /// # Panics
+    ///
+    /// Panics if pruning has not been enabled with `with_pruning`.
+    pub fn start_prune(
+        &self,
+        retained_roots: &[Blake2bHash],
+    ) -> Result<StartPruneResult, error::Error> {
+        let pruning = self.pruning.as_ref().expect(PRUNING_EXPECT);
+        let mut roots = Vec::with_capacity(retained_roots.len() + 1);
+        roots.push(self.empty_root_hash);
+        roots.extend_from_slice(retained_roots);
+
+        pruning.start::<LmdbEnvironment, LmdbTrieStore, error::Error>(
+            &self.environment,
+            &self.trie_store,
+            &roots,
+        )
+    }
+
+    /// Rewrites every stored trie with the compression of `trie_store`, committing a read-write
+    /// transaction every `batch_size` tries.
+    ///
+    /// This migrates a store to a new compression setting, and changes no state root.  It may run
+    /// alongside commits, which write with the same compression.
+    pub fn recompress(
+        &self,
+        correlation_id: CorrelationId,
+        batch_size: usize,
+    ) -> Result<RecompressStats, error::Error> {
+        let mut recompressor = TrieRecompressor::new();
+        loop {
+            let mut txn = self.environment.create_read_write_txn()?;
+            let result = recompressor.step::<Key, StoredValue, _, LmdbTrieStore, error::Error>(
+                correlation_id,
+                &mut txn,
+                &self.trie_store,
+                batch_size,
+            )?;
+            txn.commit()?;
+            if let RecompressResult::Complete(stats) = result {
+                return Ok(stats);
+            }
         }
     }
+
+    /// Returns the keys whose values differ between the states at `left_root` and `right_root`.
+    pub fn diff(
+        &self,
+        correlation_id: CorrelationId,
+        left_root: &Blake2bHash,
+        right_root: &Blake2bHash,
+    ) -> Result<DiffResult<Key, StoredValue>, error::Error> {
+        let txn = self.environment.create_read_txn()?;
+        let result = diff::diff::<Key, StoredValue, _, LmdbTrieStore, error::Error>(
+            correlation_id,
+            &txn,
+            &self.trie_store,
+            left_root,
+            right_root,
+        )?;
+        txn.commit()?;
+        Ok(result)
+    }
+
+    /// Returns statistics about the tries reachable from `root`.
+    pub fn stats(
+        &self,
+        correlation_id: CorrelationId,
+        root: &Blake2bHash,
+    ) -> Result<StatsResult, error::Error> {
+        let txn = self.environment.create_read_txn()?;
+        let result = stats::stats::<Key, StoredValue, _, LmdbTrieStore, error::Error>(
+            correlation_id,
+            &txn,
+            &self.trie_store,
+            root,
+        )?;
+        txn.commit()?;
+        Ok(result)
+    }
 }
 
 impl StateReader<Key, StoredValue> for LmdbGlobalStateView {
@@ -84,10 +218,11 @@ impl StateReader<Key, StoredValue> for LmdbGlobalStateView {
         key: &Key,
     ) -> Result<Option<StoredValue>, Self::Error> {
         let txn = self.environment.create_read_txn()?;
-        let ret = match read::<Key, StoredValue, lmdb::RoTransaction, LmdbTrieStore, Self::Error>(
+        let store = self.trie_cache.store(self.store.deref());
+        let ret = match read::<Key, StoredValue, LmdbReadTransaction, _, Self::Error>(
             correlation_id,
             &txn,
-            self.store.deref(),
+            &store,
             &self.root_hash,
             key,
         )? {
@@ -96,8 +231,57 @@ impl StateReader<Key, StoredValue> for LmdbGlobalStateView {
             ReadResult::RootNotFound => panic!("LmdbGlobalState has invalid root"),
         };
         txn.commit()?;
+        store.log_metrics(correlation_id, READ);
         Ok(ret)
     }
+
+    fn read_with_proof(
+        &self,
+        correlation_id: CorrelationId,
+        key: &Key,
+    ) -> Result<Option<TrieMerkleProof<Key, StoredValue>>, Self::Error> {
+        let txn = self.environment.create_read_txn()?;
+        let store = self.trie_cache.store(self.store.deref());
+        let ret = match read_with_proof::<Key, StoredValue, LmdbReadTransaction, _, Self::Error>(
+            correlation_id,
+            &txn,
+            &store,
+            &self.root_hash,
+            key,
+        )? {
+            Some(proof) => proof,
+            None => panic!("LmdbGlobalState has invalid root"),
+        };
+        txn.commit()?;
+        store.log_metrics(correlation_id, READ_WITH_PROOF);
+        Ok(Some(ret))
+    }
+}
+
+impl StateIterator<Key, StoredValue> for LmdbGlobalStateView {
+    fn read_prefix(
+        &self,
+        correlation_id: CorrelationId,
+        prefix: &[u8],
+        start_after: Option<&Key>,
+        limit: usize,
+    ) -> Result<StatePage<Key, StoredValue>, Self::Error> {
+        let start_after = start_after.map(ToBytes::to_bytes).transpose()?;
+        let txn = self.environment.create_read_txn()?;
+        let store = self.trie_cache.store(self.store.deref());
+        let entries = entries_with_prefix::<Key, StoredValue, LmdbReadTransaction, _>(
+            correlation_id,
+            &txn,
+            &store,
+            &self.root_hash,
+            prefix,
+            start_after.as_deref(),
+        );
+        let page = collect_page(entries, limit)?;
+        txn.commit()?;
+        store.log_metrics(correlation_id, READ_PREFIX);
+        Ok(page)
+    }
 }
 
 impl StateProvider for LmdbGlobalState {
@@ -107,11 +291,15 @@ impl StateProvider for LmdbGlobalState {
 
     fn checkout(&self, state_hash: Blake2bHash) -> Result<Option<Self::Reader>, Self::Error> {
         let txn = self.environment.create_read_txn()?;
-        let maybe_root: Option<Trie<Key, StoredValue>> = self.trie_store.get(&txn, &state_hash)?;
+        let maybe_root: Option<Trie<Key, StoredValue>> = self
+            .trie_cache
+            .store(self.trie_store.deref())
+            .get(&txn, &state_hash)?;
         let maybe_state = maybe_root.map(|_| LmdbGlobalStateView {
             environment: Arc::clone(&self.environment),
             store: Arc::clone(&self.trie_store),
             root_hash: state_hash,
+            trie_cache: Arc::clone(&self.trie_cache),
         });
         txn.commit()?;
         Ok(maybe_state)
@@ -123,6 +311,22 @@ impl StateProvider for LmdbGlobalState {
         prestate_hash: Blake2bHash,
         effects: AdditiveMap<Key, Transform>,
     ) -> Result<CommitResult, Self::Error> {
+        if let Some(pruning) = &self.pruning {
+            // Deleted tries are evicted from the cache by the caching store
+            let store = self.trie_cache.store(self.trie_store.deref());
+            let result = pruning.commit::<LmdbEnvironment, _, _, Self::Error>(
+                &self.environment,
+                &store,
+                correlation_id,
+                prestate_hash,
+                effects,
+            );
+            if result.is_err() {
+                // Tries read by a failed commit may not have been stored
+                self.trie_cache.clear();
+            }
+            return result;
+        }
         let commit_result = commit::<LmdbEnvironment, LmdbTrieStore, _, Self::Error>(
             &self.environment,
             &self.trie_store,
@@ -133,6 +337,36 @@ impl StateProvider for LmdbGlobalState {
         Ok(commit_result)
     }
 
+    fn commit_many(
+        &self,
+        correlation_id: CorrelationId,
+        prestate_hash: Blake2bHash,
+        effects: Vec<AdditiveMap<Key, Transform>>,
+    ) -> Result<CommitManyResult, Self::Error> {
+        if let Some(pruning) = &self.pruning {
+            let store = self.trie_cache.store(self.trie_store.deref());
+            let result = pruning.commit_many::<LmdbEnvironment, _, _, Self::Error>(
+                &self.environment,
+                &store,
+                correlation_id,
+                prestate_hash,
+                effects,
+            );
+            if result.is_err() {
+                self.trie_cache.clear();
+            }
+            return result;
+        }
+        let commit_many_result = commit_many::<LmdbEnvironment, LmdbTrieStore, _, Self::Error>(
+            &self.environment,
+            &self.trie_store,
+            correlation_id,
+            prestate_hash,
+            effects,
+        )?;
+        Ok(commit_many_result)
+    }
+
     fn put_protocol_data(
         &self,
         protocol_version: ProtocolVersion,
@@ -162,181 +396,124 @@ impl StateProvider for LmdbGlobalState {
 #[cfg(test)]
 mod tests {
     use lmdb::DatabaseFlags;
-    use tempfile::tempdir;
+    use tempfile::{tempdir, TempDir};
 
-    use types::{account::AccountHash, CLValue};
+    use engine_shared::os::get_page_size;
+    use types::{AccessRights, CLValue, URef};
 
     use crate::{
-        trie_store::operations::{write, WriteResult},
+        global_state::tests::{
+            commit_until_pruned, create_test_pairs, create_test_state, TestPair, TestState,
+        },
         TEST_MAP_SIZE,
     };
 
     use super::*;
 
-    #[derive(Debug, Clone)]
-    struct TestPair {
-        key: Key,
-        value: StoredValue,
-    }
+    #[test]
+    fn checkouts_share_trie_cache() {
+        let correlation_id = CorrelationId::new();
+        let (state, root_hash, _temp_dir) = create_test_state::<LmdbGlobalState>();
+        let state = state.with_trie_cache(1024 * 1024);
 
-    fn create_test_pairs() -> [TestPair; 2] {
-        [
-            TestPair {
-                key: Key::Account(AccountHash::new([1_u8; 32])),
-                value: StoredValue::CLValue(CLValue::from_t(1_i32).unwrap()),
-            },
-            TestPair {
-                key: Key::Account(AccountHash::new([2_u8; 32])),
-                value: StoredValue::CLValue(CLValue::from_t(2_i32).unwrap()),
-            },
-        ]
-    }
+        let checkout = state.checkout(root_hash).unwrap().unwrap();
+        for TestPair { key, value } in create_test_pairs().iter().cloned() {
+            assert_eq!(Some(value), checkout.read(correlation_id, &key).unwrap());
+        }
+        let misses = state.trie_cache.misses();
+        assert!(misses > 0);
+
+        let other_checkout = state.checkout(root_hash).unwrap().unwrap();
+        for TestPair { key, value } in create_test_pairs().iter().cloned() {
+            assert_eq!(
+                Some(value),
+                other_checkout.read(correlation_id, &key).unwrap()
+            );
+        }
+        assert_eq!(state.trie_cache.misses(), misses);
 
-    fn create_test_pairs_updated() -> [TestPair; 3] {
-        [
-            TestPair {
-                key: Key::Account(AccountHash::new([1u8; 32])),
-                value: StoredValue::CLValue(CLValue::from_t("one".to_string()).unwrap()),
-            },
-            TestPair {
-                key: Key::Account(AccountHash::new([2u8; 32])),
-                value: StoredValue::CLValue(CLValue::from_t("two".to_string()).unwrap()),
-            },
-            TestPair {
-                key: Key::Account(AccountHash::new([3u8; 32])),
-                value: StoredValue::CLValue(CLValue::from_t(3_i32).unwrap()),
-            },
-        ]
+        // Tries deleted by a prune are evicted from the cache
+        let state = state.with_test_pruning(1);
+        assert_eq!(
+            LmdbGlobalState::start_prune(&state, &[]).unwrap(),
+            StartPruneResult::Started
+        );
+        commit_until_pruned(&state, state.empty_root_hash);
+        assert!(state.checkout(root_hash).unwrap().is_none());
     }
 
-    fn create_test_state() -> (LmdbGlobalState, Blake2bHash) {
-        let correlation_id = CorrelationId::new();
-        let _temp_dir = tempdir().unwrap();
+    /// Creates an empty state whose map starts at 16 pages, and may grow up to `max_map_size`.
+    fn create_tiny_state(max_map_size: usize) -> (LmdbGlobalState, TempDir) {
+        let temp_dir = tempdir().unwrap();
+        let map_size = get_page_size().unwrap() * 16;
         let environment = Arc::new(
-            LmdbEnvironment::new(&_temp_dir.path().to_path_buf(), *TEST_MAP_SIZE).unwrap(),
+            LmdbEnvironment::new(&temp_dir.path().to_path_buf(), map_size)
+                .unwrap()
+                .with_map_growth(max_map_size, 2.0),
         );
         let trie_store =
             Arc::new(LmdbTrieStore::new(&environment, None, DatabaseFlags::empty()).unwrap());
         let protocol_data_store = Arc::new(
             LmdbProtocolDataStore::new(&environment, None, DatabaseFlags::empty()).unwrap(),
         );
-        let ret = LmdbGlobalState::empty(environment, trie_store, protocol_data_store).unwrap();
-        let mut current_root = ret.empty_root_hash;
-        {
-            let mut txn = ret.environment.create_read_write_txn().unwrap();
-
-            for TestPair { key, value } in &create_test_pairs() {
-                match write::<_, _, _, LmdbTrieStore, error::Error>(
-                    correlation_id,
-                    &mut txn,
-                    &ret.trie_store,
-                    &current_root,
-                    key,
-                    value,
-                )
-                .unwrap()
-                {
-                    WriteResult::Written(root_hash) => {
-                        current_root = root_hash;
-                    }
-                    WriteResult::AlreadyExists => (),
-                    WriteResult::RootNotFound => panic!("LmdbGlobalState has invalid root"),
-                }
-            }
-
-            txn.commit().unwrap();
-        }
-        (ret, current_root)
+        let state = LmdbGlobalState::empty(environment, trie_store, protocol_data_store).unwrap();
+        (state, temp_dir)
     }
 
-    #[test]
-    fn reads_from_a_checkout_return_expected_values() {
-        let correlation_id = CorrelationId::new();
-        let (state, root_hash) = create_test_state();
-        let checkout = state.checkout(root_hash).unwrap().unwrap();
-        for TestPair { key, value } in create_test_pairs().iter().cloned() {
-            assert_eq!(Some(value), checkout.read(correlation_id, &key).unwrap());
+    /// Returns writes of 1000 values of 256 bytes each.
+    fn create_large_effects() -> AdditiveMap<Key, Transform> {
+        let mut ret = AdditiveMap::new();
+        for i in 0..1000u64 {
+            let mut addr = [0u8; 32];
+            addr[..8].copy_from_slice(&i.to_le_bytes());
+            let key = Key::URef(URef::new(addr, AccessRights::READ_ADD_WRITE)).normalize();
+            let value = StoredValue::CLValue(CLValue::from_t(vec![i as u8; 256]).unwrap());
+            ret.insert(key, Transform::Write(value));
         }
+        ret
     }
 
     #[test]
-    fn checkout_fails_if_unknown_hash_is_given() {
-        let (state, _) = create_test_state();
-        let fake_hash: Blake2bHash = [1u8; 32].into();
-        let result = state.checkout(fake_hash).unwrap();
-        assert!(result.is_none());
-    }
-
-    #[test]
-    fn commit_updates_state() {
+    fn commit_grows_full_map() {
         let correlation_id = CorrelationId::new();
-        let test_pairs_updated = create_test_pairs_updated();
-
-        let (state, root_hash) = create_test_state();
+        let (state, _temp_dir) = create_tiny_state(*TEST_MAP_SIZE);
+        let initial_map_size = state.environment.map_size();
+        let effects = create_large_effects();
 
-        let effects: AdditiveMap<Key, Transform> = {
-            let mut tmp = AdditiveMap::new();
-            for TestPair { key, value } in &test_pairs_updated {
-                tmp.insert(*key, Transform::Write(value.to_owned()));
-            }
-            tmp
-        };
-
-        let updated_hash = match state.commit(correlation_id, root_hash, effects).unwrap() {
+        let root_hash = match state
+            .commit(correlation_id, state.empty_root_hash, effects.clone())
+            .unwrap()
+        {
             CommitResult::Success { state_root, .. } => state_root,
             _ => panic!("commit failed"),
         };
+        assert!(state.environment.map_size() > initial_map_size);
 
-        let updated_checkout = state.checkout(updated_hash).unwrap().unwrap();
-
-        for TestPair { key, value } in test_pairs_updated.iter().cloned() {
-            assert_eq!(
-                Some(value),
-                updated_checkout.read(correlation_id, &key).unwrap()
-            );
+        let checkout = state.checkout(root_hash).unwrap().unwrap();
+        for (key, transform) in effects.iter() {
+            let value = match transform {
+                Transform::Write(value) => value.to_owned(),
+                _ => panic!("should be a write"),
+            };
+            assert_eq!(Some(value), checkout.read(correlation_id, key).unwrap());
         }
     }
 
     #[test]
-    fn commit_updates_state_and_original_state_stays_intact() {
+    fn commit_fails_when_map_reaches_max_size() {
         let correlation_id = CorrelationId::new();
-        let test_pairs_updated = create_test_pairs_updated();
-
-        let (state, root_hash) = create_test_state();
-
-        let effects: AdditiveMap<Key, Transform> = {
-            let mut tmp = AdditiveMap::new();
-            for TestPair { key, value } in &test_pairs_updated {
-                tmp.insert(*key, Transform::Write(value.to_owned()));
-            }
-            tmp
-        };
+        let max_map_size = get_page_size().unwrap() * 32;
+        let (state, _temp_dir) = create_tiny_state(max_map_size);
 
-        let updated_hash = match state.commit(correlation_id, root_hash, effects).unwrap() {
-            CommitResult::Success { state_root, .. } => state_root,
-            _ => panic!("commit failed"),
-        };
-
-        let updated_checkout = state.checkout(updated_hash).unwrap().unwrap();
-        for TestPair { key, value } in test_pairs_updated.iter().cloned() {
-            assert_eq!(
-                Some(value),
-                updated_checkout.read(correlation_id, &key).unwrap()
-            );
-        }
-
-        let original_checkout = state.checkout(root_hash).unwrap().unwrap();
-        for TestPair { key, value } in create_test_pairs().iter().cloned() {
-            assert_eq!(
-                Some(value),
-                original_checkout.read(correlation_id, &key).unwrap()
-            );
-        }
-        assert_eq!(
-            None,
-            original_checkout
-                .read(correlation_id, &test_pairs_updated[2].key)
-                .unwrap()
+        let result = state.commit(
+            correlation_id,
+            state.empty_root_hash,
+            create_large_effects(),
         );
+        match result {
+            Err(error::Error::Lmdb(lmdb::Error::MapFull)) => (),
+            _ => panic!("unexpected result: {:?}", result),
+        }
+        assert_eq!(state.environment.map_size(), max_map_size);
     }
 }
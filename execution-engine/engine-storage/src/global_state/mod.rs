@@ -1,7 +1,15 @@
 pub mod in_memory;
 pub mod lmdb;
+pub mod scratch;
+pub mod sled;
+#[cfg(test)]
+mod tests;
 
-use std::{collections::HashMap, fmt, hash::BuildHasher, time::Instant};
+use std::{
+    cell::RefCell, collections::HashMap, fmt, hash::BuildHasher, sync::Arc, time::Instant,
+};
+
+use parking_lot::Mutex;
 
 use engine_shared::{
     additive_map::AdditiveMap,
@@ -14,11 +22,17 @@ use engine_shared::{
 use types::{account::AccountHash, bytesrepr, Key, ProtocolVersion, U512};
 
 use crate::{
+    error::TrieNotFound,
     protocol_data::ProtocolData,
-    transaction_source::{Transaction, TransactionSource},
-    trie::Trie,
+    prune_store::{PruneStore, PRUNE_STORE_KEY},
+    transaction_source::{Readable, Transaction, TransactionSource, Writable},
+    trie::{Trie, TrieMerkleProof},
     trie_store::{
-        operations::{read, write, ReadResult, WriteResult},
+        operations::{
+            batch::write_batch,
+            prune::{MarkingTrieStore, PruneStepResult, TriePruner},
+            read, ReadResult, WriteResult,
+        },
         TrieStore,
     },
     GAUGE_METRIC_KEY,
@@ -38,6 +52,62 @@ pub trait StateReader<K, V> {
 
     /// Returns the state value from the corresponding key
     fn read(&self, correlation_id: CorrelationId, key: &K) -> Result<Option<V>, Self::Error>;
+
+    /// Returns a proof of the state value from the corresponding key, or of its absence.
+    ///
+    /// Returns `None` if the reader can't prove its state, e.g. because it holds uncommitted
+    /// writes.
+    fn read_with_proof(
+        &self,
+        correlation_id: CorrelationId,
+        key: &K,
+    ) -> Result<Option<TrieMerkleProof<K, V>>, Self::Error>;
+}
+
+/// A page of keys and values, in the order of their serialized keys.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatePage<K, V> {
+    pub entries: Vec<(K, V)>,
+    /// Whether there are more entries after this page.  The next page starts after the key of the
+    /// last entry of this one.
+    pub has_more: bool,
+}
+
+/// A reader of state which can list the values under a prefix of the serialized keys.
+pub trait StateIterator<K, V>: StateReader<K, V> {
+    /// Returns up to `limit` of the keys and values whose serialized keys start with `prefix`,
+    /// starting after `start_after`.
+    ///
+    /// The prefix `&[KeyTag::URef as u8]` selects every [`Key::URef`], and so on for the other
+    /// [`KeyTag`](types::KeyTag)s.
+    fn read_prefix(
+        &self,
+        correlation_id: CorrelationId,
+        prefix: &[u8],
+        start_after: Option<&K>,
+        limit: usize,
+    ) -> Result<StatePage<K, V>, Self::Error>;
+}
+
+/// Collects the first `limit` of `entries` into a page.
+pub(crate) fn collect_page<K, V, E>(
+    entries: impl Iterator<Item = Result<(K, V), E>>,
+    limit: usize,
+) -> Result<StatePage<K, V>, E> {
+    let mut ret = Vec::new();
+    for entry in entries {
+        if ret.len() == limit {
+            return Ok(StatePage {
+                entries: ret,
+                has_more: true,
+            });
+        }
+        ret.push(entry?);
+    }
+    Ok(StatePage {
+        entries: ret,
+        has_more: false,
+    })
 }
 
 #[derive(Debug)]
@@ -73,6 +143,37 @@ impl fmt::Display for CommitResult {
     }
 }
 
+/// The result of committing several sets of effects in a row.
+#[derive(Debug)]
+pub enum CommitManyResult {
+    /// Every set of effects was committed.  Holds the state root after each of them, in order,
+    /// and the validators bonded in the last of those states.
+    Success {
+        state_roots: Vec<Blake2bHash>,
+        bonded_validators: HashMap<AccountHash, U512>,
+    },
+    /// The set of effects at `index` failed with `result`, so none of the sets were committed.
+    Failure { index: usize, result: CommitResult },
+}
+
+impl fmt::Display for CommitManyResult {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match self {
+            CommitManyResult::Success {
+                state_roots,
+                bonded_validators,
+            } => write!(
+                f,
+                "Success: state_roots: {:?}, bonded_validators: {:?}",
+                state_roots, bonded_validators
+            ),
+            CommitManyResult::Failure { index, result } => {
+                write!(f, "Failure at effects {}: {}", index, result)
+            }
+        }
+    }
+}
+
 impl From<transform::Error> for CommitResult {
     fn from(error: transform::Error) -> Self {
         match error {
@@ -100,6 +201,15 @@ pub trait StateProvider {
         effects: AdditiveMap<Key, Transform>,
     ) -> Result<CommitResult, Self::Error>;
 
+    /// Applies each set of changes on top of the post state of the previous one, and returns the
+    /// post state hash after each of them.  Either all of the sets are committed or none are.
+    fn commit_many(
+        &self,
+        correlation_id: CorrelationId,
+        state_hash: Blake2bHash,
+        effects: Vec<AdditiveMap<Key, Transform>>,
+    ) -> Result<CommitManyResult, Self::Error>;
+
     fn put_protocol_data(
         &self,
         protocol_version: ProtocolVersion,
@@ -128,21 +238,353 @@ where
     E: From<R::Error> + From<S::Error> + From<types::bytesrepr::Error>,
     H: BuildHasher,
 {
+    let start = Instant::now();
     let mut txn = environment.create_read_write_txn()?;
-    let mut state_root = prestate_hash;
 
-    let maybe_root: Option<Trie<Key, StoredValue>> = store.get(&txn, &state_root)?;
+    let maybe_root: Option<Trie<Key, StoredValue>> = store.get(&txn, &prestate_hash)?;
 
     if maybe_root.is_none() {
         return Ok(CommitResult::RootNotFound);
     };
 
+    let state_root =
+        match apply_effects::<_, _, _, E>(correlation_id, &mut txn, store, prestate_hash, effects)?
+        {
+            Ok(state_root) => state_root,
+            Err(result) => return Ok(result),
+        };
+
+    txn.commit()?;
+
+    log_duration(
+        correlation_id,
+        GLOBAL_STATE_COMMIT_DURATION,
+        COMMIT,
+        start.elapsed(),
+    );
+
+    let bonded_validators = Default::default();
+
+    Ok(CommitResult::Success {
+        state_root,
+        bonded_validators,
+    })
+}
+
+/// Applies each set of effects on top of the state root left by the previous one, starting from
+/// `prestate_hash`, all within a single transaction.
+///
+/// If any set of effects fails to apply, the transaction is aborted and none of the sets are
+/// committed.
+pub fn commit_many<'a, R, S, H, E>(
+    environment: &'a R,
+    store: &S,
+    correlation_id: CorrelationId,
+    prestate_hash: Blake2bHash,
+    effects: Vec<AdditiveMap<Key, Transform, H>>,
+) -> Result<CommitManyResult, E>
+where
+    R: TransactionSource<'a, Handle = S::Handle>,
+    S: TrieStore<Key, StoredValue>,
+    S::Error: From<R::Error>,
+    E: From<R::Error> + From<S::Error> + From<types::bytesrepr::Error>,
+    H: BuildHasher,
+{
+    commit_many_with::<_, _, _, E, _>(
+        environment,
+        store,
+        correlation_id,
+        prestate_hash,
+        effects,
+        |_, _| Ok(()),
+    )
+}
+
+/// The result of [`CommitPruning::start`].
+#[derive(Debug, PartialEq, Eq)]
+pub enum StartPruneResult {
+    Started,
+    RootNotFound(Blake2bHash),
+}
+
+/// A prune of the tries of a global state which is driven by its commits.
+///
+/// Every commit made through [`CommitPruning::commit`] or [`CommitPruning::commit_many`] while a
+/// prune is in progress marks the tries it writes, retains the state roots it produces, and then
+/// runs one step of the prune which marks or sweeps at most `batch_size` tries, within its own
+/// transaction.  The progress of
+/// the prune is saved in `prune_store` by the same transaction, so a prune interrupted by a restart
+/// carries on with the next commit.  Commits are serialized while pruning is enabled.
+pub struct CommitPruning<P> {
+    prune_store: Arc<P>,
+    batch_size: usize,
+    /// The pruner saved in `prune_store`, or `None` if it has not been loaded yet or no prune is
+    /// in progress.
+    pruner: Mutex<Option<TriePruner>>,
+}
+
+impl<P: PruneStore> CommitPruning<P> {
+    pub fn new(prune_store: Arc<P>, batch_size: usize) -> Self {
+        CommitPruning {
+            prune_store,
+            batch_size,
+            pruner: Mutex::new(None),
+        }
+    }
+
+    /// Starts a prune which keeps the tries reachable from `retained_roots`, replacing the prune
+    /// in progress if there is one.
+    ///
+    /// Nothing is started if one of `retained_roots` is not found.
+    pub fn start<'a, R, S, E>(
+        &self,
+        environment: &'a R,
+        store: &S,
+        retained_roots: &[Blake2bHash],
+    ) -> Result<StartPruneResult, E>
+    where
+        R: TransactionSource<'a, Handle = S::Handle>,
+        S: TrieStore<Key, StoredValue>,
+        S::Error: From<R::Error>,
+        P: PruneStore<Handle = S::Handle>,
+        P::Error: From<R::Error>,
+        E: From<R::Error> + From<S::Error> + From<P::Error>,
+    {
+        let mut pruner = self.pruner.lock();
+        let mut txn = environment.create_read_write_txn()?;
+        for root in retained_roots {
+            if store.get(&txn, root)?.is_none() {
+                return Ok(StartPruneResult::RootNotFound(*root));
+            }
+        }
+        let trie_pruner = TriePruner::new(retained_roots);
+        self.prune_store
+            .put(&mut txn, &PRUNE_STORE_KEY, &trie_pruner)?;
+        txn.commit()?;
+        *pruner = Some(trie_pruner);
+        Ok(StartPruneResult::Started)
+    }
+
+    /// Commits like [`commit`], running a step of the prune in progress if there is one.
+    pub fn commit<'a, R, S, H, E>(
+        &self,
+        environment: &'a R,
+        store: &S,
+        correlation_id: CorrelationId,
+        prestate_hash: Blake2bHash,
+        effects: AdditiveMap<Key, Transform, H>,
+    ) -> Result<CommitResult, E>
+    where
+        R: TransactionSource<'a, Handle = S::Handle>,
+        S: TrieStore<Key, StoredValue>,
+        S::Error: From<R::Error>,
+        P: PruneStore<Handle = S::Handle>,
+        P::Error: From<R::Error>,
+        E: From<R::Error>
+            + From<S::Error>
+            + From<P::Error>
+            + From<types::bytesrepr::Error>
+            + From<TrieNotFound>,
+        H: BuildHasher,
+    {
+        let result = self.commit_many::<R, S, H, E>(
+            environment,
+            store,
+            correlation_id,
+            prestate_hash,
+            vec![effects],
+        )?;
+        let ret = match result {
+            CommitManyResult::Success {
+                mut state_roots,
+                bonded_validators,
+            } => CommitResult::Success {
+                state_root: state_roots.pop().expect("should have a state root"),
+                bonded_validators,
+            },
+            CommitManyResult::Failure { result, .. } => result,
+        };
+        Ok(ret)
+    }
+
+    /// Commits like [`commit_many`], running a step of the prune in progress if there is one.
+    pub fn commit_many<'a, R, S, H, E>(
+        &self,
+        environment: &'a R,
+        store: &S,
+        correlation_id: CorrelationId,
+        prestate_hash: Blake2bHash,
+        effects: Vec<AdditiveMap<Key, Transform, H>>,
+    ) -> Result<CommitManyResult, E>
+    where
+        R: TransactionSource<'a, Handle = S::Handle>,
+        S: TrieStore<Key, StoredValue>,
+        S::Error: From<R::Error>,
+        P: PruneStore<Handle = S::Handle>,
+        P::Error: From<R::Error>,
+        E: From<R::Error>
+            + From<S::Error>
+            + From<P::Error>
+            + From<types::bytesrepr::Error>
+            + From<TrieNotFound>,
+        H: BuildHasher,
+    {
+        let mut maybe_pruner = self.pruner.lock();
+        if maybe_pruner.is_none() {
+            let txn = environment.create_read_txn()?;
+            *maybe_pruner = self.prune_store.get(&txn, &PRUNE_STORE_KEY)?;
+            txn.commit()?;
+        }
+        let pruner = match maybe_pruner.take() {
+            Some(pruner) => RefCell::new(pruner),
+            None => {
+                return commit_many_with::<_, _, _, E, _>(
+                    environment,
+                    store,
+                    correlation_id,
+                    prestate_hash,
+                    effects,
+                    |_, _| Ok(()),
+                )
+            }
+        };
+
+        // The tries written by the commit are marked as they are written, so the step only has to
+        // mark what they refer to
+        let marking_store = MarkingTrieStore::new(store, &pruner);
+        let mut is_complete = false;
+        let result = commit_many_with::<_, _, _, E, _>(
+            environment,
+            &marking_store,
+            correlation_id,
+            prestate_hash,
+            effects,
+            |txn, state_roots| {
+                let mut pruner = pruner.borrow_mut();
+                for state_root in state_roots {
+                    pruner.retain(*state_root);
+                }
+                match pruner.step::<Key, StoredValue, _, S, E>(
+                    correlation_id,
+                    txn,
+                    store,
+                    self.batch_size,
+                )? {
+                    PruneStepResult::InProgress => {
+                        self.prune_store.put(txn, &PRUNE_STORE_KEY, &pruner)?
+                    }
+                    PruneStepResult::Complete(_) => {
+                        self.prune_store.delete(txn, &PRUNE_STORE_KEY)?;
+                        is_complete = true;
+                    }
+                }
+                Ok(())
+            },
+        );
+        // Unless the commit succeeded, the pruner may hold progress which was not committed, so
+        // the saved one is loaded again by the next commit
+        if result.is_ok() && !is_complete {
+            *maybe_pruner = Some(pruner.into_inner());
+        }
+        result
+    }
+}
+
+/// Applies each set of effects like [`commit_many`], and calls `before_commit` with the
+/// transaction and the resulting state roots before committing it.
+fn commit_many_with<'a, R, S, H, E, F>(
+    environment: &'a R,
+    store: &S,
+    correlation_id: CorrelationId,
+    prestate_hash: Blake2bHash,
+    effects: Vec<AdditiveMap<Key, Transform, H>>,
+    before_commit: F,
+) -> Result<CommitManyResult, E>
+where
+    R: TransactionSource<'a, Handle = S::Handle>,
+    S: TrieStore<Key, StoredValue>,
+    S::Error: From<R::Error>,
+    E: From<R::Error> + From<S::Error> + From<types::bytesrepr::Error>,
+    H: BuildHasher,
+    F: FnOnce(&mut R::ReadWriteTransaction, &[Blake2bHash]) -> Result<(), E>,
+{
+    let start = Instant::now();
+    let mut txn = environment.create_read_write_txn()?;
+
+    let maybe_root: Option<Trie<Key, StoredValue>> = store.get(&txn, &prestate_hash)?;
+
+    if maybe_root.is_none() {
+        return Ok(CommitManyResult::Failure {
+            index: 0,
+            result: CommitResult::RootNotFound,
+        });
+    };
+
+    let mut state_roots = Vec::with_capacity(effects.len());
+    let mut state_root = prestate_hash;
+    for (index, effects) in effects.into_iter().enumerate() {
+        state_root = match apply_effects::<_, _, _, E>(
+            correlation_id,
+            &mut txn,
+            store,
+            state_root,
+            effects,
+        )? {
+            Ok(state_root) => state_root,
+            Err(result) => return Ok(CommitManyResult::Failure { index, result }),
+        };
+        state_roots.push(state_root);
+    }
+
+    before_commit(&mut txn, &state_roots)?;
+
+    txn.commit()?;
+
+    log_duration(
+        correlation_id,
+        GLOBAL_STATE_COMMIT_DURATION,
+        COMMIT,
+        start.elapsed(),
+    );
+
+    let bonded_validators = Default::default();
+
+    Ok(CommitManyResult::Success {
+        state_roots,
+        bonded_validators,
+    })
+}
+
+/// Applies `effects` on top of `state_root` within `txn`, and returns the resulting state root.
+///
+/// Returns the failed [`CommitResult`] if a key to transform is missing or a transform doesn't
+/// apply to its value, in which case `txn` must not be committed.
+fn apply_effects<T, S, H, E>(
+    correlation_id: CorrelationId,
+    txn: &mut T,
+    store: &S,
+    state_root: Blake2bHash,
+    effects: AdditiveMap<Key, Transform, H>,
+) -> Result<Result<Blake2bHash, CommitResult>, E>
+where
+    T: Readable<Handle = S::Handle> + Writable<Handle = S::Handle>,
+    S: TrieStore<Key, StoredValue>,
+    S::Error: From<T::Error>,
+    E: From<S::Error> + From<types::bytesrepr::Error>,
+    H: BuildHasher,
+{
     let start = Instant::now();
     let mut reads: i32 = 0;
-    let mut writes: i32 = 0;
 
+    let mut updates = Vec::with_capacity(effects.len());
     for (key, transform) in effects.into_iter() {
-        let read_result = read::<_, _, _, _, E>(correlation_id, &txn, store, &state_root, &key)?;
+        if let Transform::Prune = transform {
+            // Pruning a key which is already absent leaves the state unchanged
+            updates.push((key, None));
+            continue;
+        }
+
+        let read_result = read::<_, _, _, _, E>(correlation_id, txn, store, &state_root, &key)?;
 
         log_duration(
             correlation_id,
@@ -156,44 +598,35 @@ where
         let value = match (read_result, transform) {
             (ReadResult::NotFound, Transform::Write(new_value)) => new_value,
             (ReadResult::NotFound, _) => {
-                return Ok(CommitResult::KeyNotFound(key));
+                return Ok(Err(CommitResult::KeyNotFound(key)));
             }
             (ReadResult::Found(current_value), transform) => match transform.apply(current_value) {
                 Ok(updated_value) => updated_value,
-                Err(err) => return Ok(err.into()),
+                Err(err) => return Ok(Err(err.into())),
             },
             _x @ (ReadResult::RootNotFound, _) => panic!(stringify!(_x._1)),
         };
 
-        let write_result =
-            write::<_, _, _, _, E>(correlation_id, &mut txn, store, &state_root, &key, &value)?;
-
-        log_duration(
-            correlation_id,
-            GLOBAL_STATE_COMMIT_WRITE_DURATION,
-            COMMIT,
-            start.elapsed(),
-        );
-
-        match write_result {
-            WriteResult::Written(root_hash) => {
-                state_root = root_hash;
-                writes += 1;
-            }
-            WriteResult::AlreadyExists => (),
-            _x @ WriteResult::RootNotFound => panic!(stringify!(_x)),
-        }
+        updates.push((key, Some(value)));
     }
 
-    txn.commit()?;
+    let writes = updates.len() as i32;
+    let write_result =
+        write_batch::<_, _, _, _, E>(correlation_id, txn, store, &state_root, updates)?;
 
     log_duration(
         correlation_id,
-        GLOBAL_STATE_COMMIT_DURATION,
+        GLOBAL_STATE_COMMIT_WRITE_DURATION,
         COMMIT,
         start.elapsed(),
     );
 
+    let ret = match write_result {
+        WriteResult::Written(root_hash) => root_hash,
+        WriteResult::AlreadyExists => state_root,
+        _x @ WriteResult::RootNotFound => panic!(stringify!(_x)),
+    };
+
     log_metric(
         correlation_id,
         GLOBAL_STATE_COMMIT_READS,
@@ -210,10 +643,5 @@ where
         f64::from(writes),
     );
 
-    let bonded_validators = Default::default();
-
-    Ok(CommitResult::Success {
-        state_root,
-        bonded_validators,
-    })
+    Ok(Ok(ret))
 }
@@ -0,0 +1,456 @@
+//! A global state for "what if" simulations against an [`LmdbGlobalState`], which never writes to
+//! disk unless it is flushed.
+//!
+//! Commits against a [`ScratchGlobalState`] produce the same state roots as they would against the
+//! underlying state, and those roots can be checked out again, but the new tries and protocol data
+//! are only held in memory.  Dropping the scratch state discards them, and
+//! [`ScratchGlobalState::flush`] writes them into the underlying state.
+
+use std::{ops::Deref, sync::Arc};
+
+use engine_shared::{
+    additive_map::AdditiveMap,
+    newtypes::{Blake2bHash, CorrelationId},
+    stored_value::StoredValue,
+    transform::Transform,
+};
+use types::{bytesrepr::ToBytes, Key, ProtocolVersion};
+
+use crate::{
+    error,
+    global_state::{
+        collect_page, commit, commit_many, lmdb::LmdbGlobalState, CommitManyResult, CommitResult,
+        StateIterator, StatePage, StateProvider, StateReader,
+    },
+    protocol_data::ProtocolData,
+    protocol_data_store::scratch::ScratchProtocolDataStore,
+    store::Store,
+    transaction_source::{
+        scratch::{FlushCounts, ScratchEnvironment, ScratchReadTransaction},
+        Transaction, TransactionSource,
+    },
+    trie::{Trie, TrieMerkleProof},
+    trie_store::{
+        cache::TrieCache,
+        operations::{entries_with_prefix, read, read_with_proof, ReadResult},
+        scratch::ScratchTrieStore,
+    },
+};
+
+pub struct ScratchGlobalState {
+    pub environment: Arc<ScratchEnvironment>,
+    pub trie_store: Arc<ScratchTrieStore>,
+    pub protocol_data_store: Arc<ScratchProtocolDataStore>,
+    pub empty_root_hash: Blake2bHash,
+    /// The cache of the underlying state, which must forget the tries deleted by a flush.
+    lmdb_trie_cache: Arc<TrieCache<Key, StoredValue>>,
+}
+
+/// Represents a "view" of global state at a particular root hash.
+pub struct ScratchGlobalStateView {
+    pub environment: Arc<ScratchEnvironment>,
+    pub store: Arc<ScratchTrieStore>,
+    pub root_hash: Blake2bHash,
+}
+
+impl ScratchGlobalState {
+    /// Creates an empty overlay of `state`.
+    pub fn new(state: &LmdbGlobalState) -> Self {
+        let environment = Arc::new(ScratchEnvironment::new(Arc::clone(&state.environment)));
+        let trie_store = Arc::new(ScratchTrieStore::new(
+            &environment,
+            state.trie_store.deref().clone(),
+        ));
+        let protocol_data_store = Arc::new(ScratchProtocolDataStore::new(
+            &environment,
+            state.protocol_data_store.deref().clone(),
+        ));
+        ScratchGlobalState {
+            environment,
+            trie_store,
+            protocol_data_store,
+            empty_root_hash: state.empty_root_hash,
+            lmdb_trie_cache: Arc::clone(&state.trie_cache),
+        }
+    }
+
+    /// Writes every trie and protocol data committed to the overlay into the underlying state,
+    /// within a single read-write transaction.
+    ///
+    /// The overlay stays usable afterwards.  No transaction of the overlay may be open on the
+    /// calling thread.
+    pub fn flush(&self) -> Result<FlushCounts, error::Error> {
+        let handles = [
+            Store::<Blake2bHash, Trie<Key, StoredValue>>::handle(self.trie_store.deref()),
+            self.protocol_data_store.handle(),
+        ];
+        let ret = self.environment.flush(&handles)?;
+        if ret.deleted > 0 {
+            self.lmdb_trie_cache.clear();
+        }
+        Ok(ret)
+    }
+}
+
+impl StateReader<Key, StoredValue> for ScratchGlobalStateView {
+    type Error = error::Error;
+
+    fn read(
+        &self,
+        correlation_id: CorrelationId,
+        key: &Key,
+    ) -> Result<Option<StoredValue>, Self::Error> {
+        let txn = self.environment.create_read_txn()?;
+        let ret =
+            match read::<Key, StoredValue, ScratchReadTransaction, ScratchTrieStore, Self::Error>(
+                correlation_id,
+                &txn,
+                self.store.deref(),
+                &self.root_hash,
+                key,
+            )? {
+                ReadResult::Found(value) => Some(value),
+                ReadResult::NotFound => None,
+                ReadResult::RootNotFound => panic!("ScratchGlobalState has invalid root"),
+            };
+        txn.commit()?;
+        Ok(ret)
+    }
+
+    fn read_with_proof(
+        &self,
+        correlation_id: CorrelationId,
+        key: &Key,
+    ) -> Result<Option<TrieMerkleProof<Key, StoredValue>>, Self::Error> {
+        let txn = self.environment.create_read_txn()?;
+        let ret = match read_with_proof::<
+            Key,
+            StoredValue,
+            ScratchReadTransaction,
+            ScratchTrieStore,
+            Self::Error,
+        >(
+            correlation_id,
+            &txn,
+            self.store.deref(),
+            &self.root_hash,
+            key,
+        )? {
+            Some(proof) => proof,
+            None => panic!("ScratchGlobalState has invalid root"),
+        };
+        txn.commit()?;
+        Ok(Some(ret))
+    }
+}
+
+impl StateIterator<Key, StoredValue> for ScratchGlobalStateView {
+    fn read_prefix(
+        &self,
+        correlation_id: CorrelationId,
+        prefix: &[u8],
+        start_after: Option<&Key>,
+        limit: usize,
+    ) -> Result<StatePage<Key, StoredValue>, Self::Error> {
+        let start_after = start_after.map(ToBytes::to_bytes).transpose()?;
+        let txn = self.environment.create_read_txn()?;
+        let entries = entries_with_prefix::<Key, StoredValue, ScratchReadTransaction, _>(
+            correlation_id,
+            &txn,
+            self.store.deref(),
+            &self.root_hash,
+            prefix,
+            start_after.as_deref(),
+        );
+        let page = collect_page(entries, limit)?;
+        txn.commit()?;
+        Ok(page)
+    }
+}
+
+impl StateProvider for ScratchGlobalState {
+    type Error = error::Error;
+
+    type Reader = ScratchGlobalStateView;
+
+    fn checkout(&self, state_hash: Blake2bHash) -> Result<Option<Self::Reader>, Self::Error> {
+        let txn = self.environment.create_read_txn()?;
+        let maybe_root: Option<Trie<Key, StoredValue>> = self.trie_store.get(&txn, &state_hash)?;
+        let maybe_state = maybe_root.map(|_| ScratchGlobalStateView {
+            environment: Arc::clone(&self.environment),
+            store: Arc::clone(&self.trie_store),
+            root_hash: state_hash,
+        });
+        txn.commit()?;
+        Ok(maybe_state)
+    }
+
+    fn commit(
+        &self,
+        correlation_id: CorrelationId,
+        prestate_hash: Blake2bHash,
+        effects: AdditiveMap<Key, Transform>,
+    ) -> Result<CommitResult, Self::Error> {
+        let commit_result = commit::<ScratchEnvironment, ScratchTrieStore, _, Self::Error>(
+            &self.environment,
+            &self.trie_store,
+            correlation_id,
+            prestate_hash,
+            effects,
+        )?;
+        Ok(commit_result)
+    }
+
+    fn commit_many(
+        &self,
+        correlation_id: CorrelationId,
+        prestate_hash: Blake2bHash,
+        effects: Vec<AdditiveMap<Key, Transform>>,
+    ) -> Result<CommitManyResult, Self::Error> {
+        let commit_many_result = commit_many::<ScratchEnvironment, ScratchTrieStore, _, Self::Error>(
+            &self.environment,
+            &self.trie_store,
+            correlation_id,
+            prestate_hash,
+            effects,
+        )?;
+        Ok(commit_many_result)
+    }
+
+    fn put_protocol_data(
+        &self,
+        protocol_version: ProtocolVersion,
+        protocol_data: &ProtocolData,
+    ) -> Result<(), Self::Error> {
+        let mut txn = self.environment.create_read_write_txn()?;
+        self.protocol_data_store
+            .put(&mut txn, &protocol_version, protocol_data)?;
+        txn.commit()
+    }
+
+    fn get_protocol_data(
+        &self,
+        protocol_version: ProtocolVersion,
+    ) -> Result<Option<ProtocolData>, Self::Error> {
+        let txn = self.environment.create_read_txn()?;
+        let result = self.protocol_data_store.get(&txn, &protocol_version)?;
+        txn.commit()?;
+        Ok(result)
+    }
+
+    fn empty_root(&self) -> Blake2bHash {
+        self.empty_root_hash
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lmdb::DatabaseFlags;
+    use tempfile::{tempdir, TempDir};
+
+    use engine_shared::storage_costs::StorageCosts;
+    use engine_wasm_prep::{host_function_costs::HostFunctionCosts, wasm_costs::WasmCosts};
+    use types::{account::AccountHash, CLValue};
+
+    use crate::{
+        protocol_data_store::lmdb::LmdbProtocolDataStore,
+        transaction_source::{lmdb::LmdbEnvironment, Enumerable},
+        trie_store::lmdb::LmdbTrieStore,
+        TEST_MAP_SIZE,
+    };
+
+    use super::*;
+
+    fn create_test_pairs() -> Vec<(Key, StoredValue)> {
+        (1..=3_u8)
+            .map(|i| {
+                (
+                    Key::Account(AccountHash::new([i; 32])),
+                    StoredValue::CLValue(CLValue::from_t(i32::from(i)).unwrap()),
+                )
+            })
+            .collect()
+    }
+
+    fn create_effects(pairs: &[(Key, StoredValue)]) -> AdditiveMap<Key, Transform> {
+        let mut ret = AdditiveMap::new();
+        for (key, value) in pairs {
+            ret.insert(*key, Transform::Write(value.to_owned()));
+        }
+        ret
+    }
+
+    fn commit_effects<S>(
+        state: &S,
+        root_hash: Blake2bHash,
+        pairs: &[(Key, StoredValue)],
+    ) -> Blake2bHash
+    where
+        S: StateProvider,
+        S::Error: std::fmt::Debug,
+    {
+        match state
+            .commit(CorrelationId::new(), root_hash, create_effects(pairs))
+            .unwrap()
+        {
+            CommitResult::Success { state_root, .. } => state_root,
+            _ => panic!("commit failed"),
+        }
+    }
+
+    /// Returns a state holding the first test pair, and its root.
+    fn create_test_state() -> (LmdbGlobalState, Blake2bHash, TempDir) {
+        let temp_dir = tempdir().unwrap();
+        let environment =
+            Arc::new(LmdbEnvironment::new(&temp_dir.path().to_path_buf(), *TEST_MAP_SIZE).unwrap());
+        let trie_store =
+            Arc::new(LmdbTrieStore::new(&environment, None, DatabaseFlags::empty()).unwrap());
+        let protocol_data_store = Arc::new(
+            LmdbProtocolDataStore::new(&environment, None, DatabaseFlags::empty()).unwrap(),
+        );
+        let state = LmdbGlobalState::empty(environment, trie_store, protocol_data_store).unwrap();
+        let root_hash = commit_effects(&state, state.empty_root_hash, &create_test_pairs()[..1]);
+        (state, root_hash, temp_dir)
+    }
+
+    #[test]
+    fn commits_stay_in_memory_until_flushed() {
+        let correlation_id = CorrelationId::new();
+        let (state, root_hash, _temp_dir) = create_test_state();
+        let scratch = ScratchGlobalState::new(&state);
+        let pairs = create_test_pairs();
+
+        let scratch_root = commit_effects(&scratch, root_hash, &pairs);
+        let checkout = scratch.checkout(scratch_root).unwrap().unwrap();
+        for (key, value) in pairs.iter().cloned() {
+            assert_eq!(Some(value), checkout.read(correlation_id, &key).unwrap());
+        }
+        assert!(state.checkout(scratch_root).unwrap().is_none());
+
+        let counts = scratch.flush().unwrap();
+        assert!(counts.written > 0);
+        assert_eq!(counts.deleted, 0);
+        let lmdb_checkout = state.checkout(scratch_root).unwrap().unwrap();
+        for (key, value) in pairs.iter().cloned() {
+            assert_eq!(
+                Some(value),
+                lmdb_checkout.read(correlation_id, &key).unwrap()
+            );
+        }
+
+        // The underlying state arrives at the same root for the same effects.
+        assert_eq!(scratch_root, commit_effects(&state, root_hash, &pairs));
+    }
+
+    #[test]
+    fn dropping_the_overlay_leaves_the_underlying_state_unchanged() {
+        let correlation_id = CorrelationId::new();
+        let (state, root_hash, _temp_dir) = create_test_state();
+        let pairs = create_test_pairs();
+
+        let scratch_root = {
+            let scratch = ScratchGlobalState::new(&state);
+            let scratch_root = commit_effects(&scratch, root_hash, &pairs);
+            let protocol_version = ProtocolVersion::V1_0_0;
+            scratch
+                .put_protocol_data(protocol_version, &ProtocolData::default())
+                .unwrap();
+            assert_eq!(
+                Some(ProtocolData::default()),
+                scratch.get_protocol_data(protocol_version).unwrap()
+            );
+            scratch_root
+        };
+
+        assert!(state.checkout(scratch_root).unwrap().is_none());
+        assert_eq!(
+            None,
+            state.get_protocol_data(ProtocolVersion::V1_0_0).unwrap()
+        );
+        let checkout = state.checkout(root_hash).unwrap().unwrap();
+        assert_eq!(None, checkout.read(correlation_id, &pairs[1].0).unwrap());
+    }
+
+    #[test]
+    fn overlay_protocol_data_shadows_the_underlying_state() {
+        let (state, _, _temp_dir) = create_test_state();
+        let protocol_version = ProtocolVersion::V1_0_0;
+        let original = ProtocolData::default();
+        let updated = ProtocolData::new(
+            WasmCosts::default(),
+            HostFunctionCosts::default(),
+            StorageCosts::default(),
+            [1; 32],
+            [2; 32],
+            [3; 32],
+        );
+        state
+            .put_protocol_data(protocol_version, &original)
+            .unwrap();
+
+        let scratch = ScratchGlobalState::new(&state);
+        assert_eq!(
+            Some(original),
+            scratch.get_protocol_data(protocol_version).unwrap()
+        );
+        scratch
+            .put_protocol_data(protocol_version, &updated)
+            .unwrap();
+        assert_eq!(
+            Some(updated),
+            scratch.get_protocol_data(protocol_version).unwrap()
+        );
+        assert_eq!(
+            Some(original),
+            state.get_protocol_data(protocol_version).unwrap()
+        );
+
+        scratch.flush().unwrap();
+        assert_eq!(
+            Some(updated),
+            state.get_protocol_data(protocol_version).unwrap()
+        );
+    }
+
+    #[test]
+    fn deletes_hide_underlying_tries_until_flushed() {
+        let (state, root_hash, _temp_dir) = create_test_state();
+        let scratch = ScratchGlobalState::new(&state);
+        let handle =
+            Store::<Blake2bHash, Trie<Key, StoredValue>>::handle(scratch.trie_store.deref());
+
+        let mut txn = scratch.environment.create_read_write_txn().unwrap();
+        Store::<Blake2bHash, Trie<Key, StoredValue>>::delete(
+            scratch.trie_store.deref(),
+            &mut txn,
+            &root_hash,
+        )
+        .unwrap();
+        txn.commit().unwrap();
+
+        assert!(scratch.checkout(root_hash).unwrap().is_none());
+        assert!(state.checkout(root_hash).unwrap().is_some());
+        let txn = scratch.environment.create_read_txn().unwrap();
+        let keys = txn.keys_after(handle, None, usize::max_value()).unwrap();
+        txn.commit().unwrap();
+        assert!(!keys.contains(&root_hash.to_bytes().unwrap()));
+        assert!(keys.contains(&state.empty_root_hash.to_bytes().unwrap()));
+
+        let counts = scratch.flush().unwrap();
+        assert_eq!(
+            counts,
+            FlushCounts {
+                written: 0,
+                deleted: 1
+            }
+        );
+        assert!(state.checkout(root_hash).unwrap().is_none());
+
+        // Writing the trie again revives it.
+        assert_eq!(
+            root_hash,
+            commit_effects(&scratch, scratch.empty_root_hash, &create_test_pairs()[..1])
+        );
+        assert!(scratch.checkout(root_hash).unwrap().is_some());
+    }
+}
@@ -0,0 +1,335 @@
+use std::{ops::Deref, sync::Arc};
+
+use engine_shared::{
+    additive_map::AdditiveMap,
+    newtypes::{Blake2bHash, CorrelationId},
+    stored_value::StoredValue,
+    transform::Transform,
+};
+use types::{bytesrepr::ToBytes, Key, ProtocolVersion};
+
+use crate::{
+    error,
+    global_state::{
+        collect_page, commit, commit_many, CommitManyResult, CommitPruning, CommitResult,
+        StartPruneResult, StateIterator, StatePage, StateProvider, StateReader,
+    },
+    protocol_data::ProtocolData,
+    protocol_data_store::sled::SledProtocolDataStore,
+    prune_store::sled::SledPruneStore,
+    store::Store,
+    transaction_source::{
+        sled::{SledEnvironment, SledReadTransaction},
+        Transaction, TransactionSource,
+    },
+    trie::{operations::create_hashed_empty_trie, Trie, TrieMerkleProof},
+    trie_store::{
+        operations::{
+            diff::{self, DiffResult},
+            entries_with_prefix,
+            read, read_with_proof,
+            stats::{self, StatsResult},
+            ReadResult,
+        },
+        sled::SledTrieStore,
+    },
+};
+
+const PRUNING_EXPECT: &str = "pruning should be enabled with with_pruning";
+
+pub struct SledGlobalState {
+    pub environment: Arc<SledEnvironment>,
+    pub trie_store: Arc<SledTrieStore>,
+    pub protocol_data_store: Arc<SledProtocolDataStore>,
+    pub empty_root_hash: Blake2bHash,
+    /// The prune driven by commits, if pruning is enabled.
+    pruning: Option<CommitPruning<SledPruneStore>>,
+}
+
+/// Represents a "view" of global state at a particular root hash.
+pub struct SledGlobalStateView {
+    pub environment: Arc<SledEnvironment>,
+    pub store: Arc<SledTrieStore>,
+    pub root_hash: Blake2bHash,
+}
+
+impl SledGlobalState {
+    /// Creates an empty state from an existing environment and trie_store.
+    pub fn empty(
+        environment: Arc<SledEnvironment>,
+        trie_store: Arc<SledTrieStore>,
+        protocol_data_store: Arc<SledProtocolDataStore>,
+    ) -> Result<Self, error::Error> {
+        let root_hash: Blake2bHash = {
+            let (root_hash, root) = create_hashed_empty_trie::<Key, StoredValue>()?;
+            let mut txn = environment.create_read_write_txn()?;
+            trie_store.put(&mut txn, &root_hash, &root)?;
+            txn.commit()?;
+            root_hash
+        };
+        Ok(SledGlobalState::new(
+            environment,
+            trie_store,
+            protocol_data_store,
+            root_hash,
+        ))
+    }
+
+    /// Creates a state from an existing environment, store, and root_hash.
+    /// Intended to be used for testing.
+    pub(crate) fn new(
+        environment: Arc<SledEnvironment>,
+        trie_store: Arc<SledTrieStore>,
+        protocol_data_store: Arc<SledProtocolDataStore>,
+        empty_root_hash: Blake2bHash,
+    ) -> Self {
+        SledGlobalState {
+            environment,
+            trie_store,
+            protocol_data_store,
+            empty_root_hash,
+            pruning: None,
+        }
+    }
+
+    /// Makes the commits to this state drive the prune saved in `prune_store`, marking or
+    /// sweeping at most `batch_size` tries per commit.
+    ///
+    /// A prune which was in progress when the state was last used carries on with the next
+    /// commit.  See `CommitPruning`.
+    pub fn with_pruning(mut self, prune_store: Arc<SledPruneStore>, batch_size: usize) -> Self {
+        self.pruning = Some(CommitPruning::new(prune_store, batch_size));
+        self
+    }
+
+    /// Starts a prune which deletes every trie node which is not reachable from one of
+    /// `retained_roots`, from the empty root, or from the state root of a commit made while the
+    /// prune is in progress.  The prune is carried out by the following commits, and replaces the
+    /// prune in progress if there is one.
+    ///
+    /// Nothing is started if one of `retained_roots` is not found.  Commits should only be made
+    /// on top of retained roots until the prune is complete.
+    ///
+    /// # Panics
+    ///
+    /// Panics if pruning has not been enabled with `with_pruning`.
+    pub fn start_prune(
+        &self,
+        retained_roots: &[Blake2bHash],
+    ) -> Result<StartPruneResult, error::Error> {
+        let pruning = self.pruning.as_ref().expect(PRUNING_EXPECT);
+        let mut roots = Vec::with_capacity(retained_roots.len() + 1);
+        roots.push(self.empty_root_hash);
+        roots.extend_from_slice(retained_roots);
+
+        pruning.start::<SledEnvironment, SledTrieStore, error::Error>(
+            &self.environment,
+            &self.trie_store,
+            &roots,
+        )
+    }
+
+    /// Returns the keys whose values differ between the states at `left_root` and `right_root`.
+    pub fn diff(
+        &self,
+        correlation_id: CorrelationId,
+        left_root: &Blake2bHash,
+        right_root: &Blake2bHash,
+    ) -> Result<DiffResult<Key, StoredValue>, error::Error> {
+        let txn = self.environment.create_read_txn()?;
+        let result = diff::diff::<Key, StoredValue, _, SledTrieStore, error::Error>(
+            correlation_id,
+            &txn,
+            &self.trie_store,
+            left_root,
+            right_root,
+        )?;
+        txn.commit()?;
+        Ok(result)
+    }
+
+    /// Returns statistics about the tries reachable from `root`.
+    pub fn stats(
+        &self,
+        correlation_id: CorrelationId,
+        root: &Blake2bHash,
+    ) -> Result<StatsResult, error::Error> {
+        let txn = self.environment.create_read_txn()?;
+        let result = stats::stats::<Key, StoredValue, _, SledTrieStore, error::Error>(
+            correlation_id,
+            &txn,
+            &self.trie_store,
+            root,
+        )?;
+        txn.commit()?;
+        Ok(result)
+    }
+}
+
+impl StateReader<Key, StoredValue> for SledGlobalStateView {
+    type Error = error::Error;
+
+    fn read(
+        &self,
+        correlation_id: CorrelationId,
+        key: &Key,
+    ) -> Result<Option<StoredValue>, Self::Error> {
+        let txn = self.environment.create_read_txn()?;
+        let ret = match read::<Key, StoredValue, SledReadTransaction, SledTrieStore, Self::Error>(
+            correlation_id,
+            &txn,
+            self.store.deref(),
+            &self.root_hash,
+            key,
+        )? {
+            ReadResult::Found(value) => Some(value),
+            ReadResult::NotFound => None,
+            ReadResult::RootNotFound => panic!("SledGlobalState has invalid root"),
+        };
+        txn.commit()?;
+        Ok(ret)
+    }
+
+    fn read_with_proof(
+        &self,
+        correlation_id: CorrelationId,
+        key: &Key,
+    ) -> Result<Option<TrieMerkleProof<Key, StoredValue>>, Self::Error> {
+        let txn = self.environment.create_read_txn()?;
+        let ret = match read_with_proof::<
+            Key,
+            StoredValue,
+            SledReadTransaction,
+            SledTrieStore,
+            Self::Error,
+        >(
+            correlation_id,
+            &txn,
+            self.store.deref(),
+            &self.root_hash,
+            key,
+        )? {
+            Some(proof) => proof,
+            None => panic!("SledGlobalState has invalid root"),
+        };
+        txn.commit()?;
+        Ok(Some(ret))
+    }
+}
+
+impl StateIterator<Key, StoredValue> for SledGlobalStateView {
+    fn read_prefix(
+        &self,
+        correlation_id: CorrelationId,
+        prefix: &[u8],
+        start_after: Option<&Key>,
+        limit: usize,
+    ) -> Result<StatePage<Key, StoredValue>, Self::Error> {
+        let start_after = start_after.map(ToBytes::to_bytes).transpose()?;
+        let txn = self.environment.create_read_txn()?;
+        let entries = entries_with_prefix::<Key, StoredValue, SledReadTransaction, _>(
+            correlation_id,
+            &txn,
+            self.store.deref(),
+            &self.root_hash,
+            prefix,
+            start_after.as_deref(),
+        );
+        let page = collect_page(entries, limit)?;
+        txn.commit()?;
+        Ok(page)
+    }
+}
+
+impl StateProvider for SledGlobalState {
+    type Error = error::Error;
+
+    type Reader = SledGlobalStateView;
+
+    fn checkout(&self, state_hash: Blake2bHash) -> Result<Option<Self::Reader>, Self::Error> {
+        let txn = self.environment.create_read_txn()?;
+        let maybe_root: Option<Trie<Key, StoredValue>> = self.trie_store.get(&txn, &state_hash)?;
+        let maybe_state = maybe_root.map(|_| SledGlobalStateView {
+            environment: Arc::clone(&self.environment),
+            store: Arc::clone(&self.trie_store),
+            root_hash: state_hash,
+        });
+        txn.commit()?;
+        Ok(maybe_state)
+    }
+
+    fn commit(
+        &self,
+        correlation_id: CorrelationId,
+        prestate_hash: Blake2bHash,
+        effects: AdditiveMap<Key, Transform>,
+    ) -> Result<CommitResult, Self::Error> {
+        if let Some(pruning) = &self.pruning {
+            return pruning.commit::<SledEnvironment, SledTrieStore, _, Self::Error>(
+                &self.environment,
+                &self.trie_store,
+                correlation_id,
+                prestate_hash,
+                effects,
+            );
+        }
+        let commit_result = commit::<SledEnvironment, SledTrieStore, _, Self::Error>(
+            &self.environment,
+            &self.trie_store,
+            correlation_id,
+            prestate_hash,
+            effects,
+        )?;
+        Ok(commit_result)
+    }
+
+    fn commit_many(
+        &self,
+        correlation_id: CorrelationId,
+        prestate_hash: Blake2bHash,
+        effects: Vec<AdditiveMap<Key, Transform>>,
+    ) -> Result<CommitManyResult, Self::Error> {
+        if let Some(pruning) = &self.pruning {
+            return pruning.commit_many::<SledEnvironment, SledTrieStore, _, Self::Error>(
+                &self.environment,
+                &self.trie_store,
+                correlation_id,
+                prestate_hash,
+                effects,
+            );
+        }
+        let commit_many_result = commit_many::<SledEnvironment, SledTrieStore, _, Self::Error>(
+            &self.environment,
+            &self.trie_store,
+            correlation_id,
+            prestate_hash,
+            effects,
+        )?;
+        Ok(commit_many_result)
+    }
+
+    fn put_protocol_data(
+        &self,
+        protocol_version: ProtocolVersion,
+        protocol_data: &ProtocolData,
+    ) -> Result<(), Self::Error> {
+        let mut txn = self.environment.create_read_write_txn()?;
+        self.protocol_data_store
+            .put(&mut txn, &protocol_version, protocol_data)?;
+        txn.commit().map_err(Into::into)
+    }
+
+    fn get_protocol_data(
+        &self,
+        protocol_version: ProtocolVersion,
+    ) -> Result<Option<ProtocolData>, Self::Error> {
+        let txn = self.environment.create_read_txn()?;
+        let result = self.protocol_data_store.get(&txn, &protocol_version)?;
+        txn.commit()?;
+        Ok(result)
+    }
+
+    fn empty_root(&self) -> Blake2bHash {
+        self.empty_root_hash
+    }
+}
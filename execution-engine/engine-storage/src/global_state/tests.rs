@@ -0,0 +1,719 @@
+//! Tests shared by the LMDB-backed and the sled-backed global states.
+
+use std::sync::Arc;
+
+use lmdb::DatabaseFlags;
+use tempfile::{tempdir, TempDir};
+
+use engine_shared::{
+    additive_map::AdditiveMap,
+    newtypes::{Blake2bHash, CorrelationId},
+    stored_value::StoredValue,
+    transform::Transform,
+};
+use types::{account::AccountHash, bytesrepr::ToBytes, AccessRights, CLValue, Key, KeyTag, URef};
+
+use crate::{
+    error,
+    global_state::{
+        lmdb::LmdbGlobalState, sled::SledGlobalState, CommitManyResult, CommitResult,
+        StartPruneResult, StateIterator, StatePage, StateProvider, StateReader,
+    },
+    protocol_data_store::{lmdb::LmdbProtocolDataStore, sled::SledProtocolDataStore},
+    prune_store::{lmdb::LmdbPruneStore, sled::SledPruneStore, PRUNE_STORE_KEY},
+    store::Store,
+    transaction_source::{
+        lmdb::LmdbEnvironment, sled::SledEnvironment, Transaction, TransactionSource,
+    },
+    trie_store::{
+        lmdb::LmdbTrieStore,
+        operations::{diff::DiffResult, prune::TriePruner, stats::StatsResult},
+        sled::SledTrieStore,
+    },
+    TEST_MAP_SIZE,
+};
+
+/// A global state which the tests below are run against.
+pub(super) trait TestState: StateProvider<Error = error::Error> + Sized {
+    /// Creates an empty state stored in `temp_dir`.
+    fn create(temp_dir: &TempDir) -> Self;
+
+    fn diff(
+        &self,
+        correlation_id: CorrelationId,
+        left_root: &Blake2bHash,
+        right_root: &Blake2bHash,
+    ) -> Result<DiffResult<Key, StoredValue>, error::Error>;
+
+    fn stats(
+        &self,
+        correlation_id: CorrelationId,
+        root: &Blake2bHash,
+    ) -> Result<StatsResult, error::Error>;
+
+    /// Enables pruning, with the prune saved in the default prune store of the environment.
+    fn with_test_pruning(self, batch_size: usize) -> Self;
+
+    fn start_prune(&self, retained_roots: &[Blake2bHash])
+        -> Result<StartPruneResult, error::Error>;
+
+    /// Returns `true` if a prune is saved in the default prune store of the environment.
+    fn is_pruning(&self) -> bool;
+
+    /// Creates a state over the same stores, with pruning enabled, as if the process using this
+    /// one had restarted.
+    fn restart(&self, batch_size: usize) -> Self;
+}
+
+impl TestState for LmdbGlobalState {
+    fn create(temp_dir: &TempDir) -> Self {
+        let environment = Arc::new(
+            LmdbEnvironment::new(&temp_dir.path().to_path_buf(), *TEST_MAP_SIZE).unwrap(),
+        );
+        let trie_store =
+            Arc::new(LmdbTrieStore::new(&environment, None, DatabaseFlags::empty()).unwrap());
+        let protocol_data_store = Arc::new(
+            LmdbProtocolDataStore::new(&environment, None, DatabaseFlags::empty()).unwrap(),
+        );
+        LmdbGlobalState::empty(environment, trie_store, protocol_data_store).unwrap()
+    }
+
+    fn diff(
+        &self,
+        correlation_id: CorrelationId,
+        left_root: &Blake2bHash,
+        right_root: &Blake2bHash,
+    ) -> Result<DiffResult<Key, StoredValue>, error::Error> {
+        LmdbGlobalState::diff(self, correlation_id, left_root, right_root)
+    }
+
+    fn stats(
+        &self,
+        correlation_id: CorrelationId,
+        root: &Blake2bHash,
+    ) -> Result<StatsResult, error::Error> {
+        LmdbGlobalState::stats(self, correlation_id, root)
+    }
+
+    fn with_test_pruning(self, batch_size: usize) -> Self {
+        let prune_store =
+            LmdbPruneStore::new(&self.environment, None, DatabaseFlags::empty()).unwrap();
+        self.with_pruning(Arc::new(prune_store), batch_size)
+    }
+
+    fn start_prune(
+        &self,
+        retained_roots: &[Blake2bHash],
+    ) -> Result<StartPruneResult, error::Error> {
+        LmdbGlobalState::start_prune(self, retained_roots)
+    }
+
+    fn is_pruning(&self) -> bool {
+        let prune_store = LmdbPruneStore::open(&self.environment, None).unwrap();
+        let txn = self.environment.create_read_txn().unwrap();
+        let maybe_pruner: Option<TriePruner> = prune_store.get(&txn, &PRUNE_STORE_KEY).unwrap();
+        txn.commit().unwrap();
+        maybe_pruner.is_some()
+    }
+
+    fn restart(&self, batch_size: usize) -> Self {
+        LmdbGlobalState::new(
+            Arc::clone(&self.environment),
+            Arc::clone(&self.trie_store),
+            Arc::clone(&self.protocol_data_store),
+            self.empty_root_hash,
+        )
+        .with_test_pruning(batch_size)
+    }
+}
+
+impl TestState for SledGlobalState {
+    fn create(temp_dir: &TempDir) -> Self {
+        let environment = Arc::new(SledEnvironment::new(&temp_dir.path().to_path_buf()).unwrap());
+        let trie_store = Arc::new(SledTrieStore::new(&environment, None).unwrap());
+        let protocol_data_store = Arc::new(SledProtocolDataStore::new(&environment, None).unwrap());
+        SledGlobalState::empty(environment, trie_store, protocol_data_store).unwrap()
+    }
+
+    fn diff(
+        &self,
+        correlation_id: CorrelationId,
+        left_root: &Blake2bHash,
+        right_root: &Blake2bHash,
+    ) -> Result<DiffResult<Key, StoredValue>, error::Error> {
+        SledGlobalState::diff(self, correlation_id, left_root, right_root)
+    }
+
+    fn stats(
+        &self,
+        correlation_id: CorrelationId,
+        root: &Blake2bHash,
+    ) -> Result<StatsResult, error::Error> {
+        SledGlobalState::stats(self, correlation_id, root)
+    }
+
+    fn with_test_pruning(self, batch_size: usize) -> Self {
+        let prune_store = SledPruneStore::new(&self.environment, None).unwrap();
+        self.with_pruning(Arc::new(prune_store), batch_size)
+    }
+
+    fn start_prune(
+        &self,
+        retained_roots: &[Blake2bHash],
+    ) -> Result<StartPruneResult, error::Error> {
+        SledGlobalState::start_prune(self, retained_roots)
+    }
+
+    fn is_pruning(&self) -> bool {
+        let prune_store = SledPruneStore::new(&self.environment, None).unwrap();
+        let txn = self.environment.create_read_txn().unwrap();
+        let maybe_pruner: Option<TriePruner> = prune_store.get(&txn, &PRUNE_STORE_KEY).unwrap();
+        txn.commit().unwrap();
+        maybe_pruner.is_some()
+    }
+
+    fn restart(&self, batch_size: usize) -> Self {
+        SledGlobalState::new(
+            Arc::clone(&self.environment),
+            Arc::clone(&self.trie_store),
+            Arc::clone(&self.protocol_data_store),
+            self.empty_root_hash,
+        )
+        .with_test_pruning(batch_size)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(super) struct TestPair {
+    pub(super) key: Key,
+    pub(super) value: StoredValue,
+}
+
+pub(super) fn create_test_pairs() -> [TestPair; 2] {
+    [
+        TestPair {
+            key: Key::Account(AccountHash::new([1_u8; 32])),
+            value: StoredValue::CLValue(CLValue::from_t(1_i32).unwrap()),
+        },
+        TestPair {
+            key: Key::Account(AccountHash::new([2_u8; 32])),
+            value: StoredValue::CLValue(CLValue::from_t(2_i32).unwrap()),
+        },
+    ]
+}
+
+fn create_test_pairs_updated() -> [TestPair; 3] {
+    [
+        TestPair {
+            key: Key::Account(AccountHash::new([1u8; 32])),
+            value: StoredValue::CLValue(CLValue::from_t("one".to_string()).unwrap()),
+        },
+        TestPair {
+            key: Key::Account(AccountHash::new([2u8; 32])),
+            value: StoredValue::CLValue(CLValue::from_t("two".to_string()).unwrap()),
+        },
+        TestPair {
+            key: Key::Account(AccountHash::new([3u8; 32])),
+            value: StoredValue::CLValue(CLValue::from_t(3_i32).unwrap()),
+        },
+    ]
+}
+
+fn create_effects(pairs: &[TestPair]) -> AdditiveMap<Key, Transform> {
+    let mut ret = AdditiveMap::new();
+    for TestPair { key, value } in pairs {
+        ret.insert(*key, Transform::Write(value.to_owned()));
+    }
+    ret
+}
+
+fn commit<S: TestState>(
+    state: &S,
+    root_hash: Blake2bHash,
+    effects: AdditiveMap<Key, Transform>,
+) -> Blake2bHash {
+    match state
+        .commit(CorrelationId::new(), root_hash, effects)
+        .unwrap()
+    {
+        CommitResult::Success { state_root, .. } => state_root,
+        result => panic!("commit failed: {}", result),
+    }
+}
+
+/// Returns a state holding the test pairs, its root, and the directory holding it, which is
+/// deleted when dropped.
+pub(super) fn create_test_state<S: TestState>() -> (S, Blake2bHash, TempDir) {
+    let temp_dir = tempdir().unwrap();
+    let state = S::create(&temp_dir);
+    let root_hash = commit(
+        &state,
+        state.empty_root(),
+        create_effects(&create_test_pairs()),
+    );
+    (state, root_hash, temp_dir)
+}
+
+/// Commits a new value on top of `root_hash`, and then on top of each resulting root, until the
+/// prune in progress is complete.  Returns the last root.
+pub(super) fn commit_until_pruned<S: TestState>(state: &S, mut root_hash: Blake2bHash) -> Blake2bHash {
+    for i in 0..=u8::max_value() {
+        if !state.is_pruning() {
+            return root_hash;
+        }
+        let mut effects = AdditiveMap::new();
+        effects.insert(
+            Key::Hash([i; 32]),
+            Transform::Write(StoredValue::CLValue(CLValue::from_t(i32::from(i)).unwrap())),
+        );
+        root_hash = commit(state, root_hash, effects);
+    }
+    panic!("prune should be complete")
+}
+
+fn reads_from_a_checkout_return_expected_values<S: TestState>() {
+    let correlation_id = CorrelationId::new();
+    let (state, root_hash, _temp_dir) = create_test_state::<S>();
+    let checkout = state.checkout(root_hash).unwrap().unwrap();
+    for TestPair { key, value } in create_test_pairs().iter().cloned() {
+        assert_eq!(Some(value), checkout.read(correlation_id, &key).unwrap());
+    }
+}
+
+#[test]
+fn lmdb_reads_from_a_checkout_return_expected_values() {
+    reads_from_a_checkout_return_expected_values::<LmdbGlobalState>()
+}
+
+#[test]
+fn sled_reads_from_a_checkout_return_expected_values() {
+    reads_from_a_checkout_return_expected_values::<SledGlobalState>()
+}
+
+fn checkout_fails_if_unknown_hash_is_given<S: TestState>() {
+    let (state, _, _temp_dir) = create_test_state::<S>();
+    let fake_hash: Blake2bHash = [1u8; 32].into();
+    let result = state.checkout(fake_hash).unwrap();
+    assert!(result.is_none());
+}
+
+#[test]
+fn lmdb_checkout_fails_if_unknown_hash_is_given() {
+    checkout_fails_if_unknown_hash_is_given::<LmdbGlobalState>()
+}
+
+#[test]
+fn sled_checkout_fails_if_unknown_hash_is_given() {
+    checkout_fails_if_unknown_hash_is_given::<SledGlobalState>()
+}
+
+fn read_prefix_pages_through_keys_of_a_kind<S>()
+where
+    S: TestState,
+    S::Reader: StateIterator<Key, StoredValue>,
+{
+    let correlation_id = CorrelationId::new();
+    let (state, root_hash, _temp_dir) = create_test_state::<S>();
+    let uref_key = Key::URef(URef::new([1u8; 32], AccessRights::NONE));
+    let uref_value = StoredValue::CLValue(CLValue::from_t(3_i32).unwrap());
+    let mut effects = AdditiveMap::new();
+    effects.insert(uref_key, Transform::Write(uref_value.clone()));
+    let root_hash = commit(&state, root_hash, effects);
+    let checkout = state.checkout(root_hash).unwrap().unwrap();
+
+    let prefix = [KeyTag::Account as u8];
+    let mut start_after = None;
+    for TestPair { key, value } in create_test_pairs().iter().cloned() {
+        let page = checkout
+            .read_prefix(correlation_id, &prefix, start_after.as_ref(), 1)
+            .unwrap();
+        assert_eq!(page.entries, vec![(key, value)]);
+        start_after = Some(key);
+    }
+    let page = checkout
+        .read_prefix(correlation_id, &prefix, start_after.as_ref(), 1)
+        .unwrap();
+    assert_eq!(
+        page,
+        StatePage {
+            entries: vec![],
+            has_more: false
+        }
+    );
+
+    let page = checkout
+        .read_prefix(correlation_id, &[KeyTag::URef as u8], None, 10)
+        .unwrap();
+    assert_eq!(page.entries, vec![(uref_key, uref_value)]);
+}
+
+#[test]
+fn lmdb_read_prefix_pages_through_keys_of_a_kind() {
+    read_prefix_pages_through_keys_of_a_kind::<LmdbGlobalState>()
+}
+
+#[test]
+fn sled_read_prefix_pages_through_keys_of_a_kind() {
+    read_prefix_pages_through_keys_of_a_kind::<SledGlobalState>()
+}
+
+fn commit_updates_state<S: TestState>() {
+    let correlation_id = CorrelationId::new();
+    let test_pairs_updated = create_test_pairs_updated();
+
+    let (state, root_hash, _temp_dir) = create_test_state::<S>();
+
+    let updated_hash = commit(&state, root_hash, create_effects(&test_pairs_updated));
+
+    let updated_checkout = state.checkout(updated_hash).unwrap().unwrap();
+    for TestPair { key, value } in test_pairs_updated.iter().cloned() {
+        assert_eq!(
+            Some(value),
+            updated_checkout.read(correlation_id, &key).unwrap()
+        );
+    }
+}
+
+#[test]
+fn lmdb_commit_updates_state() {
+    commit_updates_state::<LmdbGlobalState>()
+}
+
+#[test]
+fn sled_commit_updates_state() {
+    commit_updates_state::<SledGlobalState>()
+}
+
+fn commit_updates_state_and_original_state_stays_intact<S: TestState>() {
+    let correlation_id = CorrelationId::new();
+    let test_pairs_updated = create_test_pairs_updated();
+
+    let (state, root_hash, _temp_dir) = create_test_state::<S>();
+
+    let updated_hash = commit(&state, root_hash, create_effects(&test_pairs_updated));
+
+    let updated_checkout = state.checkout(updated_hash).unwrap().unwrap();
+    for TestPair { key, value } in test_pairs_updated.iter().cloned() {
+        assert_eq!(
+            Some(value),
+            updated_checkout.read(correlation_id, &key).unwrap()
+        );
+    }
+
+    let original_checkout = state.checkout(root_hash).unwrap().unwrap();
+    for TestPair { key, value } in create_test_pairs().iter().cloned() {
+        assert_eq!(
+            Some(value),
+            original_checkout.read(correlation_id, &key).unwrap()
+        );
+    }
+    assert_eq!(
+        None,
+        original_checkout
+            .read(correlation_id, &test_pairs_updated[2].key)
+            .unwrap()
+    );
+}
+
+#[test]
+fn lmdb_commit_updates_state_and_original_state_stays_intact() {
+    commit_updates_state_and_original_state_stays_intact::<LmdbGlobalState>()
+}
+
+#[test]
+fn sled_commit_updates_state_and_original_state_stays_intact() {
+    commit_updates_state_and_original_state_stays_intact::<SledGlobalState>()
+}
+
+fn commit_many_returns_root_after_each_effects<S: TestState>() {
+    let correlation_id = CorrelationId::new();
+    let test_pairs_updated = create_test_pairs_updated();
+
+    let (state, root_hash, _temp_dir) = create_test_state::<S>();
+
+    let effects: Vec<AdditiveMap<Key, Transform>> = test_pairs_updated
+        .iter()
+        .map(|pair| create_effects(&[pair.clone()]))
+        .collect();
+
+    let state_roots = match state
+        .commit_many(correlation_id, root_hash, effects.clone())
+        .unwrap()
+    {
+        CommitManyResult::Success { state_roots, .. } => state_roots,
+        result => panic!("commit_many failed: {}", result),
+    };
+    assert_eq!(state_roots.len(), test_pairs_updated.len());
+
+    // Each root is the one a separate commit of the same effects arrives at.
+    let mut expected_root = root_hash;
+    for (effects, state_root) in effects.into_iter().zip(state_roots.iter()) {
+        expected_root = commit(&state, expected_root, effects);
+        assert_eq!(expected_root, *state_root);
+    }
+
+    let first_checkout = state.checkout(state_roots[0]).unwrap().unwrap();
+    assert_eq!(
+        Some(test_pairs_updated[0].value.clone()),
+        first_checkout
+            .read(correlation_id, &test_pairs_updated[0].key)
+            .unwrap()
+    );
+    assert_eq!(
+        None,
+        first_checkout
+            .read(correlation_id, &test_pairs_updated[2].key)
+            .unwrap()
+    );
+}
+
+#[test]
+fn lmdb_commit_many_returns_root_after_each_effects() {
+    commit_many_returns_root_after_each_effects::<LmdbGlobalState>()
+}
+
+#[test]
+fn sled_commit_many_returns_root_after_each_effects() {
+    commit_many_returns_root_after_each_effects::<SledGlobalState>()
+}
+
+fn commit_many_commits_nothing_if_any_effects_fail<S: TestState>() {
+    let correlation_id = CorrelationId::new();
+    let test_pairs_updated = create_test_pairs_updated();
+    let missing_key = Key::Account(AccountHash::new([9u8; 32]));
+
+    let (state, root_hash, _temp_dir) = create_test_state::<S>();
+
+    let first_effects = create_effects(&test_pairs_updated[2..]);
+    let mut key_not_found = AdditiveMap::new();
+    key_not_found.insert(missing_key, Transform::AddInt32(1));
+    let mut type_mismatch = AdditiveMap::new();
+    type_mismatch.insert(
+        test_pairs_updated[2].key,
+        Transform::AddKeys(Default::default()),
+    );
+
+    match state
+        .commit_many(
+            correlation_id,
+            root_hash,
+            vec![first_effects.clone(), key_not_found],
+        )
+        .unwrap()
+    {
+        CommitManyResult::Failure {
+            index: 1,
+            result: CommitResult::KeyNotFound(key),
+        } => assert_eq!(key, missing_key),
+        result => panic!("unexpected result: {}", result),
+    }
+    match state
+        .commit_many(
+            correlation_id,
+            root_hash,
+            vec![first_effects.clone(), type_mismatch],
+        )
+        .unwrap()
+    {
+        CommitManyResult::Failure {
+            index: 1,
+            result: CommitResult::TypeMismatch(_),
+        } => (),
+        result => panic!("unexpected result: {}", result),
+    }
+
+    // The first effects were rolled back along with the failed ones.
+    let (other_state, other_root_hash, _other_temp_dir) = create_test_state::<S>();
+    let first_root = commit(&other_state, other_root_hash, first_effects);
+    assert!(state.checkout(first_root).unwrap().is_none());
+}
+
+#[test]
+fn lmdb_commit_many_commits_nothing_if_any_effects_fail() {
+    commit_many_commits_nothing_if_any_effects_fail::<LmdbGlobalState>()
+}
+
+#[test]
+fn sled_commit_many_commits_nothing_if_any_effects_fail() {
+    commit_many_commits_nothing_if_any_effects_fail::<SledGlobalState>()
+}
+
+fn commit_many_of_unknown_root_fails<S: TestState>() {
+    let (state, _, _temp_dir) = create_test_state::<S>();
+    let fake_hash: Blake2bHash = [1u8; 32].into();
+    match state
+        .commit_many(CorrelationId::new(), fake_hash, vec![AdditiveMap::new()])
+        .unwrap()
+    {
+        CommitManyResult::Failure {
+            index: 0,
+            result: CommitResult::RootNotFound,
+        } => (),
+        result => panic!("unexpected result: {}", result),
+    }
+}
+
+#[test]
+fn lmdb_commit_many_of_unknown_root_fails() {
+    commit_many_of_unknown_root_fails::<LmdbGlobalState>()
+}
+
+#[test]
+fn sled_commit_many_of_unknown_root_fails() {
+    commit_many_of_unknown_root_fails::<SledGlobalState>()
+}
+
+fn prune_keeps_retained_roots_and_deletes_the_rest<S: TestState>() {
+    let correlation_id = CorrelationId::new();
+    let test_pairs_updated = create_test_pairs_updated();
+
+    let (state, root_hash, _temp_dir) = create_test_state::<S>();
+    let state = state.with_test_pruning(1);
+
+    let updated_hash = commit(&state, root_hash, create_effects(&test_pairs_updated));
+
+    let fake_hash: Blake2bHash = [1u8; 32].into();
+    assert_eq!(
+        state.start_prune(&[updated_hash, fake_hash]).unwrap(),
+        StartPruneResult::RootNotFound(fake_hash)
+    );
+    assert!(!state.is_pruning());
+
+    assert_eq!(
+        state.start_prune(&[updated_hash]).unwrap(),
+        StartPruneResult::Started
+    );
+    let last_hash = commit_until_pruned(&state, updated_hash);
+
+    assert!(state.checkout(root_hash).unwrap().is_none());
+    assert!(state.checkout(state.empty_root()).unwrap().is_some());
+    let updated_checkout = state.checkout(updated_hash).unwrap().unwrap();
+    let last_checkout = state.checkout(last_hash).unwrap().unwrap();
+    for TestPair { key, value } in test_pairs_updated.iter().cloned() {
+        assert_eq!(
+            Some(value.clone()),
+            updated_checkout.read(correlation_id, &key).unwrap()
+        );
+        assert_eq!(Some(value), last_checkout.read(correlation_id, &key).unwrap());
+    }
+}
+
+#[test]
+fn lmdb_prune_keeps_retained_roots_and_deletes_the_rest() {
+    prune_keeps_retained_roots_and_deletes_the_rest::<LmdbGlobalState>()
+}
+
+#[test]
+fn sled_prune_keeps_retained_roots_and_deletes_the_rest() {
+    prune_keeps_retained_roots_and_deletes_the_rest::<SledGlobalState>()
+}
+
+fn prune_carries_on_after_restart<S: TestState>() {
+    let (state, root_hash, _temp_dir) = create_test_state::<S>();
+    let state = state.with_test_pruning(1);
+
+    assert_eq!(state.start_prune(&[]).unwrap(), StartPruneResult::Started);
+
+    // A new state over the same stores has to load the saved prune
+    let restarted_state = state.restart(1);
+    drop(state);
+
+    let last_hash = commit_until_pruned(&restarted_state, restarted_state.empty_root());
+    assert!(restarted_state.checkout(root_hash).unwrap().is_none());
+    assert!(restarted_state.checkout(last_hash).unwrap().is_some());
+}
+
+#[test]
+fn lmdb_prune_carries_on_after_restart() {
+    prune_carries_on_after_restart::<LmdbGlobalState>()
+}
+
+#[test]
+fn sled_prune_carries_on_after_restart() {
+    prune_carries_on_after_restart::<SledGlobalState>()
+}
+
+fn diff_returns_updated_and_added_keys<S: TestState>() {
+    let correlation_id = CorrelationId::new();
+    let test_pairs = create_test_pairs();
+    let test_pairs_updated = create_test_pairs_updated();
+
+    let (state, root_hash, _temp_dir) = create_test_state::<S>();
+
+    let updated_hash = commit(&state, root_hash, create_effects(&test_pairs_updated));
+
+    let expected = vec![
+        (
+            test_pairs[0].key,
+            Some(test_pairs[0].value.clone()),
+            Some(test_pairs_updated[0].value.clone()),
+        ),
+        (
+            test_pairs[1].key,
+            Some(test_pairs[1].value.clone()),
+            Some(test_pairs_updated[1].value.clone()),
+        ),
+        (
+            test_pairs_updated[2].key,
+            None,
+            Some(test_pairs_updated[2].value.clone()),
+        ),
+    ];
+    assert_eq!(
+        state
+            .diff(correlation_id, &root_hash, &updated_hash)
+            .unwrap(),
+        DiffResult::Success(expected)
+    );
+    assert_eq!(
+        state.diff(correlation_id, &root_hash, &root_hash).unwrap(),
+        DiffResult::Success(vec![])
+    );
+}
+
+#[test]
+fn lmdb_diff_returns_updated_and_added_keys() {
+    diff_returns_updated_and_added_keys::<LmdbGlobalState>()
+}
+
+#[test]
+fn sled_diff_returns_updated_and_added_keys() {
+    diff_returns_updated_and_added_keys::<SledGlobalState>()
+}
+
+fn stats_count_leaves_by_kind<S: TestState>() {
+    let correlation_id = CorrelationId::new();
+    let test_pairs = create_test_pairs();
+    let (state, root_hash, _temp_dir) = create_test_state::<S>();
+
+    let stats = match state.stats(correlation_id, &root_hash).unwrap() {
+        StatsResult::Success(stats) => stats,
+        StatsResult::RootNotFound => panic!("root should exist"),
+    };
+    assert_eq!(stats.leaves, test_pairs.len());
+    assert_eq!(stats.leaves_by_key.get("Account"), Some(&test_pairs.len()));
+    assert_eq!(stats.leaves_by_key.get("URef"), None);
+    let value_bytes: usize = test_pairs
+        .iter()
+        .map(|pair| pair.value.serialized_length())
+        .sum();
+    assert_eq!(stats.values["CLValue"].count, test_pairs.len());
+    assert_eq!(stats.values["CLValue"].bytes, value_bytes);
+
+    assert_eq!(
+        state
+            .stats(correlation_id, &Blake2bHash::new(&[0u8]))
+            .unwrap(),
+        StatsResult::RootNotFound
+    );
+}
+
+#[test]
+fn lmdb_stats_count_leaves_by_kind() {
+    stats_count_leaves_by_kind::<LmdbGlobalState>()
+}
+
+#[test]
+fn sled_stats_count_leaves_by_kind() {
+    stats_count_leaves_by_kind::<SledGlobalState>()
+}
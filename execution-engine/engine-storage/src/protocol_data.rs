@@ -1,17 +1,26 @@
-use engine_wasm_prep::wasm_costs::{WasmCosts, WASM_COSTS_SERIALIZED_LENGTH};
+use engine_shared::storage_costs::{StorageCosts, STORAGE_COSTS_SERIALIZED_LENGTH};
+use engine_wasm_prep::{
+    host_function_costs::{HostFunctionCosts, HOST_FUNCTION_COSTS_SERIALIZED_LENGTH},
+    wasm_costs::{WasmCosts, WASM_COSTS_SERIALIZED_LENGTH},
+};
 use std::collections::BTreeMap;
 use types::{
     bytesrepr::{self, FromBytes, ToBytes},
     ContractHash, HashAddr, KEY_HASH_LENGTH,
 };
 
-const PROTOCOL_DATA_SERIALIZED_LENGTH: usize = WASM_COSTS_SERIALIZED_LENGTH + 3 * KEY_HASH_LENGTH;
+const PROTOCOL_DATA_SERIALIZED_LENGTH: usize = WASM_COSTS_SERIALIZED_LENGTH
+    + 3 * KEY_HASH_LENGTH
+    + HOST_FUNCTION_COSTS_SERIALIZED_LENGTH
+    + STORAGE_COSTS_SERIALIZED_LENGTH;
 const DEFAULT_ADDRESS: [u8; 32] = [0; 32];
 
 /// Represents a protocol's data. Intended to be associated with a given protocol version.
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub struct ProtocolData {
     wasm_costs: WasmCosts,
+    host_function_costs: HostFunctionCosts,
+    storage_costs: StorageCosts,
     mint: ContractHash,
     proof_of_stake: ContractHash,
     standard_payment: ContractHash,
@@ -25,6 +34,8 @@ impl Default for ProtocolData {
     fn default() -> ProtocolData {
         ProtocolData {
             wasm_costs: WasmCosts::default(),
+            host_function_costs: HostFunctionCosts::default(),
+            storage_costs: StorageCosts::default(),
             mint: DEFAULT_ADDRESS,
             proof_of_stake: DEFAULT_ADDRESS,
             standard_payment: DEFAULT_ADDRESS,
@@ -33,15 +44,20 @@ impl Default for ProtocolData {
 }
 
 impl ProtocolData {
-    /// Creates a new [`ProtocolData`] value from a given [`WasmCosts`] value.
+    /// Creates a new [`ProtocolData`] value from given [`WasmCosts`], [`HostFunctionCosts`] and
+    /// [`StorageCosts`] values.
     pub fn new(
         wasm_costs: WasmCosts,
+        host_function_costs: HostFunctionCosts,
+        storage_costs: StorageCosts,
         mint: ContractHash,
         proof_of_stake: ContractHash,
         standard_payment: ContractHash,
     ) -> Self {
         ProtocolData {
             wasm_costs,
+            host_function_costs,
+            storage_costs,
             mint,
             proof_of_stake,
             standard_payment,
@@ -64,11 +80,15 @@ impl ProtocolData {
     /// Used during `commit_genesis` before all system contracts' URefs are known.
     pub fn partial_without_standard_payment(
         wasm_costs: WasmCosts,
+        host_function_costs: HostFunctionCosts,
+        storage_costs: StorageCosts,
         mint: ContractHash,
         proof_of_stake: ContractHash,
     ) -> Self {
         ProtocolData {
             wasm_costs,
+            host_function_costs,
+            storage_costs,
             mint,
             proof_of_stake,
             ..Default::default()
@@ -80,6 +100,16 @@ impl ProtocolData {
         &self.wasm_costs
     }
 
+    /// Gets the [`HostFunctionCosts`] value from a given [`ProtocolData`] value.
+    pub fn host_function_costs(&self) -> &HostFunctionCosts {
+        &self.host_function_costs
+    }
+
+    /// Gets the [`StorageCosts`] value from a given [`ProtocolData`] value.
+    pub fn storage_costs(&self) -> &StorageCosts {
+        &self.storage_costs
+    }
+
     pub fn mint(&self) -> ContractHash {
         self.mint
     }
@@ -123,6 +153,23 @@ impl ProtocolData {
     }
 }
 
+/// Decodes a section which was appended to the serialized [`ProtocolData`] after protocol data
+/// had already been stored without it, falling back to `default` if `bytes` ends before it.  The
+/// fallback must be what was in force when such protocol data was stored.
+fn optional_section_from_bytes<T: FromBytes>(
+    bytes: &[u8],
+    default: T,
+) -> Result<(T, &[u8]), bytesrepr::Error> {
+    if bytes.is_empty() {
+        Ok((default, bytes))
+    } else {
+        T::from_bytes(bytes)
+    }
+}
+
+/// Serialized as the costs and system contracts stored by the first protocol versions, followed
+/// by the sections added since, so that protocol data stored before a section was added still
+/// deserializes.
 impl ToBytes for ProtocolData {
     fn to_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
         let mut ret = bytesrepr::unchecked_allocate_buffer(self);
@@ -130,6 +177,8 @@ impl ToBytes for ProtocolData {
         ret.append(&mut self.mint.to_bytes()?);
         ret.append(&mut self.proof_of_stake.to_bytes()?);
         ret.append(&mut self.standard_payment.to_bytes()?);
+        ret.append(&mut self.host_function_costs.to_bytes()?);
+        ret.append(&mut self.storage_costs.to_bytes()?);
         Ok(ret)
     }
 
@@ -144,10 +193,15 @@ impl FromBytes for ProtocolData {
         let (mint, rem) = HashAddr::from_bytes(rem)?;
         let (proof_of_stake, rem) = HashAddr::from_bytes(rem)?;
         let (standard_payment, rem) = HashAddr::from_bytes(rem)?;
+        let (host_function_costs, rem) =
+            optional_section_from_bytes(rem, HostFunctionCosts::free())?;
+        let (storage_costs, rem) = optional_section_from_bytes(rem, StorageCosts::default())?;
 
         Ok((
             ProtocolData {
                 wasm_costs,
+                host_function_costs,
+                storage_costs,
                 mint,
                 proof_of_stake,
                 standard_payment,
@@ -161,7 +215,10 @@ impl FromBytes for ProtocolData {
 pub(crate) mod gens {
     use proptest::prop_compose;
 
-    use engine_wasm_prep::wasm_costs::gens as wasm_costs_gens;
+    use engine_shared::storage_costs::gens as storage_costs_gens;
+    use engine_wasm_prep::{
+        host_function_costs::gens as host_function_costs_gens, wasm_costs::gens as wasm_costs_gens,
+    };
     use types::gens;
 
     use super::ProtocolData;
@@ -169,12 +226,16 @@ pub(crate) mod gens {
     prop_compose! {
         pub fn protocol_data_arb()(
             wasm_costs in wasm_costs_gens::wasm_costs_arb(),
+            host_function_costs in host_function_costs_gens::host_function_costs_arb(),
+            storage_costs in storage_costs_gens::storage_costs_arb(),
             mint in gens::u8_slice_32(),
             proof_of_stake in gens::u8_slice_32(),
             standard_payment in gens::u8_slice_32(),
         ) -> ProtocolData {
             ProtocolData {
                 wasm_costs,
+                host_function_costs,
+                storage_costs,
                 mint,
                 proof_of_stake,
                 standard_payment,
@@ -187,8 +248,15 @@ pub(crate) mod gens {
 mod tests {
     use proptest::proptest;
 
-    use engine_wasm_prep::wasm_costs::WasmCosts;
-    use types::{bytesrepr, ContractHash};
+    use engine_shared::storage_costs::StorageCosts;
+    use engine_wasm_prep::{
+        host_function_costs::{HostFunctionCost, HostFunctionCosts},
+        wasm_costs::WasmCosts,
+    };
+    use types::{
+        bytesrepr::{self, ToBytes},
+        ContractHash,
+    };
 
     use super::{gens, ProtocolData};
 
@@ -222,6 +290,14 @@ mod tests {
         }
     }
 
+    fn host_function_costs_mock() -> HostFunctionCosts {
+        HostFunctionCosts {
+            write: HostFunctionCost::new(10_000, 100),
+            call_contract: HostFunctionCost::new(50_000, 10),
+            ..HostFunctionCosts::free()
+        }
+    }
+
     #[test]
     fn should_serialize_and_deserialize() {
         let mock = {
@@ -231,6 +307,8 @@ mod tests {
             let standard_payment_reference = [3u8; 32];
             ProtocolData::new(
                 costs,
+                host_function_costs_mock(),
+                StorageCosts::new(10),
                 mint_reference,
                 proof_of_stake_reference,
                 standard_payment_reference,
@@ -243,6 +321,8 @@ mod tests {
             let standard_payment_reference = [2u8; 32];
             ProtocolData::new(
                 costs,
+                HostFunctionCosts::free(),
+                StorageCosts::default(),
                 mint_reference,
                 proof_of_stake_reference,
                 standard_payment_reference,
@@ -252,6 +332,58 @@ mod tests {
         bytesrepr::test_serialization_roundtrip(&free);
     }
 
+    #[test]
+    fn should_deserialize_protocol_data_stored_without_host_function_costs_as_free() {
+        let wasm_costs = wasm_costs_mock();
+        let mint_reference = [1u8; 32];
+        let proof_of_stake_reference = [2u8; 32];
+        let standard_payment_reference = [3u8; 32];
+        let mut bytes = wasm_costs.to_bytes().unwrap();
+        bytes.append(&mut mint_reference.to_bytes().unwrap());
+        bytes.append(&mut proof_of_stake_reference.to_bytes().unwrap());
+        bytes.append(&mut standard_payment_reference.to_bytes().unwrap());
+
+        let protocol_data: ProtocolData = bytesrepr::deserialize(bytes).unwrap();
+        assert_eq!(
+            protocol_data,
+            ProtocolData::new(
+                wasm_costs,
+                HostFunctionCosts::free(),
+                StorageCosts::default(),
+                mint_reference,
+                proof_of_stake_reference,
+                standard_payment_reference,
+            )
+        );
+    }
+
+    #[test]
+    fn should_deserialize_protocol_data_stored_without_storage_costs() {
+        let wasm_costs = wasm_costs_mock();
+        let host_function_costs = host_function_costs_mock();
+        let mint_reference = [1u8; 32];
+        let proof_of_stake_reference = [2u8; 32];
+        let standard_payment_reference = [3u8; 32];
+        let mut bytes = wasm_costs.to_bytes().unwrap();
+        bytes.append(&mut mint_reference.to_bytes().unwrap());
+        bytes.append(&mut proof_of_stake_reference.to_bytes().unwrap());
+        bytes.append(&mut standard_payment_reference.to_bytes().unwrap());
+        bytes.append(&mut host_function_costs.to_bytes().unwrap());
+
+        let protocol_data: ProtocolData = bytesrepr::deserialize(bytes).unwrap();
+        assert_eq!(
+            protocol_data,
+            ProtocolData::new(
+                wasm_costs,
+                host_function_costs,
+                StorageCosts::default(),
+                mint_reference,
+                proof_of_stake_reference,
+                standard_payment_reference,
+            )
+        );
+    }
+
     #[test]
     fn should_return_all_system_contracts() {
         let mint_reference = [1u8; 32];
@@ -261,6 +393,8 @@ mod tests {
             let costs = wasm_costs_mock();
             ProtocolData::new(
                 costs,
+                host_function_costs_mock(),
+                StorageCosts::new(10),
                 mint_reference,
                 proof_of_stake_reference,
                 standard_payment_reference,
@@ -291,6 +425,8 @@ mod tests {
             let costs = wasm_costs_mock();
             ProtocolData::new(
                 costs,
+                host_function_costs_mock(),
+                StorageCosts::new(10),
                 mint_reference,
                 proof_of_stake_reference,
                 standard_payment_reference,
@@ -4,6 +4,8 @@ use types::ProtocolVersion;
 
 pub mod in_memory;
 pub mod lmdb;
+pub mod scratch;
+pub mod sled;
 #[cfg(test)]
 mod tests;
 
@@ -0,0 +1,52 @@
+//! A protocol data store which reads from an LMDB-backed protocol data store, but keeps everything
+//! written to it in memory.
+//!
+//! See [`ScratchEnvironment`](crate::transaction_source::scratch::ScratchEnvironment).
+
+use types::ProtocolVersion;
+
+use crate::{
+    error,
+    protocol_data::ProtocolData,
+    protocol_data_store::{
+        in_memory::InMemoryProtocolDataStore, lmdb::LmdbProtocolDataStore, ProtocolDataStore,
+    },
+    store::Store,
+    transaction_source::scratch::{ScratchEnvironment, ScratchHandle},
+};
+
+const DELETED: &str = "deleted";
+
+/// A protocol data store which overlays an [`InMemoryProtocolDataStore`] on an
+/// [`LmdbProtocolDataStore`].
+pub struct ScratchProtocolDataStore {
+    lmdb: LmdbProtocolDataStore,
+    written: InMemoryProtocolDataStore,
+    deleted: InMemoryProtocolDataStore,
+}
+
+impl ScratchProtocolDataStore {
+    pub fn new(env: &ScratchEnvironment, lmdb: LmdbProtocolDataStore) -> Self {
+        ScratchProtocolDataStore {
+            lmdb,
+            written: InMemoryProtocolDataStore::new(env.memory(), None),
+            deleted: InMemoryProtocolDataStore::new(env.memory(), Some(DELETED)),
+        }
+    }
+}
+
+impl Store<ProtocolVersion, ProtocolData> for ScratchProtocolDataStore {
+    type Error = error::Error;
+
+    type Handle = ScratchHandle;
+
+    fn handle(&self) -> Self::Handle {
+        ScratchHandle {
+            lmdb: self.lmdb.handle(),
+            written: self.written.handle(),
+            deleted: self.deleted.handle(),
+        }
+    }
+}
+
+impl ProtocolDataStore for ScratchProtocolDataStore {}
@@ -0,0 +1,41 @@
+use sled::Tree;
+use types::ProtocolVersion;
+
+use crate::{
+    error,
+    protocol_data::ProtocolData,
+    protocol_data_store::{self, ProtocolDataStore},
+    store::Store,
+    transaction_source::sled::SledEnvironment,
+};
+
+/// A sled-backed protocol data store.
+///
+/// Wraps [`sled::Tree`].
+#[derive(Debug, Clone)]
+pub struct SledProtocolDataStore {
+    tree: Tree,
+}
+
+impl SledProtocolDataStore {
+    /// Opens the tree backing the store, creating it if it does not exist.
+    pub fn new(env: &SledEnvironment, maybe_name: Option<&str>) -> Result<Self, error::Error> {
+        let name = maybe_name
+            .map(|name| format!("{}-{}", protocol_data_store::NAME, name))
+            .unwrap_or_else(|| String::from(protocol_data_store::NAME));
+        let tree = env.db().open_tree(name)?;
+        Ok(SledProtocolDataStore { tree })
+    }
+}
+
+impl Store<ProtocolVersion, ProtocolData> for SledProtocolDataStore {
+    type Error = error::Error;
+
+    type Handle = Tree;
+
+    fn handle(&self) -> Self::Handle {
+        self.tree.clone()
+    }
+}
+
+impl ProtocolDataStore for SledProtocolDataStore {}
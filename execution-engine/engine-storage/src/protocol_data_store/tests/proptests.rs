@@ -7,9 +7,14 @@ use types::{gens as gens_ext, ProtocolVersion};
 
 use crate::{
     protocol_data::{gens, ProtocolData},
-    protocol_data_store::{in_memory::InMemoryProtocolDataStore, lmdb::LmdbProtocolDataStore},
+    protocol_data_store::{
+        in_memory::InMemoryProtocolDataStore, lmdb::LmdbProtocolDataStore,
+        sled::SledProtocolDataStore,
+    },
     store::tests as store_tests,
-    transaction_source::{in_memory::InMemoryEnvironment, lmdb::LmdbEnvironment},
+    transaction_source::{
+        in_memory::InMemoryEnvironment, lmdb::LmdbEnvironment, sled::SledEnvironment,
+    },
     TEST_MAP_SIZE,
 };
 
@@ -43,6 +48,14 @@ fn lmdb_roundtrip_succeeds(inputs: BTreeMap<ProtocolVersion, ProtocolData>) -> b
     ret
 }
 
+fn sled_roundtrip_succeeds(inputs: BTreeMap<ProtocolVersion, ProtocolData>) -> bool {
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let env = SledEnvironment::new(&tmp_dir.path().to_path_buf()).unwrap();
+    let store = SledProtocolDataStore::new(&env, None).unwrap();
+
+    store_tests::roundtrip_succeeds(&env, &store, inputs).unwrap()
+}
+
 proptest! {
     #[test]
     fn prop_in_memory_roundtrip_succeeds(
@@ -57,4 +70,11 @@ proptest! {
     ) {
         assert!(lmdb_roundtrip_succeeds(m))
     }
+
+    #[test]
+    fn prop_sled_roundtrip_succeeds(
+        m in collection::btree_map(gens_ext::protocol_version_arb(), gens::protocol_data_arb(), get_range())
+    ) {
+        assert!(sled_roundtrip_succeeds(m))
+    }
 }
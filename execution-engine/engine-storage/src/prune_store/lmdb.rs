@@ -0,0 +1,53 @@
+use lmdb::{Database, DatabaseFlags};
+
+use crate::{
+    error,
+    prune_store::{self, PruneStore},
+    store::Store,
+    transaction_source::lmdb::LmdbEnvironment,
+    trie_store::operations::prune::TriePruner,
+};
+
+/// An LMDB-backed prune store.
+///
+/// Wraps [`lmdb::Database`].
+#[derive(Debug, Clone)]
+pub struct LmdbPruneStore {
+    db: Database,
+}
+
+impl LmdbPruneStore {
+    pub fn new(
+        env: &LmdbEnvironment,
+        maybe_name: Option<&str>,
+        flags: DatabaseFlags,
+    ) -> Result<Self, error::Error> {
+        let name = Self::name(maybe_name);
+        let db = env.env().create_db(Some(&name), flags)?;
+        Ok(LmdbPruneStore { db })
+    }
+
+    pub fn open(env: &LmdbEnvironment, maybe_name: Option<&str>) -> Result<Self, error::Error> {
+        let name = Self::name(maybe_name);
+        let db = env.env().open_db(Some(&name))?;
+        Ok(LmdbPruneStore { db })
+    }
+
+    fn name(maybe_name: Option<&str>) -> String {
+        maybe_name
+            .map(|name| format!("{}-{}", prune_store::NAME, name))
+            .unwrap_or_else(|| String::from(prune_store::NAME))
+    }
+}
+
+impl Store<u8, TriePruner> for LmdbPruneStore {
+    type Error = error::Error;
+
+    type Handle = Database;
+
+    fn handle(&self) -> Self::Handle {
+        self.db
+    }
+}
+
+impl PruneStore for LmdbPruneStore {}
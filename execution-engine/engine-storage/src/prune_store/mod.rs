@@ -0,0 +1,13 @@
+//! A store for persisting the progress of a [`TriePruner`] which is driven by commits.
+pub mod lmdb;
+pub mod sled;
+
+use crate::{store::Store, trie_store::operations::prune::TriePruner};
+
+const NAME: &str = "PRUNE_STORE";
+
+/// The key the progress of the prune in progress is stored at.
+pub const PRUNE_STORE_KEY: u8 = 0;
+
+/// An entity which persists the [`TriePruner`] of the prune in progress, if there is one.
+pub trait PruneStore: Store<u8, TriePruner> {}
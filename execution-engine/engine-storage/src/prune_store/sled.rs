@@ -0,0 +1,40 @@
+use sled::Tree;
+
+use crate::{
+    error,
+    prune_store::{self, PruneStore},
+    store::Store,
+    transaction_source::sled::SledEnvironment,
+    trie_store::operations::prune::TriePruner,
+};
+
+/// A sled-backed prune store.
+///
+/// Wraps [`sled::Tree`].
+#[derive(Debug, Clone)]
+pub struct SledPruneStore {
+    tree: Tree,
+}
+
+impl SledPruneStore {
+    /// Opens the tree backing the store, creating it if it does not exist.
+    pub fn new(env: &SledEnvironment, maybe_name: Option<&str>) -> Result<Self, error::Error> {
+        let name = maybe_name
+            .map(|name| format!("{}-{}", prune_store::NAME, name))
+            .unwrap_or_else(|| String::from(prune_store::NAME));
+        let tree = env.db().open_tree(name)?;
+        Ok(SledPruneStore { tree })
+    }
+}
+
+impl Store<u8, TriePruner> for SledPruneStore {
+    type Error = error::Error;
+
+    type Handle = Tree;
+
+    fn handle(&self) -> Self::Handle {
+        self.tree.clone()
+    }
+}
+
+impl PruneStore for SledPruneStore {}
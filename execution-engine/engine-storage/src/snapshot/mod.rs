@@ -0,0 +1,547 @@
+//! Streaming export and import of global state snapshots.
+//!
+//! A snapshot holds every trie node reachable from a state root, along with the entries of the
+//! protocol data store, so that a node can rebuild its global state without replaying blocks.
+//!
+//! # Format
+//!
+//! A snapshot starts with a header made of [`SNAPSHOT_MAGIC`], the format version as a `u32` and
+//! the declared state root.  The header is followed by a sequence of chunks.  Each chunk is the
+//! length-prefixed `bytesrepr` encoding of its payload, followed by the BLAKE2b checksum of the
+//! payload.  The last chunk is an end marker, so that truncated snapshots are rejected.
+//!
+//! Trie nodes are exported children first, and the importer only writes a node once all of its
+//! children are stored.  Every chunk boundary is therefore a valid point from which to resume an
+//! import, and an interrupted import never leaves a node with missing descendants in the store.
+
+#[cfg(test)]
+mod tests;
+
+use std::{
+    convert::TryFrom,
+    io::{self, Read, Seek, SeekFrom, Write},
+};
+
+use failure::Fail;
+
+use engine_shared::{
+    newtypes::{Blake2bHash, CorrelationId, BLAKE2B_DIGEST_LENGTH},
+    stored_value::StoredValue,
+};
+use types::{
+    bytesrepr::{self, FromBytes, ToBytes, U32_SERIALIZED_LENGTH, U8_SERIALIZED_LENGTH},
+    Key, ProtocolVersion,
+};
+
+use crate::{
+    error,
+    protocol_data::ProtocolData,
+    protocol_data_store::ProtocolDataStore,
+    transaction_source::{Enumerable, Readable, Transaction, TransactionSource},
+    trie::{Pointer, Trie},
+    trie_store::TrieStore,
+};
+
+/// The bytes every snapshot starts with.
+pub const SNAPSHOT_MAGIC: [u8; 8] = *b"CLSNAPSH";
+
+/// The version of the snapshot format written by [`export`].
+pub const SNAPSHOT_FORMAT_VERSION: u32 = 1;
+
+/// The length of a snapshot header, i.e. the offset of its first chunk.
+pub const SNAPSHOT_HEADER_LENGTH: u64 =
+    (SNAPSHOT_MAGIC.len() + U32_SERIALIZED_LENGTH + BLAKE2B_DIGEST_LENGTH) as u64;
+
+/// The default number of serialized trie node bytes after which [`export`] starts a new chunk.
+pub const DEFAULT_CHUNK_LENGTH: usize = 4 * 1024 * 1024;
+
+/// The largest chunk payload accepted by the importer.
+const MAX_CHUNK_LENGTH: usize = 256 * 1024 * 1024;
+
+const PROTOCOL_DATA_TAG: u8 = 0;
+const TRIE_NODES_TAG: u8 = 1;
+const END_TAG: u8 = 2;
+
+#[derive(Debug, Fail)]
+pub enum Error {
+    #[fail(display = "{}", _0)]
+    Io(#[fail(cause)] io::Error),
+
+    #[fail(display = "{}", _0)]
+    Storage(#[fail(cause)] error::Error),
+
+    #[fail(display = "{}", _0)]
+    BytesRepr(#[fail(cause)] bytesrepr::Error),
+
+    #[fail(display = "Not a global state snapshot")]
+    InvalidMagic,
+
+    #[fail(display = "Unsupported snapshot format version: {}", _0)]
+    UnsupportedVersion(u32),
+
+    #[fail(display = "Invalid resume offset: {}", _0)]
+    InvalidOffset(u64),
+
+    #[fail(
+        display = "Chunk at offset {} has length {} which is too large",
+        offset, length
+    )]
+    ChunkTooLarge { offset: u64, length: usize },
+
+    #[fail(display = "Chunk at offset {} does not match its checksum", _0)]
+    ChecksumMismatch(u64),
+
+    #[fail(display = "No trie value at key: {:?}", _0)]
+    MissingTrieNode(Blake2bHash),
+
+    #[fail(display = "Trie node {:?} refers to missing child {:?}", node, child)]
+    MissingChild {
+        node: Blake2bHash,
+        child: Blake2bHash,
+    },
+
+    #[fail(
+        display = "Snapshot declares state root {:?} but contains {:?}",
+        declared, computed
+    )]
+    RootMismatch {
+        declared: Blake2bHash,
+        computed: Option<Blake2bHash>,
+    },
+}
+
+impl From<io::Error> for Error {
+    fn from(error: io::Error) -> Self {
+        Error::Io(error)
+    }
+}
+
+impl From<error::Error> for Error {
+    fn from(error: error::Error) -> Self {
+        Error::Storage(error)
+    }
+}
+
+impl From<error::in_memory::Error> for Error {
+    fn from(error: error::in_memory::Error) -> Self {
+        Error::Storage(error.into())
+    }
+}
+
+impl From<lmdb::Error> for Error {
+    fn from(error: lmdb::Error) -> Self {
+        Error::Storage(error.into())
+    }
+}
+
+impl From<bytesrepr::Error> for Error {
+    fn from(error: bytesrepr::Error) -> Self {
+        Error::BytesRepr(error)
+    }
+}
+
+/// Counters describing the contents of a snapshot.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct SnapshotStats {
+    pub chunks: u64,
+    pub trie_nodes: u64,
+    pub protocol_data_entries: u64,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ExportResult {
+    RootNotFound,
+    Success(SnapshotStats),
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ImportResult {
+    /// There are chunks which have not been imported yet.
+    InProgress,
+    /// The end of the snapshot was reached and its state root is stored.
+    Complete(SnapshotStats),
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Chunk {
+    ProtocolData(Vec<(ProtocolVersion, ProtocolData)>),
+    TrieNodes(Vec<Vec<u8>>),
+    End,
+}
+
+impl Chunk {
+    fn tag(&self) -> u8 {
+        match self {
+            Chunk::ProtocolData(_) => PROTOCOL_DATA_TAG,
+            Chunk::TrieNodes(_) => TRIE_NODES_TAG,
+            Chunk::End => END_TAG,
+        }
+    }
+}
+
+impl ToBytes for Chunk {
+    fn to_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
+        let mut ret = bytesrepr::allocate_buffer(self)?;
+        ret.push(self.tag());
+        match self {
+            Chunk::ProtocolData(entries) => ret.append(&mut entries.to_bytes()?),
+            Chunk::TrieNodes(nodes) => ret.append(&mut nodes.to_bytes()?),
+            Chunk::End => (),
+        }
+        Ok(ret)
+    }
+
+    fn serialized_length(&self) -> usize {
+        U8_SERIALIZED_LENGTH
+            + match self {
+                Chunk::ProtocolData(entries) => entries.serialized_length(),
+                Chunk::TrieNodes(nodes) => nodes.serialized_length(),
+                Chunk::End => 0,
+            }
+    }
+}
+
+impl FromBytes for Chunk {
+    fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), bytesrepr::Error> {
+        let (tag, rem) = u8::from_bytes(bytes)?;
+        match tag {
+            PROTOCOL_DATA_TAG => {
+                let (entries, rem) = FromBytes::from_bytes(rem)?;
+                Ok((Chunk::ProtocolData(entries), rem))
+            }
+            TRIE_NODES_TAG => {
+                let (nodes, rem) = FromBytes::from_bytes(rem)?;
+                Ok((Chunk::TrieNodes(nodes), rem))
+            }
+            END_TAG => Ok((Chunk::End, rem)),
+            _ => Err(bytesrepr::Error::Formatting),
+        }
+    }
+}
+
+/// Writes a chunk, returning the number of bytes written.
+fn write_chunk<W: Write>(writer: &mut W, chunk: &Chunk) -> Result<u64, Error> {
+    let payload = chunk.to_bytes()?;
+    let length = u32::try_from(payload.len()).map_err(|_| bytesrepr::Error::OutOfMemory)?;
+    let checksum = Blake2bHash::new(&payload);
+    writer.write_all(&length.to_bytes()?)?;
+    writer.write_all(&payload)?;
+    writer.write_all(&checksum.to_bytes()?)?;
+    Ok((U32_SERIALIZED_LENGTH + payload.len() + BLAKE2B_DIGEST_LENGTH) as u64)
+}
+
+/// Reads the chunk at `offset`, returning it along with the number of bytes read.
+fn read_chunk<Rd: Read>(reader: &mut Rd, offset: u64) -> Result<(Chunk, u64), Error> {
+    let mut length_bytes = [0u8; U32_SERIALIZED_LENGTH];
+    reader.read_exact(&mut length_bytes)?;
+    let length = u32::from_bytes(&length_bytes)?.0 as usize;
+    if length > MAX_CHUNK_LENGTH {
+        return Err(Error::ChunkTooLarge { offset, length });
+    }
+
+    let mut payload = vec![0u8; length];
+    reader.read_exact(&mut payload)?;
+    let mut checksum = [0u8; BLAKE2B_DIGEST_LENGTH];
+    reader.read_exact(&mut checksum)?;
+    if Blake2bHash::new(&payload).value() != checksum {
+        return Err(Error::ChecksumMismatch(offset));
+    }
+
+    let chunk = bytesrepr::deserialize(payload)?;
+    Ok((
+        chunk,
+        (U32_SERIALIZED_LENGTH + length + BLAKE2B_DIGEST_LENGTH) as u64,
+    ))
+}
+
+fn child_hashes<K, V>(trie: &Trie<K, V>) -> Vec<Blake2bHash> {
+    match trie {
+        Trie::Leaf { .. } => Vec::new(),
+        Trie::Node { pointer_block } => pointer_block[..]
+            .iter()
+            .flatten()
+            .map(Pointer::hash)
+            .copied()
+            .collect(),
+        Trie::Extension { pointer, .. } => vec![*pointer.hash()],
+    }
+}
+
+/// Writes a snapshot of the trie nodes reachable from `state_root` and of every protocol data
+/// entry to `writer`.
+///
+/// A new chunk is started whenever the trie nodes buffered for the current one reach
+/// `target_chunk_length` bytes.  Everything is read within a single read transaction, so commits
+/// made during the export don't affect the snapshot.
+pub fn export<'a, R, S, P, W>(
+    _correlation_id: CorrelationId,
+    environment: &'a R,
+    trie_store: &S,
+    protocol_data_store: &P,
+    state_root: &Blake2bHash,
+    target_chunk_length: usize,
+    writer: &mut W,
+) -> Result<ExportResult, Error>
+where
+    R: TransactionSource<'a, Handle = S::Handle>,
+    S: TrieStore<Key, StoredValue>,
+    P: ProtocolDataStore<Handle = S::Handle>,
+    S::Error: From<R::Error>,
+    P::Error: From<R::Error>,
+    Error: From<R::Error> + From<S::Error> + From<P::Error>,
+    W: Write,
+{
+    let txn = environment.create_read_txn()?;
+
+    let root: Trie<Key, StoredValue> = match trie_store.get(&txn, state_root)? {
+        Some(root) => root,
+        None => return Ok(ExportResult::RootNotFound),
+    };
+
+    let mut stats = SnapshotStats::default();
+
+    writer.write_all(&SNAPSHOT_MAGIC)?;
+    writer.write_all(&SNAPSHOT_FORMAT_VERSION.to_bytes()?)?;
+    writer.write_all(&state_root.to_bytes()?)?;
+
+    let mut protocol_data_entries = Vec::new();
+    for key_bytes in txn.keys_after(protocol_data_store.handle(), None, usize::max_value())? {
+        let protocol_version: ProtocolVersion = bytesrepr::deserialize(key_bytes)?;
+        if let Some(protocol_data) = protocol_data_store.get(&txn, &protocol_version)? {
+            protocol_data_entries.push((protocol_version, protocol_data));
+        }
+    }
+    stats.protocol_data_entries = protocol_data_entries.len() as u64;
+    write_chunk(writer, &Chunk::ProtocolData(protocol_data_entries))?;
+    stats.chunks += 1;
+
+    // Post-order traversal, so that children are written before their parents.  Nodes in a trie
+    // are all distinct, so none is visited twice.
+    let mut buffer: Vec<Vec<u8>> = Vec::new();
+    let mut buffered_length: usize = 0;
+    let root_children = child_hashes(&root);
+    let mut stack: Vec<(Trie<Key, StoredValue>, Vec<Blake2bHash>)> = vec![(root, root_children)];
+    while let Some((_, pending_children)) = stack.last_mut() {
+        match pending_children.pop() {
+            Some(child_hash) => {
+                let child: Trie<Key, StoredValue> = trie_store
+                    .get(&txn, &child_hash)?
+                    .ok_or(Error::MissingTrieNode(child_hash))?;
+                let grandchildren = child_hashes(&child);
+                stack.push((child, grandchildren));
+            }
+            None => {
+                let (trie, _) = stack.pop().expect("stack should not be empty");
+                let trie_bytes = trie.to_bytes()?;
+                buffered_length += trie_bytes.len();
+                buffer.push(trie_bytes);
+                stats.trie_nodes += 1;
+                if buffered_length >= target_chunk_length {
+                    let nodes = std::mem::take(&mut buffer);
+                    write_chunk(writer, &Chunk::TrieNodes(nodes))?;
+                    stats.chunks += 1;
+                    buffered_length = 0;
+                }
+            }
+        }
+    }
+    if !buffer.is_empty() {
+        write_chunk(writer, &Chunk::TrieNodes(buffer))?;
+        stats.chunks += 1;
+    }
+
+    write_chunk(writer, &Chunk::End)?;
+    stats.chunks += 1;
+    writer.flush()?;
+
+    txn.commit()?;
+    Ok(ExportResult::Success(stats))
+}
+
+/// Reads a snapshot chunk by chunk, writing its contents to a trie store and a protocol data
+/// store.
+///
+/// Each chunk is imported within its own read-write transaction.  After a chunk is imported,
+/// [`SnapshotImporter::offset`] can be persisted and later passed to [`SnapshotImporter::resume`]
+/// to continue an interrupted import.
+#[derive(Debug)]
+pub struct SnapshotImporter<Rd> {
+    reader: Rd,
+    state_root: Blake2bHash,
+    offset: u64,
+    last_trie_node: Option<Blake2bHash>,
+    stats: SnapshotStats,
+}
+
+impl<Rd: Read> SnapshotImporter<Rd> {
+    /// Reads the snapshot header and prepares to import the first chunk.
+    pub fn new(mut reader: Rd) -> Result<Self, Error> {
+        let state_root = read_header(&mut reader)?;
+        Ok(SnapshotImporter {
+            reader,
+            state_root,
+            offset: SNAPSHOT_HEADER_LENGTH,
+            last_trie_node: None,
+            stats: SnapshotStats::default(),
+        })
+    }
+
+    /// Returns the state root declared by the snapshot.
+    pub fn state_root(&self) -> Blake2bHash {
+        self.state_root
+    }
+
+    /// Returns the offset in the snapshot of the next chunk to import.
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// Returns the contents imported so far by this importer.
+    pub fn stats(&self) -> SnapshotStats {
+        self.stats
+    }
+
+    /// Imports the next chunk of the snapshot.
+    ///
+    /// Every trie node is checked to refer only to children which are already stored before
+    /// being written under its recomputed hash.  Reaching the end of the snapshot fails if its
+    /// declared state root has not been stored.
+    pub fn import_chunk<'a, R, S, P>(
+        &mut self,
+        _correlation_id: CorrelationId,
+        environment: &'a R,
+        trie_store: &S,
+        protocol_data_store: &P,
+    ) -> Result<ImportResult, Error>
+    where
+        R: TransactionSource<'a, Handle = S::Handle>,
+        S: TrieStore<Key, StoredValue>,
+        P: ProtocolDataStore<Handle = S::Handle>,
+        S::Error: From<R::Error>,
+        P::Error: From<R::Error>,
+        Error: From<R::Error> + From<S::Error> + From<P::Error>,
+    {
+        let (chunk, chunk_length) = read_chunk(&mut self.reader, self.offset)?;
+
+        let mut txn = environment.create_read_write_txn()?;
+        let result = match chunk {
+            Chunk::ProtocolData(entries) => {
+                for (protocol_version, protocol_data) in &entries {
+                    protocol_data_store.put(&mut txn, protocol_version, protocol_data)?;
+                }
+                self.stats.protocol_data_entries += entries.len() as u64;
+                ImportResult::InProgress
+            }
+            Chunk::TrieNodes(nodes) => {
+                for trie_bytes in nodes {
+                    let trie: Trie<Key, StoredValue> = bytesrepr::deserialize(trie_bytes)?;
+                    // Hash the canonical encoding, so that the stored node matches its key
+                    let trie_hash = Blake2bHash::new(&trie.to_bytes()?);
+                    for child_hash in child_hashes(&trie) {
+                        if txn
+                            .read(trie_store.handle(), &child_hash.to_bytes()?)?
+                            .is_none()
+                        {
+                            return Err(Error::MissingChild {
+                                node: trie_hash,
+                                child: child_hash,
+                            });
+                        }
+                    }
+                    trie_store.put(&mut txn, &trie_hash, &trie)?;
+                    self.last_trie_node = Some(trie_hash);
+                    self.stats.trie_nodes += 1;
+                }
+                ImportResult::InProgress
+            }
+            Chunk::End => {
+                let is_root_stored = txn
+                    .read(trie_store.handle(), &self.state_root.to_bytes()?)?
+                    .is_some();
+                let is_last_node_root = self
+                    .last_trie_node
+                    .map_or(true, |last_trie_node| last_trie_node == self.state_root);
+                if !is_root_stored || !is_last_node_root {
+                    return Err(Error::RootMismatch {
+                        declared: self.state_root,
+                        computed: self.last_trie_node,
+                    });
+                }
+                ImportResult::Complete(self.stats)
+            }
+        };
+        txn.commit()?;
+
+        self.offset += chunk_length;
+        self.stats.chunks += 1;
+        if let ImportResult::Complete(_) = result {
+            return Ok(ImportResult::Complete(self.stats));
+        }
+        Ok(result)
+    }
+}
+
+impl<Rd: Read + Seek> SnapshotImporter<Rd> {
+    /// Reads the snapshot header and prepares to import the chunk at `offset`, as returned by
+    /// [`SnapshotImporter::offset`] after the last successfully imported chunk.
+    pub fn resume(mut reader: Rd, offset: u64) -> Result<Self, Error> {
+        if offset < SNAPSHOT_HEADER_LENGTH {
+            return Err(Error::InvalidOffset(offset));
+        }
+        let state_root = read_header(&mut reader)?;
+        reader.seek(SeekFrom::Start(offset))?;
+        Ok(SnapshotImporter {
+            reader,
+            state_root,
+            offset,
+            last_trie_node: None,
+            stats: SnapshotStats::default(),
+        })
+    }
+}
+
+fn read_header<Rd: Read>(reader: &mut Rd) -> Result<Blake2bHash, Error> {
+    let mut magic = [0u8; SNAPSHOT_MAGIC.len()];
+    reader.read_exact(&mut magic)?;
+    if magic != SNAPSHOT_MAGIC {
+        return Err(Error::InvalidMagic);
+    }
+
+    let mut version_bytes = [0u8; U32_SERIALIZED_LENGTH];
+    reader.read_exact(&mut version_bytes)?;
+    let version = u32::from_bytes(&version_bytes)?.0;
+    if version != SNAPSHOT_FORMAT_VERSION {
+        return Err(Error::UnsupportedVersion(version));
+    }
+
+    let mut state_root_bytes = [0u8; BLAKE2B_DIGEST_LENGTH];
+    reader.read_exact(&mut state_root_bytes)?;
+    Ok(state_root_bytes.into())
+}
+
+/// Imports a whole snapshot, returning its declared state root along with the imported contents.
+pub fn import<'a, R, S, P, Rd>(
+    correlation_id: CorrelationId,
+    environment: &'a R,
+    trie_store: &S,
+    protocol_data_store: &P,
+    reader: Rd,
+) -> Result<(Blake2bHash, SnapshotStats), Error>
+where
+    R: TransactionSource<'a, Handle = S::Handle>,
+    S: TrieStore<Key, StoredValue>,
+    P: ProtocolDataStore<Handle = S::Handle>,
+    S::Error: From<R::Error>,
+    P::Error: From<R::Error>,
+    Error: From<R::Error> + From<S::Error> + From<P::Error>,
+    Rd: Read,
+{
+    let mut importer = SnapshotImporter::new(reader)?;
+    loop {
+        if let ImportResult::Complete(stats) =
+            importer.import_chunk(correlation_id, environment, trie_store, protocol_data_store)?
+        {
+            return Ok((importer.state_root(), stats));
+        }
+    }
+}
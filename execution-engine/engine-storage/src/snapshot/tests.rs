@@ -0,0 +1,321 @@
+use std::{io::Cursor, sync::Arc};
+
+use lmdb::DatabaseFlags;
+use tempfile::{tempdir, TempDir};
+
+use engine_shared::{additive_map::AdditiveMap, storage_costs::StorageCosts, transform::Transform};
+use engine_wasm_prep::{host_function_costs::HostFunctionCosts, wasm_costs::WasmCosts};
+use types::{account::AccountHash, CLValue};
+
+use super::*;
+use crate::{
+    global_state::{
+        in_memory::InMemoryGlobalState, lmdb::LmdbGlobalState, CommitResult, StateProvider,
+        StateReader,
+    },
+    protocol_data_store::lmdb::LmdbProtocolDataStore,
+    transaction_source::lmdb::LmdbEnvironment,
+    trie_store::lmdb::LmdbTrieStore,
+    TEST_MAP_SIZE,
+};
+
+const PROTOCOL_VERSION: ProtocolVersion = ProtocolVersion::V1_0_0;
+
+fn test_pairs() -> Vec<(Key, StoredValue)> {
+    (0u8..20)
+        .map(|i| {
+            (
+                Key::Account(AccountHash::new([i; 32])),
+                StoredValue::CLValue(CLValue::from_t(i32::from(i)).unwrap()),
+            )
+        })
+        .collect()
+}
+
+fn test_protocol_data() -> ProtocolData {
+    ProtocolData::new(
+        WasmCosts::default(),
+        HostFunctionCosts::default(),
+        StorageCosts::default(),
+        [1; 32],
+        [2; 32],
+        [3; 32],
+    )
+}
+
+fn populate<P>(state: &P) -> Blake2bHash
+where
+    P: StateProvider,
+    P::Error: std::fmt::Debug,
+{
+    let effects: AdditiveMap<Key, Transform> = test_pairs()
+        .into_iter()
+        .map(|(key, value)| (key, Transform::Write(value)))
+        .collect();
+    let state_root = match state
+        .commit(CorrelationId::new(), state.empty_root(), effects)
+        .unwrap()
+    {
+        CommitResult::Success { state_root, .. } => state_root,
+        _ => panic!("commit should succeed"),
+    };
+    state
+        .put_protocol_data(PROTOCOL_VERSION, &test_protocol_data())
+        .unwrap();
+    state_root
+}
+
+fn assert_contains_test_state<P>(state: &P, state_root: Blake2bHash)
+where
+    P: StateProvider,
+    P::Error: std::fmt::Debug,
+    <P::Reader as StateReader<Key, StoredValue>>::Error: std::fmt::Debug,
+{
+    let correlation_id = CorrelationId::new();
+    let view = state
+        .checkout(state_root)
+        .unwrap()
+        .expect("root should exist");
+    for (key, value) in test_pairs() {
+        assert_eq!(view.read(correlation_id, &key).unwrap(), Some(value));
+    }
+    assert_eq!(
+        state.get_protocol_data(PROTOCOL_VERSION).unwrap(),
+        Some(test_protocol_data())
+    );
+}
+
+fn create_lmdb_state() -> (LmdbGlobalState, TempDir) {
+    let temp_dir = tempdir().unwrap();
+    let environment =
+        Arc::new(LmdbEnvironment::new(&temp_dir.path().to_path_buf(), *TEST_MAP_SIZE).unwrap());
+    let trie_store =
+        Arc::new(LmdbTrieStore::new(&environment, None, DatabaseFlags::empty()).unwrap());
+    let protocol_data_store =
+        Arc::new(LmdbProtocolDataStore::new(&environment, None, DatabaseFlags::empty()).unwrap());
+    let state = LmdbGlobalState::empty(environment, trie_store, protocol_data_store).unwrap();
+    (state, temp_dir)
+}
+
+fn export_in_memory(
+    state: &InMemoryGlobalState,
+    state_root: &Blake2bHash,
+    target_chunk_length: usize,
+) -> (Vec<u8>, SnapshotStats) {
+    let mut snapshot = Vec::new();
+    let result = export(
+        CorrelationId::new(),
+        &*state.environment,
+        &*state.trie_store,
+        &*state.protocol_data_store,
+        state_root,
+        target_chunk_length,
+        &mut snapshot,
+    )
+    .unwrap();
+    match result {
+        ExportResult::Success(stats) => (snapshot, stats),
+        ExportResult::RootNotFound => panic!("root should exist"),
+    }
+}
+
+fn import_into_lmdb<Rd: Read>(state: &LmdbGlobalState, reader: Rd) -> Result<Blake2bHash, Error> {
+    import(
+        CorrelationId::new(),
+        &*state.environment,
+        &*state.trie_store,
+        &*state.protocol_data_store,
+        reader,
+    )
+    .map(|(state_root, _)| state_root)
+}
+
+#[test]
+fn in_memory_snapshot_round_trips_to_lmdb() {
+    let source = InMemoryGlobalState::empty().unwrap();
+    let state_root = populate(&source);
+    let (snapshot, export_stats) = export_in_memory(&source, &state_root, DEFAULT_CHUNK_LENGTH);
+    assert_eq!(export_stats.protocol_data_entries, 1);
+
+    let (target, _temp_dir) = create_lmdb_state();
+    let (imported_root, import_stats) = import(
+        CorrelationId::new(),
+        &*target.environment,
+        &*target.trie_store,
+        &*target.protocol_data_store,
+        Cursor::new(snapshot),
+    )
+    .unwrap();
+    assert_eq!(imported_root, state_root);
+    assert_eq!(import_stats, export_stats);
+    assert_contains_test_state(&target, state_root);
+}
+
+#[test]
+fn lmdb_snapshot_round_trips_to_in_memory() {
+    let (source, _temp_dir) = create_lmdb_state();
+    let state_root = populate(&source);
+    let mut snapshot = Vec::new();
+    let export_stats = match export(
+        CorrelationId::new(),
+        &*source.environment,
+        &*source.trie_store,
+        &*source.protocol_data_store,
+        &state_root,
+        DEFAULT_CHUNK_LENGTH,
+        &mut snapshot,
+    )
+    .unwrap()
+    {
+        ExportResult::Success(stats) => stats,
+        ExportResult::RootNotFound => panic!("root should exist"),
+    };
+
+    let target = InMemoryGlobalState::empty().unwrap();
+    let (imported_root, import_stats) = import(
+        CorrelationId::new(),
+        &*target.environment,
+        &*target.trie_store,
+        &*target.protocol_data_store,
+        Cursor::new(snapshot),
+    )
+    .unwrap();
+    assert_eq!(imported_root, state_root);
+    assert_eq!(import_stats, export_stats);
+    assert_contains_test_state(&target, state_root);
+}
+
+#[test]
+fn export_of_unknown_root_returns_root_not_found() {
+    let source = InMemoryGlobalState::empty().unwrap();
+    let mut snapshot = Vec::new();
+    let result = export(
+        CorrelationId::new(),
+        &*source.environment,
+        &*source.trie_store,
+        &*source.protocol_data_store,
+        &Blake2bHash::new(&[0u8]),
+        DEFAULT_CHUNK_LENGTH,
+        &mut snapshot,
+    )
+    .unwrap();
+    assert_eq!(result, ExportResult::RootNotFound);
+    assert!(snapshot.is_empty());
+}
+
+#[test]
+fn interrupted_import_can_be_resumed() {
+    let source = InMemoryGlobalState::empty().unwrap();
+    let state_root = populate(&source);
+    // A chunk per trie node
+    let (snapshot, export_stats) = export_in_memory(&source, &state_root, 1);
+    assert_eq!(export_stats.chunks, export_stats.trie_nodes + 2);
+
+    let (target, _temp_dir) = create_lmdb_state();
+    let correlation_id = CorrelationId::new();
+    let checkpoint = {
+        let mut importer = SnapshotImporter::new(Cursor::new(snapshot.clone())).unwrap();
+        for _ in 0..3 {
+            let result = importer
+                .import_chunk(
+                    correlation_id,
+                    &*target.environment,
+                    &*target.trie_store,
+                    &*target.protocol_data_store,
+                )
+                .unwrap();
+            assert_eq!(result, ImportResult::InProgress);
+        }
+        importer.offset()
+    };
+    assert!(target.checkout(state_root).unwrap().is_none());
+
+    let mut importer = SnapshotImporter::resume(Cursor::new(snapshot), checkpoint).unwrap();
+    assert_eq!(importer.state_root(), state_root);
+    loop {
+        let result = importer
+            .import_chunk(
+                correlation_id,
+                &*target.environment,
+                &*target.trie_store,
+                &*target.protocol_data_store,
+            )
+            .unwrap();
+        if let ImportResult::Complete(stats) = result {
+            assert_eq!(stats.chunks, export_stats.chunks - 3);
+            break;
+        }
+    }
+    assert_contains_test_state(&target, state_root);
+}
+
+#[test]
+fn import_rejects_corrupted_chunk() {
+    let source = InMemoryGlobalState::empty().unwrap();
+    let state_root = populate(&source);
+    let (mut snapshot, _) = export_in_memory(&source, &state_root, DEFAULT_CHUNK_LENGTH);
+
+    let last_index = snapshot.len() - 1;
+    snapshot[last_index] ^= 1;
+
+    let (target, _temp_dir) = create_lmdb_state();
+    match import_into_lmdb(&target, Cursor::new(snapshot)) {
+        Err(Error::ChecksumMismatch(_)) => (),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn import_rejects_truncated_snapshot() {
+    let source = InMemoryGlobalState::empty().unwrap();
+    let state_root = populate(&source);
+    let (mut snapshot, _) = export_in_memory(&source, &state_root, DEFAULT_CHUNK_LENGTH);
+
+    // Drops the end marker
+    let end_chunk_length = U32_SERIALIZED_LENGTH + U8_SERIALIZED_LENGTH + BLAKE2B_DIGEST_LENGTH;
+    snapshot.truncate(snapshot.len() - end_chunk_length);
+
+    let (target, _temp_dir) = create_lmdb_state();
+    match import_into_lmdb(&target, Cursor::new(snapshot)) {
+        Err(Error::Io(_)) => (),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn import_rejects_mismatched_state_root() {
+    let source = InMemoryGlobalState::empty().unwrap();
+    let state_root = populate(&source);
+    let (mut snapshot, _) = export_in_memory(&source, &state_root, DEFAULT_CHUNK_LENGTH);
+
+    // Declares the empty root, which the target already holds, instead of the exported one
+    let declared_root = source.empty_root();
+    let root_start = SNAPSHOT_MAGIC.len() + U32_SERIALIZED_LENGTH;
+    snapshot[root_start..root_start + BLAKE2B_DIGEST_LENGTH]
+        .copy_from_slice(&declared_root.value());
+
+    let (target, _temp_dir) = create_lmdb_state();
+    match import_into_lmdb(&target, Cursor::new(snapshot)) {
+        Err(Error::RootMismatch { declared, computed }) => {
+            assert_eq!(declared, declared_root);
+            assert_eq!(computed, Some(state_root));
+        }
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn import_rejects_invalid_header() {
+    let mut snapshot = SNAPSHOT_MAGIC.to_vec();
+    snapshot.extend_from_slice(&(SNAPSHOT_FORMAT_VERSION + 1).to_bytes().unwrap());
+    snapshot.extend_from_slice(&[0u8; BLAKE2B_DIGEST_LENGTH]);
+    match SnapshotImporter::new(Cursor::new(snapshot)) {
+        Err(Error::UnsupportedVersion(version)) => assert_eq!(version, SNAPSHOT_FORMAT_VERSION + 1),
+        other => panic!("unexpected result: {:?}", other),
+    }
+
+    match SnapshotImporter::new(Cursor::new(vec![0u8; SNAPSHOT_HEADER_LENGTH as usize])) {
+        Err(Error::InvalidMagic) => (),
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
@@ -5,7 +5,7 @@ pub(crate) mod tests;
 use types::bytesrepr::{self, FromBytes, ToBytes};
 
 pub use self::store_ext::StoreExt;
-use crate::transaction_source::{Readable, Writable};
+use crate::transaction_source::{Deletable, Readable, Writable};
 
 pub trait Store<K, V> {
     type Error: From<bytesrepr::Error>;
@@ -42,4 +42,14 @@ pub trait Store<K, V> {
         txn.write(handle, &key.to_bytes()?, &value.to_bytes()?)
             .map_err(Into::into)
     }
+
+    fn delete<T>(&self, txn: &mut T, key: &K) -> Result<(), Self::Error>
+    where
+        T: Deletable<Handle = Self::Handle>,
+        K: ToBytes,
+        Self::Error: From<T::Error>,
+    {
+        let handle = self.handle();
+        txn.delete(handle, &key.to_bytes()?).map_err(Into::into)
+    }
 }
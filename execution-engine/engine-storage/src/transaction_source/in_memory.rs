@@ -5,7 +5,9 @@ use std::{
 
 use crate::{
     error::in_memory::Error,
-    transaction_source::{Readable, Transaction, TransactionSource, Writable},
+    transaction_source::{
+        Deletable, Enumerable, Readable, Transaction, TransactionSource, Writable,
+    },
 };
 
 /// A marker for use in a mutex which represents the capability to perform a
@@ -16,6 +18,22 @@ type WriteLock<'a> = MutexGuard<'a, WriteCapability>;
 
 type BytesMap = HashMap<Vec<u8>, Vec<u8>>;
 
+/// Returns at most `limit` keys of `view` which are strictly greater than `start_after`, in
+/// ascending order.
+fn keys_after(view: Option<&BytesMap>, start_after: Option<&[u8]>, limit: usize) -> Vec<Vec<u8>> {
+    let mut ret: Vec<Vec<u8>> = match view {
+        Some(view) => view
+            .keys()
+            .filter(|key| start_after.map_or(true, |start_after| key.as_slice() > start_after))
+            .cloned()
+            .collect(),
+        None => return Vec::new(),
+    };
+    ret.sort();
+    ret.truncate(limit);
+    ret
+}
+
 type PoisonError<'a> = sync::PoisonError<MutexGuard<'a, HashMap<Option<String>, BytesMap>>>;
 
 /// A read transaction for the in-memory trie store.
@@ -54,6 +72,17 @@ impl Readable for InMemoryReadTransaction {
     }
 }
 
+impl Enumerable for InMemoryReadTransaction {
+    fn keys_after(
+        &self,
+        handle: Self::Handle,
+        start_after: Option<&[u8]>,
+        limit: usize,
+    ) -> Result<Vec<Vec<u8>>, Self::Error> {
+        Ok(keys_after(self.view.get(&handle), start_after, limit))
+    }
+}
+
 /// A read-write transaction for the in-memory trie store.
 pub struct InMemoryReadWriteTransaction<'a> {
     view: HashMap<Option<String>, BytesMap>,
@@ -107,6 +136,26 @@ impl<'a> Writable for InMemoryReadWriteTransaction<'a> {
     }
 }
 
+impl<'a> Deletable for InMemoryReadWriteTransaction<'a> {
+    fn delete(&mut self, handle: Self::Handle, key: &[u8]) -> Result<(), Self::Error> {
+        if let Some(sub_view) = self.view.get_mut(&handle) {
+            sub_view.remove(key);
+        }
+        Ok(())
+    }
+}
+
+impl<'a> Enumerable for InMemoryReadWriteTransaction<'a> {
+    fn keys_after(
+        &self,
+        handle: Self::Handle,
+        start_after: Option<&[u8]>,
+        limit: usize,
+    ) -> Result<Vec<Vec<u8>>, Self::Error> {
+        Ok(keys_after(self.view.get(&handle), start_after, limit))
+    }
+}
+
 /// An environment for the in-memory trie store.
 pub struct InMemoryEnvironment {
     data: Arc<Mutex<HashMap<Option<String>, BytesMap>>>,
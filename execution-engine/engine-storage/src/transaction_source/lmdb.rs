@@ -1,67 +1,267 @@
-use std::path::PathBuf;
+use std::{
+    cmp,
+    path::PathBuf,
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Instant,
+};
 
-use lmdb::{self, Database, Environment, RoTransaction, RwTransaction, WriteFlags};
+use lmdb::{
+    self, Cursor, Database, Environment, EnvironmentFlags, RoTransaction, RwTransaction, WriteFlags,
+};
+use parking_lot::{RwLock, RwLockReadGuard};
+
+use engine_shared::{
+    logging::{log_duration, log_metric},
+    newtypes::CorrelationId,
+};
 
 use crate::{
     error,
-    transaction_source::{Readable, Transaction, TransactionSource, Writable},
-    MAX_DBS,
+    transaction_source::{
+        Deletable, Enumerable, Readable, Transaction, TransactionSource, Writable,
+    },
+    GAUGE_METRIC_KEY, MAX_DBS,
 };
 
-impl<'a> Transaction for RoTransaction<'a> {
+const LMDB_MAP_RESIZE_DURATION: &str = "lmdb_map_resize_duration";
+const LMDB_MAP_SIZE: &str = "lmdb_map_size";
+const LMDB_MAP_RESIZES: &str = "lmdb_map_resizes";
+const RESIZE: &str = "resize";
+
+/// Returns at most `limit` keys of the given database which are strictly greater than
+/// `start_after`, in ascending order.
+fn keys_after<T: lmdb::Transaction>(
+    txn: &T,
+    handle: Database,
+    start_after: Option<&[u8]>,
+    limit: usize,
+) -> Result<Vec<Vec<u8>>, lmdb::Error> {
+    let mut ret = Vec::new();
+    if limit == 0 {
+        return Ok(ret);
+    }
+    let mut cursor = txn.open_ro_cursor(handle)?;
+    if let Some(start_after) = start_after {
+        // Position the cursor at the first key which is greater than or equal to `start_after`.
+        // `Cursor::iter_from` can't be used here, as it panics when there is no such key.
+        match cursor.get(Some(start_after), None, lmdb_sys::MDB_SET_RANGE) {
+            Ok((Some(key), _)) if key != start_after => ret.push(key.to_vec()),
+            Ok(_) => (),
+            Err(lmdb::Error::NotFound) => return Ok(ret),
+            Err(error) => return Err(error),
+        }
+    }
+    let remaining = limit - ret.len();
+    ret.extend(cursor.iter().take(remaining).map(|(key, _)| key.to_vec()));
+    Ok(ret)
+}
+
+/// A change made by a read-write transaction, kept so that it can be replayed if the transaction
+/// has to be restarted after growing the map.
+enum Change {
+    Write(Database, Vec<u8>, Vec<u8>),
+    Delete(Database, Vec<u8>),
+}
+
+fn apply_change(txn: &mut RwTransaction, change: &Change) -> Result<(), lmdb::Error> {
+    match change {
+        Change::Write(handle, key, value) => txn.put(*handle, key, value, WriteFlags::empty()),
+        Change::Delete(handle, key) => match txn.del(*handle, key, None) {
+            Ok(()) | Err(lmdb::Error::NotFound) => Ok(()),
+            Err(e) => Err(e),
+        },
+    }
+}
+
+fn read<T: lmdb::Transaction>(
+    txn: &T,
+    handle: Database,
+    key: &[u8],
+) -> Result<Option<Vec<u8>>, lmdb::Error> {
+    match txn.get(handle, &key) {
+        Ok(bytes) => Ok(Some(bytes.to_vec())),
+        Err(lmdb::Error::NotFound) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// A read transaction for the LMDB-backed trie store.
+pub struct LmdbReadTransaction<'a> {
+    // Declared before the guard, so that the transaction is dropped while the guard is held
+    txn: RoTransaction<'a>,
+    _resize_guard: RwLockReadGuard<'a, ()>,
+}
+
+impl<'a> Transaction for LmdbReadTransaction<'a> {
     type Error = lmdb::Error;
 
     type Handle = Database;
 
     fn commit(self) -> Result<(), Self::Error> {
-        lmdb::Transaction::commit(self)
+        lmdb::Transaction::commit(self.txn)
     }
 }
 
-impl<'a> Readable for RoTransaction<'a> {
+impl<'a> Readable for LmdbReadTransaction<'a> {
     fn read(&self, handle: Self::Handle, key: &[u8]) -> Result<Option<Vec<u8>>, Self::Error> {
-        match lmdb::Transaction::get(self, handle, &key) {
-            Ok(bytes) => Ok(Some(bytes.to_vec())),
-            Err(lmdb::Error::NotFound) => Ok(None),
-            Err(e) => Err(e),
+        read(&self.txn, handle, key)
+    }
+}
+
+impl<'a> Enumerable for LmdbReadTransaction<'a> {
+    fn keys_after(
+        &self,
+        handle: Self::Handle,
+        start_after: Option<&[u8]>,
+        limit: usize,
+    ) -> Result<Vec<Vec<u8>>, Self::Error> {
+        keys_after(&self.txn, handle, start_after, limit)
+    }
+}
+
+/// A read-write transaction for the LMDB-backed trie store.
+///
+/// If the environment's map may grow, the changes made by the transaction are kept in memory.
+/// When a change or the commit fails because the map is full, the transaction is aborted, the map
+/// is grown, and the changes are replayed in a new transaction.  Anything read by the aborted
+/// transaction is not re-read, so a transaction which may be restarted must only depend on data
+/// which can't be changed by a concurrent writer, such as content-addressed trie nodes.
+pub struct LmdbReadWriteTransaction<'a> {
+    environment: &'a LmdbEnvironment,
+    // Declared before the guard, so that the transaction is dropped while the guard is held.
+    // Only `None` while the transaction is being restarted.
+    txn: Option<RwTransaction<'a>>,
+    resize_guard: Option<RwLockReadGuard<'a, ()>>,
+    /// The size of the map when `txn` began.
+    map_size: usize,
+    changes: Vec<Change>,
+}
+
+impl<'a> LmdbReadWriteTransaction<'a> {
+    fn begin(environment: &'a LmdbEnvironment) -> Result<Self, lmdb::Error> {
+        let resize_guard = environment.resize_lock.read_recursive();
+        let map_size = environment.map_size();
+        let txn = environment.env.begin_rw_txn()?;
+        Ok(LmdbReadWriteTransaction {
+            environment,
+            txn: Some(txn),
+            resize_guard: Some(resize_guard),
+            map_size,
+            changes: Vec::new(),
+        })
+    }
+
+    fn txn(&self) -> &RwTransaction<'a> {
+        self.txn.as_ref().expect("should have a transaction")
+    }
+
+    fn apply(&mut self, change: Change) -> Result<(), lmdb::Error> {
+        loop {
+            let txn = self.txn.as_mut().expect("should have a transaction");
+            match apply_change(txn, &change) {
+                Err(lmdb::Error::MapFull) if self.environment.map_may_grow() => {
+                    self.grow_and_replay()?
+                }
+                result => {
+                    result?;
+                    break;
+                }
+            }
+        }
+        if self.environment.map_may_grow() {
+            self.changes.push(change);
+        }
+        Ok(())
+    }
+
+    /// Aborts the current transaction, grows the map and replays the changes made so far in a new
+    /// transaction.  Fails with `MapFull` if the map can't grow any further.
+    fn grow_and_replay(&mut self) -> Result<(), lmdb::Error> {
+        loop {
+            self.txn = None;
+            self.resize_guard = None;
+            self.environment.grow_map(self.map_size)?;
+
+            self.resize_guard = Some(self.environment.resize_lock.read_recursive());
+            self.map_size = self.environment.map_size();
+            let mut txn = self.environment.env.begin_rw_txn()?;
+            let replayed = self
+                .changes
+                .iter()
+                .try_for_each(|change| apply_change(&mut txn, change));
+            self.txn = Some(txn);
+            match replayed {
+                Err(lmdb::Error::MapFull) => continue,
+                result => return result,
+            }
         }
     }
 }
 
-impl<'a> Transaction for RwTransaction<'a> {
+impl<'a> Transaction for LmdbReadWriteTransaction<'a> {
     type Error = lmdb::Error;
 
     type Handle = Database;
 
-    fn commit(self) -> Result<(), Self::Error> {
-        <RwTransaction<'a> as lmdb::Transaction>::commit(self)
+    fn commit(mut self) -> Result<(), Self::Error> {
+        loop {
+            let txn = self.txn.take().expect("should have a transaction");
+            match lmdb::Transaction::commit(txn) {
+                Err(lmdb::Error::MapFull) if self.environment.map_may_grow() => {
+                    self.grow_and_replay()?
+                }
+                result => return result,
+            }
+        }
     }
 }
 
-impl<'a> Readable for RwTransaction<'a> {
+impl<'a> Readable for LmdbReadWriteTransaction<'a> {
     fn read(&self, handle: Self::Handle, key: &[u8]) -> Result<Option<Vec<u8>>, Self::Error> {
-        match lmdb::Transaction::get(self, handle, &key) {
-            Ok(bytes) => Ok(Some(bytes.to_vec())),
-            Err(lmdb::Error::NotFound) => Ok(None),
-            Err(e) => Err(e),
-        }
+        read(self.txn(), handle, key)
     }
 }
 
-impl<'a> Writable for RwTransaction<'a> {
+impl<'a> Writable for LmdbReadWriteTransaction<'a> {
     fn write(&mut self, handle: Self::Handle, key: &[u8], value: &[u8]) -> Result<(), Self::Error> {
-        self.put(handle, &key, &value, WriteFlags::empty())
-            .map_err(Into::into)
+        self.apply(Change::Write(handle, key.to_vec(), value.to_vec()))
+    }
+}
+
+impl<'a> Deletable for LmdbReadWriteTransaction<'a> {
+    fn delete(&mut self, handle: Self::Handle, key: &[u8]) -> Result<(), Self::Error> {
+        self.apply(Change::Delete(handle, key.to_vec()))
+    }
+}
+
+impl<'a> Enumerable for LmdbReadWriteTransaction<'a> {
+    fn keys_after(
+        &self,
+        handle: Self::Handle,
+        start_after: Option<&[u8]>,
+        limit: usize,
+    ) -> Result<Vec<Vec<u8>>, Self::Error> {
+        keys_after(self.txn(), handle, start_after, limit)
     }
 }
 
 /// The environment for an LMDB-backed trie store.
 ///
-/// Wraps [`lmdb::Environment`].
+/// Wraps [`lmdb::Environment`].  By default the map has a fixed size, and writes fail with
+/// `MapFull` once it is full.  [`LmdbEnvironment::with_map_growth`] lets the map grow instead.
+///
+/// Growing the map requires that no transaction is open on the environment, so every transaction
+/// holds a shared lock which is taken exclusively while the map grows.  A thread must not hold a
+/// read transaction open while it writes, as growing the map would then wait on it forever.
 #[derive(Debug)]
 pub struct LmdbEnvironment {
     path: PathBuf,
     env: Environment,
+    resize_lock: RwLock<()>,
+    map_size: AtomicUsize,
+    max_map_size: usize,
+    growth_factor: f64,
+    resizes: AtomicUsize,
 }
 
 impl LmdbEnvironment {
@@ -70,8 +270,45 @@ impl LmdbEnvironment {
             .set_max_dbs(MAX_DBS)
             .set_map_size(map_size)
             .open(path)?;
-        let path = path.to_owned();
-        Ok(LmdbEnvironment { path, env })
+        Ok(LmdbEnvironment::from_env(path, env, map_size))
+    }
+
+    /// Opens an existing environment for reading only.  The map size is taken from the
+    /// environment, and any attempt to create a read-write transaction will fail.
+    pub fn open_read_only(path: &PathBuf) -> Result<Self, error::Error> {
+        let env = Environment::new()
+            .set_max_dbs(MAX_DBS)
+            .set_flags(EnvironmentFlags::READ_ONLY)
+            .open(path)?;
+        Ok(LmdbEnvironment::from_env(path, env, 0))
+    }
+
+    fn from_env(path: &PathBuf, env: Environment, map_size: usize) -> Self {
+        LmdbEnvironment {
+            path: path.to_owned(),
+            env,
+            resize_lock: RwLock::new(()),
+            map_size: AtomicUsize::new(map_size),
+            max_map_size: map_size,
+            growth_factor: 1.0,
+            resizes: AtomicUsize::new(0),
+        }
+    }
+
+    /// Lets the map grow when a read-write transaction finds it full, rather than failing the
+    /// transaction.  Each time, the map is grown by `growth_factor`, up to `max_map_size` bytes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `growth_factor` is not greater than 1.
+    pub fn with_map_growth(mut self, max_map_size: usize, growth_factor: f64) -> Self {
+        assert!(
+            growth_factor > 1.0,
+            "growth factor should be greater than 1"
+        );
+        self.max_map_size = max_map_size;
+        self.growth_factor = growth_factor;
+        self
     }
 
     pub fn path(&self) -> &PathBuf {
@@ -81,6 +318,72 @@ impl LmdbEnvironment {
     pub fn env(&self) -> &Environment {
         &self.env
     }
+
+    /// Returns the current size of the map in bytes.
+    pub fn map_size(&self) -> usize {
+        self.map_size.load(Ordering::SeqCst)
+    }
+
+    fn map_may_grow(&self) -> bool {
+        self.max_map_size > self.map_size()
+    }
+
+    /// Grows the map after a read-write transaction found it full while it was `full_map_size`
+    /// bytes.  Does nothing if another transaction has grown it since.  No transactions may be
+    /// held by the calling thread.
+    fn grow_map(&self, full_map_size: usize) -> Result<(), lmdb::Error> {
+        let _resize_guard = self.resize_lock.write();
+        let map_size = self.map_size();
+        if map_size != full_map_size {
+            return Ok(());
+        }
+
+        let page_size = self.env.stat()?.page_size() as usize;
+        let wanted_map_size = (map_size as f64 * self.growth_factor) as usize;
+        let new_map_size = cmp::min(
+            round_up(wanted_map_size, page_size),
+            self.max_map_size / page_size * page_size,
+        );
+        if new_map_size <= map_size {
+            return Err(lmdb::Error::MapFull);
+        }
+
+        let start = Instant::now();
+        // Safe, as the environment is open and the resize lock ensures no transactions are open.
+        let result = unsafe { lmdb_sys::mdb_env_set_mapsize(self.env.env(), new_map_size) };
+        if result != lmdb_sys::MDB_SUCCESS {
+            return Err(lmdb::Error::from_err_code(result));
+        }
+        self.map_size.store(new_map_size, Ordering::SeqCst);
+        let resizes = self.resizes.fetch_add(1, Ordering::SeqCst) + 1;
+
+        let correlation_id = CorrelationId::new();
+        log_metric(
+            correlation_id,
+            LMDB_MAP_SIZE,
+            RESIZE,
+            GAUGE_METRIC_KEY,
+            new_map_size as f64,
+        );
+        log_metric(
+            correlation_id,
+            LMDB_MAP_RESIZES,
+            RESIZE,
+            GAUGE_METRIC_KEY,
+            resizes as f64,
+        );
+        log_duration(
+            correlation_id,
+            LMDB_MAP_RESIZE_DURATION,
+            RESIZE,
+            start.elapsed(),
+        );
+        Ok(())
+    }
+}
+
+fn round_up(value: usize, multiple: usize) -> usize {
+    (value + multiple - 1) / multiple * multiple
 }
 
 impl<'a> TransactionSource<'a> for LmdbEnvironment {
@@ -88,15 +391,20 @@ impl<'a> TransactionSource<'a> for LmdbEnvironment {
 
     type Handle = Database;
 
-    type ReadTransaction = RoTransaction<'a>;
+    type ReadTransaction = LmdbReadTransaction<'a>;
 
-    type ReadWriteTransaction = RwTransaction<'a>;
+    type ReadWriteTransaction = LmdbReadWriteTransaction<'a>;
 
-    fn create_read_txn(&'a self) -> Result<RoTransaction<'a>, Self::Error> {
-        self.env.begin_ro_txn()
+    fn create_read_txn(&'a self) -> Result<LmdbReadTransaction<'a>, Self::Error> {
+        let resize_guard = self.resize_lock.read_recursive();
+        let txn = self.env.begin_ro_txn()?;
+        Ok(LmdbReadTransaction {
+            txn,
+            _resize_guard: resize_guard,
+        })
     }
 
-    fn create_read_write_txn(&'a self) -> Result<RwTransaction<'a>, Self::Error> {
-        self.env.begin_rw_txn()
+    fn create_read_write_txn(&'a self) -> Result<LmdbReadWriteTransaction<'a>, Self::Error> {
+        LmdbReadWriteTransaction::begin(self)
     }
 }
@@ -1,5 +1,7 @@
 pub mod in_memory;
 pub mod lmdb;
+pub mod scratch;
+pub mod sled;
 
 /// A transaction which can be committed or aborted.
 pub trait Transaction: Sized {
@@ -33,6 +35,29 @@ pub trait Writable: Transaction {
     fn write(&mut self, handle: Self::Handle, key: &[u8], value: &[u8]) -> Result<(), Self::Error>;
 }
 
+/// A transaction with the capability to delete from a given [`Handle`](Transaction::Handle).
+pub trait Deletable: Transaction {
+    /// Removes the value at the corresponding key from a given [`Transaction::Handle`].
+    ///
+    /// Removing a key which is not present is not an error.
+    fn delete(&mut self, handle: Self::Handle, key: &[u8]) -> Result<(), Self::Error>;
+}
+
+/// A transaction with the capability to list the keys of a given
+/// [`Handle`](Transaction::Handle).
+pub trait Enumerable: Transaction {
+    /// Returns at most `limit` keys from a given [`Transaction::Handle`], in ascending byte
+    /// order, starting with the first key which is strictly greater than `start_after`.
+    ///
+    /// If `start_after` is `None`, starts with the first key.
+    fn keys_after(
+        &self,
+        handle: Self::Handle,
+        start_after: Option<&[u8]>,
+        limit: usize,
+    ) -> Result<Vec<Vec<u8>>, Self::Error>;
+}
+
 /// A source of transactions e.g. values that implement [`Readable`]
 /// and/or [`Writable`].
 pub trait TransactionSource<'a> {
@@ -44,11 +69,14 @@ pub trait TransactionSource<'a> {
     type Handle;
 
     /// Represents the type of read transactions.
-    type ReadTransaction: Readable<Error = Self::Error, Handle = Self::Handle>;
+    type ReadTransaction: Readable<Error = Self::Error, Handle = Self::Handle>
+        + Enumerable<Error = Self::Error, Handle = Self::Handle>;
 
     /// Represents the type of read-write transactions.
     type ReadWriteTransaction: Readable<Error = Self::Error, Handle = Self::Handle>
-        + Writable<Error = Self::Error, Handle = Self::Handle>;
+        + Writable<Error = Self::Error, Handle = Self::Handle>
+        + Deletable<Error = Self::Error, Handle = Self::Handle>
+        + Enumerable<Error = Self::Error, Handle = Self::Handle>;
 
     /// Creates a read transaction.
     fn create_read_txn(&'a self) -> Result<Self::ReadTransaction, Self::Error>;
@@ -0,0 +1,263 @@
+//! Transactions which read from an LMDB environment, but keep every change in memory.
+//!
+//! A [`ScratchEnvironment`] overlays an in-memory environment on an [`LmdbEnvironment`].  Reads
+//! see the changes committed to the overlay on top of the LMDB data, and nothing is ever written
+//! to LMDB until the overlay is [flushed](ScratchEnvironment::flush).  Deleted keys are remembered
+//! in memory too, so that they stay hidden even though they are still in LMDB.
+
+use std::sync::Arc;
+
+use lmdb::Database;
+
+use crate::{
+    error,
+    transaction_source::{
+        in_memory::{InMemoryEnvironment, InMemoryReadTransaction, InMemoryReadWriteTransaction},
+        lmdb::{LmdbEnvironment, LmdbReadTransaction},
+        Deletable, Enumerable, Readable, Transaction, TransactionSource, Writable,
+    },
+};
+
+/// The databases behind a store of a [`ScratchEnvironment`].
+#[derive(Debug, Clone)]
+pub struct ScratchHandle {
+    /// The LMDB database which is overlaid.
+    pub lmdb: Database,
+    /// The in-memory database holding the values written to the overlay.
+    pub written: Option<String>,
+    /// The in-memory database holding the keys deleted from the overlay, with empty values.
+    pub deleted: Option<String>,
+}
+
+/// The numbers of keys written to and deleted from LMDB by a [flush](ScratchEnvironment::flush).
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct FlushCounts {
+    pub written: usize,
+    pub deleted: usize,
+}
+
+/// Returns the value at `key`, as seen with the changes in `memory` applied to `lmdb`.
+fn read<M>(
+    lmdb: &LmdbReadTransaction,
+    memory: &M,
+    handle: ScratchHandle,
+    key: &[u8],
+) -> Result<Option<Vec<u8>>, error::Error>
+where
+    M: Readable<Handle = Option<String>>,
+    error::Error: From<M::Error>,
+{
+    if let Some(value) = memory.read(handle.written, key)? {
+        return Ok(Some(value));
+    }
+    if memory.read(handle.deleted, key)?.is_some() {
+        return Ok(None);
+    }
+    Ok(lmdb.read(handle.lmdb, key)?)
+}
+
+/// Returns at most `limit` keys which are strictly greater than `start_after`, in ascending order,
+/// as seen with the changes in `memory` applied to `lmdb`.
+fn keys_after<M>(
+    lmdb: &LmdbReadTransaction,
+    memory: &M,
+    handle: ScratchHandle,
+    start_after: Option<&[u8]>,
+    limit: usize,
+) -> Result<Vec<Vec<u8>>, error::Error>
+where
+    M: Readable<Handle = Option<String>> + Enumerable<Handle = Option<String>>,
+    error::Error: From<M::Error>,
+{
+    let mut ret = memory.keys_after(handle.written.clone(), start_after, limit)?;
+
+    // Deleted keys are skipped, so more than one page of LMDB keys may be needed.
+    let mut lmdb_start_after = start_after.map(<[u8]>::to_vec);
+    let mut lmdb_keys = Vec::new();
+    while lmdb_keys.len() < limit {
+        let page = lmdb.keys_after(handle.lmdb, lmdb_start_after.as_deref(), limit)?;
+        let is_last_page = page.len() < limit;
+        lmdb_start_after = page.last().cloned();
+        for key in page {
+            if memory.read(handle.deleted.clone(), &key)?.is_none() {
+                lmdb_keys.push(key);
+            }
+        }
+        if is_last_page {
+            break;
+        }
+    }
+
+    ret.extend(lmdb_keys);
+    ret.sort();
+    ret.dedup();
+    ret.truncate(limit);
+    Ok(ret)
+}
+
+/// A read transaction for a [`ScratchEnvironment`].
+pub struct ScratchReadTransaction<'a> {
+    lmdb: LmdbReadTransaction<'a>,
+    memory: InMemoryReadTransaction,
+}
+
+impl<'a> Transaction for ScratchReadTransaction<'a> {
+    type Error = error::Error;
+
+    type Handle = ScratchHandle;
+
+    fn commit(self) -> Result<(), Self::Error> {
+        self.memory.commit()?;
+        self.lmdb.commit().map_err(Into::into)
+    }
+}
+
+impl<'a> Readable for ScratchReadTransaction<'a> {
+    fn read(&self, handle: Self::Handle, key: &[u8]) -> Result<Option<Vec<u8>>, Self::Error> {
+        read(&self.lmdb, &self.memory, handle, key)
+    }
+}
+
+impl<'a> Enumerable for ScratchReadTransaction<'a> {
+    fn keys_after(
+        &self,
+        handle: Self::Handle,
+        start_after: Option<&[u8]>,
+        limit: usize,
+    ) -> Result<Vec<Vec<u8>>, Self::Error> {
+        keys_after(&self.lmdb, &self.memory, handle, start_after, limit)
+    }
+}
+
+/// A read-write transaction for a [`ScratchEnvironment`].
+///
+/// Only the in-memory overlay is written to, so LMDB is only read from.
+pub struct ScratchReadWriteTransaction<'a> {
+    lmdb: LmdbReadTransaction<'a>,
+    memory: InMemoryReadWriteTransaction<'a>,
+}
+
+impl<'a> Transaction for ScratchReadWriteTransaction<'a> {
+    type Error = error::Error;
+
+    type Handle = ScratchHandle;
+
+    fn commit(self) -> Result<(), Self::Error> {
+        self.memory.commit()?;
+        self.lmdb.commit().map_err(Into::into)
+    }
+}
+
+impl<'a> Readable for ScratchReadWriteTransaction<'a> {
+    fn read(&self, handle: Self::Handle, key: &[u8]) -> Result<Option<Vec<u8>>, Self::Error> {
+        read(&self.lmdb, &self.memory, handle, key)
+    }
+}
+
+impl<'a> Writable for ScratchReadWriteTransaction<'a> {
+    fn write(&mut self, handle: Self::Handle, key: &[u8], value: &[u8]) -> Result<(), Self::Error> {
+        self.memory.delete(handle.deleted, key)?;
+        self.memory
+            .write(handle.written, key, value)
+            .map_err(Into::into)
+    }
+}
+
+impl<'a> Deletable for ScratchReadWriteTransaction<'a> {
+    fn delete(&mut self, handle: Self::Handle, key: &[u8]) -> Result<(), Self::Error> {
+        self.memory.delete(handle.written, key)?;
+        self.memory
+            .write(handle.deleted, key, &[])
+            .map_err(Into::into)
+    }
+}
+
+impl<'a> Enumerable for ScratchReadWriteTransaction<'a> {
+    fn keys_after(
+        &self,
+        handle: Self::Handle,
+        start_after: Option<&[u8]>,
+        limit: usize,
+    ) -> Result<Vec<Vec<u8>>, Self::Error> {
+        keys_after(&self.lmdb, &self.memory, handle, start_after, limit)
+    }
+}
+
+/// An environment which overlays an in-memory environment on an [`LmdbEnvironment`].
+pub struct ScratchEnvironment {
+    lmdb: Arc<LmdbEnvironment>,
+    memory: InMemoryEnvironment,
+}
+
+impl ScratchEnvironment {
+    pub fn new(lmdb: Arc<LmdbEnvironment>) -> Self {
+        ScratchEnvironment {
+            lmdb,
+            memory: InMemoryEnvironment::new(),
+        }
+    }
+
+    /// Returns the environment which is overlaid.
+    pub fn lmdb(&self) -> &LmdbEnvironment {
+        &self.lmdb
+    }
+
+    /// Returns the environment holding the overlay.
+    pub fn memory(&self) -> &InMemoryEnvironment {
+        &self.memory
+    }
+
+    /// Writes the committed changes of the given stores into LMDB, within a single read-write
+    /// transaction.
+    ///
+    /// The overlay is left as it is, so it keeps reading the same values.
+    pub fn flush(&self, handles: &[ScratchHandle]) -> Result<FlushCounts, error::Error> {
+        let mut txn = self.lmdb.create_read_write_txn()?;
+        let mut ret = FlushCounts::default();
+        for handle in handles {
+            for key in self
+                .memory
+                .data(handle.deleted.as_deref())?
+                .unwrap_or_default()
+                .keys()
+            {
+                txn.delete(handle.lmdb, key)?;
+                ret.deleted += 1;
+            }
+            for (key, value) in self
+                .memory
+                .data(handle.written.as_deref())?
+                .unwrap_or_default()
+            {
+                txn.write(handle.lmdb, &key, &value)?;
+                ret.written += 1;
+            }
+        }
+        txn.commit()?;
+        Ok(ret)
+    }
+}
+
+impl<'a> TransactionSource<'a> for ScratchEnvironment {
+    type Error = error::Error;
+
+    type Handle = ScratchHandle;
+
+    type ReadTransaction = ScratchReadTransaction<'a>;
+
+    type ReadWriteTransaction = ScratchReadWriteTransaction<'a>;
+
+    fn create_read_txn(&'a self) -> Result<ScratchReadTransaction<'a>, Self::Error> {
+        Ok(ScratchReadTransaction {
+            lmdb: self.lmdb.create_read_txn()?,
+            memory: self.memory.create_read_txn()?,
+        })
+    }
+
+    fn create_read_write_txn(&'a self) -> Result<ScratchReadWriteTransaction<'a>, Self::Error> {
+        Ok(ScratchReadWriteTransaction {
+            lmdb: self.lmdb.create_read_txn()?,
+            memory: self.memory.create_read_write_txn()?,
+        })
+    }
+}
@@ -0,0 +1,282 @@
+use std::{cmp::Ordering, collections::BTreeMap, ops::Bound, path::PathBuf};
+
+use parking_lot::{Mutex, MutexGuard};
+use sled::{
+    ConflictableTransactionResult, Db, TransactionError, Transactional, TransactionalTree, Tree,
+};
+
+use crate::{
+    error,
+    transaction_source::{
+        Deletable, Enumerable, Readable, Transaction, TransactionSource, Writable,
+    },
+};
+
+/// The uncommitted changes made to a tree during a read-write transaction.  `None` marks a deleted
+/// key.
+type Changes = BTreeMap<Vec<u8>, Option<Vec<u8>>>;
+
+/// The most trees a read-write transaction can change.  A [`SledEnvironment`] backs a trie store, a
+/// protocol data store and a prune store.
+const MAX_CHANGED_TREES: usize = 3;
+
+/// Applies `changes` to `tree` within a sled transaction.
+fn apply_changes(tree: &TransactionalTree, changes: &Changes) -> ConflictableTransactionResult<()> {
+    for (key, maybe_value) in changes {
+        match maybe_value {
+            Some(value) => tree.insert(key.as_slice(), value.as_slice())?,
+            None => tree.remove(key.as_slice())?,
+        };
+    }
+    Ok(())
+}
+
+/// Returns at most `limit` keys of `tree` which are strictly greater than `start_after`, in
+/// ascending order, as seen with `changes` applied to it.
+fn keys_after(
+    tree: &Tree,
+    changes: Option<&Changes>,
+    start_after: Option<&[u8]>,
+    limit: usize,
+) -> Result<Vec<Vec<u8>>, sled::Error> {
+    let lower = match start_after {
+        Some(start_after) => Bound::Excluded(start_after.to_vec()),
+        None => Bound::Unbounded,
+    };
+    let no_changes = Changes::new();
+    let mut changed = changes
+        .unwrap_or(&no_changes)
+        .range((lower.clone(), Bound::Unbounded))
+        .peekable();
+    let mut stored = tree.range((lower, Bound::Unbounded)).keys();
+    let mut next_stored = stored.next().transpose()?;
+
+    let mut ret = Vec::new();
+    while ret.len() < limit {
+        let order = match (&next_stored, changed.peek()) {
+            (None, None) => break,
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (Some(stored_key), Some((changed_key, _))) => {
+                stored_key.as_ref().cmp(changed_key.as_slice())
+            }
+        };
+        if order == Ordering::Less {
+            let stored_key = next_stored.take().expect("should have a stored key");
+            ret.push(stored_key.to_vec());
+            next_stored = stored.next().transpose()?;
+            continue;
+        }
+        if order == Ordering::Equal {
+            // The change supersedes the stored value.
+            next_stored = stored.next().transpose()?;
+        }
+        let (changed_key, maybe_value) = changed.next().expect("should have a changed key");
+        if maybe_value.is_some() {
+            ret.push(changed_key.to_owned());
+        }
+    }
+    Ok(ret)
+}
+
+/// A read transaction for the sled-backed trie store.
+///
+/// Unlike an LMDB read transaction, this does not read from a snapshot: it reads the live trees, so
+/// changes committed while it is open become visible to it, and two reads made through it may see
+/// different states of the store.  Reads of tries are unaffected, as a trie is never changed once
+/// written, but a trie can disappear from under a reader while it is pruned.
+pub struct SledReadTransaction {
+    _private: (),
+}
+
+impl Transaction for SledReadTransaction {
+    type Error = sled::Error;
+
+    type Handle = Tree;
+
+    fn commit(self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+impl Readable for SledReadTransaction {
+    fn read(&self, handle: Self::Handle, key: &[u8]) -> Result<Option<Vec<u8>>, Self::Error> {
+        Ok(handle.get(key)?.map(|bytes| bytes.to_vec()))
+    }
+}
+
+impl Enumerable for SledReadTransaction {
+    fn keys_after(
+        &self,
+        handle: Self::Handle,
+        start_after: Option<&[u8]>,
+        limit: usize,
+    ) -> Result<Vec<Vec<u8>>, Self::Error> {
+        keys_after(&handle, None, start_after, limit)
+    }
+}
+
+/// A read-write transaction for the sled-backed trie store.
+///
+/// Changes are held in memory until the transaction is committed, at which point the changes to
+/// every tree are applied atomically by a single sled transaction, and flushed to disk.
+pub struct SledReadWriteTransaction<'a> {
+    db: &'a Db,
+    changes: Vec<(Tree, Changes)>,
+    _write_lock: MutexGuard<'a, ()>,
+}
+
+impl<'a> SledReadWriteTransaction<'a> {
+    fn changes(&self, handle: &Tree) -> Option<&Changes> {
+        let name = handle.name();
+        self.changes
+            .iter()
+            .find(|(tree, _)| tree.name() == name)
+            .map(|(_, changes)| changes)
+    }
+
+    fn changes_mut(&mut self, handle: Tree) -> &mut Changes {
+        let name = handle.name();
+        let index = match self
+            .changes
+            .iter()
+            .position(|(tree, _)| tree.name() == name)
+        {
+            Some(index) => index,
+            None => {
+                self.changes.push((handle, Changes::new()));
+                self.changes.len() - 1
+            }
+        };
+        &mut self.changes[index].1
+    }
+}
+
+impl<'a> Transaction for SledReadWriteTransaction<'a> {
+    type Error = sled::Error;
+
+    type Handle = Tree;
+
+    fn commit(self) -> Result<(), Self::Error> {
+        // sled only runs transactions over a fixed number of trees, given as a tuple
+        let result = match self.changes.as_slice() {
+            [] => return Ok(()),
+            [(tree, changes)] => tree.transaction(|tree| apply_changes(tree, changes)),
+            [(tree_0, changes_0), (tree_1, changes_1)] => {
+                (tree_0, tree_1).transaction(|(tree_0, tree_1)| {
+                    apply_changes(tree_0, changes_0)?;
+                    apply_changes(tree_1, changes_1)
+                })
+            }
+            [(tree_0, changes_0), (tree_1, changes_1), (tree_2, changes_2)] => {
+                (tree_0, tree_1, tree_2).transaction(|(tree_0, tree_1, tree_2)| {
+                    apply_changes(tree_0, changes_0)?;
+                    apply_changes(tree_1, changes_1)?;
+                    apply_changes(tree_2, changes_2)
+                })
+            }
+            _ => {
+                return Err(sled::Error::Unsupported(format!(
+                    "a transaction can change at most {} trees",
+                    MAX_CHANGED_TREES
+                )))
+            }
+        };
+        match result {
+            Ok(()) => (),
+            Err(TransactionError::Storage(error)) => return Err(error),
+            Err(TransactionError::Abort(())) => unreachable!("changes are never aborted"),
+        }
+        self.db.flush()?;
+        Ok(())
+    }
+}
+
+impl<'a> Readable for SledReadWriteTransaction<'a> {
+    fn read(&self, handle: Self::Handle, key: &[u8]) -> Result<Option<Vec<u8>>, Self::Error> {
+        if let Some(maybe_value) = self.changes(&handle).and_then(|changes| changes.get(key)) {
+            return Ok(maybe_value.to_owned());
+        }
+        Ok(handle.get(key)?.map(|bytes| bytes.to_vec()))
+    }
+}
+
+impl<'a> Writable for SledReadWriteTransaction<'a> {
+    fn write(&mut self, handle: Self::Handle, key: &[u8], value: &[u8]) -> Result<(), Self::Error> {
+        self.changes_mut(handle)
+            .insert(key.to_vec(), Some(value.to_vec()));
+        Ok(())
+    }
+}
+
+impl<'a> Deletable for SledReadWriteTransaction<'a> {
+    fn delete(&mut self, handle: Self::Handle, key: &[u8]) -> Result<(), Self::Error> {
+        self.changes_mut(handle).insert(key.to_vec(), None);
+        Ok(())
+    }
+}
+
+impl<'a> Enumerable for SledReadWriteTransaction<'a> {
+    fn keys_after(
+        &self,
+        handle: Self::Handle,
+        start_after: Option<&[u8]>,
+        limit: usize,
+    ) -> Result<Vec<Vec<u8>>, Self::Error> {
+        keys_after(&handle, self.changes(&handle), start_after, limit)
+    }
+}
+
+/// The environment for a sled-backed trie store.
+///
+/// Wraps [`sled::Db`].  Read-write transactions are serialized by a mutex.
+#[derive(Debug)]
+pub struct SledEnvironment {
+    path: PathBuf,
+    db: Db,
+    write_mutex: Mutex<()>,
+}
+
+impl SledEnvironment {
+    pub fn new(path: &PathBuf) -> Result<Self, error::Error> {
+        let db = sled::open(path)?;
+        let path = path.to_owned();
+        let write_mutex = Mutex::new(());
+        Ok(SledEnvironment {
+            path,
+            db,
+            write_mutex,
+        })
+    }
+
+    pub fn path(&self) -> &PathBuf {
+        &self.path
+    }
+
+    pub fn db(&self) -> &Db {
+        &self.db
+    }
+}
+
+impl<'a> TransactionSource<'a> for SledEnvironment {
+    type Error = sled::Error;
+
+    type Handle = Tree;
+
+    type ReadTransaction = SledReadTransaction;
+
+    type ReadWriteTransaction = SledReadWriteTransaction<'a>;
+
+    fn create_read_txn(&'a self) -> Result<SledReadTransaction, Self::Error> {
+        Ok(SledReadTransaction { _private: () })
+    }
+
+    fn create_read_write_txn(&'a self) -> Result<SledReadWriteTransaction<'a>, Self::Error> {
+        let _write_lock = self.write_mutex.lock();
+        Ok(SledReadWriteTransaction {
+            db: &self.db,
+            changes: Vec::new(),
+            _write_lock,
+        })
+    }
+}
@@ -0,0 +1,66 @@
+use engine_shared::newtypes::Blake2bHash;
+use types::{
+    bytesrepr::{self, FromBytes, ToBytes},
+    merkle_proof,
+};
+
+use crate::trie::Trie;
+
+/// A proof of the value stored at a key in a Merkle Trie, or of the key's absence.
+///
+/// Holds the trie nodes along the path of the key, starting at the root, and ending either with
+/// the leaf holding the key or with the node at which the path of the key diverges from the trie.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrieMerkleProof<K, V> {
+    key: K,
+    nodes: Vec<Trie<K, V>>,
+}
+
+impl<K, V> TrieMerkleProof<K, V> {
+    pub fn new(key: K, nodes: Vec<Trie<K, V>>) -> Self {
+        TrieMerkleProof { key, nodes }
+    }
+
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    pub fn nodes(&self) -> &[Trie<K, V>] {
+        &self.nodes
+    }
+
+    /// Returns the value proven to be stored at the key, or `None` if the proof is of the key's
+    /// absence.
+    pub fn value(&self) -> Option<&V>
+    where
+        K: Eq,
+    {
+        match self.nodes.last() {
+            Some(Trie::Leaf { key, value }) if *key == self.key => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns the serialized nodes of the proof, as expected by [`merkle_proof::verify`].
+    pub fn to_serialized_nodes(&self) -> Result<Vec<Vec<u8>>, bytesrepr::Error>
+    where
+        K: ToBytes,
+        V: ToBytes,
+    {
+        self.nodes.iter().map(ToBytes::to_bytes).collect()
+    }
+
+    /// Checks the proof against the given root, returning the proven value.
+    pub fn verify(&self, root: &Blake2bHash) -> Result<Option<V>, merkle_proof::Error>
+    where
+        K: ToBytes,
+        V: ToBytes + FromBytes,
+    {
+        let key_bytes = self.key.to_bytes()?;
+        let nodes = self.to_serialized_nodes()?;
+        match merkle_proof::verify(&root.value(), &key_bytes, &nodes)? {
+            Some(value_bytes) => Ok(Some(bytesrepr::deserialize(value_bytes.to_vec())?)),
+            None => Ok(None),
+        }
+    }
+}
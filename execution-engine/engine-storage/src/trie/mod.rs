@@ -5,10 +5,13 @@ use types::bytesrepr::{self, FromBytes, ToBytes, U8_SERIALIZED_LENGTH};
 
 #[cfg(test)]
 pub mod gens;
+mod merkle_proof;
 
 #[cfg(test)]
 mod tests;
 
+pub use self::merkle_proof::TrieMerkleProof;
+
 pub const RADIX: usize = 256;
 
 /// A parent is represented as a pair of a child index and a node or extension.
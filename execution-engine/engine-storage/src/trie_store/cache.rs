@@ -0,0 +1,346 @@
+//! A cache of decoded [`Trie`] values, shared by the readers of a trie store.
+//!
+//! Tries are stored at the hash of their serialized form, so a cached trie can never be stale: the
+//! only way for it to disagree with the store is for it to have been deleted from the store, e.g.
+//! by pruning.  [`CachingTrieStore::delete`] evicts what it deletes, and anything which deletes
+//! tries by other means should [`TrieCache::clear`] the cache.
+//!
+//! # Usage
+//!
+//! Wrap a store with [`TrieCache::store`] to get a [`TrieStore`] which serves reads from the cache
+//! when it can, and fills it from the wrapped store when it can't:
+//!
+//! ```
+//! use casperlabs_engine_storage::store::Store;
+//! use casperlabs_engine_storage::transaction_source::{Transaction, TransactionSource};
+//! use casperlabs_engine_storage::transaction_source::in_memory::InMemoryEnvironment;
+//! use casperlabs_engine_storage::trie::Trie;
+//! use casperlabs_engine_storage::trie_store::cache::TrieCache;
+//! use casperlabs_engine_storage::trie_store::in_memory::InMemoryTrieStore;
+//! use types::bytesrepr::ToBytes;
+//! use engine_shared::newtypes::Blake2bHash;
+//!
+//! let leaf = Trie::Leaf { key: vec![0u8, 0, 0], value: b"val_1".to_vec() };
+//! let leaf_hash = Blake2bHash::new(&leaf.to_bytes().unwrap());
+//!
+//! let env = InMemoryEnvironment::new();
+//! let store = InMemoryTrieStore::new(&env, None);
+//! let cache = TrieCache::new(1024 * 1024);
+//!
+//! let mut txn = env.create_read_write_txn().unwrap();
+//! store.put(&mut txn, &leaf_hash, &leaf).unwrap();
+//! txn.commit().unwrap();
+//!
+//! // The first read misses the cache and fills it, and the second one hits it.
+//! for _ in 0..2 {
+//!     let txn = env.create_read_txn().unwrap();
+//!     let cached_store = cache.store(&store);
+//!     assert_eq!(Some(leaf.clone()), cached_store.get(&txn, &leaf_hash).unwrap());
+//!     txn.commit().unwrap();
+//! }
+//! assert_eq!((cache.hits(), cache.misses()), (1, 1));
+//! ```
+
+use std::{
+    cell::Cell,
+    collections::{BTreeMap, HashMap},
+    mem,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use parking_lot::Mutex;
+
+use engine_shared::{
+    logging::log_metric,
+    newtypes::{Blake2bHash, CorrelationId},
+};
+use types::bytesrepr::{FromBytes, ToBytes};
+
+use crate::{
+    store::Store,
+    transaction_source::{Deletable, Readable, Writable},
+    trie::{PointerBlock, Trie},
+    trie_store::TrieStore,
+    GAUGE_METRIC_KEY,
+};
+
+const TRIE_CACHE_HITS: &str = "trie_cache_hits";
+const TRIE_CACHE_MISSES: &str = "trie_cache_misses";
+const TRIE_CACHE_BYTES: &str = "trie_cache_bytes";
+
+/// Returns an estimate of the memory held by a cached trie, both on the stack and on the heap.
+///
+/// Keys and values are assumed to take about as much memory as their serialized form.
+fn byte_size<K: ToBytes, V: ToBytes>(trie: &Trie<K, V>) -> usize {
+    let heap_size = match trie {
+        Trie::Leaf { key, value } => key.serialized_length() + value.serialized_length(),
+        Trie::Node { .. } => mem::size_of::<PointerBlock>(),
+        Trie::Extension { affix, .. } => affix.len(),
+    };
+    mem::size_of::<Trie<K, V>>() + heap_size + mem::size_of::<CacheEntry<K, V>>()
+}
+
+struct CacheEntry<K, V> {
+    trie: Trie<K, V>,
+    byte_size: usize,
+    last_used: u64,
+}
+
+struct CacheEntries<K, V> {
+    entries: HashMap<Blake2bHash, CacheEntry<K, V>>,
+    /// The hashes of the entries, from the least to the most recently used.
+    by_last_used: BTreeMap<u64, Blake2bHash>,
+    clock: u64,
+    bytes: usize,
+}
+
+impl<K, V> CacheEntries<K, V> {
+    fn tick(&mut self) -> u64 {
+        self.clock += 1;
+        self.clock
+    }
+
+    fn remove(&mut self, hash: &Blake2bHash) {
+        if let Some(entry) = self.entries.remove(hash) {
+            self.by_last_used.remove(&entry.last_used);
+            self.bytes -= entry.byte_size;
+        }
+    }
+
+    fn remove_least_recently_used(&mut self) {
+        let hash = match self.by_last_used.values().next() {
+            Some(hash) => *hash,
+            None => return,
+        };
+        self.remove(&hash);
+    }
+}
+
+/// A thread-safe cache of decoded tries keyed by their hashes, which evicts the least recently used
+/// tries to stay within a maximum size in bytes.
+///
+/// A cache with a maximum size of zero keeps nothing.
+pub struct TrieCache<K, V> {
+    max_bytes: usize,
+    entries: Mutex<CacheEntries<K, V>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl<K, V> TrieCache<K, V> {
+    pub fn new(max_bytes: usize) -> Self {
+        TrieCache {
+            max_bytes,
+            entries: Mutex::new(CacheEntries {
+                entries: HashMap::new(),
+                by_last_used: BTreeMap::new(),
+                clock: 0,
+                bytes: 0,
+            }),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    pub fn max_bytes(&self) -> usize {
+        self.max_bytes
+    }
+
+    /// Returns the estimated size in bytes of the cached tries.
+    pub fn bytes(&self) -> usize {
+        self.entries.lock().bytes
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.lock().entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the number of reads served by the cache since it was created.
+    pub fn hits(&self) -> u64 {
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of reads which the cache could not serve since it was created.
+    pub fn misses(&self) -> u64 {
+        self.misses.load(Ordering::Relaxed)
+    }
+
+    pub fn remove(&self, hash: &Blake2bHash) {
+        self.entries.lock().remove(hash)
+    }
+
+    pub fn clear(&self) {
+        let mut entries = self.entries.lock();
+        entries.entries.clear();
+        entries.by_last_used.clear();
+        entries.bytes = 0;
+    }
+
+    /// Wraps `store` so that its reads go through this cache.
+    pub fn store<'a, S>(&'a self, store: &'a S) -> CachingTrieStore<'a, K, V, S> {
+        CachingTrieStore {
+            cache: self,
+            store,
+            hits: Cell::new(0),
+            misses: Cell::new(0),
+        }
+    }
+}
+
+impl<K: Clone, V: Clone> TrieCache<K, V> {
+    /// Returns a copy of the trie at `hash`, if it is cached, and marks it as the most recently
+    /// used.
+    pub fn get(&self, hash: &Blake2bHash) -> Option<Trie<K, V>> {
+        let mut entries = self.entries.lock();
+        let now = entries.tick();
+        let ret = match entries.entries.get_mut(hash) {
+            Some(entry) => {
+                let last_used = mem::replace(&mut entry.last_used, now);
+                let trie = entry.trie.clone();
+                entries.by_last_used.remove(&last_used);
+                entries.by_last_used.insert(now, *hash);
+                Some(trie)
+            }
+            None => None,
+        };
+        let counter = if ret.is_some() {
+            &self.hits
+        } else {
+            &self.misses
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        ret
+    }
+}
+
+impl<K: ToBytes, V: ToBytes> TrieCache<K, V> {
+    /// Caches `trie` at `hash` as the most recently used, evicting the least recently used tries
+    /// to make room for it.  Nothing is cached if `trie` alone is bigger than the cache.
+    pub fn insert(&self, hash: Blake2bHash, trie: Trie<K, V>) {
+        let byte_size = byte_size(&trie);
+        if byte_size > self.max_bytes {
+            return;
+        }
+        let mut entries = self.entries.lock();
+        entries.remove(&hash);
+        while entries.bytes + byte_size > self.max_bytes {
+            entries.remove_least_recently_used();
+        }
+        let last_used = entries.tick();
+        entries.entries.insert(
+            hash,
+            CacheEntry {
+                trie,
+                byte_size,
+                last_used,
+            },
+        );
+        entries.by_last_used.insert(last_used, hash);
+        entries.bytes += byte_size;
+    }
+}
+
+/// A [`TrieStore`] which serves reads from a [`TrieCache`] when it can, and from the store it wraps
+/// otherwise.
+///
+/// It counts its own hits and misses so that they can be logged per operation with
+/// [`CachingTrieStore::log_metrics`].
+pub struct CachingTrieStore<'a, K, V, S> {
+    cache: &'a TrieCache<K, V>,
+    store: &'a S,
+    hits: Cell<u32>,
+    misses: Cell<u32>,
+}
+
+impl<'a, K, V, S> CachingTrieStore<'a, K, V, S> {
+    /// Logs the hits and misses of this store, and the size of the cache.
+    pub fn log_metrics(&self, correlation_id: CorrelationId, tag: &str) {
+        log_metric(
+            correlation_id,
+            TRIE_CACHE_HITS,
+            tag,
+            GAUGE_METRIC_KEY,
+            f64::from(self.hits.get()),
+        );
+        log_metric(
+            correlation_id,
+            TRIE_CACHE_MISSES,
+            tag,
+            GAUGE_METRIC_KEY,
+            f64::from(self.misses.get()),
+        );
+        log_metric(
+            correlation_id,
+            TRIE_CACHE_BYTES,
+            tag,
+            GAUGE_METRIC_KEY,
+            self.cache.bytes() as f64,
+        );
+    }
+}
+
+impl<'a, K, V, S> Store<Blake2bHash, Trie<K, V>> for CachingTrieStore<'a, K, V, S>
+where
+    K: ToBytes + Clone,
+    V: ToBytes + Clone,
+    S: TrieStore<K, V>,
+{
+    type Error = S::Error;
+
+    type Handle = S::Handle;
+
+    fn handle(&self) -> Self::Handle {
+        self.store.handle()
+    }
+
+    fn get<T>(&self, txn: &T, key: &Blake2bHash) -> Result<Option<Trie<K, V>>, Self::Error>
+    where
+        T: Readable<Handle = Self::Handle>,
+        Blake2bHash: ToBytes,
+        Trie<K, V>: FromBytes,
+        Self::Error: From<T::Error>,
+    {
+        if let Some(trie) = self.cache.get(key) {
+            self.hits.set(self.hits.get() + 1);
+            return Ok(Some(trie));
+        }
+        self.misses.set(self.misses.get() + 1);
+        let maybe_trie = self.store.get(txn, key)?;
+        if let Some(ref trie) = maybe_trie {
+            self.cache.insert(*key, trie.clone());
+        }
+        Ok(maybe_trie)
+    }
+
+    fn put<T>(&self, txn: &mut T, key: &Blake2bHash, value: &Trie<K, V>) -> Result<(), Self::Error>
+    where
+        T: Writable<Handle = Self::Handle>,
+        Blake2bHash: ToBytes,
+        Trie<K, V>: ToBytes,
+        Self::Error: From<T::Error>,
+    {
+        self.store.put(txn, key, value)
+    }
+
+    fn delete<T>(&self, txn: &mut T, key: &Blake2bHash) -> Result<(), Self::Error>
+    where
+        T: Deletable<Handle = Self::Handle>,
+        Blake2bHash: ToBytes,
+        Self::Error: From<T::Error>,
+    {
+        self.cache.remove(key);
+        self.store.delete(txn, key)
+    }
+}
+
+impl<'a, K, V, S> TrieStore<K, V> for CachingTrieStore<'a, K, V, S>
+where
+    K: ToBytes + Clone,
+    V: ToBytes + Clone,
+    S: TrieStore<K, V>,
+{
+}
@@ -0,0 +1,185 @@
+//! Optional compression of large trie leaves, as they are stored by a trie store.
+//!
+//! A trie is always hashed over its canonical `bytesrepr` encoding, so compressing it only changes
+//! the bytes which are stored under its hash, and never the hash itself nor any state root.
+//!
+//! A compressed trie is stored in a versioned envelope:
+//!
+//! | Field                  | Size            |
+//! |------------------------|-----------------|
+//! | [`COMPRESSED_TRIE_TAG`]| 1 byte          |
+//! | format version         | 1 byte          |
+//! | algorithm              | 1 byte          |
+//! | uncompressed length    | 4 bytes (LE)    |
+//! | compressed trie        | remaining bytes |
+//!
+//! A serialized trie starts with its own tag, which is never [`COMPRESSED_TRIE_TAG`], so a store
+//! may hold compressed and uncompressed tries side by side, and [`decompress`] reads both.
+//!
+//! # Usage
+//!
+//! ```
+//! use casperlabs_engine_storage::trie::Trie;
+//! use casperlabs_engine_storage::trie_store::compression::{
+//!     self, Compression, CompressionAlgorithm,
+//! };
+//! use types::bytesrepr::{self, ToBytes};
+//!
+//! let leaf: Trie<Vec<u8>, Vec<u8>> = Trie::Leaf { key: vec![0u8; 32], value: vec![7u8; 4096] };
+//!
+//! let compression = Compression::new(CompressionAlgorithm::Zstd, 1024);
+//! let stored = compression::compress_trie(Some(&compression), &leaf).unwrap();
+//! assert!(compression::is_compressed(&stored));
+//! assert!(stored.len() < leaf.serialized_length());
+//!
+//! // The stored bytes decompress to the canonical encoding of the trie.
+//! let canonical = compression::decompress(stored).unwrap();
+//! assert_eq!(canonical, leaf.to_bytes().unwrap());
+//! assert_eq!(leaf, bytesrepr::deserialize(canonical).unwrap());
+//! ```
+
+use std::convert::TryFrom;
+
+use types::bytesrepr::{self, FromBytes, ToBytes, U32_SERIALIZED_LENGTH};
+
+use crate::trie::Trie;
+
+/// The first byte of a compressed trie.
+pub const COMPRESSED_TRIE_TAG: u8 = 0xFF;
+
+/// The version of the envelope written by this module.
+const FORMAT_VERSION: u8 = 1;
+
+const HEADER_LENGTH: usize = 3 + U32_SERIALIZED_LENGTH;
+
+/// The zstd compression level, which favors speed over ratio.
+const ZSTD_LEVEL: i32 = 3;
+
+/// The default size in bytes above which a serialized leaf is compressed.
+pub const DEFAULT_THRESHOLD: usize = 1024;
+
+/// The largest uncompressed length an envelope may declare.  Larger tries are stored uncompressed.
+pub const MAX_UNCOMPRESSED_LENGTH: u32 = 64 * 1024 * 1024;
+
+/// An algorithm used to compress tries.
+#[repr(u8)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CompressionAlgorithm {
+    Lz4 = 1,
+    Zstd = 2,
+}
+
+impl TryFrom<u8> for CompressionAlgorithm {
+    type Error = bytesrepr::Error;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            tag if tag == CompressionAlgorithm::Lz4 as u8 => Ok(CompressionAlgorithm::Lz4),
+            tag if tag == CompressionAlgorithm::Zstd as u8 => Ok(CompressionAlgorithm::Zstd),
+            _ => Err(bytesrepr::Error::Formatting),
+        }
+    }
+}
+
+/// How a trie store compresses the tries it writes.
+///
+/// Only leaves are compressed, and only if their serialized length is at least `threshold` bytes.
+/// Nodes and extensions are small, and mostly made of hashes which don't compress.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Compression {
+    pub algorithm: CompressionAlgorithm,
+    pub threshold: usize,
+}
+
+impl Compression {
+    pub fn new(algorithm: CompressionAlgorithm, threshold: usize) -> Self {
+        Compression {
+            algorithm,
+            threshold,
+        }
+    }
+
+    /// Returns `bytes` wrapped in a compressed envelope, or `None` if they don't compress to fewer
+    /// bytes.
+    fn compress(&self, bytes: &[u8]) -> Option<Vec<u8>> {
+        let uncompressed_length = u32::try_from(bytes.len()).ok()?;
+        if uncompressed_length > MAX_UNCOMPRESSED_LENGTH {
+            return None;
+        }
+        let compressed = match self.algorithm {
+            CompressionAlgorithm::Lz4 => lz4::block::compress(bytes, None, false).ok()?,
+            CompressionAlgorithm::Zstd => zstd::block::compress(bytes, ZSTD_LEVEL).ok()?,
+        };
+        if HEADER_LENGTH + compressed.len() >= bytes.len() {
+            return None;
+        }
+        let mut ret = Vec::with_capacity(HEADER_LENGTH + compressed.len());
+        ret.push(COMPRESSED_TRIE_TAG);
+        ret.push(FORMAT_VERSION);
+        ret.push(self.algorithm as u8);
+        ret.extend_from_slice(&uncompressed_length.to_le_bytes());
+        ret.extend_from_slice(&compressed);
+        Some(ret)
+    }
+}
+
+/// Returns the bytes to store for `trie`: its canonical encoding, compressed if `compression` is
+/// given and applies to it.
+pub fn compress_trie<K, V>(
+    compression: Option<&Compression>,
+    trie: &Trie<K, V>,
+) -> Result<Vec<u8>, bytesrepr::Error>
+where
+    Trie<K, V>: ToBytes,
+{
+    let bytes = trie.to_bytes()?;
+    let compressed = match (compression, trie) {
+        (Some(compression), Trie::Leaf { .. }) if bytes.len() >= compression.threshold => {
+            compression.compress(&bytes)
+        }
+        _ => None,
+    };
+    Ok(compressed.unwrap_or(bytes))
+}
+
+/// Returns whether `bytes` are a compressed trie.
+pub fn is_compressed(bytes: &[u8]) -> bool {
+    bytes.first() == Some(&COMPRESSED_TRIE_TAG)
+}
+
+/// Returns the canonical encoding of a stored trie, decompressing it if it is compressed.
+///
+/// An envelope of an unknown version or algorithm, which declares a length above
+/// [`MAX_UNCOMPRESSED_LENGTH`], or which fails to decompress to its declared length, is a
+/// formatting error.  The declared length is checked before anything is allocated for it.
+pub fn decompress(bytes: Vec<u8>) -> Result<Vec<u8>, bytesrepr::Error> {
+    if !is_compressed(&bytes) {
+        return Ok(bytes);
+    }
+    let (_tag, rem) = u8::from_bytes(&bytes)?;
+    let (version, rem) = u8::from_bytes(rem)?;
+    if version != FORMAT_VERSION {
+        return Err(bytesrepr::Error::Formatting);
+    }
+    let (algorithm, rem) = u8::from_bytes(rem)?;
+    let algorithm = CompressionAlgorithm::try_from(algorithm)?;
+    let (uncompressed_length, compressed) = u32::from_bytes(rem)?;
+    if uncompressed_length > MAX_UNCOMPRESSED_LENGTH {
+        return Err(bytesrepr::Error::Formatting);
+    }
+    let ret = match algorithm {
+        CompressionAlgorithm::Lz4 => {
+            let uncompressed_length =
+                i32::try_from(uncompressed_length).map_err(|_| bytesrepr::Error::Formatting)?;
+            lz4::block::decompress(compressed, Some(uncompressed_length))
+        }
+        CompressionAlgorithm::Zstd => {
+            zstd::block::decompress(compressed, uncompressed_length as usize)
+        }
+    }
+    .map_err(|_| bytesrepr::Error::Formatting)?;
+    if ret.len() != uncompressed_length as usize {
+        return Err(bytesrepr::Error::Formatting);
+    }
+    Ok(ret)
+}
@@ -107,21 +107,28 @@
 use lmdb::{Database, DatabaseFlags};
 
 use engine_shared::newtypes::Blake2bHash;
+use types::bytesrepr::{self, FromBytes, ToBytes};
 
 use crate::{
     error,
     store::Store,
-    transaction_source::lmdb::LmdbEnvironment,
+    transaction_source::{lmdb::LmdbEnvironment, Readable, Writable},
     trie::Trie,
-    trie_store::{self, TrieStore},
+    trie_store::{
+        self,
+        compression::{self, Compression},
+        TrieStore,
+    },
 };
 
 /// An LMDB-backed trie store.
 ///
-/// Wraps [`lmdb::Database`].
+/// Wraps [`lmdb::Database`].  Tries are read whether they are stored compressed or not, and are
+/// written compressed if the store is given a [`Compression`].
 #[derive(Debug, Clone)]
 pub struct LmdbTrieStore {
     db: Database,
+    compression: Option<Compression>,
 }
 
 impl LmdbTrieStore {
@@ -132,13 +139,31 @@ impl LmdbTrieStore {
     ) -> Result<Self, error::Error> {
         let name = Self::name(maybe_name);
         let db = env.env().create_db(Some(&name), flags)?;
-        Ok(LmdbTrieStore { db })
+        Ok(LmdbTrieStore {
+            db,
+            compression: None,
+        })
     }
 
     pub fn open(env: &LmdbEnvironment, maybe_name: Option<&str>) -> Result<Self, error::Error> {
         let name = Self::name(maybe_name);
         let db = env.env().open_db(Some(&name))?;
-        Ok(LmdbTrieStore { db })
+        Ok(LmdbTrieStore {
+            db,
+            compression: None,
+        })
+    }
+
+    /// Makes the store compress the leaves it writes from now on.
+    ///
+    /// By default tries are written uncompressed.
+    pub fn with_compression(mut self, compression: Compression) -> Self {
+        self.compression = Some(compression);
+        self
+    }
+
+    pub fn compression(&self) -> Option<Compression> {
+        self.compression
     }
 
     fn name(maybe_name: Option<&str>) -> String {
@@ -156,6 +181,34 @@ impl<K, V> Store<Blake2bHash, Trie<K, V>> for LmdbTrieStore {
     fn handle(&self) -> Self::Handle {
         self.db
     }
+
+    fn get<T>(&self, txn: &T, key: &Blake2bHash) -> Result<Option<Trie<K, V>>, Self::Error>
+    where
+        T: Readable<Handle = Self::Handle>,
+        Blake2bHash: ToBytes,
+        Trie<K, V>: FromBytes,
+        Self::Error: From<T::Error>,
+    {
+        match txn.read(self.db, &key.to_bytes()?)? {
+            None => Ok(None),
+            Some(bytes) => {
+                let trie = bytesrepr::deserialize(compression::decompress(bytes)?)?;
+                Ok(Some(trie))
+            }
+        }
+    }
+
+    fn put<T>(&self, txn: &mut T, key: &Blake2bHash, value: &Trie<K, V>) -> Result<(), Self::Error>
+    where
+        T: Writable<Handle = Self::Handle>,
+        Blake2bHash: ToBytes,
+        Trie<K, V>: ToBytes,
+        Self::Error: From<T::Error>,
+    {
+        let bytes = compression::compress_trie(self.compression.as_ref(), value)?;
+        txn.write(self.db, &key.to_bytes()?, &bytes)
+            .map_err(Into::into)
+    }
 }
 
 impl<K, V> TrieStore<K, V> for LmdbTrieStore {}
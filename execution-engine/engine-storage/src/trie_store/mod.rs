@@ -1,10 +1,14 @@
 //! A store for persisting [`Trie`](crate::trie::Trie) values at their hashes.
 //!
-//! See the [in_memory](in_memory/index.html#usage) and
-//! [lmdb](lmdb/index.html#usage) modules for usage examples.
+//! See the [in_memory](in_memory/index.html#usage), [lmdb](lmdb/index.html#usage) and
+//! [sled](sled/index.html#usage) modules for usage examples.
+pub mod cache;
+pub mod compression;
 pub mod in_memory;
 pub mod lmdb;
-pub(crate) mod operations;
+pub mod operations;
+pub mod scratch;
+pub mod sled;
 #[cfg(test)]
 mod tests;
 
@@ -0,0 +1,493 @@
+//! Writing many keys to a trie at once.
+//!
+//! [`write`](super::write) rehashes the path from the root down to the written leaf, so writing
+//! many keys one at a time rebuilds the upper levels of the trie once per key.  Here the keys are
+//! sorted by their serialized form and every affected subtrie is rebuilt exactly once.  The
+//! resulting trie is the one which writing and deleting the keys one at a time would produce.
+
+use std::{marker::PhantomData, mem, time::Instant};
+
+use engine_shared::{
+    logging::{log_duration, log_metric},
+    newtypes::{Blake2bHash, CorrelationId},
+};
+use types::bytesrepr::{FromBytes, ToBytes};
+
+use super::WriteResult;
+use crate::{
+    transaction_source::{Readable, Writable},
+    trie::{Pointer, PointerBlock, Trie},
+    trie_store::TrieStore,
+    GAUGE_METRIC_KEY,
+};
+
+const TRIE_STORE_WRITE_BATCH_DURATION: &str = "trie_store_write_batch_duration";
+const TRIE_STORE_WRITE_BATCH_GETS: &str = "trie_store_write_batch_gets";
+const TRIE_STORE_WRITE_BATCH_PUTS: &str = "trie_store_write_batch_puts";
+const WRITE_BATCH: &str = "write_batch";
+const GET: &str = "get";
+const PUT: &str = "put";
+
+/// A value to be written under a key, or `None` if the key is to be deleted.
+struct Update<K, V> {
+    path: Vec<u8>,
+    key: K,
+    value: Option<V>,
+}
+
+/// The contents of a non-empty slot of a node's pointer block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Slot {
+    Leaf(Blake2bHash),
+    /// A node, reached through an extension if `affix` is non-empty.  The affix starts at the
+    /// depth of the slot.
+    Branch {
+        affix: Vec<u8>,
+        node: Blake2bHash,
+    },
+}
+
+impl Slot {
+    /// Returns the slot as seen from `prefix.len()` levels higher up in the trie.
+    fn prefixed(self, prefix: &[u8]) -> Slot {
+        match self {
+            leaf @ Slot::Leaf(_) => leaf,
+            Slot::Branch { affix, node } => Slot::Branch {
+                affix: prefix.iter().chain(affix.iter()).cloned().collect(),
+                node,
+            },
+        }
+    }
+}
+
+/// The result of applying updates to a slot.  `Changed(None)` means the slot is now empty.
+enum SlotUpdate {
+    Unchanged,
+    Changed(Option<Slot>),
+}
+
+/// A leaf of a subtrie being rebuilt.
+enum Leaf<K, V> {
+    Stored(Blake2bHash),
+    New(K, V),
+}
+
+/// Returns the updates in consecutive runs sharing the same byte at `depth` of their paths.
+fn groups_at<'u, K, V>(
+    updates: &'u [Update<K, V>],
+    depth: usize,
+) -> impl Iterator<Item = (u8, &'u [Update<K, V>])> {
+    let mut rest = updates;
+    std::iter::from_fn(move || {
+        let index = rest.first()?.path[depth];
+        let length = rest
+            .iter()
+            .take_while(|update| update.path[depth] == index)
+            .count();
+        let (group, tail) = rest.split_at(length);
+        rest = tail;
+        Some((index, group))
+    })
+}
+
+struct Builder<'a, 'b, K, V, T, S> {
+    txn: &'a mut T,
+    store: &'b S,
+    get_counter: i32,
+    put_counter: i32,
+    _marker: PhantomData<(K, V)>,
+}
+
+impl<'a, 'b, K, V, T, S> Builder<'a, 'b, K, V, T, S>
+where
+    K: ToBytes + FromBytes + Clone,
+    V: ToBytes + FromBytes + Clone + Eq,
+    T: Readable<Handle = S::Handle> + Writable<Handle = S::Handle>,
+    S: TrieStore<K, V>,
+    S::Error: From<T::Error>,
+{
+    fn get(&mut self, hash: &Blake2bHash) -> Result<Trie<K, V>, S::Error> {
+        self.get_counter += 1;
+        match self.store.get(self.txn, hash)? {
+            Some(trie) => Ok(trie),
+            None => panic!("No trie value at key: {:?}", hash),
+        }
+    }
+
+    fn put(&mut self, trie: &Trie<K, V>) -> Result<Blake2bHash, S::Error> {
+        let hash = Blake2bHash::new(&trie.to_bytes()?);
+        self.put_counter += 1;
+        self.store.put(self.txn, &hash, trie)?;
+        Ok(hash)
+    }
+
+    fn load_slot(&mut self, pointer: Pointer) -> Result<Slot, S::Error> {
+        match pointer {
+            Pointer::LeafPointer(hash) => Ok(Slot::Leaf(hash)),
+            Pointer::NodePointer(hash) => match self.get(&hash)? {
+                Trie::Node { .. } => Ok(Slot::Branch {
+                    affix: Vec::new(),
+                    node: hash,
+                }),
+                Trie::Extension { affix, pointer } => Ok(Slot::Branch {
+                    affix,
+                    node: *pointer.hash(),
+                }),
+                Trie::Leaf { .. } => panic!("a node pointer should not point to a leaf"),
+            },
+        }
+    }
+
+    fn store_slot(&mut self, slot: Slot) -> Result<Pointer, S::Error> {
+        match slot {
+            Slot::Leaf(hash) => Ok(Pointer::LeafPointer(hash)),
+            Slot::Branch { affix, node } if affix.is_empty() => Ok(Pointer::NodePointer(node)),
+            Slot::Branch { affix, node } => {
+                let extension = Trie::extension(affix, Pointer::NodePointer(node));
+                Ok(Pointer::NodePointer(self.put(&extension)?))
+            }
+        }
+    }
+
+    /// Builds a subtrie from scratch out of `leaves`, which are sorted by path.
+    fn build_leaves(
+        &mut self,
+        depth: usize,
+        mut leaves: Vec<(Vec<u8>, Leaf<K, V>)>,
+    ) -> Result<Option<Slot>, S::Error> {
+        if leaves.len() <= 1 {
+            return match leaves.pop() {
+                None => Ok(None),
+                Some((_, Leaf::Stored(hash))) => Ok(Some(Slot::Leaf(hash))),
+                Some((_, Leaf::New(key, value))) => {
+                    Ok(Some(Slot::Leaf(self.put(&Trie::Leaf { key, value })?)))
+                }
+            };
+        }
+
+        // As the leaves are sorted, the first and the last have the shortest common prefix
+        let node_depth = {
+            let first = &leaves[0].0;
+            let last = &leaves[leaves.len() - 1].0;
+            depth
+                + first[depth..]
+                    .iter()
+                    .zip(&last[depth..])
+                    .take_while(|(l, r)| l == r)
+                    .count()
+        };
+        let affix = leaves[0].0[depth..node_depth].to_vec();
+
+        let mut pointer_block = Box::new(PointerBlock::default());
+        while let Some((path, _)) = leaves.first() {
+            let index = path[node_depth];
+            let length = leaves
+                .iter()
+                .take_while(|(path, _)| path[node_depth] == index)
+                .count();
+            let rest = leaves.split_off(length);
+            let group = mem::replace(&mut leaves, rest);
+            let slot = self
+                .build_leaves(node_depth + 1, group)?
+                .expect("a group of leaves should not be empty");
+            pointer_block[usize::from(index)] = Some(self.store_slot(slot)?);
+        }
+        let node = self.put(&Trie::Node { pointer_block })?;
+        Ok(Some(Slot::Branch { affix, node }))
+    }
+
+    /// Completes a node from its unchanged children, left in `pointer_block`, and its new
+    /// children.  A node other than the root which is left with a single child is collapsed into
+    /// the slot holding it.
+    fn finish_node(
+        &mut self,
+        mut pointer_block: Box<PointerBlock>,
+        mut new_children: Vec<(usize, Slot)>,
+        is_root: bool,
+    ) -> Result<Option<Slot>, S::Error> {
+        let child_count =
+            pointer_block[..].iter().filter(|p| p.is_some()).count() + new_children.len();
+        if !is_root && child_count <= 1 {
+            let (index, slot) = match new_children.pop() {
+                Some(child) => child,
+                None => match pointer_block[..]
+                    .iter()
+                    .enumerate()
+                    .find_map(|(index, maybe_pointer)| maybe_pointer.map(|p| (index, p)))
+                {
+                    Some((index, pointer)) => (index, self.load_slot(pointer)?),
+                    None => return Ok(None),
+                },
+            };
+            return Ok(Some(slot.prefixed(&[index as u8])));
+        }
+
+        for (index, slot) in new_children {
+            pointer_block[index] = Some(self.store_slot(slot)?);
+        }
+        let node = self.put(&Trie::Node { pointer_block })?;
+        Ok(Some(Slot::Branch {
+            affix: Vec::new(),
+            node,
+        }))
+    }
+
+    /// Applies `updates` to the children of a node at `depth`.
+    fn update_node(
+        &mut self,
+        depth: usize,
+        mut pointer_block: Box<PointerBlock>,
+        updates: &[Update<K, V>],
+        is_root: bool,
+    ) -> Result<SlotUpdate, S::Error> {
+        let mut new_children = Vec::new();
+        let mut changed = false;
+        for (index, group) in groups_at(updates, depth) {
+            let index = usize::from(index);
+            let existing = match pointer_block[index] {
+                Some(pointer) => Some(self.get(pointer.hash())?),
+                None => None,
+            };
+            if let SlotUpdate::Changed(maybe_slot) = self.update_slot(depth + 1, existing, group)? {
+                changed = true;
+                pointer_block[index] = None;
+                if let Some(slot) = maybe_slot {
+                    new_children.push((index, slot));
+                }
+            }
+        }
+        if !changed {
+            return Ok(SlotUpdate::Unchanged);
+        }
+        Ok(SlotUpdate::Changed(self.finish_node(
+            pointer_block,
+            new_children,
+            is_root,
+        )?))
+    }
+
+    /// Applies `updates` to the slot at `depth` holding `existing`.
+    fn update_slot(
+        &mut self,
+        depth: usize,
+        existing: Option<Trie<K, V>>,
+        updates: &[Update<K, V>],
+    ) -> Result<SlotUpdate, S::Error> {
+        match existing {
+            None => {
+                let leaves: Vec<(Vec<u8>, Leaf<K, V>)> = updates
+                    .iter()
+                    .filter_map(|update| {
+                        update.value.as_ref().map(|value| {
+                            let leaf = Leaf::New(update.key.clone(), value.clone());
+                            (update.path.clone(), leaf)
+                        })
+                    })
+                    .collect();
+                if leaves.is_empty() {
+                    return Ok(SlotUpdate::Unchanged);
+                }
+                Ok(SlotUpdate::Changed(self.build_leaves(depth, leaves)?))
+            }
+            Some(existing_leaf @ Trie::Leaf { .. }) => {
+                let existing_hash = Blake2bHash::new(&existing_leaf.to_bytes()?);
+                let (existing_key, existing_value) = match existing_leaf {
+                    Trie::Leaf { key, value } => (key, value),
+                    _ => unreachable!(),
+                };
+                let existing_path = existing_key.to_bytes()?;
+
+                let mut keep_existing = true;
+                let mut leaves = Vec::new();
+                for update in updates {
+                    let is_existing_key = update.path == existing_path;
+                    match &update.value {
+                        Some(value) if is_existing_key && *value == existing_value => (),
+                        Some(value) => {
+                            keep_existing &= !is_existing_key;
+                            let leaf = Leaf::New(update.key.clone(), value.clone());
+                            leaves.push((update.path.clone(), leaf));
+                        }
+                        None => keep_existing &= !is_existing_key,
+                    }
+                }
+                if keep_existing && leaves.is_empty() {
+                    return Ok(SlotUpdate::Unchanged);
+                }
+                if keep_existing {
+                    leaves.push((existing_path, Leaf::Stored(existing_hash)));
+                    leaves.sort_by(|(l, _), (r, _)| l.cmp(r));
+                }
+                Ok(SlotUpdate::Changed(self.build_leaves(depth, leaves)?))
+            }
+            Some(Trie::Node { pointer_block }) => {
+                self.update_node(depth, pointer_block, updates, false)
+            }
+            Some(Trie::Extension { affix, pointer }) => {
+                self.update_extension(depth, affix, pointer, updates)
+            }
+        }
+    }
+
+    /// Applies `updates` to the slot at `depth` holding an extension.
+    fn update_extension(
+        &mut self,
+        depth: usize,
+        affix: Vec<u8>,
+        pointer: Pointer,
+        updates: &[Update<K, V>],
+    ) -> Result<SlotUpdate, S::Error> {
+        // The length of the part of the affix which all the updates share
+        let shared_length = updates
+            .iter()
+            .map(|update| {
+                update.path[depth..]
+                    .iter()
+                    .zip(&affix)
+                    .take_while(|(l, r)| l == r)
+                    .count()
+            })
+            .min()
+            .unwrap_or(affix.len());
+
+        if shared_length == affix.len() {
+            let pointer_block = match self.get(pointer.hash())? {
+                Trie::Node { pointer_block } => pointer_block,
+                _ => panic!("an extension should point to a node"),
+            };
+            let node_depth = depth + affix.len();
+            return match self.update_node(node_depth, pointer_block, updates, false)? {
+                SlotUpdate::Changed(maybe_slot) => Ok(SlotUpdate::Changed(
+                    maybe_slot.map(|slot| slot.prefixed(&affix)),
+                )),
+                unchanged => Ok(unchanged),
+            };
+        }
+
+        // Some updates diverge from the affix, so a node is needed where they do.  The rest of
+        // the extension becomes one of its children.
+        let node_depth = depth + shared_length;
+        let extension_index = affix[shared_length];
+        let rest = affix[shared_length + 1..].to_vec();
+        let rest_slot = Slot::Branch {
+            affix: rest.clone(),
+            node: *pointer.hash(),
+        };
+
+        let mut new_children = Vec::new();
+        let mut changed = false;
+        let mut extension_visited = false;
+        for (index, group) in groups_at(updates, node_depth) {
+            let existing = if index == extension_index {
+                extension_visited = true;
+                Some(if rest.is_empty() {
+                    self.get(pointer.hash())?
+                } else {
+                    Trie::extension(rest.clone(), pointer)
+                })
+            } else {
+                None
+            };
+            match self.update_slot(node_depth + 1, existing, group)? {
+                SlotUpdate::Unchanged if index == extension_index => {
+                    new_children.push((usize::from(index), rest_slot.clone()))
+                }
+                SlotUpdate::Unchanged => (),
+                SlotUpdate::Changed(maybe_slot) => {
+                    changed = true;
+                    if let Some(slot) = maybe_slot {
+                        new_children.push((usize::from(index), slot));
+                    }
+                }
+            }
+        }
+        if !changed {
+            return Ok(SlotUpdate::Unchanged);
+        }
+        if !extension_visited {
+            new_children.push((usize::from(extension_index), rest_slot));
+        }
+        let pointer_block = Box::new(PointerBlock::default());
+        let maybe_slot = self.finish_node(pointer_block, new_children, false)?;
+        Ok(SlotUpdate::Changed(
+            maybe_slot.map(|slot| slot.prefixed(&affix[..shared_length])),
+        ))
+    }
+}
+
+/// Applies `updates` to the trie at `root`, writing the value of each key, or deleting the key if
+/// its value is `None`.  If a key appears more than once, its last update takes precedence.
+///
+/// The resulting root hash is the same as the one of applying the updates one at a time with
+/// [`write`](super::write) and [`delete`](super::delete).
+pub fn write_batch<K, V, T, S, E>(
+    correlation_id: CorrelationId,
+    txn: &mut T,
+    store: &S,
+    root: &Blake2bHash,
+    updates: Vec<(K, Option<V>)>,
+) -> Result<WriteResult, E>
+where
+    K: ToBytes + FromBytes + Clone,
+    V: ToBytes + FromBytes + Clone + Eq,
+    T: Readable<Handle = S::Handle> + Writable<Handle = S::Handle>,
+    S: TrieStore<K, V>,
+    S::Error: From<T::Error>,
+    E: From<S::Error> + From<types::bytesrepr::Error>,
+{
+    let start = Instant::now();
+
+    let pointer_block = match store.get(txn, root)? {
+        Some(Trie::Node { pointer_block }) => pointer_block,
+        Some(_) => panic!("the root of a trie should be a node"),
+        None => return Ok(WriteResult::RootNotFound),
+    };
+
+    let mut updates = updates
+        .into_iter()
+        .rev()
+        .map(|(key, value)| {
+            let path = key.to_bytes()?;
+            Ok(Update { path, key, value })
+        })
+        .collect::<Result<Vec<_>, types::bytesrepr::Error>>()?;
+    // The sort is stable, so the last update of each key comes first and is the one kept
+    updates.sort_by(|l, r| l.path.cmp(&r.path));
+    updates.dedup_by(|duplicate, kept| duplicate.path == kept.path);
+
+    let mut builder = Builder {
+        txn,
+        store,
+        get_counter: 0,
+        put_counter: 0,
+        _marker: PhantomData,
+    };
+    let result = match builder.update_node(0, pointer_block, &updates, true)? {
+        SlotUpdate::Unchanged => WriteResult::AlreadyExists,
+        SlotUpdate::Changed(Some(Slot::Branch { node, .. })) => WriteResult::Written(node),
+        SlotUpdate::Changed(_) => unreachable!("the root node is never collapsed"),
+    };
+
+    log_metric(
+        correlation_id,
+        TRIE_STORE_WRITE_BATCH_GETS,
+        GET,
+        GAUGE_METRIC_KEY,
+        f64::from(builder.get_counter),
+    );
+    log_metric(
+        correlation_id,
+        TRIE_STORE_WRITE_BATCH_PUTS,
+        PUT,
+        GAUGE_METRIC_KEY,
+        f64::from(builder.put_counter),
+    );
+    log_duration(
+        correlation_id,
+        TRIE_STORE_WRITE_BATCH_DURATION,
+        WRITE_BATCH,
+        start.elapsed(),
+    );
+
+    Ok(result)
+}
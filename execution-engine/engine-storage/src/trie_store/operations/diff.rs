@@ -0,0 +1,270 @@
+//! Comparison of the contents of two tries.
+//!
+//! The tries are walked in lockstep from their roots.  Subtries which are referred to by the same
+//! hash on both sides are identical and are skipped, so the cost of a diff depends on the size of
+//! the difference rather than on the size of the tries.
+
+use std::time::Instant;
+
+use engine_shared::{
+    logging::{log_duration, log_metric},
+    newtypes::{Blake2bHash, CorrelationId},
+};
+use types::bytesrepr::{FromBytes, ToBytes};
+
+use crate::{
+    transaction_source::Readable,
+    trie::{Trie, RADIX},
+    trie_store::TrieStore,
+    GAUGE_METRIC_KEY,
+};
+
+const TRIE_STORE_DIFF_DURATION: &str = "trie_store_diff_duration";
+const TRIE_STORE_DIFF_GETS: &str = "trie_store_diff_gets";
+const DIFF: &str = "diff";
+const GET: &str = "get";
+
+/// A key whose value differs between two tries, along with its value in the left trie and its
+/// value in the right trie.  `None` means the key is absent from that trie.
+pub type KeyDiff<K, V> = (K, Option<V>, Option<V>);
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum DiffResult<K, V> {
+    RootNotFound(Blake2bHash),
+    /// The keys whose values differ, in ascending order of their serialized form.
+    Success(Vec<KeyDiff<K, V>>),
+}
+
+/// A subtrie which is either still in the store, or is the remainder of an extension which has
+/// been partially walked.
+enum Subtrie<K, V> {
+    Stored(Blake2bHash),
+    Loaded(Trie<K, V>),
+}
+
+/// Subtries found at the same path in the left and in the right trie.
+type SubtriePair<K, V> = (Option<Subtrie<K, V>>, Option<Subtrie<K, V>>);
+
+struct Walker<'a, 'b, T, S> {
+    txn: &'a T,
+    store: &'b S,
+    get_counter: i32,
+}
+
+impl<'a, 'b, T, S> Walker<'a, 'b, T, S> {
+    fn load<K, V>(&mut self, subtrie: Subtrie<K, V>) -> Result<Trie<K, V>, S::Error>
+    where
+        K: FromBytes,
+        V: FromBytes,
+        T: Readable<Handle = S::Handle>,
+        S: TrieStore<K, V>,
+        S::Error: From<T::Error>,
+    {
+        match subtrie {
+            Subtrie::Stored(hash) => {
+                self.get_counter += 1;
+                match self.store.get(self.txn, &hash)? {
+                    Some(trie) => Ok(trie),
+                    None => panic!("No trie value at key: {:?}", hash),
+                }
+            }
+            Subtrie::Loaded(trie) => Ok(trie),
+        }
+    }
+
+    /// Returns every leaf of a subtrie, in ascending order of their serialized keys.
+    fn leaves<K, V>(&mut self, subtrie: Subtrie<K, V>) -> Result<Vec<(K, V)>, S::Error>
+    where
+        K: FromBytes,
+        V: FromBytes,
+        T: Readable<Handle = S::Handle>,
+        S: TrieStore<K, V>,
+        S::Error: From<T::Error>,
+    {
+        let mut ret = Vec::new();
+        let mut stack = vec![subtrie];
+        while let Some(subtrie) = stack.pop() {
+            match self.load(subtrie)? {
+                Trie::Leaf { key, value } => ret.push((key, value)),
+                Trie::Node { pointer_block } => stack.extend(
+                    pointer_block[..]
+                        .iter()
+                        .rev()
+                        .flatten()
+                        .map(|pointer| Subtrie::Stored(*pointer.hash())),
+                ),
+                Trie::Extension { pointer, .. } => stack.push(Subtrie::Stored(*pointer.hash())),
+            }
+        }
+        Ok(ret)
+    }
+}
+
+/// Returns the children of a node or an extension, indexed by the next byte of their path.
+///
+/// An extension is seen as a node with a single child, which is either what the extension points
+/// to, or the extension minus the first byte of its affix.
+fn children<K, V>(trie: Trie<K, V>) -> Vec<Option<Subtrie<K, V>>> {
+    let mut ret: Vec<Option<Subtrie<K, V>>> = (0..RADIX).map(|_| None).collect();
+    match trie {
+        Trie::Leaf { .. } => panic!("a leaf has no children"),
+        Trie::Node { pointer_block } => {
+            for (index, maybe_pointer) in pointer_block[..].iter().enumerate() {
+                ret[index] = maybe_pointer
+                    .as_ref()
+                    .map(|pointer| Subtrie::Stored(*pointer.hash()));
+            }
+        }
+        Trie::Extension { affix, pointer } => {
+            let (first, rest) = affix
+                .split_first()
+                .expect("an extension should have a non-empty affix");
+            ret[usize::from(*first)] = Some(if rest.is_empty() {
+                Subtrie::Stored(*pointer.hash())
+            } else {
+                Subtrie::Loaded(Trie::Extension {
+                    affix: rest.to_vec(),
+                    pointer,
+                })
+            });
+        }
+    }
+    ret
+}
+
+/// Compares a leaf with the leaves found on the other side, which are in ascending order of
+/// their serialized keys.
+fn diff_leaf<K, V>(
+    key: K,
+    value: V,
+    other_leaves: Vec<(K, V)>,
+    leaf_is_left: bool,
+) -> Result<Vec<KeyDiff<K, V>>, types::bytesrepr::Error>
+where
+    K: ToBytes + Eq,
+    V: Eq,
+{
+    let orient = |key, value, other_value| {
+        if leaf_is_left {
+            (key, value, other_value)
+        } else {
+            (key, other_value, value)
+        }
+    };
+    let key_bytes = key.to_bytes()?;
+    let mut maybe_leaf = Some((key, value));
+    let mut ret = Vec::with_capacity(other_leaves.len() + 1);
+    for (other_key, other_value) in other_leaves {
+        if let Some((key, _)) = &maybe_leaf {
+            if *key == other_key {
+                let (key, value) = maybe_leaf.take().expect("leaf should be pending");
+                if value != other_value {
+                    ret.push(orient(key, Some(value), Some(other_value)));
+                }
+                continue;
+            }
+            if key_bytes < other_key.to_bytes()? {
+                let (key, value) = maybe_leaf.take().expect("leaf should be pending");
+                ret.push(orient(key, Some(value), None));
+            }
+        }
+        ret.push(orient(other_key, None, Some(other_value)));
+    }
+    if let Some((key, value)) = maybe_leaf {
+        ret.push(orient(key, Some(value), None));
+    }
+    Ok(ret)
+}
+
+/// Returns the keys whose values differ between the tries at `left_root` and `right_root`.
+pub fn diff<K, V, T, S, E>(
+    correlation_id: CorrelationId,
+    txn: &T,
+    store: &S,
+    left_root: &Blake2bHash,
+    right_root: &Blake2bHash,
+) -> Result<DiffResult<K, V>, E>
+where
+    K: ToBytes + FromBytes + Eq,
+    V: ToBytes + FromBytes + Eq,
+    T: Readable<Handle = S::Handle>,
+    S: TrieStore<K, V>,
+    S::Error: From<T::Error>,
+    E: From<S::Error> + From<types::bytesrepr::Error>,
+{
+    for root in &[left_root, right_root] {
+        if store.get(txn, root)?.is_none() {
+            return Ok(DiffResult::RootNotFound(**root));
+        }
+    }
+
+    let start = Instant::now();
+    let mut walker = Walker {
+        txn,
+        store,
+        get_counter: 0,
+    };
+    let mut ret = Vec::new();
+
+    // Pairs of subtries found at the same path on both sides, popped in ascending path order
+    let mut stack: Vec<SubtriePair<K, V>> = vec![(
+        Some(Subtrie::Stored(*left_root)),
+        Some(Subtrie::Stored(*right_root)),
+    )];
+    while let Some(pair) = stack.pop() {
+        let (left, right) = match pair {
+            (Some(Subtrie::Stored(left_hash)), Some(Subtrie::Stored(right_hash)))
+                if left_hash == right_hash =>
+            {
+                continue
+            }
+            (Some(left), Some(right)) => (walker.load(left)?, walker.load(right)?),
+            (Some(left), None) => {
+                let removed = walker.leaves(left)?;
+                ret.extend(removed.into_iter().map(|(k, v)| (k, Some(v), None)));
+                continue;
+            }
+            (None, Some(right)) => {
+                let added = walker.leaves(right)?;
+                ret.extend(added.into_iter().map(|(k, v)| (k, None, Some(v))));
+                continue;
+            }
+            (None, None) => continue,
+        };
+
+        match (left, right) {
+            (Trie::Leaf { key, value }, right) => {
+                let other_leaves = walker.leaves(Subtrie::Loaded(right))?;
+                ret.extend(diff_leaf(key, value, other_leaves, true)?);
+            }
+            (left, Trie::Leaf { key, value }) => {
+                let other_leaves = walker.leaves(Subtrie::Loaded(left))?;
+                ret.extend(diff_leaf(key, value, other_leaves, false)?);
+            }
+            (left, right) => {
+                let pairs = children(left).into_iter().zip(children(right));
+                stack.extend(
+                    pairs
+                        .rev()
+                        .filter(|(left, right)| left.is_some() || right.is_some()),
+                );
+            }
+        }
+    }
+
+    log_metric(
+        correlation_id,
+        TRIE_STORE_DIFF_GETS,
+        GET,
+        GAUGE_METRIC_KEY,
+        f64::from(walker.get_counter),
+    );
+    log_duration(
+        correlation_id,
+        TRIE_STORE_DIFF_DURATION,
+        DIFF,
+        start.elapsed(),
+    );
+
+    Ok(DiffResult::Success(ret))
+}
@@ -1,5 +1,11 @@
+pub mod batch;
+pub mod diff;
+pub mod prune;
+pub mod recompress;
+pub mod stats;
 #[cfg(test)]
 mod tests;
+pub mod verify;
 
 use std::{cmp, collections::VecDeque, mem, time::Instant};
 
@@ -11,21 +17,27 @@ use types::bytesrepr::{self, FromBytes, ToBytes};
 
 use crate::{
     transaction_source::{Readable, Writable},
-    trie::{self, Parents, Pointer, Trie, RADIX},
+    trie::{self, Parents, Pointer, Trie, TrieMerkleProof, RADIX},
     trie_store::TrieStore,
     GAUGE_METRIC_KEY,
 };
 
 const TRIE_STORE_READ_DURATION: &str = "trie_store_read_duration";
 const TRIE_STORE_READ_GETS: &str = "trie_store_read_gets";
+const TRIE_STORE_READ_WITH_PROOF_DURATION: &str = "trie_store_read_with_proof_duration";
+const TRIE_STORE_READ_WITH_PROOF_GETS: &str = "trie_store_read_with_proof_gets";
 const TRIE_STORE_SCAN_DURATION: &str = "trie_store_scan_duration";
 const TRIE_STORE_SCAN_GETS: &str = "trie_store_scan_gets";
 const TRIE_STORE_WRITE_DURATION: &str = "trie_store_write_duration";
 const TRIE_STORE_WRITE_PUTS: &str = "trie_store_write_puts";
+const TRIE_STORE_DELETE_DURATION: &str = "trie_store_delete_duration";
+const TRIE_STORE_DELETE_PUTS: &str = "trie_store_delete_puts";
 const READ: &str = "read";
+const READ_WITH_PROOF: &str = "read_with_proof";
 const GET: &str = "get";
 const SCAN: &str = "scan";
 const WRITE: &str = "write";
+const DELETE: &str = "delete";
 const PUT: &str = "put";
 
 #[derive(Debug, PartialEq, Eq)]
@@ -199,6 +211,89 @@ where
     }
 }
 
+/// Returns a proof of the value at the given key at a given root in a given store, or of the
+/// key's absence, or `None` if the root is not found.
+pub fn read_with_proof<K, V, T, S, E>(
+    correlation_id: CorrelationId,
+    txn: &T,
+    store: &S,
+    root: &Blake2bHash,
+    key: &K,
+) -> Result<Option<TrieMerkleProof<K, V>>, E>
+where
+    K: ToBytes + FromBytes + Eq + Clone + std::fmt::Debug,
+    V: ToBytes + FromBytes,
+    T: Readable<Handle = S::Handle>,
+    S: TrieStore<K, V>,
+    S::Error: From<T::Error>,
+    E: From<S::Error> + From<types::bytesrepr::Error>,
+{
+    let path: Vec<u8> = key.to_bytes()?;
+
+    let mut depth: usize = 0;
+    let mut current: Trie<K, V> = match store.get(txn, root)? {
+        Some(root) => root,
+        None => return Ok(None),
+    };
+    let mut nodes: Vec<Trie<K, V>> = Vec::new();
+
+    let start = Instant::now();
+    let mut get_counter: i32 = 0;
+
+    loop {
+        let maybe_next_hash: Option<Blake2bHash> = match &current {
+            Trie::Leaf { .. } => None,
+            Trie::Node { pointer_block } => {
+                let index: usize = {
+                    assert!(depth < path.len(), "depth must be < {}", path.len());
+                    path[depth].into()
+                };
+                depth += 1;
+                pointer_block[index].map(|pointer| *pointer.hash())
+            }
+            Trie::Extension { affix, pointer } => {
+                let end = depth + affix.len();
+                if path.get(depth..end) == Some(affix.as_slice()) {
+                    depth = end;
+                    Some(*pointer.hash())
+                } else {
+                    None
+                }
+            }
+        };
+        nodes.push(current);
+
+        let next_hash = match maybe_next_hash {
+            Some(next_hash) => next_hash,
+            None => break,
+        };
+        get_counter += 1;
+        current = match store.get(txn, &next_hash)? {
+            Some(next) => next,
+            None => panic!(
+                "No trie value at key: {:?} (reading from key: {:?})",
+                next_hash, key
+            ),
+        };
+    }
+
+    log_metric(
+        correlation_id,
+        TRIE_STORE_READ_WITH_PROOF_GETS,
+        GET,
+        GAUGE_METRIC_KEY,
+        f64::from(get_counter),
+    );
+    log_duration(
+        correlation_id,
+        TRIE_STORE_READ_WITH_PROOF_DURATION,
+        READ_WITH_PROOF,
+        start.elapsed(),
+    );
+
+    Ok(Some(TrieMerkleProof::new(key.clone(), nodes)))
+}
+
 struct TrieScan<K, V> {
     tip: Trie<K, V>,
     parents: Parents<K, V>,
@@ -731,7 +826,148 @@ where
     }
 }
 
-enum KeysIteratorState<K, V, S: TrieStore<K, V>> {
+#[derive(Debug, PartialEq, Eq)]
+pub enum DeleteResult {
+    Deleted(Blake2bHash),
+    DoesNotExist,
+    RootNotFound,
+}
+
+/// Takes the parents of a leaf which is being deleted, the last of which is the node holding the
+/// leaf.  Removes the leaf from that node, and returns the trie element which should take the
+/// place of the node, along with the remaining parents.
+///
+/// A node left with a single child is collapsed, so that the result is the same as if the deleted
+/// leaf had never been written: its remaining leaf moves up to the node's parent, or the node is
+/// merged with the extensions above and below it.  The root node is never collapsed.
+fn remove_leaf_from_parents<K, V, T, S, E>(
+    txn: &T,
+    store: &S,
+    mut parents: Parents<K, V>,
+) -> Result<(Trie<K, V>, Parents<K, V>), E>
+where
+    K: ToBytes + FromBytes,
+    V: ToBytes + FromBytes,
+    T: Readable<Handle = S::Handle>,
+    S: TrieStore<K, V>,
+    S::Error: From<T::Error>,
+    E: From<S::Error>,
+{
+    let (leaf_index, parent) = parents.pop().expect("parents should not be empty");
+    let mut pointer_block = match parent {
+        Trie::Node { pointer_block } => pointer_block,
+        _ => panic!("A leaf should have a node for its parent"),
+    };
+    pointer_block[<usize>::from(leaf_index)] = None;
+
+    let mut remaining = pointer_block[..]
+        .iter()
+        .enumerate()
+        .filter_map(|(index, maybe_pointer)| maybe_pointer.map(|pointer| (index, pointer)));
+    let (child_index, child_pointer) = match (remaining.next(), remaining.next()) {
+        (Some(only_child), None) if !parents.is_empty() => only_child,
+        _ => return Ok((Trie::Node { pointer_block }, parents)),
+    };
+
+    // The node is collapsed, and so is an extension leading to it
+    let mut affix = match parents.last() {
+        Some((_, Trie::Extension { .. })) => match parents.pop() {
+            Some((_, Trie::Extension { affix, .. })) => affix,
+            _ => unreachable!(),
+        },
+        _ => Vec::new(),
+    };
+    let child = match store.get(txn, child_pointer.hash())? {
+        Some(child) => child,
+        None => panic!("No trie value at key: {:?}", child_pointer.hash()),
+    };
+    let collapsed = match child {
+        leaf @ Trie::Leaf { .. } => leaf,
+        Trie::Node { .. } => {
+            affix.push(child_index as u8);
+            Trie::extension(affix, child_pointer)
+        }
+        Trie::Extension {
+            affix: child_affix,
+            pointer,
+        } => {
+            affix.push(child_index as u8);
+            affix.extend(child_affix);
+            Trie::extension(affix, pointer)
+        }
+    };
+    Ok((collapsed, parents))
+}
+
+/// Removes the leaf holding `key` from the trie at `root`.
+///
+/// The resulting root hash is the one of a trie into which `key` was never written.
+pub fn delete<K, V, T, S, E>(
+    correlation_id: CorrelationId,
+    txn: &mut T,
+    store: &S,
+    root: &Blake2bHash,
+    key: &K,
+) -> Result<DeleteResult, E>
+where
+    K: ToBytes + FromBytes + Clone + Eq,
+    V: ToBytes + FromBytes + Clone,
+    T: Readable<Handle = S::Handle> + Writable<Handle = S::Handle>,
+    S: TrieStore<K, V>,
+    S::Error: From<T::Error>,
+    E: From<S::Error> + From<types::bytesrepr::Error>,
+{
+    let start = Instant::now();
+    let mut put_counter: i32 = 0;
+
+    let current_root = match store.get(txn, root)? {
+        Some(current_root) => current_root,
+        None => return Ok(DeleteResult::RootNotFound),
+    };
+    let path: Vec<u8> = key.to_bytes()?;
+    let TrieScan { tip, parents } =
+        scan::<K, V, T, S, E>(correlation_id, txn, store, &path, &current_root)?;
+    match tip {
+        Trie::Leaf {
+            key: ref leaf_key, ..
+        } if key == leaf_key => (),
+        _ => {
+            log_duration(
+                correlation_id,
+                TRIE_STORE_DELETE_DURATION,
+                DELETE,
+                start.elapsed(),
+            );
+            return Ok(DeleteResult::DoesNotExist);
+        }
+    }
+
+    let (new_tip, parents) = remove_leaf_from_parents::<K, V, T, S, E>(txn, store, parents)?;
+    let new_elements = rehash(new_tip, parents)?;
+
+    let mut root_hash = root.to_owned();
+    for (hash, element) in new_elements.iter() {
+        put_counter += 1;
+        store.put(txn, hash, element)?;
+        root_hash = *hash;
+    }
+    log_metric(
+        correlation_id,
+        TRIE_STORE_DELETE_PUTS,
+        PUT,
+        GAUGE_METRIC_KEY,
+        f64::from(put_counter),
+    );
+    log_duration(
+        correlation_id,
+        TRIE_STORE_DELETE_DURATION,
+        DELETE,
+        start.elapsed(),
+    );
+    Ok(DeleteResult::Deleted(root_hash))
+}
+
+enum EntriesIteratorState<K, V, S: TrieStore<K, V>> {
     /// Iterate normally
     Ok,
     /// Return the error and stop iterating
@@ -746,15 +982,56 @@ struct VisitedTrieNode<K, V> {
     path: Vec<u8>,
 }
 
-pub struct KeysIterator<'a, 'b, K, V, T, S: TrieStore<K, V>> {
+/// An iterator over the keys of the leaves in a subtrie, in the order of their serialized keys.
+pub struct KeysIterator<'a, 'b, K, V, T, S: TrieStore<K, V>>(EntriesIterator<'a, 'b, K, V, T, S>);
+
+impl<'a, 'b, K, V, T, S> Iterator for KeysIterator<'a, 'b, K, V, T, S>
+where
+    K: ToBytes + FromBytes + Clone + Eq + std::fmt::Debug,
+    V: ToBytes + FromBytes + Clone + Eq + std::fmt::Debug,
+    T: Readable<Handle = S::Handle>,
+    S: TrieStore<K, V>,
+    S::Error: From<T::Error> + From<types::bytesrepr::Error>,
+{
+    type Item = Result<K, S::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0
+            .next()
+            .map(|maybe_entry| maybe_entry.map(|(key, _)| key))
+    }
+}
+
+/// Returns `true` if every key whose serialized form starts with `path` sorts before `start_after`.
+fn precedes<'a>(path: impl Iterator<Item = &'a u8>, start_after: &[u8]) -> bool {
+    path.zip(start_after)
+        .find(|(path_byte, start_byte)| path_byte != start_byte)
+        .map_or(false, |(path_byte, start_byte)| path_byte < start_byte)
+}
+
+/// An iterator over the keys and values of the leaves in a subtrie, in the order of their
+/// serialized keys.
+pub struct EntriesIterator<'a, 'b, K, V, T, S: TrieStore<K, V>> {
     initial_descend: VecDeque<u8>,
+    start_after: Option<Vec<u8>>,
     visited: Vec<VisitedTrieNode<K, V>>,
     store: &'a S,
     txn: &'b T,
-    state: KeysIteratorState<K, V, S>,
+    state: EntriesIteratorState<K, V, S>,
 }
 
-impl<'a, 'b, K, V, T, S> Iterator for KeysIterator<'a, 'b, K, V, T, S>
+impl<'a, 'b, K, V, T, S: TrieStore<K, V>> EntriesIterator<'a, 'b, K, V, T, S> {
+    /// Returns `true` if the subtrie at `path` followed by `affix` only holds keys at or before
+    /// `start_after`.
+    fn skips(&self, path: &[u8], affix: &[u8]) -> bool {
+        match self.start_after {
+            Some(ref start_after) => precedes(path.iter().chain(affix), start_after),
+            None => false,
+        }
+    }
+}
+
+impl<'a, 'b, K, V, T, S> Iterator for EntriesIterator<'a, 'b, K, V, T, S>
 where
     K: ToBytes + FromBytes + Clone + Eq + std::fmt::Debug,
     V: ToBytes + FromBytes + Clone + Eq + std::fmt::Debug,
@@ -762,16 +1039,16 @@ where
     S: TrieStore<K, V>,
     S::Error: From<T::Error> + From<types::bytesrepr::Error>,
 {
-    type Item = Result<K, S::Error>;
+    type Item = Result<(K, V), S::Error>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        match mem::replace(&mut self.state, KeysIteratorState::Ok) {
-            KeysIteratorState::Ok => (),
-            KeysIteratorState::ReturnError(e) => {
-                self.state = KeysIteratorState::Failed;
+        match mem::replace(&mut self.state, EntriesIteratorState::Ok) {
+            EntriesIteratorState::Ok => (),
+            EntriesIteratorState::ReturnError(e) => {
+                self.state = EntriesIteratorState::Failed;
                 return Some(Err(e));
             }
-            KeysIteratorState::Failed => {
+            EntriesIteratorState::Failed => {
                 return None;
             }
         }
@@ -784,19 +1061,24 @@ where
             let mut maybe_next_trie: Option<Trie<K, V>> = None;
 
             match trie {
-                Trie::Leaf { key, .. } => {
+                Trie::Leaf { key, value } => {
                     let key_bytes = match key.to_bytes() {
                         Ok(bytes) => bytes,
                         Err(e) => {
-                            self.state = KeysIteratorState::Failed;
+                            self.state = EntriesIteratorState::Failed;
                             return Some(Err(e.into()));
                         }
                     };
                     debug_assert!(key_bytes.starts_with(&path));
-                    // only return the leaf if it matches the initial descend path
+                    // only return the leaf if it matches the initial descend path and comes after
+                    // the start
                     path.extend(&self.initial_descend);
-                    if key_bytes.starts_with(&path) {
-                        return Some(Ok(key));
+                    let is_started = match self.start_after {
+                        Some(ref start_after) => key_bytes > *start_after,
+                        None => true,
+                    };
+                    if key_bytes.starts_with(&path) && is_started {
+                        return Some(Ok((key, value)));
                     }
                 }
                 Trie::Node { ref pointer_block } => {
@@ -810,11 +1092,13 @@ where
                         .or(maybe_index)
                         .unwrap_or_default();
                     while index < RADIX {
-                        if let Some(ref pointer) = pointer_block[index] {
+                        // subtries which only hold keys before the start are treated as absent
+                        let is_skipped = self.skips(&path, &[index as u8]);
+                        if let (Some(ref pointer), false) = (&pointer_block[index], is_skipped) {
                             maybe_next_trie = match self.store.get(self.txn, pointer.hash()) {
                                 Ok(trie) => trie,
                                 Err(e) => {
-                                    self.state = KeysIteratorState::Failed;
+                                    self.state = EntriesIteratorState::Failed;
                                     return Some(Err(e));
                                 }
                             };
@@ -848,11 +1132,11 @@ where
                     // matches the descend path
                     // if we are not, the check_prefix will be empty, so we will enter the if
                     // anyway
-                    if affix.starts_with(&check_prefix) {
+                    if affix.starts_with(&check_prefix) && !self.skips(&path, &affix) {
                         maybe_next_trie = match self.store.get(self.txn, pointer.hash()) {
                             Ok(trie) => trie,
                             Err(e) => {
-                                self.state = KeysIteratorState::Failed;
+                                self.state = EntriesIteratorState::Failed;
                                 return Some(Err(e));
                             }
                         };
@@ -904,12 +1188,42 @@ where
 /// The root should be the apex of the trie.
 #[allow(dead_code)]
 pub fn keys_with_prefix<'a, 'b, K, V, T, S>(
-    _correlation_id: CorrelationId,
+    correlation_id: CorrelationId,
     txn: &'b T,
     store: &'a S,
     root: &Blake2bHash,
     prefix: &[u8],
 ) -> KeysIterator<'a, 'b, K, V, T, S>
+where
+    K: ToBytes + FromBytes + Clone + Eq + std::fmt::Debug,
+    V: ToBytes + FromBytes + Clone + Eq + std::fmt::Debug,
+    T: Readable<Handle = S::Handle>,
+    S: TrieStore<K, V>,
+    S::Error: From<T::Error>,
+{
+    KeysIterator(entries_with_prefix(
+        correlation_id,
+        txn,
+        store,
+        root,
+        prefix,
+        None,
+    ))
+}
+
+/// Returns the iterator over the keys and values in the subtrie matching `prefix`, in the order of
+/// their serialized keys, starting after the key whose serialized form is `start_after`.
+///
+/// The root should be the apex of the trie.  Subtries which only hold keys before the start are
+/// not read.
+pub fn entries_with_prefix<'a, 'b, K, V, T, S>(
+    _correlation_id: CorrelationId,
+    txn: &'b T,
+    store: &'a S,
+    root: &Blake2bHash,
+    prefix: &[u8],
+    start_after: Option<&[u8]>,
+) -> EntriesIterator<'a, 'b, K, V, T, S>
 where
     K: ToBytes + FromBytes + Clone + Eq + std::fmt::Debug,
     V: ToBytes + FromBytes + Clone + Eq + std::fmt::Debug,
@@ -918,20 +1232,21 @@ where
     S::Error: From<T::Error>,
 {
     let (visited, init_state): (Vec<VisitedTrieNode<K, V>>, _) = match store.get(txn, root) {
-        Ok(None) => (vec![], KeysIteratorState::Ok),
-        Err(e) => (vec![], KeysIteratorState::ReturnError(e)),
+        Ok(None) => (vec![], EntriesIteratorState::Ok),
+        Err(e) => (vec![], EntriesIteratorState::ReturnError(e)),
         Ok(Some(current_root)) => (
             vec![VisitedTrieNode {
                 trie: current_root,
                 maybe_index: None,
                 path: vec![],
             }],
-            KeysIteratorState::Ok,
+            EntriesIteratorState::Ok,
         ),
     };
 
-    KeysIterator {
+    EntriesIterator {
         initial_descend: prefix.iter().cloned().collect(),
+        start_after: start_after.map(<[u8]>::to_vec),
         visited,
         store,
         txn,
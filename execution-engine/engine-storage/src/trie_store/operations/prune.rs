@@ -0,0 +1,440 @@
+//! Removal of trie nodes which are no longer reachable from any retained root.
+//!
+//! Pruning is a mark-and-sweep which proceeds in bounded steps, so that a prune can be split
+//! across many short read-write transactions which interleave with commits.  Each call to
+//! [`TriePruner::step`] either marks a bounded number of the tries reachable from the retained
+//! roots, or, once every one of them has been marked, examines a bounded number of stored tries
+//! and deletes those which were not marked.
+//!
+//! Nodes written by a commit which happens after pruning has started are not marked, unless they
+//! are written through a [`MarkingTrieStore`].  The roots produced by such a commit must be passed
+//! to [`TriePruner::retain`] before the next call to [`TriePruner::step`], otherwise the nodes they
+//! introduced may be deleted.  Commits should only be made on top of retained roots.
+//!
+//! Only the retained roots, the sweep position and the stats are serialized with [`ToBytes`].  A
+//! pruner restored with [`FromBytes`] marks its retained roots again before it carries on sweeping
+//! from the same position.
+
+use std::{cell::RefCell, collections::HashSet, time::Instant};
+
+use engine_shared::{
+    logging::{log_duration, log_metric},
+    newtypes::{Blake2bHash, CorrelationId},
+};
+use types::bytesrepr::{self, FromBytes, ToBytes, U64_SERIALIZED_LENGTH};
+
+use crate::{
+    error::TrieNotFound,
+    store::Store,
+    transaction_source::{Deletable, Enumerable, Readable, Writable},
+    trie::{Pointer, Trie},
+    trie_store::TrieStore,
+    GAUGE_METRIC_KEY,
+};
+
+const TRIE_STORE_PRUNE_MARK_DURATION: &str = "trie_store_prune_mark_duration";
+const TRIE_STORE_PRUNE_SWEEP_DURATION: &str = "trie_store_prune_sweep_duration";
+const TRIE_STORE_PRUNE_DELETES: &str = "trie_store_prune_deletes";
+const MARK: &str = "mark";
+const SWEEP: &str = "sweep";
+const DELETE: &str = "delete";
+
+/// Counters describing the work done by a [`TriePruner`].
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct PruneStats {
+    /// The number of distinct trie nodes marked as reachable from a retained root.
+    pub retained: usize,
+    /// The number of stored trie nodes examined by the sweep.
+    pub examined: usize,
+    /// The number of stored trie nodes deleted by the sweep.
+    pub deleted: usize,
+}
+
+/// The result of [`TriePruner::step`].
+#[derive(Debug, PartialEq, Eq)]
+pub enum PruneStepResult {
+    /// There are reachable tries which have not been marked yet, or stored tries which have not
+    /// been examined yet.
+    InProgress,
+    /// Every stored trie has been examined.
+    Complete(PruneStats),
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum PruneResult {
+    RootNotFound(Blake2bHash),
+    Success(PruneStats),
+}
+
+/// The state of an incremental prune of a [`TrieStore`].
+#[derive(Debug, Default, Clone)]
+pub struct TriePruner {
+    /// The roots whose tries are kept.
+    roots: Vec<Blake2bHash>,
+    /// Pointers to reachable tries which have not been marked yet.
+    frontier: Vec<Pointer>,
+    marked: HashSet<Blake2bHash>,
+    sweep_position: Option<Vec<u8>>,
+    stats: PruneStats,
+}
+
+impl TriePruner {
+    /// Creates a pruner which keeps the tries reachable from `retained_roots`.
+    pub fn new(retained_roots: &[Blake2bHash]) -> Self {
+        let mut ret = TriePruner::default();
+        for root in retained_roots {
+            ret.retain(*root);
+        }
+        ret
+    }
+
+    /// Returns the roots whose tries are kept.
+    pub fn roots(&self) -> &[Blake2bHash] {
+        &self.roots
+    }
+
+    /// Returns the work done so far.
+    pub fn stats(&self) -> PruneStats {
+        self.stats
+    }
+
+    /// Returns `true` if the node at the given hash has been marked as reachable.
+    pub fn is_retained(&self, hash: &Blake2bHash) -> bool {
+        self.marked.contains(hash)
+    }
+
+    /// Keeps every trie node reachable from `root`.
+    ///
+    /// The nodes are marked by the following calls to [`TriePruner::step`], before anything else
+    /// is swept.  Sub-tries which have already been marked are not walked again, which makes
+    /// retaining the root of a fresh commit cheap.
+    pub fn retain(&mut self, root: Blake2bHash) {
+        if !self.roots.contains(&root) {
+            self.roots.push(root);
+        }
+        if !self.marked.contains(&root) {
+            self.frontier.push(Pointer::NodePointer(root));
+        }
+    }
+
+    /// Marks at most `batch_size` of the tries reachable from the retained roots if there are any
+    /// left to mark, and otherwise examines at most `batch_size` stored trie nodes, continuing
+    /// from where the previous call left off, and deletes those which have not been marked.
+    ///
+    /// Fails with [`TrieNotFound`] if a retained root, or a trie reachable from one, is missing.
+    pub fn step<K, V, T, S, E>(
+        &mut self,
+        correlation_id: CorrelationId,
+        txn: &mut T,
+        store: &S,
+        batch_size: usize,
+    ) -> Result<PruneStepResult, E>
+    where
+        K: ToBytes + FromBytes,
+        V: ToBytes + FromBytes,
+        T: Readable<Handle = S::Handle>
+            + Enumerable<Handle = S::Handle>
+            + Deletable<Handle = S::Handle>,
+        S: TrieStore<K, V>,
+        S::Error: From<T::Error>,
+        E: From<S::Error> + From<bytesrepr::Error> + From<TrieNotFound>,
+    {
+        if !self.frontier.is_empty() {
+            self.mark::<K, V, T, S, E>(correlation_id, txn, store, batch_size)?;
+            return Ok(PruneStepResult::InProgress);
+        }
+        self.sweep::<K, V, T, S, E>(correlation_id, txn, store, batch_size)
+    }
+
+    /// Marks at most `batch_size` tries from the frontier, adding their unmarked children to it.
+    fn mark<K, V, T, S, E>(
+        &mut self,
+        correlation_id: CorrelationId,
+        txn: &T,
+        store: &S,
+        batch_size: usize,
+    ) -> Result<(), E>
+    where
+        K: ToBytes + FromBytes,
+        V: ToBytes + FromBytes,
+        T: Readable<Handle = S::Handle>,
+        S: TrieStore<K, V>,
+        S::Error: From<T::Error>,
+        E: From<S::Error> + From<bytesrepr::Error> + From<TrieNotFound>,
+    {
+        let start = Instant::now();
+        let mut get_counter: usize = 0;
+
+        while get_counter < batch_size {
+            let pointer = match self.frontier.pop() {
+                Some(pointer) => pointer,
+                None => break,
+            };
+            let hash = *pointer.hash();
+            if !self.marked.insert(hash) {
+                continue;
+            }
+            self.stats.retained += 1;
+            if let Pointer::LeafPointer(_) = pointer {
+                // Leaves have no children, so there is no need to load them.
+                continue;
+            }
+            get_counter += 1;
+            let trie: Trie<K, V> = match store.get(txn, &hash)? {
+                Some(trie) => trie,
+                None => return Err(TrieNotFound(hash).into()),
+            };
+            self.push_unmarked_children(&trie);
+        }
+
+        log_duration(
+            correlation_id,
+            TRIE_STORE_PRUNE_MARK_DURATION,
+            MARK,
+            start.elapsed(),
+        );
+        Ok(())
+    }
+
+    /// Marks `trie`, which has just been written at `hash`, and adds its unmarked children to the
+    /// frontier.
+    fn mark_written<K, V>(&mut self, hash: Blake2bHash, trie: &Trie<K, V>) {
+        if self.marked.insert(hash) {
+            self.stats.retained += 1;
+            self.push_unmarked_children(trie);
+        }
+    }
+
+    fn push_unmarked_children<K, V>(&mut self, trie: &Trie<K, V>) {
+        let marked = &self.marked;
+        match trie {
+            Trie::Leaf { .. } => (),
+            Trie::Node { pointer_block } => self.frontier.extend(
+                pointer_block[..]
+                    .iter()
+                    .flatten()
+                    .copied()
+                    .filter(|pointer| !marked.contains(pointer.hash())),
+            ),
+            Trie::Extension { pointer, .. } => {
+                if !marked.contains(pointer.hash()) {
+                    self.frontier.push(*pointer)
+                }
+            }
+        }
+    }
+
+    /// Examines at most `batch_size` stored trie nodes, continuing from where the previous call
+    /// left off, and deletes those which have not been marked.
+    fn sweep<K, V, T, S, E>(
+        &mut self,
+        correlation_id: CorrelationId,
+        txn: &mut T,
+        store: &S,
+        batch_size: usize,
+    ) -> Result<PruneStepResult, E>
+    where
+        K: ToBytes + FromBytes,
+        V: ToBytes + FromBytes,
+        T: Enumerable<Handle = S::Handle> + Deletable<Handle = S::Handle>,
+        S: TrieStore<K, V>,
+        S::Error: From<T::Error>,
+        E: From<S::Error> + From<bytesrepr::Error>,
+    {
+        let start = Instant::now();
+        let mut delete_counter: i32 = 0;
+
+        let batch = txn
+            .keys_after(store.handle(), self.sweep_position.as_deref(), batch_size)
+            .map_err(S::Error::from)?;
+
+        for key_bytes in batch.iter() {
+            let hash: Blake2bHash = bytesrepr::deserialize(key_bytes.to_owned())?;
+            self.stats.examined += 1;
+            if !self.marked.contains(&hash) {
+                store.delete(txn, &hash)?;
+                self.stats.deleted += 1;
+                delete_counter += 1;
+            }
+        }
+
+        log_metric(
+            correlation_id,
+            TRIE_STORE_PRUNE_DELETES,
+            DELETE,
+            GAUGE_METRIC_KEY,
+            f64::from(delete_counter),
+        );
+        log_duration(
+            correlation_id,
+            TRIE_STORE_PRUNE_SWEEP_DURATION,
+            SWEEP,
+            start.elapsed(),
+        );
+
+        let is_complete = batch.len() < batch_size;
+        if let Some(last) = batch.into_iter().last() {
+            self.sweep_position = Some(last);
+        }
+        if is_complete {
+            Ok(PruneStepResult::Complete(self.stats))
+        } else {
+            Ok(PruneStepResult::InProgress)
+        }
+    }
+}
+
+/// A [`TrieStore`] which marks every trie written through it as retained by a [`TriePruner`].
+///
+/// The tries introduced by a commit which writes through it are kept without being walked by
+/// [`TriePruner::step`], so a prune keeps up with commits however many tries they write.
+pub struct MarkingTrieStore<'a, S> {
+    store: &'a S,
+    pruner: &'a RefCell<TriePruner>,
+}
+
+impl<'a, S> MarkingTrieStore<'a, S> {
+    pub fn new(store: &'a S, pruner: &'a RefCell<TriePruner>) -> Self {
+        MarkingTrieStore { store, pruner }
+    }
+}
+
+impl<'a, K, V, S> Store<Blake2bHash, Trie<K, V>> for MarkingTrieStore<'a, S>
+where
+    S: TrieStore<K, V>,
+{
+    type Error = S::Error;
+
+    type Handle = S::Handle;
+
+    fn handle(&self) -> Self::Handle {
+        self.store.handle()
+    }
+
+    fn get<T>(&self, txn: &T, key: &Blake2bHash) -> Result<Option<Trie<K, V>>, Self::Error>
+    where
+        T: Readable<Handle = Self::Handle>,
+        Blake2bHash: ToBytes,
+        Trie<K, V>: FromBytes,
+        Self::Error: From<T::Error>,
+    {
+        self.store.get(txn, key)
+    }
+
+    fn put<T>(&self, txn: &mut T, key: &Blake2bHash, value: &Trie<K, V>) -> Result<(), Self::Error>
+    where
+        T: Writable<Handle = Self::Handle>,
+        Blake2bHash: ToBytes,
+        Trie<K, V>: ToBytes,
+        Self::Error: From<T::Error>,
+    {
+        self.pruner.borrow_mut().mark_written(*key, value);
+        self.store.put(txn, key, value)
+    }
+
+    fn delete<T>(&self, txn: &mut T, key: &Blake2bHash) -> Result<(), Self::Error>
+    where
+        T: Deletable<Handle = Self::Handle>,
+        Blake2bHash: ToBytes,
+        Self::Error: From<T::Error>,
+    {
+        self.store.delete(txn, key)
+    }
+}
+
+impl<'a, K, V, S> TrieStore<K, V> for MarkingTrieStore<'a, S> where S: TrieStore<K, V> {}
+
+impl ToBytes for PruneStats {
+    fn to_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
+        let mut ret = bytesrepr::allocate_buffer(self)?;
+        ret.append(&mut (self.retained as u64).to_bytes()?);
+        ret.append(&mut (self.examined as u64).to_bytes()?);
+        ret.append(&mut (self.deleted as u64).to_bytes()?);
+        Ok(ret)
+    }
+
+    fn serialized_length(&self) -> usize {
+        3 * U64_SERIALIZED_LENGTH
+    }
+}
+
+impl FromBytes for PruneStats {
+    fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), bytesrepr::Error> {
+        let (retained, rem) = u64::from_bytes(bytes)?;
+        let (examined, rem) = u64::from_bytes(rem)?;
+        let (deleted, rem) = u64::from_bytes(rem)?;
+        let stats = PruneStats {
+            retained: retained as usize,
+            examined: examined as usize,
+            deleted: deleted as usize,
+        };
+        Ok((stats, rem))
+    }
+}
+
+impl ToBytes for TriePruner {
+    fn to_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
+        let mut ret = bytesrepr::allocate_buffer(self)?;
+        ret.append(&mut self.roots.to_bytes()?);
+        ret.append(&mut self.sweep_position.to_bytes()?);
+        ret.append(&mut self.stats.to_bytes()?);
+        Ok(ret)
+    }
+
+    fn serialized_length(&self) -> usize {
+        self.roots.serialized_length()
+            + self.sweep_position.serialized_length()
+            + self.stats.serialized_length()
+    }
+}
+
+impl FromBytes for TriePruner {
+    fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), bytesrepr::Error> {
+        let (roots, rem): (Vec<Blake2bHash>, _) = FromBytes::from_bytes(bytes)?;
+        let (sweep_position, rem) = FromBytes::from_bytes(rem)?;
+        let (stats, rem) = FromBytes::from_bytes(rem)?;
+        let mut pruner = TriePruner::new(&roots);
+        pruner.sweep_position = sweep_position;
+        // The retained tries are counted again as they are marked again
+        pruner.stats = PruneStats {
+            retained: 0,
+            ..stats
+        };
+        Ok((pruner, rem))
+    }
+}
+
+/// Deletes every trie node which is not reachable from one of `retained_roots`, within a single
+/// read-write transaction.
+pub fn prune<K, V, T, S, E>(
+    correlation_id: CorrelationId,
+    txn: &mut T,
+    store: &S,
+    retained_roots: &[Blake2bHash],
+) -> Result<PruneResult, E>
+where
+    K: ToBytes + FromBytes,
+    V: ToBytes + FromBytes,
+    T: Readable<Handle = S::Handle>
+        + Enumerable<Handle = S::Handle>
+        + Deletable<Handle = S::Handle>,
+    S: TrieStore<K, V>,
+    S::Error: From<T::Error>,
+    E: From<S::Error> + From<bytesrepr::Error> + From<TrieNotFound>,
+{
+    const BATCH_SIZE: usize = 1024;
+
+    for root in retained_roots {
+        if store.get(txn, root)?.is_none() {
+            return Ok(PruneResult::RootNotFound(*root));
+        }
+    }
+    let mut pruner = TriePruner::new(retained_roots);
+    loop {
+        if let PruneStepResult::Complete(stats) =
+            pruner.step::<K, V, T, S, E>(correlation_id, txn, store, BATCH_SIZE)?
+        {
+            return Ok(PruneResult::Success(stats));
+        }
+    }
+}
@@ -0,0 +1,150 @@
+//! Rewriting every stored trie with the compression of its store.
+//!
+//! A trie store only compresses the tries it writes, so tries written before compression was
+//! enabled, or with another algorithm, stay as they are until they are rewritten.
+//! [`TrieRecompressor::step`] rewrites a bounded number of stored tries per call, so that a whole
+//! store can be migrated across many short read-write transactions.  As tries are stored at the
+//! hash of their uncompressed encoding, rewriting them changes no hash and no state root.
+
+use std::time::Instant;
+
+use engine_shared::{
+    logging::{log_duration, log_metric},
+    newtypes::{Blake2bHash, CorrelationId},
+};
+use types::bytesrepr::{self, FromBytes, ToBytes};
+
+use crate::{
+    error::TrieNotFound,
+    transaction_source::{Enumerable, Readable, Writable},
+    trie::Trie,
+    trie_store::{compression, TrieStore},
+    GAUGE_METRIC_KEY,
+};
+
+const TRIE_STORE_RECOMPRESS_DURATION: &str = "trie_store_recompress_duration";
+const TRIE_STORE_RECOMPRESS_WRITES: &str = "trie_store_recompress_writes";
+const RECOMPRESS: &str = "recompress";
+const WRITE: &str = "write";
+
+/// Counters describing the work done by a [`TrieRecompressor`].
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct RecompressStats {
+    /// The number of stored tries rewritten.
+    pub tries: usize,
+    /// The number of rewritten tries which are now stored compressed.
+    pub compressed: usize,
+    /// The total size of the rewritten tries before they were rewritten.
+    pub bytes_before: usize,
+    /// The total size of the rewritten tries after they were rewritten.
+    pub bytes_after: usize,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum RecompressResult {
+    /// There are stored tries which have not been rewritten yet.
+    InProgress,
+    /// Every stored trie has been rewritten.
+    Complete(RecompressStats),
+}
+
+/// The state of an incremental rewrite of every trie in a [`TrieStore`].
+#[derive(Debug, Default)]
+pub struct TrieRecompressor {
+    position: Option<Vec<u8>>,
+    stats: RecompressStats,
+}
+
+impl TrieRecompressor {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Returns the work done so far.
+    pub fn stats(&self) -> RecompressStats {
+        self.stats
+    }
+
+    /// Rewrites at most `batch_size` stored tries, continuing from where the previous call left
+    /// off.
+    ///
+    /// Fails with [`TrieNotFound`] if a stored key has no trie.
+    pub fn step<K, V, T, S, E>(
+        &mut self,
+        correlation_id: CorrelationId,
+        txn: &mut T,
+        store: &S,
+        batch_size: usize,
+    ) -> Result<RecompressResult, E>
+    where
+        K: ToBytes + FromBytes,
+        V: ToBytes + FromBytes,
+        T: Readable<Handle = S::Handle>
+            + Writable<Handle = S::Handle>
+            + Enumerable<Handle = S::Handle>,
+        S: TrieStore<K, V>,
+        S::Error: From<T::Error>,
+        E: From<S::Error> + From<bytesrepr::Error> + From<TrieNotFound>,
+    {
+        let start = Instant::now();
+        let mut write_counter: i32 = 0;
+
+        let batch = txn
+            .keys_after(store.handle(), self.position.as_deref(), batch_size)
+            .map_err(S::Error::from)?;
+
+        for key_bytes in batch.iter() {
+            let hash: Blake2bHash = bytesrepr::deserialize(key_bytes.to_owned())?;
+            let bytes_before = read_stored::<K, V, _, _>(txn, store, key_bytes)?;
+            let trie: Trie<K, V> = match store.get(txn, &hash)? {
+                Some(trie) => trie,
+                None => return Err(TrieNotFound(hash).into()),
+            };
+            store.put(txn, &hash, &trie)?;
+            write_counter += 1;
+            let bytes_after = read_stored::<K, V, _, _>(txn, store, key_bytes)?;
+
+            self.stats.tries += 1;
+            if compression::is_compressed(&bytes_after) {
+                self.stats.compressed += 1;
+            }
+            self.stats.bytes_before += bytes_before.len();
+            self.stats.bytes_after += bytes_after.len();
+        }
+
+        log_metric(
+            correlation_id,
+            TRIE_STORE_RECOMPRESS_WRITES,
+            WRITE,
+            GAUGE_METRIC_KEY,
+            f64::from(write_counter),
+        );
+        log_duration(
+            correlation_id,
+            TRIE_STORE_RECOMPRESS_DURATION,
+            RECOMPRESS,
+            start.elapsed(),
+        );
+
+        let is_complete = batch.len() < batch_size;
+        if let Some(last) = batch.into_iter().last() {
+            self.position = Some(last);
+        }
+        if is_complete {
+            Ok(RecompressResult::Complete(self.stats))
+        } else {
+            Ok(RecompressResult::InProgress)
+        }
+    }
+}
+
+/// Returns the bytes stored at `key_bytes`, as they are stored.
+fn read_stored<K, V, T, S>(txn: &T, store: &S, key_bytes: &[u8]) -> Result<Vec<u8>, S::Error>
+where
+    T: Readable<Handle = S::Handle>,
+    S: TrieStore<K, V>,
+    S::Error: From<T::Error>,
+{
+    let ret = txn.read(store.handle(), key_bytes)?.unwrap_or_default();
+    Ok(ret)
+}
@@ -0,0 +1,239 @@
+//! Aggregate statistics over the tries reachable from a root, for capacity planning.
+//!
+//! [`stats`] walks every trie reachable from a root and gathers a [`TrieStats`].  The statistics of
+//! two roots can be put side by side with [`compare`] to show how the state grew between them.
+
+use std::{collections::BTreeMap, time::Instant};
+
+use engine_shared::{
+    logging::{log_duration, log_metric},
+    newtypes::{Blake2bHash, CorrelationId},
+    stored_value::StoredValue,
+};
+use types::{
+    bytesrepr::{FromBytes, ToBytes},
+    Key,
+};
+
+use crate::{transaction_source::Readable, trie::Trie, trie_store::TrieStore, GAUGE_METRIC_KEY};
+
+const TRIE_STORE_STATS_DURATION: &str = "trie_store_stats_duration";
+const TRIE_STORE_STATS_GETS: &str = "trie_store_stats_gets";
+const STATS: &str = "stats";
+const GET: &str = "get";
+
+/// Names the variant of a key or a value, so that statistics can be grouped by it.
+pub trait StatsKind {
+    fn stats_kind(&self) -> &'static str;
+}
+
+impl StatsKind for Key {
+    fn stats_kind(&self) -> &'static str {
+        match self {
+            Key::Account(_) => "Account",
+            Key::Hash(_) => "Hash",
+            Key::URef(_) => "URef",
+        }
+    }
+}
+
+impl StatsKind for StoredValue {
+    fn stats_kind(&self) -> &'static str {
+        match self {
+            StoredValue::CLValue(_) => "CLValue",
+            StoredValue::Account(_) => "Account",
+            StoredValue::ContractWasm(_) => "ContractWasm",
+            StoredValue::Contract(_) => "Contract",
+            StoredValue::ContractPackage(_) => "ContractPackage",
+        }
+    }
+}
+
+/// The number and the total serialized size of a kind of value.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct ValueStats {
+    pub count: usize,
+    pub bytes: usize,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct TrieStats {
+    pub nodes: usize,
+    pub extensions: usize,
+    pub leaves: usize,
+    /// The total serialized size of every trie, including leaves.
+    pub bytes: usize,
+    /// The number of leaves, by the kind of their key.
+    pub leaves_by_key: BTreeMap<&'static str, usize>,
+    /// The number and serialized size of values, by their kind.
+    pub values: BTreeMap<&'static str, ValueStats>,
+    /// The number of leaves at each depth, where the depth of a leaf is the number of nodes and
+    /// extensions above it.
+    pub leaf_depths: BTreeMap<usize, usize>,
+    /// The total number of pointers held by the pointer blocks of every node.
+    pub node_pointers: usize,
+}
+
+impl TrieStats {
+    /// Returns the average number of pointers held by the pointer block of a node.
+    pub fn average_branching_factor(&self) -> f64 {
+        if self.nodes == 0 {
+            return 0.0;
+        }
+        self.node_pointers as f64 / self.nodes as f64
+    }
+
+    /// Returns every statistic as a name and a value, in a fixed order.
+    ///
+    /// Grouped statistics are named `<statistic>.<group>`, e.g. `leaves_by_key.URef`.
+    pub fn to_named_values(&self) -> Vec<(String, f64)> {
+        let mut ret = vec![
+            ("nodes".to_string(), self.nodes as f64),
+            ("extensions".to_string(), self.extensions as f64),
+            ("leaves".to_string(), self.leaves as f64),
+            ("bytes".to_string(), self.bytes as f64),
+            (
+                "average_branching_factor".to_string(),
+                self.average_branching_factor(),
+            ),
+        ];
+        for (kind, count) in &self.leaves_by_key {
+            ret.push((format!("leaves_by_key.{}", kind), *count as f64));
+        }
+        for (kind, value_stats) in &self.values {
+            ret.push((format!("value_count.{}", kind), value_stats.count as f64));
+            ret.push((format!("value_bytes.{}", kind), value_stats.bytes as f64));
+        }
+        for (depth, count) in &self.leaf_depths {
+            ret.push((format!("leaf_depth.{}", depth), *count as f64));
+        }
+        ret
+    }
+
+    /// Returns a copy of `self` with a zero entry for every group which only `other` has.
+    fn with_groups_of(&self, other: &TrieStats) -> TrieStats {
+        let mut ret = self.clone();
+        for kind in other.leaves_by_key.keys() {
+            ret.leaves_by_key.entry(kind).or_default();
+        }
+        for kind in other.values.keys() {
+            ret.values.entry(kind).or_default();
+        }
+        for depth in other.leaf_depths.keys() {
+            ret.leaf_depths.entry(*depth).or_default();
+        }
+        ret
+    }
+}
+
+/// A statistic of a left and a right root.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatComparison {
+    pub name: String,
+    pub left: f64,
+    pub right: f64,
+}
+
+impl StatComparison {
+    /// Returns how much the statistic grew from the left root to the right root.
+    pub fn growth(&self) -> f64 {
+        self.right - self.left
+    }
+}
+
+/// Puts every statistic of `left` beside the same statistic of `right`, in the order of
+/// [`TrieStats::to_named_values`].  A group which is only present on one side is zero on the other.
+pub fn compare(left: &TrieStats, right: &TrieStats) -> Vec<StatComparison> {
+    let left_values = left.with_groups_of(right).to_named_values();
+    let right_values = right.with_groups_of(left).to_named_values();
+    left_values
+        .into_iter()
+        .zip(right_values)
+        .map(|((name, left), (_, right))| StatComparison { name, left, right })
+        .collect()
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum StatsResult {
+    RootNotFound,
+    Success(TrieStats),
+}
+
+/// Walks every trie reachable from `root` and returns statistics about them.
+pub fn stats<K, V, T, S, E>(
+    correlation_id: CorrelationId,
+    txn: &T,
+    store: &S,
+    root: &Blake2bHash,
+) -> Result<StatsResult, E>
+where
+    K: ToBytes + FromBytes + StatsKind,
+    V: ToBytes + FromBytes + StatsKind,
+    T: Readable<Handle = S::Handle>,
+    S: TrieStore<K, V>,
+    S::Error: From<T::Error>,
+    E: From<S::Error> + From<types::bytesrepr::Error>,
+{
+    let root_trie = match store.get(txn, root)? {
+        Some(trie) => trie,
+        None => return Ok(StatsResult::RootNotFound),
+    };
+
+    let start = Instant::now();
+    let mut get_counter: i32 = 1;
+    let mut ret = TrieStats::default();
+
+    // Tries still to be counted, along with their depths
+    let mut stack: Vec<(Trie<K, V>, usize)> = vec![(root_trie, 0)];
+    while let Some((trie, depth)) = stack.pop() {
+        ret.bytes += trie.serialized_length();
+        let child_hashes: Vec<Blake2bHash> = match trie {
+            Trie::Leaf { key, value } => {
+                ret.leaves += 1;
+                *ret.leaves_by_key.entry(key.stats_kind()).or_default() += 1;
+                let value_stats = ret.values.entry(value.stats_kind()).or_default();
+                value_stats.count += 1;
+                value_stats.bytes += value.serialized_length();
+                *ret.leaf_depths.entry(depth).or_default() += 1;
+                continue;
+            }
+            Trie::Node { pointer_block } => {
+                ret.nodes += 1;
+                let hashes: Vec<Blake2bHash> = pointer_block[..]
+                    .iter()
+                    .flatten()
+                    .map(|pointer| *pointer.hash())
+                    .collect();
+                ret.node_pointers += hashes.len();
+                hashes
+            }
+            Trie::Extension { pointer, .. } => {
+                ret.extensions += 1;
+                vec![*pointer.hash()]
+            }
+        };
+        for hash in child_hashes {
+            get_counter += 1;
+            match store.get(txn, &hash)? {
+                Some(child) => stack.push((child, depth + 1)),
+                None => panic!("No trie value at key: {:?}", hash),
+            }
+        }
+    }
+
+    log_metric(
+        correlation_id,
+        TRIE_STORE_STATS_GETS,
+        GET,
+        GAUGE_METRIC_KEY,
+        f64::from(get_counter),
+    );
+    log_duration(
+        correlation_id,
+        TRIE_STORE_STATS_DURATION,
+        STATS,
+        start.elapsed(),
+    );
+
+    Ok(StatsResult::Success(ret))
+}
@@ -0,0 +1,207 @@
+use super::*;
+use crate::trie_store::operations::batch::write_batch;
+
+fn leaves_to_updates(leaves: &[TestTrie]) -> Vec<(TestKey, Option<TestValue>)> {
+    leaves
+        .iter()
+        .map(|leaf| match leaf {
+            Trie::Leaf { key, value } => (*key, Some(*value)),
+            _ => panic!("leaves should only contain leaves"),
+        })
+        .collect()
+}
+
+fn batch_write<'a, R, S, E>(
+    correlation_id: CorrelationId,
+    environment: &'a R,
+    store: &S,
+    root: &Blake2bHash,
+    updates: Vec<(TestKey, Option<TestValue>)>,
+) -> Result<WriteResult, E>
+where
+    R: TransactionSource<'a, Handle = S::Handle>,
+    S: TrieStore<TestKey, TestValue>,
+    S::Error: From<R::Error>,
+    E: From<R::Error> + From<S::Error> + From<types::bytesrepr::Error>,
+{
+    let mut txn = environment.create_read_write_txn()?;
+    let result = write_batch::<_, _, _, _, E>(correlation_id, &mut txn, store, root, updates)?;
+    txn.commit()?;
+    Ok(result)
+}
+
+/// Checks that writing `leaves` as a batch to each of the test tries results in the same root as
+/// writing them one at a time.
+fn write_batch_matches_write<'a, R, S, E>(
+    correlation_id: CorrelationId,
+    environment: &'a R,
+    store: &S,
+    leaves: &[TestTrie],
+) -> Result<(), E>
+where
+    R: TransactionSource<'a, Handle = S::Handle>,
+    S: TrieStore<TestKey, TestValue>,
+    S::Error: From<R::Error>,
+    E: From<R::Error> + From<S::Error> + From<types::bytesrepr::Error>,
+{
+    for generator in &TEST_TRIE_GENERATORS {
+        let (root_hash, tries) = generator()?;
+        put_tries::<_, _, _, _, E>(environment, store, &tries)?;
+
+        let mut expected_root = root_hash;
+        for result in
+            write_leaves::<_, _, _, _, E>(correlation_id, environment, store, &root_hash, leaves)?
+        {
+            if let WriteResult::Written(root_hash) = result {
+                expected_root = root_hash;
+            }
+        }
+
+        let result = batch_write::<_, _, E>(
+            correlation_id,
+            environment,
+            store,
+            &root_hash,
+            leaves_to_updates(leaves),
+        )?;
+        if expected_root == root_hash {
+            assert_eq!(result, WriteResult::AlreadyExists);
+        } else {
+            assert_eq!(result, WriteResult::Written(expected_root));
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn lmdb_write_batch_matches_write() {
+    let correlation_id = CorrelationId::new();
+    let context = LmdbTestContext::new(EMPTY_HASHED_TEST_TRIES).unwrap();
+
+    for leaves in &[
+        &TEST_LEAVES[..],
+        &TEST_LEAVES_UPDATED[..],
+        &TEST_LEAVES_NON_COLLIDING[..],
+        &TEST_LEAVES_ADJACENTS[..],
+    ] {
+        write_batch_matches_write::<_, _, error::Error>(
+            correlation_id,
+            &context.environment,
+            &context.store,
+            leaves,
+        )
+        .unwrap();
+    }
+}
+
+#[test]
+fn sled_write_batch_matches_write() {
+    let correlation_id = CorrelationId::new();
+    let context = SledTestContext::new(EMPTY_HASHED_TEST_TRIES).unwrap();
+
+    for leaves in &[
+        &TEST_LEAVES[..],
+        &TEST_LEAVES_UPDATED[..],
+        &TEST_LEAVES_NON_COLLIDING[..],
+        &TEST_LEAVES_ADJACENTS[..],
+    ] {
+        write_batch_matches_write::<_, _, error::Error>(
+            correlation_id,
+            &context.environment,
+            &context.store,
+            leaves,
+        )
+        .unwrap();
+    }
+}
+
+#[test]
+fn in_memory_write_batch_matches_write() {
+    let correlation_id = CorrelationId::new();
+    let context = InMemoryTestContext::new(EMPTY_HASHED_TEST_TRIES).unwrap();
+
+    for leaves in &[
+        &TEST_LEAVES[..],
+        &TEST_LEAVES_UPDATED[..],
+        &TEST_LEAVES_NON_COLLIDING[..],
+        &TEST_LEAVES_ADJACENTS[..],
+    ] {
+        write_batch_matches_write::<_, _, in_memory::Error>(
+            correlation_id,
+            &context.environment,
+            &context.store,
+            leaves,
+        )
+        .unwrap();
+    }
+}
+
+#[test]
+fn write_batch_of_deletions_collapses_to_empty_root() {
+    let correlation_id = CorrelationId::new();
+    let (empty_root, _) = TEST_TRIE_GENERATORS[0]().unwrap();
+    let (full_root, tries) = TEST_TRIE_GENERATORS[TEST_TRIE_GENERATORS_LENGTH - 1]().unwrap();
+    let context = InMemoryTestContext::new(&tries).unwrap();
+
+    let deletions = leaves_to_updates(&TEST_LEAVES)
+        .into_iter()
+        .map(|(key, _)| (key, None))
+        .collect();
+    let result = batch_write::<_, _, in_memory::Error>(
+        correlation_id,
+        &context.environment,
+        &context.store,
+        &full_root,
+        deletions,
+    )
+    .unwrap();
+    assert_eq!(result, WriteResult::Written(empty_root));
+}
+
+#[test]
+fn write_batch_keeps_last_update_of_a_key() {
+    let correlation_id = CorrelationId::new();
+    let (root_hash, tries) = TEST_TRIE_GENERATORS[0]().unwrap();
+    let context = InMemoryTestContext::new(&tries).unwrap();
+
+    let mut updates = leaves_to_updates(&TEST_LEAVES);
+    updates.extend(leaves_to_updates(&TEST_LEAVES_UPDATED));
+    let result = batch_write::<_, _, in_memory::Error>(
+        correlation_id,
+        &context.environment,
+        &context.store,
+        &root_hash,
+        updates,
+    )
+    .unwrap();
+    let batch_root = match result {
+        WriteResult::Written(root_hash) => root_hash,
+        _ => panic!("unexpected result: {:?}", result),
+    };
+    check_leaves::<_, _, _, _, in_memory::Error>(
+        correlation_id,
+        &context.environment,
+        &context.store,
+        &batch_root,
+        &TEST_LEAVES_UPDATED,
+        &TEST_LEAVES,
+    )
+    .unwrap();
+}
+
+#[test]
+fn write_batch_to_unknown_root_returns_root_not_found() {
+    let correlation_id = CorrelationId::new();
+    let (_, tries) = TEST_TRIE_GENERATORS[0]().unwrap();
+    let context = InMemoryTestContext::new(&tries).unwrap();
+
+    let result = batch_write::<_, _, in_memory::Error>(
+        correlation_id,
+        &context.environment,
+        &context.store,
+        &Blake2bHash::new(&[0u8]),
+        leaves_to_updates(&TEST_LEAVES),
+    )
+    .unwrap();
+    assert_eq!(result, WriteResult::RootNotFound);
+}
@@ -0,0 +1,194 @@
+use super::*;
+use crate::trie_store::operations::{delete, DeleteResult};
+
+/// Writes `leaves` to an empty trie, returning the resulting root.
+fn write_to_empty_trie<'a, R, S, E>(
+    correlation_id: CorrelationId,
+    environment: &'a R,
+    store: &S,
+    empty_root: &Blake2bHash,
+    leaves: &[TestTrie],
+) -> Result<Blake2bHash, E>
+where
+    R: TransactionSource<'a, Handle = S::Handle>,
+    S: TrieStore<TestKey, TestValue>,
+    S::Error: From<R::Error>,
+    E: From<R::Error> + From<S::Error> + From<types::bytesrepr::Error>,
+{
+    let mut root = *empty_root;
+    for result in write_leaves::<_, _, _, _, E>(correlation_id, environment, store, &root, leaves)?
+    {
+        match result {
+            WriteResult::Written(root_hash) => root = root_hash,
+            _ => panic!("write_leaves resulted in non-write"),
+        }
+    }
+    Ok(root)
+}
+
+fn delete_key<'a, R, S, E>(
+    correlation_id: CorrelationId,
+    environment: &'a R,
+    store: &S,
+    root: &Blake2bHash,
+    key: &TestKey,
+) -> Result<DeleteResult, E>
+where
+    R: TransactionSource<'a, Handle = S::Handle>,
+    S: TrieStore<TestKey, TestValue>,
+    S::Error: From<R::Error>,
+    E: From<R::Error> + From<S::Error> + From<types::bytesrepr::Error>,
+{
+    let mut txn = environment.create_read_write_txn()?;
+    let result = delete::<TestKey, TestValue, _, _, E>(correlation_id, &mut txn, store, root, key)?;
+    txn.commit()?;
+    Ok(result)
+}
+
+/// Checks that deleting any one of `leaves` results in the root of a trie which never contained
+/// it.
+fn delete_matches_never_written<'a, R, S, E>(
+    correlation_id: CorrelationId,
+    environment: &'a R,
+    store: &S,
+    empty_root: &Blake2bHash,
+    leaves: &[TestTrie],
+) -> Result<(), E>
+where
+    R: TransactionSource<'a, Handle = S::Handle>,
+    S: TrieStore<TestKey, TestValue>,
+    S::Error: From<R::Error>,
+    E: From<R::Error> + From<S::Error> + From<types::bytesrepr::Error>,
+{
+    for num_leaves in 1..=leaves.len() {
+        let used = &leaves[..num_leaves];
+        let full_root =
+            write_to_empty_trie::<_, _, E>(correlation_id, environment, store, empty_root, used)?;
+
+        for (index, deleted) in used.iter().enumerate() {
+            let remaining: Vec<TestTrie> = used
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| *i != index)
+                .map(|(_, leaf)| leaf.to_owned())
+                .collect();
+            let expected_root = write_to_empty_trie::<_, _, E>(
+                correlation_id,
+                environment,
+                store,
+                empty_root,
+                &remaining,
+            )?;
+
+            let key = deleted.key().expect("leaves should only contain leaves");
+            let result =
+                delete_key::<_, _, E>(correlation_id, environment, store, &full_root, key)?;
+            assert_eq!(result, DeleteResult::Deleted(expected_root));
+
+            check_leaves::<_, _, _, _, E>(
+                correlation_id,
+                environment,
+                store,
+                &expected_root,
+                &remaining,
+                &[deleted.to_owned()],
+            )?;
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn lmdb_delete_matches_never_written() {
+    let correlation_id = CorrelationId::new();
+    let (empty_root, tries) = TEST_TRIE_GENERATORS[0]().unwrap();
+    let context = LmdbTestContext::new(&tries).unwrap();
+
+    for leaves in &[
+        &TEST_LEAVES,
+        &TEST_LEAVES_NON_COLLIDING,
+        &TEST_LEAVES_ADJACENTS,
+    ] {
+        delete_matches_never_written::<_, _, error::Error>(
+            correlation_id,
+            &context.environment,
+            &context.store,
+            &empty_root,
+            *leaves,
+        )
+        .unwrap();
+    }
+}
+
+#[test]
+fn sled_delete_matches_never_written() {
+    let correlation_id = CorrelationId::new();
+    let (empty_root, tries) = TEST_TRIE_GENERATORS[0]().unwrap();
+    let context = SledTestContext::new(&tries).unwrap();
+
+    for leaves in &[
+        &TEST_LEAVES,
+        &TEST_LEAVES_NON_COLLIDING,
+        &TEST_LEAVES_ADJACENTS,
+    ] {
+        delete_matches_never_written::<_, _, error::Error>(
+            correlation_id,
+            &context.environment,
+            &context.store,
+            &empty_root,
+            *leaves,
+        )
+        .unwrap();
+    }
+}
+
+#[test]
+fn in_memory_delete_matches_never_written() {
+    let correlation_id = CorrelationId::new();
+    let (empty_root, tries) = TEST_TRIE_GENERATORS[0]().unwrap();
+    let context = InMemoryTestContext::new(&tries).unwrap();
+
+    for leaves in &[
+        &TEST_LEAVES,
+        &TEST_LEAVES_NON_COLLIDING,
+        &TEST_LEAVES_ADJACENTS,
+    ] {
+        delete_matches_never_written::<_, _, in_memory::Error>(
+            correlation_id,
+            &context.environment,
+            &context.store,
+            &empty_root,
+            *leaves,
+        )
+        .unwrap();
+    }
+}
+
+#[test]
+fn delete_of_missing_key_or_root_has_no_effect() {
+    let correlation_id = CorrelationId::new();
+    let (root_hash, tries) = TEST_TRIE_GENERATORS[3]().unwrap();
+    let context = InMemoryTestContext::new(&tries).unwrap();
+
+    let missing_key = TEST_LEAVES[4].key().unwrap();
+    let result = delete_key::<_, _, in_memory::Error>(
+        correlation_id,
+        &context.environment,
+        &context.store,
+        &root_hash,
+        missing_key,
+    )
+    .unwrap();
+    assert_eq!(result, DeleteResult::DoesNotExist);
+
+    let existing_key = TEST_LEAVES[0].key().unwrap();
+    let result = delete_key::<_, _, in_memory::Error>(
+        correlation_id,
+        &context.environment,
+        &context.store,
+        &Blake2bHash::new(&[0u8]),
+        existing_key,
+    )
+    .unwrap();
+    assert_eq!(result, DeleteResult::RootNotFound);
+}
@@ -0,0 +1,180 @@
+use std::collections::BTreeMap;
+
+use super::*;
+use crate::trie_store::operations::diff::{self, DiffResult, KeyDiff};
+
+/// Returns every key-value pair at `root`, using reads rather than a walk of the trie.
+fn contents<K, V, T, S, E>(
+    correlation_id: CorrelationId,
+    txn: &T,
+    store: &S,
+    root: &Blake2bHash,
+) -> Result<BTreeMap<K, V>, E>
+where
+    K: ToBytes + FromBytes + Clone + Eq + Ord + std::fmt::Debug,
+    V: ToBytes + FromBytes + Clone + Eq + std::fmt::Debug,
+    T: Readable<Handle = S::Handle>,
+    S: TrieStore<K, V>,
+    S::Error: From<T::Error>,
+    E: From<S::Error> + From<types::bytesrepr::Error>,
+{
+    let keys = operations::keys::<K, V, _, _>(correlation_id, txn, store, root)
+        .collect::<Result<Vec<K>, S::Error>>()?;
+    let mut ret = BTreeMap::new();
+    for key in keys {
+        match read::<_, _, _, _, E>(correlation_id, txn, store, root, &key)? {
+            ReadResult::Found(value) => {
+                ret.insert(key, value);
+            }
+            _ => panic!("key should be readable"),
+        }
+    }
+    Ok(ret)
+}
+
+fn expected_diff<K: Ord + Clone, V: Eq + Clone>(
+    left: &BTreeMap<K, V>,
+    right: &BTreeMap<K, V>,
+) -> Vec<KeyDiff<K, V>> {
+    let mut keys: Vec<&K> = left.keys().chain(right.keys()).collect();
+    keys.sort();
+    keys.dedup();
+    keys.into_iter()
+        .filter_map(|key| {
+            let left_value = left.get(key).cloned();
+            let right_value = right.get(key).cloned();
+            if left_value == right_value {
+                None
+            } else {
+                Some((key.clone(), left_value, right_value))
+            }
+        })
+        .collect()
+}
+
+fn diff_matches_contents<'a, R, S, E>(
+    correlation_id: CorrelationId,
+    environment: &'a R,
+    store: &S,
+    roots: &[Blake2bHash],
+) -> Result<(), E>
+where
+    R: TransactionSource<'a, Handle = S::Handle>,
+    S: TrieStore<TestKey, TestValue>,
+    S::Error: From<R::Error>,
+    E: From<R::Error> + From<S::Error> + From<types::bytesrepr::Error>,
+{
+    let txn = environment.create_read_txn()?;
+    let mut all_contents = Vec::new();
+    for root in roots {
+        all_contents.push(contents::<_, _, _, _, E>(
+            correlation_id,
+            &txn,
+            store,
+            root,
+        )?);
+    }
+
+    for (left_root, left_contents) in roots.iter().zip(&all_contents) {
+        for (right_root, right_contents) in roots.iter().zip(&all_contents) {
+            let actual = diff::diff::<TestKey, TestValue, _, _, E>(
+                correlation_id,
+                &txn,
+                store,
+                left_root,
+                right_root,
+            )?;
+            let expected = expected_diff(left_contents, right_contents);
+            assert_eq!(actual, DiffResult::Success(expected));
+        }
+    }
+
+    txn.commit()?;
+    Ok(())
+}
+
+fn diff_of_tries<'a, R, S, E>(
+    correlation_id: CorrelationId,
+    environment: &'a R,
+    store: &S,
+) -> Result<(), E>
+where
+    R: TransactionSource<'a, Handle = S::Handle>,
+    S: TrieStore<TestKey, TestValue>,
+    S::Error: From<R::Error>,
+    E: From<R::Error> + From<S::Error> + From<types::bytesrepr::Error>,
+{
+    let mut roots = Vec::new();
+    for generator in &TEST_TRIE_GENERATORS {
+        let (root_hash, tries) = generator()?;
+        put_tries::<_, _, _, _, E>(environment, store, &tries)?;
+        roots.push(root_hash);
+    }
+    let empty_root = roots[0];
+    let full_root = *roots.last().unwrap();
+
+    for (root, leaves) in &[
+        (full_root, &TEST_LEAVES_UPDATED),
+        (empty_root, &TEST_LEAVES_ADJACENTS),
+    ] {
+        let results =
+            write_leaves::<_, _, _, _, E>(correlation_id, environment, store, root, *leaves)?;
+        for result in results {
+            match result {
+                WriteResult::Written(root_hash) => roots.push(root_hash),
+                _ => panic!("write_leaves resulted in non-write"),
+            }
+        }
+    }
+
+    diff_matches_contents::<_, _, E>(correlation_id, environment, store, &roots)
+}
+
+#[test]
+fn lmdb_diff_matches_contents_of_tries() {
+    let correlation_id = CorrelationId::new();
+    let context = LmdbTestContext::new(EMPTY_HASHED_TEST_TRIES).unwrap();
+
+    diff_of_tries::<_, _, error::Error>(correlation_id, &context.environment, &context.store)
+        .unwrap();
+}
+
+#[test]
+fn sled_diff_matches_contents_of_tries() {
+    let correlation_id = CorrelationId::new();
+    let context = SledTestContext::new(EMPTY_HASHED_TEST_TRIES).unwrap();
+
+    diff_of_tries::<_, _, error::Error>(correlation_id, &context.environment, &context.store)
+        .unwrap();
+}
+
+#[test]
+fn in_memory_diff_matches_contents_of_tries() {
+    let correlation_id = CorrelationId::new();
+    let context = InMemoryTestContext::new(EMPTY_HASHED_TEST_TRIES).unwrap();
+
+    diff_of_tries::<_, _, in_memory::Error>(correlation_id, &context.environment, &context.store)
+        .unwrap();
+}
+
+#[test]
+fn diff_with_unknown_root_returns_root_not_found() {
+    let correlation_id = CorrelationId::new();
+    let (root_hash, tries) = TEST_TRIE_GENERATORS[2]().unwrap();
+    let context = InMemoryTestContext::new(&tries).unwrap();
+    let unknown_root = Blake2bHash::new(&[0u8]);
+
+    let txn = context.environment.create_read_txn().unwrap();
+    for (left, right) in &[(root_hash, unknown_root), (unknown_root, root_hash)] {
+        let result = diff::diff::<TestKey, TestValue, _, _, in_memory::Error>(
+            correlation_id,
+            &txn,
+            &context.store,
+            left,
+            right,
+        )
+        .unwrap();
+        assert_eq!(result, DiffResult::RootNotFound(unknown_root));
+    }
+    txn.commit().unwrap();
+}
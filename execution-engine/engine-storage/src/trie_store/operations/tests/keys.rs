@@ -7,8 +7,8 @@ mod partial_tries {
         trie_store::operations::{
             self,
             tests::{
-                InMemoryTestContext, LmdbTestContext, TestKey, TestValue, TEST_LEAVES,
-                TEST_TRIE_GENERATORS,
+                InMemoryTestContext, LmdbTestContext, SledTestContext, TestKey, TestValue,
+                TEST_LEAVES, TEST_TRIE_GENERATORS,
             },
         },
     };
@@ -49,6 +49,42 @@ mod partial_tries {
         }
     }
 
+    #[test]
+    fn sled_keys_from_n_leaf_partial_trie_had_expected_results() {
+        for (num_leaves, generator) in TEST_TRIE_GENERATORS.iter().enumerate() {
+            let correlation_id = CorrelationId::new();
+            let (root_hash, tries) = generator().unwrap();
+            let context = SledTestContext::new(&tries).unwrap();
+            let test_leaves = TEST_LEAVES;
+            let (used, _) = test_leaves.split_at(num_leaves);
+
+            let expected = {
+                let mut tmp = used
+                    .iter()
+                    .filter_map(Trie::key)
+                    .cloned()
+                    .collect::<Vec<TestKey>>();
+                tmp.sort();
+                tmp
+            };
+            let actual = {
+                let txn = context.environment.create_read_txn().unwrap();
+                let mut tmp = operations::keys::<TestKey, TestValue, _, _>(
+                    correlation_id,
+                    &txn,
+                    &context.store,
+                    &root_hash,
+                )
+                .filter_map(Result::ok)
+                .collect::<Vec<TestKey>>();
+                txn.commit().unwrap();
+                tmp.sort();
+                tmp
+            };
+            assert_eq!(actual, expected);
+        }
+    }
+
     #[test]
     fn in_memory_keys_from_n_leaf_partial_trie_had_expected_results() {
         for (num_leaves, generator) in TEST_TRIE_GENERATORS.iter().enumerate() {
@@ -307,3 +343,80 @@ mod keys_with_prefix_iterator {
         test_prefix(&[0, 0, 0, 0, 0, 0, 1]); // 1 leaf
     }
 }
+
+mod entries_with_prefix_iterator {
+    use engine_shared::newtypes::CorrelationId;
+
+    use crate::{
+        transaction_source::TransactionSource,
+        trie::Trie,
+        trie_store::operations::{
+            self,
+            tests::{create_6_leaf_trie, InMemoryTestContext, TestKey, TestValue, TEST_LEAVES},
+        },
+    };
+
+    fn expected_entries(prefix: &[u8], start_after: Option<&[u8]>) -> Vec<(TestKey, TestValue)> {
+        let mut tmp = TEST_LEAVES
+            .iter()
+            .filter_map(|leaf| match leaf {
+                Trie::Leaf { key, value } => Some((*key, *value)),
+                _ => None,
+            })
+            .filter(|(key, _)| key.0.starts_with(prefix))
+            .filter(|(key, _)| start_after.map_or(true, |start_after| &key.0[..] > start_after))
+            .collect::<Vec<(TestKey, TestValue)>>();
+        tmp.sort_by_key(|(key, _)| *key);
+        tmp
+    }
+
+    fn test_prefix(prefix: &[u8], start_after: Option<&[u8]>) {
+        let correlation_id = CorrelationId::new();
+        let (root_hash, tries) = create_6_leaf_trie().expect("should create a trie");
+        let context = InMemoryTestContext::new(&tries).expect("should create a new context");
+        let txn = context
+            .environment
+            .create_read_txn()
+            .expect("should create a read txn");
+        let expected = expected_entries(prefix, start_after);
+        // The entries come out in order, so they are not sorted here.
+        let actual = operations::entries_with_prefix::<TestKey, TestValue, _, _>(
+            correlation_id,
+            &txn,
+            &context.store,
+            &root_hash,
+            prefix,
+            start_after,
+        )
+        .collect::<Result<Vec<_>, _>>()
+        .expect("should read entries");
+        assert_eq!(
+            expected, actual,
+            "prefix {:?}, start after {:?}",
+            prefix, start_after
+        );
+    }
+
+    #[test]
+    fn test_prefixes_and_starts() {
+        let prefixes: [&[u8]; 6] = [&[], &[0], &[0, 0], &[0, 0, 0, 0], &[0, 1], &[0, 1, 1]];
+        let mut starts: Vec<Vec<u8>> = TEST_LEAVES
+            .iter()
+            .filter_map(Trie::key)
+            .map(|key| key.0.to_vec())
+            .collect();
+        // Starts which are not keys, including ones which are shorter or longer than a key
+        starts.push(vec![]);
+        starts.push(vec![0]);
+        starts.push(vec![0, 0, 1]);
+        starts.push(vec![0, 0, 0, 0, 0, 0, 0, 5]);
+        starts.push(vec![0, 0, 0, 1, 0, 0, 0]);
+        starts.push(vec![1]);
+        for prefix in prefixes.iter() {
+            test_prefix(prefix, None);
+            for start_after in &starts {
+                test_prefix(prefix, Some(start_after));
+            }
+        }
+    }
+}
@@ -1,7 +1,14 @@
+mod batch;
+mod delete;
+mod diff;
 mod keys;
 mod proptests;
+mod prune;
 mod read;
+mod read_with_proof;
 mod scan;
+mod stats;
+mod verify;
 mod write;
 
 use std::{collections::HashMap, convert};
@@ -15,8 +22,8 @@ use types::bytesrepr::{self, FromBytes, ToBytes};
 use crate::{
     error::{self, in_memory},
     transaction_source::{
-        in_memory::InMemoryEnvironment, lmdb::LmdbEnvironment, Readable, Transaction,
-        TransactionSource,
+        in_memory::InMemoryEnvironment, lmdb::LmdbEnvironment, sled::SledEnvironment, Readable,
+        Transaction, TransactionSource,
     },
     trie::{Pointer, Trie},
     trie_store::{
@@ -24,6 +31,7 @@ use crate::{
         in_memory::InMemoryTrieStore,
         lmdb::LmdbTrieStore,
         operations::{self, read, write, ReadResult, WriteResult},
+        sled::SledTrieStore,
         TrieStore,
     },
     TEST_MAP_SIZE,
@@ -536,6 +544,41 @@ impl LmdbTestContext {
     }
 }
 
+// A context for holding sled test resources
+struct SledTestContext {
+    environment: SledEnvironment,
+    store: SledTrieStore,
+    // Declared last so that the directory outlives the database
+    _temp_dir: TempDir,
+}
+
+impl SledTestContext {
+    fn new<K, V>(tries: &[HashedTrie<K, V>]) -> Result<Self, failure::Error>
+    where
+        K: FromBytes + ToBytes,
+        V: FromBytes + ToBytes,
+    {
+        let _temp_dir = tempdir()?;
+        let environment = SledEnvironment::new(&_temp_dir.path().to_path_buf())?;
+        let store = SledTrieStore::new(&environment, None)?;
+        put_tries::<_, _, _, _, error::Error>(&environment, &store, tries)?;
+        Ok(SledTestContext {
+            environment,
+            store,
+            _temp_dir,
+        })
+    }
+
+    fn update<K, V>(&self, tries: &[HashedTrie<K, V>]) -> Result<(), failure::Error>
+    where
+        K: ToBytes,
+        V: ToBytes,
+    {
+        put_tries::<_, _, _, _, error::Error>(&self.environment, &self.store, tries)?;
+        Ok(())
+    }
+}
+
 // A context for holding in-memory test resources
 struct InMemoryTestContext {
     environment: InMemoryEnvironment,
@@ -1,8 +1,9 @@
-use std::ops::RangeInclusive;
+use std::{collections::BTreeMap, ops::RangeInclusive};
 
 use proptest::{
     array,
     collection::vec,
+    option,
     prelude::{any, proptest, Strategy},
 };
 
@@ -49,6 +50,33 @@ fn lmdb_roundtrip_succeeds(pairs: &[(TestKey, TestValue)]) -> bool {
     .unwrap()
 }
 
+fn sled_roundtrip_succeeds(pairs: &[(TestKey, TestValue)]) -> bool {
+    let correlation_id = CorrelationId::new();
+    let (root_hash, tries) = TEST_TRIE_GENERATORS[0]().unwrap();
+    let context = SledTestContext::new(&tries).unwrap();
+    let mut states_to_check = vec![];
+
+    let root_hashes = write_pairs::<_, _, _, _, error::Error>(
+        correlation_id,
+        &context.environment,
+        &context.store,
+        &root_hash,
+        pairs,
+    )
+    .unwrap();
+
+    states_to_check.extend(root_hashes);
+
+    check_pairs::<_, _, _, _, error::Error>(
+        correlation_id,
+        &context.environment,
+        &context.store,
+        &states_to_check,
+        &pairs,
+    )
+    .unwrap()
+}
+
 fn in_memory_roundtrip_succeeds(pairs: &[(TestKey, TestValue)]) -> bool {
     let correlation_id = CorrelationId::new();
     let (root_hash, tries) = TEST_TRIE_GENERATORS[0]().unwrap();
@@ -76,14 +104,265 @@ fn in_memory_roundtrip_succeeds(pairs: &[(TestKey, TestValue)]) -> bool {
     .unwrap()
 }
 
+/// Writes `pairs`, deletes every other key, and checks that the resulting root is the one of a
+/// trie to which only the remaining keys were written.
+fn delete_matches_never_written<'a, R, S, E>(
+    environment: &'a R,
+    store: &S,
+    empty_root: &Blake2bHash,
+    pairs: &[(TestKey, TestValue)],
+) -> Result<bool, E>
+where
+    R: TransactionSource<'a, Handle = S::Handle>,
+    S: TrieStore<TestKey, TestValue>,
+    S::Error: From<R::Error>,
+    E: From<R::Error> + From<S::Error> + From<types::bytesrepr::Error>,
+{
+    let correlation_id = CorrelationId::new();
+    let pairs: Vec<(TestKey, TestValue)> = pairs
+        .iter()
+        .cloned()
+        .collect::<BTreeMap<_, _>>()
+        .into_iter()
+        .collect();
+    let (deleted, remaining): (Vec<_>, Vec<_>) = pairs
+        .iter()
+        .enumerate()
+        .partition(|(index, _)| index % 2 == 0);
+    let remaining: Vec<(TestKey, TestValue)> =
+        remaining.into_iter().map(|(_, pair)| *pair).collect();
+
+    let full_root =
+        write_pairs::<_, _, _, _, E>(correlation_id, environment, store, empty_root, &pairs)?
+            .last()
+            .cloned()
+            .unwrap_or(*empty_root);
+    let expected_root =
+        write_pairs::<_, _, _, _, E>(correlation_id, environment, store, empty_root, &remaining)?
+            .last()
+            .cloned()
+            .unwrap_or(*empty_root);
+
+    let mut root = full_root;
+    let mut txn = environment.create_read_write_txn()?;
+    for (_, (key, _)) in deleted {
+        match operations::delete::<TestKey, TestValue, _, _, E>(
+            correlation_id,
+            &mut txn,
+            store,
+            &root,
+            key,
+        )? {
+            operations::DeleteResult::Deleted(root_hash) => root = root_hash,
+            _ => return Ok(false),
+        }
+    }
+    txn.commit()?;
+
+    Ok(root == expected_root)
+}
+
+fn lmdb_delete_succeeds(pairs: &[(TestKey, TestValue)]) -> bool {
+    let (root_hash, tries) = TEST_TRIE_GENERATORS[0]().unwrap();
+    let context = LmdbTestContext::new(&tries).unwrap();
+
+    delete_matches_never_written::<_, _, error::Error>(
+        &context.environment,
+        &context.store,
+        &root_hash,
+        pairs,
+    )
+    .unwrap()
+}
+
+fn sled_delete_succeeds(pairs: &[(TestKey, TestValue)]) -> bool {
+    let (root_hash, tries) = TEST_TRIE_GENERATORS[0]().unwrap();
+    let context = SledTestContext::new(&tries).unwrap();
+
+    delete_matches_never_written::<_, _, error::Error>(
+        &context.environment,
+        &context.store,
+        &root_hash,
+        pairs,
+    )
+    .unwrap()
+}
+
+fn in_memory_delete_succeeds(pairs: &[(TestKey, TestValue)]) -> bool {
+    let (root_hash, tries) = TEST_TRIE_GENERATORS[0]().unwrap();
+    let context = InMemoryTestContext::new(&tries).unwrap();
+
+    delete_matches_never_written::<_, _, in_memory::Error>(
+        &context.environment,
+        &context.store,
+        &root_hash,
+        pairs,
+    )
+    .unwrap()
+}
+
+/// Applies `updates` to a trie holding `initial`, both one key at a time and as a batch, and checks
+/// that both result in the same trie.
+fn write_batch_matches_sequential<'a, R, S, E>(
+    environment: &'a R,
+    store: &S,
+    empty_root: &Blake2bHash,
+    initial: &[(TestKey, TestValue)],
+    updates: &[(TestKey, Option<TestValue>)],
+) -> Result<bool, E>
+where
+    R: TransactionSource<'a, Handle = S::Handle>,
+    S: TrieStore<TestKey, TestValue>,
+    S::Error: From<R::Error>,
+    E: From<R::Error> + From<S::Error> + From<types::bytesrepr::Error>,
+{
+    let correlation_id = CorrelationId::new();
+    let initial_root =
+        write_pairs::<_, _, _, _, E>(correlation_id, environment, store, empty_root, initial)?
+            .last()
+            .cloned()
+            .unwrap_or(*empty_root);
+
+    let mut txn = environment.create_read_write_txn()?;
+    let mut expected_root = initial_root;
+    let mut expected_contents: BTreeMap<TestKey, TestValue> = initial.iter().cloned().collect();
+    for (key, maybe_value) in updates {
+        match maybe_value {
+            Some(value) => {
+                match write::<_, _, _, _, E>(
+                    correlation_id,
+                    &mut txn,
+                    store,
+                    &expected_root,
+                    key,
+                    value,
+                )? {
+                    WriteResult::Written(root_hash) => expected_root = root_hash,
+                    WriteResult::AlreadyExists => (),
+                    WriteResult::RootNotFound => return Ok(false),
+                }
+                expected_contents.insert(*key, *value);
+            }
+            None => {
+                match operations::delete::<TestKey, TestValue, _, _, E>(
+                    correlation_id,
+                    &mut txn,
+                    store,
+                    &expected_root,
+                    key,
+                )? {
+                    operations::DeleteResult::Deleted(root_hash) => expected_root = root_hash,
+                    operations::DeleteResult::DoesNotExist => (),
+                    operations::DeleteResult::RootNotFound => return Ok(false),
+                }
+                expected_contents.remove(key);
+            }
+        }
+    }
+
+    let batch_root = match operations::batch::write_batch::<_, _, _, _, E>(
+        correlation_id,
+        &mut txn,
+        store,
+        &initial_root,
+        updates.to_vec(),
+    )? {
+        WriteResult::Written(root_hash) => root_hash,
+        WriteResult::AlreadyExists => initial_root,
+        WriteResult::RootNotFound => return Ok(false),
+    };
+    if batch_root != expected_root {
+        return Ok(false);
+    }
+
+    // Every node of the batch-written trie must have been stored
+    let keys = initial
+        .iter()
+        .map(|(key, _)| key)
+        .chain(updates.iter().map(|(key, _)| key));
+    for key in keys {
+        let expected = match expected_contents.get(key) {
+            Some(value) => ReadResult::Found(*value),
+            None => ReadResult::NotFound,
+        };
+        if read::<_, _, _, _, E>(correlation_id, &txn, store, &batch_root, key)? != expected {
+            return Ok(false);
+        }
+    }
+    txn.commit()?;
+
+    Ok(true)
+}
+
+fn lmdb_write_batch_succeeds(
+    initial: &[(TestKey, TestValue)],
+    updates: &[(TestKey, Option<TestValue>)],
+) -> bool {
+    let (root_hash, tries) = TEST_TRIE_GENERATORS[0]().unwrap();
+    let context = LmdbTestContext::new(&tries).unwrap();
+
+    write_batch_matches_sequential::<_, _, error::Error>(
+        &context.environment,
+        &context.store,
+        &root_hash,
+        initial,
+        updates,
+    )
+    .unwrap()
+}
+
+fn sled_write_batch_succeeds(
+    initial: &[(TestKey, TestValue)],
+    updates: &[(TestKey, Option<TestValue>)],
+) -> bool {
+    let (root_hash, tries) = TEST_TRIE_GENERATORS[0]().unwrap();
+    let context = SledTestContext::new(&tries).unwrap();
+
+    write_batch_matches_sequential::<_, _, error::Error>(
+        &context.environment,
+        &context.store,
+        &root_hash,
+        initial,
+        updates,
+    )
+    .unwrap()
+}
+
+fn in_memory_write_batch_succeeds(
+    initial: &[(TestKey, TestValue)],
+    updates: &[(TestKey, Option<TestValue>)],
+) -> bool {
+    let (root_hash, tries) = TEST_TRIE_GENERATORS[0]().unwrap();
+    let context = InMemoryTestContext::new(&tries).unwrap();
+
+    write_batch_matches_sequential::<_, _, in_memory::Error>(
+        &context.environment,
+        &context.store,
+        &root_hash,
+        initial,
+        updates,
+    )
+    .unwrap()
+}
+
 fn test_key_arb() -> impl Strategy<Value = TestKey> {
     array::uniform7(any::<u8>()).prop_map(TestKey)
 }
 
+/// Keys drawn from a small alphabet, so that many of them share prefixes.
+fn test_colliding_key_arb() -> impl Strategy<Value = TestKey> {
+    array::uniform7(0u8..3).prop_map(TestKey)
+}
+
 fn test_value_arb() -> impl Strategy<Value = TestValue> {
     array::uniform6(any::<u8>()).prop_map(TestValue)
 }
 
+/// Values drawn from a small alphabet, so that some writes leave the value unchanged.
+fn test_colliding_value_arb() -> impl Strategy<Value = TestValue> {
+    array::uniform6(0u8..2).prop_map(TestValue)
+}
+
 proptest! {
     #[test]
     fn prop_in_memory_roundtrip_succeeds(inputs in vec((test_key_arb(), test_value_arb()), get_range())) {
@@ -94,4 +373,57 @@ proptest! {
     fn prop_lmdb_roundtrip_succeeds(inputs in vec((test_key_arb(), test_value_arb()), get_range())) {
         assert!(lmdb_roundtrip_succeeds(&inputs));
     }
+
+    #[test]
+    fn prop_sled_roundtrip_succeeds(inputs in vec((test_key_arb(), test_value_arb()), get_range())) {
+        assert!(sled_roundtrip_succeeds(&inputs));
+    }
+
+    #[test]
+    fn prop_in_memory_delete_succeeds(inputs in vec((test_key_arb(), test_value_arb()), get_range())) {
+        assert!(in_memory_delete_succeeds(&inputs));
+    }
+
+    #[test]
+    fn prop_lmdb_delete_succeeds(inputs in vec((test_key_arb(), test_value_arb()), get_range())) {
+        assert!(lmdb_delete_succeeds(&inputs));
+    }
+
+    #[test]
+    fn prop_sled_delete_succeeds(inputs in vec((test_key_arb(), test_value_arb()), get_range())) {
+        assert!(sled_delete_succeeds(&inputs));
+    }
+
+    #[test]
+    fn prop_in_memory_write_batch_succeeds(
+        initial in vec((test_colliding_key_arb(), test_colliding_value_arb()), get_range()),
+        updates in vec(
+            (test_colliding_key_arb(), option::of(test_colliding_value_arb())),
+            get_range()
+        )
+    ) {
+        assert!(in_memory_write_batch_succeeds(&initial, &updates));
+    }
+
+    #[test]
+    fn prop_lmdb_write_batch_succeeds(
+        initial in vec((test_colliding_key_arb(), test_colliding_value_arb()), get_range()),
+        updates in vec(
+            (test_colliding_key_arb(), option::of(test_colliding_value_arb())),
+            get_range()
+        )
+    ) {
+        assert!(lmdb_write_batch_succeeds(&initial, &updates));
+    }
+
+    #[test]
+    fn prop_sled_write_batch_succeeds(
+        initial in vec((test_colliding_key_arb(), test_colliding_value_arb()), get_range()),
+        updates in vec(
+            (test_colliding_key_arb(), option::of(test_colliding_value_arb())),
+            get_range()
+        )
+    ) {
+        assert!(sled_write_batch_succeeds(&initial, &updates));
+    }
 }
@@ -0,0 +1,369 @@
+use super::*;
+use crate::{
+    error::TrieNotFound,
+    transaction_source::Enumerable,
+    trie_store::operations::prune::{self, PruneResult, PruneStepResult, TriePruner},
+};
+
+fn stored_hashes<'a, R, S, E>(environment: &'a R, store: &S) -> Result<Vec<Blake2bHash>, E>
+where
+    R: TransactionSource<'a, Handle = S::Handle>,
+    S: TrieStore<TestKey, TestValue>,
+    S::Error: From<R::Error>,
+    E: From<R::Error> + From<S::Error> + From<types::bytesrepr::Error>,
+{
+    let txn = environment.create_read_txn()?;
+    let keys = txn.keys_after(store.handle(), None, usize::max_value())?;
+    txn.commit()?;
+    keys.into_iter()
+        .map(|key| bytesrepr::deserialize(key).map_err(Into::into))
+        .collect()
+}
+
+fn prune_keeps_only_retained_history<'a, R, S, E>(
+    correlation_id: CorrelationId,
+    environment: &'a R,
+    store: &S,
+    root_hash: &Blake2bHash,
+) -> Result<(), E>
+where
+    R: TransactionSource<'a, Handle = S::Handle>,
+    S: TrieStore<TestKey, TestValue>,
+    S::Error: From<R::Error>,
+    E: From<R::Error> + From<S::Error> + From<types::bytesrepr::Error> + From<TrieNotFound>,
+{
+    let states = writes_to_n_leaf_empty_trie_had_expected_results::<_, _, _, _, E>(
+        correlation_id,
+        environment,
+        store,
+        &[*root_hash],
+        &TEST_LEAVES,
+    )?;
+    let retained_root = *states.last().unwrap();
+
+    let stats = {
+        let mut txn = environment.create_read_write_txn()?;
+        let result = prune::prune::<TestKey, TestValue, _, _, E>(
+            correlation_id,
+            &mut txn,
+            store,
+            &[retained_root],
+        )?;
+        txn.commit()?;
+        match result {
+            PruneResult::Success(stats) => stats,
+            PruneResult::RootNotFound(_) => panic!("retained root should exist"),
+        }
+    };
+
+    let remaining = stored_hashes::<_, _, E>(environment, store)?;
+    assert_eq!(remaining.len(), stats.retained);
+    assert_eq!(stats.examined, stats.retained + stats.deleted);
+    assert!(stats.deleted > 0);
+
+    // The retained root is intact, while earlier roots are gone
+    check_leaves::<_, _, _, _, E>(
+        correlation_id,
+        environment,
+        store,
+        &retained_root,
+        &TEST_LEAVES,
+        &[],
+    )?;
+    let txn = environment.create_read_txn()?;
+    for state in &states[..states.len() - 1] {
+        let maybe_trie: Option<TestTrie> = store.get(&txn, state)?;
+        assert!(maybe_trie.is_none());
+    }
+    txn.commit()?;
+
+    Ok(())
+}
+
+#[test]
+fn lmdb_prune_keeps_only_retained_history() {
+    let correlation_id = CorrelationId::new();
+    let (root_hash, tries) = TEST_TRIE_GENERATORS[0]().unwrap();
+    let context = LmdbTestContext::new(&tries).unwrap();
+
+    prune_keeps_only_retained_history::<_, _, error::Error>(
+        correlation_id,
+        &context.environment,
+        &context.store,
+        &root_hash,
+    )
+    .unwrap();
+}
+
+#[test]
+fn sled_prune_keeps_only_retained_history() {
+    let correlation_id = CorrelationId::new();
+    let (root_hash, tries) = TEST_TRIE_GENERATORS[0]().unwrap();
+    let context = SledTestContext::new(&tries).unwrap();
+
+    prune_keeps_only_retained_history::<_, _, error::Error>(
+        correlation_id,
+        &context.environment,
+        &context.store,
+        &root_hash,
+    )
+    .unwrap();
+}
+
+#[test]
+fn in_memory_prune_keeps_only_retained_history() {
+    let correlation_id = CorrelationId::new();
+    let (root_hash, tries) = TEST_TRIE_GENERATORS[0]().unwrap();
+    let context = InMemoryTestContext::new(&tries).unwrap();
+
+    prune_keeps_only_retained_history::<_, _, in_memory::Error>(
+        correlation_id,
+        &context.environment,
+        &context.store,
+        &root_hash,
+    )
+    .unwrap();
+}
+
+fn incremental_prune_survives_interleaved_writes<'a, R, S, E>(
+    correlation_id: CorrelationId,
+    environment: &'a R,
+    store: &S,
+    root_hash: &Blake2bHash,
+) -> Result<(), E>
+where
+    R: TransactionSource<'a, Handle = S::Handle>,
+    S: TrieStore<TestKey, TestValue>,
+    S::Error: From<R::Error>,
+    E: From<R::Error> + From<S::Error> + From<types::bytesrepr::Error> + From<TrieNotFound>,
+{
+    let states = writes_to_n_leaf_empty_trie_had_expected_results::<_, _, _, _, E>(
+        correlation_id,
+        environment,
+        store,
+        &[*root_hash],
+        &TEST_LEAVES,
+    )?;
+    let mut current_root = *states.last().unwrap();
+
+    let mut pruner = TriePruner::new(&[current_root]);
+
+    let mut adjacents = TEST_LEAVES_ADJACENTS.iter();
+    loop {
+        // Mark or sweep a single node
+        let step_result = {
+            let mut txn = environment.create_read_write_txn()?;
+            let result =
+                pruner.step::<TestKey, TestValue, _, _, E>(correlation_id, &mut txn, store, 1)?;
+            txn.commit()?;
+            result
+        };
+        if let PruneStepResult::Complete(_) = step_result {
+            break;
+        }
+        // Then commit a new leaf on top of the retained root, and retain the result
+        if let Some(Trie::Leaf { key, value }) = adjacents.next() {
+            let mut txn = environment.create_read_write_txn()?;
+            if let WriteResult::Written(new_root) =
+                write::<_, _, _, _, E>(correlation_id, &mut txn, store, &current_root, key, value)?
+            {
+                current_root = new_root;
+            }
+            txn.commit()?;
+            pruner.retain(current_root);
+        }
+    }
+
+    let expected: Vec<TestTrie> = TEST_LEAVES
+        .iter()
+        .chain(TEST_LEAVES_ADJACENTS.iter())
+        .cloned()
+        .collect();
+    check_leaves::<_, _, _, _, E>(
+        correlation_id,
+        environment,
+        store,
+        &current_root,
+        &expected,
+        &[],
+    )?;
+
+    let remaining = stored_hashes::<_, _, E>(environment, store)?;
+    assert!(remaining.iter().all(|hash| pruner.is_retained(hash)));
+
+    Ok(())
+}
+
+#[test]
+fn lmdb_incremental_prune_survives_interleaved_writes() {
+    let correlation_id = CorrelationId::new();
+    let (root_hash, tries) = TEST_TRIE_GENERATORS[0]().unwrap();
+    let context = LmdbTestContext::new(&tries).unwrap();
+
+    incremental_prune_survives_interleaved_writes::<_, _, error::Error>(
+        correlation_id,
+        &context.environment,
+        &context.store,
+        &root_hash,
+    )
+    .unwrap();
+}
+
+#[test]
+fn sled_incremental_prune_survives_interleaved_writes() {
+    let correlation_id = CorrelationId::new();
+    let (root_hash, tries) = TEST_TRIE_GENERATORS[0]().unwrap();
+    let context = SledTestContext::new(&tries).unwrap();
+
+    incremental_prune_survives_interleaved_writes::<_, _, error::Error>(
+        correlation_id,
+        &context.environment,
+        &context.store,
+        &root_hash,
+    )
+    .unwrap();
+}
+
+#[test]
+fn in_memory_incremental_prune_survives_interleaved_writes() {
+    let correlation_id = CorrelationId::new();
+    let (root_hash, tries) = TEST_TRIE_GENERATORS[0]().unwrap();
+    let context = InMemoryTestContext::new(&tries).unwrap();
+
+    incremental_prune_survives_interleaved_writes::<_, _, in_memory::Error>(
+        correlation_id,
+        &context.environment,
+        &context.store,
+        &root_hash,
+    )
+    .unwrap();
+}
+
+#[test]
+fn in_memory_prune_with_unknown_root_deletes_nothing() {
+    let correlation_id = CorrelationId::new();
+    let (root_hash, tries) = TEST_TRIE_GENERATORS[6]().unwrap();
+    let context = InMemoryTestContext::new(&tries).unwrap();
+    let unknown_root: Blake2bHash = [1u8; 32].into();
+
+    let mut txn = context.environment.create_read_write_txn().unwrap();
+    let result = prune::prune::<TestKey, TestValue, _, _, in_memory::Error>(
+        correlation_id,
+        &mut txn,
+        &context.store,
+        &[root_hash, unknown_root],
+    )
+    .unwrap();
+    assert_eq!(result, PruneResult::RootNotFound(unknown_root));
+    drop(txn);
+
+    let remaining =
+        stored_hashes::<_, _, in_memory::Error>(&context.environment, &context.store).unwrap();
+    assert_eq!(remaining.len(), tries.len());
+}
+
+fn prune_resumes_from_serialized_pruner<'a, R, S, E>(
+    correlation_id: CorrelationId,
+    environment: &'a R,
+    store: &S,
+    root_hash: &Blake2bHash,
+) -> Result<(), E>
+where
+    R: TransactionSource<'a, Handle = S::Handle>,
+    S: TrieStore<TestKey, TestValue>,
+    S::Error: From<R::Error>,
+    E: From<R::Error> + From<S::Error> + From<types::bytesrepr::Error> + From<TrieNotFound>,
+{
+    let states = writes_to_n_leaf_empty_trie_had_expected_results::<_, _, _, _, E>(
+        correlation_id,
+        environment,
+        store,
+        &[*root_hash],
+        &TEST_LEAVES,
+    )?;
+    let retained_roots = [*states.last().unwrap()];
+
+    // The prune is interrupted once the sweep has started, and carries on with a pruner restored
+    // from the bytes saved at that point
+    let mut pruner = TriePruner::new(&retained_roots);
+    let mut is_restored = false;
+    let stats = loop {
+        if !is_restored && pruner.stats().examined > 0 {
+            let saved = pruner.to_bytes()?;
+            pruner = bytesrepr::deserialize(saved)?;
+            assert_eq!(pruner.roots(), &retained_roots);
+            is_restored = true;
+        }
+        let mut txn = environment.create_read_write_txn()?;
+        let result =
+            pruner.step::<TestKey, TestValue, _, _, E>(correlation_id, &mut txn, store, 1)?;
+        txn.commit()?;
+        if let PruneStepResult::Complete(stats) = result {
+            break stats;
+        }
+    };
+    assert!(is_restored);
+
+    let remaining = stored_hashes::<_, _, E>(environment, store)?;
+    assert_eq!(remaining.len(), stats.retained);
+    assert_eq!(stats.examined, stats.retained + stats.deleted);
+    assert!(stats.deleted > 0);
+    check_leaves::<_, _, _, _, E>(
+        correlation_id,
+        environment,
+        store,
+        &retained_roots[0],
+        &TEST_LEAVES,
+        &[],
+    )?;
+
+    Ok(())
+}
+
+#[test]
+fn lmdb_prune_resumes_from_serialized_pruner() {
+    let correlation_id = CorrelationId::new();
+    let (root_hash, tries) = TEST_TRIE_GENERATORS[0]().unwrap();
+    let context = LmdbTestContext::new(&tries).unwrap();
+
+    prune_resumes_from_serialized_pruner::<_, _, error::Error>(
+        correlation_id,
+        &context.environment,
+        &context.store,
+        &root_hash,
+    )
+    .unwrap();
+}
+
+#[test]
+fn in_memory_prune_resumes_from_serialized_pruner() {
+    let correlation_id = CorrelationId::new();
+    let (root_hash, tries) = TEST_TRIE_GENERATORS[0]().unwrap();
+    let context = InMemoryTestContext::new(&tries).unwrap();
+
+    prune_resumes_from_serialized_pruner::<_, _, in_memory::Error>(
+        correlation_id,
+        &context.environment,
+        &context.store,
+        &root_hash,
+    )
+    .unwrap();
+}
+
+#[test]
+fn in_memory_prune_with_missing_trie_fails() {
+    let correlation_id = CorrelationId::new();
+    let (root_hash, mut tries) = TEST_TRIE_GENERATORS[3]().unwrap();
+    // The extension below the root
+    let missing = tries.remove(4);
+    let context = InMemoryTestContext::new(&tries).unwrap();
+
+    let mut txn = context.environment.create_read_write_txn().unwrap();
+    let result = prune::prune::<TestKey, TestValue, _, _, in_memory::Error>(
+        correlation_id,
+        &mut txn,
+        &context.store,
+        &[root_hash],
+    );
+    assert_eq!(result, Err(in_memory::Error::TrieNotFound(missing.hash)));
+}
@@ -0,0 +1,150 @@
+use super::*;
+use types::merkle_proof;
+
+use crate::{trie::TrieMerkleProof, trie_store::operations::read_with_proof};
+
+fn check_proofs<'a, R, S, E>(
+    correlation_id: CorrelationId,
+    environment: &'a R,
+    store: &S,
+    root: &Blake2bHash,
+    present: &[TestTrie],
+    absent: &[TestTrie],
+) -> Result<(), E>
+where
+    R: TransactionSource<'a, Handle = S::Handle>,
+    S: TrieStore<TestKey, TestValue>,
+    S::Error: From<R::Error>,
+    E: From<R::Error> + From<S::Error> + From<bytesrepr::Error>,
+{
+    let txn = environment.create_read_txn()?;
+    for leaf in present.iter().chain(absent.iter()) {
+        let (key, value) = match leaf {
+            Trie::Leaf { key, value } => (key, value),
+            _ => panic!("leaves should only contain leaves"),
+        };
+        let proof = read_with_proof::<_, _, _, _, E>(correlation_id, &txn, store, root, key)?
+            .expect("root should exist");
+        let expected = if present.contains(leaf) {
+            Some(*value)
+        } else {
+            None
+        };
+        assert_eq!(proof.value().copied(), expected);
+        assert_eq!(proof.verify(root), Ok(expected));
+
+        // A proof is only valid against the root it was produced from
+        let other_root: Blake2bHash = [1u8; 32].into();
+        assert!(proof.verify(&other_root).is_err());
+    }
+    txn.commit()?;
+    Ok(())
+}
+
+#[test]
+fn lmdb_proofs_from_n_leaf_partial_trie_verify() {
+    for (num_leaves, generator) in TEST_TRIE_GENERATORS.iter().enumerate() {
+        let correlation_id = CorrelationId::new();
+        let (root_hash, tries) = generator().unwrap();
+        let context = LmdbTestContext::new(&tries).unwrap();
+        let (present, absent) = TEST_LEAVES.split_at(num_leaves);
+
+        check_proofs::<_, _, error::Error>(
+            correlation_id,
+            &context.environment,
+            &context.store,
+            &root_hash,
+            present,
+            absent,
+        )
+        .unwrap();
+    }
+}
+
+#[test]
+fn sled_proofs_from_n_leaf_partial_trie_verify() {
+    for (num_leaves, generator) in TEST_TRIE_GENERATORS.iter().enumerate() {
+        let correlation_id = CorrelationId::new();
+        let (root_hash, tries) = generator().unwrap();
+        let context = SledTestContext::new(&tries).unwrap();
+        let (present, absent) = TEST_LEAVES.split_at(num_leaves);
+
+        check_proofs::<_, _, error::Error>(
+            correlation_id,
+            &context.environment,
+            &context.store,
+            &root_hash,
+            present,
+            absent,
+        )
+        .unwrap();
+    }
+}
+
+#[test]
+fn in_memory_proofs_from_n_leaf_partial_trie_verify() {
+    for (num_leaves, generator) in TEST_TRIE_GENERATORS.iter().enumerate() {
+        let correlation_id = CorrelationId::new();
+        let (root_hash, tries) = generator().unwrap();
+        let context = InMemoryTestContext::new(&tries).unwrap();
+        let (present, absent) = TEST_LEAVES.split_at(num_leaves);
+
+        check_proofs::<_, _, in_memory::Error>(
+            correlation_id,
+            &context.environment,
+            &context.store,
+            &root_hash,
+            present,
+            absent,
+        )
+        .unwrap();
+    }
+}
+
+#[test]
+fn in_memory_proof_with_unknown_root_is_none() {
+    let correlation_id = CorrelationId::new();
+    let (_, tries) = TEST_TRIE_GENERATORS[6]().unwrap();
+    let context = InMemoryTestContext::new(&tries).unwrap();
+    let unknown_root: Blake2bHash = [1u8; 32].into();
+
+    let txn = context.environment.create_read_txn().unwrap();
+    let key = TEST_LEAVES[0].key().unwrap();
+    let maybe_proof = read_with_proof::<_, TestValue, _, _, in_memory::Error>(
+        correlation_id,
+        &txn,
+        &context.store,
+        &unknown_root,
+        key,
+    )
+    .unwrap();
+    assert!(maybe_proof.is_none());
+}
+
+#[test]
+fn in_memory_tampered_proof_is_rejected() {
+    let correlation_id = CorrelationId::new();
+    let (root_hash, tries) = TEST_TRIE_GENERATORS[6]().unwrap();
+    let context = InMemoryTestContext::new(&tries).unwrap();
+
+    let txn = context.environment.create_read_txn().unwrap();
+    let key = TEST_LEAVES[0].key().unwrap();
+    let proof = read_with_proof::<_, TestValue, _, _, in_memory::Error>(
+        correlation_id,
+        &txn,
+        &context.store,
+        &root_hash,
+        key,
+    )
+    .unwrap()
+    .unwrap();
+
+    let mut nodes = proof.nodes().to_vec();
+    let tampered_leaf = Trie::leaf(*key, TestValue(*b"foobar"));
+    *nodes.last_mut().unwrap() = tampered_leaf;
+    let tampered_proof = TrieMerkleProof::new(*key, nodes);
+    assert_eq!(
+        tampered_proof.verify(&root_hash),
+        Err(merkle_proof::Error::HashMismatch(proof.nodes().len() - 1))
+    );
+}
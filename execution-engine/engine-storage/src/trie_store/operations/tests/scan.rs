@@ -79,6 +79,27 @@ mod partial_tries {
         }
     }
 
+    #[test]
+    fn sled_scans_from_n_leaf_partial_trie_had_expected_results() {
+        for generator in &TEST_TRIE_GENERATORS {
+            let correlation_id = CorrelationId::new();
+            let (root_hash, tries) = generator().unwrap();
+            let context = SledTestContext::new(&tries).unwrap();
+
+            for leaf in TEST_LEAVES.iter() {
+                let leaf_bytes = leaf.to_bytes().unwrap();
+                check_scan::<_, _, error::Error>(
+                    correlation_id,
+                    &context.environment,
+                    &context.store,
+                    &root_hash,
+                    &leaf_bytes,
+                )
+                .unwrap()
+            }
+        }
+    }
+
     #[test]
     fn in_memory_scans_from_n_leaf_partial_trie_had_expected_results() {
         for generator in &TEST_TRIE_GENERATORS {
@@ -131,6 +152,33 @@ mod full_tries {
         }
     }
 
+    #[test]
+    fn sled_scans_from_n_leaf_full_trie_had_expected_results() {
+        let correlation_id = CorrelationId::new();
+        let context = SledTestContext::new(EMPTY_HASHED_TEST_TRIES).unwrap();
+        let mut states: Vec<Blake2bHash> = Vec::new();
+
+        for (state_index, generator) in TEST_TRIE_GENERATORS.iter().enumerate() {
+            let (root_hash, tries) = generator().unwrap();
+            context.update(&tries).unwrap();
+            states.push(root_hash);
+
+            for state in &states[..state_index] {
+                for leaf in TEST_LEAVES.iter() {
+                    let leaf_bytes = leaf.to_bytes().unwrap();
+                    check_scan::<_, _, error::Error>(
+                        correlation_id,
+                        &context.environment,
+                        &context.store,
+                        state,
+                        &leaf_bytes,
+                    )
+                    .unwrap()
+                }
+            }
+        }
+    }
+
     #[test]
     fn in_memory_scans_from_n_leaf_full_trie_had_expected_results() {
         let correlation_id = CorrelationId::new();
@@ -0,0 +1,227 @@
+use super::*;
+use crate::trie_store::operations::stats::{
+    compare, stats, StatComparison, StatsKind, StatsResult, TrieStats, ValueStats,
+};
+
+impl StatsKind for TestKey {
+    fn stats_kind(&self) -> &'static str {
+        "TestKey"
+    }
+}
+
+impl StatsKind for TestValue {
+    fn stats_kind(&self) -> &'static str {
+        "TestValue"
+    }
+}
+
+fn root_stats<'a, R, S, E>(
+    correlation_id: CorrelationId,
+    environment: &'a R,
+    store: &S,
+    root: &Blake2bHash,
+) -> Result<StatsResult, E>
+where
+    R: TransactionSource<'a, Handle = S::Handle>,
+    S: TrieStore<TestKey, TestValue>,
+    S::Error: From<R::Error>,
+    E: From<R::Error> + From<S::Error> + From<types::bytesrepr::Error>,
+{
+    let txn = environment.create_read_txn()?;
+    let result = stats::<TestKey, TestValue, _, _, E>(correlation_id, &txn, store, root)?;
+    txn.commit()?;
+    Ok(result)
+}
+
+fn unwrap_stats(result: StatsResult) -> TrieStats {
+    match result {
+        StatsResult::Success(stats) => stats,
+        StatsResult::RootNotFound => panic!("root should exist"),
+    }
+}
+
+/// Checks that the stats of each of the test tries count all of its tries and leaves.
+fn stats_count_every_trie<'a, R, S, E>(
+    correlation_id: CorrelationId,
+    environment: &'a R,
+    store: &S,
+) -> Result<(), E>
+where
+    R: TransactionSource<'a, Handle = S::Handle>,
+    S: TrieStore<TestKey, TestValue>,
+    S::Error: From<R::Error>,
+    E: From<R::Error> + From<S::Error> + From<types::bytesrepr::Error>,
+{
+    for (num_leaves, generator) in TEST_TRIE_GENERATORS.iter().enumerate() {
+        let (root_hash, tries) = generator()?;
+        put_tries::<_, _, _, _, E>(environment, store, &tries)?;
+
+        let stats = unwrap_stats(root_stats::<_, _, E>(
+            correlation_id,
+            environment,
+            store,
+            &root_hash,
+        )?);
+        assert_eq!(stats.nodes + stats.extensions + stats.leaves, tries.len());
+        assert_eq!(stats.leaves, num_leaves);
+        assert_eq!(
+            stats.bytes,
+            tries
+                .iter()
+                .map(|hashed| hashed.trie.serialized_length())
+                .sum::<usize>()
+        );
+        assert_eq!(stats.leaf_depths.values().sum::<usize>(), num_leaves);
+    }
+    Ok(())
+}
+
+#[test]
+fn lmdb_stats_count_every_trie() {
+    let correlation_id = CorrelationId::new();
+    let context = LmdbTestContext::new(EMPTY_HASHED_TEST_TRIES).unwrap();
+
+    stats_count_every_trie::<_, _, error::Error>(
+        correlation_id,
+        &context.environment,
+        &context.store,
+    )
+    .unwrap();
+}
+
+#[test]
+fn sled_stats_count_every_trie() {
+    let correlation_id = CorrelationId::new();
+    let context = SledTestContext::new(EMPTY_HASHED_TEST_TRIES).unwrap();
+
+    stats_count_every_trie::<_, _, error::Error>(
+        correlation_id,
+        &context.environment,
+        &context.store,
+    )
+    .unwrap();
+}
+
+#[test]
+fn in_memory_stats_count_every_trie() {
+    let correlation_id = CorrelationId::new();
+    let context = InMemoryTestContext::new(EMPTY_HASHED_TEST_TRIES).unwrap();
+
+    stats_count_every_trie::<_, _, in_memory::Error>(
+        correlation_id,
+        &context.environment,
+        &context.store,
+    )
+    .unwrap();
+}
+
+#[test]
+fn stats_of_two_leaf_trie() {
+    let correlation_id = CorrelationId::new();
+    let (root_hash, tries) = create_2_leaf_trie().unwrap();
+    let context = InMemoryTestContext::new(&tries).unwrap();
+
+    let stats = unwrap_stats(
+        root_stats::<_, _, in_memory::Error>(
+            correlation_id,
+            &context.environment,
+            &context.store,
+            &root_hash,
+        )
+        .unwrap(),
+    );
+
+    // A root node, pointing to an extension, pointing to a node with two leaves
+    assert_eq!(stats.nodes, 2);
+    assert_eq!(stats.extensions, 1);
+    assert_eq!(stats.leaves, 2);
+    assert_eq!(stats.node_pointers, 3);
+    assert_eq!(stats.average_branching_factor(), 1.5);
+    assert_eq!(
+        stats.leaves_by_key.into_iter().collect::<Vec<_>>(),
+        vec![("TestKey", 2)]
+    );
+    assert_eq!(
+        stats.values.into_iter().collect::<Vec<_>>(),
+        vec![(
+            "TestValue",
+            ValueStats {
+                count: 2,
+                bytes: 2 * TEST_VAL_LENGTH
+            }
+        )]
+    );
+    assert_eq!(
+        stats.leaf_depths.into_iter().collect::<Vec<_>>(),
+        vec![(3, 2)]
+    );
+}
+
+#[test]
+fn compare_shows_growth_between_roots() {
+    let correlation_id = CorrelationId::new();
+    let (left_root, left_tries) = create_2_leaf_trie().unwrap();
+    let (right_root, right_tries) = create_3_leaf_trie().unwrap();
+    let context = InMemoryTestContext::new(&left_tries).unwrap();
+    context.update(&right_tries).unwrap();
+
+    let left = unwrap_stats(
+        root_stats::<_, _, in_memory::Error>(
+            correlation_id,
+            &context.environment,
+            &context.store,
+            &left_root,
+        )
+        .unwrap(),
+    );
+    let right = unwrap_stats(
+        root_stats::<_, _, in_memory::Error>(
+            correlation_id,
+            &context.environment,
+            &context.store,
+            &right_root,
+        )
+        .unwrap(),
+    );
+
+    let comparisons = compare(&left, &right);
+    let find = |name: &str| -> StatComparison {
+        comparisons
+            .iter()
+            .find(|comparison| comparison.name == name)
+            .unwrap_or_else(|| panic!("should have {}", name))
+            .to_owned()
+    };
+    assert_eq!(find("leaves").growth(), 1.0);
+    assert_eq!(find("leaves_by_key.TestKey").growth(), 1.0);
+    assert_eq!(
+        find("value_bytes.TestValue").growth(),
+        TEST_VAL_LENGTH as f64
+    );
+    // The leaves of the three leaf trie are at depths 3 and 5.
+    let depth_3 = find("leaf_depth.3");
+    assert_eq!((depth_3.left, depth_3.right), (2.0, 1.0));
+    let depth_5 = find("leaf_depth.5");
+    assert_eq!((depth_5.left, depth_5.right), (0.0, 2.0));
+
+    // Comparing a root with itself shows no growth.
+    assert!(compare(&right, &right)
+        .iter()
+        .all(|comparison| comparison.growth() == 0.0));
+}
+
+#[test]
+fn stats_of_unknown_root_returns_root_not_found() {
+    let correlation_id = CorrelationId::new();
+    let (_, tries) = TEST_TRIE_GENERATORS[0]().unwrap();
+    let context = InMemoryTestContext::new(&tries).unwrap();
+
+    let result = root_stats::<_, _, in_memory::Error>(
+        correlation_id,
+        &context.environment,
+        &context.store,
+        &Blake2bHash::new(&[0u8]),
+    )
+    .unwrap();
+    assert_eq!(result, StatsResult::RootNotFound);
+}
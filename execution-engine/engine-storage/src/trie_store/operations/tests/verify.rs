@@ -0,0 +1,247 @@
+use super::*;
+use crate::{
+    transaction_source::Writable,
+    trie_store::operations::verify::{verify, Defect, VerifyReport},
+};
+
+fn verify_root<'a, R, S, E>(
+    correlation_id: CorrelationId,
+    environment: &'a R,
+    store: &S,
+    root: &Blake2bHash,
+) -> Result<VerifyReport, E>
+where
+    R: TransactionSource<'a, Handle = S::Handle>,
+    S: TrieStore<TestKey, TestValue>,
+    S::Error: From<R::Error>,
+    E: From<R::Error> + From<S::Error> + From<types::bytesrepr::Error>,
+{
+    let txn = environment.create_read_txn()?;
+    let report = verify::<TestKey, TestValue, _, _, E>(correlation_id, &txn, store, root)?;
+    txn.commit()?;
+    Ok(report)
+}
+
+/// Stores `bytes` under `hash`, whether or not they hash to it.
+fn put_raw<'a, R, S, E>(
+    environment: &'a R,
+    store: &S,
+    hash: &Blake2bHash,
+    bytes: &[u8],
+) -> Result<(), E>
+where
+    R: TransactionSource<'a, Handle = S::Handle>,
+    S: TrieStore<TestKey, TestValue>,
+    E: From<R::Error> + From<types::bytesrepr::Error>,
+{
+    let mut txn = environment.create_read_write_txn()?;
+    txn.write(store.handle(), &hash.to_bytes()?, bytes)?;
+    txn.commit()?;
+    Ok(())
+}
+
+/// Checks that each of the test tries is found to have no defects.
+fn verify_finds_no_defects<'a, R, S, E>(
+    correlation_id: CorrelationId,
+    environment: &'a R,
+    store: &S,
+) -> Result<(), E>
+where
+    R: TransactionSource<'a, Handle = S::Handle>,
+    S: TrieStore<TestKey, TestValue>,
+    S::Error: From<R::Error>,
+    E: From<R::Error> + From<S::Error> + From<types::bytesrepr::Error>,
+{
+    for (num_leaves, generator) in TEST_TRIE_GENERATORS.iter().enumerate() {
+        let (root_hash, tries) = generator()?;
+        put_tries::<_, _, _, _, E>(environment, store, &tries)?;
+
+        let report = verify_root::<_, _, E>(correlation_id, environment, store, &root_hash)?;
+        assert_eq!(
+            report,
+            VerifyReport {
+                tries: tries.len(),
+                leaves: num_leaves,
+                defects: Vec::new(),
+            }
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn lmdb_verify_finds_no_defects() {
+    let correlation_id = CorrelationId::new();
+    let context = LmdbTestContext::new(EMPTY_HASHED_TEST_TRIES).unwrap();
+
+    verify_finds_no_defects::<_, _, error::Error>(
+        correlation_id,
+        &context.environment,
+        &context.store,
+    )
+    .unwrap();
+}
+
+#[test]
+fn sled_verify_finds_no_defects() {
+    let correlation_id = CorrelationId::new();
+    let context = SledTestContext::new(EMPTY_HASHED_TEST_TRIES).unwrap();
+
+    verify_finds_no_defects::<_, _, error::Error>(
+        correlation_id,
+        &context.environment,
+        &context.store,
+    )
+    .unwrap();
+}
+
+#[test]
+fn in_memory_verify_finds_no_defects() {
+    let correlation_id = CorrelationId::new();
+    let context = InMemoryTestContext::new(EMPTY_HASHED_TEST_TRIES).unwrap();
+
+    verify_finds_no_defects::<_, _, in_memory::Error>(
+        correlation_id,
+        &context.environment,
+        &context.store,
+    )
+    .unwrap();
+}
+
+#[test]
+fn verify_reports_missing_tries() {
+    let correlation_id = CorrelationId::new();
+    let (root_hash, tries) = create_2_leaf_trie().unwrap();
+    let missing_leaf = tries[1].hash;
+    let context = InMemoryTestContext::new(&[&tries[..1], &tries[2..]].concat()).unwrap();
+
+    let report = verify_root::<_, _, in_memory::Error>(
+        correlation_id,
+        &context.environment,
+        &context.store,
+        &root_hash,
+    )
+    .unwrap();
+    assert_eq!(report.tries, tries.len() - 1);
+    assert_eq!(report.leaves, 1);
+    assert_eq!(
+        report.defects,
+        vec![Defect::Missing {
+            hash: missing_leaf,
+            path: vec![0, 0, 0, 0, 0, 0, 1],
+        }]
+    );
+
+    let missing_root = Blake2bHash::new(&[0u8]);
+    let report = verify_root::<_, _, in_memory::Error>(
+        correlation_id,
+        &context.environment,
+        &context.store,
+        &missing_root,
+    )
+    .unwrap();
+    assert_eq!(
+        report.defects,
+        vec![Defect::Missing {
+            hash: missing_root,
+            path: Vec::new(),
+        }]
+    );
+}
+
+#[test]
+fn verify_reports_corrupted_tries() {
+    let correlation_id = CorrelationId::new();
+    let (root_hash, tries) = create_2_leaf_trie().unwrap();
+    let context = InMemoryTestContext::new(&tries).unwrap();
+
+    // Overwrite the first leaf with the second one.
+    put_raw::<_, _, in_memory::Error>(
+        &context.environment,
+        &context.store,
+        &tries[0].hash,
+        &tries[1].trie.to_bytes().unwrap(),
+    )
+    .unwrap();
+
+    let report = verify_root::<_, _, in_memory::Error>(
+        correlation_id,
+        &context.environment,
+        &context.store,
+        &root_hash,
+    )
+    .unwrap();
+    assert_eq!(
+        report.defects,
+        vec![Defect::HashMismatch {
+            hash: tries[0].hash,
+            path: vec![0, 0, 0, 0, 0, 0, 0],
+            actual: tries[1].hash,
+        }]
+    );
+
+    // Store bytes which hash correctly but are not a trie.
+    let garbage = vec![9u8];
+    let garbage_hash = Blake2bHash::new(&garbage);
+    put_raw::<_, _, in_memory::Error>(
+        &context.environment,
+        &context.store,
+        &garbage_hash,
+        &garbage,
+    )
+    .unwrap();
+
+    let report = verify_root::<_, _, in_memory::Error>(
+        correlation_id,
+        &context.environment,
+        &context.store,
+        &garbage_hash,
+    )
+    .unwrap();
+    assert_eq!(report.tries, 1);
+    assert_eq!(
+        report.defects,
+        vec![Defect::Undecodable {
+            hash: garbage_hash,
+            path: Vec::new(),
+            error: bytesrepr::Error::Formatting,
+        }]
+    );
+}
+
+#[test]
+fn verify_reports_misplaced_leaves_and_mismatched_pointers() {
+    let correlation_id = CorrelationId::new();
+    let leaves = hash_test_tries(&TEST_LEAVES[..2]).unwrap();
+    // Both leaves have keys starting with 0.
+    let root = HashedTrie::new(Trie::node(&[
+        (0, Pointer::NodePointer(leaves[0].hash)),
+        (1, Pointer::LeafPointer(leaves[1].hash)),
+    ]))
+    .unwrap();
+    let root_hash = root.hash;
+    let context = InMemoryTestContext::new(&[&leaves[..], &[root]].concat()).unwrap();
+
+    let report = verify_root::<_, _, in_memory::Error>(
+        correlation_id,
+        &context.environment,
+        &context.store,
+        &root_hash,
+    )
+    .unwrap();
+    assert_eq!(report.tries, 3);
+    assert_eq!(report.leaves, 2);
+    assert_eq!(
+        report.defects,
+        vec![
+            Defect::PointerMismatch {
+                hash: leaves[0].hash,
+                path: vec![0],
+            },
+            Defect::MisplacedLeaf {
+                hash: leaves[1].hash,
+                path: vec![1],
+            },
+        ]
+    );
+}
@@ -24,6 +24,25 @@ mod empty_tries {
         }
     }
 
+    #[test]
+    fn sled_non_colliding_writes_to_n_leaf_empty_trie_had_expected_results() {
+        for num_leaves in 1..=TEST_LEAVES_LENGTH {
+            let correlation_id = CorrelationId::new();
+            let (root_hash, tries) = TEST_TRIE_GENERATORS[0]().unwrap();
+            let context = SledTestContext::new(&tries).unwrap();
+            let initial_states = vec![root_hash];
+
+            writes_to_n_leaf_empty_trie_had_expected_results::<_, _, _, _, error::Error>(
+                correlation_id,
+                &context.environment,
+                &context.store,
+                &initial_states,
+                &TEST_LEAVES_NON_COLLIDING[..num_leaves],
+            )
+            .unwrap();
+        }
+    }
+
     #[test]
     fn in_memory_non_colliding_writes_to_n_leaf_empty_trie_had_expected_results() {
         for num_leaves in 1..=TEST_LEAVES_LENGTH {
@@ -62,6 +81,25 @@ mod empty_tries {
         }
     }
 
+    #[test]
+    fn sled_writes_to_n_leaf_empty_trie_had_expected_results() {
+        for num_leaves in 1..=TEST_LEAVES_LENGTH {
+            let correlation_id = CorrelationId::new();
+            let (root_hash, tries) = TEST_TRIE_GENERATORS[0]().unwrap();
+            let context = SledTestContext::new(&tries).unwrap();
+            let initial_states = vec![root_hash];
+
+            writes_to_n_leaf_empty_trie_had_expected_results::<_, _, _, _, error::Error>(
+                correlation_id,
+                &context.environment,
+                &context.store,
+                &initial_states,
+                &TEST_LEAVES[..num_leaves],
+            )
+            .unwrap();
+        }
+    }
+
     #[test]
     fn in_memory_writes_to_n_leaf_empty_trie_had_expected_results() {
         for num_leaves in 1..=TEST_LEAVES_LENGTH {
@@ -184,6 +222,25 @@ mod partial_tries {
         }
     }
 
+    #[test]
+    fn sled_noop_writes_to_n_leaf_partial_trie_had_expected_results() {
+        for (num_leaves, generator) in TEST_TRIE_GENERATORS.iter().enumerate() {
+            let correlation_id = CorrelationId::new();
+            let (root_hash, tries) = generator().unwrap();
+            let context = SledTestContext::new(&tries).unwrap();
+            let states = vec![root_hash];
+
+            noop_writes_to_n_leaf_partial_trie_had_expected_results::<_, _, error::Error>(
+                correlation_id,
+                &context.environment,
+                &context.store,
+                &states,
+                num_leaves,
+            )
+            .unwrap()
+        }
+    }
+
     #[test]
     fn in_memory_noop_writes_to_n_leaf_partial_trie_had_expected_results() {
         for (num_leaves, generator) in TEST_TRIE_GENERATORS.iter().enumerate() {
@@ -290,6 +347,25 @@ mod partial_tries {
         }
     }
 
+    #[test]
+    fn sled_update_writes_to_n_leaf_partial_trie_had_expected_results() {
+        for (num_leaves, generator) in TEST_TRIE_GENERATORS.iter().enumerate() {
+            let correlation_id = CorrelationId::new();
+            let (root_hash, tries) = generator().unwrap();
+            let context = SledTestContext::new(&tries).unwrap();
+            let initial_states = vec![root_hash];
+
+            update_writes_to_n_leaf_partial_trie_had_expected_results::<_, _, error::Error>(
+                correlation_id,
+                &context.environment,
+                &context.store,
+                &initial_states,
+                num_leaves,
+            )
+            .unwrap()
+        }
+    }
+
     #[test]
     fn in_memory_update_writes_to_n_leaf_partial_trie_had_expected_results() {
         for (num_leaves, generator) in TEST_TRIE_GENERATORS.iter().enumerate() {
@@ -388,6 +464,28 @@ mod full_tries {
         }
     }
 
+    #[test]
+    fn sled_noop_writes_to_n_leaf_full_trie_had_expected_results() {
+        let correlation_id = CorrelationId::new();
+        let context = SledTestContext::new(EMPTY_HASHED_TEST_TRIES).unwrap();
+        let mut states: Vec<Blake2bHash> = Vec::new();
+
+        for (index, generator) in TEST_TRIE_GENERATORS.iter().enumerate() {
+            let (root_hash, tries) = generator().unwrap();
+            context.update(&tries).unwrap();
+            states.push(root_hash);
+
+            noop_writes_to_n_leaf_full_trie_had_expected_results::<_, _, error::Error>(
+                correlation_id,
+                &context.environment,
+                &context.store,
+                &states,
+                index,
+            )
+            .unwrap();
+        }
+    }
+
     #[test]
     fn in_memory_noop_writes_to_n_leaf_full_trie_had_expected_results() {
         let correlation_id = CorrelationId::new();
@@ -512,6 +610,28 @@ mod full_tries {
         }
     }
 
+    #[test]
+    fn sled_update_writes_to_n_leaf_full_trie_had_expected_results() {
+        let correlation_id = CorrelationId::new();
+        let context = SledTestContext::new(EMPTY_HASHED_TEST_TRIES).unwrap();
+        let mut states: Vec<Blake2bHash> = Vec::new();
+
+        for (num_leaves, generator) in TEST_TRIE_GENERATORS.iter().enumerate() {
+            let (root_hash, tries) = generator().unwrap();
+            context.update(&tries).unwrap();
+            states.push(root_hash);
+
+            update_writes_to_n_leaf_full_trie_had_expected_results::<_, _, error::Error>(
+                correlation_id,
+                &context.environment,
+                &context.store,
+                &states,
+                num_leaves,
+            )
+            .unwrap()
+        }
+    }
+
     #[test]
     fn in_memory_update_writes_to_n_leaf_full_trie_had_expected_results() {
         let correlation_id = CorrelationId::new();
@@ -634,6 +754,27 @@ mod full_tries {
         .unwrap()
     }
 
+    #[test]
+    fn sled_node_writes_to_5_leaf_full_trie_had_expected_results() {
+        let correlation_id = CorrelationId::new();
+        let context = SledTestContext::new(EMPTY_HASHED_TEST_TRIES).unwrap();
+        let mut states: Vec<Blake2bHash> = Vec::new();
+
+        for generator in &TEST_TRIE_GENERATORS {
+            let (root_hash, tries) = generator().unwrap();
+            context.update(&tries).unwrap();
+            states.push(root_hash);
+        }
+
+        node_writes_to_5_leaf_full_trie_had_expected_results::<_, _, error::Error>(
+            correlation_id,
+            &context.environment,
+            &context.store,
+            &states,
+        )
+        .unwrap()
+    }
+
     #[test]
     fn in_memory_node_writes_to_5_leaf_full_trie_had_expected_results() {
         let correlation_id = CorrelationId::new();
@@ -0,0 +1,218 @@
+//! Integrity checking of the tries reachable from a root.
+//!
+//! Every reachable trie is read as raw bytes, decompressed if it was stored compressed, and hashed,
+//! so that a trie whose stored bytes no longer hash to the key they are stored under is detected
+//! even if they still deserialize.  The subtrie below a trie which fails to hash or to deserialize
+//! is not walked, as its pointers can't be trusted.
+
+use std::time::Instant;
+
+use engine_shared::{
+    logging::{log_duration, log_metric},
+    newtypes::{Blake2bHash, CorrelationId},
+};
+use types::bytesrepr::{self, FromBytes, ToBytes};
+
+use crate::{
+    transaction_source::Readable,
+    trie::{Pointer, Trie},
+    trie_store::{compression, TrieStore},
+    GAUGE_METRIC_KEY,
+};
+
+const TRIE_STORE_VERIFY_DURATION: &str = "trie_store_verify_duration";
+const TRIE_STORE_VERIFY_GETS: &str = "trie_store_verify_gets";
+const VERIFY: &str = "verify";
+const GET: &str = "get";
+
+/// A problem found with a trie reachable from the verified root.
+///
+/// `path` is the sequence of pointer block indices and extension affixes followed from the root
+/// to reach the trie.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Defect {
+    /// No trie is stored under the hash.
+    Missing { hash: Blake2bHash, path: Vec<u8> },
+    /// The bytes stored under the hash hash to `actual`.
+    HashMismatch {
+        hash: Blake2bHash,
+        path: Vec<u8>,
+        actual: Blake2bHash,
+    },
+    /// The bytes stored under the hash are not a valid trie, or fail to decompress.
+    Undecodable {
+        hash: Blake2bHash,
+        path: Vec<u8>,
+        error: bytesrepr::Error,
+    },
+    /// The trie is a leaf but is pointed to by a node pointer, or is not a leaf but is pointed to
+    /// by a leaf pointer.
+    PointerMismatch { hash: Blake2bHash, path: Vec<u8> },
+    /// The trie is a leaf whose serialized key does not start with `path`.
+    MisplacedLeaf { hash: Blake2bHash, path: Vec<u8> },
+}
+
+impl Defect {
+    pub fn hash(&self) -> &Blake2bHash {
+        match self {
+            Defect::Missing { hash, .. }
+            | Defect::HashMismatch { hash, .. }
+            | Defect::Undecodable { hash, .. }
+            | Defect::PointerMismatch { hash, .. }
+            | Defect::MisplacedLeaf { hash, .. } => hash,
+        }
+    }
+
+    pub fn path(&self) -> &[u8] {
+        match self {
+            Defect::Missing { path, .. }
+            | Defect::HashMismatch { path, .. }
+            | Defect::Undecodable { path, .. }
+            | Defect::PointerMismatch { path, .. }
+            | Defect::MisplacedLeaf { path, .. } => path,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct VerifyReport {
+    /// The number of tries which were found, including leaves.
+    pub tries: usize,
+    /// The number of leaves which were found.
+    pub leaves: usize,
+    /// The problems found, in ascending order of their paths.
+    pub defects: Vec<Defect>,
+}
+
+impl VerifyReport {
+    pub fn is_ok(&self) -> bool {
+        self.defects.is_empty()
+    }
+}
+
+/// Walks every trie reachable from `root`, checking that it is stored, that its stored bytes
+/// decompress, hash to the hash it is referred to by and deserialize to a trie, that it is a leaf
+/// if and only if it is referred to by a leaf pointer, and, if it is a leaf, that its key agrees
+/// with its path.
+///
+/// A missing `root` is reported as a [`Defect::Missing`] with an empty path.
+pub fn verify<K, V, T, S, E>(
+    correlation_id: CorrelationId,
+    txn: &T,
+    store: &S,
+    root: &Blake2bHash,
+) -> Result<VerifyReport, E>
+where
+    K: ToBytes + FromBytes,
+    V: FromBytes,
+    T: Readable<Handle = S::Handle>,
+    S: TrieStore<K, V>,
+    S::Error: From<T::Error>,
+    E: From<S::Error> + From<bytesrepr::Error>,
+{
+    let start = Instant::now();
+    let mut get_counter: i32 = 0;
+    let mut report = VerifyReport::default();
+
+    // Hashes still to be checked, along with their paths and the pointers which referred to them,
+    // popped in ascending path order
+    let mut stack: Vec<(Blake2bHash, Vec<u8>, Option<Pointer>)> = vec![(*root, Vec::new(), None)];
+    while let Some((hash, path, maybe_pointer)) = stack.pop() {
+        get_counter += 1;
+        let bytes = match txn
+            .read(store.handle(), &hash.to_bytes()?)
+            .map_err(S::Error::from)?
+        {
+            Some(bytes) => bytes,
+            None => {
+                report.defects.push(Defect::Missing { hash, path });
+                continue;
+            }
+        };
+        report.tries += 1;
+
+        let bytes = match compression::decompress(bytes) {
+            Ok(bytes) => bytes,
+            Err(error) => {
+                report
+                    .defects
+                    .push(Defect::Undecodable { hash, path, error });
+                continue;
+            }
+        };
+
+        let actual = Blake2bHash::new(&bytes);
+        if actual != hash {
+            report
+                .defects
+                .push(Defect::HashMismatch { hash, path, actual });
+            continue;
+        }
+
+        let trie: Trie<K, V> = match bytesrepr::deserialize(bytes) {
+            Ok(trie) => trie,
+            Err(error) => {
+                report
+                    .defects
+                    .push(Defect::Undecodable { hash, path, error });
+                continue;
+            }
+        };
+
+        let is_leaf = trie.key().is_some();
+        match maybe_pointer {
+            Some(Pointer::LeafPointer(_)) if !is_leaf => {
+                report.defects.push(Defect::PointerMismatch {
+                    hash,
+                    path: path.clone(),
+                })
+            }
+            Some(Pointer::NodePointer(_)) if is_leaf => {
+                report.defects.push(Defect::PointerMismatch {
+                    hash,
+                    path: path.clone(),
+                })
+            }
+            _ => (),
+        }
+
+        match trie {
+            Trie::Leaf { key, .. } => {
+                report.leaves += 1;
+                if !key.to_bytes()?.starts_with(&path) {
+                    report.defects.push(Defect::MisplacedLeaf { hash, path });
+                }
+            }
+            Trie::Node { pointer_block } => {
+                for (index, maybe_pointer) in pointer_block[..].iter().enumerate().rev() {
+                    if let Some(pointer) = maybe_pointer {
+                        let mut child_path = path.clone();
+                        child_path.push(index as u8);
+                        stack.push((*pointer.hash(), child_path, Some(*pointer)));
+                    }
+                }
+            }
+            Trie::Extension { affix, pointer } => {
+                let mut child_path = path;
+                child_path.extend_from_slice(&affix);
+                stack.push((*pointer.hash(), child_path, Some(pointer)));
+            }
+        }
+    }
+
+    log_metric(
+        correlation_id,
+        TRIE_STORE_VERIFY_GETS,
+        GET,
+        GAUGE_METRIC_KEY,
+        f64::from(get_counter),
+    );
+    log_duration(
+        correlation_id,
+        TRIE_STORE_VERIFY_DURATION,
+        VERIFY,
+        start.elapsed(),
+    );
+
+    Ok(report)
+}
@@ -0,0 +1,85 @@
+//! A trie store which reads from an LMDB-backed trie store, but keeps every written trie in
+//! memory.
+//!
+//! See [`ScratchEnvironment`](crate::transaction_source::scratch::ScratchEnvironment).  Tries are
+//! written with the compression of the LMDB-backed store, so that flushing them writes the same
+//! bytes as writing them to that store directly.
+
+use engine_shared::newtypes::Blake2bHash;
+use types::bytesrepr::{self, FromBytes, ToBytes};
+
+use crate::{
+    error,
+    store::Store,
+    transaction_source::{
+        scratch::{ScratchEnvironment, ScratchHandle},
+        Readable, Writable,
+    },
+    trie::Trie,
+    trie_store::{compression, in_memory::InMemoryTrieStore, lmdb::LmdbTrieStore, TrieStore},
+};
+
+const DELETED: &str = "deleted";
+
+/// A trie store which overlays an [`InMemoryTrieStore`] on an [`LmdbTrieStore`].
+pub struct ScratchTrieStore {
+    lmdb: LmdbTrieStore,
+    written: InMemoryTrieStore,
+    deleted: InMemoryTrieStore,
+}
+
+impl ScratchTrieStore {
+    pub fn new(env: &ScratchEnvironment, lmdb: LmdbTrieStore) -> Self {
+        ScratchTrieStore {
+            lmdb,
+            written: InMemoryTrieStore::new(env.memory(), None),
+            deleted: InMemoryTrieStore::new(env.memory(), Some(DELETED)),
+        }
+    }
+}
+
+impl<K, V> Store<Blake2bHash, Trie<K, V>> for ScratchTrieStore {
+    type Error = error::Error;
+
+    type Handle = ScratchHandle;
+
+    fn handle(&self) -> Self::Handle {
+        ScratchHandle {
+            lmdb: Store::<Blake2bHash, Trie<K, V>>::handle(&self.lmdb),
+            written: Store::<Blake2bHash, Trie<K, V>>::handle(&self.written),
+            deleted: Store::<Blake2bHash, Trie<K, V>>::handle(&self.deleted),
+        }
+    }
+
+    fn get<T>(&self, txn: &T, key: &Blake2bHash) -> Result<Option<Trie<K, V>>, Self::Error>
+    where
+        T: Readable<Handle = Self::Handle>,
+        Blake2bHash: ToBytes,
+        Trie<K, V>: FromBytes,
+        Self::Error: From<T::Error>,
+    {
+        let handle = Store::<Blake2bHash, Trie<K, V>>::handle(self);
+        match txn.read(handle, &key.to_bytes()?)? {
+            None => Ok(None),
+            Some(bytes) => {
+                let trie = bytesrepr::deserialize(compression::decompress(bytes)?)?;
+                Ok(Some(trie))
+            }
+        }
+    }
+
+    fn put<T>(&self, txn: &mut T, key: &Blake2bHash, value: &Trie<K, V>) -> Result<(), Self::Error>
+    where
+        T: Writable<Handle = Self::Handle>,
+        Blake2bHash: ToBytes,
+        Trie<K, V>: ToBytes,
+        Self::Error: From<T::Error>,
+    {
+        let handle = Store::<Blake2bHash, Trie<K, V>>::handle(self);
+        let bytes = compression::compress_trie(self.lmdb.compression().as_ref(), value)?;
+        txn.write(handle, &key.to_bytes()?, &bytes)
+            .map_err(Into::into)
+    }
+}
+
+impl<K, V> TrieStore<K, V> for ScratchTrieStore {}
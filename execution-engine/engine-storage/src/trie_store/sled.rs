@@ -0,0 +1,78 @@
+//! A sled-backed trie store.
+//!
+//! # Usage
+//!
+//! ```
+//! use casperlabs_engine_storage::store::Store;
+//! use casperlabs_engine_storage::transaction_source::{Transaction, TransactionSource};
+//! use casperlabs_engine_storage::transaction_source::sled::SledEnvironment;
+//! use casperlabs_engine_storage::trie::Trie;
+//! use casperlabs_engine_storage::trie_store::sled::SledTrieStore;
+//! use types::bytesrepr::ToBytes;
+//! use engine_shared::newtypes::Blake2bHash;
+//! use tempfile::tempdir;
+//!
+//! let leaf = Trie::Leaf { key: vec![0u8, 0, 0], value: b"val_1".to_vec() };
+//! let leaf_hash = Blake2bHash::new(&leaf.to_bytes().unwrap());
+//!
+//! let tmp_dir = tempdir().unwrap();
+//! let env = SledEnvironment::new(&tmp_dir.path().to_path_buf()).unwrap();
+//! let store = SledTrieStore::new(&env, None).unwrap();
+//!
+//! {
+//!     let mut txn = env.create_read_write_txn().unwrap();
+//!     store.put(&mut txn, &leaf_hash, &leaf).unwrap();
+//!     txn.commit().unwrap();
+//! }
+//!
+//! {
+//!     let txn = env.create_read_txn().unwrap();
+//!     assert_eq!(Some(leaf), store.get(&txn, &leaf_hash).unwrap());
+//!     txn.commit().unwrap();
+//! }
+//!
+//! tmp_dir.close().unwrap();
+//! ```
+
+use sled::Tree;
+
+use engine_shared::newtypes::Blake2bHash;
+
+use crate::{
+    error,
+    store::Store,
+    transaction_source::sled::SledEnvironment,
+    trie::Trie,
+    trie_store::{self, TrieStore},
+};
+
+/// A sled-backed trie store.
+///
+/// Wraps [`sled::Tree`].
+#[derive(Debug, Clone)]
+pub struct SledTrieStore {
+    tree: Tree,
+}
+
+impl SledTrieStore {
+    /// Opens the tree backing the store, creating it if it does not exist.
+    pub fn new(env: &SledEnvironment, maybe_name: Option<&str>) -> Result<Self, error::Error> {
+        let name = maybe_name
+            .map(|name| format!("{}-{}", trie_store::NAME, name))
+            .unwrap_or_else(|| String::from(trie_store::NAME));
+        let tree = env.db().open_tree(name)?;
+        Ok(SledTrieStore { tree })
+    }
+}
+
+impl<K, V> Store<Blake2bHash, Trie<K, V>> for SledTrieStore {
+    type Error = error::Error;
+
+    type Handle = Tree;
+
+    fn handle(&self) -> Self::Handle {
+        self.tree.clone()
+    }
+}
+
+impl<K, V> TrieStore<K, V> for SledTrieStore {}
@@ -0,0 +1,211 @@
+use std::{sync::Arc, thread};
+
+use tempfile::tempdir;
+
+use engine_shared::newtypes::Blake2bHash;
+
+use super::TestData;
+use crate::{
+    store::Store,
+    transaction_source::{
+        in_memory::InMemoryEnvironment, lmdb::LmdbEnvironment, Transaction, TransactionSource,
+    },
+    trie::Trie,
+    trie_store::{cache::TrieCache, in_memory::InMemoryTrieStore, lmdb::LmdbTrieStore},
+    TEST_MAP_SIZE,
+};
+
+type TestTrie = Trie<Vec<u8>, Vec<u8>>;
+
+fn put_data(
+    env: &InMemoryEnvironment,
+    store: &InMemoryTrieStore,
+    data: &[TestData<Vec<u8>, Vec<u8>>],
+) {
+    let mut txn = env.create_read_write_txn().unwrap();
+    for TestData(hash, trie) in data {
+        store.put(&mut txn, hash, trie).unwrap();
+    }
+    txn.commit().unwrap();
+}
+
+fn cached_get(
+    env: &InMemoryEnvironment,
+    store: &InMemoryTrieStore,
+    cache: &TrieCache<Vec<u8>, Vec<u8>>,
+    hash: &Blake2bHash,
+) -> Option<TestTrie> {
+    let txn = env.create_read_txn().unwrap();
+    let ret = cache.store(store).get(&txn, hash).unwrap();
+    txn.commit().unwrap();
+    ret
+}
+
+/// Returns the size which the cache accounts for a trie.
+fn cached_byte_size(trie: &TestTrie) -> usize {
+    let cache = TrieCache::new(usize::max_value());
+    cache.insert(Blake2bHash::new(&[]), trie.clone());
+    cache.bytes()
+}
+
+#[test]
+fn cache_serves_repeated_reads() {
+    let env = InMemoryEnvironment::new();
+    let store = InMemoryTrieStore::new(&env, None);
+    let data = super::create_data();
+    put_data(&env, &store, &data);
+    let cache = TrieCache::new(1024 * 1024);
+
+    for _ in 0..3 {
+        for TestData(hash, trie) in &data {
+            assert_eq!(Some(trie.clone()), cached_get(&env, &store, &cache, hash));
+        }
+    }
+    assert_eq!(cache.len(), data.len());
+    assert_eq!(cache.misses(), data.len() as u64);
+    assert_eq!(cache.hits(), 2 * data.len() as u64);
+
+    // Missing tries are not cached.
+    let missing = Blake2bHash::new(&[0u8]);
+    assert_eq!(None, cached_get(&env, &store, &cache, &missing));
+    assert_eq!(None, cached_get(&env, &store, &cache, &missing));
+    assert_eq!(cache.len(), data.len());
+    assert_eq!(cache.misses(), data.len() as u64 + 2);
+}
+
+#[test]
+fn cache_evicts_least_recently_used_tries() {
+    let env = InMemoryEnvironment::new();
+    let store = InMemoryTrieStore::new(&env, None);
+    // Three leaves of the same size.
+    let leaves = &super::create_data()[0..3];
+    put_data(&env, &store, leaves);
+    let leaf_size = cached_byte_size(&leaves[0].1);
+    let cache = TrieCache::new(2 * leaf_size);
+
+    cached_get(&env, &store, &cache, &leaves[0].0);
+    cached_get(&env, &store, &cache, &leaves[1].0);
+    // Make the first leaf the most recently used, so that the second one is evicted.
+    cached_get(&env, &store, &cache, &leaves[0].0);
+    cached_get(&env, &store, &cache, &leaves[2].0);
+    assert_eq!(cache.len(), 2);
+    assert_eq!(cache.bytes(), 2 * leaf_size);
+    assert_eq!((cache.hits(), cache.misses()), (1, 3));
+
+    cached_get(&env, &store, &cache, &leaves[0].0);
+    cached_get(&env, &store, &cache, &leaves[2].0);
+    assert_eq!((cache.hits(), cache.misses()), (3, 3));
+    cached_get(&env, &store, &cache, &leaves[1].0);
+    assert_eq!((cache.hits(), cache.misses()), (3, 4));
+}
+
+#[test]
+fn cache_keeps_nothing_bigger_than_itself() {
+    let env = InMemoryEnvironment::new();
+    let store = InMemoryTrieStore::new(&env, None);
+    let data = super::create_data();
+    put_data(&env, &store, &data);
+    let TestData(leaf_hash, leaf) = &data[0];
+    let TestData(node_hash, node) = &data[3];
+    let cache = TrieCache::new(cached_byte_size(leaf));
+    assert!(cached_byte_size(node) > cache.max_bytes());
+
+    assert_eq!(
+        Some(leaf.clone()),
+        cached_get(&env, &store, &cache, leaf_hash)
+    );
+    assert_eq!(
+        Some(node.clone()),
+        cached_get(&env, &store, &cache, node_hash)
+    );
+    assert_eq!(cache.len(), 1);
+    assert_eq!(
+        Some(leaf.clone()),
+        cached_get(&env, &store, &cache, leaf_hash)
+    );
+    assert_eq!(cache.hits(), 1);
+
+    let disabled = TrieCache::new(0);
+    assert_eq!(
+        Some(leaf.clone()),
+        cached_get(&env, &store, &disabled, leaf_hash)
+    );
+    assert!(disabled.is_empty());
+    assert_eq!(disabled.bytes(), 0);
+}
+
+#[test]
+fn cached_store_evicts_deleted_tries() {
+    let env = InMemoryEnvironment::new();
+    let store = InMemoryTrieStore::new(&env, None);
+    let data = super::create_data();
+    put_data(&env, &store, &data);
+    let TestData(leaf_hash, leaf) = &data[0];
+    let cache = TrieCache::new(1024 * 1024);
+
+    assert_eq!(
+        Some(leaf.clone()),
+        cached_get(&env, &store, &cache, leaf_hash)
+    );
+    let mut txn = env.create_read_write_txn().unwrap();
+    cache.store(&store).delete(&mut txn, leaf_hash).unwrap();
+    txn.commit().unwrap();
+
+    assert!(cache.is_empty());
+    assert_eq!(None, cached_get(&env, &store, &cache, leaf_hash));
+}
+
+#[test]
+fn lmdb_cache_is_shared_by_concurrent_readers() {
+    let dir = tempdir().unwrap();
+    let env = Arc::new(LmdbEnvironment::new(&dir.path().to_path_buf(), *TEST_MAP_SIZE).unwrap());
+    let store = Arc::new(LmdbTrieStore::new(&env, None, Default::default()).unwrap());
+    let cache = Arc::new(TrieCache::new(1024 * 1024));
+    let data = super::create_data();
+    {
+        let mut txn = env.create_read_write_txn().unwrap();
+        for TestData(hash, trie) in &data {
+            store.put(&mut txn, hash, trie).unwrap();
+        }
+        txn.commit().unwrap();
+    }
+
+    let num_threads = 10;
+    let handles: Vec<_> = (0..num_threads)
+        .map(|_| {
+            let env = Arc::clone(&env);
+            let store = Arc::clone(&store);
+            let cache = Arc::clone(&cache);
+            let data = data.clone();
+            thread::spawn(move || {
+                for TestData(hash, trie) in &data {
+                    let txn = env.create_read_txn().unwrap();
+                    let actual: Option<TestTrie> = cache.store(&*store).get(&txn, hash).unwrap();
+                    txn.commit().unwrap();
+                    assert_eq!(Some(trie.clone()), actual);
+                }
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+
+    assert_eq!(cache.len(), data.len());
+    assert_eq!(
+        cache.hits() + cache.misses(),
+        (num_threads * data.len()) as u64
+    );
+
+    // Every trie is now served by the cache.
+    let hits = cache.hits();
+    let txn = env.create_read_txn().unwrap();
+    for TestData(hash, trie) in &data {
+        let actual: Option<TestTrie> = cache.store(&*store).get(&txn, hash).unwrap();
+        assert_eq!(Some(trie.clone()), actual);
+    }
+    txn.commit().unwrap();
+    assert_eq!(cache.hits(), hits + data.len() as u64);
+
+    dir.close().unwrap();
+}
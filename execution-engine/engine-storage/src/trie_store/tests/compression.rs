@@ -0,0 +1,348 @@
+use std::sync::Arc;
+
+use lmdb::DatabaseFlags;
+use rand::{rngs::StdRng, RngCore, SeedableRng};
+use tempfile::{tempdir, TempDir};
+
+use engine_shared::newtypes::{Blake2bHash, CorrelationId};
+use types::bytesrepr::ToBytes;
+
+use crate::{
+    error,
+    store::Store,
+    transaction_source::{
+        lmdb::LmdbEnvironment, scratch::ScratchEnvironment, Readable, Transaction,
+        TransactionSource,
+    },
+    trie::{operations::create_hashed_empty_trie, Trie},
+    trie_store::{
+        compression::{self, Compression, CompressionAlgorithm, COMPRESSED_TRIE_TAG},
+        lmdb::LmdbTrieStore,
+        operations::{
+            recompress::{RecompressResult, TrieRecompressor},
+            verify::verify,
+            write, WriteResult,
+        },
+        scratch::ScratchTrieStore,
+    },
+    TEST_MAP_SIZE,
+};
+
+type TestTrie = Trie<Vec<u8>, Vec<u8>>;
+
+const ALGORITHMS: [CompressionAlgorithm; 2] =
+    [CompressionAlgorithm::Lz4, CompressionAlgorithm::Zstd];
+
+const THRESHOLD: usize = 256;
+
+/// Returns a leaf whose value is compressible, and bigger than `THRESHOLD`.
+fn large_leaf(key_byte: u8) -> TestTrie {
+    let value = (0..4096_u32).map(|i| (i % 16) as u8).collect();
+    Trie::Leaf {
+        key: vec![key_byte; 32],
+        value,
+    }
+}
+
+fn small_leaf(key_byte: u8) -> TestTrie {
+    Trie::Leaf {
+        key: vec![key_byte; 32],
+        value: b"val".to_vec(),
+    }
+}
+
+fn hash(trie: &TestTrie) -> Blake2bHash {
+    Blake2bHash::new(&trie.to_bytes().unwrap())
+}
+
+fn create_env() -> (TempDir, LmdbEnvironment) {
+    let dir = tempdir().unwrap();
+    let env = LmdbEnvironment::new(&dir.path().to_path_buf(), *TEST_MAP_SIZE).unwrap();
+    (dir, env)
+}
+
+fn stored_bytes(env: &LmdbEnvironment, store: &LmdbTrieStore, hash: &Blake2bHash) -> Vec<u8> {
+    let txn = env.create_read_txn().unwrap();
+    let handle = Store::<Blake2bHash, TestTrie>::handle(store);
+    let ret = txn
+        .read(handle, &hash.to_bytes().unwrap())
+        .unwrap()
+        .unwrap();
+    txn.commit().unwrap();
+    ret
+}
+
+fn put_tries(env: &LmdbEnvironment, store: &LmdbTrieStore, tries: &[TestTrie]) {
+    let mut txn = env.create_read_write_txn().unwrap();
+    for trie in tries {
+        store.put(&mut txn, &hash(trie), trie).unwrap();
+    }
+    txn.commit().unwrap();
+}
+
+fn get_trie(env: &LmdbEnvironment, store: &LmdbTrieStore, hash: &Blake2bHash) -> Option<TestTrie> {
+    let txn = env.create_read_txn().unwrap();
+    let ret = store.get(&txn, hash).unwrap();
+    txn.commit().unwrap();
+    ret
+}
+
+/// Writes `count` large leaves to an empty trie, returning the resulting root.
+fn write_large_leaves(env: &LmdbEnvironment, store: &LmdbTrieStore, count: u8) -> Blake2bHash {
+    let correlation_id = CorrelationId::new();
+    let (mut root, empty_trie) = create_hashed_empty_trie::<Vec<u8>, Vec<u8>>().unwrap();
+    let mut txn = env.create_read_write_txn().unwrap();
+    store.put(&mut txn, &root, &empty_trie).unwrap();
+    for key_byte in 0..count {
+        let (key, value) = match large_leaf(key_byte) {
+            Trie::Leaf { key, value } => (key, value),
+            _ => unreachable!(),
+        };
+        match write::<_, _, _, _, error::Error>(
+            correlation_id,
+            &mut txn,
+            store,
+            &root,
+            &key,
+            &value,
+        )
+        .unwrap()
+        {
+            WriteResult::Written(root_hash) => root = root_hash,
+            result => panic!("unexpected write result: {:?}", result),
+        }
+    }
+    txn.commit().unwrap();
+    root
+}
+
+#[test]
+fn large_leaves_round_trip_with_each_algorithm() {
+    let leaf = large_leaf(0);
+    let canonical = leaf.to_bytes().unwrap();
+    for algorithm in ALGORITHMS.iter() {
+        let compression = Compression::new(*algorithm, THRESHOLD);
+        let stored = compression::compress_trie(Some(&compression), &leaf).unwrap();
+        assert!(compression::is_compressed(&stored));
+        assert!(stored.len() < canonical.len());
+        assert_eq!(canonical, compression::decompress(stored).unwrap());
+    }
+}
+
+#[test]
+fn only_large_compressible_leaves_are_compressed() {
+    let compression = Compression::new(CompressionAlgorithm::Zstd, THRESHOLD);
+    let small = small_leaf(0);
+    let node = Trie::<Vec<u8>, Vec<u8>>::node(&[]);
+    let incompressible = {
+        let mut value = vec![0u8; 4096];
+        StdRng::seed_from_u64(0).fill_bytes(&mut value);
+        Trie::Leaf {
+            key: vec![0u8; 32],
+            value,
+        }
+    };
+
+    for trie in &[small, node, incompressible] {
+        let stored = compression::compress_trie(Some(&compression), trie).unwrap();
+        assert_eq!(trie.to_bytes().unwrap(), stored);
+        assert!(!compression::is_compressed(&stored));
+        assert_eq!(stored.clone(), compression::decompress(stored).unwrap());
+    }
+
+    let large = large_leaf(0);
+    let stored = compression::compress_trie(None, &large).unwrap();
+    assert_eq!(large.to_bytes().unwrap(), stored);
+}
+
+#[test]
+fn malformed_envelopes_are_rejected() {
+    let compression = Compression::new(CompressionAlgorithm::Lz4, THRESHOLD);
+    let stored = compression::compress_trie(Some(&compression), &large_leaf(0)).unwrap();
+
+    let mut unknown_version = stored.clone();
+    unknown_version[1] = 2;
+    let mut unknown_algorithm = stored.clone();
+    unknown_algorithm[2] = 0;
+    let mut wrong_length = stored.clone();
+    wrong_length[3] ^= 1;
+    let mut too_long = stored.clone();
+    too_long[3..7].copy_from_slice(&(compression::MAX_UNCOMPRESSED_LENGTH + 1).to_le_bytes());
+    let truncated = stored[..stored.len() - 1].to_vec();
+    let no_header = vec![COMPRESSED_TRIE_TAG, 1];
+
+    for bytes in &[
+        unknown_version,
+        unknown_algorithm,
+        wrong_length,
+        too_long,
+        truncated,
+        no_header,
+    ] {
+        assert!(compression::decompress(bytes.clone()).is_err());
+    }
+}
+
+#[test]
+fn lmdb_store_reads_compressed_and_uncompressed_tries() {
+    let (_dir, env) = create_env();
+    let plain_store = LmdbTrieStore::new(&env, None, DatabaseFlags::empty()).unwrap();
+    let store = plain_store
+        .clone()
+        .with_compression(Compression::new(CompressionAlgorithm::Zstd, THRESHOLD));
+    let old_leaf = large_leaf(0);
+    let new_leaf = large_leaf(1);
+    let small = small_leaf(2);
+
+    put_tries(&env, &plain_store, &[old_leaf.clone()]);
+    put_tries(&env, &store, &[new_leaf.clone(), small.clone()]);
+
+    assert!(!compression::is_compressed(&stored_bytes(
+        &env,
+        &store,
+        &hash(&old_leaf)
+    )));
+    assert!(compression::is_compressed(&stored_bytes(
+        &env,
+        &store,
+        &hash(&new_leaf)
+    )));
+    assert!(!compression::is_compressed(&stored_bytes(
+        &env,
+        &store,
+        &hash(&small)
+    )));
+    // Reading doesn't depend on the compression of the store.
+    for trie in &[old_leaf, new_leaf, small] {
+        assert_eq!(Some(trie.clone()), get_trie(&env, &store, &hash(trie)));
+        assert_eq!(
+            Some(trie.clone()),
+            get_trie(&env, &plain_store, &hash(trie))
+        );
+    }
+}
+
+#[test]
+fn compression_does_not_change_state_roots() {
+    let (_plain_dir, plain_env) = create_env();
+    let plain_store = LmdbTrieStore::new(&plain_env, None, DatabaseFlags::empty()).unwrap();
+    let plain_root = write_large_leaves(&plain_env, &plain_store, 8);
+
+    for algorithm in ALGORITHMS.iter() {
+        let (_dir, env) = create_env();
+        let store = LmdbTrieStore::new(&env, None, DatabaseFlags::empty())
+            .unwrap()
+            .with_compression(Compression::new(*algorithm, THRESHOLD));
+        assert_eq!(plain_root, write_large_leaves(&env, &store, 8));
+
+        let txn = env.create_read_txn().unwrap();
+        let report = verify::<Vec<u8>, Vec<u8>, _, _, error::Error>(
+            CorrelationId::new(),
+            &txn,
+            &store,
+            &plain_root,
+        )
+        .unwrap();
+        txn.commit().unwrap();
+        assert!(report.is_ok(), "{:?}", report.defects);
+        assert_eq!(report.leaves, 8);
+    }
+}
+
+#[test]
+fn recompress_migrates_every_trie() {
+    let correlation_id = CorrelationId::new();
+    let (_dir, env) = create_env();
+    let plain_store = LmdbTrieStore::new(&env, None, DatabaseFlags::empty()).unwrap();
+    let root = write_large_leaves(&env, &plain_store, 8);
+    let leaves: Vec<TestTrie> = (0..8).map(large_leaf).collect();
+
+    let recompress = |store: &LmdbTrieStore| {
+        let mut recompressor = TrieRecompressor::new();
+        loop {
+            let mut txn = env.create_read_write_txn().unwrap();
+            let result = recompressor
+                .step::<Vec<u8>, Vec<u8>, _, _, error::Error>(correlation_id, &mut txn, store, 3)
+                .unwrap();
+            txn.commit().unwrap();
+            if let RecompressResult::Complete(stats) = result {
+                return stats;
+            }
+        }
+    };
+
+    let store = plain_store
+        .clone()
+        .with_compression(Compression::new(CompressionAlgorithm::Lz4, THRESHOLD));
+    let stats = recompress(&store);
+    assert_eq!(stats.compressed, leaves.len());
+    assert!(stats.tries > stats.compressed);
+    assert!(stats.bytes_after < stats.bytes_before);
+    for leaf in &leaves {
+        assert!(compression::is_compressed(&stored_bytes(
+            &env,
+            &store,
+            &hash(leaf)
+        )));
+        assert_eq!(Some(leaf.clone()), get_trie(&env, &store, &hash(leaf)));
+    }
+    let txn = env.create_read_txn().unwrap();
+    let report =
+        verify::<Vec<u8>, Vec<u8>, _, _, error::Error>(correlation_id, &txn, &store, &root)
+            .unwrap();
+    txn.commit().unwrap();
+    assert!(report.is_ok(), "{:?}", report.defects);
+
+    // Recompressing without compression restores the original bytes.
+    let stats_back = recompress(&plain_store);
+    assert_eq!(stats_back.compressed, 0);
+    assert_eq!(stats_back.tries, stats.tries);
+    assert_eq!(stats_back.bytes_after, stats.bytes_before);
+    for leaf in &leaves {
+        assert_eq!(
+            leaf.to_bytes().unwrap(),
+            stored_bytes(&env, &plain_store, &hash(leaf))
+        );
+    }
+}
+
+#[test]
+fn scratch_store_reads_and_writes_compressed_tries() {
+    let (_dir, env) = create_env();
+    let env = Arc::new(env);
+    let store = LmdbTrieStore::new(&env, None, DatabaseFlags::empty())
+        .unwrap()
+        .with_compression(Compression::new(CompressionAlgorithm::Zstd, THRESHOLD));
+    let old_leaf = large_leaf(0);
+    let new_leaf = large_leaf(1);
+    put_tries(&env, &store, &[old_leaf.clone()]);
+
+    let scratch_env = ScratchEnvironment::new(Arc::clone(&env));
+    let scratch_store = ScratchTrieStore::new(&scratch_env, store.clone());
+    let mut txn = scratch_env.create_read_write_txn().unwrap();
+    scratch_store
+        .put(&mut txn, &hash(&new_leaf), &new_leaf)
+        .unwrap();
+    txn.commit().unwrap();
+
+    let txn = scratch_env.create_read_txn().unwrap();
+    for leaf in &[old_leaf, new_leaf.clone()] {
+        assert_eq!(
+            Some(leaf.clone()),
+            scratch_store.get(&txn, &hash(leaf)).unwrap()
+        );
+    }
+    txn.commit().unwrap();
+
+    let handle = Store::<Blake2bHash, TestTrie>::handle(&scratch_store);
+    scratch_env.flush(&[handle]).unwrap();
+    assert!(compression::is_compressed(&stored_bytes(
+        &env,
+        &store,
+        &hash(&new_leaf)
+    )));
+    assert_eq!(
+        Some(new_leaf.clone()),
+        get_trie(&env, &store, &hash(&new_leaf))
+    );
+}
@@ -1,3 +1,5 @@
+mod cache;
+mod compression;
 mod concurrent;
 mod proptests;
 mod simple;
@@ -6,12 +6,15 @@ use types::bytesrepr::{FromBytes, ToBytes};
 use super::TestData;
 use crate::{
     error::{self, in_memory},
-    store::StoreExt,
+    store::{Store, StoreExt},
     transaction_source::{
-        in_memory::InMemoryEnvironment, lmdb::LmdbEnvironment, Transaction, TransactionSource,
+        in_memory::InMemoryEnvironment, lmdb::LmdbEnvironment, sled::SledEnvironment, Transaction,
+        TransactionSource,
     },
     trie::Trie,
-    trie_store::{in_memory::InMemoryTrieStore, lmdb::LmdbTrieStore, TrieStore},
+    trie_store::{
+        in_memory::InMemoryTrieStore, lmdb::LmdbTrieStore, sled::SledTrieStore, TrieStore,
+    },
     TEST_MAP_SIZE,
 };
 
@@ -56,6 +59,16 @@ fn lmdb_put_succeeds() {
     tmp_dir.close().unwrap();
 }
 
+#[test]
+fn sled_put_succeeds() {
+    let tmp_dir = tempdir().unwrap();
+    let env = SledEnvironment::new(&tmp_dir.path().to_path_buf()).unwrap();
+    let store = SledTrieStore::new(&env, None).unwrap();
+    let data = &super::create_data()[0..1];
+
+    assert!(put_succeeds::<_, _, _, _, error::Error>(&store, &env, data).is_ok());
+}
+
 fn put_get_succeeds<'a, K, V, S, X, E>(
     store: &S,
     transaction_source: &'a X,
@@ -119,6 +132,50 @@ fn lmdb_put_get_succeeds() {
     tmp_dir.close().unwrap();
 }
 
+#[test]
+fn sled_put_get_succeeds() {
+    let tmp_dir = tempdir().unwrap();
+    let env = SledEnvironment::new(&tmp_dir.path().to_path_buf()).unwrap();
+    let store = SledTrieStore::new(&env, None).unwrap();
+    let data = &super::create_data()[0..1];
+
+    let expected: Vec<Trie<Vec<u8>, Vec<u8>>> =
+        data.to_vec().into_iter().map(|TestData(_, v)| v).collect();
+
+    assert_eq!(
+        expected,
+        put_get_succeeds::<_, _, _, _, error::Error>(&store, &env, data)
+            .expect("put_get_succeeds failed")
+            .into_iter()
+            .collect::<Option<Vec<Trie<Vec<u8>, Vec<u8>>>>>()
+            .expect("one of the outputs was empty")
+    );
+}
+
+#[test]
+fn lmdb_read_only_environment_reads_committed_data() {
+    let tmp_dir = tempdir().unwrap();
+    let path = tmp_dir.path().to_path_buf();
+    let data = &super::create_data()[0..1];
+    {
+        let env = LmdbEnvironment::new(&path, *TEST_MAP_SIZE).unwrap();
+        let store = LmdbTrieStore::new(&env, None, DatabaseFlags::empty()).unwrap();
+        put_succeeds::<_, _, _, _, error::Error>(&store, &env, data).unwrap();
+    }
+
+    let env = LmdbEnvironment::open_read_only(&path).unwrap();
+    let store = LmdbTrieStore::open(&env, None).unwrap();
+    let TestData(key, expected) = data[0].clone();
+    let txn = env.create_read_txn().unwrap();
+    let actual: Option<Trie<Vec<u8>, Vec<u8>>> = store.get(&txn, &key).unwrap();
+    txn.commit().unwrap();
+    assert_eq!(actual, Some(expected));
+
+    assert!(env.create_read_write_txn().is_err());
+
+    tmp_dir.close().unwrap();
+}
+
 #[test]
 fn in_memory_put_get_many_succeeds() {
     let env = InMemoryEnvironment::new();
@@ -160,6 +217,26 @@ fn lmdb_put_get_many_succeeds() {
     tmp_dir.close().unwrap();
 }
 
+#[test]
+fn sled_put_get_many_succeeds() {
+    let tmp_dir = tempdir().unwrap();
+    let env = SledEnvironment::new(&tmp_dir.path().to_path_buf()).unwrap();
+    let store = SledTrieStore::new(&env, None).unwrap();
+    let data = super::create_data();
+
+    let expected: Vec<Trie<Vec<u8>, Vec<u8>>> =
+        data.to_vec().into_iter().map(|TestData(_, v)| v).collect();
+
+    assert_eq!(
+        expected,
+        put_get_succeeds::<_, _, _, _, error::Error>(&store, &env, &data)
+            .expect("put_get failed")
+            .into_iter()
+            .collect::<Option<Vec<Trie<Vec<u8>, Vec<u8>>>>>()
+            .expect("one of the outputs was empty")
+    );
+}
+
 fn uncommitted_read_write_txn_does_not_persist<'a, K, V, S, X, E>(
     store: &S,
     transaction_source: &'a X,
@@ -224,6 +301,24 @@ fn lmdb_uncommitted_read_write_txn_does_not_persist() {
     tmp_dir.close().unwrap();
 }
 
+#[test]
+fn sled_uncommitted_read_write_txn_does_not_persist() {
+    let tmp_dir = tempdir().unwrap();
+    let env = SledEnvironment::new(&tmp_dir.path().to_path_buf()).unwrap();
+    let store = SledTrieStore::new(&env, None).unwrap();
+    let data = super::create_data();
+
+    assert_eq!(
+        None,
+        uncommitted_read_write_txn_does_not_persist::<_, _, _, _, error::Error>(
+            &store, &env, &data,
+        )
+        .expect("uncommitted_read_write_txn_does_not_persist failed")
+        .into_iter()
+        .collect::<Option<Vec<Trie<Vec<u8>, Vec<u8>>>>>()
+    );
+}
+
 fn read_write_transaction_does_not_block_read_transaction<'a, X, E>(
     transaction_source: &'a X,
 ) -> Result<(), E>
@@ -255,6 +350,14 @@ fn lmdb_read_write_transaction_does_not_block_read_transaction() {
     assert!(read_write_transaction_does_not_block_read_transaction::<_, error::Error>(&env).is_ok())
 }
 
+#[test]
+fn sled_read_write_transaction_does_not_block_read_transaction() {
+    let dir = tempdir().unwrap();
+    let env = SledEnvironment::new(&dir.path().to_path_buf()).unwrap();
+
+    assert!(read_write_transaction_does_not_block_read_transaction::<_, error::Error>(&env).is_ok())
+}
+
 fn reads_are_isolated<'a, S, X, E>(store: &S, env: &'a X) -> Result<(), E>
 where
     S: TrieStore<Vec<u8>, Vec<u8>>,
@@ -307,6 +410,28 @@ fn lmdb_reads_are_isolated() {
     assert!(reads_are_isolated::<_, _, error::Error>(&store, &env).is_ok())
 }
 
+/// A sled read transaction does not read from a snapshot, so unlike the other backends it observes
+/// changes committed after it was created.
+#[test]
+fn sled_read_transaction_observes_later_commits() {
+    let dir = tempdir().unwrap();
+    let env = SledEnvironment::new(&dir.path().to_path_buf()).unwrap();
+    let store = SledTrieStore::new(&env, None).unwrap();
+    let TestData(leaf_1_hash, leaf_1) = &super::create_data()[0..1][0];
+
+    let read_txn = env.create_read_txn().unwrap();
+    let result: Option<Trie<Vec<u8>, Vec<u8>>> = store.get(&read_txn, leaf_1_hash).unwrap();
+    assert_eq!(result, None);
+
+    let mut write_txn = env.create_read_write_txn().unwrap();
+    store.put(&mut write_txn, leaf_1_hash, leaf_1).unwrap();
+    write_txn.commit().unwrap();
+
+    let result = store.get(&read_txn, leaf_1_hash).unwrap();
+    read_txn.commit().unwrap();
+    assert_eq!(result, Some(leaf_1.to_owned()));
+}
+
 fn reads_are_isolated_2<'a, S, X, E>(store: &S, env: &'a X) -> Result<(), E>
 where
     S: TrieStore<Vec<u8>, Vec<u8>>,
@@ -363,6 +488,15 @@ fn lmdb_reads_are_isolated_2() {
     assert!(reads_are_isolated_2::<_, _, error::Error>(&store, &env).is_ok())
 }
 
+#[test]
+fn sled_reads_are_isolated_2() {
+    let dir = tempdir().unwrap();
+    let env = SledEnvironment::new(&dir.path().to_path_buf()).unwrap();
+    let store = SledTrieStore::new(&env, None).unwrap();
+
+    assert!(reads_are_isolated_2::<_, _, error::Error>(&store, &env).is_ok())
+}
+
 fn dbs_are_isolated<'a, S, X, E>(env: &'a X, store_a: &S, store_b: &S) -> Result<(), E>
 where
     S: TrieStore<Vec<u8>, Vec<u8>>,
@@ -426,6 +560,16 @@ fn lmdb_dbs_are_isolated() {
     assert!(dbs_are_isolated::<_, _, error::Error>(&env, &store_a, &store_b).is_ok())
 }
 
+#[test]
+fn sled_dbs_are_isolated() {
+    let dir = tempdir().unwrap();
+    let env = SledEnvironment::new(&dir.path().to_path_buf()).unwrap();
+    let store_a = SledTrieStore::new(&env, Some("a")).unwrap();
+    let store_b = SledTrieStore::new(&env, Some("b")).unwrap();
+
+    assert!(dbs_are_isolated::<_, _, error::Error>(&env, &store_a, &store_b).is_ok())
+}
+
 fn transactions_can_be_used_across_sub_databases<'a, S, X, E>(
     env: &'a X,
     store_a: &S,
@@ -489,6 +633,21 @@ fn lmdb_transactions_can_be_used_across_sub_databases() {
     )
 }
 
+#[test]
+fn sled_transactions_can_be_used_across_sub_databases() {
+    let dir = tempdir().unwrap();
+    let env = SledEnvironment::new(&dir.path().to_path_buf()).unwrap();
+    let store_a = SledTrieStore::new(&env, Some("a")).unwrap();
+    let store_b = SledTrieStore::new(&env, Some("b")).unwrap();
+
+    assert!(
+        transactions_can_be_used_across_sub_databases::<_, _, error::Error>(
+            &env, &store_a, &store_b,
+        )
+        .is_ok()
+    )
+}
+
 fn uncommitted_transactions_across_sub_databases_do_not_persist<'a, S, X, E>(
     env: &'a X,
     store_a: &S,
@@ -550,3 +709,18 @@ fn lmdb_uncommitted_transactions_across_sub_databases_do_not_persist() {
         .is_ok()
     )
 }
+
+#[test]
+fn sled_uncommitted_transactions_across_sub_databases_do_not_persist() {
+    let dir = tempdir().unwrap();
+    let env = SledEnvironment::new(&dir.path().to_path_buf()).unwrap();
+    let store_a = SledTrieStore::new(&env, Some("a")).unwrap();
+    let store_b = SledTrieStore::new(&env, Some("b")).unwrap();
+
+    assert!(
+        uncommitted_transactions_across_sub_databases_do_not_persist::<_, _, error::Error>(
+            &env, &store_a, &store_b,
+        )
+        .is_ok()
+    )
+}
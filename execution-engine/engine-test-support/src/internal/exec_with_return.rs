@@ -2,10 +2,10 @@ use std::{cell::RefCell, collections::BTreeSet, convert::TryInto, rc::Rc};
 
 use engine_core::{
     engine_state::{
-        executable_deploy_item::ExecutableDeployItem, execution_effect::ExecutionEffect,
-        EngineConfig, EngineState,
+        block_info::BlockInfo, executable_deploy_item::ExecutableDeployItem,
+        execution_effect::ExecutionEffect, EngineConfig, EngineState,
     },
-    execution::{self, AddressGenerator},
+    execution::{self, AddressGenerator, RandomBytesGenerator},
     runtime::{self, Runtime},
     runtime_context::RuntimeContext,
 };
@@ -18,7 +18,9 @@ use types::{
     ProtocolVersion, RuntimeArgs, URef, U512,
 };
 
-use crate::internal::{utils, WasmTestBuilder, DEFAULT_WASM_COSTS};
+use crate::internal::{
+    utils, WasmTestBuilder, DEFAULT_HOST_FUNCTION_COSTS, DEFAULT_STORAGE_COSTS, DEFAULT_WASM_COSTS,
+};
 
 /// This function allows executing the contract stored in the given `wasm_file`, while capturing the
 /// output. It is essentially the same functionality as `Executor::exec`, but the return value of
@@ -65,6 +67,11 @@ where
         let fn_store_id = AddressGenerator::new(&deploy_hash, phase);
         Rc::new(RefCell::new(fn_store_id))
     };
+    let random_bytes_generator = {
+        let random_bytes_generator =
+            RandomBytesGenerator::new(&deploy_hash, phase, BlockTime::new(block_time));
+        Rc::new(RefCell::new(random_bytes_generator))
+    };
     let gas_limit = Gas::new(U512::from(std::u64::MAX));
     let protocol_version = ProtocolVersion::V1_0_0;
     let correlation_id = CorrelationId::new();
@@ -85,7 +92,14 @@ where
         let mint = builder.get_mint_contract_hash();
         let pos = builder.get_mint_contract_hash();
         let standard_payment = builder.get_standard_payment_contract_hash();
-        ProtocolData::new(*DEFAULT_WASM_COSTS, mint, pos, standard_payment)
+        ProtocolData::new(
+            *DEFAULT_WASM_COSTS,
+            *DEFAULT_HOST_FUNCTION_COSTS,
+            *DEFAULT_STORAGE_COSTS,
+            mint,
+            pos,
+            standard_payment,
+        )
     };
 
     let context = RuntimeContext::new(
@@ -98,11 +112,13 @@ where
         &account,
         base_key,
         BlockTime::new(block_time),
+        BlockInfo::new(prestate, 0, None),
         deploy_hash,
         gas_limit,
         gas_counter,
         fn_store_id,
         address_generator,
+        random_bytes_generator,
         protocol_version,
         correlation_id,
         phase,
@@ -140,6 +156,7 @@ where
         memory,
         parity_module.take_module(),
         context,
+        None,
     );
 
     match instance.invoke_export(entry_point_name, &[], &mut runtime) {
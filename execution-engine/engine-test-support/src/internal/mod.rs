@@ -13,8 +13,8 @@ use engine_core::engine_state::{
     genesis::{ExecConfig, GenesisAccount, GenesisConfig},
     run_genesis_request::RunGenesisRequest,
 };
-use engine_shared::{motes::Motes, newtypes::Blake2bHash, test_utils};
-use engine_wasm_prep::wasm_costs::WasmCosts;
+use engine_shared::{motes::Motes, newtypes::Blake2bHash, storage_costs::StorageCosts, test_utils};
+use engine_wasm_prep::{host_function_costs::HostFunctionCosts, wasm_costs::WasmCosts};
 use types::{account::AccountHash, ProtocolVersion, U512};
 
 use super::{DEFAULT_ACCOUNT_ADDR, DEFAULT_ACCOUNT_INITIAL_BALANCE};
@@ -54,6 +54,8 @@ lazy_static! {
     pub static ref DEFAULT_PROTOCOL_VERSION: ProtocolVersion = ProtocolVersion::V1_0_0;
     pub static ref DEFAULT_PAYMENT: U512 = 100_000_000.into();
     pub static ref DEFAULT_WASM_COSTS: WasmCosts = test_utils::wasm_costs_mock();
+    pub static ref DEFAULT_HOST_FUNCTION_COSTS: HostFunctionCosts = HostFunctionCosts::default();
+    pub static ref DEFAULT_STORAGE_COSTS: StorageCosts = StorageCosts::default();
     pub static ref DEFAULT_EXEC_CONFIG: ExecConfig = {
         let mint_installer_bytes;
         let pos_installer_bytes;
@@ -69,6 +71,8 @@ lazy_static! {
             standard_payment_installer_bytes,
             DEFAULT_ACCOUNTS.clone(),
             *DEFAULT_WASM_COSTS,
+            *DEFAULT_HOST_FUNCTION_COSTS,
+            *DEFAULT_STORAGE_COSTS,
         )
     };
     pub static ref DEFAULT_GENESIS_CONFIG: GenesisConfig = {
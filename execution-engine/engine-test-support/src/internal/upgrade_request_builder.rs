@@ -1,11 +1,13 @@
 use engine_grpc_server::engine_server::{
     ipc::{
-        ChainSpec_ActivationPoint, ChainSpec_CostTable_WasmCosts, ChainSpec_UpgradePoint,
+        ChainSpec_ActivationPoint, ChainSpec_CostTable, ChainSpec_CostTable_HostFunctionCosts,
+        ChainSpec_CostTable_StorageCosts, ChainSpec_CostTable_WasmCosts, ChainSpec_UpgradePoint,
         DeployCode, UpgradeRequest,
     },
     state,
 };
-use engine_wasm_prep::wasm_costs::WasmCosts;
+use engine_shared::storage_costs::StorageCosts;
+use engine_wasm_prep::{host_function_costs::HostFunctionCosts, wasm_costs::WasmCosts};
 use types::ProtocolVersion;
 
 pub struct UpgradeRequestBuilder {
@@ -14,6 +16,8 @@ pub struct UpgradeRequestBuilder {
     new_protocol_version: state::ProtocolVersion,
     upgrade_installer: DeployCode,
     new_costs: Option<ChainSpec_CostTable_WasmCosts>,
+    new_host_function_costs: Option<ChainSpec_CostTable_HostFunctionCosts>,
+    new_storage_costs: Option<ChainSpec_CostTable_StorageCosts>,
     activation_point: ChainSpec_ActivationPoint,
 }
 
@@ -58,6 +62,16 @@ impl UpgradeRequestBuilder {
         self
     }
 
+    pub fn with_new_host_function_costs(mut self, host_function_costs: HostFunctionCosts) -> Self {
+        self.new_host_function_costs = Some(host_function_costs.into());
+        self
+    }
+
+    pub fn with_new_storage_costs(mut self, storage_costs: StorageCosts) -> Self {
+        self.new_storage_costs = Some(storage_costs.into());
+        self
+    }
+
     pub fn with_activation_point(mut self, rank: u64) -> Self {
         self.activation_point = {
             let mut ret = ChainSpec_ActivationPoint::new();
@@ -70,14 +84,21 @@ impl UpgradeRequestBuilder {
     pub fn build(self) -> UpgradeRequest {
         let mut upgrade_point = ChainSpec_UpgradePoint::new();
         upgrade_point.set_activation_point(self.activation_point);
-        match self.new_costs {
-            None => {}
-            Some(new_costs) => {
-                let mut cost_table =
-                    engine_grpc_server::engine_server::ipc::ChainSpec_CostTable::new();
+        if self.new_costs.is_some()
+            || self.new_host_function_costs.is_some()
+            || self.new_storage_costs.is_some()
+        {
+            let mut cost_table = ChainSpec_CostTable::new();
+            if let Some(new_costs) = self.new_costs {
                 cost_table.set_wasm(new_costs);
-                upgrade_point.set_new_costs(cost_table);
             }
+            if let Some(new_host_function_costs) = self.new_host_function_costs {
+                cost_table.set_host_functions(new_host_function_costs);
+            }
+            if let Some(new_storage_costs) = self.new_storage_costs {
+                cost_table.set_storage(new_storage_costs);
+            }
+            upgrade_point.set_new_costs(cost_table);
         }
         upgrade_point.set_protocol_version(self.new_protocol_version);
         upgrade_point.set_upgrade_installer(self.upgrade_installer);
@@ -97,6 +118,8 @@ impl Default for UpgradeRequestBuilder {
             new_protocol_version: Default::default(),
             upgrade_installer: Default::default(),
             new_costs: None,
+            new_host_function_costs: None,
+            new_storage_costs: None,
             activation_point: Default::default(),
         }
     }
@@ -14,10 +14,15 @@ use log::LevelFilter;
 
 use engine_core::{
     engine_state::{
-        execute_request::ExecuteRequest, execution_result::ExecutionResult,
-        run_genesis_request::RunGenesisRequest, EngineConfig, EngineState, SYSTEM_ACCOUNT_ADDR,
+        deploy_item::DeployItem,
+        estimate::{EstimateRequest, EstimateResult},
+        execute_request::{ExecuteRequest, ExecutionMode, SequentialExecutionResult},
+        execution_result::ExecutionResult,
+        run_genesis_request::RunGenesisRequest,
+        EngineConfig, EngineState, SYSTEM_ACCOUNT_ADDR,
     },
     execution,
+    runtime::trace::ExecutionTrace,
 };
 use engine_grpc_server::engine_server::{
     ipc::{
@@ -50,7 +55,7 @@ use types::{
     CLValue, Contract, ContractHash, ContractWasm, Key, URef, U512,
 };
 
-use crate::internal::utils;
+use crate::internal::{utils, DEFAULT_BLOCK_TIME, DEFAULT_PROTOCOL_VERSION};
 
 /// LMDB initial map size is calculated based on DEFAULT_LMDB_PAGES and systems page size.
 ///
@@ -286,7 +291,7 @@ impl LmdbWasmTestBuilder {
 
 impl<S> WasmTestBuilder<S>
 where
-    S: StateProvider,
+    S: StateProvider + Sync,
     S::Error: Into<execution::Error>,
     EngineState<S>: ExecutionEngineService,
 {
@@ -394,8 +399,28 @@ where
             .engine_state
             .run_execute(CorrelationId::new(), exec_request);
         assert!(exec_response.is_ok());
-        // Parse deploy results
-        let execution_results = exec_response.as_ref().unwrap();
+        self.push_exec_response(exec_response.unwrap());
+        self
+    }
+
+    /// As [`exec`](Self::exec), but also returns the host functions called by each deploy.
+    pub fn exec_traced(&mut self, mut exec_request: ExecuteRequest) -> Vec<ExecutionTrace> {
+        let hash = self
+            .post_state_hash
+            .clone()
+            .expect("expected post_state_hash");
+        exec_request.parent_state_hash = hash.as_slice().try_into().expect("expected a valid hash");
+        let (execution_results, traces) = self
+            .engine_state
+            .run_execute_traced(CorrelationId::new(), exec_request)
+            .expect("should execute deploys")
+            .into_iter()
+            .unzip();
+        self.push_exec_response(execution_results);
+        traces
+    }
+
+    fn push_exec_response(&mut self, execution_results: Vec<ExecutionResult>) {
         // Cache transformations
         self.transforms.extend(
             execution_results
@@ -403,10 +428,77 @@ where
                 .map(|res| res.effect().transforms.clone()),
         );
         self.exec_responses
-            .push(exec_response.unwrap().into_iter().map(Rc::new).collect());
+            .push(execution_results.into_iter().map(Rc::new).collect());
+    }
+
+    /// Executes the deploys of `exec_request` one after the other on the latest post-state hash,
+    /// and moves the post-state hash to the state with their combined effect committed.
+    ///
+    /// Unlike [`exec`](Self::exec), there is no need to [`commit`](Self::commit) afterwards.
+    pub fn exec_sequential(&mut self, exec_request: ExecuteRequest) -> &mut Self {
+        self.exec_in_order(exec_request, ExecutionMode::Sequential)
+    }
+
+    /// As [`exec_sequential`](Self::exec_sequential), but speculatively executes the deploys in
+    /// parallel first.
+    pub fn exec_parallel(&mut self, exec_request: ExecuteRequest) -> &mut Self {
+        self.exec_in_order(exec_request, ExecutionMode::Parallel)
+    }
+
+    fn exec_in_order(
+        &mut self,
+        mut exec_request: ExecuteRequest,
+        execution_mode: ExecutionMode,
+    ) -> &mut Self {
+        let hash = self
+            .post_state_hash
+            .clone()
+            .expect("expected post_state_hash");
+        exec_request.parent_state_hash = hash.as_slice().try_into().expect("expected a valid hash");
+        exec_request.execution_mode = execution_mode;
+        let sequential_execution_result = match execution_mode {
+            ExecutionMode::Parallel => self
+                .engine_state
+                .run_execute_parallel(CorrelationId::new(), exec_request),
+            _ => self
+                .engine_state
+                .run_execute_sequential(CorrelationId::new(), exec_request),
+        }
+        .expect("should execute deploys in order");
+        let (execution_results, post_state_hash) = match sequential_execution_result {
+            SequentialExecutionResult::Success {
+                execution_results,
+                post_state_hash,
+                ..
+            } => (execution_results, post_state_hash),
+            result => panic!(
+                "Expected sequential execution success but received a failure instead: {}",
+                result
+            ),
+        };
+        self.push_exec_response(execution_results);
+        self.post_state_hash = Some(post_state_hash.to_vec());
         self
     }
 
+    /// Estimates the gas spent by the session code of `deploy_item` on the latest post-state
+    /// hash.  Nothing is committed.
+    pub fn estimate(&self, deploy_item: DeployItem) -> EstimateResult {
+        let hash = self
+            .post_state_hash
+            .clone()
+            .expect("expected post_state_hash");
+        let estimate_request = EstimateRequest::new(
+            hash.as_slice().try_into().expect("expected a valid hash"),
+            DEFAULT_BLOCK_TIME,
+            deploy_item,
+            *DEFAULT_PROTOCOL_VERSION,
+        );
+        self.engine_state
+            .estimate(CorrelationId::new(), estimate_request)
+            .expect("should estimate")
+    }
+
     /// Commit effects of previous exec call on the latest post-state hash.
     pub fn commit(&mut self) -> &mut Self {
         let prestate_hash = self
@@ -0,0 +1,157 @@
+use std::convert::TryFrom;
+
+use ed25519_dalek::Signer;
+
+use engine_core::engine_state::{genesis::ExecConfig, run_genesis_request::RunGenesisRequest};
+use engine_shared::{gas::Gas, newtypes::Blake2bHash};
+use engine_test_support::{
+    internal::{
+        utils, ExecuteRequestBuilder, InMemoryWasmTestBuilder, DEFAULT_ACCOUNTS,
+        DEFAULT_GENESIS_CONFIG_HASH, DEFAULT_PROTOCOL_VERSION, DEFAULT_RUN_GENESIS_REQUEST,
+        DEFAULT_STORAGE_COSTS, DEFAULT_WASM_COSTS, MINT_INSTALL_CONTRACT, POS_INSTALL_CONTRACT,
+        STANDARD_PAYMENT_INSTALL_CONTRACT,
+    },
+    DEFAULT_ACCOUNT_ADDR,
+};
+use engine_wasm_prep::host_function_costs::{HostFunctionCost, HostFunctionCosts};
+use types::{
+    bytesrepr::FromBytes, runtime_args, CLTyped, CLValue, RuntimeArgs, BLAKE2B_DIGEST_LENGTH,
+};
+
+const CONTRACT_CRYPTO_HOST_FUNCTIONS: &str = "crypto_host_functions.wasm";
+const ARG_SCHEME: &str = "scheme";
+const ARG_PUBLIC_KEY: &str = "public_key";
+const ARG_MESSAGE: &str = "message";
+const ARG_SIGNATURE: &str = "signature";
+const DIGEST_KEY_NAME: &str = "digest";
+const VERIFIED_KEY_NAME: &str = "verified";
+const SCHEME_ED25519: &str = "ed25519";
+const SCHEME_SECP256K1: &str = "secp256k1";
+const MESSAGE: &[u8] = b"message to sign";
+const OTHER_MESSAGE: &[u8] = b"other message";
+const BLAKE2B_BASE_COST: u32 = 10_000;
+const ED25519_VERIFY_BASE_COST: u32 = 200_000;
+
+fn ed25519_keypair() -> ed25519_dalek::Keypair {
+    let secret = ed25519_dalek::SecretKey::from_bytes(&[1u8; 32]).unwrap();
+    let public = ed25519_dalek::PublicKey::from(&secret);
+    ed25519_dalek::Keypair { secret, public }
+}
+
+fn ed25519_args(message: &[u8]) -> RuntimeArgs {
+    let keypair = ed25519_keypair();
+    let signature = keypair.sign(MESSAGE).to_bytes().to_vec();
+    runtime_args! {
+        ARG_SCHEME => SCHEME_ED25519.to_string(),
+        ARG_PUBLIC_KEY => keypair.public.to_bytes().to_vec(),
+        ARG_MESSAGE => message.to_vec(),
+        ARG_SIGNATURE => signature,
+    }
+}
+
+fn secp256k1_args(message: &[u8]) -> RuntimeArgs {
+    let secret_key = secp256k1::SecretKey::parse(&[2u8; 32]).unwrap();
+    let public_key = secp256k1::PublicKey::from_secret_key(&secret_key);
+    let digest = Blake2bHash::new(MESSAGE).value();
+    let (signature, _recovery_id) =
+        secp256k1::sign(&secp256k1::Message::parse(&digest), &secret_key);
+    runtime_args! {
+        ARG_SCHEME => SCHEME_SECP256K1.to_string(),
+        ARG_PUBLIC_KEY => public_key.serialize_compressed().to_vec(),
+        ARG_MESSAGE => message.to_vec(),
+        ARG_SIGNATURE => signature.serialize().to_vec(),
+    }
+}
+
+fn run_genesis_request(host_function_costs: HostFunctionCosts) -> RunGenesisRequest {
+    let exec_config = ExecConfig::new(
+        utils::read_wasm_file_bytes(MINT_INSTALL_CONTRACT),
+        utils::read_wasm_file_bytes(POS_INSTALL_CONTRACT),
+        utils::read_wasm_file_bytes(STANDARD_PAYMENT_INSTALL_CONTRACT),
+        DEFAULT_ACCOUNTS.clone(),
+        *DEFAULT_WASM_COSTS,
+        host_function_costs,
+        *DEFAULT_STORAGE_COSTS,
+    );
+    RunGenesisRequest::new(
+        *DEFAULT_GENESIS_CONFIG_HASH,
+        *DEFAULT_PROTOCOL_VERSION,
+        exec_config,
+    )
+}
+
+fn exec_crypto(builder: &mut InMemoryWasmTestBuilder, args: RuntimeArgs) {
+    let exec_request =
+        ExecuteRequestBuilder::standard(DEFAULT_ACCOUNT_ADDR, CONTRACT_CRYPTO_HOST_FUNCTIONS, args)
+            .build();
+    builder.exec(exec_request).expect_success().commit();
+}
+
+fn query_named_value<T: CLTyped + FromBytes>(builder: &InMemoryWasmTestBuilder, name: &str) -> T {
+    let account = builder
+        .get_account(DEFAULT_ACCOUNT_ADDR)
+        .expect("should have account");
+    let key = account.named_keys()[name].normalize();
+    CLValue::try_from(builder.query(None, key, &[]).expect("should have value"))
+        .expect("should be a CLValue")
+        .into_t()
+        .expect("should have expected type")
+}
+
+fn verify(args: RuntimeArgs) -> bool {
+    let mut builder = InMemoryWasmTestBuilder::default();
+    builder.run_genesis(&DEFAULT_RUN_GENESIS_REQUEST);
+    exec_crypto(&mut builder, args);
+    query_named_value(&builder, VERIFIED_KEY_NAME)
+}
+
+#[ignore]
+#[test]
+fn should_hash_with_blake2b() {
+    let mut builder = InMemoryWasmTestBuilder::default();
+    builder.run_genesis(&DEFAULT_RUN_GENESIS_REQUEST);
+    exec_crypto(&mut builder, ed25519_args(MESSAGE));
+
+    let digest: [u8; BLAKE2B_DIGEST_LENGTH] = query_named_value(&builder, DIGEST_KEY_NAME);
+    assert_eq!(digest, Blake2bHash::new(MESSAGE).value());
+}
+
+#[ignore]
+#[test]
+fn should_verify_ed25519_signature() {
+    assert!(verify(ed25519_args(MESSAGE)));
+    assert!(!verify(ed25519_args(OTHER_MESSAGE)));
+}
+
+#[ignore]
+#[test]
+fn should_verify_secp256k1_signature() {
+    assert!(verify(secp256k1_args(MESSAGE)));
+    assert!(!verify(secp256k1_args(OTHER_MESSAGE)));
+}
+
+#[ignore]
+#[test]
+fn should_charge_crypto_host_function_costs() {
+    let mut free_builder = InMemoryWasmTestBuilder::default();
+    free_builder.run_genesis(&run_genesis_request(HostFunctionCosts::free()));
+    exec_crypto(&mut free_builder, ed25519_args(MESSAGE));
+    let free_cost = free_builder.last_exec_gas_cost();
+
+    let host_function_costs = HostFunctionCosts {
+        blake2b: HostFunctionCost::new(BLAKE2B_BASE_COST, 0),
+        ed25519_verify: HostFunctionCost::new(ED25519_VERIFY_BASE_COST, 0),
+        ..HostFunctionCosts::free()
+    };
+    let mut builder = InMemoryWasmTestBuilder::default();
+    builder.run_genesis(&run_genesis_request(host_function_costs));
+    exec_crypto(&mut builder, ed25519_args(MESSAGE));
+    let cost = builder.last_exec_gas_cost();
+
+    let expected_host_function_cost =
+        u64::from(BLAKE2B_BASE_COST) + u64::from(ED25519_VERIFY_BASE_COST);
+    assert_eq!(
+        cost,
+        free_cost + Gas::new(expected_host_function_cost.into())
+    );
+}
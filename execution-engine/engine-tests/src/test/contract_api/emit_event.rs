@@ -0,0 +1,81 @@
+use engine_core::engine_state::event::Event;
+use engine_test_support::{
+    internal::{
+        utils, ExecuteRequestBuilder, InMemoryWasmTestBuilder, DEFAULT_RUN_GENESIS_REQUEST,
+    },
+    DEFAULT_ACCOUNT_ADDR,
+};
+use types::{runtime_args, CLValue, Key, RuntimeArgs};
+
+const CONTRACT_EMIT_EVENT: &str = "emit_event.wasm";
+const HASH_KEY_NAME: &str = "emit_event_hash";
+const ARG_REVERT: &str = "revert";
+
+fn exec_emit_event(revert: bool) -> InMemoryWasmTestBuilder {
+    let exec_request = ExecuteRequestBuilder::standard(
+        DEFAULT_ACCOUNT_ADDR,
+        CONTRACT_EMIT_EVENT,
+        runtime_args! { ARG_REVERT => revert },
+    )
+    .build();
+
+    let mut builder = InMemoryWasmTestBuilder::default();
+    builder
+        .run_genesis(&DEFAULT_RUN_GENESIS_REQUEST)
+        .exec(exec_request)
+        .commit();
+    builder
+}
+
+#[ignore]
+#[test]
+fn should_return_events_in_emission_order() {
+    let builder = exec_emit_event(false);
+
+    let response = builder
+        .get_exec_response(0)
+        .expect("there should be a response");
+    let execution_result = utils::get_success_result(response);
+    assert!(execution_result.is_success());
+
+    let account = builder
+        .get_account(DEFAULT_ACCOUNT_ADDR)
+        .expect("should have account");
+    let contract_key = *account
+        .named_keys()
+        .get(HASH_KEY_NAME)
+        .expect("should have contract hash");
+    let account_key = Key::Account(DEFAULT_ACCOUNT_ADDR);
+
+    let expected = vec![
+        Event::new(
+            account_key,
+            "session_start".to_string(),
+            CLValue::from_t(1u64).unwrap(),
+        ),
+        Event::new(
+            contract_key,
+            "contract_event".to_string(),
+            CLValue::from_t("hello".to_string()).unwrap(),
+        ),
+        Event::new(
+            account_key,
+            "session_end".to_string(),
+            CLValue::from_t(2u64).unwrap(),
+        ),
+    ];
+    assert_eq!(execution_result.events(), expected.as_slice());
+}
+
+#[ignore]
+#[test]
+fn should_discard_events_of_failed_deploy() {
+    let builder = exec_emit_event(true);
+
+    let response = builder
+        .get_exec_response(0)
+        .expect("there should be a response");
+    let execution_result = utils::get_success_result(response);
+    assert!(execution_result.is_failure());
+    assert!(execution_result.events().is_empty());
+}
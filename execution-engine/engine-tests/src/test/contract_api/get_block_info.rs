@@ -0,0 +1,72 @@
+use std::convert::TryInto;
+
+use engine_test_support::{
+    internal::{
+        DeployItemBuilder, ExecuteRequestBuilder, InMemoryWasmTestBuilder, DEFAULT_PAYMENT,
+        DEFAULT_RUN_GENESIS_REQUEST,
+    },
+    DEFAULT_ACCOUNT_ADDR,
+};
+use types::{account::AccountHash, runtime_args, RuntimeArgs, BLAKE2B_DIGEST_LENGTH};
+
+const CONTRACT_GET_BLOCK_INFO: &str = "get_block_info.wasm";
+const ARG_AMOUNT: &str = "amount";
+const ARG_KNOWN_DEPLOY_HASH: &str = "known_deploy_hash";
+const ARG_KNOWN_BLOCK_HEIGHT: &str = "known_block_height";
+const ARG_KNOWN_PROPOSER: &str = "known_proposer";
+const ARG_KNOWN_PARENT_STATE_HASH: &str = "known_parent_state_hash";
+const DEPLOY_HASH: [u8; 32] = [7u8; 32];
+const BLOCK_HEIGHT: u64 = 42;
+const PROPOSER: AccountHash = AccountHash::new([3u8; 32]);
+
+fn run_get_block_info_contract(proposer: Option<AccountHash>) {
+    let mut builder = InMemoryWasmTestBuilder::default();
+    builder.run_genesis(&DEFAULT_RUN_GENESIS_REQUEST);
+
+    let parent_state_hash: [u8; BLAKE2B_DIGEST_LENGTH] = builder
+        .get_post_state_hash()
+        .as_slice()
+        .try_into()
+        .expect("should be a valid hash");
+
+    let exec_request = {
+        let deploy = DeployItemBuilder::new()
+            .with_address(DEFAULT_ACCOUNT_ADDR)
+            .with_deploy_hash(DEPLOY_HASH)
+            .with_session_code(
+                CONTRACT_GET_BLOCK_INFO,
+                runtime_args! {
+                    ARG_KNOWN_DEPLOY_HASH => DEPLOY_HASH,
+                    ARG_KNOWN_BLOCK_HEIGHT => BLOCK_HEIGHT,
+                    ARG_KNOWN_PROPOSER => proposer,
+                    ARG_KNOWN_PARENT_STATE_HASH => parent_state_hash,
+                },
+            )
+            .with_empty_payment_bytes(runtime_args! { ARG_AMOUNT => *DEFAULT_PAYMENT })
+            .with_authorization_keys(&[DEFAULT_ACCOUNT_ADDR])
+            .build();
+
+        let exec_request_builder = ExecuteRequestBuilder::new()
+            .push_deploy(deploy)
+            .with_block_height(BLOCK_HEIGHT);
+        match proposer {
+            Some(proposer) => exec_request_builder.with_proposer(proposer),
+            None => exec_request_builder,
+        }
+        .build()
+    };
+
+    builder.exec(exec_request).commit().expect_success();
+}
+
+#[ignore]
+#[test]
+fn should_run_get_block_info_contract() {
+    run_get_block_info_contract(Some(PROPOSER));
+}
+
+#[ignore]
+#[test]
+fn should_run_get_block_info_contract_without_proposer() {
+    run_get_block_info_contract(None);
+}
@@ -1,6 +1,9 @@
 mod account;
 mod create_purse;
+mod crypto;
+mod emit_event;
 mod get_arg;
+mod get_block_info;
 mod get_blocktime;
 mod get_caller;
 mod get_phase;
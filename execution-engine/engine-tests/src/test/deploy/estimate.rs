@@ -0,0 +1,203 @@
+use assert_matches::assert_matches;
+
+use engine_core::engine_state::{
+    deploy_item::DeployItem, estimate::EstimateResult, EngineConfig, Error, CONV_RATE,
+};
+use engine_shared::{gas::Gas, motes::Motes, transform::Transform};
+use engine_storage::global_state::in_memory::InMemoryGlobalState;
+use engine_test_support::{
+    internal::{
+        DeployItemBuilder, ExecuteRequestBuilder, InMemoryWasmTestBuilder, DEFAULT_PAYMENT,
+        DEFAULT_RUN_GENESIS_REQUEST,
+    },
+    DEFAULT_ACCOUNT_ADDR,
+};
+use types::{account::AccountHash, runtime_args, ApiError, Key, RuntimeArgs, U512};
+
+const CONTRACT_DO_NOTHING: &str = "do_nothing.wasm";
+const CONTRACT_ENDLESS_LOOP: &str = "endless_loop.wasm";
+const CONTRACT_REVERT: &str = "revert.wasm";
+const CONTRACT_TRANSFER_TO_ACCOUNT: &str = "transfer_to_account_u512.wasm";
+const ACCOUNT_1_ADDR: AccountHash = AccountHash::new([1u8; 32]);
+const ARG_TARGET: &str = "target";
+const ARG_AMOUNT: &str = "amount";
+
+fn deploy(sender: AccountHash, session_file: &str, session_args: RuntimeArgs) -> DeployItem {
+    DeployItemBuilder::new()
+        .with_address(sender)
+        .with_session_code(session_file, session_args)
+        .with_empty_payment_bytes(runtime_args! { ARG_AMOUNT => *DEFAULT_PAYMENT })
+        .with_authorization_keys(&[sender])
+        .with_deploy_hash([42; 32])
+        .build()
+}
+
+#[ignore]
+#[test]
+fn should_estimate_session_cost_without_committing() {
+    let mut builder = InMemoryWasmTestBuilder::default();
+    builder.run_genesis(&DEFAULT_RUN_GENESIS_REQUEST);
+    let pre_state_hash = builder.get_post_state_hash();
+
+    let transfer_amount = U512::from(1000);
+    let estimate_result = builder.estimate(deploy(
+        DEFAULT_ACCOUNT_ADDR,
+        CONTRACT_TRANSFER_TO_ACCOUNT,
+        runtime_args! { ARG_TARGET => ACCOUNT_1_ADDR, ARG_AMOUNT => transfer_amount },
+    ));
+
+    let (session_cost, payment, effect) = match estimate_result {
+        EstimateResult::Success {
+            session_cost,
+            payment,
+            effect,
+            revert: None,
+        } => (session_cost, payment, effect),
+        other => panic!("unexpected estimate result: {:?}", other),
+    };
+
+    assert!(session_cost.value() > U512::zero());
+    assert_eq!(Some(payment), Motes::from_gas(session_cost, CONV_RATE));
+    // The new account would be written
+    assert_matches!(
+        effect.transforms.get(&Key::Account(ACCOUNT_1_ADDR)),
+        Some(Transform::Write(_))
+    );
+
+    // Nothing was committed
+    assert_eq!(builder.get_post_state_hash(), pre_state_hash);
+    assert!(builder.get_account(ACCOUNT_1_ADDR).is_none());
+}
+
+#[ignore]
+#[test]
+fn should_estimate_with_balance_below_payment() {
+    let mut builder = InMemoryWasmTestBuilder::default();
+    builder.run_genesis(&DEFAULT_RUN_GENESIS_REQUEST);
+
+    // Fund account 1 with far less than any payment would take
+    let exec_request = ExecuteRequestBuilder::standard(
+        DEFAULT_ACCOUNT_ADDR,
+        CONTRACT_TRANSFER_TO_ACCOUNT,
+        runtime_args! { ARG_TARGET => ACCOUNT_1_ADDR, ARG_AMOUNT => U512::one() },
+    )
+    .build();
+    builder.exec(exec_request).expect_success().commit();
+
+    let estimate_result = builder.estimate(deploy(
+        ACCOUNT_1_ADDR,
+        CONTRACT_DO_NOTHING,
+        RuntimeArgs::default(),
+    ));
+
+    assert_matches!(
+        estimate_result,
+        EstimateResult::Success { revert: None, .. }
+    );
+}
+
+#[ignore]
+#[test]
+fn should_estimate_reverting_session() {
+    let mut builder = InMemoryWasmTestBuilder::default();
+    builder.run_genesis(&DEFAULT_RUN_GENESIS_REQUEST);
+
+    let estimate_result = builder.estimate(deploy(
+        DEFAULT_ACCOUNT_ADDR,
+        CONTRACT_REVERT,
+        RuntimeArgs::default(),
+    ));
+
+    match estimate_result {
+        EstimateResult::Success {
+            session_cost,
+            revert,
+            ..
+        } => {
+            assert!(session_cost.value() > U512::zero());
+            assert_eq!(revert, Some(ApiError::User(100)));
+        }
+        other => panic!("unexpected estimate result: {:?}", other),
+    }
+}
+
+#[ignore]
+#[test]
+fn should_not_estimate_for_unknown_account() {
+    let mut builder = InMemoryWasmTestBuilder::default();
+    builder.run_genesis(&DEFAULT_RUN_GENESIS_REQUEST);
+
+    let estimate_result = builder.estimate(deploy(
+        ACCOUNT_1_ADDR,
+        CONTRACT_DO_NOTHING,
+        RuntimeArgs::default(),
+    ));
+
+    assert_matches!(
+        estimate_result,
+        EstimateResult::Failure(Error::Authorization)
+    );
+}
+
+#[ignore]
+#[test]
+fn should_estimate_transfer() {
+    let mut builder = InMemoryWasmTestBuilder::default();
+    builder.run_genesis(&DEFAULT_RUN_GENESIS_REQUEST);
+
+    let deploy_item = DeployItemBuilder::new()
+        .with_address(DEFAULT_ACCOUNT_ADDR)
+        .with_empty_payment_bytes(runtime_args! { ARG_AMOUNT => *DEFAULT_PAYMENT })
+        .with_transfer_args(runtime_args! {
+            ARG_TARGET => ACCOUNT_1_ADDR,
+            ARG_AMOUNT => U512::from(1000),
+        })
+        .with_authorization_keys(&[DEFAULT_ACCOUNT_ADDR])
+        .with_deploy_hash([42; 32])
+        .build();
+
+    let effect = match builder.estimate(deploy_item) {
+        EstimateResult::Success {
+            effect,
+            revert: None,
+            ..
+        } => effect,
+        other => panic!("unexpected estimate result: {:?}", other),
+    };
+
+    // The new account would be written
+    assert_matches!(
+        effect.transforms.get(&Key::Account(ACCOUNT_1_ADDR)),
+        Some(Transform::Write(_))
+    );
+    assert!(builder.get_account(ACCOUNT_1_ADDR).is_none());
+}
+
+#[ignore]
+#[test]
+fn should_fail_estimate_exceeding_gas_limit() {
+    const ESTIMATE_GAS_LIMIT: u64 = 1_000_000;
+
+    let global_state = InMemoryGlobalState::empty().expect("should create global state");
+    let empty_root_hash = global_state.empty_root_hash;
+    let engine_config = EngineConfig::new()
+        .with_use_system_contracts(cfg!(feature = "use-system-contracts"))
+        .with_enable_bonding(cfg!(feature = "enable-bonding"))
+        .with_estimate_gas_limit(ESTIMATE_GAS_LIMIT);
+    let mut builder =
+        InMemoryWasmTestBuilder::new(global_state, engine_config, empty_root_hash.to_vec());
+    builder.run_genesis(&DEFAULT_RUN_GENESIS_REQUEST);
+
+    let estimate_result = builder.estimate(deploy(
+        DEFAULT_ACCOUNT_ADDR,
+        CONTRACT_ENDLESS_LOOP,
+        RuntimeArgs::default(),
+    ));
+
+    match estimate_result {
+        EstimateResult::GasLimitExceeded(gas_limit) => {
+            assert_eq!(gas_limit, Gas::new(U512::from(ESTIMATE_GAS_LIMIT)))
+        }
+        other => panic!("unexpected estimate result: {:?}", other),
+    }
+}
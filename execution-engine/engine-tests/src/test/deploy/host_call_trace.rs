@@ -0,0 +1,160 @@
+use assert_matches::assert_matches;
+
+use engine_core::{
+    resolvers::v1_function_index::FunctionIndex,
+    runtime::trace::{ExecutionTrace, HostCall, HostCallArg},
+};
+use engine_test_support::{
+    internal::{ExecuteRequestBuilder, InMemoryWasmTestBuilder, DEFAULT_RUN_GENESIS_REQUEST},
+    DEFAULT_ACCOUNT_ADDR,
+};
+use types::{bytesrepr::ToBytes, runtime_args, ApiError, ContractHash, Key, RuntimeArgs};
+
+const CONTRACT_REVERT: &str = "revert.wasm";
+const CONTRACT_PURSE_HOLDER_STORED: &str = "purse_holder_stored.wasm";
+const CONTRACT_PURSE_HOLDER_STORED_CALLER: &str = "purse_holder_stored_caller.wasm";
+const PURSE_HOLDER_STORED_CONTRACT_NAME: &str = "purse_holder_stored";
+const ARG_ENTRY_POINT: &str = "entry_point";
+const ARG_PURSE_HOLDER: &str = "purse_holder";
+const METHOD_VERSION: &str = "version";
+
+fn find_host_call(trace: &ExecutionTrace, function_index: FunctionIndex) -> &HostCall {
+    trace
+        .iter()
+        .find(|host_call| host_call.function_index == function_index)
+        .unwrap_or_else(|| panic!("should have called {:?}", function_index))
+}
+
+#[ignore]
+#[test]
+fn should_not_change_execution_result_when_tracing() {
+    let mut builder = InMemoryWasmTestBuilder::default();
+    builder.run_genesis(&DEFAULT_RUN_GENESIS_REQUEST);
+
+    let exec_request = || {
+        ExecuteRequestBuilder::standard(
+            DEFAULT_ACCOUNT_ADDR,
+            CONTRACT_PURSE_HOLDER_STORED,
+            RuntimeArgs::new(),
+        )
+        .build()
+    };
+
+    let mut traced_builder = builder.clone();
+    let traces = traced_builder.exec_traced(exec_request());
+    traced_builder.expect_success().commit();
+    assert_eq!(traces.len(), 1);
+    assert!(!traces[0].is_empty());
+
+    builder.exec(exec_request()).expect_success().commit();
+
+    let result = &builder.get_exec_responses().last().unwrap()[0];
+    let traced_result = &traced_builder.get_exec_responses().last().unwrap()[0];
+    assert_eq!(result.cost(), traced_result.cost());
+    assert_eq!(
+        builder.get_post_state_hash(),
+        traced_builder.get_post_state_hash()
+    );
+}
+
+#[ignore]
+#[test]
+fn should_trace_reverting_session() {
+    let mut builder = InMemoryWasmTestBuilder::default();
+    builder.run_genesis(&DEFAULT_RUN_GENESIS_REQUEST);
+
+    let exec_request =
+        ExecuteRequestBuilder::standard(DEFAULT_ACCOUNT_ADDR, CONTRACT_REVERT, RuntimeArgs::new())
+            .build();
+    let traces = builder.exec_traced(exec_request);
+    assert!(builder.is_error());
+
+    assert_eq!(traces.len(), 1);
+    let trace = &traces[0];
+
+    let revert = find_host_call(trace, FunctionIndex::RevertFuncIndex);
+    assert_eq!(
+        revert.args,
+        vec![HostCallArg::I32(u32::from(ApiError::User(100)) as i32)]
+    );
+    assert_eq!(revert.base_key, Key::Account(DEFAULT_ACCOUNT_ADDR));
+    assert_eq!(revert.depth, 0);
+
+    for host_call in trace {
+        assert!(host_call.gas_before <= host_call.gas_after);
+    }
+}
+
+#[ignore]
+#[test]
+fn should_trace_nested_contract_calls() {
+    let mut builder = InMemoryWasmTestBuilder::default();
+    builder.run_genesis(&DEFAULT_RUN_GENESIS_REQUEST);
+
+    let exec_request = ExecuteRequestBuilder::standard(
+        DEFAULT_ACCOUNT_ADDR,
+        CONTRACT_PURSE_HOLDER_STORED,
+        RuntimeArgs::new(),
+    )
+    .build();
+    builder.exec(exec_request).expect_success().commit();
+
+    let contract_hash: ContractHash = builder
+        .get_account(DEFAULT_ACCOUNT_ADDR)
+        .expect("should have account")
+        .named_keys()
+        .get(PURSE_HOLDER_STORED_CONTRACT_NAME)
+        .expect("should have contract hash")
+        .into_hash()
+        .expect("should be hash");
+
+    let exec_request = ExecuteRequestBuilder::standard(
+        DEFAULT_ACCOUNT_ADDR,
+        CONTRACT_PURSE_HOLDER_STORED_CALLER,
+        runtime_args! {
+            ARG_ENTRY_POINT => METHOD_VERSION.to_string(),
+            ARG_PURSE_HOLDER => contract_hash,
+        },
+    )
+    .build();
+    let traces = builder.exec_traced(exec_request);
+    builder.expect_success().commit();
+
+    let trace = &traces[0];
+
+    let call_contract = find_host_call(trace, FunctionIndex::CallContractFuncIndex);
+    assert_eq!(call_contract.base_key, Key::Account(DEFAULT_ACCOUNT_ADDR));
+    assert_eq!(call_contract.depth, 0);
+    // The contract hash, entry point and runtime args are read from memory, while the pointer to
+    // the result size is passed as is
+    assert_eq!(call_contract.args.len(), 4);
+    assert_matches!(
+        &call_contract.args[0],
+        HostCallArg::Buffer { bytes, .. } if *bytes == contract_hash.to_bytes().unwrap()
+    );
+    assert_matches!(
+        &call_contract.args[1],
+        HostCallArg::Buffer { bytes, .. } if *bytes == METHOD_VERSION.to_bytes().unwrap()
+    );
+    assert_matches!(
+        &call_contract.args[2],
+        HostCallArg::Buffer { bytes, .. } if *bytes == RuntimeArgs::new().to_bytes().unwrap()
+    );
+    assert_matches!(call_contract.args[3], HostCallArg::I32(_));
+
+    // `ret` is called by the stored contract, from within `call_contract`
+    let ret = find_host_call(trace, FunctionIndex::RetFuncIndex);
+    assert_eq!(ret.base_key, Key::Hash(contract_hash));
+    assert_eq!(ret.depth, 1);
+    assert!(call_contract.gas_before <= ret.gas_before);
+    assert!(ret.gas_after <= call_contract.gas_after);
+
+    // The session stores the version it got back once the call returned
+    let put_key_after_call = trace
+        .iter()
+        .skip_while(|host_call| host_call.function_index != FunctionIndex::CallContractFuncIndex)
+        .find(|host_call| host_call.function_index == FunctionIndex::PutKeyFuncIndex)
+        .expect("should put key after call");
+    assert_eq!(put_key_after_call.depth, 0);
+    assert!(call_contract.gas_after <= put_key_after_call.gas_before);
+}
@@ -1,3 +1,9 @@
+mod estimate;
+mod host_call_trace;
+mod host_function_costs;
 mod non_standard_payment;
+mod parallel_execution;
 mod preconditions;
+mod sequential_execution;
+mod storage_costs;
 mod stored_contracts;
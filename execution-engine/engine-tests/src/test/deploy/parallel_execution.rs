@@ -0,0 +1,405 @@
+use std::convert::TryInto;
+
+use engine_core::engine_state::{
+    deploy_item::DeployItem,
+    execute_request::{ExecuteRequest, SequentialExecutionResult},
+    execution_result::ExecutionResult,
+};
+use engine_shared::newtypes::CorrelationId;
+use engine_test_support::{
+    internal::{
+        DeployItemBuilder, ExecuteRequestBuilder, InMemoryWasmTestBuilder, DEFAULT_PAYMENT,
+        DEFAULT_RUN_GENESIS_REQUEST,
+    },
+    DEFAULT_ACCOUNT_ADDR,
+};
+use types::{account::AccountHash, runtime_args, RuntimeArgs, U512};
+
+const CONTRACT_ADD_U512_STORED: &str = "add_u512_stored.wasm";
+const CONTRACT_CREATE_ACCOUNTS: &str = "create_accounts.wasm";
+const CONTRACT_CREATE_PURSE_01: &str = "create_purse_01.wasm";
+const CONTRACT_DO_NOTHING: &str = "do_nothing.wasm";
+const CONTRACT_EE_441_RNG_STATE: &str = "ee_441_rng_state.wasm";
+const CONTRACT_ENDLESS_LOOP: &str = "endless_loop.wasm";
+const CONTRACT_NAMED_KEYS: &str = "named_keys.wasm";
+const CONTRACT_TRANSFER_TO_ACCOUNT: &str = "transfer_to_account_u512.wasm";
+const CONTRACT_TRANSFER_TO_EXISTING_ACCOUNT: &str = "transfer_to_existing_account.wasm";
+
+const ARG_ACCOUNTS: &str = "accounts";
+const ARG_AMOUNT: &str = "amount";
+const ARG_COMMAND: &str = "command";
+const ARG_FLAG: &str = "flag";
+const ARG_PURSE_NAME: &str = "purse_name";
+const ARG_SEED_AMOUNT: &str = "seed_amount";
+const ARG_TARGET: &str = "target";
+
+const ADD_U512_ENTRY_POINT: &str = "add";
+const ADD_U512_HASH_KEY_NAME: &str = "add_u512_hash";
+
+const SEED_AMOUNT: u64 = 1_000_000_000;
+
+fn account(i: u8) -> AccountHash {
+    AccountHash::new([i; 32])
+}
+
+/// Returns a distinct deploy hash for each `i`.
+fn deploy_hash(i: usize) -> [u8; 32] {
+    let mut result = [200; 32];
+    result[32 - 8..].copy_from_slice(&(i as u64).to_be_bytes());
+    result
+}
+
+fn deploy(
+    sender: AccountHash,
+    session_file: &str,
+    session_args: RuntimeArgs,
+    deploy_hash: [u8; 32],
+) -> DeployItem {
+    DeployItemBuilder::new()
+        .with_address(sender)
+        .with_session_code(session_file, session_args)
+        .with_empty_payment_bytes(runtime_args! { ARG_AMOUNT => *DEFAULT_PAYMENT })
+        .with_authorization_keys(&[sender])
+        .with_deploy_hash(deploy_hash)
+        .build()
+}
+
+/// Returns a builder past genesis, in which each of `accounts` has been funded.
+fn setup(accounts: Vec<AccountHash>) -> InMemoryWasmTestBuilder {
+    let mut builder = InMemoryWasmTestBuilder::default();
+    builder.run_genesis(&DEFAULT_RUN_GENESIS_REQUEST);
+
+    if !accounts.is_empty() {
+        let exec_request = ExecuteRequestBuilder::standard(
+            DEFAULT_ACCOUNT_ADDR,
+            CONTRACT_CREATE_ACCOUNTS,
+            runtime_args! {
+                ARG_ACCOUNTS => accounts,
+                ARG_SEED_AMOUNT => U512::from(SEED_AMOUNT),
+            },
+        )
+        .build();
+        builder.exec(exec_request).expect_success().commit();
+    }
+
+    builder
+}
+
+fn assert_same_result(index: usize, sequential: &ExecutionResult, parallel: &ExecutionResult) {
+    assert_eq!(
+        sequential.as_error().map(ToString::to_string),
+        parallel.as_error().map(ToString::to_string),
+        "deploy {} should fail in the same way",
+        index
+    );
+    assert_eq!(
+        sequential.cost(),
+        parallel.cost(),
+        "deploy {} should cost the same",
+        index
+    );
+    assert_eq!(
+        sequential.effect(),
+        parallel.effect(),
+        "deploy {} should have the same effect",
+        index
+    );
+}
+
+fn exec_request(deploys: &[DeployItem]) -> ExecuteRequest {
+    deploys
+        .iter()
+        .cloned()
+        .fold(ExecuteRequestBuilder::new(), |builder, deploy| {
+            builder.push_deploy(deploy)
+        })
+        .build()
+}
+
+/// Executes `deploys` on top of `builder` both sequentially and in parallel, and asserts that
+/// both give the same results and post-state.
+fn assert_parallel_matches_sequential(builder: InMemoryWasmTestBuilder, deploys: Vec<DeployItem>) {
+    let exec_request = || exec_request(&deploys);
+
+    let mut sequential_builder = builder.clone();
+    sequential_builder.exec_sequential(exec_request());
+
+    let mut parallel_builder = builder;
+    parallel_builder.exec_parallel(exec_request());
+
+    let sequential_results = sequential_builder
+        .get_exec_responses()
+        .last()
+        .expect("should have sequential exec response");
+    let parallel_results = parallel_builder
+        .get_exec_responses()
+        .last()
+        .expect("should have parallel exec response");
+
+    assert_eq!(sequential_results.len(), deploys.len());
+    assert_eq!(parallel_results.len(), deploys.len());
+    for (index, (sequential, parallel)) in sequential_results
+        .iter()
+        .zip(parallel_results.iter())
+        .enumerate()
+    {
+        assert_same_result(index, sequential, parallel);
+    }
+
+    assert_eq!(
+        sequential_builder.get_post_state_hash(),
+        parallel_builder.get_post_state_hash()
+    );
+}
+
+#[ignore]
+#[test]
+fn should_execute_independent_deploys_in_parallel() {
+    let accounts: Vec<AccountHash> = (1..=8).map(account).collect();
+    let builder = setup(accounts.clone());
+
+    let deploys = accounts
+        .iter()
+        .enumerate()
+        .map(|(i, sender)| {
+            deploy(
+                *sender,
+                CONTRACT_CREATE_PURSE_01,
+                runtime_args! { ARG_PURSE_NAME => format!("purse-{}", i) },
+                deploy_hash(i),
+            )
+        })
+        .collect();
+
+    assert_parallel_matches_sequential(builder, deploys);
+}
+
+#[ignore]
+#[test]
+fn should_reuse_speculative_results_of_independent_deploys() {
+    let accounts: Vec<AccountHash> = (1..=8).map(account).collect();
+    let builder = setup(accounts.clone());
+
+    // Every deploy pays into the same rewards purse, which must not make the deploys after the
+    // first one execute again.
+    let deploys: Vec<DeployItem> = accounts
+        .iter()
+        .enumerate()
+        .map(|(i, sender)| {
+            deploy(
+                *sender,
+                CONTRACT_CREATE_PURSE_01,
+                runtime_args! { ARG_PURSE_NAME => format!("purse-{}", i) },
+                deploy_hash(i),
+            )
+        })
+        .collect();
+
+    let mut exec_request = exec_request(&deploys);
+    exec_request.parent_state_hash = builder
+        .get_post_state_hash()
+        .as_slice()
+        .try_into()
+        .expect("should have a valid post-state hash");
+    let result = builder
+        .get_engine_state()
+        .run_execute_parallel(CorrelationId::new(), exec_request)
+        .expect("should execute deploys in parallel");
+
+    match result {
+        SequentialExecutionResult::Success {
+            execution_results,
+            speculative_results_used,
+            ..
+        } => {
+            assert!(execution_results.iter().all(ExecutionResult::is_success));
+            assert_eq!(speculative_results_used, deploys.len());
+        }
+        result => panic!("expected success but received {}", result),
+    }
+}
+
+#[ignore]
+#[test]
+fn should_execute_transfers_from_one_account_in_parallel() {
+    let accounts: Vec<AccountHash> = (1..=6).map(account).collect();
+    let builder = setup(accounts.clone());
+
+    // Every transfer spends from the same main purse, so each one reads a balance changed by the
+    // one before it.
+    let deploys = accounts
+        .iter()
+        .enumerate()
+        .map(|(i, target)| {
+            deploy(
+                DEFAULT_ACCOUNT_ADDR,
+                CONTRACT_TRANSFER_TO_EXISTING_ACCOUNT,
+                runtime_args! { ARG_TARGET => *target, ARG_AMOUNT => U512::from(i + 1) },
+                deploy_hash(i),
+            )
+        })
+        .collect();
+
+    assert_parallel_matches_sequential(builder, deploys);
+}
+
+#[ignore]
+#[test]
+fn should_execute_dependent_deploys_in_parallel() {
+    let builder = setup(vec![]);
+    let new_account_1 = account(101);
+    let new_account_2 = account(102);
+
+    // Each deploy spends funds received in the deploy before it, from accounts which don't exist
+    // in the parent state.
+    let deploys = vec![
+        deploy(
+            DEFAULT_ACCOUNT_ADDR,
+            CONTRACT_TRANSFER_TO_ACCOUNT,
+            runtime_args! {
+                ARG_TARGET => new_account_1,
+                ARG_AMOUNT => U512::from(SEED_AMOUNT),
+            },
+            deploy_hash(0),
+        ),
+        deploy(
+            new_account_1,
+            CONTRACT_TRANSFER_TO_ACCOUNT,
+            runtime_args! {
+                ARG_TARGET => new_account_2,
+                ARG_AMOUNT => U512::from(SEED_AMOUNT / 2),
+            },
+            deploy_hash(1),
+        ),
+        deploy(
+            new_account_2,
+            CONTRACT_DO_NOTHING,
+            RuntimeArgs::default(),
+            deploy_hash(2),
+        ),
+    ];
+
+    assert_parallel_matches_sequential(builder, deploys);
+}
+
+#[ignore]
+#[test]
+fn should_execute_named_key_commands_in_parallel() {
+    let builder = setup(vec![]);
+
+    // The reads only succeed when executed after the writes before them.
+    let commands = [
+        "create-uref1",
+        "test-read-uref1",
+        "create-uref2",
+        "test-read-uref2",
+        "increase-uref2",
+        "overwrite-uref2",
+        "remove-uref1",
+        "test-read-uref1",
+        "remove-uref2",
+    ];
+    let deploys = commands
+        .iter()
+        .enumerate()
+        .map(|(i, command)| {
+            deploy(
+                DEFAULT_ACCOUNT_ADDR,
+                CONTRACT_NAMED_KEYS,
+                runtime_args! { ARG_COMMAND => command.to_string() },
+                deploy_hash(i),
+            )
+        })
+        .collect();
+
+    assert_parallel_matches_sequential(builder, deploys);
+}
+
+#[ignore]
+#[test]
+fn should_execute_failing_and_contract_deploys_in_parallel() {
+    let accounts: Vec<AccountHash> = (1..=4).map(account).collect();
+    let builder = setup(accounts.clone());
+
+    let deploys = vec![
+        deploy(
+            accounts[0],
+            CONTRACT_EE_441_RNG_STATE,
+            runtime_args! { ARG_FLAG => "pass1".to_string() },
+            deploy_hash(0),
+        ),
+        deploy(
+            accounts[1],
+            CONTRACT_ENDLESS_LOOP,
+            RuntimeArgs::default(),
+            deploy_hash(1),
+        ),
+        deploy(
+            accounts[0],
+            CONTRACT_EE_441_RNG_STATE,
+            runtime_args! { ARG_FLAG => "pass2".to_string() },
+            deploy_hash(2),
+        ),
+        deploy(
+            accounts[2],
+            CONTRACT_TRANSFER_TO_EXISTING_ACCOUNT,
+            runtime_args! { ARG_TARGET => accounts[3], ARG_AMOUNT => U512::from(SEED_AMOUNT) },
+            deploy_hash(3),
+        ),
+        deploy(
+            accounts[3],
+            CONTRACT_TRANSFER_TO_EXISTING_ACCOUNT,
+            runtime_args! { ARG_TARGET => accounts[2], ARG_AMOUNT => U512::from(SEED_AMOUNT) },
+            deploy_hash(4),
+        ),
+    ];
+
+    assert_parallel_matches_sequential(builder, deploys);
+}
+
+#[ignore]
+#[test]
+fn should_charge_adds_to_one_value_the_same_in_parallel() {
+    let accounts: Vec<AccountHash> = (1..=2).map(account).collect();
+    let mut builder = setup(accounts.clone());
+
+    let exec_request = ExecuteRequestBuilder::standard(
+        DEFAULT_ACCOUNT_ADDR,
+        CONTRACT_ADD_U512_STORED,
+        RuntimeArgs::default(),
+    )
+    .build();
+    builder.exec(exec_request).expect_success().commit();
+
+    let contract_hash = builder
+        .get_account(DEFAULT_ACCOUNT_ADDR)
+        .expect("should have account")
+        .named_keys()
+        .get(ADD_U512_HASH_KEY_NAME)
+        .expect("should have contract hash")
+        .into_hash()
+        .expect("should be hash");
+
+    // The second add takes the total past 255, which serializes to one byte more, but only when
+    // it's executed after the first add.
+    let deploys = [255u64, 1]
+        .iter()
+        .zip(accounts.iter())
+        .enumerate()
+        .map(|(i, (amount, sender))| {
+            DeployItemBuilder::new()
+                .with_address(*sender)
+                .with_stored_session_hash(
+                    contract_hash,
+                    ADD_U512_ENTRY_POINT,
+                    runtime_args! { ARG_AMOUNT => U512::from(*amount) },
+                )
+                .with_empty_payment_bytes(runtime_args! { ARG_AMOUNT => *DEFAULT_PAYMENT })
+                .with_authorization_keys(&[*sender])
+                .with_deploy_hash(deploy_hash(i))
+                .build()
+        })
+        .collect();
+
+    assert_parallel_matches_sequential(builder, deploys);
+}
@@ -0,0 +1,134 @@
+use assert_matches::assert_matches;
+use lazy_static::lazy_static;
+
+use engine_core::engine_state::{deploy_item::DeployItem, Error};
+use engine_test_support::{
+    internal::{
+        DeployItemBuilder, ExecuteRequestBuilder, InMemoryWasmTestBuilder, DEFAULT_PAYMENT,
+        DEFAULT_RUN_GENESIS_REQUEST,
+    },
+    DEFAULT_ACCOUNT_ADDR,
+};
+use types::{account::AccountHash, runtime_args, RuntimeArgs, U512};
+
+const CONTRACT_TRANSFER_TO_ACCOUNT: &str = "transfer_to_account_u512.wasm";
+const ACCOUNT_1_ADDR: AccountHash = AccountHash::new([1u8; 32]);
+const ACCOUNT_2_ADDR: AccountHash = AccountHash::new([2u8; 32]);
+const ARG_TARGET: &str = "target";
+const ARG_AMOUNT: &str = "amount";
+
+lazy_static! {
+    static ref TRANSFER_1_AMOUNT: U512 = U512::from(250_000_000) + 1000;
+    static ref TRANSFER_2_AMOUNT: U512 = U512::from(750);
+}
+
+fn transfer_deploy(
+    sender: AccountHash,
+    target: AccountHash,
+    amount: U512,
+    deploy_hash: [u8; 32],
+) -> DeployItem {
+    DeployItemBuilder::new()
+        .with_address(sender)
+        .with_session_code(
+            CONTRACT_TRANSFER_TO_ACCOUNT,
+            runtime_args! { ARG_TARGET => target, ARG_AMOUNT => amount },
+        )
+        .with_empty_payment_bytes(runtime_args! { ARG_AMOUNT => *DEFAULT_PAYMENT })
+        .with_authorization_keys(&[sender])
+        .with_deploy_hash(deploy_hash)
+        .build()
+}
+
+/// The first deploy creates account 1, which the second deploy transfers from.
+fn dependent_deploys() -> Vec<DeployItem> {
+    vec![
+        transfer_deploy(
+            DEFAULT_ACCOUNT_ADDR,
+            ACCOUNT_1_ADDR,
+            *TRANSFER_1_AMOUNT,
+            [1u8; 32],
+        ),
+        transfer_deploy(
+            ACCOUNT_1_ADDR,
+            ACCOUNT_2_ADDR,
+            *TRANSFER_2_AMOUNT,
+            [2u8; 32],
+        ),
+    ]
+}
+
+#[ignore]
+#[test]
+fn should_execute_deploys_sequentially() {
+    let mut builder = InMemoryWasmTestBuilder::default();
+    builder.run_genesis(&DEFAULT_RUN_GENESIS_REQUEST);
+
+    let exec_request = dependent_deploys()
+        .into_iter()
+        .fold(ExecuteRequestBuilder::new(), |builder, deploy| {
+            builder.push_deploy(deploy)
+        })
+        .build();
+
+    builder.exec_sequential(exec_request);
+
+    let exec_response = builder
+        .get_exec_response(0)
+        .expect("should have exec response");
+    assert_eq!(exec_response.len(), 2);
+    for exec_result in exec_response {
+        assert!(
+            exec_result.as_error().is_none(),
+            "deploy should succeed: {:?}",
+            exec_result
+        );
+    }
+
+    let account_2 = builder
+        .get_account(ACCOUNT_2_ADDR)
+        .expect("should get account 2");
+    assert_eq!(
+        builder.get_purse_balance(account_2.main_purse()),
+        *TRANSFER_2_AMOUNT
+    );
+
+    // Executing and committing the deploys one at a time arrives at the same state.
+    let mut one_at_a_time_builder = InMemoryWasmTestBuilder::default();
+    one_at_a_time_builder.run_genesis(&DEFAULT_RUN_GENESIS_REQUEST);
+    for deploy in dependent_deploys() {
+        let exec_request = ExecuteRequestBuilder::from_deploy_item(deploy).build();
+        one_at_a_time_builder
+            .exec(exec_request)
+            .expect_success()
+            .commit();
+    }
+
+    assert_eq!(
+        builder.get_post_state_hash(),
+        one_at_a_time_builder.get_post_state_hash()
+    );
+}
+
+#[ignore]
+#[test]
+fn should_not_see_earlier_deploys_when_executing_independently() {
+    let mut builder = InMemoryWasmTestBuilder::default();
+    builder.run_genesis(&DEFAULT_RUN_GENESIS_REQUEST);
+
+    let exec_request = dependent_deploys()
+        .into_iter()
+        .fold(ExecuteRequestBuilder::new(), |builder, deploy| {
+            builder.push_deploy(deploy)
+        })
+        .build();
+
+    builder.exec(exec_request);
+
+    let exec_response = builder
+        .get_exec_response(0)
+        .expect("should have exec response");
+    assert!(exec_response[0].as_error().is_none());
+    // Account 1 doesn't exist in the parent state
+    assert_matches!(exec_response[1].as_error(), Some(Error::Authorization));
+}
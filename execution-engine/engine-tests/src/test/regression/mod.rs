@@ -18,3 +18,4 @@ mod ee_601;
 mod ee_771;
 mod ee_803;
 mod ee_890;
+mod random_bytes_rng_state;
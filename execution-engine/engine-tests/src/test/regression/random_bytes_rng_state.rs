@@ -0,0 +1,97 @@
+use std::convert::TryFrom;
+
+use engine_test_support::{
+    internal::{
+        DeployItemBuilder, ExecuteRequestBuilder, InMemoryWasmTestBuilder, ARG_AMOUNT,
+        DEFAULT_PAYMENT, DEFAULT_RUN_GENESIS_REQUEST,
+    },
+    DEFAULT_ACCOUNT_ADDR,
+};
+use types::{runtime_args, CLValue, Key, RuntimeArgs, URef};
+
+const RANDOM_BYTES_RNG_STATE: &str = "random_bytes_rng_state.wasm";
+const RANDOM_BYTES_LENGTH: usize = 32;
+
+fn get_uref(key: Key) -> URef {
+    match key {
+        Key::URef(uref) => uref,
+        _ => panic!("Key {:?} is not an URef", key),
+    }
+}
+
+fn exec_pass(pass: &str) -> InMemoryWasmTestBuilder {
+    let exec_request = {
+        let deploy = DeployItemBuilder::new()
+            .with_address(DEFAULT_ACCOUNT_ADDR)
+            .with_empty_payment_bytes(runtime_args! { ARG_AMOUNT => *DEFAULT_PAYMENT, })
+            .with_session_code(
+                RANDOM_BYTES_RNG_STATE,
+                runtime_args! {
+                    "flag" => pass,
+                },
+            )
+            .with_deploy_hash([1u8; 32])
+            .with_authorization_keys(&[DEFAULT_ACCOUNT_ADDR])
+            .build();
+        ExecuteRequestBuilder::from_deploy_item(deploy).build()
+    };
+
+    let mut builder = InMemoryWasmTestBuilder::default();
+    builder
+        .run_genesis(&DEFAULT_RUN_GENESIS_REQUEST)
+        .exec(exec_request)
+        .expect_success()
+        .commit();
+    builder
+}
+
+fn get_named_uref(builder: &InMemoryWasmTestBuilder, name: &str) -> URef {
+    let account = builder
+        .get_account(DEFAULT_ACCOUNT_ADDR)
+        .expect("should have account");
+    get_uref(account.named_keys()[name])
+}
+
+fn get_random_bytes(builder: &InMemoryWasmTestBuilder, name: &str) -> Vec<u8> {
+    let key = Key::URef(get_named_uref(builder, name)).normalize();
+    CLValue::try_from(builder.query(None, key, &[]).expect("should have value"))
+        .expect("should be a CLValue")
+        .into_t()
+        .expect("should be Vec<u8>")
+}
+
+#[ignore]
+#[test]
+fn should_not_advance_address_rng_when_drawing_random_bytes() {
+    // the baseline pass, no random bytes
+    let pass1 = exec_pass("pass1");
+    // second pass draws random bytes directly and in a subcall between creating the urefs
+    let pass2 = exec_pass("pass2");
+
+    assert_eq!(
+        get_named_uref(&pass1, "uref1").addr(),
+        get_named_uref(&pass2, "uref1").addr()
+    );
+    assert_eq!(
+        get_named_uref(&pass1, "uref2").addr(),
+        get_named_uref(&pass2, "uref2").addr()
+    );
+}
+
+#[ignore]
+#[test]
+fn should_share_random_bytes_stream_with_subcontracts() {
+    let builder = exec_pass("pass2");
+    let random1 = get_random_bytes(&builder, "random1");
+    let random2 = get_random_bytes(&builder, "random2");
+
+    assert_eq!(random1.len(), RANDOM_BYTES_LENGTH);
+    assert_eq!(random2.len(), RANDOM_BYTES_LENGTH);
+    // the subcall continues the session's stream rather than starting it again
+    assert_ne!(random1, random2);
+
+    // the same deploy yields the same random bytes
+    let other_builder = exec_pass("pass2");
+    assert_eq!(random1, get_random_bytes(&other_builder, "random1"));
+    assert_eq!(random2, get_random_bytes(&other_builder, "random2"));
+}
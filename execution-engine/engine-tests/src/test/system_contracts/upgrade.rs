@@ -1,14 +1,18 @@
 use engine_core::engine_state::{upgrade::ActivationPoint, Error};
 use engine_grpc_server::engine_server::ipc::DeployCode;
+use engine_shared::storage_costs::StorageCosts;
 #[cfg(feature = "use-system-contracts")]
 use engine_shared::{stored_value::StoredValue, transform::Transform};
 use engine_test_support::internal::{
-    utils, InMemoryWasmTestBuilder, UpgradeRequestBuilder, DEFAULT_RUN_GENESIS_REQUEST,
-    DEFAULT_WASM_COSTS,
+    utils, InMemoryWasmTestBuilder, UpgradeRequestBuilder, DEFAULT_HOST_FUNCTION_COSTS,
+    DEFAULT_RUN_GENESIS_REQUEST, DEFAULT_WASM_COSTS,
 };
 #[cfg(feature = "use-system-contracts")]
 use engine_test_support::{internal::ExecuteRequestBuilder, DEFAULT_ACCOUNT_ADDR};
-use engine_wasm_prep::wasm_costs::WasmCosts;
+use engine_wasm_prep::{
+    host_function_costs::{HostFunctionCost, HostFunctionCosts},
+    wasm_costs::WasmCosts,
+};
 use types::ProtocolVersion;
 #[cfg(feature = "use-system-contracts")]
 use types::{runtime_args, CLValue, Key, RuntimeArgs, U512};
@@ -38,6 +42,16 @@ fn get_upgraded_wasm_costs() -> WasmCosts {
     }
 }
 
+fn get_upgraded_host_function_costs() -> HostFunctionCosts {
+    HostFunctionCosts {
+        write: HostFunctionCost::new(10_000, 100),
+        create_purse: HostFunctionCost::new(100_000, 0),
+        call_contract: HostFunctionCost::new(50_000, 10),
+        transfer_to_account: HostFunctionCost::new(100_000, 0),
+        ..Default::default()
+    }
+}
+
 #[ignore]
 #[test]
 fn should_upgrade_only_protocol_version() {
@@ -353,6 +367,107 @@ fn should_allow_only_wasm_costs_patch_version() {
     );
 }
 
+#[ignore]
+#[test]
+fn should_allow_only_host_function_costs_patch_version() {
+    let mut builder = InMemoryWasmTestBuilder::default();
+
+    builder.run_genesis(&DEFAULT_RUN_GENESIS_REQUEST);
+
+    let sem_ver = PROTOCOL_VERSION.value();
+    let new_protocol_version =
+        ProtocolVersion::from_parts(sem_ver.major, sem_ver.minor, sem_ver.patch + 2);
+
+    let new_host_function_costs = get_upgraded_host_function_costs();
+
+    let mut upgrade_request = {
+        UpgradeRequestBuilder::new()
+            .with_current_protocol_version(PROTOCOL_VERSION)
+            .with_new_protocol_version(new_protocol_version)
+            .with_activation_point(DEFAULT_ACTIVATION_POINT)
+            .with_new_host_function_costs(new_host_function_costs)
+            .build()
+    };
+
+    builder.upgrade_with_upgrade_request(&mut upgrade_request);
+
+    let upgrade_response = builder
+        .get_upgrade_response(0)
+        .expect("should have response");
+
+    assert!(upgrade_response.has_success(), "expected success");
+
+    let upgraded_protocol_data = builder
+        .get_engine_state()
+        .get_protocol_data(new_protocol_version)
+        .expect("should have result")
+        .expect("should have upgraded protocol data");
+
+    assert_eq!(
+        new_host_function_costs,
+        *upgraded_protocol_data.host_function_costs(),
+        "upgraded host function costs should equal new host function costs"
+    );
+    assert_eq!(
+        *DEFAULT_WASM_COSTS,
+        *upgraded_protocol_data.wasm_costs(),
+        "wasm costs should be unchanged"
+    );
+}
+
+#[ignore]
+#[test]
+fn should_allow_only_storage_costs_patch_version() {
+    let mut builder = InMemoryWasmTestBuilder::default();
+
+    builder.run_genesis(&DEFAULT_RUN_GENESIS_REQUEST);
+
+    let sem_ver = PROTOCOL_VERSION.value();
+    let new_protocol_version =
+        ProtocolVersion::from_parts(sem_ver.major, sem_ver.minor, sem_ver.patch + 2);
+
+    let new_storage_costs = StorageCosts::new(100);
+
+    let mut upgrade_request = {
+        UpgradeRequestBuilder::new()
+            .with_current_protocol_version(PROTOCOL_VERSION)
+            .with_new_protocol_version(new_protocol_version)
+            .with_activation_point(DEFAULT_ACTIVATION_POINT)
+            .with_new_storage_costs(new_storage_costs)
+            .build()
+    };
+
+    builder.upgrade_with_upgrade_request(&mut upgrade_request);
+
+    let upgrade_response = builder
+        .get_upgrade_response(0)
+        .expect("should have response");
+
+    assert!(upgrade_response.has_success(), "expected success");
+
+    let upgraded_protocol_data = builder
+        .get_engine_state()
+        .get_protocol_data(new_protocol_version)
+        .expect("should have result")
+        .expect("should have upgraded protocol data");
+
+    assert_eq!(
+        new_storage_costs,
+        *upgraded_protocol_data.storage_costs(),
+        "upgraded storage costs should equal new storage costs"
+    );
+    assert_eq!(
+        *DEFAULT_WASM_COSTS,
+        *upgraded_protocol_data.wasm_costs(),
+        "wasm costs should be unchanged"
+    );
+    assert_eq!(
+        *DEFAULT_HOST_FUNCTION_COSTS,
+        *upgraded_protocol_data.host_function_costs(),
+        "host function costs should be unchanged"
+    );
+}
+
 #[ignore]
 #[test]
 fn should_allow_only_wasm_costs_minor_version() {
@@ -0,0 +1,440 @@
+use types::bytesrepr::{self, FromBytes, ToBytes, U32_SERIALIZED_LENGTH};
+
+pub const HOST_FUNCTION_COST_SERIALIZED_LENGTH: usize = 2 * U32_SERIALIZED_LENGTH;
+const NUM_HOST_FUNCTIONS: usize = 50;
+pub const HOST_FUNCTION_COSTS_SERIALIZED_LENGTH: usize = U32_SERIALIZED_LENGTH
+    + NUM_HOST_FUNCTIONS * (U32_SERIALIZED_LENGTH + HOST_FUNCTION_COST_SERIALIZED_LENGTH);
+
+/// Cost of the host functions which do not otherwise have a default cost of their own.
+const DEFAULT_COST: HostFunctionCost = HostFunctionCost::new(200, 1);
+/// Cost of the host functions which write to global state, on top of the storage cost of the
+/// written values.
+const STORAGE_COST: HostFunctionCost = HostFunctionCost::new(10_000, 10);
+/// Cost of the host functions which create purses or transfer between them.
+const PURSE_COST: HostFunctionCost = HostFunctionCost::new(100_000, 0);
+/// Cost of the host functions which create or add a version to a contract package.
+const CONTRACT_VERSION_COST: HostFunctionCost = HostFunctionCost::new(100_000, 10);
+/// Cost of the host functions which call another contract.
+const CALL_CONTRACT_COST: HostFunctionCost = HostFunctionCost::new(10_000, 10);
+/// Cost of hashing, which grows with the size of the hashed data.
+const HASH_COST: HostFunctionCost = HostFunctionCost::new(1_000, 10);
+/// Cost of verifying a signature.
+const VERIFY_SIGNATURE_COST: HostFunctionCost = HostFunctionCost::new(50_000, 10);
+
+/// The gas charged for a single call to a host function.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct HostFunctionCost {
+    /// Flat cost of every call
+    pub base: u32,
+    /// Cost per byte of the arguments passed in Wasm memory
+    pub per_byte: u32,
+}
+
+impl HostFunctionCost {
+    pub const fn new(base: u32, per_byte: u32) -> Self {
+        HostFunctionCost { base, per_byte }
+    }
+
+    /// Returns the cost of a call passing arguments of the given sizes in bytes.
+    pub fn calculate(&self, sizes: &[u32]) -> u64 {
+        let total_size = sizes
+            .iter()
+            .fold(0u64, |total, size| total.saturating_add(u64::from(*size)));
+        u64::from(self.per_byte)
+            .saturating_mul(total_size)
+            .saturating_add(u64::from(self.base))
+    }
+}
+
+impl ToBytes for HostFunctionCost {
+    fn to_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
+        let mut ret = bytesrepr::unchecked_allocate_buffer(self);
+        ret.append(&mut self.base.to_bytes()?);
+        ret.append(&mut self.per_byte.to_bytes()?);
+        Ok(ret)
+    }
+
+    fn serialized_length(&self) -> usize {
+        HOST_FUNCTION_COST_SERIALIZED_LENGTH
+    }
+}
+
+impl FromBytes for HostFunctionCost {
+    fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), bytesrepr::Error> {
+        let (base, rem): (u32, &[u8]) = FromBytes::from_bytes(bytes)?;
+        let (per_byte, rem): (u32, &[u8]) = FromBytes::from_bytes(rem)?;
+        Ok((HostFunctionCost { base, per_byte }, rem))
+    }
+}
+
+/// The cost of each host function, in addition to the cost of the Wasm opcodes executed by the
+/// contract calling it.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct HostFunctionCosts {
+    pub read_value: HostFunctionCost,
+    pub read_value_local: HostFunctionCost,
+    pub load_named_keys: HostFunctionCost,
+    pub write: HostFunctionCost,
+    pub write_local: HostFunctionCost,
+    pub add: HostFunctionCost,
+    pub new_uref: HostFunctionCost,
+    pub ret: HostFunctionCost,
+    pub get_key: HostFunctionCost,
+    pub has_key: HostFunctionCost,
+    pub put_key: HostFunctionCost,
+    pub remove_key: HostFunctionCost,
+    pub is_valid_uref: HostFunctionCost,
+    pub revert: HostFunctionCost,
+    pub add_associated_key: HostFunctionCost,
+    pub remove_associated_key: HostFunctionCost,
+    pub update_associated_key: HostFunctionCost,
+    pub set_action_threshold: HostFunctionCost,
+    pub get_caller: HostFunctionCost,
+    pub get_blocktime: HostFunctionCost,
+    pub create_purse: HostFunctionCost,
+    pub transfer_to_account: HostFunctionCost,
+    pub transfer_from_purse_to_account: HostFunctionCost,
+    pub transfer_from_purse_to_purse: HostFunctionCost,
+    pub get_balance: HostFunctionCost,
+    pub get_phase: HostFunctionCost,
+    pub get_system_contract: HostFunctionCost,
+    pub get_main_purse: HostFunctionCost,
+    pub read_host_buffer: HostFunctionCost,
+    pub create_contract_package_at_hash: HostFunctionCost,
+    pub create_contract_user_group: HostFunctionCost,
+    pub add_contract_version: HostFunctionCost,
+    pub disable_contract_version: HostFunctionCost,
+    pub call_contract: HostFunctionCost,
+    pub call_versioned_contract: HostFunctionCost,
+    pub get_named_arg_size: HostFunctionCost,
+    pub get_named_arg: HostFunctionCost,
+    pub remove_contract_user_group: HostFunctionCost,
+    pub provision_contract_user_group_uref: HostFunctionCost,
+    pub remove_contract_user_group_urefs: HostFunctionCost,
+    pub prune: HostFunctionCost,
+    pub emit_event: HostFunctionCost,
+    pub blake2b: HostFunctionCost,
+    pub ed25519_verify: HostFunctionCost,
+    pub secp256k1_verify: HostFunctionCost,
+    pub random_bytes: HostFunctionCost,
+    pub get_deploy_hash: HostFunctionCost,
+    pub get_block_height: HostFunctionCost,
+    pub get_proposer: HostFunctionCost,
+    pub get_parent_state_hash: HostFunctionCost,
+}
+
+impl HostFunctionCosts {
+    /// Returns a table in which every host function is free.
+    pub fn free() -> Self {
+        HostFunctionCosts::from_array([HostFunctionCost::default(); NUM_HOST_FUNCTIONS])
+    }
+
+    /// Returns the costs in the order of their index in the serialized table.
+    ///
+    /// New host functions are only ever appended, so that the index of a cost never changes.
+    fn to_array(&self) -> [HostFunctionCost; NUM_HOST_FUNCTIONS] {
+        [
+            self.read_value,
+            self.read_value_local,
+            self.load_named_keys,
+            self.write,
+            self.write_local,
+            self.add,
+            self.new_uref,
+            self.ret,
+            self.get_key,
+            self.has_key,
+            self.put_key,
+            self.remove_key,
+            self.is_valid_uref,
+            self.revert,
+            self.add_associated_key,
+            self.remove_associated_key,
+            self.update_associated_key,
+            self.set_action_threshold,
+            self.get_caller,
+            self.get_blocktime,
+            self.create_purse,
+            self.transfer_to_account,
+            self.transfer_from_purse_to_account,
+            self.transfer_from_purse_to_purse,
+            self.get_balance,
+            self.get_phase,
+            self.get_system_contract,
+            self.get_main_purse,
+            self.read_host_buffer,
+            self.create_contract_package_at_hash,
+            self.create_contract_user_group,
+            self.add_contract_version,
+            self.disable_contract_version,
+            self.call_contract,
+            self.call_versioned_contract,
+            self.get_named_arg_size,
+            self.get_named_arg,
+            self.remove_contract_user_group,
+            self.provision_contract_user_group_uref,
+            self.remove_contract_user_group_urefs,
+            self.prune,
+            self.emit_event,
+            self.blake2b,
+            self.ed25519_verify,
+            self.secp256k1_verify,
+            self.random_bytes,
+            self.get_deploy_hash,
+            self.get_block_height,
+            self.get_proposer,
+            self.get_parent_state_hash,
+        ]
+    }
+
+    fn from_array(costs: [HostFunctionCost; NUM_HOST_FUNCTIONS]) -> Self {
+        HostFunctionCosts {
+            read_value: costs[0],
+            read_value_local: costs[1],
+            load_named_keys: costs[2],
+            write: costs[3],
+            write_local: costs[4],
+            add: costs[5],
+            new_uref: costs[6],
+            ret: costs[7],
+            get_key: costs[8],
+            has_key: costs[9],
+            put_key: costs[10],
+            remove_key: costs[11],
+            is_valid_uref: costs[12],
+            revert: costs[13],
+            add_associated_key: costs[14],
+            remove_associated_key: costs[15],
+            update_associated_key: costs[16],
+            set_action_threshold: costs[17],
+            get_caller: costs[18],
+            get_blocktime: costs[19],
+            create_purse: costs[20],
+            transfer_to_account: costs[21],
+            transfer_from_purse_to_account: costs[22],
+            transfer_from_purse_to_purse: costs[23],
+            get_balance: costs[24],
+            get_phase: costs[25],
+            get_system_contract: costs[26],
+            get_main_purse: costs[27],
+            read_host_buffer: costs[28],
+            create_contract_package_at_hash: costs[29],
+            create_contract_user_group: costs[30],
+            add_contract_version: costs[31],
+            disable_contract_version: costs[32],
+            call_contract: costs[33],
+            call_versioned_contract: costs[34],
+            get_named_arg_size: costs[35],
+            get_named_arg: costs[36],
+            remove_contract_user_group: costs[37],
+            provision_contract_user_group_uref: costs[38],
+            remove_contract_user_group_urefs: costs[39],
+            prune: costs[40],
+            emit_event: costs[41],
+            blake2b: costs[42],
+            ed25519_verify: costs[43],
+            secp256k1_verify: costs[44],
+            random_bytes: costs[45],
+            get_deploy_hash: costs[46],
+            get_block_height: costs[47],
+            get_proposer: costs[48],
+            get_parent_state_hash: costs[49],
+        }
+    }
+}
+
+impl Default for HostFunctionCosts {
+    fn default() -> Self {
+        HostFunctionCosts {
+            read_value: DEFAULT_COST,
+            read_value_local: DEFAULT_COST,
+            load_named_keys: DEFAULT_COST,
+            write: STORAGE_COST,
+            write_local: STORAGE_COST,
+            add: STORAGE_COST,
+            new_uref: STORAGE_COST,
+            ret: DEFAULT_COST,
+            get_key: DEFAULT_COST,
+            has_key: DEFAULT_COST,
+            put_key: STORAGE_COST,
+            remove_key: DEFAULT_COST,
+            is_valid_uref: DEFAULT_COST,
+            revert: DEFAULT_COST,
+            add_associated_key: DEFAULT_COST,
+            remove_associated_key: DEFAULT_COST,
+            update_associated_key: DEFAULT_COST,
+            set_action_threshold: DEFAULT_COST,
+            get_caller: DEFAULT_COST,
+            get_blocktime: DEFAULT_COST,
+            create_purse: PURSE_COST,
+            transfer_to_account: PURSE_COST,
+            transfer_from_purse_to_account: PURSE_COST,
+            transfer_from_purse_to_purse: PURSE_COST,
+            get_balance: DEFAULT_COST,
+            get_phase: DEFAULT_COST,
+            get_system_contract: DEFAULT_COST,
+            get_main_purse: DEFAULT_COST,
+            read_host_buffer: DEFAULT_COST,
+            create_contract_package_at_hash: CONTRACT_VERSION_COST,
+            create_contract_user_group: DEFAULT_COST,
+            add_contract_version: CONTRACT_VERSION_COST,
+            disable_contract_version: DEFAULT_COST,
+            call_contract: CALL_CONTRACT_COST,
+            call_versioned_contract: CALL_CONTRACT_COST,
+            get_named_arg_size: DEFAULT_COST,
+            get_named_arg: DEFAULT_COST,
+            remove_contract_user_group: DEFAULT_COST,
+            provision_contract_user_group_uref: DEFAULT_COST,
+            remove_contract_user_group_urefs: DEFAULT_COST,
+            prune: DEFAULT_COST,
+            emit_event: STORAGE_COST,
+            blake2b: HASH_COST,
+            ed25519_verify: VERIFY_SIGNATURE_COST,
+            secp256k1_verify: VERIFY_SIGNATURE_COST,
+            random_bytes: DEFAULT_COST,
+            get_deploy_hash: DEFAULT_COST,
+            get_block_height: DEFAULT_COST,
+            get_proposer: DEFAULT_COST,
+            get_parent_state_hash: DEFAULT_COST,
+        }
+    }
+}
+
+/// Serialized as the number of costs followed by each cost prefixed by its index, so that a table
+/// serialized before a host function was added still deserializes.  The functions it lacks cost
+/// nothing, as they did when it was serialized.  Costs of unknown host functions are ignored.
+impl ToBytes for HostFunctionCosts {
+    fn to_bytes(&self) -> Result<Vec<u8>, bytesrepr::Error> {
+        let mut ret = bytesrepr::unchecked_allocate_buffer(self);
+        ret.append(&mut (NUM_HOST_FUNCTIONS as u32).to_bytes()?);
+        for (index, cost) in self.to_array().iter().enumerate() {
+            ret.append(&mut (index as u32).to_bytes()?);
+            ret.append(&mut cost.to_bytes()?);
+        }
+        Ok(ret)
+    }
+
+    fn serialized_length(&self) -> usize {
+        HOST_FUNCTION_COSTS_SERIALIZED_LENGTH
+    }
+}
+
+impl FromBytes for HostFunctionCosts {
+    fn from_bytes(bytes: &[u8]) -> Result<(Self, &[u8]), bytesrepr::Error> {
+        let (count, mut rem): (u32, &[u8]) = FromBytes::from_bytes(bytes)?;
+        let mut costs = [HostFunctionCost::default(); NUM_HOST_FUNCTIONS];
+        for _ in 0..count {
+            let (index, cost_rem): (u32, &[u8]) = FromBytes::from_bytes(rem)?;
+            let (cost, cost_rem): (HostFunctionCost, &[u8]) = FromBytes::from_bytes(cost_rem)?;
+            if let Some(entry) = costs.get_mut(index as usize) {
+                *entry = cost;
+            }
+            rem = cost_rem;
+        }
+        Ok((HostFunctionCosts::from_array(costs), rem))
+    }
+}
+
+pub mod gens {
+    use proptest::{collection, num, prop_compose, strategy::Strategy};
+
+    use super::{HostFunctionCost, HostFunctionCosts, NUM_HOST_FUNCTIONS};
+
+    prop_compose! {
+        pub fn host_function_cost_arb()(
+            base in num::u32::ANY,
+            per_byte in num::u32::ANY,
+        ) -> HostFunctionCost {
+            HostFunctionCost { base, per_byte }
+        }
+    }
+
+    pub fn host_function_costs_arb() -> impl Strategy<Value = HostFunctionCosts> {
+        collection::vec(host_function_cost_arb(), NUM_HOST_FUNCTIONS).prop_map(|costs| {
+            let mut array = [HostFunctionCost::default(); NUM_HOST_FUNCTIONS];
+            array.copy_from_slice(&costs);
+            HostFunctionCosts::from_array(array)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::proptest;
+
+    use types::bytesrepr::{self, ToBytes};
+
+    use super::{gens, HostFunctionCost, HostFunctionCosts, NUM_HOST_FUNCTIONS};
+
+    #[test]
+    fn should_calculate_cost_from_argument_sizes() {
+        let cost = HostFunctionCost::new(100, 2);
+        assert_eq!(cost.calculate(&[]), 100);
+        assert_eq!(cost.calculate(&[33, 7]), 180);
+    }
+
+    #[test]
+    fn should_saturate_cost() {
+        let cost = HostFunctionCost::new(u32::max_value(), u32::max_value());
+        assert_eq!(
+            cost.calculate(&[u32::max_value(), u32::max_value()]),
+            u64::max_value()
+        );
+    }
+
+    #[test]
+    fn should_serialize_and_deserialize() {
+        let free = HostFunctionCosts::free();
+        let mock = HostFunctionCosts {
+            write: HostFunctionCost::new(10_000, 100),
+            call_contract: HostFunctionCost::new(50_000, 10),
+            ..HostFunctionCosts::free()
+        };
+        bytesrepr::test_serialization_roundtrip(&free);
+        bytesrepr::test_serialization_roundtrip(&HostFunctionCosts::default());
+        bytesrepr::test_serialization_roundtrip(&mock);
+    }
+
+    #[test]
+    fn should_charge_for_every_host_function_by_default() {
+        let default = HostFunctionCosts::default();
+        assert!(default.to_array().iter().all(|cost| cost.base > 0));
+    }
+
+    #[test]
+    fn should_deserialize_missing_costs_as_free() {
+        let write = HostFunctionCost::new(10_000, 100);
+        // A table holding only the cost at index 3, `write`.
+        let mut bytes = 1u32.to_bytes().unwrap();
+        bytes.append(&mut 3u32.to_bytes().unwrap());
+        bytes.append(&mut write.to_bytes().unwrap());
+
+        let host_function_costs: HostFunctionCosts = bytesrepr::deserialize(bytes).unwrap();
+        assert_eq!(
+            host_function_costs,
+            HostFunctionCosts {
+                write,
+                ..HostFunctionCosts::free()
+            }
+        );
+    }
+
+    #[test]
+    fn should_ignore_costs_of_unknown_host_functions() {
+        let mut bytes = 1u32.to_bytes().unwrap();
+        bytes.append(&mut (NUM_HOST_FUNCTIONS as u32).to_bytes().unwrap());
+        bytes.append(&mut HostFunctionCost::new(1, 1).to_bytes().unwrap());
+
+        let host_function_costs: HostFunctionCosts = bytesrepr::deserialize(bytes).unwrap();
+        assert_eq!(host_function_costs, HostFunctionCosts::free());
+    }
+
+    proptest! {
+        #[test]
+        fn should_serialize_and_deserialize_with_arbitrary_values(
+            host_function_costs in gens::host_function_costs_arb()
+        ) {
+            bytesrepr::test_serialization_roundtrip(&host_function_costs);
+        }
+    }
+}
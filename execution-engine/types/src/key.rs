@@ -53,6 +53,21 @@ pub enum Key {
     URef(URef),
 }
 
+/// The variant of a [`Key`], which is the first byte of its serialized form.
+///
+/// Serialized keys which share a tag share a prefix, so the tag can be used to iterate over every
+/// key of one variant in global state.
+#[repr(u8)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy, PartialOrd, Ord, Hash)]
+pub enum KeyTag {
+    /// The tag of a [`Key::Account`].
+    Account = ACCOUNT_ID,
+    /// The tag of a [`Key::Hash`].
+    Hash = HASH_ID,
+    /// The tag of a [`Key::URef`].
+    URef = UREF_ID,
+}
+
 impl Key {
     // This method is not intended to be used by third party crates.
     #[doc(hidden)]
@@ -64,6 +79,15 @@ impl Key {
         }
     }
 
+    /// Returns the [`KeyTag`] of `self`.
+    pub fn tag(&self) -> KeyTag {
+        match self {
+            Key::Account(_) => KeyTag::Account,
+            Key::Hash(_) => KeyTag::Hash,
+            Key::URef(_) => KeyTag::URef,
+        }
+    }
+
     /// Returns the maximum size a [`Key`] can be serialized into.
     pub const fn max_serialized_length() -> usize {
         KEY_UREF_SERIALIZED_LENGTH
@@ -340,4 +364,16 @@ mod tests {
         let key_uref = Key::URef(URef::new([42; BLAKE2B_DIGEST_LENGTH], AccessRights::READ));
         assert!(key_uref.serialized_length() <= Key::max_serialized_length());
     }
+
+    #[test]
+    fn key_tag_is_first_serialized_byte() {
+        let keys = [
+            Key::Account(AccountHash::new([42; BLAKE2B_DIGEST_LENGTH])),
+            Key::Hash([42; KEY_HASH_LENGTH]),
+            Key::URef(URef::new([42; BLAKE2B_DIGEST_LENGTH], AccessRights::READ)),
+        ];
+        for key in keys.iter() {
+            assert_eq!(key.to_bytes().unwrap()[0], key.tag() as u8);
+        }
+    }
 }
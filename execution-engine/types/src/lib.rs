@@ -35,6 +35,7 @@ pub mod contracts;
 #[cfg(any(feature = "gens", test))]
 pub mod gens;
 mod key;
+pub mod merkle_proof;
 mod phase;
 mod protocol_version;
 pub mod runtime_args;
@@ -60,8 +61,8 @@ pub use contracts::{
 pub use contract_wasm::ContractWasm;
 #[doc(inline)]
 pub use key::{
-    ContractHash, ContractPackageHash, ContractWasmHash, HashAddr, Key, BLAKE2B_DIGEST_LENGTH,
-    KEY_HASH_LENGTH,
+    ContractHash, ContractPackageHash, ContractWasmHash, HashAddr, Key, KeyTag,
+    BLAKE2B_DIGEST_LENGTH, KEY_HASH_LENGTH,
 };
 pub use phase::{Phase, PHASE_SERIALIZED_LENGTH};
 pub use protocol_version::{ProtocolVersion, VersionCheckResult};
@@ -0,0 +1,266 @@
+//! Verification of Merkle proofs of reads from global state.
+//!
+//! A proof is the list of serialized trie nodes along the path of a key, starting at the state
+//! root.  It ends either with the leaf holding the key, or with the node at which the path of the
+//! key diverges from the trie, proving that the key is absent.
+//!
+//! Verification only relies on `core` and `alloc`, so that it can be performed by light clients
+//! which don't run the execution engine.
+
+use alloc::vec::Vec;
+
+use blake2::{
+    digest::{Input, VariableOutput},
+    VarBlake2b,
+};
+use failure::Fail;
+
+use crate::{
+    bytesrepr::{self, FromBytes},
+    BLAKE2B_DIGEST_LENGTH,
+};
+
+const RADIX: usize = 256;
+
+const LEAF_TAG: u8 = 0;
+const NODE_TAG: u8 = 1;
+const EXTENSION_TAG: u8 = 2;
+
+const LEAF_POINTER_TAG: u8 = 0;
+const NODE_POINTER_TAG: u8 = 1;
+
+const OPTION_NONE_TAG: u8 = 0;
+const OPTION_SOME_TAG: u8 = 1;
+
+/// Error while verifying a Merkle proof.
+#[derive(Fail, Debug, PartialEq, Eq, Clone)]
+pub enum Error {
+    /// The proof contains no nodes.
+    #[fail(display = "Proof error: no nodes")]
+    Empty,
+    /// The hash of the node at the given position in the proof doesn't match the hash expected by
+    /// its parent, or by the state root for the first node.
+    #[fail(display = "Proof error: hash mismatch at node {}", _0)]
+    HashMismatch(usize),
+    /// The proof ends before the path of the key reaches a leaf or diverges from the trie.
+    #[fail(display = "Proof error: incomplete path")]
+    Incomplete,
+    /// The proof contains nodes past the end of the path of the key.
+    #[fail(display = "Proof error: trailing nodes")]
+    TrailingNodes,
+    /// The key is too short to index the node at the given position in the proof.
+    #[fail(display = "Proof error: key too short for node {}", _0)]
+    KeyTooShort(usize),
+    /// A node of the proof could not be deserialized.
+    #[fail(display = "Proof error: {}", _0)]
+    Serialization(bytesrepr::Error),
+}
+
+impl From<bytesrepr::Error> for Error {
+    fn from(error: bytesrepr::Error) -> Self {
+        Error::Serialization(error)
+    }
+}
+
+/// Verifies that `proof` proves the result of reading the key serialized as `key_bytes` at
+/// `state_root`.
+///
+/// On success, returns the serialized value stored at the key, or `None` if the proof shows that
+/// the key is absent.
+pub fn verify<'a>(
+    state_root: &[u8; BLAKE2B_DIGEST_LENGTH],
+    key_bytes: &[u8],
+    proof: &'a [Vec<u8>],
+) -> Result<Option<&'a [u8]>, Error> {
+    if proof.is_empty() {
+        return Err(Error::Empty);
+    }
+
+    let mut expected_hash = *state_root;
+    let mut depth: usize = 0;
+
+    for (position, node) in proof.iter().enumerate() {
+        if hash(node) != expected_hash {
+            return Err(Error::HashMismatch(position));
+        }
+        let is_last = position == proof.len() - 1;
+
+        let (tag, rem) = u8::from_bytes(node)?;
+        let maybe_child = match tag {
+            LEAF_TAG => {
+                if !is_last {
+                    return Err(Error::TrailingNodes);
+                }
+                // Keys may not match in the case of a compressed path from a Node directly to a
+                // Leaf
+                return Ok(if rem.starts_with(key_bytes) {
+                    Some(&rem[key_bytes.len()..])
+                } else {
+                    None
+                });
+            }
+            NODE_TAG => {
+                let index = *key_bytes
+                    .get(depth)
+                    .ok_or_else(|| Error::KeyTooShort(position))?;
+                depth += 1;
+                child_hash_from_pointer_block(rem, index)?
+            }
+            EXTENSION_TAG => {
+                let (affix, rem) = Vec::<u8>::from_bytes(rem)?;
+                let (pointer_hash, rem) = pointer_hash_from_bytes(rem)?;
+                if !rem.is_empty() {
+                    return Err(bytesrepr::Error::LeftOverBytes.into());
+                }
+                let end = depth + affix.len();
+                if key_bytes.get(depth..end) == Some(affix.as_slice()) {
+                    depth = end;
+                    Some(pointer_hash)
+                } else {
+                    None
+                }
+            }
+            _ => return Err(bytesrepr::Error::Formatting.into()),
+        };
+
+        match maybe_child {
+            Some(child_hash) if !is_last => expected_hash = child_hash,
+            Some(_) => return Err(Error::Incomplete),
+            None if is_last => return Ok(None),
+            None => return Err(Error::TrailingNodes),
+        }
+    }
+
+    Err(Error::Incomplete)
+}
+
+fn hash(data: &[u8]) -> [u8; BLAKE2B_DIGEST_LENGTH] {
+    let mut ret = [0u8; BLAKE2B_DIGEST_LENGTH];
+    // Safe to unwrap here because our digest length is constant and valid
+    let mut hasher = VarBlake2b::new(BLAKE2B_DIGEST_LENGTH).unwrap();
+    hasher.input(data);
+    hasher.variable_result(|hash| ret.clone_from_slice(hash));
+    ret
+}
+
+/// Deserializes a pointer, returning the hash it points to.
+fn pointer_hash_from_bytes(
+    bytes: &[u8],
+) -> Result<([u8; BLAKE2B_DIGEST_LENGTH], &[u8]), bytesrepr::Error> {
+    let (tag, rem) = u8::from_bytes(bytes)?;
+    match tag {
+        LEAF_POINTER_TAG | NODE_POINTER_TAG => FromBytes::from_bytes(rem),
+        _ => Err(bytesrepr::Error::Formatting),
+    }
+}
+
+/// Deserializes a pointer block, returning the hash of the child at `index` if there is one.
+fn child_hash_from_pointer_block(
+    mut bytes: &[u8],
+    index: u8,
+) -> Result<Option<[u8; BLAKE2B_DIGEST_LENGTH]>, bytesrepr::Error> {
+    let mut ret = None;
+    for current_index in 0..RADIX {
+        let (tag, rem) = u8::from_bytes(bytes)?;
+        bytes = match tag {
+            OPTION_NONE_TAG => rem,
+            OPTION_SOME_TAG => {
+                let (child_hash, rem) = pointer_hash_from_bytes(rem)?;
+                if current_index == usize::from(index) {
+                    ret = Some(child_hash);
+                }
+                rem
+            }
+            _ => return Err(bytesrepr::Error::Formatting),
+        };
+    }
+    if !bytes.is_empty() {
+        return Err(bytesrepr::Error::LeftOverBytes);
+    }
+    Ok(ret)
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use super::*;
+
+    const KEY: [u8; 3] = [7, 8, 9];
+    const VALUE: [u8; 2] = [42, 43];
+
+    fn leaf(key: &[u8], value: &[u8]) -> Vec<u8> {
+        let mut ret = vec![LEAF_TAG];
+        ret.extend_from_slice(key);
+        ret.extend_from_slice(value);
+        ret
+    }
+
+    fn node(children: &[(u8, &[u8])]) -> Vec<u8> {
+        let mut ret = vec![NODE_TAG];
+        for index in 0..RADIX {
+            match children.iter().find(|(i, _)| usize::from(*i) == index) {
+                Some((_, child)) => {
+                    ret.push(OPTION_SOME_TAG);
+                    ret.push(NODE_POINTER_TAG);
+                    ret.extend_from_slice(&hash(child));
+                }
+                None => ret.push(OPTION_NONE_TAG),
+            }
+        }
+        ret
+    }
+
+    #[test]
+    fn should_verify_inclusion() {
+        let leaf = leaf(&KEY, &VALUE);
+        let root = node(&[(KEY[0], &leaf)]);
+        let proof = vec![root.clone(), leaf];
+        assert_eq!(verify(&hash(&root), &KEY, &proof), Ok(Some(&VALUE[..])));
+    }
+
+    #[test]
+    fn should_verify_non_inclusion() {
+        let other_leaf = leaf(&[7, 8, 0], &VALUE);
+        let root = node(&[(KEY[0], &other_leaf)]);
+
+        // The path of the key leads to a leaf holding another key
+        let proof = vec![root.clone(), other_leaf];
+        assert_eq!(verify(&hash(&root), &KEY, &proof), Ok(None));
+
+        // The path of the key ends at an empty slot of the root
+        let proof = vec![root.clone()];
+        assert_eq!(verify(&hash(&root), &[1, 2, 3], &proof), Ok(None));
+    }
+
+    #[test]
+    fn should_reject_invalid_proofs() {
+        let leaf = leaf(&KEY, &VALUE);
+        let root = node(&[(KEY[0], &leaf)]);
+        let root_hash = hash(&root);
+
+        assert_eq!(verify(&root_hash, &KEY, &[]), Err(Error::Empty));
+        assert_eq!(
+            verify(&root_hash, &KEY, &[root.clone()]),
+            Err(Error::Incomplete)
+        );
+        assert_eq!(
+            verify(&root_hash, &[1, 2, 3], &[root.clone(), leaf.clone()]),
+            Err(Error::TrailingNodes)
+        );
+        assert_eq!(
+            verify(&root_hash, &[], &[root.clone(), leaf.clone()]),
+            Err(Error::KeyTooShort(0))
+        );
+
+        let tampered_leaf = self::leaf(&KEY, &[0, 0]);
+        assert_eq!(
+            verify(&root_hash, &KEY, &[root.clone(), tampered_leaf]),
+            Err(Error::HashMismatch(1))
+        );
+        assert_eq!(
+            verify(&[0u8; BLAKE2B_DIGEST_LENGTH], &KEY, &[root, leaf]),
+            Err(Error::HashMismatch(0))
+        );
+    }
+}